@@ -0,0 +1,93 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+#![no_std]
+#![allow(async_fn_in_trait)]
+
+pub use embedded_can::{Error, ErrorKind, ExtendedId, Frame, Id, StandardId};
+
+/// An async CAN interface that is able to transmit and receive frames.
+///
+/// This is the async version of [`embedded_can::nb::Can`].
+///
+/// # Example
+///
+/// ```
+/// use embedded_can_async::{Can, Frame, StandardId};
+/// # use embedded_can_async::{ErrorKind, Id};
+/// # struct MockFrame { id: Id, data: [u8; 8], len: usize }
+/// # impl Frame for MockFrame {
+/// #     fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+/// #         let mut frame = MockFrame { id: id.into(), data: [0; 8], len: data.len() };
+/// #         frame.data.get_mut(..data.len())?.copy_from_slice(data);
+/// #         Some(frame)
+/// #     }
+/// #     fn new_remote(_: impl Into<Id>, _: usize) -> Option<Self> { None }
+/// #     fn is_extended(&self) -> bool { matches!(self.id, Id::Extended(_)) }
+/// #     fn is_remote_frame(&self) -> bool { false }
+/// #     fn id(&self) -> Id { self.id }
+/// #     fn dlc(&self) -> usize { self.len }
+/// #     fn data(&self) -> &[u8] { &self.data[..self.len] }
+/// # }
+/// # struct MockCan;
+/// # impl Can for MockCan {
+/// #     type Frame = MockFrame;
+/// #     type Error = ErrorKind;
+/// #     async fn transmit(&mut self, _: &MockFrame) -> Result<Option<MockFrame>, ErrorKind> { Ok(None) }
+/// #     async fn receive(&mut self) -> Result<MockFrame, ErrorKind> {
+/// #         Ok(MockFrame::new(StandardId::new(0x101).unwrap(), &[0x2A]).unwrap())
+/// #     }
+/// # }
+///
+/// /// Send a request, and wait for the response.
+/// async fn request<C: Can>(can: &mut C, request: &C::Frame) -> Result<C::Frame, C::Error> {
+///     // A displaced lower priority frame must be sent again.
+///     if let Some(displaced) = can.transmit(request).await? {
+///         can.transmit(&displaced).await?;
+///     }
+///     loop {
+///         let frame = can.receive().await?;
+///         if frame.id() == StandardId::new(0x101).unwrap().into() {
+///             return Ok(frame);
+///         }
+///     }
+/// }
+/// # let _ = request(&mut MockCan, &MockFrame::new(StandardId::new(0x100).unwrap(), &[]).unwrap());
+/// ```
+pub trait Can {
+    /// Associated frame type.
+    type Frame: Frame;
+
+    /// Associated error type.
+    type Error: Error;
+
+    /// Puts a frame in the transmit buffer to be sent on the bus, waiting until space is available.
+    ///
+    /// If the transmit buffer is full, this function will try to replace a pending
+    /// lower priority frame and return the frame that was replaced, without waiting.
+    ///
+    /// # Notes for implementers
+    ///
+    /// * Frames of equal identifier shall be transmitted in FIFO fashion when more
+    ///   than one transmit buffer is available.
+    /// * When replacing pending frames make sure the frame is not in the process of
+    ///   being send to the bus.
+    async fn transmit(&mut self, frame: &Self::Frame) -> Result<Option<Self::Frame>, Self::Error>;
+
+    /// Waits until a frame was received or an error occurred.
+    async fn receive(&mut self) -> Result<Self::Frame, Self::Error>;
+}
+
+impl<T: Can + ?Sized> Can for &mut T {
+    type Frame = T::Frame;
+    type Error = T::Error;
+
+    #[inline]
+    async fn transmit(&mut self, frame: &Self::Frame) -> Result<Option<Self::Frame>, Self::Error> {
+        T::transmit(self, frame).await
+    }
+
+    #[inline]
+    async fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+        T::receive(self).await
+    }
+}
@@ -10,8 +10,238 @@ pub trait Can {
 
     /// Puts a frame in the transmit buffer. Blocks until space is available in
     /// the transmit buffer.
+    ///
+    /// Unlike [`nb::Can::transmit`](crate::nb::Can::transmit), no pending frame is returned:
+    /// if a lower priority frame is displaced from the transmit buffer, it must still be
+    /// transmitted by the implementation.
     fn transmit(&mut self, frame: &Self::Frame) -> Result<(), Self::Error>;
 
     /// Blocks until a frame was received or an error occurred.
     fn receive(&mut self) -> Result<Self::Frame, Self::Error>;
 }
+
+/// Adapter implementing the blocking [`Can`] trait for a [`nb::Can`](crate::nb::Can) implementation.
+///
+/// Each operation is retried until it does not return [`nb::Error::WouldBlock`].
+///
+/// When transmitting a frame displaces a pending lower priority frame from the transmit buffer,
+/// the displaced frame is put back in the transmit buffer before returning, so no frame is lost.
+/// This can in turn displace another pending frame of lower priority, which is handled the same way.
+///
+/// # Example
+///
+/// ```
+/// use embedded_can::{blocking::{Blocking, Can as _}, nb, Frame};
+///
+/// fn send_frame<C: nb::Can>(can: C, frame: &C::Frame) -> Result<(), C::Error> {
+///     let mut can = Blocking::new(can);
+///     can.transmit(frame)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Blocking<C> {
+    inner: C,
+}
+
+impl<C> Blocking<C> {
+    /// Create a new [`Blocking`] adapter.
+    #[inline]
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+
+    /// Consume the adapter, returning the inner `nb` CAN interface.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Borrow the inner `nb` CAN interface.
+    #[inline]
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner `nb` CAN interface.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+}
+
+impl<C: crate::nb::Can> Can for Blocking<C> {
+    type Frame = C::Frame;
+    type Error = C::Error;
+
+    fn transmit(&mut self, frame: &Self::Frame) -> Result<(), Self::Error> {
+        let mut displaced = nb::block!(self.inner.transmit(frame))?;
+        while let Some(frame) = displaced {
+            displaced = nb::block!(self.inner.transmit(&frame))?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn receive(&mut self) -> Result<Self::Frame, Self::Error> {
+        nb::block!(self.inner.receive())
+    }
+}
+
+/// A blocking CAN FD interface that is able to transmit and receive both classic and CAN FD frames.
+///
+/// This is the CAN FD counterpart of [`Can`]: the frame type implements [`FdFrame`](crate::FdFrame),
+/// and whether a frame is transmitted as a classic or as an FD frame depends on how it was created.
+pub trait FdCan {
+    /// Associated frame type.
+    type Frame: crate::FdFrame;
+
+    /// Associated error type.
+    type Error: crate::Error;
+
+    /// Puts a frame in the transmit buffer. Blocks until space is available in
+    /// the transmit buffer.
+    fn transmit(&mut self, frame: &Self::Frame) -> Result<(), Self::Error>;
+
+    /// Blocks until a frame was received or an error occurred.
+    fn receive(&mut self) -> Result<Self::Frame, Self::Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::{ErrorKind, Id, StandardId};
+    use std::{collections::VecDeque, vec::Vec};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestFrame {
+        id: Id,
+        data: Vec<u8>,
+    }
+
+    impl crate::Frame for TestFrame {
+        fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+            Some(Self {
+                id: id.into(),
+                data: data.into(),
+            })
+        }
+
+        fn new_remote(_id: impl Into<Id>, _dlc: usize) -> Option<Self> {
+            None
+        }
+
+        fn is_extended(&self) -> bool {
+            matches!(self.id, Id::Extended(_))
+        }
+
+        fn is_remote_frame(&self) -> bool {
+            false
+        }
+
+        fn id(&self) -> Id {
+            self.id
+        }
+
+        fn dlc(&self) -> usize {
+            self.data.len()
+        }
+
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+    }
+
+    fn frame(id: u16) -> TestFrame {
+        crate::Frame::new(StandardId::new(id).unwrap(), &[]).unwrap()
+    }
+
+    /// A controller with a single transmit mailbox, which is busy for `busy` polls after each frame,
+    /// and displaces the pending frame if the new one has a higher priority.
+    #[derive(Default)]
+    struct FakeCan {
+        pending: Option<TestFrame>,
+        busy: usize,
+        polls: usize,
+        sent: Vec<TestFrame>,
+        rx: VecDeque<TestFrame>,
+    }
+
+    impl FakeCan {
+        fn poll(&mut self) {
+            self.polls += 1;
+            if self.polls > self.busy {
+                self.polls = 0;
+                self.sent.extend(self.pending.take());
+            }
+        }
+    }
+
+    impl crate::nb::Can for FakeCan {
+        type Frame = TestFrame;
+        type Error = ErrorKind;
+
+        fn transmit(&mut self, frame: &TestFrame) -> nb::Result<Option<TestFrame>, ErrorKind> {
+            match &self.pending {
+                None => {
+                    self.pending = Some(frame.clone());
+                    Ok(None)
+                }
+                Some(pending) if frame.id < pending.id => Ok(self.pending.replace(frame.clone())),
+                Some(_) => {
+                    self.poll();
+                    Err(nb::Error::WouldBlock)
+                }
+            }
+        }
+
+        fn receive(&mut self) -> nb::Result<TestFrame, ErrorKind> {
+            self.polls += 1;
+            if self.polls > self.busy {
+                self.polls = 0;
+                return self.rx.pop_front().ok_or(nb::Error::WouldBlock);
+            }
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    #[test]
+    fn transmit_blocks_until_queued() {
+        let mut can = Blocking::new(FakeCan {
+            busy: 3,
+            ..Default::default()
+        });
+        can.transmit(&frame(0x10)).unwrap();
+        can.transmit(&frame(0x20)).unwrap();
+
+        let can = can.into_inner();
+        assert_eq!(can.sent, [frame(0x10)]);
+        assert_eq!(can.pending, Some(frame(0x20)));
+    }
+
+    #[test]
+    fn displaced_frame_is_retransmitted() {
+        let mut can = Blocking::new(FakeCan {
+            busy: 2,
+            ..Default::default()
+        });
+        can.transmit(&frame(0x20)).unwrap();
+        can.transmit(&frame(0x10)).unwrap();
+
+        // The higher priority frame took the mailbox, the displaced one was queued again after it.
+        let can = can.into_inner();
+        assert_eq!(can.sent, [frame(0x10)]);
+        assert_eq!(can.pending, Some(frame(0x20)));
+    }
+
+    #[test]
+    fn receive_blocks_until_available() {
+        let mut can = Blocking::new(FakeCan {
+            busy: 2,
+            rx: [frame(0x01)].into(),
+            ..Default::default()
+        });
+        assert_eq!(can.receive(), Ok(frame(0x01)));
+    }
+}
@@ -0,0 +1,145 @@
+//! CAN FD frames.
+
+use crate::{Frame, Id};
+
+/// Maximum data length of a CAN FD frame, in bytes.
+pub const MAX_FD_DATA_LEN: usize = 64;
+
+/// Data lengths of CAN FD frames, indexed by data length code (DLC).
+const FD_LENGTHS: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+/// Returns the data length of a CAN FD frame with the given data length code (DLC).
+///
+/// DLC values 0 to 8 map to the same lengths as for classic CAN frames, while values
+/// 9 to 15 map to 12, 16, 20, 24, 32, 48 and 64 bytes.
+///
+/// Returns `None` if `dlc` is greater than 15.
+pub const fn fd_dlc_to_len(dlc: u8) -> Option<usize> {
+    if dlc < 16 {
+        Some(FD_LENGTHS[dlc as usize] as usize)
+    } else {
+        None
+    }
+}
+
+/// Returns the data length code (DLC) of a CAN FD frame carrying `len` bytes of data.
+///
+/// Returns `None` if `len` cannot be encoded in a DLC, such as 10 or 65.
+/// Such payloads must be padded to the next valid length, see [`fd_padded_len`].
+pub const fn fd_len_to_dlc(len: usize) -> Option<u8> {
+    let mut dlc = 0;
+    while dlc < FD_LENGTHS.len() {
+        if FD_LENGTHS[dlc] as usize == len {
+            return Some(dlc as u8);
+        }
+        dlc += 1;
+    }
+    None
+}
+
+/// Returns the smallest valid CAN FD data length which can hold `len` bytes of data.
+///
+/// Returns `None` if `len` is greater than [`MAX_FD_DATA_LEN`].
+pub const fn fd_padded_len(len: usize) -> Option<usize> {
+    let mut dlc = 0;
+    while dlc < FD_LENGTHS.len() {
+        if FD_LENGTHS[dlc] as usize >= len {
+            return Some(FD_LENGTHS[dlc] as usize);
+        }
+        dlc += 1;
+    }
+    None
+}
+
+/// A frame which can be either a classic CAN2.0 frame or a CAN FD frame.
+///
+/// Frames created with [`Frame::new`] and [`Frame::new_remote`] are classic frames.
+/// For CAN FD frames, [`Frame::dlc`] returns the data length code (0..=15, see [`fd_dlc_to_len`])
+/// and [`Frame::data`] returns up to [`MAX_FD_DATA_LEN`] bytes.
+pub trait FdFrame: Frame {
+    /// Creates a new CAN FD frame, transmitted at the nominal bit rate.
+    ///
+    /// This will return `None` if the data length cannot be encoded in a DLC
+    /// (see [`fd_len_to_dlc`]).
+    fn new_fd(id: impl Into<Id>, data: &[u8]) -> Option<Self>;
+
+    /// Creates a new CAN FD frame with the bit rate switch (BRS) bit set:
+    /// its data phase is transmitted at the data bit rate.
+    ///
+    /// This will return `None` if the data length cannot be encoded in a DLC
+    /// (see [`fd_len_to_dlc`]).
+    fn new_fd_brs(id: impl Into<Id>, data: &[u8]) -> Option<Self>;
+
+    /// Returns true if this frame is a CAN FD frame (FDF bit set).
+    fn is_fd_frame(&self) -> bool;
+
+    /// Returns true if this frame is a classic CAN2.0 frame.
+    fn is_classic_frame(&self) -> bool {
+        !self.is_fd_frame()
+    }
+
+    /// Returns true if the bit rate switch (BRS) bit is set.
+    ///
+    /// This is always false for classic frames.
+    fn is_bit_rate_switched(&self) -> bool;
+
+    /// Returns true if the error state indicator (ESI) bit is set,
+    /// meaning that the transmitting node is error passive.
+    ///
+    /// This is always false for classic frames.
+    fn is_error_passive(&self) -> bool;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dlc_to_len_classic_range() {
+        for dlc in 0..=8 {
+            assert_eq!(fd_dlc_to_len(dlc), Some(usize::from(dlc)));
+        }
+    }
+
+    #[test]
+    fn dlc_to_len_fd_range() {
+        assert_eq!(fd_dlc_to_len(9), Some(12));
+        assert_eq!(fd_dlc_to_len(10), Some(16));
+        assert_eq!(fd_dlc_to_len(11), Some(20));
+        assert_eq!(fd_dlc_to_len(12), Some(24));
+        assert_eq!(fd_dlc_to_len(13), Some(32));
+        assert_eq!(fd_dlc_to_len(14), Some(48));
+        assert_eq!(fd_dlc_to_len(15), Some(64));
+        assert_eq!(fd_dlc_to_len(16), None);
+    }
+
+    #[test]
+    fn len_to_dlc_round_trip() {
+        for dlc in 0..16 {
+            let len = fd_dlc_to_len(dlc).unwrap();
+            assert_eq!(fd_len_to_dlc(len), Some(dlc));
+        }
+    }
+
+    #[test]
+    fn len_to_dlc_invalid_lengths() {
+        for len in [9, 10, 11, 13, 17, 25, 33, 47, 49, 63, 65] {
+            assert_eq!(fd_len_to_dlc(len), None);
+        }
+    }
+
+    #[test]
+    fn padded_len() {
+        assert_eq!(fd_padded_len(0), Some(0));
+        assert_eq!(fd_padded_len(8), Some(8));
+        assert_eq!(fd_padded_len(9), Some(12));
+        assert_eq!(fd_padded_len(12), Some(12));
+        assert_eq!(fd_padded_len(13), Some(16));
+        assert_eq!(fd_padded_len(21), Some(24));
+        assert_eq!(fd_padded_len(25), Some(32));
+        assert_eq!(fd_padded_len(33), Some(48));
+        assert_eq!(fd_padded_len(49), Some(64));
+        assert_eq!(fd_padded_len(64), Some(64));
+        assert_eq!(fd_padded_len(65), None);
+    }
+}
@@ -0,0 +1,408 @@
+//! Acceptance filters.
+
+use crate::{ExtendedId, Frame, Id, StandardId};
+
+/// Identifiers accepted by a [`Filter`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IdFilter {
+    /// Accepts every identifier, standard or extended.
+    Any,
+    /// Accepts a single identifier.
+    Exact(Id),
+    /// Accepts the standard identifiers whose bits selected by `mask` are equal to the ones of `id`.
+    ///
+    /// A mask of zero accepts every standard identifier.
+    StandardMask {
+        /// Identifier to compare to.
+        id: StandardId,
+        /// Bits of the identifier to compare.
+        mask: u16,
+    },
+    /// Accepts the extended identifiers whose bits selected by `mask` are equal to the ones of `id`.
+    ///
+    /// A mask of zero accepts every extended identifier.
+    ExtendedMask {
+        /// Identifier to compare to.
+        id: ExtendedId,
+        /// Bits of the identifier to compare.
+        mask: u32,
+    },
+    /// Accepts the standard identifiers from `start` to `end`, inclusive.
+    StandardRange {
+        /// First accepted identifier.
+        start: StandardId,
+        /// Last accepted identifier.
+        end: StandardId,
+    },
+    /// Accepts the extended identifiers from `start` to `end`, inclusive.
+    ExtendedRange {
+        /// First accepted identifier.
+        start: ExtendedId,
+        /// Last accepted identifier.
+        end: ExtendedId,
+    },
+}
+
+impl IdFilter {
+    /// Returns true if `id` is accepted.
+    pub fn matches(&self, id: Id) -> bool {
+        match (*self, id) {
+            (Self::Any, _) => true,
+            (Self::Exact(expected), id) => expected == id,
+            (Self::StandardMask { id: expected, mask }, Id::Standard(id)) => {
+                (id.as_raw() ^ expected.as_raw()) & mask == 0
+            }
+            (Self::ExtendedMask { id: expected, mask }, Id::Extended(id)) => {
+                (id.as_raw() ^ expected.as_raw()) & mask == 0
+            }
+            (Self::StandardRange { start, end }, Id::Standard(id)) => (start..=end).contains(&id),
+            (Self::ExtendedRange { start, end }, Id::Extended(id)) => (start..=end).contains(&id),
+            _ => false,
+        }
+    }
+}
+
+/// Frame types accepted by a [`Filter`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameTypeFilter {
+    /// Accepts both data and remote frames.
+    Any,
+    /// Accepts data frames only.
+    Data,
+    /// Accepts remote frames (RTR bit set) only.
+    Remote,
+}
+
+/// An acceptance filter, matching the identifier and the type of frames.
+///
+/// Filters can be used to configure the hardware filters of a CAN controller
+/// with [`ConfigurableFilters`], or to filter frames in software.
+///
+/// # Example
+///
+/// ```
+/// use embedded_can::{Filter, IdFilter, StandardId};
+///
+/// // Accept data frames with identifiers 0x100 to 0x10F.
+/// let filter = Filter::new(IdFilter::StandardMask {
+///     id: StandardId::new(0x100).unwrap(),
+///     mask: 0x7F0,
+/// })
+/// .data_only();
+///
+/// assert!(filter.matches(StandardId::new(0x10A).unwrap().into()));
+/// assert!(!filter.matches(StandardId::new(0x110).unwrap().into()));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Filter {
+    /// Accepted identifiers.
+    pub id: IdFilter,
+    /// Accepted frame types.
+    pub frame_type: FrameTypeFilter,
+}
+
+impl Filter {
+    /// A filter accepting every frame.
+    pub const ACCEPT_ALL: Self = Self::new(IdFilter::Any);
+
+    /// Creates a new filter accepting both data and remote frames with the identifiers accepted by `id`.
+    pub const fn new(id: IdFilter) -> Self {
+        Self {
+            id,
+            frame_type: FrameTypeFilter::Any,
+        }
+    }
+
+    /// Only accept data frames.
+    pub const fn data_only(self) -> Self {
+        Self {
+            frame_type: FrameTypeFilter::Data,
+            ..self
+        }
+    }
+
+    /// Only accept remote frames.
+    pub const fn remote_only(self) -> Self {
+        Self {
+            frame_type: FrameTypeFilter::Remote,
+            ..self
+        }
+    }
+
+    /// Returns true if a frame with identifier `id` is accepted, regardless of its type.
+    pub fn matches(&self, id: Id) -> bool {
+        self.id.matches(id)
+    }
+
+    /// Returns true if `frame` is accepted.
+    pub fn matches_frame(&self, frame: &impl Frame) -> bool {
+        let frame_type = match self.frame_type {
+            FrameTypeFilter::Any => true,
+            FrameTypeFilter::Data => frame.is_data_frame(),
+            FrameTypeFilter::Remote => frame.is_remote_frame(),
+        };
+        frame_type && self.matches(frame.id())
+    }
+}
+
+/// A set of up to `N` filters.
+///
+/// A frame is accepted if it matches any of the filters, so an empty set accepts no frame.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Filters<const N: usize> {
+    filters: [Filter; N],
+    len: usize,
+}
+
+impl<const N: usize> Filters<N> {
+    /// Creates an empty set of filters.
+    pub const fn new() -> Self {
+        Self {
+            filters: [Filter::ACCEPT_ALL; N],
+            len: 0,
+        }
+    }
+
+    /// Adds a filter to the set.
+    ///
+    /// Returns the filter back if the set is full.
+    pub fn push(&mut self, filter: Filter) -> Result<(), Filter> {
+        let slot = self.filters.get_mut(self.len).ok_or(filter)?;
+        *slot = filter;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes all the filters.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the filters of the set.
+    pub fn as_slice(&self) -> &[Filter] {
+        &self.filters[..self.len]
+    }
+
+    /// Returns true if a frame with identifier `id` is accepted by any filter, regardless of its type.
+    pub fn matches(&self, id: Id) -> bool {
+        self.as_slice().iter().any(|filter| filter.matches(id))
+    }
+
+    /// Returns true if `frame` is accepted by any filter.
+    pub fn matches_frame(&self, frame: &impl Frame) -> bool {
+        self.as_slice()
+            .iter()
+            .any(|filter| filter.matches_frame(frame))
+    }
+}
+
+impl<const N: usize> Default for Filters<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::ops::Deref for Filters<N> {
+    type Target = [Filter];
+
+    fn deref(&self) -> &[Filter] {
+        self.as_slice()
+    }
+}
+
+/// A CAN controller with configurable acceptance filters.
+pub trait ConfigurableFilters {
+    /// Associated error type.
+    type Error: crate::Error;
+
+    /// Replaces the acceptance filters of the controller.
+    ///
+    /// Once configured, only frames matching any of `filters` are received: an empty slice
+    /// rejects every frame, while [`Filter::ACCEPT_ALL`] accepts every frame.
+    ///
+    /// Returns an error if the controller cannot represent the filters, for instance because
+    /// there are too many of them or because a filter type is not supported in hardware.
+    fn set_filters(&mut self, filters: &[Filter]) -> Result<(), Self::Error>;
+}
+
+impl<T: ConfigurableFilters + ?Sized> ConfigurableFilters for &mut T {
+    type Error = T::Error;
+
+    #[inline]
+    fn set_filters(&mut self, filters: &[Filter]) -> Result<(), Self::Error> {
+        T::set_filters(self, filters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestFrame {
+        id: Id,
+        remote: bool,
+    }
+
+    impl Frame for TestFrame {
+        fn new(id: impl Into<Id>, _data: &[u8]) -> Option<Self> {
+            Some(Self {
+                id: id.into(),
+                remote: false,
+            })
+        }
+
+        fn new_remote(id: impl Into<Id>, _dlc: usize) -> Option<Self> {
+            Some(Self {
+                id: id.into(),
+                remote: true,
+            })
+        }
+
+        fn is_extended(&self) -> bool {
+            matches!(self.id, Id::Extended(_))
+        }
+
+        fn is_remote_frame(&self) -> bool {
+            self.remote
+        }
+
+        fn id(&self) -> Id {
+            self.id
+        }
+
+        fn dlc(&self) -> usize {
+            0
+        }
+
+        fn data(&self) -> &[u8] {
+            &[]
+        }
+    }
+
+    fn standard(raw: u16) -> Id {
+        StandardId::new(raw).unwrap().into()
+    }
+
+    fn extended(raw: u32) -> Id {
+        ExtendedId::new(raw).unwrap().into()
+    }
+
+    #[test]
+    fn exact() {
+        let filter = Filter::new(IdFilter::Exact(standard(0x123)));
+        assert!(filter.matches(standard(0x123)));
+        assert!(!filter.matches(standard(0x124)));
+        // Same raw value, but an extended identifier.
+        assert!(!filter.matches(extended(0x123)));
+    }
+
+    #[test]
+    fn standard_mask() {
+        let filter = Filter::new(IdFilter::StandardMask {
+            id: StandardId::new(0x120).unwrap(),
+            mask: 0x7F0,
+        });
+        assert!(filter.matches(standard(0x120)));
+        assert!(filter.matches(standard(0x12F)));
+        assert!(!filter.matches(standard(0x130)));
+        assert!(!filter.matches(extended(0x120)));
+    }
+
+    #[test]
+    fn extended_mask() {
+        let filter = Filter::new(IdFilter::ExtendedMask {
+            id: ExtendedId::new(0x18FF_0000).unwrap(),
+            mask: 0x1FFF_FF00,
+        });
+        assert!(filter.matches(extended(0x18FF_0042)));
+        assert!(!filter.matches(extended(0x18FE_0042)));
+        assert!(!filter.matches(standard(0x042)));
+    }
+
+    #[test]
+    fn zero_mask_accepts_all_of_the_same_kind() {
+        let filter = Filter::new(IdFilter::StandardMask {
+            id: StandardId::new(0x555).unwrap(),
+            mask: 0,
+        });
+        assert!(filter.matches(standard(0x000)));
+        assert!(filter.matches(standard(0x7FF)));
+        assert!(!filter.matches(extended(0)));
+
+        let filter = Filter::new(IdFilter::ExtendedMask {
+            id: ExtendedId::new(0x555).unwrap(),
+            mask: 0,
+        });
+        assert!(filter.matches(extended(0)));
+        assert!(filter.matches(extended(0x1FFF_FFFF)));
+        assert!(!filter.matches(standard(0)));
+
+        assert!(Filter::ACCEPT_ALL.matches(standard(0x7FF)));
+        assert!(Filter::ACCEPT_ALL.matches(extended(0x1FFF_FFFF)));
+    }
+
+    #[test]
+    fn range() {
+        let filter = Filter::new(IdFilter::StandardRange {
+            start: StandardId::new(0x100).unwrap(),
+            end: StandardId::new(0x1FF).unwrap(),
+        });
+        assert!(!filter.matches(standard(0x0FF)));
+        assert!(filter.matches(standard(0x100)));
+        assert!(filter.matches(standard(0x1FF)));
+        assert!(!filter.matches(standard(0x200)));
+        assert!(!filter.matches(extended(0x150)));
+
+        let filter = Filter::new(IdFilter::ExtendedRange {
+            start: ExtendedId::new(0x100).unwrap(),
+            end: ExtendedId::new(0x1FF).unwrap(),
+        });
+        assert!(filter.matches(extended(0x150)));
+        assert!(!filter.matches(standard(0x150)));
+    }
+
+    #[test]
+    fn rtr_bit() {
+        let data = TestFrame::new(StandardId::new(0x10).unwrap(), &[]).unwrap();
+        let remote = TestFrame::new_remote(StandardId::new(0x10).unwrap(), 0).unwrap();
+        let filter = Filter::new(IdFilter::Exact(standard(0x10)));
+
+        assert!(filter.matches_frame(&data));
+        assert!(filter.matches_frame(&remote));
+        assert!(filter.data_only().matches_frame(&data));
+        assert!(!filter.data_only().matches_frame(&remote));
+        assert!(!filter.remote_only().matches_frame(&data));
+        assert!(filter.remote_only().matches_frame(&remote));
+    }
+
+    #[test]
+    fn filters() {
+        let mut filters = Filters::<2>::new();
+        assert!(!filters.matches(standard(0x10)));
+
+        filters
+            .push(Filter::new(IdFilter::Exact(standard(0x10))))
+            .unwrap();
+        filters
+            .push(Filter::new(IdFilter::Exact(extended(0x20))).remote_only())
+            .unwrap();
+        let full = Filter::new(IdFilter::Exact(standard(0x30)));
+        assert_eq!(filters.push(full), Err(full));
+        assert_eq!(filters.len(), 2);
+
+        assert!(filters.matches(standard(0x10)));
+        assert!(filters.matches(extended(0x20)));
+        assert!(!filters.matches(standard(0x30)));
+
+        let data = TestFrame::new(ExtendedId::new(0x20).unwrap(), &[]).unwrap();
+        let remote = TestFrame::new_remote(ExtendedId::new(0x20).unwrap(), 0).unwrap();
+        assert!(!filters.matches_frame(&data));
+        assert!(filters.matches_frame(&remote));
+
+        filters.clear();
+        assert!(filters.is_empty());
+    }
+}
@@ -6,8 +6,12 @@
 pub mod blocking;
 pub mod nb;
 
+mod fd;
+mod filter;
 mod id;
 
+pub use fd::*;
+pub use filter::*;
 pub use id::*;
 
 /// A CAN2.0 Frame
@@ -100,6 +104,20 @@ pub enum ErrorKind {
     /// monitor a dominant bit during the ACK slot.
     Acknowledge,
 
+    // Fault confinement states
+    /// The controller entered the bus-off state after too many errors, and
+    /// no longer takes part in bus communication until it is recovered.
+    BusOff,
+
+    /// The controller entered the error-passive state after too many errors.
+    ///
+    /// It can still communicate, but may only signal errors passively.
+    ErrorPassive,
+
+    /// The controller lost the arbitration while transmitting a frame, and
+    /// the frame was not retransmitted.
+    ArbitrationLoss,
+
     /// A different error occurred. The original error may contain more information.
     Other,
 }
@@ -127,6 +145,9 @@ impl core::fmt::Display for ErrorKind {
                 "A fixed-form bit field contains one or more illegal bits"
             ),
             Self::Acknowledge => write!(f, "Transmitted frame was not acknowledged"),
+            Self::BusOff => write!(f, "The controller is in the bus-off state"),
+            Self::ErrorPassive => write!(f, "The controller is in the error-passive state"),
+            Self::ArbitrationLoss => write!(f, "The arbitration was lost"),
             Self::Other => write!(
                 f,
                 "A different error occurred. The original error may contain more information"
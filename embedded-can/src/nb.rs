@@ -1,5 +1,7 @@
 //! Non-blocking CAN API
 
+use crate::Frame;
+
 /// A CAN interface that is able to transmit and receive frames.
 pub trait Can {
     /// Associated frame type.
@@ -26,3 +28,408 @@ pub trait Can {
     /// Returns a received frame if available.
     fn receive(&mut self) -> nb::Result<Self::Frame, Self::Error>;
 }
+
+/// A CAN FD interface that is able to transmit and receive both classic and CAN FD frames.
+///
+/// This is the CAN FD counterpart of [`Can`]: the frame type implements [`FdFrame`](crate::FdFrame),
+/// and whether a frame is transmitted as a classic or as an FD frame depends on how it was created.
+pub trait FdCan {
+    /// Associated frame type.
+    type Frame: crate::FdFrame;
+
+    /// Associated error type.
+    type Error: crate::Error;
+
+    /// Puts a frame in the transmit buffer to be sent on the bus.
+    ///
+    /// This behaves like [`Can::transmit`], including the replacement of a pending
+    /// lower priority frame.
+    fn transmit(&mut self, frame: &Self::Frame) -> nb::Result<Option<Self::Frame>, Self::Error>;
+
+    /// Returns a received frame if available.
+    fn receive(&mut self) -> nb::Result<Self::Frame, Self::Error>;
+}
+
+/// What [`TxQueue::enqueue`] does when the queue is full.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum QueueFullPolicy {
+    /// Reject the new frame.
+    Reject,
+    /// Drop the lowest priority queued frame to make room for the new frame, if the new frame has
+    /// a higher priority. Otherwise, reject the new frame.
+    ReplaceLowestPriority,
+}
+
+/// Software transmit queue, ordered by CAN arbitration priority.
+///
+/// CAN controllers usually have very few transmit mailboxes. [`TxQueue`] keeps up to `N` frames in
+/// a fixed-size buffer, and hands them to the controller from the highest priority one, following
+/// the [`Ord`] implementation of [`Id`](crate::Id): lower identifiers first, and standard frames
+/// before extended frames with the same base identifier. Frames with the same identifier are sent in
+/// the order they were enqueued.
+///
+/// [`poll_transmit`](TxQueue::poll_transmit) must be called whenever a mailbox may be free, typically
+/// from the transmit complete interrupt and after enqueuing frames. When the controller displaces a
+/// pending lower priority frame (the `Ok(Some(frame))` result of [`Can::transmit`]), the displaced
+/// frame is put back in the queue, so no frame is lost.
+///
+/// # Example
+///
+/// ```
+/// use embedded_can::nb::{Can, QueueFullPolicy, TxQueue};
+///
+/// fn send_all<C: Can>(can: C, frames: impl IntoIterator<Item = C::Frame>) -> Result<(), C::Error> {
+///     let mut queue = TxQueue::<C, 16>::new(can, QueueFullPolicy::Reject);
+///     for frame in frames {
+///         if queue.enqueue(frame).is_err() {
+///             // The queue is full: drop the frame.
+///         }
+///     }
+///     // Fill the free mailboxes, call again when a transmission completes.
+///     queue.poll_transmit()
+/// }
+/// ```
+pub struct TxQueue<C: Can, const N: usize> {
+    inner: C,
+    policy: QueueFullPolicy,
+    /// Queued frames, the first `len` ones sorted by decreasing priority.
+    frames: [Option<C::Frame>; N],
+    len: usize,
+}
+
+impl<C: Can, const N: usize> TxQueue<C, N> {
+    /// Create a new empty [`TxQueue`], applying `policy` when it is full.
+    pub fn new(inner: C, policy: QueueFullPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            frames: core::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+
+    /// Consume the queue, returning the inner `nb` CAN interface. Queued frames are dropped.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Borrow the inner `nb` CAN interface.
+    #[inline]
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner `nb` CAN interface, for example to receive frames.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Returns the number of queued frames.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no frame is queued.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the queue holds `N` frames.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the highest priority queued frame, which is the next one to be transmitted.
+    #[inline]
+    pub fn peek(&self) -> Option<&C::Frame> {
+        self.frames[..self.len].first().and_then(Option::as_ref)
+    }
+
+    /// Add `frame` to the queue.
+    ///
+    /// The frame is only transmitted by the next call to [`poll_transmit`](TxQueue::poll_transmit).
+    ///
+    /// Returns `Ok(None)` if the frame was queued, and `Ok(Some(dropped))` if it was queued in place
+    /// of the lowest priority frame, with the [`ReplaceLowestPriority`](QueueFullPolicy::ReplaceLowestPriority)
+    /// policy. Returns `Err(frame)` if the queue is full and the frame was rejected.
+    pub fn enqueue(&mut self, frame: C::Frame) -> Result<Option<C::Frame>, C::Frame> {
+        let mut dropped = None;
+        if self.is_full() {
+            match (self.policy, self.frames.last()) {
+                (QueueFullPolicy::ReplaceLowestPriority, Some(Some(lowest)))
+                    if frame.id() < lowest.id() =>
+                {
+                    dropped = self.frames[N - 1].take();
+                    self.len -= 1;
+                }
+                _ => return Err(frame),
+            }
+        }
+        // After the frames with the same identifier, to send them in FIFO order.
+        let index = self.position(|queued| queued.id() <= frame.id());
+        self.insert(index, frame);
+        Ok(dropped)
+    }
+
+    /// Hand the queued frames to the controller, from the highest priority one, until its transmit
+    /// buffer is full.
+    ///
+    /// Frames displaced from the transmit buffer by the controller are put back in the queue. On
+    /// error, the frame being transmitted stays in the queue and the error is returned.
+    pub fn poll_transmit(&mut self) -> Result<(), C::Error> {
+        while let Some(Some(frame)) = self.frames[..self.len].first() {
+            match self.inner.transmit(frame) {
+                Ok(displaced) => {
+                    self.remove_first();
+                    if let Some(displaced) = displaced {
+                        // Ahead of the frames with the same identifier, which were queued after it.
+                        let index = self.position(|queued| queued.id() < displaced.id());
+                        self.insert(index, displaced);
+                    }
+                }
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the first queued frame not matching `before`.
+    fn position(&self, before: impl Fn(&C::Frame) -> bool) -> usize {
+        self.frames[..self.len]
+            .iter()
+            .position(|queued| !queued.as_ref().is_some_and(&before))
+            .unwrap_or(self.len)
+    }
+
+    fn insert(&mut self, index: usize, frame: C::Frame) {
+        self.frames[index..=self.len].rotate_right(1);
+        self.frames[index] = Some(frame);
+        self.len += 1;
+    }
+
+    fn remove_first(&mut self) {
+        self.frames[0] = None;
+        self.frames[..self.len].rotate_left(1);
+        self.len -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::{ErrorKind, ExtendedId, Id, StandardId};
+    use std::vec::Vec;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestFrame {
+        id: Id,
+        data: u8,
+    }
+
+    impl Frame for TestFrame {
+        fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+            Some(Self {
+                id: id.into(),
+                data: *data.first()?,
+            })
+        }
+
+        fn new_remote(_id: impl Into<Id>, _dlc: usize) -> Option<Self> {
+            None
+        }
+
+        fn is_extended(&self) -> bool {
+            matches!(self.id, Id::Extended(_))
+        }
+
+        fn is_remote_frame(&self) -> bool {
+            false
+        }
+
+        fn id(&self) -> Id {
+            self.id
+        }
+
+        fn dlc(&self) -> usize {
+            1
+        }
+
+        fn data(&self) -> &[u8] {
+            core::slice::from_ref(&self.data)
+        }
+    }
+
+    fn frame(id: u16, data: u8) -> TestFrame {
+        Frame::new(StandardId::new(id).unwrap(), &[data]).unwrap()
+    }
+
+    fn extended(id: u32, data: u8) -> TestFrame {
+        Frame::new(ExtendedId::new(id).unwrap(), &[data]).unwrap()
+    }
+
+    /// A controller with two transmit mailboxes, displacing the lowest priority pending frame if
+    /// the new one has a higher priority.
+    #[derive(Default)]
+    struct FakeCan {
+        mailboxes: Vec<TestFrame>,
+        sent: Vec<TestFrame>,
+        fail: bool,
+    }
+
+    impl FakeCan {
+        /// Transmit the highest priority pending frame.
+        fn complete(&mut self) {
+            if let Some(index) = (0..self.mailboxes.len()).min_by_key(|&i| self.mailboxes[i].id) {
+                let frame = self.mailboxes.remove(index);
+                self.sent.push(frame);
+            }
+        }
+    }
+
+    impl Can for FakeCan {
+        type Frame = TestFrame;
+        type Error = ErrorKind;
+
+        fn transmit(&mut self, frame: &TestFrame) -> nb::Result<Option<TestFrame>, ErrorKind> {
+            if self.fail {
+                return Err(nb::Error::Other(ErrorKind::Overrun));
+            }
+            if self.mailboxes.len() < 2 {
+                self.mailboxes.push(frame.clone());
+                return Ok(None);
+            }
+            let lowest = (0..2).max_by_key(|&i| self.mailboxes[i].id).unwrap();
+            if frame.id < self.mailboxes[lowest].id {
+                Ok(Some(core::mem::replace(
+                    &mut self.mailboxes[lowest],
+                    frame.clone(),
+                )))
+            } else {
+                Err(nb::Error::WouldBlock)
+            }
+        }
+
+        fn receive(&mut self) -> nb::Result<TestFrame, ErrorKind> {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Poll the queue and complete transmissions until every frame was sent.
+    fn drain<const N: usize>(queue: &mut TxQueue<FakeCan, N>) -> Vec<TestFrame> {
+        queue.poll_transmit().unwrap();
+        while !queue.inner().mailboxes.is_empty() {
+            queue.inner_mut().complete();
+            queue.poll_transmit().unwrap();
+        }
+        core::mem::take(&mut queue.inner_mut().sent)
+    }
+
+    #[test]
+    fn transmits_by_priority() {
+        let mut queue = TxQueue::<_, 8>::new(FakeCan::default(), QueueFullPolicy::Reject);
+        for f in [
+            frame(0x300, 0),
+            extended(0x100 << 18, 1),
+            frame(0x100, 2),
+            frame(0x050, 3),
+        ] {
+            queue.enqueue(f).unwrap();
+        }
+        assert_eq!(queue.peek(), Some(&frame(0x050, 3)));
+        assert_eq!(
+            drain(&mut queue),
+            [
+                frame(0x050, 3),
+                frame(0x100, 2),
+                extended(0x100 << 18, 1),
+                frame(0x300, 0)
+            ]
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn frames_with_the_same_id_are_sent_in_fifo_order() {
+        let mut queue = TxQueue::<_, 8>::new(FakeCan::default(), QueueFullPolicy::Reject);
+        for data in 0..5 {
+            queue.enqueue(frame(0x123, data)).unwrap();
+        }
+        let sent: Vec<u8> = drain(&mut queue).iter().map(|f| f.data).collect();
+        assert_eq!(sent, [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn displaced_frames_are_requeued() {
+        let mut queue = TxQueue::<_, 4>::new(FakeCan::default(), QueueFullPolicy::Reject);
+        queue.enqueue(frame(0x200, 0)).unwrap();
+        queue.enqueue(frame(0x300, 1)).unwrap();
+        queue.poll_transmit().unwrap();
+        assert!(queue.is_empty());
+
+        // The urgent frame displaces 0x300 from the mailboxes, which goes back in the queue.
+        queue.enqueue(frame(0x010, 2)).unwrap();
+        queue.poll_transmit().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.peek(), Some(&frame(0x300, 1)));
+        assert_eq!(
+            drain(&mut queue),
+            [frame(0x010, 2), frame(0x200, 0), frame(0x300, 1)]
+        );
+    }
+
+    #[test]
+    fn displaced_frames_keep_their_place_among_equal_ids() {
+        let mut queue = TxQueue::<_, 4>::new(FakeCan::default(), QueueFullPolicy::Reject);
+        queue.enqueue(frame(0x200, 0)).unwrap();
+        queue.enqueue(frame(0x300, 1)).unwrap();
+        queue.poll_transmit().unwrap();
+        queue.enqueue(frame(0x300, 2)).unwrap();
+        queue.enqueue(frame(0x010, 3)).unwrap();
+        queue.poll_transmit().unwrap();
+        let sent: Vec<u8> = drain(&mut queue).iter().map(|f| f.data).collect();
+        assert_eq!(sent, [3, 0, 1, 2]);
+    }
+
+    #[test]
+    fn full_queue_policies() {
+        let mut queue = TxQueue::<_, 2>::new(FakeCan::default(), QueueFullPolicy::Reject);
+        queue.enqueue(frame(0x200, 0)).unwrap();
+        queue.enqueue(frame(0x300, 1)).unwrap();
+        assert!(queue.is_full());
+        assert_eq!(queue.enqueue(frame(0x100, 2)), Err(frame(0x100, 2)));
+
+        let mut queue =
+            TxQueue::<_, 2>::new(FakeCan::default(), QueueFullPolicy::ReplaceLowestPriority);
+        queue.enqueue(frame(0x200, 0)).unwrap();
+        queue.enqueue(frame(0x300, 1)).unwrap();
+        assert_eq!(queue.enqueue(frame(0x100, 2)), Ok(Some(frame(0x300, 1))));
+        assert_eq!(queue.enqueue(frame(0x400, 3)), Err(frame(0x400, 3)));
+        assert_eq!(drain(&mut queue), [frame(0x100, 2), frame(0x200, 0)]);
+    }
+
+    #[test]
+    fn errors_keep_the_frame_queued() {
+        let mut queue = TxQueue::<_, 2>::new(
+            FakeCan {
+                fail: true,
+                ..Default::default()
+            },
+            QueueFullPolicy::Reject,
+        );
+        queue.enqueue(frame(0x100, 0)).unwrap();
+        assert_eq!(queue.poll_transmit(), Err(ErrorKind::Overrun));
+        assert_eq!(queue.len(), 1);
+
+        queue.inner_mut().fail = false;
+        assert_eq!(drain(&mut queue), [frame(0x100, 0)]);
+    }
+}
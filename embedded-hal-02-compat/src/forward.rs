@@ -0,0 +1,688 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, InputPin, OutputPin, StatefulOutputPin};
+use embedded_hal::i2c::{self, I2c, Operation};
+use embedded_hal::spi::{self, SpiBus};
+use embedded_hal_02::blocking::delay::DelayUs;
+use embedded_hal_02::blocking::{i2c as i2c02, spi as spi02};
+use embedded_hal_02::digital::v2 as digital02;
+
+/// Marker of a [`Forward`] wrapper around a 0.2 input pin, see [`Forward::input_pin`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ForwardInputPin;
+
+/// Marker of a [`Forward`] wrapper around a 0.2 output pin, see [`Forward::output_pin`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ForwardOutputPin;
+
+/// Wrapper implementing the embedded-hal 1.0 traits on top of a 0.2 implementation.
+///
+/// Depending on the 0.2 traits the wrapped value implements, this implements:
+///
+/// - [`I2c`], from the 0.2 `Read`, `Write`, `WriteRead`, `WriteIter` and `WriteIterRead` I2C traits,
+///   with 7-bit addresses.
+/// - [`SpiBus`], from the 0.2 `Transfer` and `Write` SPI traits.
+/// - [`DelayNs`], from the 0.2 `DelayUs<u32>` trait.
+/// - With [`ForwardOutputPin`] as `M`, [`OutputPin`], from the 0.2 `OutputPin` trait, and
+///   [`StatefulOutputPin`] and [`InputPin`], if the pin also implements their 0.2 counterparts.
+/// - With [`ForwardInputPin`] as `M`, [`InputPin`], from the 0.2 `InputPin` trait.
+///
+/// The 1.0 `digital` traits share a single error type, while each 0.2 pin trait has its own: the marker
+/// `M` selects the 0.2 trait the error type is taken from.
+///
+/// The errors are wrapped in [`ForwardError`], which implements the 1.0 `Error` traits.
+///
+/// # I2C transactions
+///
+/// A 0.2 I2C implementation cannot run arbitrary transactions. The transactions made of a single
+/// read, a single write, or a write followed by a read, are run with the matching 0.2 method. Adjacent
+/// writes are merged into a single write with `WriteIter`, or with `WriteIterRead` when followed by a
+/// read. This covers the transactions made of writes, optionally followed by a single read.
+///
+/// # Panics
+///
+/// [`transaction`](I2c::transaction) panics if the operations cannot be run in a single 0.2
+/// transaction, i.e. if a read is followed by another operation. Splitting them into several
+/// transactions would break the [`I2c`] contract, and the target would see several transfers.
+///
+/// # SPI transfers
+///
+/// The 0.2 SPI traits are blocking, so [`flush`](SpiBus::flush) does nothing. [`read`](SpiBus::read)
+/// writes `W::default()` words, and [`transfer`](SpiBus::transfer) with buffers of different
+/// lengths transfers the common part in place, then reads or writes the rest. The error type is taken
+/// from the 0.2 `Write<u8>` implementation.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::OutputPin;
+/// use embedded_hal_02_compat::ForwardCompat;
+/// # struct Pin02;
+/// # impl embedded_hal_02::digital::v2::OutputPin for Pin02 {
+/// #     type Error = core::convert::Infallible;
+/// #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+/// # }
+///
+/// // A 0.2 pin, driven through the 1.0 trait.
+/// let mut led = Pin02.forward_output_pin();
+/// led.set_high().unwrap();
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Forward<T, M = ()> {
+    inner: T,
+    _marker: PhantomData<M>,
+}
+
+impl<T, M> Forward<T, M> {
+    fn wrap(inner: T) -> Self {
+        Self {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the wrapped value.
+    #[inline]
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped value.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Release the wrapped value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> Forward<T> {
+    /// Create a new [`Forward`] wrapper around a 0.2 I2C bus, SPI bus or delay.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self::wrap(inner)
+    }
+}
+
+impl<T> Forward<T, ForwardInputPin> {
+    /// Create a new [`Forward`] wrapper around a 0.2 input pin.
+    #[inline]
+    pub fn input_pin(inner: T) -> Self {
+        Self::wrap(inner)
+    }
+}
+
+impl<T> Forward<T, ForwardOutputPin> {
+    /// Create a new [`Forward`] wrapper around a 0.2 output pin.
+    #[inline]
+    pub fn output_pin(inner: T) -> Self {
+        Self::wrap(inner)
+    }
+}
+
+/// Extension trait wrapping values in a [`Forward`] wrapper.
+pub trait ForwardCompat: Sized {
+    /// Wrap a 0.2 I2C bus, SPI bus or delay, see [`Forward::new`].
+    #[inline]
+    fn forward(self) -> Forward<Self> {
+        Forward::new(self)
+    }
+
+    /// Wrap a 0.2 input pin, see [`Forward::input_pin`].
+    #[inline]
+    fn forward_input_pin(self) -> Forward<Self, ForwardInputPin> {
+        Forward::input_pin(self)
+    }
+
+    /// Wrap a 0.2 output pin, see [`Forward::output_pin`].
+    #[inline]
+    fn forward_output_pin(self) -> Forward<Self, ForwardOutputPin> {
+        Forward::output_pin(self)
+    }
+}
+
+impl<T> ForwardCompat for T {}
+
+/// Error of a [`Forward`] wrapper, wrapping the error of the 0.2 implementation.
+///
+/// The 0.2 errors carry no error kind, so the kind is always `Other`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ForwardError<E>(pub E);
+
+impl<E: Debug> Display for ForwardError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "embedded-hal 0.2 error: {:?}", self.0)
+    }
+}
+
+impl<E: Debug> core::error::Error for ForwardError<E> {}
+
+impl<E: Debug> i2c::Error for ForwardError<E> {
+    #[inline]
+    fn kind(&self) -> i2c::ErrorKind {
+        i2c::ErrorKind::Other
+    }
+}
+
+impl<E: Debug> spi::Error for ForwardError<E> {
+    #[inline]
+    fn kind(&self) -> spi::ErrorKind {
+        spi::ErrorKind::Other
+    }
+}
+
+impl<E: Debug> digital::Error for ForwardError<E> {
+    #[inline]
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+impl<T> i2c::ErrorType for Forward<T>
+where
+    T: i2c02::Write,
+    T::Error: Debug,
+{
+    type Error = ForwardError<T::Error>;
+}
+
+impl<T, E> I2c for Forward<T>
+where
+    T: i2c02::Read<Error = E>
+        + i2c02::Write<Error = E>
+        + i2c02::WriteRead<Error = E>
+        + i2c02::WriteIter<Error = E>
+        + i2c02::WriteIterRead<Error = E>,
+    E: Debug,
+{
+    #[inline]
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        i2c02::Read::read(&mut self.inner, address, read).map_err(ForwardError)
+    }
+
+    #[inline]
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        i2c02::Write::write(&mut self.inner, address, write).map_err(ForwardError)
+    }
+
+    #[inline]
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        i2c02::WriteRead::write_read(&mut self.inner, address, write, read).map_err(ForwardError)
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        match operations {
+            [Operation::Read(read)] => I2c::read(self, address, read),
+            [Operation::Write(write)] => I2c::write(self, address, write),
+            [Operation::Write(write), Operation::Read(read)] => {
+                I2c::write_read(self, address, write, read)
+            }
+            [] => Ok(()),
+            [writes @ .., Operation::Read(read)] if only_writes(writes) => {
+                i2c02::WriteIterRead::write_iter_read(
+                    &mut self.inner,
+                    address,
+                    written_bytes(writes),
+                    read,
+                )
+                .map_err(ForwardError)
+            }
+            writes if only_writes(writes) => {
+                i2c02::WriteIter::write(&mut self.inner, address, written_bytes(writes))
+                    .map_err(ForwardError)
+            }
+            _ => panic!(
+                "0.2 I2C implementations cannot run a transaction with an operation after a read"
+            ),
+        }
+    }
+}
+
+fn only_writes(operations: &[Operation<'_>]) -> bool {
+    operations
+        .iter()
+        .all(|operation| matches!(operation, Operation::Write(_)))
+}
+
+/// Bytes of the writes among `operations`.
+fn written_bytes<'a>(operations: &'a [Operation<'_>]) -> impl Iterator<Item = u8> + 'a {
+    operations
+        .iter()
+        .flat_map(|operation| match operation {
+            Operation::Write(write) => *write,
+            Operation::Read(_) => &[],
+        })
+        .copied()
+}
+
+impl<T> spi::ErrorType for Forward<T>
+where
+    T: spi02::Write<u8>,
+    T::Error: Debug,
+{
+    type Error = ForwardError<T::Error>;
+}
+
+impl<T, W, E> SpiBus<W> for Forward<T>
+where
+    Self: spi::ErrorType<Error = ForwardError<E>>,
+    T: spi02::Transfer<W, Error = E> + spi02::Write<W, Error = E>,
+    W: Copy + Default + 'static,
+    E: Debug,
+{
+    #[inline]
+    fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        words.fill(W::default());
+        SpiBus::transfer_in_place(self, words)
+    }
+
+    #[inline]
+    fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        spi02::Write::write(&mut self.inner, words).map_err(ForwardError)
+    }
+
+    fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        let common = read.len().min(write.len());
+        let (read, read_rest) = read.split_at_mut(common);
+        let (write, write_rest) = write.split_at(common);
+        read.copy_from_slice(write);
+        SpiBus::transfer_in_place(self, read)?;
+        if !read_rest.is_empty() {
+            SpiBus::read(self, read_rest)?;
+        } else if !write_rest.is_empty() {
+            SpiBus::write(self, write_rest)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        spi02::Transfer::transfer(&mut self.inner, words)
+            .map(drop)
+            .map_err(ForwardError)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<T> digital::ErrorType for Forward<T, ForwardInputPin>
+where
+    T: digital02::InputPin,
+    T::Error: Debug,
+{
+    type Error = ForwardError<T::Error>;
+}
+
+impl<T> InputPin for Forward<T, ForwardInputPin>
+where
+    T: digital02::InputPin,
+    T::Error: Debug,
+{
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.inner.is_high().map_err(ForwardError)
+    }
+
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.inner.is_low().map_err(ForwardError)
+    }
+}
+
+impl<T> digital::ErrorType for Forward<T, ForwardOutputPin>
+where
+    T: digital02::OutputPin,
+    T::Error: Debug,
+{
+    type Error = ForwardError<T::Error>;
+}
+
+impl<T> OutputPin for Forward<T, ForwardOutputPin>
+where
+    T: digital02::OutputPin,
+    T::Error: Debug,
+{
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.inner.set_low().map_err(ForwardError)
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.inner.set_high().map_err(ForwardError)
+    }
+}
+
+impl<T> StatefulOutputPin for Forward<T, ForwardOutputPin>
+where
+    T: digital02::StatefulOutputPin,
+    T::Error: Debug,
+{
+    #[inline]
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        self.inner.is_set_high().map_err(ForwardError)
+    }
+
+    #[inline]
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self.inner.is_set_low().map_err(ForwardError)
+    }
+}
+
+/// Reading back an output pin, e.g. an open-drain pin, requires its input and output errors to be the same.
+impl<T, E> InputPin for Forward<T, ForwardOutputPin>
+where
+    T: digital02::OutputPin<Error = E> + digital02::InputPin<Error = E>,
+    E: Debug,
+{
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        digital02::InputPin::is_high(&self.inner).map_err(ForwardError)
+    }
+
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        digital02::InputPin::is_low(&self.inner).map_err(ForwardError)
+    }
+}
+
+impl<T: DelayUs<u32>> DelayNs for Forward<T> {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.inner.delay_us(ns.div_ceil(1_000));
+    }
+
+    #[inline]
+    fn delay_us(&mut self, us: u32) {
+        self.inner.delay_us(us);
+    }
+
+    fn delay_ms(&mut self, mut ms: u32) {
+        const MAX_MILLIS: u32 = u32::MAX / 1_000;
+
+        // Split the delay, as the microseconds could overflow.
+        while ms > MAX_MILLIS {
+            ms -= MAX_MILLIS;
+            self.inner.delay_us(MAX_MILLIS * 1_000);
+        }
+        self.inner.delay_us(ms * 1_000);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use std::{vec, vec::Vec};
+
+    /// 0.2 bus logging the calls, and returning each written word plus one.
+    #[derive(Default)]
+    struct Bus02 {
+        log: Vec<(&'static str, u8, Vec<u8>)>,
+    }
+
+    impl i2c02::Read for Bus02 {
+        type Error = Infallible;
+        fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            buffer.fill(0xAA);
+            self.log.push(("read", address, buffer.to_vec()));
+            Ok(())
+        }
+    }
+
+    impl i2c02::Write for Bus02 {
+        type Error = Infallible;
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.log.push(("write", address, bytes.to_vec()));
+            Ok(())
+        }
+    }
+
+    impl i2c02::WriteRead for Bus02 {
+        type Error = Infallible;
+        fn write_read(
+            &mut self,
+            address: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            buffer.fill(0xAA);
+            self.log.push(("write_read", address, bytes.to_vec()));
+            Ok(())
+        }
+    }
+
+    impl i2c02::WriteIter for Bus02 {
+        type Error = Infallible;
+        fn write<B>(&mut self, address: u8, bytes: B) -> Result<(), Self::Error>
+        where
+            B: IntoIterator<Item = u8>,
+        {
+            self.log
+                .push(("write_iter", address, bytes.into_iter().collect()));
+            Ok(())
+        }
+    }
+
+    impl i2c02::WriteIterRead for Bus02 {
+        type Error = Infallible;
+        fn write_iter_read<B>(
+            &mut self,
+            address: u8,
+            bytes: B,
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error>
+        where
+            B: IntoIterator<Item = u8>,
+        {
+            buffer.fill(0xAA);
+            self.log
+                .push(("write_iter_read", address, bytes.into_iter().collect()));
+            Ok(())
+        }
+    }
+
+    impl spi02::Transfer<u8> for Bus02 {
+        type Error = Infallible;
+        fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+            self.log.push(("transfer", 0, words.to_vec()));
+            for word in words.iter_mut() {
+                *word += 1;
+            }
+            Ok(words)
+        }
+    }
+
+    impl spi02::Write<u8> for Bus02 {
+        type Error = Infallible;
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.log.push(("spi_write", 0, words.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn i2c_transactions() {
+        let mut i2c = Bus02::default().forward();
+        let mut buf = [0; 2];
+        i2c.transaction(
+            0x10,
+            &mut [Operation::Write(&[1]), Operation::Read(&mut buf)],
+        )
+        .unwrap();
+        assert_eq!(buf, [0xAA; 2]);
+        i2c.transaction(0x20, &mut [Operation::Read(&mut buf)])
+            .unwrap();
+        // Adjacent writes are merged into a single 0.2 transaction.
+        i2c.transaction(
+            0x30,
+            &mut [Operation::Write(&[1]), Operation::Write(&[2, 3])],
+        )
+        .unwrap();
+        i2c.transaction(
+            0x40,
+            &mut [
+                Operation::Write(&[4]),
+                Operation::Write(&[5]),
+                Operation::Read(&mut buf),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            i2c.into_inner().log,
+            [
+                ("write_read", 0x10, vec![1]),
+                ("read", 0x20, vec![0xAA; 2]),
+                ("write_iter", 0x30, vec![1, 2, 3]),
+                ("write_iter_read", 0x40, vec![4, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn i2c_transaction_with_operation_after_read() {
+        let mut i2c = Bus02::default().forward();
+        let mut buf = [0; 2];
+        let _ = i2c.transaction(
+            0x10,
+            &mut [Operation::Read(&mut buf), Operation::Write(&[1])],
+        );
+    }
+
+    #[test]
+    fn spi_transfers_of_different_lengths() {
+        let mut spi = Bus02::default().forward();
+
+        let mut read = [0; 3];
+        SpiBus::transfer(&mut spi, &mut read, &[1]).unwrap();
+        assert_eq!(read, [2, 1, 1]);
+
+        let mut read = [0; 1];
+        SpiBus::transfer(&mut spi, &mut read, &[5, 6, 7]).unwrap();
+        assert_eq!(read, [6]);
+
+        let mut read = [9; 2];
+        SpiBus::read(&mut spi, &mut read).unwrap();
+        assert_eq!(read, [1, 1]);
+        SpiBus::flush(&mut spi).unwrap();
+
+        assert_eq!(
+            spi.into_inner().log,
+            [
+                ("transfer", 0, vec![1]),
+                ("transfer", 0, vec![0, 0]),
+                ("transfer", 0, vec![5]),
+                ("spi_write", 0, vec![6, 7]),
+                ("transfer", 0, vec![0, 0]),
+            ]
+        );
+    }
+
+    /// 0.2 delay logging the delays.
+    #[derive(Default)]
+    struct Delay02(Vec<u32>);
+
+    impl DelayUs<u32> for Delay02 {
+        fn delay_us(&mut self, us: u32) {
+            self.0.push(us);
+        }
+    }
+
+    #[test]
+    fn delays_are_rounded_up_and_split() {
+        let mut delay = Delay02::default().forward();
+        delay.delay_ns(1);
+        delay.delay_ns(2_000);
+        delay.delay_us(7);
+        delay.delay_ms(3);
+        assert_eq!(delay.inner().0, [1, 2, 7, 3_000]);
+
+        delay.inner_mut().0.clear();
+        delay.delay_ms(u32::MAX);
+        let total: u64 = delay.inner().0.iter().copied().map(u64::from).sum();
+        assert_eq!(total, u64::from(u32::MAX) * 1_000);
+    }
+
+    /// 0.2 open-drain pin, reading back its own level.
+    #[derive(Default)]
+    struct Pin02 {
+        high: bool,
+    }
+
+    impl digital02::OutputPin for Pin02 {
+        type Error = Infallible;
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.high = false;
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    impl digital02::StatefulOutputPin for Pin02 {
+        fn is_set_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.high)
+        }
+        fn is_set_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.high)
+        }
+    }
+
+    impl digital02::InputPin for Pin02 {
+        type Error = Infallible;
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.high)
+        }
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.high)
+        }
+    }
+
+    #[test]
+    fn pins() {
+        let mut pin = Pin02::default().forward_output_pin();
+        pin.set_high().unwrap();
+        assert!(InputPin::is_high(&mut pin).unwrap());
+        pin.toggle().unwrap();
+        assert!(pin.is_set_low().unwrap());
+
+        let mut input = pin.into_inner().forward_input_pin();
+        assert!(input.is_low().unwrap());
+    }
+
+    #[test]
+    fn errors_are_wrapped() {
+        use embedded_hal::i2c::Error;
+
+        let error = ForwardError("timeout");
+        assert_eq!(error.kind(), i2c::ErrorKind::Other);
+        assert_eq!(
+            std::format!("{error}"),
+            "embedded-hal 0.2 error: \"timeout\""
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use core::cell::RefCell;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin, StatefulOutputPin};
+use embedded_hal::i2c::{self, I2c};
+use embedded_hal::spi::SpiBus;
+use embedded_hal_02::blocking::delay::{DelayMs, DelayUs};
+use embedded_hal_02::blocking::{i2c as i2c02, spi as spi02};
+use embedded_hal_02::digital::v2 as digital02;
+
+/// Wrapper implementing the embedded-hal 0.2 traits on top of a 1.0 implementation.
+///
+/// Depending on the 1.0 traits the wrapped value implements, this implements:
+///
+/// - The 0.2 `Read`, `Write` and `WriteRead` I2C traits, from [`I2c`].
+/// - The 0.2 `Transfer`, `Write` and `Transactional` SPI traits for `u8` and `u16` words, from
+///   [`SpiBus`]. The bus is flushed before returning, as the 0.2 traits are blocking.
+/// - The 0.2 `OutputPin`, `StatefulOutputPin`, `ToggleableOutputPin` and `InputPin` traits, from
+///   [`OutputPin`], [`StatefulOutputPin`] and [`InputPin`].
+/// - The 0.2 `DelayMs` and `DelayUs` traits for `u8`, `u16` and `u32`, from [`DelayNs`].
+///
+/// The errors are the ones of the 1.0 implementation.
+///
+/// The 0.2 pin traits read the state of the pin through `&self`, while the 1.0 ones take `&mut self`:
+/// the wrapped value is kept in a [`RefCell`], so `Reverse` is not `Sync`.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_02::blocking::delay::DelayMs;
+/// use embedded_hal_02_compat::ReverseCompat;
+/// # struct Delay;
+/// # impl embedded_hal::delay::DelayNs for Delay {
+/// #     fn delay_ns(&mut self, _ns: u32) {}
+/// # }
+///
+/// // A 1.0 delay, used through the 0.2 traits.
+/// let mut delay = Delay.reverse();
+/// delay.delay_ms(10u8);
+/// ```
+#[derive(Debug)]
+pub struct Reverse<T> {
+    inner: RefCell<T>,
+}
+
+impl<T> Reverse<T> {
+    /// Create a new [`Reverse`] wrapper.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner: RefCell::new(inner),
+        }
+    }
+
+    /// Returns a mutable reference to the wrapped value.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    /// Release the wrapped value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+/// Extension trait wrapping values in a [`Reverse`] wrapper.
+pub trait ReverseCompat: Sized {
+    /// Wrap a 1.0 implementation, see [`Reverse::new`].
+    #[inline]
+    fn reverse(self) -> Reverse<Self> {
+        Reverse::new(self)
+    }
+}
+
+impl<T> ReverseCompat for T {}
+
+impl<T, A> i2c02::Read<A> for Reverse<T>
+where
+    T: I2c<A>,
+    A: i2c::AddressMode + i2c02::AddressMode,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn read(&mut self, address: A, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.get_mut().read(address, buffer)
+    }
+}
+
+impl<T, A> i2c02::Write<A> for Reverse<T>
+where
+    T: I2c<A>,
+    A: i2c::AddressMode + i2c02::AddressMode,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn write(&mut self, address: A, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.inner.get_mut().write(address, bytes)
+    }
+}
+
+impl<T, A> i2c02::WriteRead<A> for Reverse<T>
+where
+    T: I2c<A>,
+    A: i2c::AddressMode + i2c02::AddressMode,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn write_read(
+        &mut self,
+        address: A,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.inner.get_mut().write_read(address, bytes, buffer)
+    }
+}
+
+// The 0.2 SPI traits have blanket implementations for the types implementing their `Default` marker
+// traits, with a generic word type: implementing them for a generic word type would conflict.
+macro_rules! impl_spi {
+    ($($word:ty),*) => {
+        $(
+            impl<T: SpiBus<$word>> spi02::Transfer<$word> for Reverse<T> {
+                type Error = T::Error;
+
+                fn transfer<'w>(&mut self, words: &'w mut [$word]) -> Result<&'w [$word], Self::Error> {
+                    let bus = self.inner.get_mut();
+                    bus.transfer_in_place(words)?;
+                    bus.flush()?;
+                    Ok(words)
+                }
+            }
+
+            impl<T: SpiBus<$word>> spi02::Write<$word> for Reverse<T> {
+                type Error = T::Error;
+
+                fn write(&mut self, words: &[$word]) -> Result<(), Self::Error> {
+                    let bus = self.inner.get_mut();
+                    bus.write(words)?;
+                    bus.flush()
+                }
+            }
+
+            impl<T: SpiBus<$word>> spi02::Transactional<$word> for Reverse<T> {
+                type Error = T::Error;
+
+                fn exec(
+                    &mut self,
+                    operations: &mut [spi02::Operation<'_, $word>],
+                ) -> Result<(), Self::Error> {
+                    let bus = self.inner.get_mut();
+                    for operation in operations {
+                        match operation {
+                            spi02::Operation::Write(words) => bus.write(words)?,
+                            spi02::Operation::Transfer(words) => bus.transfer_in_place(words)?,
+                        }
+                    }
+                    bus.flush()
+                }
+            }
+        )*
+    };
+}
+
+impl_spi!(u8, u16);
+
+impl<T: OutputPin> digital02::OutputPin for Reverse<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.inner.get_mut().set_low()
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.inner.get_mut().set_high()
+    }
+}
+
+impl<T: StatefulOutputPin> digital02::StatefulOutputPin for Reverse<T> {
+    #[inline]
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        self.inner.borrow_mut().is_set_high()
+    }
+
+    #[inline]
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        self.inner.borrow_mut().is_set_low()
+    }
+}
+
+impl<T: StatefulOutputPin> digital02::ToggleableOutputPin for Reverse<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.inner.get_mut().toggle()
+    }
+}
+
+impl<T: InputPin> digital02::InputPin for Reverse<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.inner.borrow_mut().is_high()
+    }
+
+    #[inline]
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.inner.borrow_mut().is_low()
+    }
+}
+
+macro_rules! impl_delay {
+    ($($uxx:ty),*) => {
+        $(
+            impl<T: DelayNs> DelayMs<$uxx> for Reverse<T> {
+                #[inline]
+                fn delay_ms(&mut self, ms: $uxx) {
+                    self.inner.get_mut().delay_ms(u32::from(ms));
+                }
+            }
+
+            impl<T: DelayNs> DelayUs<$uxx> for Reverse<T> {
+                #[inline]
+                fn delay_us(&mut self, us: $uxx) {
+                    self.inner.get_mut().delay_us(u32::from(us));
+                }
+            }
+        )*
+    };
+}
+
+impl_delay!(u8, u16, u32);
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::digital::ErrorType;
+    use std::{vec, vec::Vec};
+
+    /// 1.0 SPI bus logging the calls, and returning each written word plus one.
+    #[derive(Default)]
+    struct Bus {
+        log: Vec<(&'static str, Vec<u8>)>,
+    }
+
+    impl embedded_hal::spi::ErrorType for Bus {
+        type Error = Infallible;
+    }
+
+    impl SpiBus for Bus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.log.push(("read", words.to_vec()));
+            Ok(())
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.log.push(("write", words.to_vec()));
+            Ok(())
+        }
+
+        fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), Self::Error> {
+            unreachable!()
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.log.push(("transfer_in_place", words.to_vec()));
+            for word in words.iter_mut() {
+                *word += 1;
+            }
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.log.push(("flush", vec![]));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spi_operations_are_flushed() {
+        use spi02::{Operation, Transactional, Transfer, Write};
+
+        let mut spi = Bus::default().reverse();
+        let mut words = [1, 2];
+        assert_eq!(spi.transfer(&mut words).unwrap(), [2, 3]);
+        spi.write(&[4]).unwrap();
+        spi.exec(&mut [Operation::Write(&[5]), Operation::Transfer(&mut [6])])
+            .unwrap();
+        assert_eq!(
+            spi.into_inner().log,
+            [
+                ("transfer_in_place", vec![1, 2]),
+                ("flush", vec![]),
+                ("write", vec![4]),
+                ("flush", vec![]),
+                ("write", vec![5]),
+                ("transfer_in_place", vec![6]),
+                ("flush", vec![]),
+            ]
+        );
+    }
+
+    /// 1.0 I2C bus logging the addresses.
+    #[derive(Default)]
+    struct I2cBus(Vec<u16>);
+
+    impl i2c::ErrorType for I2cBus {
+        type Error = Infallible;
+    }
+
+    impl I2c<u8> for I2cBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.0.push(address.into());
+            Ok(())
+        }
+    }
+
+    impl I2c<u16> for I2cBus {
+        fn transaction(
+            &mut self,
+            address: u16,
+            _: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.0.push(address);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn i2c_address_modes() {
+        use i2c02::{Read, Write, WriteRead};
+
+        let mut i2c = I2cBus::default().reverse();
+        i2c.write(0x10u8, &[1]).unwrap();
+        i2c.read(0x3FFu16, &mut [0]).unwrap();
+        i2c.write_read(0x20u8, &[1], &mut [0]).unwrap();
+        assert_eq!(i2c.into_inner().0, [0x10, 0x3FF, 0x20]);
+    }
+
+    /// 1.0 delay logging the delays, in nanoseconds.
+    #[derive(Default)]
+    struct Delay(Vec<u32>);
+
+    impl DelayNs for Delay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.push(ns);
+        }
+    }
+
+    #[test]
+    fn delays_of_all_widths() {
+        let mut delay = Delay::default().reverse();
+        delay.delay_us(3u8);
+        delay.delay_us(500u16);
+        delay.delay_ms(2u32);
+        delay.delay_ms(1u8);
+        assert_eq!(delay.inner_mut().0, [3_000, 500_000, 2_000_000, 1_000_000]);
+    }
+
+    /// 1.0 pin.
+    #[derive(Default)]
+    struct Pin {
+        high: bool,
+    }
+
+    impl ErrorType for Pin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for Pin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.high = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    impl StatefulOutputPin for Pin {
+        fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.high)
+        }
+
+        fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.high)
+        }
+    }
+
+    impl InputPin for Pin {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.high)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.high)
+        }
+    }
+
+    #[test]
+    fn pins() {
+        use digital02::{InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin};
+
+        let mut pin = Pin::default().reverse();
+        pin.set_high().unwrap();
+        assert!(pin.is_set_high().unwrap());
+        pin.toggle().unwrap();
+        assert!(pin.is_low().unwrap());
+    }
+}
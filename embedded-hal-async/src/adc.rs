@@ -0,0 +1,43 @@
+//! Asynchronous analog-to-digital converter (ADC) traits.
+//!
+//! The [`AdcChannel`] trait is the `async` variant of the
+//! [blocking trait](embedded_hal::adc::AdcChannel). It lets tasks wait for a conversion
+//! to complete, for instance using the end-of-conversion interrupt.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal_async::adc::AdcChannel;
+//!
+//! /// Read a thermistor, returning the ratio of the full-scale input in per mille.
+//! async fn thermistor_ratio<A: AdcChannel>(adc: &mut A) -> Result<u32, A::Error> {
+//!     let sample = u64::from(adc.read().await?);
+//!     Ok((sample * 1_000 / u64::from(adc.max_count())) as u32)
+//! }
+//! ```
+pub use embedded_hal::adc::{Error, ErrorKind, ErrorType};
+
+/// Asynchronous single ADC channel, performing one-shot conversions.
+pub trait AdcChannel: ErrorType {
+    /// Get the maximum sample value.
+    ///
+    /// This value corresponds to the full-scale input, for instance `4095` for a 12-bit ADC.
+    fn max_count(&self) -> u32;
+
+    /// Perform a conversion, and return the sample, from 0 to [`max_count`](AdcChannel::max_count).
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) when the conversion completed.
+    async fn read(&mut self) -> Result<u32, Self::Error>;
+}
+
+impl<T: AdcChannel + ?Sized> AdcChannel for &mut T {
+    #[inline]
+    fn max_count(&self) -> u32 {
+        T::max_count(self)
+    }
+
+    #[inline]
+    async fn read(&mut self) -> Result<u32, Self::Error> {
+        T::read(self).await
+    }
+}
@@ -0,0 +1,96 @@
+//! Asynchronous input capture traits.
+//!
+//! The [`InputCapture`] trait is the `async` variant of the
+//! [blocking trait](embedded_hal::capture::InputCapture). Measurements wait for the
+//! capture interrupts instead of busy-polling the timer.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal_async::capture::InputCapture;
+//! use embedded_hal_async::digital::PinState;
+//!
+//! /// Wait for the next pulse of an infrared receiver, returning its width in microseconds.
+//! async fn next_mark_us<C: InputCapture>(ir: &mut C) -> Result<u32, C::Error> {
+//!     // The output of the receiver is active low.
+//!     Ok(ir.measure_pulse_width_ns(PinState::Low).await? / 1_000)
+//! }
+//! ```
+pub use embedded_hal::capture::{Error, ErrorKind, ErrorType};
+use embedded_hal::digital::PinState;
+
+/// Asynchronous input capture channel.
+pub trait InputCapture: ErrorType {
+    /// Measure the period of the input signal, between two consecutive rising edges, in nanoseconds.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once a complete period was measured.
+    async fn measure_period_ns(&mut self) -> Result<u32, Self::Error>;
+
+    /// Measure the width of a pulse of the input signal at `level`, in nanoseconds.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once a complete pulse was measured.
+    async fn measure_pulse_width_ns(&mut self, level: PinState) -> Result<u32, Self::Error>;
+}
+
+impl<T: InputCapture + ?Sized> InputCapture for &mut T {
+    #[inline]
+    async fn measure_period_ns(&mut self) -> Result<u32, Self::Error> {
+        T::measure_period_ns(self).await
+    }
+
+    #[inline]
+    async fn measure_pulse_width_ns(&mut self, level: PinState) -> Result<u32, Self::Error> {
+        T::measure_pulse_width_ns(self, level).await
+    }
+}
+
+/// Asynchronous PWM input capture channel.
+///
+/// This is the `async` variant of [`embedded_hal::capture::PwmInputCapture`].
+pub trait PwmInputCapture: ErrorType {
+    /// Get the maximum duty cycle value.
+    ///
+    /// This value corresponds to a 100% duty cycle.
+    fn max_duty_cycle(&self) -> u16;
+
+    /// Measure the period of the input signal, between two consecutive rising edges, in nanoseconds.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once a complete period was measured.
+    /// The period is never 0.
+    async fn measure_period_ns(&mut self) -> Result<u64, Self::Error>;
+
+    /// Measure the duty cycle of the input signal, scaled to
+    /// [`max_duty_cycle`](PwmInputCapture::max_duty_cycle).
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once a complete period was measured.
+    async fn measure_duty_cycle(&mut self) -> Result<u16, Self::Error>;
+
+    /// Measure the frequency of the input signal, in Hz, rounded to the nearest integer.
+    #[inline]
+    async fn measure_frequency_hz(&mut self) -> Result<u32, Self::Error> {
+        let period_ns = self.measure_period_ns().await?.max(1);
+        Ok(((1_000_000_000 + period_ns / 2) / period_ns) as u32)
+    }
+}
+
+impl<T: PwmInputCapture + ?Sized> PwmInputCapture for &mut T {
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        T::max_duty_cycle(self)
+    }
+
+    #[inline]
+    async fn measure_period_ns(&mut self) -> Result<u64, Self::Error> {
+        T::measure_period_ns(self).await
+    }
+
+    #[inline]
+    async fn measure_duty_cycle(&mut self) -> Result<u16, Self::Error> {
+        T::measure_duty_cycle(self).await
+    }
+
+    #[inline]
+    async fn measure_frequency_hz(&mut self) -> Result<u32, Self::Error> {
+        T::measure_frequency_hz(self).await
+    }
+}
@@ -1,5 +1,12 @@
 //! Delays.
 
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+pub use embedded_hal::delay::Counter;
+
 /// Delay with up to nanosecond precision.
 pub trait DelayNs {
     /// Pauses execution for at minimum `ns` nanoseconds. Pause can be longer
@@ -47,3 +54,85 @@ where
         T::delay_ms(self, ms).await;
     }
 }
+
+/// Error returned by [`TimeoutFuture`] when its deadline passes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TimeoutError;
+
+impl fmt::Display for TimeoutError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The deadline passed before the operation completed")
+    }
+}
+
+impl core::error::Error for TimeoutError {}
+
+/// Future bounding the duration of another future with a [`Counter`].
+///
+/// The counter is started when the `TimeoutFuture` is created. Each time it is polled, the inner
+/// future is polled first: if it is not ready and the deadline passed, `Err(TimeoutError)` is
+/// returned, and the inner future is not polled again.
+///
+/// A [`Counter`] cannot wake a task: while the inner future is pending, the `TimeoutFuture` wakes its
+/// task immediately, so that the deadline is checked again. This keeps the executor busy: prefer
+/// racing against [`DelayNs`] if the timer supports waking.
+///
+/// # Example
+///
+/// ```
+/// use core::pin::pin;
+/// use embedded_hal_async::delay::{Counter, TimeoutError, TimeoutFuture};
+/// use embedded_hal_async::digital::Wait;
+///
+/// async fn wait_for_irq<P: Wait, C: Counter>(irq: &mut P, counter: C) -> Result<(), TimeoutError> {
+///     // Wait at most 10 ms for the interrupt line to go low.
+///     let _ = TimeoutFuture::new(counter, 10_000_000, pin!(irq.wait_for_low())).await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TimeoutFuture<C, F> {
+    counter: C,
+    deadline_ns: u64,
+    future: F,
+}
+
+impl<C: Counter, F> TimeoutFuture<C, F> {
+    /// Create a new `TimeoutFuture`, starting `counter`.
+    ///
+    /// The future fails with [`TimeoutError`] if `future` is not ready after `deadline_ns` nanoseconds.
+    #[inline]
+    pub fn new(mut counter: C, deadline_ns: u64, future: F) -> Self {
+        counter.start();
+        Self {
+            counter,
+            deadline_ns,
+            future,
+        }
+    }
+
+    /// Consumes the `TimeoutFuture`, returning the counter and the inner future.
+    #[inline]
+    pub fn into_inner(self) -> (C, F) {
+        (self.counter, self.future)
+    }
+}
+
+impl<C: Counter, F: Future + Unpin> Future for TimeoutFuture<C, F> {
+    type Output = Result<F::Output, TimeoutError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the fields are not structurally pinned: `future` is `Unpin`, and `counter`
+        // is never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Poll::Ready(output) = Pin::new(&mut this.future).poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        if this.counter.has_exceeded(this.deadline_ns) {
+            return Poll::Ready(Err(TimeoutError));
+        }
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -7,6 +7,9 @@
 //!
 //! The [`Wait`] trait allows asynchronously waiting for a change in pin level.
 //!
+//! The lower-level [`PinChangeWaker`] trait only exposes the interrupt of a pin, for executors and
+//! middleware building their own event systems. [`PinChangeWait`] implements [`Wait`] on top of it.
+//!
 //! # Example
 //!
 //! ```rust
@@ -27,8 +30,11 @@
 //!
 //! If the digital I/O is implemented using memory mapped I/O and acts immediately, then the async traits
 //! (except for [`Wait`]) can be implemented by calling the blocking traits and wrapping the result in
-//! [`Poll::Ready`](core::task::Poll::Ready).
-pub use embedded_hal::digital::{Error, ErrorKind, ErrorType, PinState};
+//! [`Poll::Ready`].
+use core::future::poll_fn;
+use core::task::{Poll, Waker};
+
+pub use embedded_hal::digital::{Error, ErrorKind, ErrorType, PinState, Trigger};
 
 /// Asynchronous single digital push-pull output pin.
 pub trait OutputPin: ErrorType {
@@ -209,3 +215,143 @@ impl<T: Wait + ?Sized> Wait for &mut T {
         T::wait_for_any_edge(self).await
     }
 }
+
+/// Interrupt of a pin, waking a registered [`Waker`] when its [`Trigger`] condition occurs.
+///
+/// This is the primitive below [`Wait`]: implementations only configure the interrupt and wake the
+/// waker from the interrupt handler, leaving the futures to the caller. [`PinChangeWait`] builds a
+/// [`Wait`] implementation from it.
+///
+/// # For HAL authors
+///
+/// When the trigger condition occurs, the interrupt handler records it, wakes the registered waker,
+/// if any, and clears it. Level triggers keep firing while the level holds: implementations should
+/// mask the interrupt once the condition was recorded, until [`set_trigger`](Self::set_trigger) or
+/// [`register_waker`](Self::register_waker) is called again.
+pub trait PinChangeWaker: ErrorType {
+    /// Registers `waker`, to be woken the next time the trigger condition occurs.
+    ///
+    /// This replaces the previously registered waker, and takes `&self` so that it can be called
+    /// while other references to the pin exist, for instance from an executor. If the condition
+    /// already occurred since the last [`take_triggered`](Self::take_triggered), `waker` is woken
+    /// immediately.
+    fn register_waker(&self, waker: &Waker) -> Result<(), Self::Error>;
+
+    /// Sets the condition triggering the interrupt, and enables it.
+    ///
+    /// This clears any condition recorded for the previous trigger. If the pin does not support
+    /// `trigger`, an error is returned.
+    fn set_trigger(&mut self, trigger: Trigger) -> Result<(), Self::Error>;
+
+    /// Returns whether the trigger condition occurred, clearing it.
+    ///
+    /// Wakers can be woken spuriously, for instance when several futures share a task: this tells
+    /// whether the pin actually triggered.
+    fn take_triggered(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<T: PinChangeWaker + ?Sized> PinChangeWaker for &mut T {
+    #[inline]
+    fn register_waker(&self, waker: &Waker) -> Result<(), Self::Error> {
+        T::register_waker(self, waker)
+    }
+
+    #[inline]
+    fn set_trigger(&mut self, trigger: Trigger) -> Result<(), Self::Error> {
+        T::set_trigger(self, trigger)
+    }
+
+    #[inline]
+    fn take_triggered(&mut self) -> Result<bool, Self::Error> {
+        T::take_triggered(self)
+    }
+}
+
+/// [`Wait`] implementation for a [`PinChangeWaker`] input pin.
+///
+/// Each wait sets the [`Trigger`] matching the awaited level or edge, then polls
+/// [`take_triggered`](PinChangeWaker::take_triggered). The levels are also read with the
+/// [`InputPin`](embedded_hal::digital::InputPin), so that waiting for the current level returns immediately.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::InputPin;
+/// use embedded_hal_async::digital::{PinChangeWait, PinChangeWaker, Wait};
+///
+/// /// Wait for the next press of an active-low button.
+/// async fn wait_for_press<P: PinChangeWaker + InputPin>(button: P) -> Result<P, P::Error> {
+///     let mut button = PinChangeWait::new(button);
+///     button.wait_for_falling_edge().await?;
+///     Ok(button.into_inner())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PinChangeWait<P> {
+    pin: P,
+}
+
+impl<P: PinChangeWaker + embedded_hal::digital::InputPin> PinChangeWait<P> {
+    /// Create a new [`PinChangeWait`], waiting on `pin`.
+    #[inline]
+    pub fn new(pin: P) -> Self {
+        Self { pin }
+    }
+
+    /// Consumes the [`PinChangeWait`], returning the pin.
+    #[inline]
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+
+    /// Wait until `trigger` occurs, or until the pin reads as `level` if it is set.
+    async fn wait_for(&mut self, trigger: Trigger, level: Option<bool>) -> Result<(), P::Error> {
+        self.pin.set_trigger(trigger)?;
+        poll_fn(|cx| {
+            // Register before checking, so that a trigger occurring in between is not missed.
+            self.pin.register_waker(cx.waker())?;
+            let done = match (self.pin.take_triggered()?, level) {
+                (true, _) => true,
+                (false, Some(high)) => self.pin.is_high()? == high,
+                (false, None) => false,
+            };
+            if done {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl<P: ErrorType> ErrorType for PinChangeWait<P> {
+    type Error = P::Error;
+}
+
+impl<P: PinChangeWaker + embedded_hal::digital::InputPin> Wait for PinChangeWait<P> {
+    #[inline]
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Trigger::LevelHigh, Some(true)).await
+    }
+
+    #[inline]
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Trigger::LevelLow, Some(false)).await
+    }
+
+    #[inline]
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Trigger::RisingEdge, None).await
+    }
+
+    #[inline]
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Trigger::FallingEdge, None).await
+    }
+
+    #[inline]
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Trigger::BothEdges, None).await
+    }
+}
@@ -17,8 +17,8 @@
 //! `SevenBitAddress` has been set as default mode and thus can be omitted if desired.
 
 pub use embedded_hal::i2c::{
-    AddressMode, Error, ErrorKind, ErrorType, NoAcknowledgeSource, Operation, SevenBitAddress,
-    TenBitAddress,
+    AddressMode, Direction, Error, ErrorKind, ErrorType, I2cBusSpeed, NoAcknowledgeSource,
+    Operation, SevenBitAddress, TargetEvent, TenBitAddress, GENERAL_CALL_ADDRESS,
 };
 
 /// Async I2c.
@@ -155,3 +155,90 @@ impl<A: AddressMode, T: I2c<A> + ?Sized> I2c<A> for &mut T {
         T::transaction(self, address, operations).await
     }
 }
+
+/// Async general call writes.
+///
+/// This is the async version of [`embedded_hal::i2c::I2cGeneralCall`].
+pub trait I2cGeneralCall: I2c {
+    /// Writes `data` to the general call address, in a single transaction.
+    async fn write_general_call(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<T: I2cGeneralCall + ?Sized> I2cGeneralCall for &mut T {
+    #[inline]
+    async fn write_general_call(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        T::write_general_call(self, data).await
+    }
+}
+
+/// Async scatter-gather transfers.
+///
+/// This is the async version of [`embedded_hal::i2c::I2cVectored`].
+pub trait I2cVectored<A: AddressMode = SevenBitAddress>: I2c<A> {
+    /// Writes all the `bufs` to the target with `address`, in order, in a single transaction.
+    async fn write_vectored(&mut self, address: A, bufs: &[&[u8]]) -> Result<(), Self::Error>;
+
+    /// Reads enough bytes from the target with `address` to fill all the `bufs`, in order, in a
+    /// single transaction.
+    async fn read_vectored(
+        &mut self,
+        address: A,
+        bufs: &mut [&mut [u8]],
+    ) -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: I2cVectored<A> + ?Sized> I2cVectored<A> for &mut T {
+    #[inline]
+    async fn write_vectored(&mut self, address: A, bufs: &[&[u8]]) -> Result<(), Self::Error> {
+        T::write_vectored(self, address, bufs).await
+    }
+
+    #[inline]
+    async fn read_vectored(
+        &mut self,
+        address: A,
+        bufs: &mut [&mut [u8]],
+    ) -> Result<(), Self::Error> {
+        T::read_vectored(self, address, bufs).await
+    }
+}
+
+/// Async I2C target (slave).
+///
+/// This is the async version of [`embedded_hal::i2c::I2cTarget`]: the clock stretching and
+/// early stop behavior are the same.
+pub trait I2cTarget<A: AddressMode = SevenBitAddress>: ErrorType {
+    /// Wait for the next event on the bus.
+    ///
+    /// Only start and repeated start conditions followed by one of the addresses of
+    /// the target are reported. Transactions addressed to other targets are ignored.
+    async fn listen(&mut self) -> Result<TargetEvent<A>, Self::Error>;
+
+    /// Send `write` to the controller, after a [`Direction::Read`] event.
+    ///
+    /// Returns the number of bytes read by the controller, which can be smaller or larger than
+    /// the length of `write`.
+    async fn respond(&mut self, write: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Receive bytes from the controller into `read`, after a [`Direction::Write`] event.
+    ///
+    /// Returns the number of bytes received, which is at most the length of `read`.
+    async fn accept(&mut self, read: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+impl<A: AddressMode, T: I2cTarget<A> + ?Sized> I2cTarget<A> for &mut T {
+    #[inline]
+    async fn listen(&mut self) -> Result<TargetEvent<A>, Self::Error> {
+        T::listen(self).await
+    }
+
+    #[inline]
+    async fn respond(&mut self, write: &[u8]) -> Result<usize, Self::Error> {
+        T::respond(self, write).await
+    }
+
+    #[inline]
+    async fn accept(&mut self, read: &mut [u8]) -> Result<usize, Self::Error> {
+        T::accept(self, read).await
+    }
+}
@@ -0,0 +1,58 @@
+//! Asynchronous I2S traits.
+//!
+//! The [`I2sBus`] trait is the `async` variant of the [blocking trait](embedded_hal::i2s::I2sBus),
+//! with the same sample format. It suits DMA-backed audio streaming: the CPU is free while the
+//! samples are transferred.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal_async::i2s::I2sBus;
+//!
+//! /// Forward the audio of an I2S microphone to an I2S amplifier, with the same configuration.
+//! async fn passthrough<M: I2sBus, A: I2sBus>(mic: &mut M, amp: &mut A) -> Result<(), ()> {
+//!     let mut samples = [0; 256];
+//!     loop {
+//!         let len = mic.receive(&mut samples).await.map_err(drop)?;
+//!         amp.transmit(&samples[..len]).await.map_err(drop)?;
+//!     }
+//! }
+//! ```
+pub use embedded_hal::i2s::{BitDepth, ChannelMode, Error, ErrorKind, ErrorType, I2sConfig};
+
+/// Asynchronous I2S bus, in controller mode.
+pub trait I2sBus: ErrorType {
+    /// Apply `config` to the bus.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once pending transmissions completed and the
+    /// configuration was applied.
+    async fn set_config(&mut self, config: &I2sConfig) -> Result<(), Self::Error>;
+
+    /// Transmit `samples`.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once all the samples were handed to the hardware.
+    async fn transmit(&mut self, samples: &[i32]) -> Result<(), Self::Error>;
+
+    /// Receive samples into `samples`.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once at least one frame was received, with the
+    /// number of samples received, which is a whole number of frames.
+    async fn receive(&mut self, samples: &mut [i32]) -> Result<usize, Self::Error>;
+}
+
+impl<T: I2sBus + ?Sized> I2sBus for &mut T {
+    #[inline]
+    async fn set_config(&mut self, config: &I2sConfig) -> Result<(), Self::Error> {
+        T::set_config(self, config).await
+    }
+
+    #[inline]
+    async fn transmit(&mut self, samples: &[i32]) -> Result<(), Self::Error> {
+        T::transmit(self, samples).await
+    }
+
+    #[inline]
+    async fn receive(&mut self, samples: &mut [i32]) -> Result<usize, Self::Error> {
+        T::receive(self, samples).await
+    }
+}
@@ -3,7 +3,13 @@
 #![no_std]
 #![allow(async_fn_in_trait)]
 
+pub mod adc;
+pub mod capture;
 pub mod delay;
 pub mod digital;
 pub mod i2c;
+pub mod i2s;
+pub mod rng;
 pub mod spi;
+pub mod timer;
+pub mod uart;
@@ -0,0 +1,35 @@
+//! Asynchronous random number generator traits.
+//!
+//! The [`Rng`] trait is the `async` variant of the [blocking trait](embedded_hal::rng::Rng). It
+//! lets tasks wait for entropy to become available, for instance using the data-ready interrupt
+//! of the generator.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal_async::rng::Rng;
+//!
+//! /// Generate a random TCP initial sequence number.
+//! async fn initial_sequence_number<R: Rng>(rng: &mut R) -> Result<u32, R::Error> {
+//!     let mut isn = [0; 4];
+//!     rng.fill_bytes(&mut isn).await?;
+//!     Ok(u32::from_le_bytes(isn))
+//! }
+//! ```
+pub use embedded_hal::rng::{Error, ErrorKind, ErrorType};
+
+/// Asynchronous random number generator.
+pub trait Rng: ErrorType {
+    /// Fill `buf` with random bytes.
+    ///
+    /// This waits for entropy to be available instead of returning an error of kind
+    /// [`ErrorKind::NotReady`]. On error, the content of `buf` is unspecified and must not be used.
+    async fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+impl<T: Rng + ?Sized> Rng for &mut T {
+    #[inline]
+    async fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        T::fill_bytes(self, buf).await
+    }
+}
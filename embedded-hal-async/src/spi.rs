@@ -2,6 +2,7 @@
 
 pub use embedded_hal::spi::{
     Error, ErrorKind, ErrorType, Mode, Operation, Phase, Polarity, MODE_0, MODE_1, MODE_2, MODE_3,
+    WRITE_ITER_CHUNK_SIZE,
 };
 
 /// SPI device trait.
@@ -104,6 +105,121 @@ impl<Word: Copy + 'static, T: SpiDevice<Word> + ?Sized> SpiDevice<Word> for &mut
     }
 }
 
+/// An open SPI transaction, with CS asserted for as long as it is alive.
+///
+/// Obtained from [`SpiTransactionalDevice::begin_transaction`]. Every operation is performed
+/// with CS asserted, and the bus stays locked until the transaction ends. This allows
+/// interleaving other async work (computing the next buffer, waiting on a pin, ...) between
+/// operations, which the [`SpiDevice::transaction`] operation list cannot express.
+///
+/// The transaction ends either with [`commit`](SpiTransaction::commit), which flushes the bus
+/// and deasserts CS, or by being dropped. Dropping deasserts CS **without** flushing the bus,
+/// so a transaction dropped in the middle of an operation (e.g. because its future was
+/// cancelled) is aborted: the device may have received an incomplete frame.
+pub trait SpiTransaction<Word: Copy + 'static = u8>: ErrorType {
+    /// Read `buf` from the device, with CS asserted.
+    ///
+    /// See also: [`SpiBus::read`]
+    async fn read(&mut self, buf: &mut [Word]) -> Result<(), Self::Error>;
+
+    /// Write `buf` to the device, with CS asserted.
+    ///
+    /// See also: [`SpiBus::write`]
+    async fn write(&mut self, buf: &[Word]) -> Result<(), Self::Error>;
+
+    /// Transfer `write` to the device and read the response into `read`, with CS asserted.
+    ///
+    /// See also: [`SpiBus::transfer`]
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error>;
+
+    /// Transfer `buf` in place, with CS asserted.
+    ///
+    /// See also: [`SpiBus::transfer_in_place`]
+    async fn transfer_in_place(&mut self, buf: &mut [Word]) -> Result<(), Self::Error>;
+
+    /// Flush the bus, then delay for at least `ns` nanoseconds, with CS asserted.
+    ///
+    /// This is the equivalent of [`Operation::DelayNs`].
+    async fn delay_ns(&mut self, ns: u32) -> Result<(), Self::Error>;
+
+    /// End the transaction.
+    ///
+    /// - [Flushes](SpiBus::flush) the bus.
+    /// - Deasserts the CS pin.
+    /// - Unlocks the bus.
+    ///
+    /// If an error occurs while deasserting CS, a flush error should take priority as the
+    /// return value.
+    async fn commit(self) -> Result<(), Self::Error>;
+
+    /// Perform all the `operations` within this transaction, in order.
+    ///
+    /// Stops at the first error. The transaction is not ended, call
+    /// [`commit`](SpiTransaction::commit) or drop it afterwards.
+    async fn run(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        for op in operations {
+            match op {
+                Operation::Read(buf) => self.read(buf).await?,
+                Operation::Write(buf) => self.write(buf).await?,
+                Operation::Transfer(read, write) => self.transfer(read, write).await?,
+                Operation::TransferInPlace(buf) => self.transfer_in_place(buf).await?,
+                Operation::DelayNs(ns) => self.delay_ns(*ns).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// SPI device that can open a [`SpiTransaction`] with an explicit CS lifetime.
+///
+/// [`SpiDevice::transaction`] takes the whole transaction upfront as a list of operations.
+/// This trait instead hands out a transaction object: CS is asserted by
+/// [`begin_transaction`](SpiTransactionalDevice::begin_transaction) and deasserted by
+/// [`SpiTransaction::commit`], or when the transaction is dropped.
+///
+/// ```
+/// use embedded_hal_async::spi::{SpiTransaction, SpiTransactionalDevice};
+///
+/// async fn write_frame<D: SpiTransactionalDevice>(
+///     dev: &mut D,
+///     header: &[u8],
+///     payload: &[u8],
+/// ) -> Result<(), D::Error> {
+///     let mut tx = dev.begin_transaction().await?;
+///     tx.write(header).await?;
+///     tx.write(payload).await?;
+///     tx.commit().await
+/// }
+/// ```
+///
+/// For implementations, `transaction(ops)` must behave the same as beginning a transaction,
+/// [running](SpiTransaction::run) `ops` in it and committing it.
+pub trait SpiTransactionalDevice<Word: Copy + 'static = u8>: SpiDevice<Word> {
+    /// Transaction type returned by [`begin_transaction`](SpiTransactionalDevice::begin_transaction).
+    type Transaction<'a>: SpiTransaction<Word, Error = Self::Error>
+    where
+        Self: 'a;
+
+    /// Lock the bus and assert the CS pin, returning the open transaction.
+    ///
+    /// If an error occurs while asserting CS, the bus must be unlocked again.
+    async fn begin_transaction(&mut self) -> Result<Self::Transaction<'_>, Self::Error>;
+}
+
+impl<Word: Copy + 'static, T: SpiTransactionalDevice<Word> + ?Sized> SpiTransactionalDevice<Word>
+    for &mut T
+{
+    type Transaction<'a>
+        = T::Transaction<'a>
+    where
+        Self: 'a;
+
+    #[inline]
+    async fn begin_transaction(&mut self) -> Result<Self::Transaction<'_>, Self::Error> {
+        T::begin_transaction(self).await
+    }
+}
+
 /// SPI bus.
 ///
 /// `SpiBus` represents **exclusive ownership** over the whole SPI bus, with SCK, MOSI and MISO pins.
@@ -178,3 +294,91 @@ impl<T: SpiBus<Word> + ?Sized, Word: 'static + Copy> SpiBus<Word> for &mut T {
         T::flush(self).await
     }
 }
+
+/// Extension methods for [`SpiBus`], streaming words that are not in a contiguous buffer.
+///
+/// This is the `async` version of [`embedded_hal::spi::SpiBusExt`], implemented for every
+/// [`SpiBus<u8>`](SpiBus): the words are sent in chunks, back to back, and each method calls
+/// [`flush`](SpiBus::flush) once, after the last chunk.
+pub trait SpiBusExt: SpiBus<u8> {
+    /// Writes the `words` from an iterator, ignoring all the incoming words.
+    ///
+    /// The words are copied to an internal buffer of [`WRITE_ITER_CHUNK_SIZE`] words, and each
+    /// chunk is sent with [`SpiBus::write`].
+    async fn write_iter<W>(&mut self, words: W) -> Result<(), Self::Error>
+    where
+        W: IntoIterator<Item = u8>,
+    {
+        let mut words = words.into_iter();
+        let mut chunk = [0; WRITE_ITER_CHUNK_SIZE];
+        loop {
+            let mut len = 0;
+            for (word, new) in chunk.iter_mut().zip(&mut words) {
+                *word = new;
+                len += 1;
+            }
+            if len == 0 {
+                break;
+            }
+            self.write(&chunk[..len]).await?;
+        }
+        self.flush().await
+    }
+
+    /// Writes `len` words, the word at index `i` being `word(i)`, ignoring all the incoming words.
+    ///
+    /// This is [`write_iter`](SpiBusExt::write_iter) of `(0..len).map(word)`.
+    #[inline]
+    async fn write_from_fn<F>(&mut self, len: usize, word: F) -> Result<(), Self::Error>
+    where
+        F: FnMut(usize) -> u8,
+    {
+        self.write_iter((0..len).map(word)).await
+    }
+
+    /// Like [`SpiBus::transfer`], split into transfers of at most `chunk_len` words.
+    ///
+    /// See [`embedded_hal::spi::SpiBusExt::transfer_chunked`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is zero.
+    async fn transfer_chunked(
+        &mut self,
+        read: &mut [u8],
+        write: &[u8],
+        chunk_len: usize,
+    ) -> Result<(), Self::Error> {
+        assert!(chunk_len > 0, "chunk_len must not be zero");
+        let len = read.len().max(write.len());
+        for start in (0..len).step_by(chunk_len) {
+            let range = |len: usize| start.min(len)..start.saturating_add(chunk_len).min(len);
+            let (read_range, write_range) = (range(read.len()), range(write.len()));
+            self.transfer(&mut read[read_range], &write[write_range])
+                .await?;
+        }
+        self.flush().await
+    }
+}
+
+impl<T: SpiBus<u8> + ?Sized> SpiBusExt for T {}
+
+/// Async SPI peripheral (slave).
+///
+/// This is the async version of [`embedded_hal::spi::SpiPeripheral`]: [`transfer`](SpiPeripheral::transfer)
+/// waits for the controller to run a transfer, and completes once the controller deasserts CS.
+pub trait SpiPeripheral<Word: Copy + 'static = u8>: ErrorType {
+    /// Wait for the controller to run a transfer, sending `write` and receiving into `read`.
+    ///
+    /// Returns the number of words actually clocked by the controller, which can be smaller or
+    /// larger than the lengths of the buffers. See [`embedded_hal::spi::SpiPeripheral::transfer`]
+    /// for details.
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<usize, Self::Error>;
+}
+
+impl<T: SpiPeripheral<Word> + ?Sized, Word: Copy + 'static> SpiPeripheral<Word> for &mut T {
+    #[inline]
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<usize, Self::Error> {
+        T::transfer(self, read, write).await
+    }
+}
@@ -0,0 +1,90 @@
+//! Asynchronous timer traits.
+//!
+//! The [`CountDown`] and [`Periodic`] traits are the `async` variants of the
+//! [blocking traits](embedded_hal::timer), with the same semantics. Waiting for a timer waits for its
+//! interrupt instead of busy-polling it, and [`Periodic`] is a ticker: its periods do not drift,
+//! however long the task takes between two waits.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal_async::timer::{CountDown, Periodic};
+//!
+//! /// Blink `toggle` at 1 Hz, forever.
+//! async fn blink<T: Periodic>(timer: &mut T, mut toggle: impl FnMut()) -> Result<(), T::Error> {
+//!     timer.start_periodic(500_000_000)?;
+//!     loop {
+//!         timer.wait().await?;
+//!         toggle();
+//!     }
+//! }
+//! ```
+pub use embedded_hal::timer::{Error, ErrorKind, ErrorType};
+
+/// Asynchronous timer expiring once, after a duration.
+///
+/// This is the `async` variant of [`embedded_hal::timer::CountDown`].
+pub trait CountDown: ErrorType {
+    /// Start the timer, expiring after `duration_ns` nanoseconds.
+    ///
+    /// Returns an error of kind [`ErrorKind::UnsupportedDuration`] if the duration is too long for the
+    /// timer, in which case the timer is not running.
+    fn start(&mut self, duration_ns: u64) -> Result<(), Self::Error>;
+
+    /// Returns `true` if the timer expired.
+    ///
+    /// Returns an error of kind [`ErrorKind::NotRunning`] if the timer is not running.
+    fn is_expired(&mut self) -> Result<bool, Self::Error>;
+
+    /// Wait until the timer expired.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) immediately if the timer already expired. Returns
+    /// an error of kind [`ErrorKind::NotRunning`] if the timer is not running, instead of waiting forever.
+    async fn wait(&mut self) -> Result<(), Self::Error>;
+
+    /// Stop the timer.
+    ///
+    /// Returns an error of kind [`ErrorKind::NotRunning`] if the timer is not running.
+    fn cancel(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: CountDown + ?Sized> CountDown for &mut T {
+    #[inline]
+    fn start(&mut self, duration_ns: u64) -> Result<(), Self::Error> {
+        T::start(self, duration_ns)
+    }
+
+    #[inline]
+    fn is_expired(&mut self) -> Result<bool, Self::Error> {
+        T::is_expired(self)
+    }
+
+    #[inline]
+    async fn wait(&mut self) -> Result<(), Self::Error> {
+        T::wait(self).await
+    }
+
+    #[inline]
+    fn cancel(&mut self) -> Result<(), Self::Error> {
+        T::cancel(self)
+    }
+}
+
+/// Asynchronous timer expiring periodically.
+///
+/// This is the `async` variant of [`embedded_hal::timer::Periodic`]: each [`wait`](CountDown::wait)
+/// returns at the end of the next period which was not observed yet.
+pub trait Periodic: CountDown {
+    /// Start the timer in periodic mode, with a period of `period_ns` nanoseconds.
+    ///
+    /// Returns an error of kind [`ErrorKind::UnsupportedDuration`] if the period is 0 or too long for
+    /// the timer, in which case the timer is not running.
+    fn start_periodic(&mut self, period_ns: u64) -> Result<(), Self::Error>;
+}
+
+impl<T: Periodic + ?Sized> Periodic for &mut T {
+    #[inline]
+    fn start_periodic(&mut self, period_ns: u64) -> Result<(), Self::Error> {
+        T::start_periodic(self, period_ns)
+    }
+}
@@ -0,0 +1,105 @@
+//! Asynchronous UART traits.
+//!
+//! [`UartRx`] and [`UartTx`] transfer data over a UART without blocking the executor. They suit
+//! DMA-backed UARTs, whose transfer-complete and idle-line interrupts wake the pending task.
+//! The two halves are separate traits, so that HALs can split a UART into a receiver and a
+//! transmitter owned by different tasks.
+//!
+//! The link itself is configured with the [blocking traits](embedded_hal::uart), such as
+//! [`SerialConfig`](embedded_hal::uart::SerialConfig).
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal_async::uart::{UartRx, UartTx};
+//!
+//! /// Echo every received line back to the sender.
+//! async fn echo<U: UartRx + UartTx>(uart: &mut U) -> Result<(), U::Error> {
+//!     let mut line = [0; 82];
+//!     loop {
+//!         let len = uart.read(&mut line).await?;
+//!         uart.write_all(&line[..len]).await?;
+//!         uart.flush().await?;
+//!     }
+//! }
+//! ```
+pub use embedded_hal::uart::{Error, ErrorKind, ErrorType};
+
+/// Asynchronous UART receiver.
+pub trait UartRx: ErrorType {
+    /// Read bytes into `buf`.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once at least one byte was received, with
+    /// the number of bytes read. Implementations should not wait for `buf` to be full: DMA-backed
+    /// UARTs typically return when the RX line goes idle. If `buf` is empty, this returns `Ok(0)`
+    /// immediately.
+    ///
+    /// Bytes received while no read is pending may be buffered or dropped, depending on the
+    /// implementation. Dropped bytes should be reported as an [`ErrorKind::Overrun`] error.
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Read exactly `buf.len()` bytes into `buf`.
+    ///
+    /// The default implementation calls [`read`](UartRx::read) until `buf` is full. If an error
+    /// occurs, the contents of `buf` are unspecified.
+    async fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<(), Self::Error> {
+        while !buf.is_empty() {
+            let len = self.read(buf).await?;
+            buf = &mut buf[len..];
+        }
+        Ok(())
+    }
+}
+
+impl<T: UartRx + ?Sized> UartRx for &mut T {
+    #[inline]
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        T::read(self, buf).await
+    }
+
+    #[inline]
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        T::read_exact(self, buf).await
+    }
+}
+
+/// Asynchronous UART transmitter.
+pub trait UartTx: ErrorType {
+    /// Write bytes from `buf`.
+    ///
+    /// This returns [`Ready`](core::task::Poll::Ready) once at least one byte was handed to the
+    /// hardware, with the number of bytes written. The bytes may still be in flight: see
+    /// [`flush`](UartTx::flush). If `buf` is empty, this returns `Ok(0)` immediately.
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Wait until all the written bytes have been transmitted, including their stop bits.
+    async fn flush(&mut self) -> Result<(), Self::Error>;
+
+    /// Write all the bytes of `buf`.
+    ///
+    /// The default implementation calls [`write`](UartTx::write) until all of `buf` was written.
+    async fn write_all(&mut self, mut buf: &[u8]) -> Result<(), Self::Error> {
+        while !buf.is_empty() {
+            let len = self.write(buf).await?;
+            buf = &buf[len..];
+        }
+        Ok(())
+    }
+}
+
+impl<T: UartTx + ?Sized> UartTx for &mut T {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        T::write(self, buf).await
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        T::flush(self).await
+    }
+
+    #[inline]
+    async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        T::write_all(self, buf).await
+    }
+}
@@ -0,0 +1,4 @@
+//! Wrappers around `Adc` implementations.
+
+mod oversampler;
+pub use oversampler::*;
@@ -0,0 +1,252 @@
+use embedded_hal::adc::{Adc, ErrorType};
+use embedded_hal::delay::DelayNs;
+
+/// Number of channels converted together by [`AdcOversampler::read_channels`].
+const CHUNK: usize = 8;
+
+/// [`Adc`] wrapper oversampling each conversion in software.
+///
+/// Each read performs `N` conversions of the channel, `sample_delay_ns` nanoseconds apart, sums
+/// them, and right-shifts the sum by `SHIFT` bits. Averaging reduces the noise, and oversampling by
+/// `4^n` then decimating by `n` bits adds `n` bits of effective resolution, provided the input has
+/// enough noise to dither the samples. `N` and `SHIFT` are constants, so the loop and the shift
+/// compile down to what a hand-written version would do.
+///
+/// [`max_count`](Adc::max_count) is scaled accordingly, so drivers converting samples to physical
+/// units keep working. Sums saturate at `u32::MAX`, which only matters if `N` times the maximum
+/// sample of the inner ADC does not fit in a `u32`.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::adc::Adc;
+/// use embedded_hal_bus::adc::AdcOversampler;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::{adc::ErrorType, delay::DelayNs};
+/// # struct Adc1;
+/// # impl ErrorType for Adc1 { type Error = Infallible; }
+/// # impl Adc for Adc1 {
+/// #     fn max_count(&self) -> u32 { 4095 }
+/// #     fn read_channel(&mut self, _: u8) -> Result<u32, Infallible> { Ok(2048) }
+/// #     fn read_channels(&mut self, _: &[u8], r: &mut [u32]) -> Result<(), Infallible> { Ok(r.fill(2048)) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _ns: u32) {} }
+/// # let (adc, delay) = (Adc1, Delay);
+///
+/// // 16 samples decimated by 2 bits: a 12-bit ADC gives 14-bit samples.
+/// let mut adc: AdcOversampler<_, _, 16, 2> = AdcOversampler::new(adc, delay, 10_000);
+/// assert_eq!(adc.max_count(), 4095 * 16 >> 2);
+/// let sample = adc.read_channel(3)?;
+/// # assert_eq!(sample, 8192);
+/// # Ok::<(), Infallible>(())
+/// ```
+pub struct AdcOversampler<A, D, const N: usize, const SHIFT: u8> {
+    adc: A,
+    delay: D,
+    sample_delay_ns: u32,
+}
+
+impl<A, D, const N: usize, const SHIFT: u8> AdcOversampler<A, D, N, SHIFT> {
+    /// Create a new [`AdcOversampler`], waiting `sample_delay_ns` between two conversions.
+    ///
+    /// Fails to compile if `N` is zero, or `SHIFT` is not in `0..32`.
+    #[inline]
+    pub fn new(adc: A, delay: D, sample_delay_ns: u32) -> Self {
+        const { assert!(N > 0 && SHIFT < 32) };
+        Self {
+            adc,
+            delay,
+            sample_delay_ns,
+        }
+    }
+
+    /// Release the inner ADC and delay.
+    #[inline]
+    pub fn release(self) -> (A, D) {
+        (self.adc, self.delay)
+    }
+}
+
+impl<A: Adc, D: DelayNs, const N: usize, const SHIFT: u8> AdcOversampler<A, D, N, SHIFT> {
+    /// Run `convert` `N` times, waiting between the conversions.
+    fn oversample(
+        &mut self,
+        mut convert: impl FnMut(&mut A) -> Result<(), A::Error>,
+    ) -> Result<(), A::Error> {
+        for i in 0..N {
+            if i > 0 && self.sample_delay_ns > 0 {
+                self.delay.delay_ns(self.sample_delay_ns);
+            }
+            convert(&mut self.adc)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: ErrorType, D, const N: usize, const SHIFT: u8> ErrorType
+    for AdcOversampler<A, D, N, SHIFT>
+{
+    type Error = A::Error;
+}
+
+impl<A: Adc, D: DelayNs, const N: usize, const SHIFT: u8> Adc for AdcOversampler<A, D, N, SHIFT> {
+    #[inline]
+    fn max_count(&self) -> u32 {
+        let max = u64::from(self.adc.max_count()).saturating_mul(N as u64) >> SHIFT;
+        max.min(u64::from(u32::MAX)) as u32
+    }
+
+    fn read_channel(&mut self, channel: u8) -> Result<u32, Self::Error> {
+        let mut sum = 0u32;
+        self.oversample(|adc| {
+            sum = sum.saturating_add(adc.read_channel(channel)?);
+            Ok(())
+        })?;
+        Ok(sum >> SHIFT)
+    }
+
+    fn read_channels(&mut self, channels: &[u8], results: &mut [u32]) -> Result<(), Self::Error> {
+        if results.len() < channels.len() {
+            // Let the inner ADC report the error.
+            return self.adc.read_channels(channels, results);
+        }
+
+        for (channels, results) in channels.chunks(CHUNK).zip(results.chunks_mut(CHUNK)) {
+            let results = &mut results[..channels.len()];
+            results.fill(0);
+            let mut samples = [0; CHUNK];
+            let samples = &mut samples[..channels.len()];
+            self.oversample(|adc| {
+                adc.read_channels(channels, samples)?;
+                for (sum, sample) in results.iter_mut().zip(samples.iter()) {
+                    *sum = sum.saturating_add(*sample);
+                }
+                Ok(())
+            })?;
+            for sum in results {
+                *sum >>= SHIFT;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::adc::ErrorKind;
+    use std::{vec, vec::Vec};
+
+    /// 12-bit ADC returning `channel * 100 + conversion`, and logging conversions and delays.
+    #[derive(Default)]
+    struct FakeAdc {
+        conversions: u32,
+        log: Vec<&'static str>,
+    }
+
+    impl FakeAdc {
+        fn convert(&mut self, channel: u8) -> Result<u32, ErrorKind> {
+            if channel > 15 {
+                return Err(ErrorKind::InvalidChannel);
+            }
+            let sample = u32::from(channel) * 100 + self.conversions;
+            self.conversions += 1;
+            Ok(sample)
+        }
+    }
+
+    impl ErrorType for FakeAdc {
+        type Error = ErrorKind;
+    }
+
+    impl Adc for FakeAdc {
+        fn max_count(&self) -> u32 {
+            4095
+        }
+
+        fn read_channel(&mut self, channel: u8) -> Result<u32, ErrorKind> {
+            self.log.push("read");
+            self.convert(channel)
+        }
+
+        fn read_channels(&mut self, channels: &[u8], results: &mut [u32]) -> Result<(), ErrorKind> {
+            if results.len() < channels.len() {
+                return Err(ErrorKind::BufferTooSmall);
+            }
+            self.log.push("scan");
+            for (channel, result) in channels.iter().zip(results) {
+                *result = self.convert(*channel)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Delay logging into the log of the ADC.
+    struct Delay<'a>(&'a core::cell::RefCell<Vec<u32>>);
+
+    impl DelayNs for Delay<'_> {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.borrow_mut().push(ns);
+        }
+    }
+
+    #[test]
+    fn read_channel_sums_and_shifts() {
+        let delays = Default::default();
+        let mut adc: AdcOversampler<_, _, 4, 1> =
+            AdcOversampler::new(FakeAdc::default(), Delay(&delays), 500);
+        assert_eq!(adc.max_count(), 8190);
+        // (300 + 301 + 302 + 303) >> 1
+        assert_eq!(adc.read_channel(3), Ok(603));
+        assert_eq!(adc.release().0.log, ["read"; 4]);
+        assert_eq!(*delays.borrow(), [500, 500, 500]);
+    }
+
+    #[test]
+    fn read_channels_oversamples_each_channel() {
+        let delays = Default::default();
+        let mut adc: AdcOversampler<_, _, 2, 0> =
+            AdcOversampler::new(FakeAdc::default(), Delay(&delays), 0);
+        let channels: Vec<u8> = (0..10).collect();
+        let mut results = [0; 10];
+        adc.read_channels(&channels, &mut results).unwrap();
+        // First chunk: channels 0 to 7 in conversions 0 to 15, then channels 8 and 9.
+        let expected: Vec<u32> = (0..10)
+            .map(|c| {
+                let first = if c < 8 { c } else { 16 + c - 8 };
+                let second = if c < 8 { 8 + c } else { 18 + c - 8 };
+                2 * 100 * c + first + second
+            })
+            .collect();
+        assert_eq!(results.to_vec(), expected);
+        assert_eq!(adc.release().0.log, ["scan"; 4]);
+        assert!(delays.borrow().is_empty());
+    }
+
+    #[test]
+    fn read_channels_errors() {
+        let delays = Default::default();
+        let mut adc: AdcOversampler<_, _, 4, 2> =
+            AdcOversampler::new(FakeAdc::default(), Delay(&delays), 100);
+        assert_eq!(
+            adc.read_channels(&[0, 1], &mut [0]),
+            Err(ErrorKind::BufferTooSmall)
+        );
+        assert_eq!(
+            adc.read_channels(&[0, 16], &mut [0, 0]),
+            Err(ErrorKind::InvalidChannel)
+        );
+        assert_eq!(adc.release().0.log, vec!["scan"]);
+    }
+
+    #[test]
+    fn max_count_saturates() {
+        let delays = Default::default();
+        let adc: AdcOversampler<_, _, 1_000_000_000, 0> =
+            AdcOversampler::new(FakeAdc::default(), Delay(&delays), 0);
+        assert_eq!(adc.max_count(), u32::MAX);
+    }
+}
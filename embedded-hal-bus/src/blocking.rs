@@ -0,0 +1,455 @@
+//! Blocking-to-async adapter.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use embedded_hal::{delay, digital, i2c, spi};
+use embedded_hal_async::{
+    delay::DelayNs as AsyncDelayNs, digital::Wait, i2c::I2c as AsyncI2c,
+    spi::SpiBus as AsyncSpiBus, spi::SpiDevice as AsyncSpiDevice,
+};
+
+/// Adapter presenting a blocking implementation as the `embedded-hal-async` traits.
+///
+/// Every async method calls the blocking counterpart of the inner implementation, and therefore
+/// completes on its first poll. Errors are passed through unchanged. This is mostly useful to
+/// exercise async drivers on the host with blocking fakes, or to run them on top of a HAL which
+/// only provides blocking implementations.
+///
+/// The following traits are implemented when the inner type implements their blocking version:
+///
+/// - [`I2c`](embedded_hal_async::i2c::I2c), for every address mode.
+/// - [`SpiBus`](embedded_hal_async::spi::SpiBus) and [`SpiDevice`](embedded_hal_async::spi::SpiDevice),
+///   for every word type.
+/// - [`DelayNs`](embedded_hal_async::delay::DelayNs). Note that the delay blocks the whole
+///   executor, not only the calling task.
+/// - [`Wait`], by polling [`InputPin`](embedded_hal::digital::InputPin) and yielding to the
+///   executor between two reads.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_async::i2c::I2c;
+/// use embedded_hal_bus::blocking::Blocking;
+/// # use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+///
+/// /// An async driver reading the temperature register of a sensor.
+/// async fn read_temperature<I2C: I2c>(i2c: &mut I2C) -> Result<u8, I2C::Error> {
+///     let mut buf = [0];
+///     i2c.write_read(0x48, &[0x00], &mut buf).await?;
+///     Ok(buf[0])
+/// }
+///
+/// /// A blocking fake answering 21 to every read.
+/// struct FakeSensor;
+///
+/// impl ErrorType for FakeSensor {
+///     type Error = ErrorKind;
+/// }
+///
+/// impl embedded_hal::i2c::I2c for FakeSensor {
+///     fn transaction(&mut self, _: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+///         for op in operations {
+///             if let Operation::Read(buf) = op {
+///                 buf.fill(21);
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// # fn block_on<F: core::future::Future>(f: F) -> F::Output {
+/// #     let mut f = core::pin::pin!(f);
+/// #     use core::task::{RawWaker, RawWakerVTable, Waker};
+/// #     const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+/// #     const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+/// #     let waker = unsafe { Waker::from_raw(RAW) };
+/// #     let mut cx = core::task::Context::from_waker(&waker);
+/// #     loop {
+/// #         if let core::task::Poll::Ready(out) = f.as_mut().poll(&mut cx) {
+/// #             return out;
+/// #         }
+/// #     }
+/// # }
+/// let mut i2c = Blocking::new(FakeSensor);
+/// assert_eq!(block_on(read_temperature(&mut i2c)), Ok(21));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Blocking<T> {
+    inner: T,
+}
+
+impl<T> Blocking<T> {
+    /// Create a new [`Blocking`] adapter wrapping `inner`.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Consume the adapter, returning the inner implementation.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Borrow the inner implementation.
+    #[inline]
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner implementation.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: i2c::ErrorType> i2c::ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<T: i2c::I2c<A>, A: i2c::AddressMode> AsyncI2c<A> for Blocking<T> {
+    #[inline]
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.read(address, read)
+    }
+
+    #[inline]
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write(address, write)
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.inner.write_read(address, write, read)
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.inner.transaction(address, operations)
+    }
+}
+
+impl<T: spi::ErrorType> spi::ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<Word: Copy + 'static, T: spi::SpiBus<Word>> AsyncSpiBus<Word> for Blocking<T> {
+    #[inline]
+    async fn read(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        self.inner.read(words)
+    }
+
+    #[inline]
+    async fn write(&mut self, words: &[Word]) -> Result<(), Self::Error> {
+        self.inner.write(words)
+    }
+
+    #[inline]
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error> {
+        self.inner.transfer(read, write)
+    }
+
+    #[inline]
+    async fn transfer_in_place(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        self.inner.transfer_in_place(words)
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+impl<Word: Copy + 'static, T: spi::SpiDevice<Word>> AsyncSpiDevice<Word> for Blocking<T> {
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        self.inner.transaction(operations)
+    }
+
+    #[inline]
+    async fn read(&mut self, buf: &mut [Word]) -> Result<(), Self::Error> {
+        self.inner.read(buf)
+    }
+
+    #[inline]
+    async fn write(&mut self, buf: &[Word]) -> Result<(), Self::Error> {
+        self.inner.write(buf)
+    }
+
+    #[inline]
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error> {
+        self.inner.transfer(read, write)
+    }
+
+    #[inline]
+    async fn transfer_in_place(&mut self, buf: &mut [Word]) -> Result<(), Self::Error> {
+        self.inner.transfer_in_place(buf)
+    }
+}
+
+impl<T: delay::DelayNs> AsyncDelayNs for Blocking<T> {
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) {
+        self.inner.delay_ns(ns);
+    }
+
+    #[inline]
+    async fn delay_us(&mut self, us: u32) {
+        self.inner.delay_us(us);
+    }
+
+    #[inline]
+    async fn delay_ms(&mut self, ms: u32) {
+        self.inner.delay_ms(ms);
+    }
+}
+
+impl<T: digital::ErrorType> digital::ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<T: digital::InputPin> Blocking<T> {
+    /// Poll the inner pin until it reads `high`, yielding to the executor between two reads.
+    async fn poll_level(&mut self, high: bool) -> Result<(), T::Error> {
+        while self.inner.is_high()? != high {
+            YieldNow(false).await;
+        }
+        Ok(())
+    }
+}
+
+impl<T: digital::InputPin> Wait for Blocking<T> {
+    #[inline]
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(true).await
+    }
+
+    #[inline]
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(false).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(false).await?;
+        self.poll_level(true).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(true).await?;
+        self.poll_level(false).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        let initial = self.inner.is_high()?;
+        self.poll_level(!initial).await
+    }
+}
+
+/// Future returning `Pending` once, waking the task immediately.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::util::noop_waker;
+    use core::convert::Infallible;
+    use embedded_hal::spi::Operation;
+    use std::{vec, vec::Vec};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeSpi {
+        written: Vec<u16>,
+        flushed: bool,
+    }
+
+    impl spi::ErrorType for FakeSpi {
+        type Error = spi::ErrorKind;
+    }
+
+    impl spi::SpiBus<u16> for FakeSpi {
+        fn read(&mut self, words: &mut [u16]) -> Result<(), Self::Error> {
+            words.fill(0xABCD);
+            Ok(())
+        }
+
+        fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+            self.written.extend_from_slice(words);
+            Ok(())
+        }
+
+        fn transfer(&mut self, read: &mut [u16], write: &[u16]) -> Result<(), Self::Error> {
+            self.write(write)?;
+            self.read(read)
+        }
+
+        fn transfer_in_place(&mut self, _words: &mut [u16]) -> Result<(), Self::Error> {
+            Err(spi::ErrorKind::Overrun)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flushed = true;
+            Ok(())
+        }
+    }
+
+    impl spi::SpiDevice<u16> for FakeSpi {
+        fn transaction(
+            &mut self,
+            operations: &mut [Operation<'_, u16>],
+        ) -> Result<(), Self::Error> {
+            for op in operations {
+                match op {
+                    Operation::Write(words) => spi::SpiBus::write(self, words)?,
+                    Operation::Read(words) => spi::SpiBus::read(self, words)?,
+                    _ => unimplemented!(),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spi_bus_forwards() {
+        let mut bus = Blocking::new(FakeSpi::default());
+        let mut read = [0; 2];
+        block_on(AsyncSpiBus::transfer(&mut bus, &mut read, &[1, 2, 3])).unwrap();
+        block_on(AsyncSpiBus::flush(&mut bus)).unwrap();
+        assert_eq!(read, [0xABCD; 2]);
+        assert_eq!(bus.inner().written, [1, 2, 3]);
+        assert!(bus.inner().flushed);
+        assert_eq!(
+            block_on(AsyncSpiBus::transfer_in_place(&mut bus, &mut read)),
+            Err(spi::ErrorKind::Overrun)
+        );
+    }
+
+    #[test]
+    fn spi_device_forwards() {
+        let mut device = Blocking::new(FakeSpi::default());
+        let mut read = [0; 1];
+        block_on(AsyncSpiDevice::transaction(
+            &mut device,
+            &mut [Operation::Write(&[7]), Operation::Read(&mut read)],
+        ))
+        .unwrap();
+        assert_eq!(read, [0xABCD]);
+        assert_eq!(device.into_inner().written, [7]);
+    }
+
+    struct FakeDelay(u64);
+
+    impl delay::DelayNs for FakeDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 += u64::from(ns);
+        }
+    }
+
+    #[test]
+    fn delay_forwards() {
+        let mut delay = Blocking::new(FakeDelay(0));
+        block_on(async {
+            delay.delay_ns(5).await;
+            delay.delay_us(1).await;
+            delay.delay_ms(1).await;
+        });
+        assert_eq!(delay.inner().0, 1_001_005);
+    }
+
+    /// Pin returning a scripted sequence of levels, then staying at the last one.
+    struct ScriptedPin {
+        levels: Vec<bool>,
+        reads: usize,
+    }
+
+    impl digital::ErrorType for ScriptedPin {
+        type Error = Infallible;
+    }
+
+    impl digital::InputPin for ScriptedPin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            let level = self.levels[self.reads.min(self.levels.len() - 1)];
+            self.reads += 1;
+            Ok(level)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    fn pin(levels: Vec<bool>) -> Blocking<ScriptedPin> {
+        Blocking::new(ScriptedPin { levels, reads: 0 })
+    }
+
+    #[test]
+    fn wait_for_level_polls() {
+        let mut p = pin(vec![false, false, true]);
+        block_on(p.wait_for_high()).unwrap();
+        assert_eq!(p.inner().reads, 3);
+        block_on(p.wait_for_high()).unwrap();
+        assert_eq!(p.inner().reads, 4);
+    }
+
+    #[test]
+    fn wait_for_edges_polls() {
+        let mut p = pin(vec![true, true, false, true]);
+        block_on(p.wait_for_rising_edge()).unwrap();
+        assert_eq!(p.inner().reads, 4);
+
+        let mut p = pin(vec![true, false]);
+        block_on(p.wait_for_any_edge()).unwrap();
+        assert_eq!(p.inner().reads, 2);
+    }
+
+    #[test]
+    fn wait_yields_between_reads() {
+        let mut p = pin(vec![false, true]);
+        let mut future = core::pin::pin!(p.wait_for_high());
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert!(future.as_mut().poll(&mut cx).is_ready());
+    }
+}
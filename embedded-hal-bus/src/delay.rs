@@ -0,0 +1,247 @@
+//! Delay implementations.
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::time::{Duration, MonotonicClock};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Number of CPU cycles assumed for one iteration of the portable spin loop of [`BusyWaitDelay`].
+///
+/// One cycle is a lower bound for any loop iteration, so the portable loop never waits less than
+/// requested. In practice, an iteration takes a few cycles, and longer when running from slow
+/// memory: the loop then waits several times longer than requested.
+pub const SPIN_LOOP_CYCLES_PER_ITERATION: u32 = 1;
+
+/// [`DelayNs`] implementation busy-waiting for a number of CPU cycles.
+///
+/// This needs no timer, which makes it useful early in the bring-up of a board, for instance to
+/// build an [`ExclusiveDevice`](crate::spi::ExclusiveDevice) or to run a reset sequence. Once a timer
+/// is configured, prefer a delay based on it.
+///
+/// The delays are converted to CPU cycles from the core clock frequency, rounding up. On Cortex-M,
+/// with the `cortex-m` feature, the cycles are spent with [`cortex_m::asm::delay`]. Elsewhere, a
+/// portable loop spins on a volatile counter, assuming each iteration takes
+/// [`SPIN_LOOP_CYCLES_PER_ITERATION`] cycles.
+///
+/// # Accuracy
+///
+/// The delays are never shorter than requested, as long as the core clock frequency is right, but
+/// they can be much longer:
+///
+/// - Interrupt handlers running during the delay extend it by their own duration.
+/// - Flash wait states, cache misses and instruction fetches from slow memory make the loop
+///   iterations slower than assumed.
+/// - The portable loop assumes the fastest possible iteration.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::delay::DelayNs;
+/// use embedded_hal_bus::delay::BusyWaitDelay;
+///
+/// // Core running from the 16 MHz internal oscillator, after reset.
+/// let mut delay = BusyWaitDelay::new(16_000_000);
+/// delay.delay_us(10);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct BusyWaitDelay {
+    core_clock_hz: u32,
+}
+
+impl BusyWaitDelay {
+    /// Create a new [`BusyWaitDelay`], for a core clocked at `core_clock_hz`.
+    #[inline]
+    pub const fn new(core_clock_hz: u32) -> Self {
+        Self { core_clock_hz }
+    }
+
+    /// Returns the number of CPU cycles lasting at least `amount` units, with `units_per_sec` units per
+    /// second.
+    ///
+    /// Both factors fit in 32 bits, so their product cannot overflow 64 bits.
+    #[inline]
+    fn cycles(&self, amount: u32, units_per_sec: u64) -> u64 {
+        (u64::from(amount) * u64::from(self.core_clock_hz)).div_ceil(units_per_sec)
+    }
+
+    fn spin(&mut self, mut cycles: u64) {
+        while cycles > 0 {
+            let chunk = cycles.min(u64::from(u32::MAX)) as u32;
+            spin_cycles(chunk);
+            cycles -= u64::from(chunk);
+        }
+    }
+}
+
+#[cfg(all(feature = "cortex-m", target_arch = "arm"))]
+#[inline]
+fn spin_cycles(cycles: u32) {
+    cortex_m::asm::delay(cycles);
+}
+
+#[cfg(not(all(feature = "cortex-m", target_arch = "arm")))]
+fn spin_cycles(cycles: u32) {
+    let mut counter = 0u32;
+    for _ in 0..cycles.div_ceil(SPIN_LOOP_CYCLES_PER_ITERATION) {
+        // The volatile accesses keep the compiler from removing the loop.
+        // SAFETY: `counter` is a valid, aligned local variable.
+        unsafe {
+            let value = core::ptr::read_volatile(&counter);
+            core::ptr::write_volatile(&mut counter, value.wrapping_add(1));
+        }
+    }
+}
+
+impl DelayNs for BusyWaitDelay {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        let cycles = self.cycles(ns, 1_000_000_000);
+        self.spin(cycles);
+    }
+
+    #[inline]
+    fn delay_us(&mut self, us: u32) {
+        let cycles = self.cycles(us, 1_000_000);
+        self.spin(cycles);
+    }
+
+    #[inline]
+    fn delay_ms(&mut self, ms: u32) {
+        let cycles = self.cycles(ms, 1_000);
+        self.spin(cycles);
+    }
+}
+
+/// [`DelayNs`] implementation spinning until a deadline of a [`MonotonicClock`].
+///
+/// This turns any clock into a delay, without tying up a timer. The delays are never shorter than
+/// requested, and longer by up to the resolution of the clock, plus the time to read it.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::delay::DelayNs;
+/// use embedded_hal::time::{Instant, MonotonicClock};
+/// use embedded_hal_bus::delay::ClockDelay;
+/// # struct SysTick;
+/// # impl MonotonicClock for SysTick {
+/// #     fn now(&self) -> Instant {
+/// #         static TICKS: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+/// #         Instant::from_nanos(TICKS.fetch_add(1_000, core::sync::atomic::Ordering::Relaxed))
+/// #     }
+/// # }
+///
+/// let clock = SysTick;
+/// let mut delay = ClockDelay::new(&clock);
+/// delay.delay_ms(2);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct ClockDelay<C> {
+    clock: C,
+}
+
+impl<C: MonotonicClock> ClockDelay<C> {
+    /// Create a new [`ClockDelay`], reading `clock`.
+    #[inline]
+    pub fn new(clock: C) -> Self {
+        Self { clock }
+    }
+
+    /// Release the clock.
+    #[inline]
+    pub fn release(self) -> C {
+        self.clock
+    }
+
+    fn wait(&mut self, duration: Duration) {
+        let start = self.clock.now();
+        while self.clock.elapsed(start) < duration {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<C: MonotonicClock> DelayNs for ClockDelay<C> {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        self.wait(Duration::from_nanos(u64::from(ns)));
+    }
+
+    #[inline]
+    fn delay_us(&mut self, us: u32) {
+        self.wait(Duration::from_micros(u64::from(us)));
+    }
+
+    #[inline]
+    fn delay_ms(&mut self, ms: u32) {
+        self.wait(Duration::from_millis(u64::from(ms)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_round_up() {
+        let delay = BusyWaitDelay::new(16_000_000);
+        assert_eq!(delay.cycles(0, 1_000_000_000), 0);
+        assert_eq!(delay.cycles(1, 1_000_000_000), 1);
+        assert_eq!(delay.cycles(62, 1_000_000_000), 1);
+        assert_eq!(delay.cycles(63, 1_000_000_000), 2);
+        assert_eq!(delay.cycles(10, 1_000_000), 160);
+        assert_eq!(delay.cycles(1, 1_000), 16_000);
+    }
+
+    #[test]
+    fn cycles_do_not_overflow() {
+        let delay = BusyWaitDelay::new(1_000_000_000);
+        assert_eq!(delay.cycles(u32::MAX, 1_000_000_000), u64::from(u32::MAX));
+        assert_eq!(
+            delay.cycles(u32::MAX, 1_000),
+            u64::from(u32::MAX) * 1_000_000
+        );
+
+        let delay = BusyWaitDelay::new(u32::MAX);
+        assert_eq!(
+            delay.cycles(u32::MAX, 1_000),
+            (u64::from(u32::MAX) * u64::from(u32::MAX)).div_ceil(1_000)
+        );
+    }
+
+    #[test]
+    fn short_delays_return() {
+        let mut delay = BusyWaitDelay::new(8_000_000);
+        delay.delay_ns(500);
+        delay.delay_us(20);
+        delay.delay_ms(0);
+    }
+
+    /// Clock advancing by 300 ns every time it is read.
+    struct TickingClock(core::cell::Cell<u64>);
+
+    impl MonotonicClock for TickingClock {
+        fn now(&self) -> embedded_hal::time::Instant {
+            self.0.set(self.0.get() + 300);
+            embedded_hal::time::Instant::from_nanos(self.0.get())
+        }
+    }
+
+    #[test]
+    fn clock_delay_waits_until_the_deadline() {
+        let clock = TickingClock(Default::default());
+        let mut delay = ClockDelay::new(&clock);
+        delay.delay_ns(1_000);
+        // Read at 300, then at 600, 900, 1200 and 1500: 1200 ns elapsed.
+        assert_eq!(clock.0.get(), 1_500);
+
+        delay.delay_us(1);
+        assert_eq!(clock.0.get(), 3_000);
+        // Zero delays still read the clock twice.
+        delay.delay_ns(0);
+        assert_eq!(clock.0.get(), 3_600);
+    }
+}
@@ -0,0 +1,249 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{Error, ErrorKind, ErrorType, InputPin};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, digital::Wait};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Error type for [`DebouncedPin`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DebounceError<PIN> {
+    /// Reading the inner pin failed.
+    Pin(PIN),
+    /// The level of the pin changed during the debounce window.
+    ///
+    /// The pin is still bouncing: it should be read again later.
+    Unstable,
+}
+
+impl<PIN: Display> Display for DebounceError<PIN> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Pin(pin) => write!(f, "Pin error: {pin}"),
+            Self::Unstable => write!(f, "The pin level is not stable"),
+        }
+    }
+}
+
+impl<PIN: Debug + Display> core::error::Error for DebounceError<PIN> {}
+
+impl<PIN: Error> Error for DebounceError<PIN> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Pin(e) => e.kind(),
+            Self::Unstable => ErrorKind::Other,
+        }
+    }
+}
+
+/// Number of samples taken during each debounce window by [`DebouncedPin::wait_for_stable_high`]
+/// and [`DebouncedPin::wait_for_stable_low`].
+pub const STABLE_SAMPLES: u32 = 4;
+
+/// Input pin wrapper debouncing a mechanical switch.
+///
+/// Reading the pin samples it twice, `debounce_ns` nanoseconds apart, and returns the level
+/// if both samples agree. Otherwise, it returns [`DebounceError::Unstable`].
+///
+/// [`wait_for_stable_high`](DebouncedPin::wait_for_stable_high) and
+/// [`wait_for_stable_low`](DebouncedPin::wait_for_stable_low) block until the pin stays at a level
+/// for a whole debounce window, sampling it [`STABLE_SAMPLES`] times per window: chatter shorter
+/// than the window restarts the wait.
+///
+/// With the `async` feature, the [`Wait`] trait is also implemented: after the inner pin
+/// reports an edge, the pin is sampled again after the debounce window, and the wait
+/// continues until the level is stable.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::InputPin;
+/// use embedded_hal_bus::gpio::DebouncedPin;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::{delay::DelayNs, digital::ErrorType};
+/// # struct Pin;
+/// # impl ErrorType for Pin { type Error = Infallible; }
+/// # impl InputPin for Pin {
+/// #     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(false) }
+/// #     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _ns: u32) {} }
+/// # let (button_pin, delay) = (Pin, Delay);
+///
+/// // Wait for 20 ms between the two samples.
+/// let mut button = DebouncedPin::new(button_pin, delay, 20_000_000);
+/// let pressed = button.is_low()?;
+/// # Ok::<(), embedded_hal_bus::gpio::DebounceError<Infallible>>(())
+/// ```
+pub struct DebouncedPin<P, D> {
+    pin: P,
+    delay: D,
+    debounce_ns: u64,
+}
+
+impl<P, D> DebouncedPin<P, D> {
+    /// Create a new [`DebouncedPin`], with a debounce window of `debounce_ns` nanoseconds.
+    #[inline]
+    pub fn new(pin: P, delay: D, debounce_ns: u64) -> Self {
+        Self {
+            pin,
+            delay,
+            debounce_ns,
+        }
+    }
+
+    /// Release the inner pin and the delay.
+    #[inline]
+    pub fn release(self) -> (P, D) {
+        (self.pin, self.delay)
+    }
+}
+
+impl<P: InputPin, D> DebouncedPin<P, D> {
+    fn sample(&mut self) -> Result<bool, DebounceError<P::Error>> {
+        self.pin.is_high().map_err(DebounceError::Pin)
+    }
+}
+
+impl<P: InputPin, D: DelayNs> DebouncedPin<P, D> {
+    fn wait_debounce(&mut self) {
+        self.wait_ns(self.debounce_ns);
+    }
+
+    fn wait_ns(&mut self, mut ns: u64) {
+        while ns > 0 {
+            let chunk = ns.min(u64::from(u32::MAX));
+            self.delay.delay_ns(chunk as u32);
+            ns -= chunk;
+        }
+    }
+
+    fn read(&mut self) -> Result<bool, DebounceError<P::Error>> {
+        let first = self.sample()?;
+        self.wait_debounce();
+        if self.sample()? == first {
+            Ok(first)
+        } else {
+            Err(DebounceError::Unstable)
+        }
+    }
+
+    /// Wait until the pin has been high for a whole debounce window.
+    #[inline]
+    pub fn wait_for_stable_high(&mut self) -> Result<(), DebounceError<P::Error>> {
+        self.wait_for_stable(true)
+    }
+
+    /// Wait until the pin has been low for a whole debounce window.
+    #[inline]
+    pub fn wait_for_stable_low(&mut self) -> Result<(), DebounceError<P::Error>> {
+        self.wait_for_stable(false)
+    }
+
+    fn wait_for_stable(&mut self, level: bool) -> Result<(), DebounceError<P::Error>> {
+        let interval = self.debounce_ns / u64::from(STABLE_SAMPLES);
+        let mut stable = 0;
+        loop {
+            if self.sample()? != level {
+                stable = 0;
+            } else if stable == STABLE_SAMPLES {
+                return Ok(());
+            } else {
+                stable += 1;
+            }
+            self.wait_ns(interval);
+        }
+    }
+}
+
+impl<P: InputPin, D> ErrorType for DebouncedPin<P, D> {
+    type Error = DebounceError<P::Error>;
+}
+
+impl<P: InputPin, D: DelayNs> InputPin for DebouncedPin<P, D> {
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.read()
+    }
+
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.read().map(|high| !high)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<P: InputPin + Wait, D: AsyncDelayNs> DebouncedPin<P, D> {
+    async fn wait_debounce_async(&mut self) {
+        let mut ns = self.debounce_ns;
+        while ns > 0 {
+            let chunk = ns.min(u64::from(u32::MAX));
+            self.delay.delay_ns(chunk as u32).await;
+            ns -= chunk;
+        }
+    }
+
+    /// Wait for the inner pin to report an edge, then wait until the pin settles at `level`.
+    async fn wait_for_level(
+        &mut self,
+        level: bool,
+        edge: bool,
+    ) -> Result<(), DebounceError<P::Error>> {
+        loop {
+            match (edge, level) {
+                (false, true) => self.pin.wait_for_high().await,
+                (false, false) => self.pin.wait_for_low().await,
+                (true, true) => self.pin.wait_for_rising_edge().await,
+                (true, false) => self.pin.wait_for_falling_edge().await,
+            }
+            .map_err(DebounceError::Pin)?;
+            self.wait_debounce_async().await;
+            if self.sample()? == level {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<P: InputPin + Wait, D: AsyncDelayNs> Wait for DebouncedPin<P, D> {
+    #[inline]
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_level(true, false).await
+    }
+
+    #[inline]
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_level(false, false).await
+    }
+
+    #[inline]
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_level(true, true).await
+    }
+
+    #[inline]
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for_level(false, true).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        let initial = self.sample()?;
+        loop {
+            self.pin
+                .wait_for_any_edge()
+                .await
+                .map_err(DebounceError::Pin)?;
+            self.wait_debounce_async().await;
+            if self.sample()? != initial {
+                return Ok(());
+            }
+        }
+    }
+}
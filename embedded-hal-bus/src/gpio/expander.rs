@@ -0,0 +1,96 @@
+use core::cell::RefCell;
+use embedded_hal::digital::{ErrorType, GpioExpander, InputPin, OutputPin};
+
+/// Single pin of a [`GpioExpander`], usable as an [`InputPin`] or [`OutputPin`].
+///
+/// The expander is shared through a `RefCell`, so that each of its pins can be handed to a
+/// different driver. Like [`RefCellDevice`](crate::i2c::RefCellDevice), this only allows sharing
+/// within a single thread (interrupt priority level).
+///
+/// The pin does not change the direction of the expander pins: configure them with
+/// [`GpioExpander::set_direction`] before using the pins.
+///
+/// # Example
+///
+/// ```
+/// use core::cell::RefCell;
+/// use embedded_hal::digital::{GpioExpander, InputPin, OutputPin};
+/// use embedded_hal_bus::gpio::ExpanderPin;
+/// # use embedded_hal::digital::{ErrorKind, ErrorType};
+/// # struct Mcp23017;
+/// # impl ErrorType for Mcp23017 { type Error = ErrorKind; }
+/// # impl GpioExpander for Mcp23017 {
+/// #     fn port_count(&self) -> u8 { 2 }
+/// #     fn read_port(&mut self, _: u8) -> Result<u8, ErrorKind> { Ok(0) }
+/// #     fn write_port(&mut self, _: u8, _: u8, _: u8) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn set_direction(&mut self, _: u8, _: u8) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # let mut expander = Mcp23017;
+///
+/// // Port 0 (GPA) is made of inputs, port 1 (GPB) of outputs.
+/// expander.set_direction(0, 0x00)?;
+/// expander.set_direction(1, 0xFF)?;
+///
+/// let expander = RefCell::new(expander);
+/// let mut button = ExpanderPin::new(&expander, 0, 3);
+/// let mut led = ExpanderPin::new(&expander, 1, 0);
+/// if button.is_low()? {
+///     led.set_high()?;
+/// }
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct ExpanderPin<'a, E> {
+    expander: &'a RefCell<E>,
+    port: u8,
+    mask: u8,
+}
+
+impl<'a, E> ExpanderPin<'a, E> {
+    /// Create a new [`ExpanderPin`], for pin `bit` of `port`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit` is not in `0..8`.
+    #[inline]
+    pub fn new(expander: &'a RefCell<E>, port: u8, bit: u8) -> Self {
+        assert!(bit < 8, "expander ports have 8 pins");
+        Self {
+            expander,
+            port,
+            mask: 1 << bit,
+        }
+    }
+}
+
+impl<E: ErrorType> ErrorType for ExpanderPin<'_, E> {
+    type Error = E::Error;
+}
+
+impl<E: GpioExpander> OutputPin for ExpanderPin<'_, E> {
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.expander
+            .borrow_mut()
+            .write_port(self.port, 0, self.mask)
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.expander
+            .borrow_mut()
+            .write_port(self.port, self.mask, self.mask)
+    }
+}
+
+impl<E: GpioExpander> InputPin for ExpanderPin<'_, E> {
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        let value = self.expander.borrow_mut().read_port(self.port)?;
+        Ok(value & self.mask != 0)
+    }
+
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
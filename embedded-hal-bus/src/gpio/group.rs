@@ -0,0 +1,144 @@
+use embedded_hal::digital::{Error, ErrorKind, ErrorType, GpioPort, InputPin, OutputPin};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Error type for [`PinGroup`] operations, identifying the pin which failed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct PinGroupError<E> {
+    /// Index of the failed pin in the group, which is also its bit in the port values.
+    pub pin: u8,
+    /// Error of the pin.
+    pub error: E,
+}
+
+impl<E: Error> Error for PinGroupError<E> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        self.error.kind()
+    }
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for PinGroupError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Pin {} error: {}", self.pin, self.error)
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for PinGroupError<E> {}
+
+/// [`GpioPort`] made of `N` individual pins, for HALs without native port access.
+///
+/// Bit `n` of the port values corresponds to `pins[n]`, so the least significant bit is the first
+/// pin of the array. `N` must be at most 32.
+///
+/// The pins are accessed one after the other, in index order: unlike a hardware port, a write
+/// changes the pins at different times, and a read samples them at different times. Make sure the
+/// device tolerates this, for example by writing the data lines of a parallel bus before toggling
+/// its strobe line in a separate write. When a pin fails, the following pins are not accessed.
+///
+/// [`write_port`](PinGroup::write_port) only needs [`OutputPin`]s, and
+/// [`read_port`](PinGroup::read_port) only needs [`InputPin`]s. The [`GpioPort`] trait is
+/// implemented for groups of pins implementing both.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::gpio::PinGroup;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::digital::{ErrorType, OutputPin};
+/// # struct Pin;
+/// # impl ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # let (d0, d1, d2, d3, d4, d5, d6, d7) = (Pin, Pin, Pin, Pin, Pin, Pin, Pin, Pin);
+///
+/// // Data lines of an 8080 display interface, D0 being bit 0.
+/// let mut data = PinGroup::new([d0, d1, d2, d3, d4, d5, d6, d7]);
+/// data.write_port(0xA5, 0xFF)?;
+/// # Ok::<(), embedded_hal_bus::gpio::PinGroupError<Infallible>>(())
+/// ```
+pub struct PinGroup<P, const N: usize> {
+    pins: [P; N],
+}
+
+impl<P, const N: usize> PinGroup<P, N> {
+    /// Create a new [`PinGroup`].
+    ///
+    /// Fails to compile if `N` is larger than 32.
+    #[inline]
+    pub fn new(pins: [P; N]) -> Self {
+        const { assert!(N <= 32, "a port has at most 32 pins") };
+        Self { pins }
+    }
+
+    /// Mutably borrow the pins.
+    #[inline]
+    pub fn pins_mut(&mut self) -> &mut [P; N] {
+        &mut self.pins
+    }
+
+    /// Destroy the group, returning the pins.
+    #[inline]
+    pub fn release(self) -> [P; N] {
+        self.pins
+    }
+}
+
+impl<P: OutputPin, const N: usize> PinGroup<P, N> {
+    /// Drives the pins selected by `mask` to the state given by `value`, in index order.
+    ///
+    /// Pins whose bit is `0` in `mask` are left untouched.
+    pub fn write_port(&mut self, value: u32, mask: u32) -> Result<(), PinGroupError<P::Error>> {
+        for (i, pin) in self.pins.iter_mut().enumerate() {
+            let bit = 1 << i;
+            if mask & bit == 0 {
+                continue;
+            }
+            let res = if value & bit != 0 {
+                pin.set_high()
+            } else {
+                pin.set_low()
+            };
+            res.map_err(|error| PinGroupError {
+                pin: i as u8,
+                error,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: InputPin, const N: usize> PinGroup<P, N> {
+    /// Reads the input state of all the pins, in index order.
+    pub fn read_port(&mut self) -> Result<u32, PinGroupError<P::Error>> {
+        let mut value = 0;
+        for (i, pin) in self.pins.iter_mut().enumerate() {
+            let high = pin.is_high().map_err(|error| PinGroupError {
+                pin: i as u8,
+                error,
+            })?;
+            value |= u32::from(high) << i;
+        }
+        Ok(value)
+    }
+}
+
+impl<P: ErrorType, const N: usize> ErrorType for PinGroup<P, N> {
+    type Error = PinGroupError<P::Error>;
+}
+
+impl<P: InputPin + OutputPin, const N: usize> GpioPort<N> for PinGroup<P, N> {
+    #[inline]
+    fn read_port(&mut self) -> Result<u32, Self::Error> {
+        PinGroup::read_port(self)
+    }
+
+    #[inline]
+    fn write_port(&mut self, value: u32, mask: u32) -> Result<(), Self::Error> {
+        PinGroup::write_port(self, value, mask)
+    }
+}
@@ -0,0 +1,146 @@
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin, PinState, StatefulOutputPin};
+#[cfg(feature = "async")]
+use embedded_hal_async::digital::Wait;
+
+/// Pin wrapper inverting the logic level.
+///
+/// Setting an `InvertedPin` high sets the inner pin low and vice versa, and reading it
+/// returns the opposite level of the inner pin. This lets drivers expecting an active-high
+/// signal work with an active-low one, such as an active-low enable or reset line.
+///
+/// Errors of the inner pin are forwarded unchanged.
+///
+/// With the `async` feature, the [`Wait`](embedded_hal_async::digital::Wait) trait is also
+/// implemented, waiting for the opposite level or edge of the inner pin: `wait_for_high` waits for
+/// the inner pin to be low, and `wait_for_rising_edge` for a falling edge of the inner pin.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::OutputPin;
+/// use embedded_hal_bus::gpio::InvertedPin;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::digital::ErrorType;
+/// # struct Pin;
+/// # impl ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # let reset_n = Pin;
+///
+/// // The reset line is active low, the driver expects an active high pin.
+/// let mut reset = InvertedPin::new(reset_n);
+/// // Drives the line low.
+/// reset.set_high()?;
+/// # Ok::<(), Infallible>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvertedPin<T> {
+    pin: T,
+}
+
+impl<T> InvertedPin<T> {
+    /// Create a new [`InvertedPin`].
+    #[inline]
+    pub fn new(pin: T) -> Self {
+        Self { pin }
+    }
+
+    /// Borrow the inner pin.
+    #[inline]
+    pub fn inner(&self) -> &T {
+        &self.pin
+    }
+
+    /// Mutably borrow the inner pin.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.pin
+    }
+
+    /// Destroy the wrapper, returning the inner pin.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.pin
+    }
+}
+
+impl<T: ErrorType> ErrorType for InvertedPin<T> {
+    type Error = T::Error;
+}
+
+impl<T: OutputPin> OutputPin for InvertedPin<T> {
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high()
+    }
+
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low()
+    }
+
+    #[inline]
+    fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+        self.pin.set_state(!state)
+    }
+}
+
+impl<T: StatefulOutputPin> StatefulOutputPin for InvertedPin<T> {
+    #[inline]
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_set_low()
+    }
+
+    #[inline]
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_set_high()
+    }
+
+    #[inline]
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.pin.toggle()
+    }
+}
+
+impl<T: InputPin> InputPin for InvertedPin<T> {
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: Wait> Wait for InvertedPin<T> {
+    #[inline]
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_low().await
+    }
+
+    #[inline]
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_high().await
+    }
+
+    #[inline]
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_falling_edge().await
+    }
+
+    #[inline]
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_rising_edge().await
+    }
+
+    #[inline]
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.pin.wait_for_any_edge().await
+    }
+}
@@ -0,0 +1,539 @@
+//! Wrappers around `OutputPin` and `InputPin` implementations.
+
+mod debounce;
+pub use debounce::*;
+mod expander;
+pub use expander::*;
+mod group;
+pub use group::*;
+mod inverted;
+pub use inverted::*;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+mod polling;
+#[cfg(feature = "async")]
+pub use polling::*;
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::delay::DelayNs;
+    use embedded_hal::digital::{
+        ErrorType, GpioExpander, GpioPort, InputPin, OutputPin, PinState, StatefulOutputPin,
+    };
+    use std::{collections::VecDeque, vec::Vec};
+
+    #[derive(Default)]
+    struct FakePin {
+        high: bool,
+    }
+
+    impl ErrorType for FakePin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for FakePin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.high = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    impl StatefulOutputPin for FakePin {
+        fn is_set_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.high)
+        }
+
+        fn is_set_low(&mut self) -> Result<bool, Infallible> {
+            Ok(!self.high)
+        }
+    }
+
+    impl InputPin for FakePin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.high)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(!self.high)
+        }
+    }
+
+    #[test]
+    fn inverted_output() {
+        let mut pin = InvertedPin::new(FakePin::default());
+        pin.set_high().unwrap();
+        assert!(!pin.inner().high);
+        pin.set_low().unwrap();
+        assert!(pin.inner().high);
+        pin.set_state(PinState::High).unwrap();
+        assert!(!pin.inner().high);
+        pin.set_state(PinState::Low).unwrap();
+        assert!(pin.inner().high);
+    }
+
+    #[test]
+    fn inverted_stateful_output() {
+        let mut pin = InvertedPin::new(FakePin::default());
+        assert!(pin.is_set_high().unwrap());
+        assert!(!pin.is_set_low().unwrap());
+        pin.toggle().unwrap();
+        assert!(pin.is_set_low().unwrap());
+        assert!(!pin.is_set_high().unwrap());
+        pin.toggle().unwrap();
+        assert!(pin.is_set_high().unwrap());
+        pin.set_state(PinState::Low).unwrap();
+        assert!(pin.is_set_low().unwrap());
+        assert!(pin.into_inner().high);
+    }
+
+    /// Pin recording which `Wait` method was called.
+    #[cfg(feature = "async")]
+    #[derive(Default)]
+    struct WaitingPin(Vec<&'static str>);
+
+    #[cfg(feature = "async")]
+    impl ErrorType for WaitingPin {
+        type Error = Infallible;
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::digital::Wait for WaitingPin {
+        async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+            self.0.push("high");
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+            self.0.push("low");
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+            self.0.push("rising");
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+            self.0.push("falling");
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+            self.0.push("any");
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn inverted_wait() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::digital::Wait;
+
+        let mut pin = InvertedPin::new(WaitingPin::default());
+        poll_to_completion(pin.wait_for_high()).unwrap();
+        poll_to_completion(pin.wait_for_low()).unwrap();
+        poll_to_completion(pin.wait_for_rising_edge()).unwrap();
+        poll_to_completion(pin.wait_for_falling_edge()).unwrap();
+        poll_to_completion(pin.wait_for_any_edge()).unwrap();
+        assert_eq!(
+            pin.into_inner().0,
+            ["low", "high", "falling", "rising", "any"]
+        );
+    }
+
+    #[test]
+    fn inverted_input() {
+        let mut pin = InvertedPin::new(FakePin { high: true });
+        assert!(!pin.is_high().unwrap());
+        assert!(pin.is_low().unwrap());
+        pin.inner_mut().high = false;
+        assert!(pin.is_high().unwrap());
+        assert!(!pin.is_low().unwrap());
+    }
+
+    /// Input pin returning a scripted sequence of levels, and logging when it is sampled.
+    struct ScriptedPin {
+        levels: VecDeque<bool>,
+        log: Vec<&'static str>,
+    }
+
+    impl ScriptedPin {
+        fn new(levels: &[bool]) -> Self {
+            Self {
+                levels: levels.iter().copied().collect(),
+                log: Vec::new(),
+            }
+        }
+    }
+
+    impl ErrorType for ScriptedPin {
+        type Error = Infallible;
+    }
+
+    impl InputPin for ScriptedPin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            self.log.push("sample");
+            Ok(self.levels.pop_front().unwrap())
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::digital::Wait for ScriptedPin {
+        async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+            self.log.push("wait");
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+            self.log.push("wait");
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+            self.log.push("wait");
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+            self.log.push("wait");
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+            self.log.push("wait");
+            Ok(())
+        }
+    }
+
+    /// Delay recording the total waited time.
+    #[derive(Default)]
+    struct RecordingDelay(u64);
+
+    impl DelayNs for RecordingDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 += u64::from(ns);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::delay::DelayNs for RecordingDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0 += u64::from(ns);
+        }
+    }
+
+    #[test]
+    fn debounced_stable_level() {
+        let mut pin = DebouncedPin::new(
+            ScriptedPin::new(&[true, true, false, false]),
+            RecordingDelay::default(),
+            20_000_000,
+        );
+        assert_eq!(pin.is_high(), Ok(true));
+        assert_eq!(pin.is_low(), Ok(true));
+        let (_, delay) = pin.release();
+        assert_eq!(delay.0, 40_000_000);
+    }
+
+    #[test]
+    fn debounced_unstable_level() {
+        let mut pin = DebouncedPin::new(
+            ScriptedPin::new(&[true, false]),
+            RecordingDelay::default(),
+            5_000_000,
+        );
+        assert_eq!(pin.is_high(), Err(DebounceError::Unstable));
+    }
+
+    #[test]
+    fn debounced_long_window() {
+        let mut pin = DebouncedPin::new(
+            ScriptedPin::new(&[true, true]),
+            RecordingDelay::default(),
+            10_000_000_000,
+        );
+        assert_eq!(pin.is_high(), Ok(true));
+        let (_, delay) = pin.release();
+        assert_eq!(delay.0, 10_000_000_000);
+    }
+
+    #[test]
+    fn debounced_wait_for_stable_high_suppresses_chatter() {
+        use embedded_hal_mock::sim::{FakeDelay, FakePin, SimClock};
+
+        // Two short presses, then a press lasting a whole window, sampled every 1 µs.
+        let clock = SimClock::new();
+        let button = FakePin::new(&clock, PinState::Low).with_schedule([
+            (1_000, PinState::High),
+            (3_000, PinState::Low),
+            (4_000, PinState::High),
+            (5_000, PinState::Low),
+            (6_000, PinState::High),
+        ]);
+        let mut pin = DebouncedPin::new(button, FakeDelay::new(&clock), 4_000);
+        pin.wait_for_stable_high().unwrap();
+        assert_eq!(clock.now_ns(), 10_000);
+    }
+
+    #[test]
+    fn debounced_wait_for_stable_low_suppresses_chatter() {
+        // A release bouncing back high for less than a window.
+        let levels = [
+            true, false, false, false, true, false, false, false, false, false,
+        ];
+        let mut pin =
+            DebouncedPin::new(ScriptedPin::new(&levels), RecordingDelay::default(), 8_000);
+        pin.wait_for_stable_low().unwrap();
+        let (pin, delay) = pin.release();
+        assert!(pin.levels.is_empty());
+        assert_eq!(delay.0, 18_000);
+    }
+
+    #[test]
+    fn debounced_wait_for_stable_level_already_stable() {
+        let mut pin = DebouncedPin::new(
+            ScriptedPin::new(&[true; 5]),
+            RecordingDelay::default(),
+            1_000,
+        );
+        pin.wait_for_stable_high().unwrap();
+        let (pin, delay) = pin.release();
+        assert_eq!(pin.log.len(), 5);
+        assert_eq!(delay.0, 1_000);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn debounced_wait_ignores_bounces() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::digital::Wait;
+
+        // The first edge is a bounce: the pin is low again after the debounce window.
+        let mut pin = DebouncedPin::new(
+            ScriptedPin::new(&[false, true]),
+            RecordingDelay::default(),
+            1_000,
+        );
+        poll_to_completion(pin.wait_for_rising_edge()).unwrap();
+        let (pin, delay) = pin.release();
+        assert_eq!(pin.log, ["wait", "sample", "wait", "sample"]);
+        assert_eq!(delay.0, 2_000);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn debounced_wait_for_any_edge() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::digital::Wait;
+
+        let mut pin = DebouncedPin::new(
+            ScriptedPin::new(&[true, true, false]),
+            RecordingDelay::default(),
+            1_000,
+        );
+        poll_to_completion(pin.wait_for_any_edge()).unwrap();
+        let (pin, _) = pin.release();
+        assert_eq!(pin.log, ["sample", "wait", "sample", "wait", "sample"]);
+    }
+
+    #[cfg(feature = "async")]
+    fn polled(levels: &[bool]) -> PollingWait<ScriptedPin, RecordingDelay> {
+        PollingWait::new(ScriptedPin::new(levels), RecordingDelay::default())
+            .with_poll_interval_ns(100)
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn polling_wait_for_level() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::digital::Wait;
+
+        // Already high: no delay.
+        let mut pin = polled(&[true]);
+        poll_to_completion(pin.wait_for_high()).unwrap();
+        assert_eq!(pin.release().1 .0, 0);
+
+        let mut pin = polled(&[true, true, false]);
+        poll_to_completion(pin.wait_for_low()).unwrap();
+        let (pin, delay) = pin.release();
+        assert_eq!(pin.log, ["sample"; 3]);
+        assert_eq!(delay.0, 200);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn polling_wait_for_edges() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::digital::Wait;
+
+        // A high level is not a rising edge: wait for the pin to go low, then high again.
+        let mut pin = polled(&[true, true, false, false, true]);
+        poll_to_completion(pin.wait_for_rising_edge()).unwrap();
+        assert_eq!(pin.release().1 .0, 300);
+
+        let mut pin = polled(&[false, true, true, false]);
+        poll_to_completion(pin.wait_for_falling_edge()).unwrap();
+        assert_eq!(pin.release().1 .0, 200);
+
+        let mut pin = polled(&[false, false, false, true]);
+        poll_to_completion(pin.wait_for_any_edge()).unwrap();
+        assert_eq!(pin.release().1 .0, 200);
+    }
+
+    /// Two-port expander, reading back the output latch of its output pins.
+    #[derive(Default)]
+    struct FakeExpander {
+        latch: [u8; 2],
+        inputs: [u8; 2],
+        directions: [u8; 2],
+    }
+
+    impl FakeExpander {
+        fn port(port: u8) -> Result<usize, embedded_hal::digital::ErrorKind> {
+            match port {
+                0 | 1 => Ok(port.into()),
+                _ => Err(embedded_hal::digital::ErrorKind::Other),
+            }
+        }
+    }
+
+    impl ErrorType for FakeExpander {
+        type Error = embedded_hal::digital::ErrorKind;
+    }
+
+    impl GpioExpander for FakeExpander {
+        fn port_count(&self) -> u8 {
+            2
+        }
+
+        fn read_port(&mut self, port: u8) -> Result<u8, Self::Error> {
+            let port = Self::port(port)?;
+            let outputs = self.directions[port];
+            Ok(self.latch[port] & outputs | self.inputs[port] & !outputs)
+        }
+
+        fn write_port(&mut self, port: u8, value: u8, output_mask: u8) -> Result<(), Self::Error> {
+            let port = Self::port(port)?;
+            self.latch[port] = self.latch[port] & !output_mask | value & output_mask;
+            Ok(())
+        }
+
+        fn set_direction(&mut self, port: u8, dir_mask: u8) -> Result<(), Self::Error> {
+            self.directions[Self::port(port)?] = dir_mask;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn expander_pins() {
+        let mut expander = FakeExpander::default();
+        expander.set_direction(1, 0x0F).unwrap();
+        expander.inputs = [0b0000_1000, 0xF0];
+        let expander = core::cell::RefCell::new(expander);
+
+        let mut button = ExpanderPin::new(&expander, 0, 3);
+        let mut other = ExpanderPin::new(&expander, 0, 2);
+        let mut led = ExpanderPin::new(&expander, 1, 1);
+        let mut led2 = ExpanderPin::new(&expander, 1, 2);
+        assert!(button.is_high().unwrap());
+        assert!(other.is_low().unwrap());
+
+        led.set_high().unwrap();
+        led2.set_high().unwrap();
+        led.set_low().unwrap();
+        assert_eq!(expander.borrow().latch[1], 0b0000_0100);
+        assert!(led2.is_high().unwrap());
+
+        let mut missing = ExpanderPin::new(&expander, 2, 0);
+        assert_eq!(
+            missing.set_high(),
+            Err(embedded_hal::digital::ErrorKind::Other)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn expander_pin_out_of_range() {
+        let expander = core::cell::RefCell::new(FakeExpander::default());
+        ExpanderPin::new(&expander, 0, 8);
+    }
+
+    #[test]
+    fn pin_group_bit_order() {
+        let mut group = PinGroup::new(core::array::from_fn::<_, 10, _>(|_| FakePin::default()));
+        group.write_port(0b10_0000_0101, 0x3FF).unwrap();
+        let levels: Vec<bool> = group.pins_mut().iter().map(|pin| pin.high).collect();
+        assert_eq!(
+            levels,
+            [true, false, true, false, false, false, false, false, false, true]
+        );
+        assert_eq!(GpioPort::read_port(&mut group), Ok(0b10_0000_0101));
+
+        // Only the masked pins change.
+        GpioPort::write_port(&mut group, 0b00_0000_0010, 0b00_0000_0011).unwrap();
+        assert_eq!(group.read_port(), Ok(0b10_0000_0110));
+    }
+
+    /// Output pin failing on every access if it is broken.
+    struct FlakyPin {
+        high: bool,
+        broken: bool,
+    }
+
+    impl ErrorType for FlakyPin {
+        type Error = embedded_hal::digital::ErrorKind;
+    }
+
+    impl OutputPin for FlakyPin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.set_state(PinState::Low)
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.set_state(PinState::High)
+        }
+
+        fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+            if self.broken {
+                return Err(embedded_hal::digital::ErrorKind::Other);
+            }
+            self.high = state == PinState::High;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pin_group_reports_the_failed_pin() {
+        let mut group = PinGroup::new([false, true, false].map(|broken| FlakyPin {
+            high: false,
+            broken,
+        }));
+        // The broken pin is skipped when it is not in the mask.
+        group.write_port(0b101, 0b101).unwrap();
+        assert_eq!(
+            group.write_port(0b000, 0b111),
+            Err(PinGroupError {
+                pin: 1,
+                error: embedded_hal::digital::ErrorKind::Other
+            })
+        );
+        let pins = group.release();
+        assert!(!pins[0].high);
+        // Pins after the failed one are not written.
+        assert!(pins[2].high);
+    }
+}
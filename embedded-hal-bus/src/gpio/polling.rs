@@ -0,0 +1,122 @@
+use embedded_hal::digital::{ErrorType, InputPin};
+use embedded_hal_async::{delay::DelayNs, digital::Wait};
+
+/// Default poll interval of [`PollingWait`], in nanoseconds: 1 ms.
+pub const DEFAULT_POLL_INTERVAL_NS: u32 = 1_000_000;
+
+/// [`Wait`] implementation polling an [`InputPin`], for HALs without interrupt-driven inputs.
+///
+/// The pin is sampled, then the task waits for the poll interval with the async [`DelayNs`]
+/// before sampling it again, until it reaches the awaited level. Edges are detected by waiting for
+/// the opposite level first, then for the awaited one.
+///
+/// Compared to an interrupt-driven implementation, the wait completes up to one poll interval
+/// after the pin changed, and the CPU wakes up at every interval: a shorter interval lowers the
+/// latency but costs more power. Pulses shorter than the interval may also be missed entirely.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_async::digital::Wait;
+/// use embedded_hal_bus::gpio::PollingWait;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::digital::{ErrorType, InputPin};
+/// # use embedded_hal_async::delay::DelayNs;
+/// # struct Pin;
+/// # impl ErrorType for Pin { type Error = Infallible; }
+/// # impl InputPin for Pin {
+/// #     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// #     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(false) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { async fn delay_ns(&mut self, _ns: u32) {} }
+///
+/// async fn wait_data_ready(drdy: Pin, delay: Delay) -> Result<(), Infallible> {
+///     // Check the data-ready line every 100 µs.
+///     let mut drdy = PollingWait::new(drdy, delay).with_poll_interval_ns(100_000);
+///     drdy.wait_for_high().await
+/// }
+/// ```
+pub struct PollingWait<P, D> {
+    pin: P,
+    delay: D,
+    poll_interval_ns: u32,
+}
+
+impl<P, D> PollingWait<P, D> {
+    /// Create a new [`PollingWait`], polling every [`DEFAULT_POLL_INTERVAL_NS`].
+    #[inline]
+    pub fn new(pin: P, delay: D) -> Self {
+        Self {
+            pin,
+            delay,
+            poll_interval_ns: DEFAULT_POLL_INTERVAL_NS,
+        }
+    }
+
+    /// Set the interval between two samples of the pin, in nanoseconds.
+    #[inline]
+    pub fn with_poll_interval_ns(mut self, ns: u32) -> Self {
+        self.poll_interval_ns = ns;
+        self
+    }
+
+    /// Release the inner pin and delay.
+    #[inline]
+    pub fn release(self) -> (P, D) {
+        (self.pin, self.delay)
+    }
+}
+
+impl<P: InputPin, D: DelayNs> PollingWait<P, D> {
+    /// Poll the inner pin until it reads `high`.
+    async fn poll_level(&mut self, high: bool) -> Result<(), P::Error> {
+        while self.pin.is_high()? != high {
+            self.delay.delay_ns(self.poll_interval_ns).await;
+        }
+        Ok(())
+    }
+}
+
+impl<P: ErrorType, D> ErrorType for PollingWait<P, D> {
+    type Error = P::Error;
+}
+
+impl<P: InputPin, D> InputPin for PollingWait<P, D> {
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+}
+
+impl<P: InputPin, D: DelayNs> Wait for PollingWait<P, D> {
+    #[inline]
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(true).await
+    }
+
+    #[inline]
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(false).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(false).await?;
+        self.poll_level(true).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.poll_level(true).await?;
+        self.poll_level(false).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        let initial = self.pin.is_high()?;
+        self.poll_level(!initial).await
+    }
+}
@@ -0,0 +1,183 @@
+use core::fmt::{self, Display, Formatter};
+use embedded_hal::i2c::SevenBitAddress;
+
+use super::AddressedDevice;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Registry of named I2C devices, mapping their names to their addresses at runtime.
+///
+/// Boards with several instances of a chip, or chips whose address depends on strapping pins, can register
+/// the address of each device from their configuration, and have the application access the devices by
+/// name. Up to `N` devices can be registered: the registry is stored inline, without allocation. Lookups
+/// are linear searches, which is fast for the handful of devices of a bus.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c::I2cAddressBook;
+/// # use embedded_hal::i2c::{self, ErrorKind, I2c, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         assert_eq!(address, 0x49);
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+/// # let board_revision = 2;
+///
+/// let mut devices = I2cAddressBook::<_, 4>::new(i2c);
+/// devices.register("temperature", 0x48).unwrap();
+/// if board_revision >= 2 {
+///     // The address pin of the sensor is tied high on newer boards.
+///     devices.register("temperature", 0x49).unwrap();
+/// }
+///
+/// let mut sensor = devices.with_device("temperature").unwrap();
+/// sensor.write(&[0x01, 0x60])?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct I2cAddressBook<'a, T, const N: usize> {
+    i2c: T,
+    entries: [(&'a str, SevenBitAddress); N],
+    len: usize,
+}
+
+impl<'a, T, const N: usize> I2cAddressBook<'a, T, N> {
+    /// Create a new, empty [`I2cAddressBook`], with room for `N` devices.
+    #[inline]
+    pub fn new(i2c: T) -> Self {
+        Self {
+            i2c,
+            entries: [("", 0); N],
+            len: 0,
+        }
+    }
+
+    /// Register the device `name` at `address`.
+    ///
+    /// If `name` is already registered, its address is replaced. Otherwise, an error is returned if `N`
+    /// devices are already registered.
+    pub fn register(
+        &mut self,
+        name: &'a str,
+        address: SevenBitAddress,
+    ) -> Result<(), AddressBookFull> {
+        if let Some(entry) = self.entries[..self.len]
+            .iter_mut()
+            .find(|(n, _)| *n == name)
+        {
+            entry.1 = address;
+            return Ok(());
+        }
+        let entry = self.entries.get_mut(self.len).ok_or(AddressBookFull)?;
+        *entry = (name, address);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the address of the device `name`, if it is registered.
+    #[inline]
+    pub fn address(&self, name: &str) -> Option<SevenBitAddress> {
+        self.entries[..self.len]
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, address)| address)
+    }
+
+    /// Returns the registered devices, as `(name, address)` pairs, in registration order.
+    #[inline]
+    pub fn devices(&self) -> &[(&'a str, SevenBitAddress)] {
+        &self.entries[..self.len]
+    }
+
+    /// Borrow the bus for the device `name`, sending every operation to its registered address.
+    ///
+    /// Returns `None` if `name` is not registered.
+    #[inline]
+    pub fn with_device(&mut self, name: &str) -> Option<AddressedDevice<&mut T>> {
+        let address = self.address(name)?;
+        Some(AddressedDevice::new(&mut self.i2c, address))
+    }
+
+    /// Get a mutable reference to the inner bus.
+    #[inline]
+    pub fn bus_mut(&mut self) -> &mut T {
+        &mut self.i2c
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> T {
+        self.i2c
+    }
+}
+
+/// Error returned by [`I2cAddressBook::register`] when all the entries are used.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct AddressBookFull;
+
+impl Display for AddressBookFull {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "I2C address book is full")
+    }
+}
+
+impl core::error::Error for AddressBookFull {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::i2c::{ErrorType, I2c, Operation};
+
+    /// Bus recording the address of the last transaction.
+    #[derive(Default)]
+    struct FakeBus {
+        last: Option<u8>,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = Infallible;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Infallible> {
+            self.last = Some(address);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn devices_are_accessed_by_name() {
+        let mut book = I2cAddressBook::<_, 2>::new(FakeBus::default());
+        book.register("rtc", 0x68).unwrap();
+        book.register("eeprom", 0x50).unwrap();
+
+        book.with_device("eeprom").unwrap().read(&mut [0]).unwrap();
+        assert_eq!(book.bus_mut().last.take(), Some(0x50));
+        book.with_device("rtc").unwrap().write(&[0]).unwrap();
+        assert_eq!(book.bus_mut().last.take(), Some(0x68));
+        assert!(book.with_device("imu").is_none());
+        assert_eq!(book.address("imu"), None);
+    }
+
+    #[test]
+    fn register_replaces_or_fails_when_full() {
+        let mut book = I2cAddressBook::<_, 2>::new(FakeBus::default());
+        book.register("rtc", 0x68).unwrap();
+        book.register("eeprom", 0x50).unwrap();
+        assert_eq!(book.register("imu", 0x6A), Err(AddressBookFull));
+        // Remapping a registered device does not need a free entry.
+        book.register("rtc", 0x69).unwrap();
+        assert_eq!(book.devices(), [("rtc", 0x69), ("eeprom", 0x50)]);
+    }
+}
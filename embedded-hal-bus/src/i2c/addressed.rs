@@ -0,0 +1,243 @@
+use embedded_hal::i2c::{AddressMode, ErrorType, I2c, Operation, SevenBitAddress, TenBitAddress};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use super::{Endianness, RegisterAccess, RegisterAddress};
+
+/// I2C device bound to a fixed address.
+///
+/// This wraps a bus, or a shared bus device such as [`RefCellDevice`](super::RefCellDevice), and
+/// sends every operation to the address given at construction. Drivers for chips with a
+/// strapping-selectable address can store an [`AddressedDevice`] instead of a bus and an
+/// address.
+///
+/// With the `async` feature, the `*_async` methods perform the same operations on async buses.
+///
+/// # Example
+///
+/// ```
+/// use core::cell::RefCell;
+/// use embedded_hal_bus::i2c::{AddressedDevice, RefCellDevice};
+/// # use embedded_hal::i2c::{self, ErrorKind, I2c, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// let bus = RefCell::new(i2c);
+/// // Two identical sensors, with their address pin tied low and high.
+/// let mut left = AddressedDevice::new(RefCellDevice::new(&bus), 0x48);
+/// let mut right = AddressedDevice::new(RefCellDevice::new(&bus), 0x49);
+///
+/// let mut temperature = [0; 2];
+/// left.write_read(&[0x00], &mut temperature)?;
+/// right.write_register_u8(0x01u8, 0x60)?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct AddressedDevice<T, A: AddressMode = SevenBitAddress> {
+    i2c: T,
+    address: A,
+}
+
+impl<T> AddressedDevice<T, SevenBitAddress> {
+    /// Create a new [`AddressedDevice`], at the 7-bit `address`.
+    #[inline]
+    pub fn new(i2c: T, address: SevenBitAddress) -> Self {
+        Self { i2c, address }
+    }
+}
+
+impl<T> AddressedDevice<T, TenBitAddress> {
+    /// Create a new [`AddressedDevice`], at the 10-bit `address`.
+    #[inline]
+    pub fn new_ten_bit(i2c: T, address: TenBitAddress) -> Self {
+        Self { i2c, address }
+    }
+}
+
+impl<T, A: AddressMode> AddressedDevice<T, A> {
+    /// Get the address of the device.
+    #[inline]
+    pub fn address(&self) -> A {
+        self.address
+    }
+
+    /// Get a mutable reference to the inner bus.
+    ///
+    /// Operations performed through it are not bound to the address of the device.
+    #[inline]
+    pub fn bus_mut(&mut self) -> &mut T {
+        &mut self.i2c
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> T {
+        self.i2c
+    }
+}
+
+impl<T, A: AddressMode> From<(T, A)> for AddressedDevice<T, A> {
+    /// Bind the bus to the address, from a `(bus, address)` tuple.
+    #[inline]
+    fn from((i2c, address): (T, A)) -> Self {
+        Self { i2c, address }
+    }
+}
+
+impl<T: ErrorType, A: AddressMode> ErrorType for AddressedDevice<T, A> {
+    type Error = T::Error;
+}
+
+impl<T: I2c<A>, A: AddressMode> AddressedDevice<T, A> {
+    /// Read `read.len()` bytes from the device.
+    ///
+    /// See [`I2c::read`].
+    #[inline]
+    pub fn read(&mut self, read: &mut [u8]) -> Result<(), T::Error> {
+        self.i2c.read(self.address, read)
+    }
+
+    /// Write `write` to the device.
+    ///
+    /// See [`I2c::write`].
+    #[inline]
+    pub fn write(&mut self, write: &[u8]) -> Result<(), T::Error> {
+        self.i2c.write(self.address, write)
+    }
+
+    /// Write `write` to the device, then read `read.len()` bytes in the same transaction.
+    ///
+    /// See [`I2c::write_read`].
+    #[inline]
+    pub fn write_read(&mut self, write: &[u8], read: &mut [u8]) -> Result<(), T::Error> {
+        self.i2c.write_read(self.address, write, read)
+    }
+
+    /// Execute the provided operations on the device, in a single transaction.
+    ///
+    /// See [`I2c::transaction`].
+    #[inline]
+    pub fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result<(), T::Error> {
+        self.i2c.transaction(self.address, operations)
+    }
+}
+
+impl<T: I2c> AddressedDevice<T> {
+    /// Read the 8-bit register `reg`.
+    #[inline]
+    pub fn read_register_u8<R: RegisterAddress>(&mut self, reg: R) -> Result<u8, T::Error> {
+        self.i2c.read_register_u8(self.address, reg)
+    }
+
+    /// Write `value` to the 8-bit register `reg`.
+    #[inline]
+    pub fn write_register_u8<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        value: u8,
+    ) -> Result<(), T::Error> {
+        self.i2c.write_register_u8(self.address, reg, value)
+    }
+
+    /// Read the 16-bit register `reg`, transferred in `endianness` order.
+    #[inline]
+    pub fn read_register_u16<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        endianness: Endianness,
+    ) -> Result<u16, T::Error> {
+        self.i2c.read_register_u16(self.address, reg, endianness)
+    }
+
+    /// Write `value` to the 16-bit register `reg`, transferred in `endianness` order.
+    #[inline]
+    pub fn write_register_u16<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        value: u16,
+        endianness: Endianness,
+    ) -> Result<(), T::Error> {
+        self.i2c
+            .write_register_u16(self.address, reg, value, endianness)
+    }
+
+    /// Read consecutive registers starting at `reg`.
+    #[inline]
+    pub fn read_registers<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        buf: &mut [u8],
+    ) -> Result<(), T::Error> {
+        self.i2c.read_registers(self.address, reg, buf)
+    }
+
+    /// Write consecutive registers starting at `reg`.
+    #[inline]
+    pub fn write_registers<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        data: &[u8],
+    ) -> Result<(), T::Error> {
+        self.i2c.write_registers(self.address, reg, data)
+    }
+
+    /// Read the 8-bit register `reg`, and write back the value returned by `f`.
+    ///
+    /// See [`RegisterAccess::modify_register`].
+    #[inline]
+    pub fn modify_register<R: RegisterAddress>(
+        &mut self,
+        reg: R,
+        f: impl FnOnce(u8) -> u8,
+    ) -> Result<u8, T::Error> {
+        self.i2c.modify_register(self.address, reg, f)
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: AsyncI2c<A>, A: AddressMode> AddressedDevice<T, A> {
+    /// Read `read.len()` bytes from the device.
+    ///
+    /// See [`AsyncI2c::read`].
+    #[inline]
+    pub async fn read_async(&mut self, read: &mut [u8]) -> Result<(), T::Error> {
+        self.i2c.read(self.address, read).await
+    }
+
+    /// Write `write` to the device.
+    ///
+    /// See [`AsyncI2c::write`].
+    #[inline]
+    pub async fn write_async(&mut self, write: &[u8]) -> Result<(), T::Error> {
+        self.i2c.write(self.address, write).await
+    }
+
+    /// Write `write` to the device, then read `read.len()` bytes in the same transaction.
+    ///
+    /// See [`AsyncI2c::write_read`].
+    #[inline]
+    pub async fn write_read_async(
+        &mut self,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), T::Error> {
+        self.i2c.write_read(self.address, write, read).await
+    }
+
+    /// Execute the provided operations on the device, in a single transaction.
+    ///
+    /// See [`AsyncI2c::transaction`].
+    #[inline]
+    pub async fn transaction_async(
+        &mut self,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), T::Error> {
+        self.i2c.transaction(self.address, operations).await
+    }
+}
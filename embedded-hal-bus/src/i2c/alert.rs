@@ -0,0 +1,213 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::digital::InputPin;
+use embedded_hal::i2c::{Error, ErrorKind, I2c, SevenBitAddress};
+use embedded_hal::smbus::Smbus;
+#[cfg(feature = "async")]
+use embedded_hal_async::digital::Wait;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// SMBALERT# input of an SMBus host.
+///
+/// The line is active-low, and shared by all the devices of the bus: [`poll`](SmbusAlertPin::poll) reads
+/// the [Alert Response Address](embedded_hal::smbus::ALERT_RESPONSE_ADDRESS) while the line is asserted,
+/// to find which device raised the alert. Call it until it returns `None`, since several devices may
+/// alert at the same time.
+///
+/// With the `async` feature, [`wait_for_alert`](SmbusAlertPin::wait_for_alert) waits for the line to be
+/// asserted, for example from the interrupt of the pin.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c::SmbusAlertPin;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::digital::{self, InputPin};
+/// # use embedded_hal::i2c::{self, ErrorKind, I2c, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         if let [Operation::Read(buf)] = operations { buf[0] = 0x5C << 1; }
+/// #         Ok(())
+/// #     }
+/// # }
+/// # struct Pin(bool);
+/// # impl digital::ErrorType for Pin { type Error = Infallible; }
+/// # impl InputPin for Pin {
+/// #     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(core::mem::replace(&mut self.0, true)) }
+/// #     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(!self.is_high()?) }
+/// # }
+/// # let (mut i2c, pin) = (I2c0, Pin(false));
+///
+/// let mut alert = SmbusAlertPin::new(pin);
+/// while let Some(address) = alert.poll(&mut i2c)? {
+///     // Read the status registers of the regulator at `address`...
+///     assert_eq!(address, 0x5C);
+/// }
+/// # Ok::<(), embedded_hal_bus::i2c::SmbusAlertError<Infallible, ErrorKind>>(())
+/// ```
+pub struct SmbusAlertPin<P> {
+    pin: P,
+}
+
+impl<P: InputPin> SmbusAlertPin<P> {
+    /// Create a new [`SmbusAlertPin`].
+    #[inline]
+    pub fn new(pin: P) -> Self {
+        Self { pin }
+    }
+
+    /// Release the inner pin.
+    #[inline]
+    pub fn release(self) -> P {
+        self.pin
+    }
+
+    /// Returns `true` if a device is pulling SMBALERT# low.
+    #[inline]
+    pub fn is_asserted(&mut self) -> Result<bool, P::Error> {
+        self.pin.is_low()
+    }
+
+    /// Returns the address of a device raising an alert, if SMBALERT# is asserted.
+    ///
+    /// The device releases the line once it has sent its address, unless another device is also alerting.
+    pub fn poll<I: I2c>(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Option<SevenBitAddress>, SmbusAlertError<P::Error, I::Error>> {
+        if !self.is_asserted().map_err(SmbusAlertError::Pin)? {
+            return Ok(None);
+        }
+        i2c.read_alert_response()
+            .map(Some)
+            .map_err(SmbusAlertError::I2c)
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<P: InputPin + Wait> SmbusAlertPin<P> {
+    /// Wait until a device pulls SMBALERT# low.
+    ///
+    /// This returns immediately if the line is already asserted.
+    #[inline]
+    pub async fn wait_for_alert(&mut self) -> Result<(), P::Error> {
+        self.pin.wait_for_low().await
+    }
+}
+
+/// Error type for [`SmbusAlertPin`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum SmbusAlertError<PIN, I2C> {
+    /// Reading SMBALERT# failed.
+    Pin(PIN),
+    /// Reading the Alert Response Address failed.
+    I2c(I2C),
+}
+
+impl<PIN: Display, I2C: Display> Display for SmbusAlertError<PIN, I2C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Pin(e) => write!(f, "SMBALERT# pin error: {e}"),
+            Self::I2c(e) => write!(f, "I2C bus error: {e}"),
+        }
+    }
+}
+
+impl<PIN: Debug + Display, I2C: Debug + Display> core::error::Error for SmbusAlertError<PIN, I2C> {}
+
+impl<PIN: Debug, I2C: Error> Error for SmbusAlertError<PIN, I2C> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Pin(_) => ErrorKind::Other,
+            Self::I2c(e) => e.kind(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::digital::ErrorType as PinErrorType;
+    use embedded_hal::i2c::{ErrorType, NoAcknowledgeSource, Operation};
+    use std::{cell::RefCell, vec::Vec};
+
+    /// Devices of the bus which are alerting, pulling SMBALERT# low. The lowest address responds to
+    /// the Alert Response Address.
+    type Alerting = RefCell<Vec<u8>>;
+
+    struct FakeBus<'a>(&'a Alerting);
+
+    impl ErrorType for FakeBus<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus<'_> {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), ErrorKind> {
+            assert_eq!(address, embedded_hal::smbus::ALERT_RESPONSE_ADDRESS);
+            let mut alerting = self.0.borrow_mut();
+            alerting.sort_unstable();
+            if alerting.is_empty() {
+                return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+            }
+            if let [Operation::Read(buf)] = operations {
+                buf[0] = alerting.remove(0) << 1;
+            }
+            Ok(())
+        }
+    }
+
+    struct FakePin<'a>(&'a Alerting);
+
+    impl PinErrorType for FakePin<'_> {
+        type Error = Infallible;
+    }
+
+    impl InputPin for FakePin<'_> {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.0.borrow().is_empty())
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            Ok(!self.0.borrow().is_empty())
+        }
+    }
+
+    #[test]
+    fn polls_every_alerting_device() {
+        let alerting = Alerting::new(std::vec![0x5C, 0x40]);
+        let mut i2c = FakeBus(&alerting);
+        let mut alert = SmbusAlertPin::new(FakePin(&alerting));
+
+        assert_eq!(alert.poll(&mut i2c), Ok(Some(0x40)));
+        assert_eq!(alert.poll(&mut i2c), Ok(Some(0x5C)));
+        assert!(!alert.is_asserted().unwrap());
+        // The Alert Response Address is not read while the line is released.
+        assert_eq!(alert.poll(&mut i2c), Ok(None));
+    }
+
+    #[test]
+    fn reports_i2c_errors() {
+        let alerting = Alerting::new(std::vec![0x40]);
+        let mut alert = SmbusAlertPin::new(FakePin(&alerting));
+        // No device responds, although the line is asserted.
+        let empty = Alerting::default();
+        let err = alert.poll(&mut FakeBus(&empty)).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
+        );
+    }
+}
@@ -1,7 +1,12 @@
-use embedded_hal::i2c::{Error, ErrorKind, ErrorType, I2c};
+use embedded_hal::i2c::{AddressMode, Error, ErrorKind, ErrorType, I2c, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
 
 use crate::util::AtomicCell;
 
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
 /// Atomics-based shared bus [`I2c`] implementation.
 ///
 /// Sharing is implemented with a [`AtomicDevice`], which consists of an `UnsafeCell` and an `AtomicBool` "locked" flag.
@@ -20,6 +25,14 @@ use crate::util::AtomicCell;
 /// This primitive is particularly well-suited for applications that have external arbitration
 /// rules that prevent `Busy` errors in the first place, such as the RTIC framework.
 ///
+/// With the `async` feature, [`AtomicDevice`] also implements the async [`I2c`](AsyncI2c) trait.
+/// Async transactions never return `Busy` errors: when the bus is in use, they wait for it to be released
+/// without blocking the executor, which makes [`AtomicDevice`] usable to share a bus between several async tasks.
+/// Up to 4 tasks wait for the bus without being polled, and are woken up when it is released, without using
+/// critical sections. When more tasks wait at once, the others are polled again until one of them is served.
+/// If an async transaction is cancelled, the bus is released. Use [`TryAtomicDevice`] to get `Busy` errors
+/// from async transactions too.
+///
 /// # Examples
 ///
 /// Assuming there is a pressure sensor with address `0x42` on the same bus as a temperature sensor
@@ -81,6 +94,7 @@ pub struct AtomicDevice<'a, T> {
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 /// Wrapper type for errors originating from the atomically-checked I2C bus manager.
 pub enum AtomicError<T: Error> {
     /// This error is returned if the I2C bus was already in use when an operation was attempted,
@@ -105,7 +119,7 @@ unsafe impl<T> Send for AtomicDevice<'_, T> {}
 
 impl<'a, T> AtomicDevice<'a, T>
 where
-    T: I2c,
+    T: ErrorType,
 {
     /// Create a new `AtomicDevice`.
     #[inline]
@@ -117,21 +131,13 @@ where
     where
         F: FnOnce(&mut T) -> Result<R, <T as ErrorType>::Error>,
     {
-        self.bus
-            .busy
-            .compare_exchange(
-                false,
-                true,
-                core::sync::atomic::Ordering::SeqCst,
-                core::sync::atomic::Ordering::SeqCst,
-            )
-            .map_err(|_| AtomicError::<T::Error>::Busy)?;
+        if !self.bus.try_lock() {
+            return Err(AtomicError::Busy);
+        }
 
         let result = f(unsafe { &mut *self.bus.bus.get() });
 
-        self.bus
-            .busy
-            .store(false, core::sync::atomic::Ordering::SeqCst);
+        self.bus.unlock();
 
         result.map_err(AtomicError::Other)
     }
@@ -139,41 +145,153 @@ where
 
 impl<T> ErrorType for AtomicDevice<'_, T>
 where
-    T: I2c,
+    T: ErrorType,
 {
     type Error = AtomicError<T::Error>;
 }
 
-impl<T> I2c for AtomicDevice<'_, T>
+impl<T, A: AddressMode> I2c<A> for AtomicDevice<'_, T>
 where
-    T: I2c,
+    T: I2c<A>,
 {
     #[inline]
-    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
         self.lock(|bus| bus.read(address, read))
     }
 
     #[inline]
-    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
         self.lock(|bus| bus.write(address, write))
     }
 
     #[inline]
-    fn write_read(
-        &mut self,
-        address: u8,
-        write: &[u8],
-        read: &mut [u8],
-    ) -> Result<(), Self::Error> {
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
         self.lock(|bus| bus.write_read(address, write, read))
     }
 
     #[inline]
     fn transaction(
         &mut self,
-        address: u8,
-        operations: &mut [embedded_hal::i2c::Operation<'_>],
+        address: A,
+        operations: &mut [Operation<'_>],
     ) -> Result<(), Self::Error> {
         self.lock(|bus| bus.transaction(address, operations))
     }
 }
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for AtomicDevice<'_, T>
+where
+    T: AsyncI2c<A>,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut guard = self.bus.lock_async().await;
+        guard
+            .bus()
+            .transaction(address, operations)
+            .await
+            .map_err(AtomicError::Other)
+    }
+}
+
+/// Atomics-based shared bus [`I2c`] implementation, returning `Busy` errors from async transactions.
+///
+/// This is the same as [`AtomicDevice`], except that async transactions started while the bus is in use do
+/// not wait for it: they return [`AtomicError::Busy`] right away. The bus is claimed with a single
+/// compare-exchange at the start of the transaction, held across its `.await` points, and released when it
+/// completes or is cancelled. This suits applications where contention is a bug to detect, or where a task
+/// would rather drop or defer its transfer than wait for the bus.
+///
+/// Blocking transactions behave as those of [`AtomicDevice`].
+#[cfg(feature = "async")]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(
+        feature = "async",
+        any(feature = "portable-atomic", target_has_atomic = "8")
+    )))
+)]
+pub struct TryAtomicDevice<'a, T> {
+    device: AtomicDevice<'a, T>,
+}
+
+#[cfg(feature = "async")]
+unsafe impl<T> Send for TryAtomicDevice<'_, T> {}
+
+#[cfg(feature = "async")]
+impl<'a, T> TryAtomicDevice<'a, T>
+where
+    T: ErrorType,
+{
+    /// Create a new `TryAtomicDevice`.
+    #[inline]
+    pub fn new(bus: &'a AtomicCell<T>) -> Self {
+        Self {
+            device: AtomicDevice::new(bus),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> ErrorType for TryAtomicDevice<'_, T>
+where
+    T: ErrorType,
+{
+    type Error = AtomicError<T::Error>;
+}
+
+#[cfg(feature = "async")]
+impl<T, A: AddressMode> I2c<A> for TryAtomicDevice<'_, T>
+where
+    T: I2c<A>,
+{
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.device.read(address, read)
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.device.write(address, write)
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.device.write_read(address, write, read)
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.device.transaction(address, operations)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T, A: AddressMode> AsyncI2c<A> for TryAtomicDevice<'_, T>
+where
+    T: AsyncI2c<A>,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut guard = self.device.bus.try_lock_guard().ok_or(AtomicError::Busy)?;
+        guard
+            .bus()
+            .transaction(address, operations)
+            .await
+            .map_err(AtomicError::Other)
+    }
+}
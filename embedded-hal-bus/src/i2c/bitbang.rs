@@ -0,0 +1,743 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal::i2c::{
+    Error, ErrorKind, ErrorType, I2c, I2cBusSpeed, NoAcknowledgeSource, Operation,
+};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Configuration of a [`BitBangI2c`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct BitBangConfig {
+    /// SCL clock frequency, in Hz.
+    ///
+    /// This is an upper bound: the time spent toggling the pins adds to the configured delays.
+    pub frequency_hz: u32,
+    /// Maximum time a target may hold SCL low (clock stretching), in microseconds.
+    pub stretch_timeout_us: u32,
+}
+
+impl Default for BitBangConfig {
+    /// Standard mode (100 kHz), with a 1 ms clock stretching timeout.
+    fn default() -> Self {
+        Self {
+            frequency_hz: 100_000,
+            stretch_timeout_us: 1_000,
+        }
+    }
+}
+
+/// Error type for [`BitBangI2c`] operations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum BitBangError<SCL, SDA> {
+    /// The target did not acknowledge its address or a data byte.
+    NoAcknowledge(NoAcknowledgeSource),
+    /// SDA was low while it was released, another controller is using the bus.
+    ArbitrationLoss,
+    /// SCL was held low by a target for longer than [`BitBangConfig::stretch_timeout_us`].
+    ClockStretchTimeout,
+    /// Setting or reading the SCL pin failed.
+    Scl(SCL),
+    /// Setting or reading the SDA pin failed.
+    Sda(SDA),
+}
+
+impl<SCL: core::fmt::Debug, SDA: core::fmt::Debug> Error for BitBangError<SCL, SDA> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::NoAcknowledge(source) => ErrorKind::NoAcknowledge(*source),
+            Self::ArbitrationLoss => ErrorKind::ArbitrationLoss,
+            Self::ClockStretchTimeout => ErrorKind::Timeout,
+            Self::Scl(_) | Self::Sda(_) => ErrorKind::Other,
+        }
+    }
+}
+
+impl<SCL: core::fmt::Display, SDA: core::fmt::Display> core::fmt::Display
+    for BitBangError<SCL, SDA>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NoAcknowledge(source) => source.fmt(f),
+            Self::ArbitrationLoss => write!(f, "The arbitration was lost"),
+            Self::ClockStretchTimeout => write!(f, "SCL was held low for too long"),
+            Self::Scl(e) => write!(f, "SCL pin error: {e}"),
+            Self::Sda(e) => write!(f, "SDA pin error: {e}"),
+        }
+    }
+}
+
+impl<SCL, SDA> core::error::Error for BitBangError<SCL, SDA>
+where
+    SCL: core::fmt::Debug + core::fmt::Display,
+    SDA: core::fmt::Debug + core::fmt::Display,
+{
+}
+
+/// Software ("bit-banged") I2C controller.
+///
+/// Implements [`I2c`] on top of two GPIO pins and a [`DelayNs`], for when no hardware
+/// I2C peripheral is available.
+///
+/// Both pins must be configured as open-drain outputs with pull-ups (internal or external):
+/// `set_high` releases the line, `set_low` drives it low, and `is_high` reads the actual
+/// line level. Reading SCL back is used to support clock stretching, reading SDA back is
+/// used to receive data and detect arbitration loss.
+///
+/// Only 7-bit addresses are supported.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c::{BitBangConfig, BitBangI2c};
+/// # use core::convert::Infallible;
+/// # use embedded_hal::{delay::DelayNs, digital::{ErrorType, InputPin, OutputPin}};
+/// # struct Pin;
+/// # impl ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # impl InputPin for Pin {
+/// #     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// #     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(false) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _ns: u32) {} }
+/// # let (scl, sda, delay) = (Pin, Pin, Delay);
+///
+/// let config = BitBangConfig {
+///     frequency_hz: 400_000,
+///     ..Default::default()
+/// };
+/// let i2c = BitBangI2c::new(scl, sda, delay, config);
+/// ```
+pub struct BitBangI2c<SCL, SDA, D> {
+    scl: SCL,
+    sda: SDA,
+    delay: D,
+    half_period_ns: u32,
+    stretch_timeout_us: u32,
+}
+
+impl<SCL, SDA, D> BitBangI2c<SCL, SDA, D>
+where
+    SCL: OutputPin + InputPin,
+    SDA: OutputPin + InputPin,
+    D: DelayNs,
+{
+    /// Create a new [`BitBangI2c`].
+    ///
+    /// The pins are not touched until the first transaction. They should already
+    /// be released (high) to avoid glitches on the bus.
+    #[inline]
+    pub fn new(scl: SCL, sda: SDA, delay: D, config: BitBangConfig) -> Self {
+        Self {
+            scl,
+            sda,
+            delay,
+            half_period_ns: half_period_ns(config.frequency_hz),
+            stretch_timeout_us: config.stretch_timeout_us,
+        }
+    }
+
+    /// Release the pins and the delay.
+    #[inline]
+    pub fn release(self) -> (SCL, SDA, D) {
+        (self.scl, self.sda, self.delay)
+    }
+
+    fn wait(&mut self) {
+        self.delay.delay_ns(self.half_period_ns);
+    }
+
+    fn scl_low(&mut self) -> Result<(), BitBangError<SCL::Error, SDA::Error>> {
+        self.scl.set_low().map_err(BitBangError::Scl)
+    }
+
+    /// Release SCL, and wait for targets to stop stretching the clock.
+    fn scl_high(&mut self) -> Result<(), BitBangError<SCL::Error, SDA::Error>> {
+        self.scl.set_high().map_err(BitBangError::Scl)?;
+        let mut waited_us = 0;
+        while self.scl.is_low().map_err(BitBangError::Scl)? {
+            if waited_us >= self.stretch_timeout_us {
+                return Err(BitBangError::ClockStretchTimeout);
+            }
+            self.delay.delay_us(1);
+            waited_us += 1;
+        }
+        Ok(())
+    }
+
+    fn set_sda(&mut self, high: bool) -> Result<(), BitBangError<SCL::Error, SDA::Error>> {
+        if high {
+            self.sda.set_high().map_err(BitBangError::Sda)
+        } else {
+            self.sda.set_low().map_err(BitBangError::Sda)
+        }
+    }
+
+    fn sda_is_high(&mut self) -> Result<bool, BitBangError<SCL::Error, SDA::Error>> {
+        self.sda.is_high().map_err(BitBangError::Sda)
+    }
+
+    /// Generate a START condition, or a repeated START if a transfer is in progress.
+    ///
+    /// SCL is low when this returns.
+    fn start(&mut self) -> Result<(), BitBangError<SCL::Error, SDA::Error>> {
+        self.set_sda(true)?;
+        self.wait();
+        self.scl_high()?;
+        self.wait();
+        if !self.sda_is_high()? {
+            return Err(BitBangError::ArbitrationLoss);
+        }
+        self.set_sda(false)?;
+        self.wait();
+        self.scl_low()
+    }
+
+    /// Generate a STOP condition, leaving both lines released.
+    fn stop(&mut self) -> Result<(), BitBangError<SCL::Error, SDA::Error>> {
+        self.set_sda(false)?;
+        self.wait();
+        self.scl_high()?;
+        self.wait();
+        self.set_sda(true)?;
+        self.wait();
+        if !self.sda_is_high()? {
+            return Err(BitBangError::ArbitrationLoss);
+        }
+        Ok(())
+    }
+
+    /// Transmit a bit. SCL must be low, and is low when this returns.
+    fn write_bit(&mut self, bit: bool) -> Result<(), BitBangError<SCL::Error, SDA::Error>> {
+        self.set_sda(bit)?;
+        self.wait();
+        self.scl_high()?;
+        self.wait();
+        if bit && !self.sda_is_high()? {
+            return Err(BitBangError::ArbitrationLoss);
+        }
+        self.scl_low()
+    }
+
+    /// Receive a bit. SCL must be low, and is low when this returns.
+    fn read_bit(&mut self) -> Result<bool, BitBangError<SCL::Error, SDA::Error>> {
+        self.set_sda(true)?;
+        self.wait();
+        self.scl_high()?;
+        self.wait();
+        let bit = self.sda_is_high()?;
+        self.scl_low()?;
+        Ok(bit)
+    }
+
+    /// Transmit a byte, returning whether the target acknowledged it.
+    fn write_byte(&mut self, byte: u8) -> Result<bool, BitBangError<SCL::Error, SDA::Error>> {
+        for i in (0..8).rev() {
+            self.write_bit(byte & (1 << i) != 0)?;
+        }
+        Ok(!self.read_bit()?)
+    }
+
+    /// Receive a byte, acknowledging it if `ack` is true.
+    fn read_byte(&mut self, ack: bool) -> Result<u8, BitBangError<SCL::Error, SDA::Error>> {
+        let mut byte = 0;
+        for _ in 0..8 {
+            byte = (byte << 1) | u8::from(self.read_bit()?);
+        }
+        self.write_bit(!ack)?;
+        Ok(byte)
+    }
+
+    fn run(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), BitBangError<SCL::Error, SDA::Error>> {
+        let mut previous_is_read = None;
+        let mut operations = operations.iter_mut().peekable();
+        while let Some(operation) = operations.next() {
+            let is_read = matches!(operation, Operation::Read(_));
+            if previous_is_read != Some(is_read) {
+                self.start()?;
+                if !self.write_byte((address << 1) | u8::from(is_read))? {
+                    return Err(BitBangError::NoAcknowledge(NoAcknowledgeSource::Address));
+                }
+                previous_is_read = Some(is_read);
+            }
+
+            match operation {
+                Operation::Write(bytes) => {
+                    for byte in bytes.iter() {
+                        if !self.write_byte(*byte)? {
+                            return Err(BitBangError::NoAcknowledge(NoAcknowledgeSource::Data));
+                        }
+                    }
+                }
+                Operation::Read(buffer) => {
+                    // The last byte before a repeated START or a STOP is not acknowledged.
+                    let next_is_read = matches!(operations.peek(), Some(Operation::Read(_)));
+                    let len = buffer.len();
+                    for (i, byte) in buffer.iter_mut().enumerate() {
+                        *byte = self.read_byte(i + 1 < len || next_is_read)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<SCL, SDA, D> ErrorType for BitBangI2c<SCL, SDA, D>
+where
+    SCL: OutputPin + InputPin,
+    SDA: OutputPin + InputPin,
+{
+    type Error = BitBangError<SCL::Error, SDA::Error>;
+}
+
+/// Half period of SCL at `frequency_hz`, in nanoseconds.
+fn half_period_ns(frequency_hz: u32) -> u32 {
+    500_000_000 / frequency_hz.max(1)
+}
+
+/// Sets [`BitBangConfig::frequency_hz`], which never fails.
+impl<SCL, SDA, D> I2cBusSpeed for BitBangI2c<SCL, SDA, D>
+where
+    SCL: OutputPin + InputPin,
+    SDA: OutputPin + InputPin,
+{
+    #[inline]
+    fn set_clock_speed(&mut self, frequency_hz: u32) -> Result<(), Self::Error> {
+        self.half_period_ns = half_period_ns(frequency_hz);
+        Ok(())
+    }
+}
+
+impl<SCL, SDA, D> I2c for BitBangI2c<SCL, SDA, D>
+where
+    SCL: OutputPin + InputPin,
+    SDA: OutputPin + InputPin,
+    D: DelayNs,
+{
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+        match self.run(address, operations) {
+            // Release the bus to the other controller.
+            Err(BitBangError::ArbitrationLoss) => {
+                self.set_sda(true)?;
+                self.scl.set_high().map_err(BitBangError::Scl)?;
+                Err(BitBangError::ArbitrationLoss)
+            }
+            // Always try to return the bus to the idle state.
+            Err(e) => {
+                let _ = self.stop();
+                Err(e)
+            }
+            Ok(()) => self.stop(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+    use embedded_hal::digital;
+    use std::{rc::Rc, vec::Vec};
+
+    const ADDRESS: u8 = 0x42;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Start,
+        Stop,
+        Address { read: bool },
+        Received(u8),
+        Sent { byte: u8, acked: bool },
+    }
+
+    #[derive(Clone, Copy)]
+    enum Phase {
+        Idle,
+        Receive { byte: u8, bits: u8, address: bool },
+        AckSlot { transmit: bool },
+        Transmit { byte: u8, sent: u8 },
+        ControllerAck { byte: u8, acked: bool },
+    }
+
+    /// Open-drain bus wires, with a simulated target at `ADDRESS`.
+    struct Wire {
+        controller_scl: bool,
+        controller_sda: bool,
+        target_sda: bool,
+        stretch_per_clock: u32,
+        stretch_remaining: u32,
+        scl_stuck: bool,
+        nack_data: bool,
+        prev_scl: bool,
+        prev_sda: bool,
+        phase: Phase,
+        memory: Vec<u8>,
+        log: Vec<Event>,
+    }
+
+    impl Wire {
+        fn scl(&self) -> bool {
+            self.controller_scl && self.stretch_remaining == 0 && !self.scl_stuck
+        }
+
+        fn sda(&self) -> bool {
+            self.controller_sda && self.target_sda
+        }
+
+        fn update(&mut self) {
+            let (scl, sda) = (self.scl(), self.sda());
+            if scl && self.prev_scl && sda != self.prev_sda {
+                // SDA may only change while SCL is high for START and STOP conditions.
+                self.target_sda = true;
+                if sda {
+                    self.log.push(Event::Stop);
+                    self.phase = Phase::Idle;
+                } else {
+                    self.log.push(Event::Start);
+                    self.phase = Phase::Receive {
+                        byte: 0,
+                        bits: 0,
+                        address: true,
+                    };
+                }
+            } else if scl && !self.prev_scl {
+                self.on_scl_rise(sda);
+            } else if !scl && self.prev_scl {
+                self.on_scl_fall();
+            }
+            self.prev_scl = self.scl();
+            self.prev_sda = self.sda();
+        }
+
+        fn on_scl_rise(&mut self, sda: bool) {
+            match self.phase {
+                Phase::Receive {
+                    byte,
+                    bits,
+                    address,
+                } => {
+                    self.phase = Phase::Receive {
+                        byte: (byte << 1) | u8::from(sda),
+                        bits: bits + 1,
+                        address,
+                    }
+                }
+                Phase::ControllerAck { byte, .. } => {
+                    self.log.push(Event::Sent { byte, acked: !sda });
+                    self.phase = Phase::ControllerAck { byte, acked: !sda }
+                }
+                _ => {}
+            }
+        }
+
+        fn on_scl_fall(&mut self) {
+            match self.phase {
+                Phase::Receive {
+                    byte,
+                    bits: 8,
+                    address,
+                } => {
+                    let ack = if address {
+                        let matches = byte >> 1 == ADDRESS;
+                        if matches {
+                            self.log.push(Event::Address {
+                                read: byte & 1 == 1,
+                            });
+                        }
+                        matches
+                    } else {
+                        self.log.push(Event::Received(byte));
+                        !self.nack_data
+                    };
+                    if ack {
+                        self.target_sda = false;
+                        self.phase = Phase::AckSlot {
+                            transmit: address && byte & 1 == 1,
+                        };
+                    } else {
+                        self.phase = Phase::Idle;
+                    }
+                }
+                Phase::AckSlot { transmit: false } => {
+                    self.target_sda = true;
+                    self.phase = Phase::Receive {
+                        byte: 0,
+                        bits: 0,
+                        address: false,
+                    };
+                }
+                Phase::AckSlot { transmit: true } | Phase::ControllerAck { acked: true, .. } => {
+                    let byte = self.memory.remove(0);
+                    self.target_sda = byte & 0x80 != 0;
+                    self.phase = Phase::Transmit { byte, sent: 1 };
+                }
+                Phase::Transmit { byte, sent: 8 } => {
+                    self.target_sda = true;
+                    self.phase = Phase::ControllerAck { byte, acked: false };
+                }
+                Phase::Transmit { byte, sent } => {
+                    self.target_sda = byte & (0x80 >> sent) != 0;
+                    self.phase = Phase::Transmit {
+                        byte,
+                        sent: sent + 1,
+                    };
+                }
+                Phase::ControllerAck { acked: false, .. } => self.phase = Phase::Idle,
+                _ => {}
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    enum Line {
+        Scl,
+        Sda,
+    }
+
+    struct SimPin {
+        wire: Rc<RefCell<Wire>>,
+        line: Line,
+    }
+
+    impl digital::ErrorType for SimPin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for SimPin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.set(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.set(true);
+            Ok(())
+        }
+    }
+
+    impl SimPin {
+        fn set(&mut self, high: bool) {
+            let mut wire = self.wire.borrow_mut();
+            match self.line {
+                Line::Scl => {
+                    if high && !wire.controller_scl {
+                        wire.stretch_remaining = wire.stretch_per_clock;
+                    }
+                    wire.controller_scl = high;
+                }
+                Line::Sda => wire.controller_sda = high,
+            }
+            wire.update();
+        }
+    }
+
+    impl InputPin for SimPin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            let mut wire = self.wire.borrow_mut();
+            Ok(match self.line {
+                Line::Scl => {
+                    wire.stretch_remaining = wire.stretch_remaining.saturating_sub(1);
+                    wire.update();
+                    wire.scl()
+                }
+                Line::Sda => wire.sda(),
+            })
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    struct NoopDelay;
+
+    impl DelayNs for NoopDelay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    fn setup(
+        configure: impl FnOnce(&mut Wire),
+    ) -> (Rc<RefCell<Wire>>, BitBangI2c<SimPin, SimPin, NoopDelay>) {
+        let mut wire = Wire {
+            controller_scl: true,
+            controller_sda: true,
+            target_sda: true,
+            stretch_per_clock: 0,
+            stretch_remaining: 0,
+            scl_stuck: false,
+            nack_data: false,
+            prev_scl: true,
+            prev_sda: true,
+            phase: Phase::Idle,
+            memory: Vec::new(),
+            log: Vec::new(),
+        };
+        configure(&mut wire);
+        let wire = Rc::new(RefCell::new(wire));
+        let scl = SimPin {
+            wire: wire.clone(),
+            line: Line::Scl,
+        };
+        let sda = SimPin {
+            wire: wire.clone(),
+            line: Line::Sda,
+        };
+        let i2c = BitBangI2c::new(scl, sda, NoopDelay, BitBangConfig::default());
+        (wire, i2c)
+    }
+
+    fn is_idle(wire: &Rc<RefCell<Wire>>) -> bool {
+        let wire = wire.borrow();
+        wire.scl() && wire.sda()
+    }
+
+    #[test]
+    fn write_read_uses_repeated_start() {
+        let (wire, mut i2c) = setup(|wire| wire.memory = [0xA0, 0x5B].into());
+        let mut read = [0; 2];
+        i2c.write_read(ADDRESS, &[0x10], &mut read).unwrap();
+
+        assert_eq!(read, [0xA0, 0x5B]);
+        assert_eq!(
+            wire.borrow().log,
+            [
+                Event::Start,
+                Event::Address { read: false },
+                Event::Received(0x10),
+                Event::Start,
+                Event::Address { read: true },
+                Event::Sent {
+                    byte: 0xA0,
+                    acked: true
+                },
+                Event::Sent {
+                    byte: 0x5B,
+                    acked: false
+                },
+                Event::Stop,
+            ]
+        );
+        assert!(is_idle(&wire));
+    }
+
+    #[test]
+    fn adjacent_operations_of_the_same_type_are_merged() {
+        let (wire, mut i2c) = setup(|wire| wire.memory = [1, 2].into());
+        let (mut a, mut b) = ([0], [0]);
+        i2c.transaction(
+            ADDRESS,
+            &mut [
+                Operation::Write(&[0x01]),
+                Operation::Write(&[0x02]),
+                Operation::Read(&mut a),
+                Operation::Read(&mut b),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!((a, b), ([1], [2]));
+        assert_eq!(
+            wire.borrow().log,
+            [
+                Event::Start,
+                Event::Address { read: false },
+                Event::Received(0x01),
+                Event::Received(0x02),
+                Event::Start,
+                Event::Address { read: true },
+                Event::Sent {
+                    byte: 1,
+                    acked: true
+                },
+                Event::Sent {
+                    byte: 2,
+                    acked: false
+                },
+                Event::Stop,
+            ]
+        );
+    }
+
+    #[test]
+    fn address_nack() {
+        let (wire, mut i2c) = setup(|_| {});
+        let result = i2c.write(0x50, &[0x01]);
+
+        assert_eq!(
+            result.map_err(|e| e.kind()),
+            Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+        );
+        assert_eq!(wire.borrow().log, [Event::Start, Event::Stop]);
+        assert!(is_idle(&wire));
+    }
+
+    #[test]
+    fn data_nack() {
+        let (wire, mut i2c) = setup(|wire| wire.nack_data = true);
+        let result = i2c.write(ADDRESS, &[0x01, 0x02]);
+
+        assert_eq!(
+            result.map_err(|e| e.kind()),
+            Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data))
+        );
+        assert_eq!(
+            wire.borrow().log,
+            [
+                Event::Start,
+                Event::Address { read: false },
+                Event::Received(0x01),
+                Event::Stop
+            ]
+        );
+        assert!(is_idle(&wire));
+    }
+
+    #[test]
+    fn clock_stretching() {
+        let (wire, mut i2c) = setup(|wire| wire.stretch_per_clock = 5);
+        i2c.write(ADDRESS, &[0x5A]).unwrap();
+
+        assert_eq!(
+            wire.borrow().log,
+            [
+                Event::Start,
+                Event::Address { read: false },
+                Event::Received(0x5A),
+                Event::Stop
+            ]
+        );
+    }
+
+    #[test]
+    fn clock_stretching_timeout() {
+        let (wire, mut i2c) = setup(|wire| wire.scl_stuck = true);
+        let result = i2c.write(ADDRESS, &[0x5A]);
+
+        assert_eq!(result, Err(BitBangError::ClockStretchTimeout));
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Timeout);
+        assert!(wire.borrow().log.is_empty());
+    }
+}
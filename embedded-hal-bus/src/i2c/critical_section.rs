@@ -1,75 +1,130 @@
 use core::cell::RefCell;
 use critical_section::Mutex;
-use embedded_hal::i2c::{ErrorType, I2c};
+#[cfg(feature = "async")]
+use embedded_hal::i2c::{AddressMode, I2cBusSpeed, I2cPowerManagement, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
 
-/// `critical-section`-based shared bus [`I2c`] implementation.
+#[cfg(feature = "async")]
+use super::device::suspend_after;
+use super::{Device, DeviceWithClockSpeed, DeviceWithPowerManagement};
+#[cfg(feature = "async")]
+use crate::util::{poll_to_completion, BusMutex};
+
+/// `critical-section`-based shared bus [`I2c`](embedded_hal::i2c::I2c) implementation.
 ///
 /// Sharing is implemented with a `critical-section` [`Mutex`]. A critical section is taken for
 /// the entire duration of a transaction. This allows sharing a single bus across multiple threads (interrupt priority levels).
 /// The downside is critical sections typically require globally disabling interrupts, so `CriticalSectionDevice` will likely
 /// negatively impact real-time properties, such as interrupt latency. If you can, prefer using
 /// [`RefCellDevice`](super::RefCellDevice) instead, which does not require taking critical sections.
-pub struct CriticalSectionDevice<'a, T> {
-    bus: &'a Mutex<RefCell<T>>,
-}
-
-impl<'a, T> CriticalSectionDevice<'a, T> {
-    /// Create a new `CriticalSectionDevice`.
-    #[inline]
-    pub fn new(bus: &'a Mutex<RefCell<T>>) -> Self {
-        Self { bus }
-    }
-}
-
-impl<T> ErrorType for CriticalSectionDevice<'_, T>
-where
-    T: I2c,
-{
-    type Error = T::Error;
-}
+///
+/// To share a bus obtained at runtime between `'static` devices, for example moved to interrupt handlers,
+/// store it in a [`StaticBus`](crate::util::StaticBus).
+///
+/// # Async
+///
+/// With the `async` feature, `CriticalSectionDevice` also implements the async
+/// [`I2c`](embedded_hal_async::i2c::I2c) trait if the bus does. Since a critical section can't be held
+/// across an `.await` point, the critical section is taken for the whole transaction and the bus future
+/// is polled to completion in a busy loop, without yielding to the executor. Transactions from different
+/// devices therefore never interleave, and are never reported as "busy".
+///
+/// This requires the bus future to make progress when polled with interrupts disabled, e.g. by
+/// checking the peripheral status registers. A bus whose future only completes after being woken by an
+/// interrupt handler will deadlock.
+pub type CriticalSectionDevice<'a, T> = Device<&'a Mutex<RefCell<T>>>;
 
-impl<T> I2c for CriticalSectionDevice<'_, T>
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for CriticalSectionDevice<'_, T>
 where
-    T: I2c,
+    T: AsyncI2c<A>,
 {
     #[inline]
-    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
-        critical_section::with(|cs| {
-            let bus = &mut *self.bus.borrow_ref_mut(cs);
-            bus.read(address, read)
-        })
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus
+            .lock(|bus| poll_to_completion(bus.read(address, read)))
     }
 
     #[inline]
-    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
-        critical_section::with(|cs| {
-            let bus = &mut *self.bus.borrow_ref_mut(cs);
-            bus.write(address, write)
-        })
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.bus
+            .lock(|bus| poll_to_completion(bus.write(address, write)))
     }
 
     #[inline]
-    fn write_read(
+    async fn write_read(
         &mut self,
-        address: u8,
+        address: A,
         write: &[u8],
         read: &mut [u8],
     ) -> Result<(), Self::Error> {
-        critical_section::with(|cs| {
-            let bus = &mut *self.bus.borrow_ref_mut(cs);
-            bus.write_read(address, write, read)
+        self.bus
+            .lock(|bus| poll_to_completion(bus.write_read(address, write, read)))
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus
+            .lock(|bus| poll_to_completion(bus.transaction(address, operations)))
+    }
+}
+
+/// `critical-section`-based shared bus [`I2c`](embedded_hal::i2c::I2c) implementation applying a
+/// per-device SCL clock frequency.
+///
+/// This is the same as [`CriticalSectionDevice`], except that the clock frequency of the device is
+/// applied to the bus at the start of every transaction, see [`DeviceWithClockSpeed`].
+pub type CriticalSectionDeviceWithClockSpeed<'a, T> = DeviceWithClockSpeed<&'a Mutex<RefCell<T>>>;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for CriticalSectionDeviceWithClockSpeed<'_, T>
+where
+    T: AsyncI2c<A> + I2cBusSpeed,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| {
+            bus.set_clock_speed(self.frequency_hz)?;
+            poll_to_completion(bus.transaction(address, operations))
         })
     }
+}
 
+/// `critical-section`-based shared bus [`I2c`](embedded_hal::i2c::I2c) implementation suspending the bus
+/// peripheral between transactions.
+///
+/// This is the same as [`CriticalSectionDevice`], except that the bus is resumed at the start of every
+/// transaction, and optionally suspended at its end, see [`DeviceWithPowerManagement`].
+pub type CriticalSectionDeviceWithPowerManagement<'a, T> =
+    DeviceWithPowerManagement<&'a Mutex<RefCell<T>>>;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for CriticalSectionDeviceWithPowerManagement<'_, T>
+where
+    T: AsyncI2c<A> + I2cPowerManagement,
+{
     #[inline]
-    fn transaction(
+    async fn transaction(
         &mut self,
-        address: u8,
-        operations: &mut [embedded_hal::i2c::Operation<'_>],
+        address: A,
+        operations: &mut [Operation<'_>],
     ) -> Result<(), Self::Error> {
-        critical_section::with(|cs| {
-            let bus = &mut *self.bus.borrow_ref_mut(cs);
-            bus.transaction(address, operations)
+        self.bus.lock(|bus| {
+            bus.resume()?;
+            let result = poll_to_completion(bus.transaction(address, operations));
+            suspend_after(bus, self.suspend_when_idle, result)
         })
     }
 }
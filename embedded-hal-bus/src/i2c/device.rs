@@ -0,0 +1,218 @@
+use embedded_hal::i2c::{AddressMode, ErrorType, I2c, I2cBusSpeed, I2cPowerManagement, Operation};
+
+use crate::util::BusMutex;
+
+/// Shared bus [`I2c`] implementation, generic over the [`BusMutex`] protecting the bus.
+///
+/// The mutex is locked for the entire duration of a transaction. The provided device types, such as
+/// [`RefCellDevice`](super::RefCellDevice) or [`CriticalSectionDevice`](super::CriticalSectionDevice),
+/// are instantiations of this type. Use it directly to share a bus with your own lock type.
+///
+/// # Example
+///
+/// ```
+/// use core::cell::RefCell;
+/// use embedded_hal_bus::{i2c, util::BusMutex};
+/// # use embedded_hal::i2c::{ErrorType, I2c, Operation};
+/// # struct I2c0;
+/// # impl ErrorType for I2c0 {
+/// #     type Error = core::convert::Infallible;
+/// # }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+/// #       Ok(())
+/// #     }
+/// # }
+///
+/// struct MyMutex<T>(RefCell<T>);
+///
+/// impl<T> BusMutex for MyMutex<T> {
+///     type Bus = T;
+///
+///     fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+///         f(&mut self.0.borrow_mut())
+///     }
+/// }
+///
+/// let bus = MyMutex(RefCell::new(I2c0));
+/// let mut device = i2c::Device::new(&bus);
+/// device.write(0x20, &[0x01]).unwrap();
+/// ```
+pub struct Device<M> {
+    pub(super) bus: M,
+}
+
+impl<M> Device<M> {
+    /// Create a new `Device`.
+    #[inline]
+    pub fn new(bus: M) -> Self {
+        Self { bus }
+    }
+}
+
+impl<M> ErrorType for Device<M>
+where
+    M: BusMutex,
+    M::Bus: ErrorType,
+{
+    type Error = <M::Bus as ErrorType>::Error;
+}
+
+impl<M, A: AddressMode> I2c<A> for Device<M>
+where
+    M: BusMutex,
+    M::Bus: I2c<A>,
+{
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| bus.read(address, read))
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| bus.write(address, write))
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| bus.write_read(address, write, read))
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| bus.transaction(address, operations))
+    }
+}
+
+/// Shared bus [`I2c`] implementation applying a per-device SCL clock frequency to the bus.
+///
+/// This is the same as [`Device`], except that the device sets its clock frequency with
+/// [`I2cBusSpeed::set_clock_speed`] at the start of every transaction, while the mutex is locked.
+/// This allows devices with different maximum frequencies to share a bus: a device without
+/// constraints can use [`Device`], which keeps the frequency set by the previous transaction.
+///
+/// The provided device types, such as [`RefCellDeviceWithClockSpeed`](super::RefCellDeviceWithClockSpeed),
+/// are instantiations of this type.
+pub struct DeviceWithClockSpeed<M> {
+    pub(super) bus: M,
+    pub(super) frequency_hz: u32,
+}
+
+impl<M> DeviceWithClockSpeed<M> {
+    /// Create a new `DeviceWithClockSpeed`, clocked at `frequency_hz`.
+    #[inline]
+    pub fn new(bus: M, frequency_hz: u32) -> Self {
+        Self { bus, frequency_hz }
+    }
+
+    /// Returns the SCL clock frequency of the device, in Hz.
+    #[inline]
+    pub fn clock_speed(&self) -> u32 {
+        self.frequency_hz
+    }
+
+    /// Sets the SCL clock frequency of the device, in Hz, applied from the next transaction.
+    #[inline]
+    pub fn set_clock_speed(&mut self, frequency_hz: u32) {
+        self.frequency_hz = frequency_hz;
+    }
+}
+
+impl<M> ErrorType for DeviceWithClockSpeed<M>
+where
+    M: BusMutex,
+    M::Bus: ErrorType,
+{
+    type Error = <M::Bus as ErrorType>::Error;
+}
+
+impl<M, A: AddressMode> I2c<A> for DeviceWithClockSpeed<M>
+where
+    M: BusMutex,
+    M::Bus: I2c<A> + I2cBusSpeed,
+{
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| {
+            bus.set_clock_speed(self.frequency_hz)?;
+            bus.transaction(address, operations)
+        })
+    }
+}
+
+/// Shared bus [`I2c`] implementation suspending the bus peripheral between transactions.
+///
+/// This is the same as [`Device`], except that the device calls [`I2cPowerManagement::resume`] at the
+/// start of every transaction, while the mutex is locked. If `suspend_when_idle` is set, it also calls
+/// [`I2cPowerManagement::suspend`] at the end of the transaction, whether it succeeded or not.
+///
+/// Since the bus is resumed before every transaction, devices suspending the bus can share it with
+/// devices which do not: a device doing many transactions in a row, such as a display, can keep the
+/// bus running until a device with `suspend_when_idle` suspends it.
+///
+/// The provided device types, such as [`RefCellDeviceWithPowerManagement`](super::RefCellDeviceWithPowerManagement),
+/// are instantiations of this type.
+pub struct DeviceWithPowerManagement<M> {
+    pub(super) bus: M,
+    pub(super) suspend_when_idle: bool,
+}
+
+impl<M> DeviceWithPowerManagement<M> {
+    /// Create a new `DeviceWithPowerManagement`, suspending the bus after each transaction if
+    /// `suspend_when_idle` is set.
+    #[inline]
+    pub fn new(bus: M, suspend_when_idle: bool) -> Self {
+        Self {
+            bus,
+            suspend_when_idle,
+        }
+    }
+}
+
+impl<M> ErrorType for DeviceWithPowerManagement<M>
+where
+    M: BusMutex,
+    M::Bus: ErrorType,
+{
+    type Error = <M::Bus as ErrorType>::Error;
+}
+
+impl<M, A: AddressMode> I2c<A> for DeviceWithPowerManagement<M>
+where
+    M: BusMutex,
+    M::Bus: I2c<A> + I2cPowerManagement,
+{
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| {
+            bus.resume()?;
+            let result = bus.transaction(address, operations);
+            suspend_after(bus, self.suspend_when_idle, result)
+        })
+    }
+}
+
+/// Suspends `bus` if `suspend`, returning the error of the transaction first.
+pub(super) fn suspend_after<B: I2cPowerManagement>(
+    bus: &mut B,
+    suspend: bool,
+    result: Result<(), B::Error>,
+) -> Result<(), B::Error> {
+    if !suspend {
+        return result;
+    }
+    let suspended = bus.suspend();
+    result.and(suspended)
+}
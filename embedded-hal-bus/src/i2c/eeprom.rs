@@ -0,0 +1,361 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{Error, ErrorKind, I2c, NoAcknowledgeSource, Operation};
+
+/// Page-write helper for I2C EEPROMs, such as the AT24 and M24 families.
+///
+/// EEPROMs can only write within a single page at once, and do not answer for a few milliseconds
+/// after each page write, while the data is being programmed (the "write cycle").
+/// [`write_bytes`](EepromI2c::write_bytes) splits the data at page boundaries, and waits for the
+/// write cycle to complete after each page.
+///
+/// By default, the helper waits for `write_cycle_ns` after each page. With
+/// [`ack_polling`](EepromI2c::ack_polling), it instead polls the device until it acknowledges its
+/// address again, which usually completes faster than the worst case stated in the datasheet.
+///
+/// Memory addresses are sent as two bytes, most significant first. Small EEPROMs (up to 16 kbit)
+/// use a single byte, see [`one_byte_address`](EepromI2c::one_byte_address).
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c::EepromI2c;
+/// # use embedded_hal::delay::DelayNs;
+/// # use embedded_hal::i2c::{self, ErrorKind, I2c, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _: u32) {} }
+/// # let (i2c, delay) = (I2c0, Delay);
+///
+/// // AT24C256: 64-byte pages, 5 ms write cycle.
+/// let mut eeprom = EepromI2c::new(i2c, delay, 64, 5_000_000);
+/// eeprom.write_bytes(0x50, 0x0100, b"hello, world")?;
+///
+/// let mut buf = [0; 12];
+/// eeprom.read_bytes(0x50, 0x0100, &mut buf)?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct EepromI2c<I2C, D> {
+    i2c: I2C,
+    delay: D,
+    page_size: u16,
+    write_cycle_ns: u32,
+    poll_interval_ns: Option<u32>,
+    two_byte_address: bool,
+}
+
+impl<I2C: I2c, D: DelayNs> EepromI2c<I2C, D> {
+    /// Create a new [`EepromI2c`], for pages of `page_size` bytes and a write cycle of
+    /// at most `write_cycle_ns` nanoseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_size` is zero.
+    #[inline]
+    pub fn new(i2c: I2C, delay: D, page_size: u16, write_cycle_ns: u32) -> Self {
+        assert!(page_size > 0, "EEPROM page size must not be zero");
+        Self {
+            i2c,
+            delay,
+            page_size,
+            write_cycle_ns,
+            poll_interval_ns: None,
+            two_byte_address: true,
+        }
+    }
+
+    /// Send memory addresses as a single byte, for EEPROMs of up to 16 kbit.
+    ///
+    /// The memory address bits above the first 8 are sent in the 3 lowest bits of the device address,
+    /// as the AT24C04 to AT24C16 expect: pass the base address of the device as `device_addr`, for
+    /// example `0x50`, and the full memory address as `mem_addr`.
+    ///
+    /// # Panics
+    ///
+    /// [`write_bytes`](EepromI2c::write_bytes) and [`read_bytes`](EepromI2c::read_bytes) panic if
+    /// the memory addresses accessed do not fit in 11 bits, the most a one-byte address EEPROM has.
+    #[inline]
+    pub fn one_byte_address(mut self) -> Self {
+        self.two_byte_address = false;
+        self
+    }
+
+    /// Wait for the write cycle by polling the device every `poll_interval_ns` nanoseconds,
+    /// until it acknowledges its address again.
+    ///
+    /// If the device still does not acknowledge after `write_cycle_ns`, the error of
+    /// the last poll is returned.
+    #[inline]
+    pub fn ack_polling(mut self, poll_interval_ns: u32) -> Self {
+        self.poll_interval_ns = Some(poll_interval_ns);
+        self
+    }
+
+    /// Release the I2C bus and the delay.
+    #[inline]
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+
+    /// Write `data` at `mem_addr`, splitting it at page boundaries, and wait for the write
+    /// cycle of each page to complete.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` extends past the memory address `0xFFFF`, or past the range of one-byte
+    /// addresses (see [`one_byte_address`](EepromI2c::one_byte_address)), before writing anything.
+    pub fn write_bytes(
+        &mut self,
+        device_addr: u8,
+        mem_addr: u16,
+        data: &[u8],
+    ) -> Result<(), I2C::Error> {
+        // Check the whole range first, so that the data is not written partially.
+        if let Some(last) = data.len().checked_sub(1) {
+            self.check_address(usize::from(mem_addr) + last);
+        }
+        let mut addr = mem_addr;
+        let mut data = data;
+        while !data.is_empty() {
+            let room = usize::from(self.page_size - addr % self.page_size);
+            let (page, rest) = data.split_at(room.min(data.len()));
+            let (device, addr_buf, addr_len) = self.address_bytes(device_addr, addr);
+            let addr_bytes = &addr_buf[..addr_len];
+            self.i2c.transaction(
+                device,
+                &mut [Operation::Write(addr_bytes), Operation::Write(page)],
+            )?;
+            self.wait_write_cycle(device, addr_bytes)?;
+            addr = addr.wrapping_add(page.len() as u16);
+            data = rest;
+        }
+        Ok(())
+    }
+
+    /// Read `buf.len()` bytes starting at `mem_addr`.
+    ///
+    /// EEPROMs read sequentially across page boundaries, so this is a single transaction.
+    #[inline]
+    pub fn read_bytes(
+        &mut self,
+        device_addr: u8,
+        mem_addr: u16,
+        buf: &mut [u8],
+    ) -> Result<(), I2C::Error> {
+        let (device, addr_buf, addr_len) = self.address_bytes(device_addr, mem_addr);
+        self.i2c.write_read(device, &addr_buf[..addr_len], buf)
+    }
+
+    /// Encode `mem_addr`, returning the device address, the buffer and the number of bytes to send from it.
+    fn address_bytes(&self, device_addr: u8, mem_addr: u16) -> (u8, [u8; 2], usize) {
+        if self.two_byte_address {
+            (device_addr, mem_addr.to_be_bytes(), 2)
+        } else {
+            self.check_address(usize::from(mem_addr));
+            let [high, low] = mem_addr.to_be_bytes();
+            (device_addr | high, [low, 0], 1)
+        }
+    }
+
+    fn check_address(&self, mem_addr: usize) {
+        if self.two_byte_address {
+            assert!(
+                mem_addr <= 0xFFFF,
+                "EEPROM memory address {mem_addr:#X} does not fit in a two-byte address"
+            );
+        } else {
+            assert!(
+                mem_addr <= 0x7FF,
+                "EEPROM memory address {mem_addr:#X} does not fit in a one-byte address"
+            );
+        }
+    }
+
+    fn wait_write_cycle(&mut self, device_addr: u8, addr_bytes: &[u8]) -> Result<(), I2C::Error> {
+        let Some(interval) = self.poll_interval_ns else {
+            self.delay.delay_ns(self.write_cycle_ns);
+            return Ok(());
+        };
+        let mut waited = 0;
+        loop {
+            // Writing the address only moves the internal pointer of the EEPROM.
+            match self.i2c.write(device_addr, addr_bytes) {
+                Ok(()) => return Ok(()),
+                Err(e)
+                    if waited < self.write_cycle_ns
+                        && matches!(
+                            e.kind(),
+                            ErrorKind::NoAcknowledge(
+                                NoAcknowledgeSource::Address | NoAcknowledgeSource::Unknown
+                            )
+                        ) =>
+                {
+                    self.delay.delay_ns(interval);
+                    waited = waited.saturating_add(interval);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::i2c::ErrorType;
+    use std::{vec, vec::Vec};
+
+    /// Fake EEPROM recording page writes, and refusing `busy_polls` polls after each of them.
+    #[derive(Default)]
+    struct FakeEeprom {
+        writes: Vec<(u8, Vec<u8>)>,
+        reads: Vec<(u8, Vec<u8>)>,
+        busy_polls: usize,
+        busy: usize,
+        polls: usize,
+    }
+
+    impl ErrorType for FakeEeprom {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeEeprom {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            if self.busy > 0 {
+                self.busy -= 1;
+                self.polls += 1;
+                return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+            }
+            match operations {
+                [Operation::Write(_)] => self.polls += 1,
+                [Operation::Write(addr), Operation::Write(data)] => {
+                    let mut bytes = addr.to_vec();
+                    bytes.extend_from_slice(data);
+                    self.writes.push((address, bytes));
+                    self.busy = self.busy_polls;
+                }
+                [Operation::Write(addr), Operation::Read(buf)] => {
+                    self.reads.push((address, addr.to_vec()));
+                    buf.fill(0xAA);
+                }
+                _ => panic!("unexpected transaction"),
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeDelay(u64);
+
+    impl DelayNs for FakeDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 += u64::from(ns);
+        }
+    }
+
+    #[test]
+    fn splits_writes_at_page_boundaries() {
+        let mut eeprom = EepromI2c::new(FakeEeprom::default(), FakeDelay::default(), 8, 5_000_000);
+        eeprom
+            .write_bytes(0x50, 0x0106, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11])
+            .unwrap();
+        let (bus, delay) = eeprom.release();
+        assert_eq!(
+            bus.writes,
+            [
+                (0x50, vec![0x01, 0x06, 1, 2]),
+                (0x50, vec![0x01, 0x08, 3, 4, 5, 6, 7, 8, 9, 10]),
+                (0x50, vec![0x01, 0x10, 11]),
+            ]
+        );
+        assert_eq!(delay.0, 15_000_000);
+    }
+
+    #[test]
+    fn one_byte_address() {
+        let mut eeprom =
+            EepromI2c::new(FakeEeprom::default(), FakeDelay::default(), 16, 0).one_byte_address();
+        eeprom.write_bytes(0x51, 0x00F0, &[1, 2]).unwrap();
+        let mut buf = [0; 3];
+        eeprom.read_bytes(0x51, 0x00F0, &mut buf).unwrap();
+        assert_eq!(buf, [0xAA; 3]);
+        assert_eq!(eeprom.release().0.writes, [(0x51, vec![0xF0, 1, 2])]);
+    }
+
+    #[test]
+    fn one_byte_address_high_bits_select_the_block() {
+        // AT24C16: 16-byte pages, 8 blocks of 256 bytes.
+        let mut eeprom =
+            EepromI2c::new(FakeEeprom::default(), FakeDelay::default(), 16, 0).one_byte_address();
+        eeprom.write_bytes(0x50, 0x01FE, &[1, 2, 3]).unwrap();
+        eeprom.read_bytes(0x50, 0x07F0, &mut [0; 4]).unwrap();
+        let bus = eeprom.release().0;
+        assert_eq!(
+            bus.writes,
+            [(0x51, vec![0xFE, 1, 2]), (0x52, vec![0x00, 3])]
+        );
+        assert_eq!(bus.reads, [(0x57, vec![0xF0])]);
+    }
+
+    #[test]
+    #[should_panic = "does not fit in a one-byte address"]
+    fn one_byte_address_out_of_range_panics() {
+        let mut eeprom =
+            EepromI2c::new(FakeEeprom::default(), FakeDelay::default(), 16, 0).one_byte_address();
+        let _ = eeprom.write_bytes(0x50, 0x07FE, &[1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic = "does not fit in a two-byte address"]
+    fn two_byte_address_out_of_range_panics() {
+        let mut eeprom = EepromI2c::new(FakeEeprom::default(), FakeDelay::default(), 64, 0);
+        let _ = eeprom.write_bytes(0x50, 0xFFFE, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn write_up_to_the_last_address() {
+        let mut eeprom = EepromI2c::new(FakeEeprom::default(), FakeDelay::default(), 64, 0);
+        eeprom.write_bytes(0x50, 0xFFFE, &[1, 2]).unwrap();
+        assert_eq!(eeprom.release().0.writes, [(0x50, vec![0xFF, 0xFE, 1, 2])]);
+    }
+
+    #[test]
+    fn ack_polling_stops_once_acknowledged() {
+        let bus = FakeEeprom {
+            busy_polls: 3,
+            ..Default::default()
+        };
+        let mut eeprom =
+            EepromI2c::new(bus, FakeDelay::default(), 8, 5_000_000).ack_polling(100_000);
+        eeprom.write_bytes(0x50, 0, &[1; 10]).unwrap();
+        let (bus, delay) = eeprom.release();
+        assert_eq!(bus.writes.len(), 2);
+        // Three refused polls and a successful one after each page.
+        assert_eq!(bus.polls, 8);
+        assert_eq!(delay.0, 600_000);
+    }
+
+    #[test]
+    fn ack_polling_times_out() {
+        let bus = FakeEeprom {
+            busy_polls: 100,
+            ..Default::default()
+        };
+        let mut eeprom =
+            EepromI2c::new(bus, FakeDelay::default(), 8, 1_000_000).ack_polling(250_000);
+        assert_eq!(
+            eeprom.write_bytes(0x50, 0, &[1]),
+            Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+        );
+        assert_eq!(eeprom.release().1 .0, 1_000_000);
+    }
+}
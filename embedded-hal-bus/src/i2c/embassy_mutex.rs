@@ -0,0 +1,78 @@
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal::i2c::{AddressMode, ErrorType, Operation};
+use embedded_hal_async::i2c::I2c;
+
+/// `embassy-sync` async mutex based shared bus [`I2c`] implementation.
+///
+/// This allows multiple async tasks to share a bus: a task waits for the bus to be released
+/// instead of failing, and the mutex is locked for the entire duration of a transaction,
+/// `.await` points included. The raw mutex type `M` chooses which contexts can share the bus,
+/// e.g. `NoopRawMutex` for tasks of a single executor, or `CriticalSectionRawMutex` across
+/// interrupt priority levels.
+///
+/// `embassy-sync` mutexes cannot be poisoned, so the errors are the ones of the bus.
+///
+/// # Example
+///
+/// ```
+/// use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+/// use embassy_sync::mutex::Mutex;
+/// use embedded_hal_async::i2c::I2c;
+/// use embedded_hal_bus::i2c::AsyncMutexDevice;
+///
+/// async fn two_sensors<T: I2c>(i2c: T) -> Result<(), T::Error> {
+///     let bus = Mutex::<NoopRawMutex, _>::new(i2c);
+///     let mut temperature = AsyncMutexDevice::new(&bus);
+///     let mut pressure = AsyncMutexDevice::new(&bus);
+///     temperature.write(0x20, &[0x01]).await?;
+///     pressure.write(0x42, &[0x02]).await?;
+///     Ok(())
+/// }
+/// ```
+pub struct AsyncMutexDevice<'a, M: RawMutex, T> {
+    bus: &'a Mutex<M, T>,
+}
+
+impl<'a, M: RawMutex, T> AsyncMutexDevice<'a, M, T> {
+    /// Create a new `AsyncMutexDevice`.
+    #[inline]
+    pub fn new(bus: &'a Mutex<M, T>) -> Self {
+        Self { bus }
+    }
+}
+
+impl<M: RawMutex, T: ErrorType> ErrorType for AsyncMutexDevice<'_, M, T> {
+    type Error = T::Error;
+}
+
+impl<M: RawMutex, T: I2c<A>, A: AddressMode> I2c<A> for AsyncMutexDevice<'_, M, T> {
+    #[inline]
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.lock().await.read(address, read).await
+    }
+
+    #[inline]
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.bus.lock().await.write(address, write).await
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock().await.write_read(address, write, read).await
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock().await.transaction(address, operations).await
+    }
+}
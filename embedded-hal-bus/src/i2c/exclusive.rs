@@ -0,0 +1,126 @@
+use embedded_hal::i2c::{AddressMode, ErrorType, I2c, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+/// [`I2c`] wrapper giving exclusive access to a bus, with no sharing overhead.
+///
+/// Every method is forwarded to the inner bus. This is the I2C counterpart of the SPI
+/// [`ExclusiveDevice`](crate::spi::ExclusiveDevice): applications with a single device on a bus can
+/// pass an `ExclusiveDevice` to its driver, and later switch to a sharing wrapper such as
+/// [`RefCellDevice`](super::RefCellDevice) when another device is added, with no change to the
+/// driver.
+///
+/// With the `async` feature, the async [`I2c`](embedded_hal_async::i2c::I2c) trait is also
+/// implemented if the inner bus implements it.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::ExclusiveDevice;
+/// # use embedded_hal::i2c::{self as hali2c, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl hali2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # let i2c = I2c0;
+///
+/// let mut sensor = ExclusiveDevice::new(i2c);
+/// sensor.write(0x48, &[0x01, 0x60])?;
+/// let i2c = sensor.release();
+/// # Ok::<(), ErrorKind>(())
+/// ```
+#[derive(Debug)]
+pub struct ExclusiveDevice<T> {
+    bus: T,
+}
+
+impl<T> ExclusiveDevice<T> {
+    /// Create a new [`ExclusiveDevice`].
+    #[inline]
+    pub fn new(bus: T) -> Self {
+        Self { bus }
+    }
+
+    /// Returns a reference to the underlying bus object.
+    #[inline]
+    pub fn bus(&self) -> &T {
+        &self.bus
+    }
+
+    /// Returns a mutable reference to the underlying bus object.
+    #[inline]
+    pub fn bus_mut(&mut self) -> &mut T {
+        &mut self.bus
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> T {
+        self.bus
+    }
+}
+
+impl<T: ErrorType> ErrorType for ExclusiveDevice<T> {
+    type Error = T::Error;
+}
+
+impl<T: I2c<A>, A: AddressMode> I2c<A> for ExclusiveDevice<T> {
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.read(address, read)
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(address, write)
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.write_read(address, write, read)
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.transaction(address, operations)
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: AsyncI2c<A>, A: AddressMode> AsyncI2c<A> for ExclusiveDevice<T> {
+    #[inline]
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.read(address, read).await
+    }
+
+    #[inline]
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.bus.write(address, write).await
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.bus.write_read(address, write, read).await
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.bus.transaction(address, operations).await
+    }
+}
@@ -0,0 +1,259 @@
+use embedded_hal::i2c::{AddressMode, Error, ErrorType, I2c, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(all(feature = "defmt-03", not(feature = "log")))]
+use crate::defmt;
+use crate::logging::{record, Address, HexDump, Outcome, DEFAULT_MAX_DUMP};
+
+/// [`I2c`] wrapper logging every operation.
+///
+/// Each operation is logged once performed, at the debug level, with the target address, the
+/// direction, the number of bytes, a hex dump of the data, and the result of the transaction. The
+/// hex dump is limited to 16 bytes by default, see [`Logged::with_max_dump`].
+///
+/// Records are emitted with the `log` crate when the `log` feature is enabled, and with `defmt`
+/// when only the `defmt-03` feature is.
+///
+/// With the `async` feature, `Logged` also implements the async [`I2c`](embedded_hal_async::i2c::I2c)
+/// trait if the inner bus does.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::Logged;
+/// # use embedded_hal::i2c::{self, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// let mut i2c = Logged::new(i2c);
+/// // Logs "i2c 0x48: write 2 bytes [01 60] -> ok".
+/// i2c.write(0x48, &[0x01, 0x60])?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct Logged<T> {
+    inner: T,
+    max_dump: usize,
+}
+
+impl<T> Logged<T> {
+    /// Create a new [`Logged`] wrapper around `inner`.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            max_dump: DEFAULT_MAX_DUMP,
+        }
+    }
+
+    /// Set the maximum number of bytes dumped per operation.
+    ///
+    /// Longer data is truncated, and marked with `...`.
+    #[inline]
+    pub fn with_max_dump(mut self, max_dump: usize) -> Self {
+        self.max_dump = max_dump;
+        self
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> T {
+        self.inner
+    }
+
+    fn log<E: Error>(&self, address: u16, operations: &[Operation<'_>], result: &Result<(), E>) {
+        for operation in operations {
+            let (direction, data): (&str, &[u8]) = match operation {
+                Operation::Read(data) => ("read", data),
+                Operation::Write(data) => ("write", data),
+            };
+            record!(
+                "i2c {}: {} {} bytes {} -> {}",
+                Address(address),
+                direction,
+                data.len(),
+                HexDump {
+                    data,
+                    max: self.max_dump
+                },
+                Outcome(result.as_ref().copied().map_err(|e| e.kind()))
+            );
+        }
+    }
+}
+
+impl<T: ErrorType> ErrorType for Logged<T> {
+    type Error = T::Error;
+}
+
+impl<T, A> I2c<A> for Logged<T>
+where
+    T: I2c<A>,
+    A: AddressMode + Into<u16>,
+{
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read);
+        self.log(address.into(), &[Operation::Read(read)], &result);
+        result
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write);
+        self.log(address.into(), &[Operation::Write(write)], &result);
+        result
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read);
+        let operations = [Operation::Write(write), Operation::Read(read)];
+        self.log(address.into(), &operations, &result);
+        result
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations);
+        self.log(address.into(), operations, &result);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A> AsyncI2c<A> for Logged<T>
+where
+    T: AsyncI2c<A>,
+    A: AddressMode + Into<u16>,
+{
+    #[inline]
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read).await;
+        self.log(address.into(), &[Operation::Read(read)], &result);
+        result
+    }
+
+    #[inline]
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write).await;
+        self.log(address.into(), &[Operation::Write(write)], &result);
+        result
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read).await;
+        let operations = [Operation::Write(write), Operation::Read(read)];
+        self.log(address.into(), &operations, &result);
+        result
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations).await;
+        self.log(address.into(), operations, &result);
+        result
+    }
+}
+
+#[cfg(all(test, feature = "log"))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::logging::capture::records;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource, TenBitAddress};
+
+    struct FakeBus;
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            if address == 0x10 {
+                return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+            }
+            for op in operations {
+                if let Operation::Read(buf) = op {
+                    buf.fill(0xAB);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl I2c<TenBitAddress> for FakeBus {
+        fn transaction(
+            &mut self,
+            _address: TenBitAddress,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn logs_each_operation() {
+        let mut i2c = Logged::new(FakeBus);
+        let log = records(|| {
+            let mut buf = [0; 2];
+            i2c.write_read(0x48u8, &[0x01], &mut buf).unwrap();
+        });
+        assert_eq!(
+            log,
+            [
+                "i2c 0x48: write 1 bytes [01] -> ok",
+                "i2c 0x48: read 2 bytes [ab ab] -> ok",
+            ]
+        );
+    }
+
+    #[test]
+    fn logs_errors() {
+        let mut i2c = Logged::new(FakeBus);
+        let log = records(|| {
+            i2c.write(0x10u8, &[]).unwrap_err();
+        });
+        assert_eq!(
+            log,
+            ["i2c 0x10: write 0 bytes [] -> error NoAcknowledge(Address)"]
+        );
+    }
+
+    #[test]
+    fn truncates_dumps() {
+        let mut i2c = Logged::new(FakeBus).with_max_dump(2);
+        let log = records(|| {
+            I2c::<TenBitAddress>::write(&mut i2c, 0x3A5, &[1, 2, 3]).unwrap();
+        });
+        assert_eq!(log, ["i2c 0x3a5: write 3 bytes [01 02 ...] -> ok"]);
+    }
+}
@@ -1,5 +1,48 @@
 //! `I2c` shared bus implementations.
 
+mod address_book;
+pub use address_book::*;
+mod addressed;
+pub use addressed::*;
+mod alert;
+pub use alert::*;
+mod bitbang;
+pub use bitbang::*;
+mod device;
+pub use device::*;
+mod eeprom;
+pub use eeprom::*;
+mod exclusive;
+pub use exclusive::*;
+#[cfg(any(feature = "log", feature = "defmt-03"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "log", feature = "defmt-03"))))]
+mod logged;
+#[cfg(any(feature = "log", feature = "defmt-03"))]
+pub use logged::*;
+mod multiplexer;
+pub use multiplexer::*;
+mod recovery;
+pub use recovery::*;
+mod register;
+pub use register::*;
+mod retry;
+pub use retry::*;
+mod scanner;
+pub use scanner::*;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "32"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "portable-atomic", target_has_atomic = "32")))
+)]
+mod statistics;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "32"))]
+pub use statistics::*;
+mod stretch;
+pub use stretch::*;
+mod translator;
+pub use translator::*;
+mod trace;
+pub use trace::*;
 mod refcell;
 pub use refcell::*;
 #[cfg(feature = "std")]
@@ -17,3 +60,619 @@ pub use atomic::*;
 mod rc;
 #[cfg(feature = "alloc")]
 pub use rc::*;
+
+#[cfg(feature = "embassy-sync")]
+mod embassy_mutex;
+#[cfg(feature = "embassy-sync")]
+pub use embassy_mutex::*;
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use embedded_hal::i2c::{
+        ErrorType, I2c, I2cBusSpeed, I2cPowerManagement, Operation, SevenBitAddress, TenBitAddress,
+    };
+
+    #[derive(Debug, PartialEq)]
+    enum Address {
+        SevenBit(SevenBitAddress),
+        TenBit(TenBitAddress),
+    }
+
+    #[derive(Default)]
+    struct FakeBus {
+        last: Option<Address>,
+        clock_speed: Option<u32>,
+    }
+
+    impl I2cBusSpeed for FakeBus {
+        fn set_clock_speed(&mut self, frequency_hz: u32) -> Result<(), Self::Error> {
+            self.clock_speed = Some(frequency_hz);
+            Ok(())
+        }
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c<SevenBitAddress> for FakeBus {
+        fn transaction(
+            &mut self,
+            address: SevenBitAddress,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.last = Some(Address::SevenBit(address));
+            Ok(())
+        }
+    }
+
+    impl I2c<TenBitAddress> for FakeBus {
+        fn transaction(
+            &mut self,
+            address: TenBitAddress,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.last = Some(Address::TenBit(address));
+            Ok(())
+        }
+    }
+
+    fn round_trip<D>(mut device: D, last: impl Fn() -> Option<Address>)
+    where
+        D: I2c<SevenBitAddress> + I2c<TenBitAddress>,
+    {
+        I2c::<SevenBitAddress>::write(&mut device, 0x42, &[0]).unwrap();
+        assert_eq!(last(), Some(Address::SevenBit(0x42)));
+        I2c::<TenBitAddress>::write(&mut device, 0x3A5, &[0]).unwrap();
+        assert_eq!(last(), Some(Address::TenBit(0x3A5)));
+        I2c::<TenBitAddress>::read(&mut device, 0x123, &mut [0]).unwrap();
+        assert_eq!(last(), Some(Address::TenBit(0x123)));
+        I2c::<TenBitAddress>::write_read(&mut device, 0x321, &[0], &mut [0]).unwrap();
+        assert_eq!(last(), Some(Address::TenBit(0x321)));
+    }
+
+    #[test]
+    fn refcell_device_address_modes() {
+        let bus = RefCell::new(FakeBus::default());
+        round_trip(RefCellDevice::new(&bus), || bus.borrow_mut().last.take());
+    }
+
+    #[test]
+    fn try_refcell_device_address_modes() {
+        let bus = RefCell::new(FakeBus::default());
+        round_trip(TryRefCellDevice::new(&bus), || bus.borrow_mut().last.take());
+    }
+
+    #[test]
+    fn exclusive_device_address_modes() {
+        let bus = RefCell::new(FakeBus::default());
+        round_trip(ExclusiveDevice::new(RefCellDevice::new(&bus)), || {
+            bus.borrow_mut().last.take()
+        });
+
+        let mut device = ExclusiveDevice::new(FakeBus::default());
+        I2c::<TenBitAddress>::write(&mut device, 0x3A5, &[0]).unwrap();
+        assert_eq!(device.bus_mut().last.take(), Some(Address::TenBit(0x3A5)));
+        I2c::<SevenBitAddress>::read(&mut device, 0x42, &mut [0]).unwrap();
+        assert_eq!(device.release().last, Some(Address::SevenBit(0x42)));
+    }
+
+    #[test]
+    fn addressed_devices_share_a_refcell_bus() {
+        let bus = RefCell::new(FakeBus::default());
+        let mut a = AddressedDevice::new(RefCellDevice::new(&bus), 0x48);
+        let mut b = AddressedDevice::new_ten_bit(RefCellDevice::new(&bus), 0x3A5);
+
+        a.write(&[0]).unwrap();
+        assert_eq!(bus.borrow_mut().last.take(), Some(Address::SevenBit(0x48)));
+        b.write_read(&[0], &mut [0]).unwrap();
+        assert_eq!(bus.borrow_mut().last.take(), Some(Address::TenBit(0x3A5)));
+        a.read_register_u8(0x01u8).unwrap();
+        assert_eq!(bus.borrow_mut().last.take(), Some(Address::SevenBit(0x48)));
+        b.transaction(&mut [Operation::Write(&[1])]).unwrap();
+        assert_eq!(bus.borrow_mut().last.take(), Some(Address::TenBit(0x3A5)));
+        assert_eq!((a.address(), b.address()), (0x48, 0x3A5));
+
+        let mut c = AddressedDevice::from((ExclusiveDevice::new(FakeBus::default()), 0x20u8));
+        c.read(&mut [0]).unwrap();
+        assert_eq!(c.release().release().last, Some(Address::SevenBit(0x20)));
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn addressed_device_async() {
+        let bus = ::critical_section::Mutex::new(RefCell::new(AsyncBus {
+            log: std::vec::Vec::new(),
+        }));
+        let mut a = AddressedDevice::new(CriticalSectionDevice::new(&bus), 0x10);
+        let mut b = AddressedDevice::new(CriticalSectionDevice::new(&bus), 0x20);
+
+        crate::util::poll_to_completion(a.write_async(&[1])).unwrap();
+        crate::util::poll_to_completion(b.read_async(&mut [0])).unwrap();
+        assert_eq!(
+            ::critical_section::with(|cs| core::mem::take(&mut bus.borrow_ref_mut(cs).log)),
+            [
+                (0x10, "start"),
+                (0x10, "end"),
+                (0x20, "start"),
+                (0x20, "end")
+            ]
+        );
+    }
+
+    /// Calls `nested` in the middle of every transaction.
+    struct ReentrantBus<'a> {
+        nested: Option<&'a dyn Fn()>,
+    }
+
+    impl ErrorType for ReentrantBus<'_> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c for ReentrantBus<'_> {
+        fn transaction(
+            &mut self,
+            _address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            if let Some(nested) = self.nested {
+                nested();
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn try_refcell_device_nested_transaction_is_busy() {
+        let bus = RefCell::new(ReentrantBus { nested: None });
+        let result = core::cell::Cell::new(None);
+        let nested = || result.set(Some(TryRefCellDevice::new(&bus).write(0x20, &[0])));
+        bus.borrow_mut().nested = Some(&nested);
+
+        TryRefCellDevice::new(&bus).write(0x10, &[0]).unwrap();
+        assert_eq!(result.take(), Some(Err(RefCellDeviceError::Busy)));
+    }
+
+    #[test]
+    fn critical_section_device_address_modes() {
+        let bus = ::critical_section::Mutex::new(RefCell::new(FakeBus::default()));
+        round_trip(CriticalSectionDevice::new(&bus), || {
+            ::critical_section::with(|cs| bus.borrow_ref_mut(cs).last.take())
+        });
+    }
+
+    #[test]
+    fn refcell_device_with_clock_speed_sets_the_speed() {
+        let bus = RefCell::new(FakeBus::default());
+        let mut rtc = RefCellDeviceWithClockSpeed::new(&bus, 100_000);
+        let mut imu = RefCellDeviceWithClockSpeed::new(&bus, 400_000);
+        let mut other = RefCellDevice::new(&bus);
+
+        imu.write(0x68u8, &[0]).unwrap();
+        assert_eq!(bus.borrow().clock_speed, Some(400_000));
+        rtc.write(0x51u8, &[0]).unwrap();
+        assert_eq!(bus.borrow().clock_speed, Some(100_000));
+        // Devices without a clock speed keep the current one.
+        other.write(0x20u8, &[0]).unwrap();
+        assert_eq!(bus.borrow().clock_speed, Some(100_000));
+
+        imu.set_clock_speed(1_000_000);
+        assert_eq!(imu.clock_speed(), 1_000_000);
+        imu.read(0x68u8, &mut [0]).unwrap();
+        assert_eq!(bus.borrow().clock_speed, Some(1_000_000));
+
+        round_trip(RefCellDeviceWithClockSpeed::new(&bus, 100_000), || {
+            bus.borrow_mut().last.take()
+        });
+    }
+
+    #[test]
+    fn critical_section_device_with_clock_speed_sets_the_speed() {
+        let bus = ::critical_section::Mutex::new(RefCell::new(FakeBus::default()));
+        let speed = || ::critical_section::with(|cs| bus.borrow_ref(cs).clock_speed);
+        CriticalSectionDeviceWithClockSpeed::new(&bus, 400_000)
+            .write(0x68u8, &[0])
+            .unwrap();
+        assert_eq!(speed(), Some(400_000));
+        CriticalSectionDeviceWithClockSpeed::new(&bus, 100_000)
+            .write(0x51u8, &[0])
+            .unwrap();
+        assert_eq!(speed(), Some(100_000));
+    }
+
+    /// Bus logging the power management calls and transactions, failing writes to address `0x00`.
+    #[derive(Default)]
+    struct PowerBus {
+        log: std::vec::Vec<&'static str>,
+    }
+
+    impl ErrorType for PowerBus {
+        type Error = embedded_hal::i2c::ErrorKind;
+    }
+
+    impl I2cPowerManagement for PowerBus {
+        fn suspend(&mut self) -> Result<(), Self::Error> {
+            self.log.push("suspend");
+            Ok(())
+        }
+
+        fn resume(&mut self) -> Result<(), Self::Error> {
+            self.log.push("resume");
+            Ok(())
+        }
+    }
+
+    impl I2c for PowerBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.log.push("transaction");
+            if address == 0 {
+                return Err(embedded_hal::i2c::ErrorKind::Other);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn refcell_device_with_power_management_suspends_when_idle() {
+        let bus = RefCell::new(PowerBus::default());
+        let mut sensor = RefCellDeviceWithPowerManagement::new(&bus, true);
+        let mut display = RefCellDeviceWithPowerManagement::new(&bus, false);
+
+        sensor.write(0x48, &[0]).unwrap();
+        display.write(0x3C, &[0]).unwrap();
+        // Suspended even if the transaction fails.
+        assert!(sensor.write(0x00, &[0]).is_err());
+        assert_eq!(
+            core::mem::take(&mut bus.borrow_mut().log),
+            [
+                "resume",
+                "transaction",
+                "suspend",
+                "resume",
+                "transaction",
+                "resume",
+                "transaction",
+                "suspend"
+            ]
+        );
+    }
+
+    #[test]
+    fn critical_section_device_with_power_management_suspends_when_idle() {
+        let bus = ::critical_section::Mutex::new(RefCell::new(PowerBus::default()));
+        CriticalSectionDeviceWithPowerManagement::new(&bus, true)
+            .read(0x48, &mut [0])
+            .unwrap();
+        assert_eq!(
+            ::critical_section::with(|cs| core::mem::take(&mut bus.borrow_ref_mut(cs).log)),
+            ["resume", "transaction", "suspend"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn mutex_device_address_modes() {
+        let bus = std::sync::Mutex::new(FakeBus::default());
+        round_trip(MutexDevice::new(&bus), || bus.lock().unwrap().last.take());
+        round_trip(TryMutexDevice::new(&bus), || {
+            bus.lock().unwrap().last.take()
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn try_mutex_device_reports_poisoned_and_busy_mutex() {
+        let bus = std::sync::Mutex::new(FakeBus::default());
+        let mut waiting = TryMutexDevice::new(&bus);
+        let mut trying = TryMutexDevice::new_try_lock(&bus);
+
+        let guard = bus.lock().unwrap();
+        assert_eq!(trying.write(0x20u8, &[0]), Err(MutexDeviceError::Busy));
+        drop(guard);
+        trying.write(0x20u8, &[0]).unwrap();
+
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = bus.lock().unwrap();
+            panic!("poisoning the mutex");
+        });
+        assert_eq!(waiting.write(0x30u8, &[0]), Err(MutexDeviceError::Poisoned));
+        assert_eq!(
+            trying.read(0x30u8, &mut [0]),
+            Err(MutexDeviceError::Poisoned)
+        );
+        // The bus was not touched.
+        assert_eq!(
+            bus.lock().unwrap_or_else(|e| e.into_inner()).last,
+            Some(Address::SevenBit(0x20))
+        );
+
+        bus.clear_poison();
+        waiting.write(0x30u8, &[0]).unwrap();
+    }
+
+    #[test]
+    fn static_bus_shares_bus_between_devices() {
+        static BUS: crate::util::StaticBus<FakeBus> = crate::util::StaticBus::new();
+        let bus = BUS.init(FakeBus::default());
+        CriticalSectionDevice::new(bus).write(0x10u8, &[0]).unwrap();
+        CriticalSectionDevice::new(bus).write(0x11u8, &[0]).unwrap();
+        let last = ::critical_section::with(|cs| bus.borrow_ref_mut(cs).last.take());
+        assert_eq!(last, Some(Address::SevenBit(0x11)));
+
+        let result = std::panic::catch_unwind(|| BUS.init(FakeBus::default()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[cfg(any(feature = "portable-atomic", target_has_atomic = "8"))]
+    fn atomic_device_address_modes() {
+        let bus = crate::util::AtomicCell::new(FakeBus::default());
+        round_trip(AtomicDevice::new(&bus), || unsafe {
+            (*bus.bus.get()).last.take()
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn rc_device_address_modes() {
+        extern crate alloc;
+        let bus = alloc::rc::Rc::new(RefCell::new(FakeBus::default()));
+        round_trip(RcDevice::new(bus.clone()), || bus.borrow_mut().last.take());
+    }
+
+    /// Returns `Pending` once, to force an `.await` point inside the transaction.
+    #[cfg(feature = "async")]
+    async fn yield_now() {
+        use core::future::poll_fn;
+        use core::task::Poll;
+
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Logs the start and the end of every transaction.
+    #[cfg(feature = "async")]
+    struct AsyncBus {
+        log: std::vec::Vec<(u8, &'static str)>,
+    }
+
+    #[cfg(feature = "async")]
+    impl ErrorType for AsyncBus {
+        type Error = core::convert::Infallible;
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::i2c::I2c for AsyncBus {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.log.push((address, "start"));
+            yield_now().await;
+            yield_now().await;
+            self.log.push((address, "end"));
+            Ok(())
+        }
+    }
+
+    /// Run two transactions on each device, from two concurrent tasks, and check that the
+    /// transactions do not interleave.
+    #[cfg(feature = "async")]
+    fn assert_async_transactions_do_not_interleave<D: embedded_hal_async::i2c::I2c>(
+        mut a: D,
+        mut b: D,
+        log: impl FnOnce() -> std::vec::Vec<(u8, &'static str)>,
+    ) {
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::Context;
+        use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+        let mut fa = pin!(async {
+            AsyncI2c::write(&mut a, 0x10, &[1]).await.unwrap();
+            yield_now().await;
+            AsyncI2c::write(&mut a, 0x10, &[2]).await.unwrap();
+        });
+        let mut fb = pin!(async {
+            AsyncI2c::write(&mut b, 0x20, &[1]).await.unwrap();
+            yield_now().await;
+            AsyncI2c::write(&mut b, 0x20, &[2]).await.unwrap();
+        });
+
+        // Poll both futures alternately, as an executor running two tasks would.
+        let waker = crate::util::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let (mut a_done, mut b_done) = (false, false);
+        while !(a_done && b_done) {
+            a_done = a_done || fa.as_mut().poll(&mut cx).is_ready();
+            b_done = b_done || fb.as_mut().poll(&mut cx).is_ready();
+        }
+
+        assert_eq!(
+            log(),
+            [
+                (0x10, "start"),
+                (0x10, "end"),
+                (0x20, "start"),
+                (0x20, "end"),
+                (0x10, "start"),
+                (0x10, "end"),
+                (0x20, "start"),
+                (0x20, "end"),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn critical_section_device_async_transactions_do_not_interleave() {
+        let bus = ::critical_section::Mutex::new(RefCell::new(AsyncBus {
+            log: std::vec::Vec::new(),
+        }));
+        assert_async_transactions_do_not_interleave(
+            CriticalSectionDevice::new(&bus),
+            CriticalSectionDevice::new(&bus),
+            || ::critical_section::with(|cs| core::mem::take(&mut bus.borrow_ref_mut(cs).log)),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "embassy-sync")]
+    fn async_mutex_device_transactions_do_not_interleave() {
+        use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+        use embassy_sync::mutex::Mutex;
+
+        let bus = Mutex::<NoopRawMutex, _>::new(AsyncBus {
+            log: std::vec::Vec::new(),
+        });
+        assert_async_transactions_do_not_interleave(
+            AsyncMutexDevice::new(&bus),
+            AsyncMutexDevice::new(&bus),
+            || core::mem::take(&mut bus.try_lock().unwrap().log),
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn atomic_device_async_transactions_do_not_interleave() {
+        let bus = crate::util::AtomicCell::new(AsyncBus {
+            log: std::vec::Vec::new(),
+        });
+        assert_async_transactions_do_not_interleave(
+            AtomicDevice::new(&bus),
+            AtomicDevice::new(&bus),
+            || unsafe { core::mem::take(&mut (*bus.bus.get()).log) },
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn atomic_device_async_cancelled_transaction_releases_bus() {
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll};
+        use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+        let bus = crate::util::AtomicCell::new(AsyncBus {
+            log: std::vec::Vec::new(),
+        });
+        let mut a = AtomicDevice::new(&bus);
+        let mut b = AtomicDevice::new(&bus);
+        let waker = crate::util::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        {
+            // Suspended in the middle of the transaction, then dropped.
+            let mut fa = pin!(AsyncI2c::write(&mut a, 0x10, &[1]));
+            assert!(fa.as_mut().poll(&mut cx).is_pending());
+        }
+
+        // The bus can be claimed again: `b` starts its transaction on the first poll.
+        let mut fb = pin!(AsyncI2c::write(&mut b, 0x20, &[2]));
+        assert!(fb.as_mut().poll(&mut cx).is_pending());
+        assert!(fb.as_mut().poll(&mut cx).is_pending());
+        assert!(matches!(fb.as_mut().poll(&mut cx), Poll::Ready(Ok(()))));
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn try_atomic_device_async_busy_and_cancelled() {
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll};
+        use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+        let bus = crate::util::AtomicCell::new(AsyncBus {
+            log: std::vec::Vec::new(),
+        });
+        let mut a = TryAtomicDevice::new(&bus);
+        let mut b = TryAtomicDevice::new(&bus);
+        let waker = crate::util::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        {
+            // Suspended in the middle of the transaction: `b` is busy.
+            let mut fa = pin!(AsyncI2c::write(&mut a, 0x10, &[1]));
+            assert!(fa.as_mut().poll(&mut cx).is_pending());
+            let busy = pin!(AsyncI2c::write(&mut b, 0x20, &[1])).poll(&mut cx);
+            assert!(matches!(busy, Poll::Ready(Err(AtomicError::Busy))));
+            // Then cancelled.
+        }
+
+        // The bus can be claimed again.
+        crate::util::poll_to_completion(AsyncI2c::write(&mut b, 0x20, &[2])).unwrap();
+        assert_eq!(
+            unsafe { &(*bus.bus.get()).log }[..],
+            [(0x10, "start"), (0x20, "start"), (0x20, "end")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn try_refcell_device_async_overlapping_transaction_is_busy() {
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll};
+        use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+        let bus = RefCell::new(AsyncBus {
+            log: std::vec::Vec::new(),
+        });
+        let mut a = TryRefCellDevice::new(&bus);
+        let mut b = TryRefCellDevice::new(&bus);
+        let waker = crate::util::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut fa = pin!(AsyncI2c::write(&mut a, 0x10, &[1]));
+        assert!(fa.as_mut().poll(&mut cx).is_pending());
+        // `a` holds the bus across its `.await` point.
+        let busy = pin!(AsyncI2c::write(&mut b, 0x20, &[1])).poll(&mut cx);
+        assert_eq!(busy, Poll::Ready(Err(RefCellDeviceError::Busy)));
+        assert_eq!(crate::util::poll_to_completion(fa), Ok(()));
+
+        // Once `a` is done, `b` gets the bus.
+        crate::util::poll_to_completion(AsyncI2c::write(&mut b, 0x20, &[2])).unwrap();
+        assert_eq!(
+            bus.borrow().log,
+            [
+                (0x10, "start"),
+                (0x10, "end"),
+                (0x20, "start"),
+                (0x20, "end")
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "defmt-03")]
+    fn error_types_implement_defmt_format() {
+        use embedded_hal::{digital, i2c::ErrorKind};
+
+        fn assert_format<T: crate::defmt::Format>() {}
+        assert_format::<AtomicError<ErrorKind>>();
+        assert_format::<RefCellDeviceError<ErrorKind>>();
+        assert_format::<MultiplexedBusError<ErrorKind, ErrorKind>>();
+        assert_format::<BitBangError<digital::ErrorKind, digital::ErrorKind>>();
+        assert_format::<ClockStretchError<ErrorKind>>();
+        assert_format::<RecoveryError<ErrorKind, digital::ErrorKind>>();
+        assert_format::<BusRecoveryError<digital::ErrorKind, digital::ErrorKind>>();
+    }
+}
@@ -0,0 +1,284 @@
+use core::cell::RefCell;
+use embedded_hal::i2c::{AddressMode, Error, ErrorKind, ErrorType, I2c, Multiplexer, Operation};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Bus behind an I2C [`Multiplexer`], exposing each of its channels as an [`I2c`] implementation.
+///
+/// [`channel`](MultiplexedBus::channel) returns a [`MultiplexedChannel`], which selects its channel
+/// before each transaction on the bus, and deselects all the channels after it. This way, devices
+/// with the same address on different channels can be handed to different drivers.
+///
+/// Like [`RefCellDevice`](super::RefCellDevice), the multiplexer and bus are shared through a
+/// `RefCell`, so this only allows sharing within a single thread (interrupt priority level).
+///
+/// # Example
+///
+/// ```
+/// use core::cell::RefCell;
+/// use embedded_hal::i2c::{ErrorType, I2c, Multiplexer};
+/// use embedded_hal_bus::i2c::{MultiplexedBus, RefCellDevice};
+/// # use embedded_hal::i2c::{self as hali2c, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl hali2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # let i2c = I2c0;
+///
+/// /// PCA9548 driver, writing the channel mask to its control register.
+/// struct Pca9548<I2C> {
+///     i2c: I2C,
+///     address: u8,
+/// }
+///
+/// impl<I2C: I2c> ErrorType for Pca9548<I2C> {
+///     type Error = I2C::Error;
+/// }
+///
+/// impl<I2C: I2c> Multiplexer for Pca9548<I2C> {
+///     fn select_channel(&mut self, channel: u8) -> Result<(), Self::Error> {
+///         assert!(channel < 8);
+///         self.i2c.write(self.address, &[1 << channel])
+///     }
+///
+///     fn deselect_all(&mut self) -> Result<(), Self::Error> {
+///         self.i2c.write(self.address, &[0])
+///     }
+/// }
+///
+/// let i2c = RefCell::new(i2c);
+/// let mux = Pca9548 {
+///     i2c: RefCellDevice::new(&i2c),
+///     address: 0x70,
+/// };
+/// let bus = MultiplexedBus::new(mux, RefCellDevice::new(&i2c));
+///
+/// // Two sensors with the same address, on channels 0 and 1.
+/// let mut sensor_a = bus.channel(0);
+/// let mut sensor_b = bus.channel(1);
+/// sensor_a.write(0x48, &[0x01, 0x60])?;
+/// sensor_b.write(0x48, &[0x01, 0x60])?;
+/// # Ok::<(), embedded_hal_bus::i2c::MultiplexedBusError<ErrorKind, ErrorKind>>(())
+/// ```
+pub struct MultiplexedBus<M, B> {
+    inner: RefCell<(M, B)>,
+}
+
+impl<M, B> MultiplexedBus<M, B> {
+    /// Create a new [`MultiplexedBus`], accessing the devices of `bus` through the channels of `mux`.
+    #[inline]
+    pub fn new(mux: M, bus: B) -> Self {
+        Self {
+            inner: RefCell::new((mux, bus)),
+        }
+    }
+
+    /// Returns an [`I2c`] implementation for the devices on `channel`.
+    #[inline]
+    pub fn channel(&self, channel: u8) -> MultiplexedChannel<'_, M, B> {
+        MultiplexedChannel {
+            inner: &self.inner,
+            channel,
+        }
+    }
+
+    /// Release the multiplexer and bus.
+    #[inline]
+    pub fn release(self) -> (M, B) {
+        self.inner.into_inner()
+    }
+}
+
+/// Channel of a [`MultiplexedBus`], returned by [`MultiplexedBus::channel`].
+pub struct MultiplexedChannel<'a, M, B> {
+    inner: &'a RefCell<(M, B)>,
+    channel: u8,
+}
+
+/// Error type for [`MultiplexedChannel`] operations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum MultiplexedBusError<MUX, BUS> {
+    /// Selecting or deselecting the channel failed.
+    Multiplexer(MUX),
+    /// The transaction on the bus failed.
+    Bus(BUS),
+}
+
+impl<MUX: Error, BUS: Error> Error for MultiplexedBusError<MUX, BUS> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Multiplexer(e) => e.kind(),
+            Self::Bus(e) => e.kind(),
+        }
+    }
+}
+
+impl<MUX: core::fmt::Display, BUS: core::fmt::Display> core::fmt::Display
+    for MultiplexedBusError<MUX, BUS>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Multiplexer(e) => write!(f, "I2C multiplexer error: {e}"),
+            Self::Bus(e) => write!(f, "I2C bus error: {e}"),
+        }
+    }
+}
+
+impl<MUX, BUS> core::error::Error for MultiplexedBusError<MUX, BUS>
+where
+    MUX: core::fmt::Debug + core::fmt::Display,
+    BUS: core::fmt::Debug + core::fmt::Display,
+{
+}
+
+impl<M: Multiplexer, B: ErrorType> ErrorType for MultiplexedChannel<'_, M, B> {
+    type Error = MultiplexedBusError<M::Error, B::Error>;
+}
+
+impl<M, B, A> I2c<A> for MultiplexedChannel<'_, M, B>
+where
+    M: Multiplexer,
+    B: I2c<A>,
+    A: AddressMode,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let (mux, bus) = &mut *self.inner.borrow_mut();
+        mux.select_channel(self.channel)
+            .map_err(MultiplexedBusError::Multiplexer)?;
+
+        // Deselect the channel even if the transaction failed, reporting the bus error first.
+        let res = bus.transaction(address, operations);
+        let deselect_res = mux.deselect_all();
+        res.map_err(MultiplexedBusError::Bus)?;
+        deselect_res.map_err(MultiplexedBusError::Multiplexer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{vec, vec::Vec};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Select(u8),
+        DeselectAll,
+        Transaction(u8),
+    }
+
+    type Log = RefCell<Vec<Event>>;
+
+    /// Multiplexer with `channels` channels, rejecting the other ones.
+    struct FakeMux<'a> {
+        log: &'a Log,
+        channels: u8,
+    }
+
+    impl ErrorType for FakeMux<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Multiplexer for FakeMux<'_> {
+        fn select_channel(&mut self, channel: u8) -> Result<(), Self::Error> {
+            if channel >= self.channels {
+                return Err(ErrorKind::Other);
+            }
+            self.log.borrow_mut().push(Event::Select(channel));
+            Ok(())
+        }
+
+        fn deselect_all(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::DeselectAll);
+            Ok(())
+        }
+    }
+
+    /// Bus on which only the device at 0x48 acknowledges.
+    struct FakeBus<'a> {
+        log: &'a Log,
+    }
+
+    impl ErrorType for FakeBus<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus<'_> {
+        fn transaction(&mut self, address: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+            self.log.borrow_mut().push(Event::Transaction(address));
+            if address == 0x48 {
+                Ok(())
+            } else {
+                Err(ErrorKind::NoAcknowledge(
+                    embedded_hal::i2c::NoAcknowledgeSource::Address,
+                ))
+            }
+        }
+    }
+
+    fn fixture(log: &Log) -> MultiplexedBus<FakeMux<'_>, FakeBus<'_>> {
+        MultiplexedBus::new(FakeMux { log, channels: 4 }, FakeBus { log })
+    }
+
+    #[test]
+    fn selects_the_channel_around_each_transaction() {
+        let log = Log::default();
+        let bus = fixture(&log);
+        let mut a = bus.channel(0);
+        let mut b = bus.channel(3);
+        a.write(0x48, &[1]).unwrap();
+        b.read(0x48, &mut [0]).unwrap();
+        a.write_read(0x48, &[1], &mut [0]).unwrap();
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                Event::Select(0),
+                Event::Transaction(0x48),
+                Event::DeselectAll,
+                Event::Select(3),
+                Event::Transaction(0x48),
+                Event::DeselectAll,
+                Event::Select(0),
+                Event::Transaction(0x48),
+                Event::DeselectAll,
+            ]
+        );
+    }
+
+    #[test]
+    fn deselects_after_failed_transaction() {
+        let log = Log::default();
+        let bus = fixture(&log);
+        let err = bus.channel(1).write(0x50, &[1]).unwrap_err();
+        assert!(matches!(err, MultiplexedBusError::Bus(_)));
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                Event::Select(1),
+                Event::Transaction(0x50),
+                Event::DeselectAll
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_selection_skips_the_transaction() {
+        let log = Log::default();
+        let bus = fixture(&log);
+        assert_eq!(
+            bus.channel(4).write(0x48, &[1]),
+            Err(MultiplexedBusError::Multiplexer(ErrorKind::Other))
+        );
+        assert!(log.borrow().is_empty());
+        bus.release();
+    }
+}
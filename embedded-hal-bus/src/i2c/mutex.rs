@@ -1,65 +1,171 @@
-use embedded_hal::i2c::{ErrorType, I2c};
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::i2c::{AddressMode, Error, ErrorKind, ErrorType, I2c, Operation};
 use std::sync::Mutex;
 
-/// `std` `Mutex`-based shared bus [`I2c`] implementation.
+use super::{Device, DeviceWithClockSpeed, DeviceWithPowerManagement};
+use crate::util::{lock_mutex, LockError};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// `std` `Mutex`-based shared bus [`I2c`](embedded_hal::i2c::I2c) implementation.
 ///
 /// Sharing is implemented with an `std` [`Mutex`]. It allows a single bus across multiple threads,
 /// with finer-grained locking than [`CriticalSectionDevice`](super::CriticalSectionDevice). The downside is that
 /// it is only available in `std` targets.
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-pub struct MutexDevice<'a, T> {
+pub type MutexDevice<'a, T> = Device<&'a Mutex<T>>;
+
+/// `std` `Mutex`-based shared bus [`I2c`](embedded_hal::i2c::I2c) implementation applying a per-device
+/// SCL clock frequency.
+///
+/// This is the same as [`MutexDevice`], except that the clock frequency of the device is applied to the
+/// bus at the start of every transaction, see [`DeviceWithClockSpeed`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub type MutexDeviceWithClockSpeed<'a, T> = DeviceWithClockSpeed<&'a Mutex<T>>;
+
+/// `std` `Mutex`-based shared bus [`I2c`](embedded_hal::i2c::I2c) implementation suspending the bus
+/// peripheral between transactions.
+///
+/// This is the same as [`MutexDevice`], except that the bus is resumed at the start of every transaction,
+/// and optionally suspended at its end, see [`DeviceWithPowerManagement`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub type MutexDeviceWithPowerManagement<'a, T> = DeviceWithPowerManagement<&'a Mutex<T>>;
+
+/// `std` `Mutex`-based shared bus [`I2c`] implementation, returning an error instead of panicking when the
+/// mutex is poisoned.
+///
+/// This is the same as [`MutexDevice`], except that an operation started while the mutex is poisoned, because
+/// a thread panicked while using the bus, returns [`MutexDeviceError::Poisoned`] without touching the bus. The
+/// application can then bring the bus back to a known state, and call [`Mutex::clear_poison`].
+///
+/// A device created with [`new_try_lock`](TryMutexDevice::new_try_lock) does not wait for the mutex either:
+/// starting an operation while another thread holds it returns [`MutexDeviceError::Busy`].
+///
+/// # Examples
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::{MutexDeviceError, TryMutexDevice};
+/// use std::sync::Mutex;
+/// # use embedded_hal::i2c::{self as hali2c, ErrorKind, Operation};
+/// # pub struct I2c0;
+/// # impl hali2c::ErrorType for I2c0 {
+/// #     type Error = ErrorKind;
+/// # }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+/// #       Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// let i2c_mutex = Mutex::new(i2c);
+/// let mut sensor = TryMutexDevice::new_try_lock(&i2c_mutex);
+///
+/// let guard = i2c_mutex.lock().unwrap();
+/// assert!(matches!(sensor.write(0x20, &[0]), Err(MutexDeviceError::Busy)));
+/// drop(guard);
+/// sensor.write(0x20, &[0])?;
+/// # Ok::<(), MutexDeviceError<ErrorKind>>(())
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct TryMutexDevice<'a, T> {
     bus: &'a Mutex<T>,
+    wait: bool,
 }
 
-impl<'a, T> MutexDevice<'a, T> {
-    /// Create a new `MutexDevice`.
+impl<'a, T> TryMutexDevice<'a, T> {
+    /// Create a new [`TryMutexDevice`], waiting for the mutex when another thread holds it.
     #[inline]
     pub fn new(bus: &'a Mutex<T>) -> Self {
-        Self { bus }
+        Self { bus, wait: true }
+    }
+
+    /// Create a new [`TryMutexDevice`], returning [`MutexDeviceError::Busy`] when another thread holds the mutex.
+    #[inline]
+    pub fn new_try_lock(bus: &'a Mutex<T>) -> Self {
+        Self { bus, wait: false }
     }
 }
 
-impl<T> ErrorType for MutexDevice<'_, T>
-where
-    T: I2c,
-{
-    type Error = T::Error;
+impl<T: ErrorType> TryMutexDevice<'_, T> {
+    fn lock<R>(
+        &self,
+        f: impl FnOnce(&mut T) -> Result<R, T::Error>,
+    ) -> Result<R, MutexDeviceError<T::Error>> {
+        match lock_mutex(self.bus, self.wait, f) {
+            Ok(result) => result.map_err(MutexDeviceError::Bus),
+            Err(LockError::Poisoned) => Err(MutexDeviceError::Poisoned),
+            Err(LockError::WouldBlock) => Err(MutexDeviceError::Busy),
+        }
+    }
+}
+
+/// Error type for [`TryMutexDevice`] operations.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum MutexDeviceError<BUS> {
+    /// A thread panicked while holding the mutex: the bus may be in an inconsistent state.
+    Poisoned,
+    /// The mutex was held by another thread when the operation was started.
+    Busy,
+    /// An inner I2C bus operation failed.
+    Bus(BUS),
+}
+
+impl<BUS: Display> Display for MutexDeviceError<BUS> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Poisoned => write!(f, "I2C bus mutex poisoned"),
+            Self::Busy => write!(f, "I2C bus already in use"),
+            Self::Bus(bus) => write!(f, "I2C bus error: {bus}"),
+        }
+    }
 }
 
-impl<T> I2c for MutexDevice<'_, T>
+impl<BUS: Debug + Display> core::error::Error for MutexDeviceError<BUS> {}
+
+impl<BUS: Error> Error for MutexDeviceError<BUS> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Poisoned | Self::Busy => ErrorKind::Other,
+            Self::Bus(e) => e.kind(),
+        }
+    }
+}
+
+impl<T: ErrorType> ErrorType for TryMutexDevice<'_, T> {
+    type Error = MutexDeviceError<T::Error>;
+}
+
+impl<T, A: AddressMode> I2c<A> for TryMutexDevice<'_, T>
 where
-    T: I2c,
+    T: I2c<A>,
 {
     #[inline]
-    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.lock().unwrap();
-        bus.read(address, read)
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.read(address, read))
     }
 
     #[inline]
-    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.lock().unwrap();
-        bus.write(address, write)
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write(address, write))
     }
 
     #[inline]
-    fn write_read(
-        &mut self,
-        address: u8,
-        write: &[u8],
-        read: &mut [u8],
-    ) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.lock().unwrap();
-        bus.write_read(address, write, read)
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write_read(address, write, read))
     }
 
     #[inline]
     fn transaction(
         &mut self,
-        address: u8,
-        operations: &mut [embedded_hal::i2c::Operation<'_>],
+        address: A,
+        operations: &mut [Operation<'_>],
     ) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.lock().unwrap();
-        bus.transaction(address, operations)
+        self.lock(|bus| bus.transaction(address, operations))
     }
 }
@@ -0,0 +1,728 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal::i2c::{AddressMode, Error, ErrorKind, ErrorType, I2c, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs as AsyncDelayNs;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Error type for [`StuckBusRecovery`] operations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum RecoveryError<I2C, SCL> {
+    /// The inner I2C bus operation failed, and recovering the bus did not help.
+    I2c(I2C),
+    /// Setting the SCL pin failed during the recovery sequence.
+    Scl(SCL),
+}
+
+impl<I2C: Error, SCL: core::fmt::Debug> Error for RecoveryError<I2C, SCL> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(e) => e.kind(),
+            Self::Scl(_) => ErrorKind::Other,
+        }
+    }
+}
+
+impl<I2C: core::fmt::Display, SCL: core::fmt::Display> core::fmt::Display
+    for RecoveryError<I2C, SCL>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::I2c(e) => write!(f, "I2C bus error: {e}"),
+            Self::Scl(e) => write!(f, "SCL pin error: {e}"),
+        }
+    }
+}
+
+impl<I2C, SCL> core::error::Error for RecoveryError<I2C, SCL>
+where
+    I2C: core::fmt::Debug + core::fmt::Display,
+    SCL: core::fmt::Debug + core::fmt::Display,
+{
+}
+
+/// [`I2c`] wrapper recovering stuck buses.
+///
+/// A target can hold SDA low forever if a transaction was interrupted, for example by a reset of
+/// the controller. The I2C specification describes how to recover from this situation: clock SCL
+/// nine times, so that the target completes the byte it was sending and releases SDA.
+///
+/// When a transaction fails with an error of kind [`ErrorKind::Bus`], [`ErrorKind::ArbitrationLoss`],
+/// [`ErrorKind::NoAcknowledge`] or [`ErrorKind::Timeout`], this wrapper runs the recovery sequence on the
+/// `scl` pin, then tries the transaction again, up to the configured number of retries. Timeouts are
+/// included since many peripherals report a bus held low by a target as one.
+///
+/// The `scl` pin must be able to drive the SCL line while the I2C peripheral is idle, for example a GPIO
+/// configured as open-drain output on the same line. Note that the whole transaction is run again
+/// when retrying, so a write can be received twice if the target stopped acknowledging after the first bytes.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::StuckBusRecovery;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::delay::DelayNs;
+/// # use embedded_hal::digital::{self, OutputPin};
+/// # use embedded_hal::i2c::{self, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # struct Pin;
+/// # impl digital::ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _ns: u32) {} }
+/// # let (i2c, scl, delay) = (I2c0, Pin, Delay);
+///
+/// // Retry twice, clocking SCL at 100 kHz during the recovery.
+/// let mut i2c = StuckBusRecovery::new(i2c, scl, delay, 2, 5_000);
+/// let mut temperature = [0; 2];
+/// i2c.write_read(0x48, &[0x00], &mut temperature)?;
+/// # Ok::<(), embedded_hal_bus::i2c::RecoveryError<ErrorKind, Infallible>>(())
+/// ```
+pub struct StuckBusRecovery<I2C, SCL, D> {
+    i2c: I2C,
+    scl: SCL,
+    delay: D,
+    retries: usize,
+    half_period_ns: u32,
+}
+
+impl<I2C, SCL, D> StuckBusRecovery<I2C, SCL, D> {
+    /// Create a new [`StuckBusRecovery`].
+    ///
+    /// Failed transactions are retried up to `retries` times. During the recovery sequence,
+    /// SCL is held low, then high, for `half_period_ns` nanoseconds for each clock pulse.
+    #[inline]
+    pub fn new(i2c: I2C, scl: SCL, delay: D, retries: usize, half_period_ns: u32) -> Self {
+        Self {
+            i2c,
+            scl,
+            delay,
+            retries,
+            half_period_ns,
+        }
+    }
+
+    /// Release the inner bus, SCL pin and delay.
+    #[inline]
+    pub fn release(self) -> (I2C, SCL, D) {
+        (self.i2c, self.scl, self.delay)
+    }
+}
+
+impl<I2C, SCL: OutputPin, D: DelayNs> StuckBusRecovery<I2C, SCL, D> {
+    /// Run the recovery sequence: clock SCL nine times, leaving it released (high).
+    ///
+    /// This is done automatically when a transaction fails, but can also be called
+    /// manually, for example at startup.
+    pub fn recover(&mut self) -> Result<(), SCL::Error> {
+        for _ in 0..9 {
+            self.scl.set_low()?;
+            self.delay.delay_ns(self.half_period_ns);
+            self.scl.set_high()?;
+            self.delay.delay_ns(self.half_period_ns);
+        }
+        Ok(())
+    }
+}
+
+impl<I2C, SCL, D> ErrorType for StuckBusRecovery<I2C, SCL, D>
+where
+    I2C: ErrorType,
+    SCL: OutputPin,
+{
+    type Error = RecoveryError<I2C::Error, SCL::Error>;
+}
+
+impl<A, I2C, SCL, D> I2c<A> for StuckBusRecovery<I2C, SCL, D>
+where
+    A: AddressMode,
+    I2C: I2c<A>,
+    SCL: OutputPin,
+    D: DelayNs,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut retries = self.retries;
+        loop {
+            let e = match self.i2c.transaction(address, operations) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            let recoverable = matches!(
+                e.kind(),
+                ErrorKind::Bus
+                    | ErrorKind::ArbitrationLoss
+                    | ErrorKind::NoAcknowledge(_)
+                    | ErrorKind::Timeout
+            );
+            if !recoverable || retries == 0 {
+                return Err(RecoveryError::I2c(e));
+            }
+            retries -= 1;
+            self.recover().map_err(RecoveryError::Scl)?;
+        }
+    }
+}
+
+/// Default SCL half-period of [`recover_bus`], in nanoseconds (100 kHz clock).
+pub const DEFAULT_RECOVERY_HALF_PERIOD_NS: u32 = 5_000;
+
+/// Error type for [`recover_bus`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum BusRecoveryError<SCL, SDA> {
+    /// SDA is still held low after nine clock pulses and a STOP condition.
+    SdaStuck,
+    /// SCL is held low by another device, so the bus cannot be clocked.
+    SclStuck,
+    /// Setting or reading the SCL pin failed.
+    Scl(SCL),
+    /// Setting or reading the SDA pin failed.
+    Sda(SDA),
+}
+
+impl<SCL: core::fmt::Debug, SDA: core::fmt::Debug> Error for BusRecoveryError<SCL, SDA> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::SdaStuck | Self::SclStuck => ErrorKind::Bus,
+            Self::Scl(_) | Self::Sda(_) => ErrorKind::Other,
+        }
+    }
+}
+
+impl<SCL: core::fmt::Display, SDA: core::fmt::Display> core::fmt::Display
+    for BusRecoveryError<SCL, SDA>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::SdaStuck => write!(f, "SDA is stuck low"),
+            Self::SclStuck => write!(f, "SCL is stuck low"),
+            Self::Scl(e) => write!(f, "SCL pin error: {e}"),
+            Self::Sda(e) => write!(f, "SDA pin error: {e}"),
+        }
+    }
+}
+
+impl<SCL, SDA> core::error::Error for BusRecoveryError<SCL, SDA>
+where
+    SCL: core::fmt::Debug + core::fmt::Display,
+    SDA: core::fmt::Debug + core::fmt::Display,
+{
+}
+
+/// Pins of the bus being recovered, driven as open-drain outputs.
+struct RecoveryPins<'a, SCL, SDA> {
+    scl: &'a mut SCL,
+    sda: &'a mut SDA,
+}
+
+type PinsResult<T, SCL, SDA> = Result<
+    T,
+    BusRecoveryError<
+        <SCL as embedded_hal::digital::ErrorType>::Error,
+        <SDA as embedded_hal::digital::ErrorType>::Error,
+    >,
+>;
+
+impl<SCL: OutputPin + InputPin, SDA: OutputPin + InputPin> RecoveryPins<'_, SCL, SDA> {
+    fn scl(&mut self, high: bool) -> PinsResult<(), SCL, SDA> {
+        if high {
+            self.scl.set_high()
+        } else {
+            self.scl.set_low()
+        }
+        .map_err(BusRecoveryError::Scl)
+    }
+
+    fn sda(&mut self, high: bool) -> PinsResult<(), SCL, SDA> {
+        if high {
+            self.sda.set_high()
+        } else {
+            self.sda.set_low()
+        }
+        .map_err(BusRecoveryError::Sda)
+    }
+
+    fn sda_is_high(&mut self) -> PinsResult<bool, SCL, SDA> {
+        self.sda.is_high().map_err(BusRecoveryError::Sda)
+    }
+
+    /// Check that SCL follows the released level.
+    fn check_scl(&mut self) -> PinsResult<(), SCL, SDA> {
+        if self.scl.is_high().map_err(BusRecoveryError::Scl)? {
+            Ok(())
+        } else {
+            Err(BusRecoveryError::SclStuck)
+        }
+    }
+}
+
+/// Recover an I2C bus whose SDA line is held low by a target.
+///
+/// A target can hold SDA low forever if a transaction was interrupted, for example by a reset of
+/// the controller in the middle of a read. This clocks SCL up to nine times, until the target
+/// completes the byte it was sending and releases SDA, then generates a STOP condition.
+///
+/// `scl` and `sda` must drive the bus lines as open-drain outputs, and read back their level: this
+/// is typically done with GPIOs, before handing the pins over to the I2C peripheral. SCL is held
+/// low, then released, for `half_period_ns` nanoseconds for each clock pulse, see
+/// [`DEFAULT_RECOVERY_HALF_PERIOD_NS`]. Both lines are released when this returns successfully.
+///
+/// Returns [`BusRecoveryError::SclStuck`] if SCL does not go high when released, and
+/// [`BusRecoveryError::SdaStuck`] if SDA is still low at the end of the sequence.
+///
+/// With the `async` feature, [`recover_bus_async`] does the same with an async delay.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c::{recover_bus, DEFAULT_RECOVERY_HALF_PERIOD_NS};
+/// # use core::convert::Infallible;
+/// # use embedded_hal::delay::DelayNs;
+/// # use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+/// # struct Pin;
+/// # impl ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # impl InputPin for Pin {
+/// #     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// #     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(false) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _ns: u32) {} }
+/// # let (mut scl, mut sda, mut delay) = (Pin, Pin, Delay);
+///
+/// recover_bus(&mut scl, &mut sda, &mut delay, DEFAULT_RECOVERY_HALF_PERIOD_NS)?;
+/// // ... hand over `scl` and `sda` to the I2C peripheral ...
+/// # Ok::<(), embedded_hal_bus::i2c::BusRecoveryError<Infallible, Infallible>>(())
+/// ```
+pub fn recover_bus<SCL, SDA, D>(
+    scl: &mut SCL,
+    sda: &mut SDA,
+    delay: &mut D,
+    half_period_ns: u32,
+) -> Result<(), BusRecoveryError<SCL::Error, SDA::Error>>
+where
+    SCL: OutputPin + InputPin,
+    SDA: OutputPin + InputPin,
+    D: DelayNs,
+{
+    let mut pins = RecoveryPins { scl, sda };
+    pins.sda(true)?;
+    pins.scl(true)?;
+    delay.delay_ns(half_period_ns);
+    pins.check_scl()?;
+
+    for _ in 0..9 {
+        if pins.sda_is_high()? {
+            break;
+        }
+        pins.scl(false)?;
+        delay.delay_ns(half_period_ns);
+        pins.scl(true)?;
+        delay.delay_ns(half_period_ns);
+        pins.check_scl()?;
+    }
+
+    // STOP condition: SDA rising while SCL is high.
+    pins.scl(false)?;
+    delay.delay_ns(half_period_ns);
+    pins.sda(false)?;
+    delay.delay_ns(half_period_ns);
+    pins.scl(true)?;
+    delay.delay_ns(half_period_ns);
+    pins.sda(true)?;
+    delay.delay_ns(half_period_ns);
+
+    if pins.sda_is_high()? {
+        Ok(())
+    } else {
+        Err(BusRecoveryError::SdaStuck)
+    }
+}
+
+/// Recover an I2C bus whose SDA line is held low by a target, with an async delay.
+///
+/// See [`recover_bus`] for details.
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub async fn recover_bus_async<SCL, SDA, D>(
+    scl: &mut SCL,
+    sda: &mut SDA,
+    delay: &mut D,
+    half_period_ns: u32,
+) -> Result<(), BusRecoveryError<SCL::Error, SDA::Error>>
+where
+    SCL: OutputPin + InputPin,
+    SDA: OutputPin + InputPin,
+    D: AsyncDelayNs,
+{
+    let mut pins = RecoveryPins { scl, sda };
+    pins.sda(true)?;
+    pins.scl(true)?;
+    delay.delay_ns(half_period_ns).await;
+    pins.check_scl()?;
+
+    for _ in 0..9 {
+        if pins.sda_is_high()? {
+            break;
+        }
+        pins.scl(false)?;
+        delay.delay_ns(half_period_ns).await;
+        pins.scl(true)?;
+        delay.delay_ns(half_period_ns).await;
+        pins.check_scl()?;
+    }
+
+    // STOP condition: SDA rising while SCL is high.
+    pins.scl(false)?;
+    delay.delay_ns(half_period_ns).await;
+    pins.sda(false)?;
+    delay.delay_ns(half_period_ns).await;
+    pins.scl(true)?;
+    delay.delay_ns(half_period_ns).await;
+    pins.sda(true)?;
+    delay.delay_ns(half_period_ns).await;
+
+    if pins.sda_is_high()? {
+        Ok(())
+    } else {
+        Err(BusRecoveryError::SdaStuck)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::digital;
+    use embedded_hal::i2c::NoAcknowledgeSource;
+    use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Event {
+        Transaction,
+        SclLow,
+        SclHigh,
+        Delay(u32),
+    }
+
+    type Log = Rc<RefCell<Vec<Event>>>;
+
+    /// Bus failing with the given errors, then succeeding.
+    struct FakeBus {
+        log: Log,
+        errors: Vec<ErrorKind>,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+            self.log.borrow_mut().push(Event::Transaction);
+            if self.errors.is_empty() {
+                Ok(())
+            } else {
+                Err(self.errors.remove(0))
+            }
+        }
+    }
+
+    struct FakePin(Log);
+
+    impl digital::ErrorType for FakePin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for FakePin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.0.borrow_mut().push(Event::SclLow);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.0.borrow_mut().push(Event::SclHigh);
+            Ok(())
+        }
+    }
+
+    struct FakeDelay(Log);
+
+    impl DelayNs for FakeDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.borrow_mut().push(Event::Delay(ns));
+        }
+    }
+
+    fn recovery(
+        errors: &[ErrorKind],
+        retries: usize,
+    ) -> (Log, StuckBusRecovery<FakeBus, FakePin, FakeDelay>) {
+        let log = Log::default();
+        let bus = FakeBus {
+            log: log.clone(),
+            errors: errors.into(),
+        };
+        let i2c = StuckBusRecovery::new(
+            bus,
+            FakePin(log.clone()),
+            FakeDelay(log.clone()),
+            retries,
+            5_000,
+        );
+        (log, i2c)
+    }
+
+    fn count(log: &Log, event: Event) -> usize {
+        log.borrow().iter().filter(|e| **e == event).count()
+    }
+
+    #[test]
+    fn recovery_sequence() {
+        let (log, mut i2c) = recovery(&[], 0);
+        i2c.recover().unwrap();
+        let pulse = [
+            Event::SclLow,
+            Event::Delay(5_000),
+            Event::SclHigh,
+            Event::Delay(5_000),
+        ];
+        assert_eq!(*log.borrow(), pulse.repeat(9));
+    }
+
+    #[test]
+    fn recovers_and_retries() {
+        let (log, mut i2c) = recovery(&[ErrorKind::Bus], 1);
+        assert_eq!(i2c.write(0x10, &[1]), Ok(()));
+        assert_eq!(count(&log, Event::Transaction), 2);
+        assert_eq!(count(&log, Event::SclLow), 9);
+        assert_eq!(log.borrow().last(), Some(&Event::Transaction));
+    }
+
+    #[test]
+    fn recovers_from_timeouts() {
+        let (log, mut i2c) = recovery(&[ErrorKind::Timeout], 1);
+        assert_eq!(i2c.write(0x10, &[1]), Ok(()));
+        assert_eq!(count(&log, Event::Transaction), 2);
+    }
+
+    #[test]
+    fn gives_up_after_retries() {
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        let (log, mut i2c) = recovery(&[nack; 3], 2);
+        assert_eq!(i2c.read(0x10, &mut [0]), Err(RecoveryError::I2c(nack)));
+        assert_eq!(count(&log, Event::Transaction), 3);
+        assert_eq!(count(&log, Event::SclLow), 18);
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        let (log, mut i2c) = recovery(&[ErrorKind::Overrun], 2);
+        assert_eq!(
+            i2c.write(0x10, &[1]),
+            Err(RecoveryError::I2c(ErrorKind::Overrun))
+        );
+        assert_eq!(*log.borrow(), [Event::Transaction]);
+    }
+
+    /// Lines of a bus with a stuck target, which releases SDA after `release_after` SCL pulses.
+    #[derive(Default)]
+    struct Lines {
+        scl_driven_low: bool,
+        sda_driven_low: bool,
+        scl_stuck: bool,
+        release_after: usize,
+        pulses: usize,
+        stop: bool,
+    }
+
+    type SharedLines = Rc<RefCell<Lines>>;
+
+    struct LinePin {
+        lines: SharedLines,
+        scl: bool,
+    }
+
+    impl digital::ErrorType for LinePin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for LinePin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            let mut lines = self.lines.borrow_mut();
+            if self.scl {
+                lines.scl_driven_low = true;
+            } else {
+                lines.sda_driven_low = true;
+            }
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            let mut lines = self.lines.borrow_mut();
+            if self.scl {
+                if lines.scl_driven_low {
+                    lines.pulses += 1;
+                }
+                lines.scl_driven_low = false;
+            } else {
+                if lines.sda_driven_low && !lines.scl_driven_low {
+                    lines.stop = true;
+                }
+                lines.sda_driven_low = false;
+            }
+            Ok(())
+        }
+    }
+
+    impl InputPin for LinePin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            let lines = self.lines.borrow();
+            Ok(if self.scl {
+                !lines.scl_driven_low && !lines.scl_stuck
+            } else {
+                !lines.sda_driven_low && lines.pulses >= lines.release_after
+            })
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    struct NoopDelay;
+
+    impl DelayNs for NoopDelay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    fn unstick(
+        lines: Lines,
+    ) -> (
+        SharedLines,
+        Result<(), BusRecoveryError<Infallible, Infallible>>,
+    ) {
+        let lines = Rc::new(RefCell::new(lines));
+        let mut scl = LinePin {
+            lines: lines.clone(),
+            scl: true,
+        };
+        let mut sda = LinePin {
+            lines: lines.clone(),
+            scl: false,
+        };
+        let result = recover_bus(&mut scl, &mut sda, &mut NoopDelay, 5_000);
+        (lines, result)
+    }
+
+    #[test]
+    fn releases_stuck_target() {
+        let (lines, result) = unstick(Lines {
+            release_after: 4,
+            ..Default::default()
+        });
+        assert_eq!(result, Ok(()));
+        let lines = lines.borrow();
+        // Four pulses to release SDA, and one for the STOP condition.
+        assert_eq!(lines.pulses, 5);
+        assert!(lines.stop);
+        assert!(!lines.scl_driven_low && !lines.sda_driven_low);
+    }
+
+    #[test]
+    fn idle_bus_only_gets_a_stop() {
+        let (lines, result) = unstick(Lines::default());
+        assert_eq!(result, Ok(()));
+        assert_eq!(lines.borrow().pulses, 1);
+        assert!(lines.borrow().stop);
+    }
+
+    #[test]
+    fn sda_stuck() {
+        let (lines, result) = unstick(Lines {
+            release_after: 100,
+            ..Default::default()
+        });
+        assert_eq!(result, Err(BusRecoveryError::SdaStuck));
+        assert_eq!(lines.borrow().pulses, 10);
+    }
+
+    #[test]
+    fn scl_stuck() {
+        let (lines, result) = unstick(Lines {
+            release_after: 1,
+            scl_stuck: true,
+            ..Default::default()
+        });
+        assert_eq!(result, Err(BusRecoveryError::SclStuck));
+        assert_eq!(lines.borrow().pulses, 0);
+    }
+
+    #[test]
+    fn bus_recovery_error_kinds() {
+        use embedded_hal::digital::ErrorKind as PinErrorKind;
+
+        type E = BusRecoveryError<PinErrorKind, PinErrorKind>;
+        assert_eq!(E::SdaStuck.kind(), ErrorKind::Bus);
+        assert_eq!(E::SclStuck.kind(), ErrorKind::Bus);
+        let scl = E::Scl(PinErrorKind::Other);
+        assert_eq!(scl.kind(), ErrorKind::Other);
+        assert_eq!(
+            std::format!("{scl}"),
+            std::format!("SCL pin error: {}", PinErrorKind::Other)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn recover_bus_async_releases_stuck_target() {
+        struct AsyncNoopDelay;
+
+        impl AsyncDelayNs for AsyncNoopDelay {
+            async fn delay_ns(&mut self, _ns: u32) {}
+        }
+
+        let lines = Rc::new(RefCell::new(Lines {
+            release_after: 9,
+            ..Default::default()
+        }));
+        let mut scl = LinePin {
+            lines: lines.clone(),
+            scl: true,
+        };
+        let mut sda = LinePin {
+            lines: lines.clone(),
+            scl: false,
+        };
+        let result = crate::util::poll_to_completion(recover_bus_async(
+            &mut scl,
+            &mut sda,
+            &mut AsyncNoopDelay,
+            5_000,
+        ));
+        assert_eq!(result, Ok(()));
+        assert_eq!(lines.borrow().pulses, 10);
+    }
+}
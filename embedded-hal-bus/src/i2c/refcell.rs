@@ -1,12 +1,37 @@
 use core::cell::RefCell;
-use embedded_hal::i2c::{ErrorType, I2c};
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::i2c::{AddressMode, Error, ErrorKind, ErrorType, I2c, Operation};
+#[cfg(feature = "async")]
+use embedded_hal::i2c::{I2cBusSpeed, I2cPowerManagement};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
 
-/// `RefCell`-based shared bus [`I2c`] implementation.
+#[cfg(feature = "async")]
+use super::device::suspend_after;
+use super::{Device, DeviceWithClockSpeed, DeviceWithPowerManagement};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// `RefCell`-based shared bus [`I2c`](embedded_hal::i2c::I2c) implementation.
 ///
 /// Sharing is implemented with a `RefCell`. This means it has low overhead, but `RefCellDevice` instances are not `Send`,
 /// so it only allows sharing within a single thread (interrupt priority level). If you need to share a bus across several
 /// threads, use [`CriticalSectionDevice`](super::CriticalSectionDevice) instead.
 ///
+/// Starting an operation while the bus is already borrowed, for example from a driver calling another device
+/// sharing the same bus in the middle of a transaction, panics. Use [`TryRefCellDevice`] to get a
+/// [`RefCellDeviceError::Busy`] error instead.
+///
+/// # Async
+///
+/// With the `async` feature, `RefCellDevice` also implements the async
+/// [`I2c`](embedded_hal_async::i2c::I2c) trait if the bus does. The `RefCell` stays borrowed
+/// across `.await` points for the whole transaction, so all devices sharing the bus must be used from
+/// tasks of the same executor, and a transaction on one device must not be started while another
+/// device's transaction is still in progress: this causes a panic. [`TryRefCellDevice`] returns
+/// [`RefCellDeviceError::Busy`] instead, which the task can handle, e.g. by retrying later.
+///
 /// # Examples
 ///
 /// Assuming there is a pressure sensor with address `0x42` on the same bus as a temperature sensor
@@ -63,59 +88,226 @@ use embedded_hal::i2c::{ErrorType, I2c};
 ///   0x42,
 /// );
 /// ```
-pub struct RefCellDevice<'a, T> {
+pub type RefCellDevice<'a, T> = Device<&'a RefCell<T>>;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for RefCellDevice<'_, T>
+where
+    T: AsyncI2c<A>,
+{
+    #[inline]
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        // Holding the borrow across `.await` points is intentional, see the type's documentation.
+        let bus = &mut *self.bus.borrow_mut();
+        bus.transaction(address, operations).await
+    }
+}
+
+/// `RefCell`-based shared bus [`I2c`] implementation applying a per-device SCL clock frequency.
+///
+/// This is the same as [`RefCellDevice`], except that the clock frequency of the device is applied to
+/// the bus at the start of every transaction, see [`DeviceWithClockSpeed`].
+pub type RefCellDeviceWithClockSpeed<'a, T> = DeviceWithClockSpeed<&'a RefCell<T>>;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for RefCellDeviceWithClockSpeed<'_, T>
+where
+    T: AsyncI2c<A> + I2cBusSpeed,
+{
+    #[inline]
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        // Holding the borrow across `.await` points is intentional, see the documentation of `RefCellDevice`.
+        let bus = &mut *self.bus.borrow_mut();
+        bus.set_clock_speed(self.frequency_hz)?;
+        bus.transaction(address, operations).await
+    }
+}
+
+/// `RefCell`-based shared bus [`I2c`] implementation suspending the bus peripheral between transactions.
+///
+/// This is the same as [`RefCellDevice`], except that the bus is resumed at the start of every transaction,
+/// and optionally suspended at its end, see [`DeviceWithPowerManagement`].
+pub type RefCellDeviceWithPowerManagement<'a, T> = DeviceWithPowerManagement<&'a RefCell<T>>;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for RefCellDeviceWithPowerManagement<'_, T>
+where
+    T: AsyncI2c<A> + I2cPowerManagement,
+{
+    #[inline]
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        // Holding the borrow across `.await` points is intentional, see the documentation of `RefCellDevice`.
+        let bus = &mut *self.bus.borrow_mut();
+        bus.resume()?;
+        let result = bus.transaction(address, operations).await;
+        suspend_after(bus, self.suspend_when_idle, result)
+    }
+}
+
+/// `RefCell`-based shared bus [`I2c`] implementation, returning an error instead of panicking when the bus is busy.
+///
+/// This is the same as [`RefCellDevice`], except that starting an operation while the `RefCell` is already
+/// borrowed returns [`RefCellDeviceError::Busy`]. Such reentrant accesses are usually bugs, and this lets an
+/// application handle them without a panic.
+///
+/// With the `async` feature, the async [`I2c`](embedded_hal_async::i2c::I2c) trait is also
+/// implemented if the bus does. The `RefCell` stays borrowed for the whole transaction, across `.await`
+/// points: a transaction started from another task in the meantime returns [`RefCellDeviceError::Busy`].
+///
+/// # Examples
+///
+/// ```
+/// use core::cell::RefCell;
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::{RefCellDeviceError, TryRefCellDevice};
+/// # use embedded_hal::i2c::{self as hali2c, ErrorKind, Operation};
+/// # pub struct I2c0;
+/// # impl hali2c::ErrorType for I2c0 {
+/// #     type Error = ErrorKind;
+/// # }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+/// #       Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// let i2c_ref_cell = RefCell::new(i2c);
+/// let mut sensor = TryRefCellDevice::new(&i2c_ref_cell);
+///
+/// let guard = i2c_ref_cell.borrow_mut();
+/// assert!(matches!(sensor.write(0x20, &[0]), Err(RefCellDeviceError::Busy)));
+/// drop(guard);
+/// sensor.write(0x20, &[0])?;
+/// # Ok::<(), RefCellDeviceError<ErrorKind>>(())
+/// ```
+pub struct TryRefCellDevice<'a, T> {
     bus: &'a RefCell<T>,
 }
 
-impl<'a, T> RefCellDevice<'a, T> {
-    /// Create a new `RefCellDevice`.
+impl<'a, T> TryRefCellDevice<'a, T> {
+    /// Create a new [`TryRefCellDevice`].
     #[inline]
     pub fn new(bus: &'a RefCell<T>) -> Self {
         Self { bus }
     }
 }
 
-impl<T> ErrorType for RefCellDevice<'_, T>
-where
-    T: I2c,
-{
-    type Error = T::Error;
+impl<T: ErrorType> TryRefCellDevice<'_, T> {
+    fn lock<R>(
+        &self,
+        f: impl FnOnce(&mut T) -> Result<R, T::Error>,
+    ) -> Result<R, RefCellDeviceError<T::Error>> {
+        let mut bus = self
+            .bus
+            .try_borrow_mut()
+            .map_err(|_| RefCellDeviceError::Busy)?;
+        f(&mut bus).map_err(RefCellDeviceError::Bus)
+    }
+}
+
+/// Error type for [`TryRefCellDevice`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum RefCellDeviceError<BUS> {
+    /// The bus was already borrowed when the operation was started.
+    Busy,
+    /// An inner I2C bus operation failed.
+    Bus(BUS),
 }
 
-impl<T> I2c for RefCellDevice<'_, T>
+impl<BUS: Display> Display for RefCellDeviceError<BUS> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Busy => write!(f, "I2C bus already in use"),
+            Self::Bus(bus) => write!(f, "I2C bus error: {bus}"),
+        }
+    }
+}
+
+impl<BUS: Debug + Display> core::error::Error for RefCellDeviceError<BUS> {}
+
+impl<BUS: Error> Error for RefCellDeviceError<BUS> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Busy => ErrorKind::Other,
+            Self::Bus(e) => e.kind(),
+        }
+    }
+}
+
+impl<T: ErrorType> ErrorType for TryRefCellDevice<'_, T> {
+    type Error = RefCellDeviceError<T::Error>;
+}
+
+impl<T, A: AddressMode> I2c<A> for TryRefCellDevice<'_, T>
 where
-    T: I2c,
+    T: I2c<A>,
 {
     #[inline]
-    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.borrow_mut();
-        bus.read(address, read)
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.read(address, read))
     }
 
     #[inline]
-    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.borrow_mut();
-        bus.write(address, write)
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write(address, write))
     }
 
     #[inline]
-    fn write_read(
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.lock(|bus| bus.write_read(address, write, read))
+    }
+
+    #[inline]
+    fn transaction(
         &mut self,
-        address: u8,
-        write: &[u8],
-        read: &mut [u8],
+        address: A,
+        operations: &mut [Operation<'_>],
     ) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.borrow_mut();
-        bus.write_read(address, write, read)
+        self.lock(|bus| bus.transaction(address, operations))
     }
+}
 
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A: AddressMode> AsyncI2c<A> for TryRefCellDevice<'_, T>
+where
+    T: AsyncI2c<A>,
+{
     #[inline]
-    fn transaction(
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn transaction(
         &mut self,
-        address: u8,
-        operations: &mut [embedded_hal::i2c::Operation<'_>],
+        address: A,
+        operations: &mut [Operation<'_>],
     ) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.borrow_mut();
+        // Holding the borrow across `.await` points is intentional, see the type's documentation.
+        let mut bus = self
+            .bus
+            .try_borrow_mut()
+            .map_err(|_| RefCellDeviceError::Busy)?;
         bus.transaction(address, operations)
+            .await
+            .map_err(RefCellDeviceError::Bus)
     }
 }
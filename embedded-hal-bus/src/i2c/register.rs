@@ -0,0 +1,304 @@
+use embedded_hal::i2c::{I2c, Operation};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Byte order of multi-byte register values.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Endianness {
+    /// Most significant byte first.
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+/// Address of a register, as sent on the bus before the register data.
+///
+/// This is implemented for `u8`, for 8-bit register addresses, and `u16`, for 16-bit register
+/// addresses sent most significant byte first (as is the case for most EEPROMs and camera sensors).
+pub trait RegisterAddress: Copy {
+    /// Encoded register address.
+    type Bytes: AsRef<[u8]>;
+
+    /// Encode the register address.
+    fn to_bytes(self) -> Self::Bytes;
+}
+
+impl RegisterAddress for u8 {
+    type Bytes = [u8; 1];
+
+    #[inline]
+    fn to_bytes(self) -> [u8; 1] {
+        [self]
+    }
+}
+
+impl RegisterAddress for u16 {
+    type Bytes = [u8; 2];
+
+    #[inline]
+    fn to_bytes(self) -> [u8; 2] {
+        self.to_be_bytes()
+    }
+}
+
+/// Extension trait for [`I2c`], adding register accesses.
+///
+/// Registers are read by writing the register address then reading in the same transaction,
+/// and written by writing the register address followed by the data. The width of the register
+/// address is selected by the type of `reg`, see [`RegisterAddress`].
+///
+/// This trait is implemented for all I2C buses.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c::{Endianness, RegisterAccess};
+/// # use embedded_hal::i2c::{self, ErrorKind, I2c, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         if let [_, Operation::Read(buf)] = ops {
+/// #             buf.fill(0x01);
+/// #         }
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let mut i2c = I2c0;
+///
+/// const SENSOR: u8 = 0x76;
+/// const CTRL: u8 = 0xF4;
+/// const DATA: u8 = 0xFA;
+///
+/// // Set the mode bits of the control register, keeping the other ones.
+/// i2c.modify_register(SENSOR, CTRL, |ctrl| ctrl | 0b11)?;
+/// let raw = i2c.read_register_u16(SENSOR, DATA, Endianness::Big)?;
+/// assert_eq!(raw, 0x0101);
+///
+/// // 16-bit register addresses are selected by the type of the register.
+/// let id = i2c.read_register_u8(0x3C, 0x300Au16)?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub trait RegisterAccess: I2c {
+    /// Read the 8-bit register `reg`.
+    #[inline]
+    fn read_register_u8<R: RegisterAddress>(
+        &mut self,
+        address: u8,
+        reg: R,
+    ) -> Result<u8, Self::Error> {
+        let mut buf = [0];
+        self.read_registers(address, reg, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    /// Write `value` to the 8-bit register `reg`.
+    #[inline]
+    fn write_register_u8<R: RegisterAddress>(
+        &mut self,
+        address: u8,
+        reg: R,
+        value: u8,
+    ) -> Result<(), Self::Error> {
+        self.write_registers(address, reg, &[value])
+    }
+
+    /// Read the 16-bit register `reg`, transferred in `endianness` order.
+    #[inline]
+    fn read_register_u16<R: RegisterAddress>(
+        &mut self,
+        address: u8,
+        reg: R,
+        endianness: Endianness,
+    ) -> Result<u16, Self::Error> {
+        let mut buf = [0; 2];
+        self.read_registers(address, reg, &mut buf)?;
+        Ok(match endianness {
+            Endianness::Big => u16::from_be_bytes(buf),
+            Endianness::Little => u16::from_le_bytes(buf),
+        })
+    }
+
+    /// Write `value` to the 16-bit register `reg`, transferred in `endianness` order.
+    #[inline]
+    fn write_register_u16<R: RegisterAddress>(
+        &mut self,
+        address: u8,
+        reg: R,
+        value: u16,
+        endianness: Endianness,
+    ) -> Result<(), Self::Error> {
+        let bytes = match endianness {
+            Endianness::Big => value.to_be_bytes(),
+            Endianness::Little => value.to_le_bytes(),
+        };
+        self.write_registers(address, reg, &bytes)
+    }
+
+    /// Read consecutive registers starting at `reg`, relying on the device auto-incrementing
+    /// the register address.
+    #[inline]
+    fn read_registers<R: RegisterAddress>(
+        &mut self,
+        address: u8,
+        reg: R,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write_read(address, reg.to_bytes().as_ref(), buf)
+    }
+
+    /// Write consecutive registers starting at `reg`, relying on the device auto-incrementing
+    /// the register address.
+    #[inline]
+    fn write_registers<R: RegisterAddress>(
+        &mut self,
+        address: u8,
+        reg: R,
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        self.transaction(
+            address,
+            &mut [
+                Operation::Write(reg.to_bytes().as_ref()),
+                Operation::Write(data),
+            ],
+        )
+    }
+
+    /// Read the 8-bit register `reg`, and write back the value returned by `f`.
+    ///
+    /// The read and the write are separate transactions. `f` is given the value read from the
+    /// register, which for some registers (e.g. status bits or write-1-to-clear flags) is not
+    /// the value last written. Returns the value written.
+    #[inline]
+    fn modify_register<R: RegisterAddress>(
+        &mut self,
+        address: u8,
+        reg: R,
+        f: impl FnOnce(u8) -> u8,
+    ) -> Result<u8, Self::Error> {
+        let value = f(self.read_register_u8(address, reg)?);
+        self.write_register_u8(address, reg, value)?;
+        Ok(value)
+    }
+}
+
+impl<T: I2c> RegisterAccess for T {}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, ErrorType};
+    use std::vec::Vec;
+
+    /// Device with 16 registers, a register pointer auto-incremented on each access, and an
+    /// optional register which reads back inverted.
+    #[derive(Default)]
+    struct FakeDevice {
+        registers: [u8; 16],
+        inverted: Option<usize>,
+        pointer: usize,
+        addresses: Vec<Vec<u8>>,
+    }
+
+    impl ErrorType for FakeDevice {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeDevice {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            assert_eq!(address, 0x20);
+            let [Operation::Write(reg), rest @ ..] = operations else {
+                panic!("transaction must start with the register address");
+            };
+            self.addresses.push(reg.to_vec());
+            self.pointer = usize::from(*reg.last().unwrap());
+            for op in rest {
+                match op {
+                    Operation::Write(data) => {
+                        for &byte in data.iter() {
+                            self.registers[self.pointer] = byte;
+                            self.pointer += 1;
+                        }
+                    }
+                    Operation::Read(buf) => {
+                        for byte in buf.iter_mut() {
+                            let value = self.registers[self.pointer];
+                            *byte = if self.inverted == Some(self.pointer) {
+                                !value
+                            } else {
+                                value
+                            };
+                            self.pointer += 1;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn u8_registers() {
+        let mut dev = FakeDevice::default();
+        dev.write_register_u8(0x20, 3u8, 0x42).unwrap();
+        assert_eq!(dev.read_register_u8(0x20, 3u8), Ok(0x42));
+        assert_eq!(dev.registers[3], 0x42);
+    }
+
+    #[test]
+    fn u16_endianness() {
+        let mut dev = FakeDevice::default();
+        dev.write_register_u16(0x20, 0u8, 0x1234, Endianness::Big)
+            .unwrap();
+        dev.write_register_u16(0x20, 2u8, 0x1234, Endianness::Little)
+            .unwrap();
+        assert_eq!(dev.registers[..4], [0x12, 0x34, 0x34, 0x12]);
+        assert_eq!(
+            dev.read_register_u16(0x20, 0u8, Endianness::Big),
+            Ok(0x1234)
+        );
+        assert_eq!(
+            dev.read_register_u16(0x20, 0u8, Endianness::Little),
+            Ok(0x3412)
+        );
+    }
+
+    #[test]
+    fn burst_access() {
+        let mut dev = FakeDevice::default();
+        dev.write_registers(0x20, 4u8, &[1, 2, 3]).unwrap();
+        let mut buf = [0; 4];
+        dev.read_registers(0x20, 3u8, &mut buf).unwrap();
+        assert_eq!(buf, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn sixteen_bit_register_address() {
+        let mut dev = FakeDevice::default();
+        dev.write_register_u8(0x20, 0x0105u16, 7).unwrap();
+        assert_eq!(dev.addresses, [[0x01, 0x05]]);
+        assert_eq!(dev.registers[5], 7);
+    }
+
+    #[test]
+    fn modify_uses_value_read_back() {
+        let mut dev = FakeDevice {
+            inverted: Some(1),
+            ..Default::default()
+        };
+        dev.registers[1] = 0b0000_1111;
+        // The register reads back as 0b1111_0000.
+        assert_eq!(dev.modify_register(0x20, 1u8, |v| v | 1), Ok(0b1111_0001));
+        assert_eq!(dev.registers[1], 0b1111_0001);
+        assert_eq!(dev.addresses.len(), 2);
+    }
+}
@@ -0,0 +1,328 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{
+    AddressMode, Error, ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, i2c::I2c as AsyncI2c};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Delay between the attempts of a [`Retry`] wrapper.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Backoff {
+    /// Wait the same number of nanoseconds before each retry.
+    Fixed(u32),
+    /// Wait `initial_ns` before the first retry, then double the delay before each
+    /// following retry, up to `max_ns`.
+    Exponential {
+        /// Delay before the first retry, in nanoseconds.
+        initial_ns: u32,
+        /// Maximum delay between two attempts, in nanoseconds.
+        max_ns: u32,
+    },
+}
+
+impl Backoff {
+    /// Delay before retry number `retry`, counting from zero.
+    fn delay_ns(&self, retry: u32) -> u32 {
+        match *self {
+            Self::Fixed(ns) => ns,
+            Self::Exponential { initial_ns, max_ns } => 1u32
+                .checked_shl(retry)
+                .map_or(u32::MAX, |factor| initial_ns.saturating_mul(factor))
+                .min(max_ns),
+        }
+    }
+}
+
+/// Default predicate of [`Retry`]: address NACKs and arbitration losses are retried.
+fn is_transient(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address) | ErrorKind::ArbitrationLoss
+    )
+}
+
+/// [`I2c`] wrapper retrying transactions which failed with a transient error.
+///
+/// Some errors are expected from time to time on a healthy bus: EEPROMs do not acknowledge their
+/// address while a write cycle is in progress, and another controller can win the arbitration on a
+/// multi-controller bus. When a transaction fails with such an error, this wrapper waits according
+/// to its [`Backoff`], then runs the whole transaction again, up to `max_attempts` attempts in
+/// total. The error of the last attempt is returned if they all fail.
+///
+/// By default, [`ErrorKind::NoAcknowledge`] errors with [`NoAcknowledgeSource::Address`] and
+/// [`ErrorKind::ArbitrationLoss`] errors are retried, see [`Retry::retry_if`] to select other errors.
+/// Note that a write may be received twice if the target stopped acknowledging after the first bytes.
+///
+/// With the `async` feature, `Retry` also implements the async [`I2c`](embedded_hal_async::i2c::I2c)
+/// trait if the inner bus does, waiting with an async [`DelayNs`](embedded_hal_async::delay::DelayNs).
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::{Backoff, Retry};
+/// # use embedded_hal::delay::DelayNs;
+/// # use embedded_hal::i2c::{self, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _ns: u32) {} }
+/// # let (i2c, delay) = (I2c0, Delay);
+///
+/// // Try up to 5 times, waiting 100 µs, 200 µs, 400 µs then 800 µs between the attempts.
+/// let backoff = Backoff::Exponential {
+///     initial_ns: 100_000,
+///     max_ns: 1_000_000,
+/// };
+/// let mut i2c = Retry::new(i2c, delay, 5, backoff);
+/// i2c.write(0x50, &[0x00, 0x10, 0x42])?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct Retry<T, D, P = fn(ErrorKind) -> bool> {
+    i2c: T,
+    delay: D,
+    max_attempts: u32,
+    backoff: Backoff,
+    retryable: P,
+}
+
+impl<T, D> Retry<T, D> {
+    /// Create a new [`Retry`], running each transaction up to `max_attempts` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_attempts` is zero.
+    #[inline]
+    pub fn new(i2c: T, delay: D, max_attempts: u32, backoff: Backoff) -> Self {
+        assert!(max_attempts > 0, "at least one attempt is required");
+        Self {
+            i2c,
+            delay,
+            max_attempts,
+            backoff,
+            retryable: is_transient,
+        }
+    }
+}
+
+impl<T, D, P> Retry<T, D, P> {
+    /// Retry the transactions whose error kind matches `retryable`, instead of the default ones.
+    #[inline]
+    pub fn retry_if<Q>(self, retryable: Q) -> Retry<T, D, Q>
+    where
+        Q: FnMut(ErrorKind) -> bool,
+    {
+        Retry {
+            i2c: self.i2c,
+            delay: self.delay,
+            max_attempts: self.max_attempts,
+            backoff: self.backoff,
+            retryable,
+        }
+    }
+
+    /// Release the inner bus and delay.
+    #[inline]
+    pub fn release(self) -> (T, D) {
+        (self.i2c, self.delay)
+    }
+}
+
+impl<T: ErrorType, D, P> ErrorType for Retry<T, D, P> {
+    type Error = T::Error;
+}
+
+impl<T, D, P, A> I2c<A> for Retry<T, D, P>
+where
+    T: I2c<A>,
+    D: DelayNs,
+    P: FnMut(ErrorKind) -> bool,
+    A: AddressMode,
+{
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut retry = 0;
+        loop {
+            match self.i2c.transaction(address, operations) {
+                Ok(()) => return Ok(()),
+                Err(e) if retry + 1 < self.max_attempts && (self.retryable)(e.kind()) => {
+                    self.delay.delay_ns(self.backoff.delay_ns(retry));
+                    retry += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, D, P, A> AsyncI2c<A> for Retry<T, D, P>
+where
+    T: AsyncI2c<A>,
+    D: AsyncDelayNs,
+    P: FnMut(ErrorKind) -> bool,
+    A: AddressMode,
+{
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut retry = 0;
+        loop {
+            match self.i2c.transaction(address, operations).await {
+                Ok(()) => return Ok(()),
+                Err(e) if retry + 1 < self.max_attempts && (self.retryable)(e.kind()) => {
+                    self.delay.delay_ns(self.backoff.delay_ns(retry)).await;
+                    retry += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{vec, vec::Vec};
+
+    /// Bus failing with the scripted errors, then succeeding.
+    struct ScriptedBus {
+        errors: Vec<ErrorKind>,
+        attempts: usize,
+    }
+
+    impl ScriptedBus {
+        fn new(errors: &[ErrorKind]) -> Self {
+            Self {
+                errors: errors.iter().rev().copied().collect(),
+                attempts: 0,
+            }
+        }
+    }
+
+    impl ErrorType for ScriptedBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for ScriptedBus {
+        fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+            self.attempts += 1;
+            self.errors.pop().map_or(Ok(()), Err)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncI2c for ScriptedBus {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            ops: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            I2c::transaction(self, address, ops)
+        }
+    }
+
+    /// Delay recording the requested delays.
+    #[derive(Default)]
+    struct RecordingDelay(Vec<u32>);
+
+    impl DelayNs for RecordingDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.push(ns);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncDelayNs for RecordingDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0.push(ns);
+        }
+    }
+
+    const NACK: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+    #[test]
+    fn retries_transient_errors() {
+        let bus = ScriptedBus::new(&[NACK, ErrorKind::ArbitrationLoss, NACK]);
+        let mut i2c = Retry::new(bus, RecordingDelay::default(), 4, Backoff::Fixed(50));
+        I2c::write(&mut i2c, 0x50, &[0]).unwrap();
+        let (bus, delay) = i2c.release();
+        assert_eq!(bus.attempts, 4);
+        assert_eq!(delay.0, [50, 50, 50]);
+    }
+
+    #[test]
+    fn returns_last_error_when_exhausted() {
+        let bus = ScriptedBus::new(&[NACK, NACK, ErrorKind::ArbitrationLoss]);
+        let backoff = Backoff::Exponential {
+            initial_ns: 100,
+            max_ns: 300,
+        };
+        let mut i2c = Retry::new(bus, RecordingDelay::default(), 3, backoff);
+        assert_eq!(
+            I2c::write(&mut i2c, 0x50, &[0]),
+            Err(ErrorKind::ArbitrationLoss)
+        );
+        let (bus, delay) = i2c.release();
+        assert_eq!(bus.attempts, 3);
+        assert_eq!(delay.0, [100, 200]);
+    }
+
+    #[test]
+    fn other_errors_are_returned_immediately() {
+        let data_nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+        let bus = ScriptedBus::new(&[data_nack]);
+        let mut i2c = Retry::new(bus, RecordingDelay::default(), 5, Backoff::Fixed(50));
+        assert_eq!(I2c::write(&mut i2c, 0x50, &[0]), Err(data_nack));
+        assert_eq!(i2c.release().0.attempts, 1);
+    }
+
+    #[test]
+    fn custom_predicate() {
+        let bus = ScriptedBus::new(&[ErrorKind::Bus, NACK]);
+        let mut i2c = Retry::new(bus, RecordingDelay::default(), 5, Backoff::Fixed(50))
+            .retry_if(|kind| kind == ErrorKind::Bus);
+        assert_eq!(I2c::write(&mut i2c, 0x50, &[0]), Err(NACK));
+        assert_eq!(i2c.release().0.attempts, 2);
+    }
+
+    #[test]
+    fn exponential_backoff_saturates() {
+        let backoff = Backoff::Exponential {
+            initial_ns: 3,
+            max_ns: u32::MAX,
+        };
+        let delays: Vec<_> = [0, 1, 2, 31, 32]
+            .map(|retry| backoff.delay_ns(retry))
+            .into();
+        assert_eq!(delays, vec![3, 6, 12, u32::MAX, u32::MAX]);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_retries() {
+        use crate::util::poll_to_completion;
+
+        let bus = ScriptedBus::new(&[NACK, NACK]);
+        let mut i2c = Retry::new(bus, RecordingDelay::default(), 3, Backoff::Fixed(10));
+        poll_to_completion(AsyncI2c::write(&mut i2c, 0x50, &[0])).unwrap();
+        let (bus, delay) = i2c.release();
+        assert_eq!(bus.attempts, 3);
+        assert_eq!(delay.0, [10, 10]);
+    }
+}
@@ -0,0 +1,312 @@
+use core::ops::RangeInclusive;
+
+use embedded_hal::i2c::{Error, ErrorKind, I2c, NoAcknowledgeSource};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Transfer used by [`I2cScanner`] to probe an address.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ScanProbe {
+    /// Zero-length write. This does not change the state of most devices.
+    #[default]
+    EmptyWrite,
+    /// Single byte read, for buses or devices which do not support zero-length writes.
+    ///
+    /// Note that reading can have side effects on some devices, such as clearing status flags.
+    ReadByte,
+}
+
+/// I2C bus scanner.
+///
+/// Probes the 7-bit addresses of a bus with a zero-length write, and reports the ones
+/// which acknowledge. The reserved addresses (`0x00..=0x07` and `0x78..=0x7F`) are skipped,
+/// unless [`include_reserved`](I2cScanner::include_reserved) is used.
+///
+/// Some I2C peripherals or devices do not support zero-length writes: check the documentation of
+/// your HAL, and use [`ScanProbe::ReadByte`] instead if needed.
+///
+/// With the `async` feature, buses implementing the async [`I2c`](embedded_hal_async::i2c::I2c)
+/// trait can be scanned with [`scan_async`](I2cScanner::scan_async).
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c::I2cScanner;
+/// # use embedded_hal::i2c::{self, ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+/// # struct I2c0;
+/// # impl ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         match address {
+/// #             0x3C | 0x68 => Ok(()),
+/// #             _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+/// #         }
+/// #     }
+/// # }
+/// # let mut i2c = I2c0;
+///
+/// let mut scanner = I2cScanner::new(&mut i2c);
+/// let mut found = [0; 2];
+/// for (slot, address) in found.iter_mut().zip(scanner.scan()) {
+///     *slot = address?;
+/// }
+/// assert_eq!(found, [0x3C, 0x68]);
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct I2cScanner<'a, T> {
+    i2c: &'a mut T,
+    probe: ScanProbe,
+    include_reserved: bool,
+}
+
+impl<'a, T> I2cScanner<'a, T> {
+    /// Create a new [`I2cScanner`].
+    #[inline]
+    pub fn new(i2c: &'a mut T) -> Self {
+        Self {
+            i2c,
+            probe: ScanProbe::EmptyWrite,
+            include_reserved: false,
+        }
+    }
+
+    /// Probe addresses with `probe` instead of a zero-length write.
+    #[inline]
+    pub fn with_probe(mut self, probe: ScanProbe) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Also probe the reserved addresses `0x00..=0x07` and `0x78..=0x7F`.
+    ///
+    /// Probing them can have side effects, for example `0x00` is the general call address.
+    #[inline]
+    pub fn include_reserved(mut self) -> Self {
+        self.include_reserved = true;
+        self
+    }
+
+    fn addresses(&self) -> RangeInclusive<u8> {
+        if self.include_reserved {
+            0x00..=0x7F
+        } else {
+            0x08..=0x77
+        }
+    }
+}
+
+impl<T: I2c> I2cScanner<'_, T> {
+    /// Scan the bus, lazily yielding the addresses which acknowledged.
+    ///
+    /// Addresses which do not acknowledge are skipped. Any other error is yielded, and
+    /// the scan continues with the next address if the iterator is polled again.
+    pub fn scan(&mut self) -> impl Iterator<Item = Result<u8, T::Error>> + '_ {
+        let (probe, addresses) = (self.probe, self.addresses());
+        let i2c = &mut *self.i2c;
+        addresses.filter_map(move |address| {
+            let result = match probe {
+                ScanProbe::EmptyWrite => i2c.write(address, &[]),
+                ScanProbe::ReadByte => i2c.read(address, &mut [0]),
+            };
+            acknowledged(address, result)
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: AsyncI2c> I2cScanner<'_, T> {
+    /// Scan the bus, calling `found` with each address which acknowledged.
+    ///
+    /// Addresses which do not acknowledge are skipped. The scan stops at the first other error,
+    /// which is returned.
+    pub async fn scan_async(&mut self, mut found: impl FnMut(u8)) -> Result<(), T::Error> {
+        for address in self.addresses() {
+            let result = match self.probe {
+                ScanProbe::EmptyWrite => self.i2c.write(address, &[]).await,
+                ScanProbe::ReadByte => self.i2c.read(address, &mut [0]).await,
+            };
+            if let Some(address) = acknowledged(address, result) {
+                found(address?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Map the result of probing `address` to the scan result, if any.
+fn acknowledged<E: Error>(address: u8, result: Result<(), E>) -> Option<Result<u8, E>> {
+    match result {
+        Ok(()) => Some(Ok(address)),
+        Err(e) => match e.kind() {
+            ErrorKind::NoAcknowledge(
+                NoAcknowledgeSource::Address | NoAcknowledgeSource::Unknown,
+            ) => None,
+            _ => Some(Err(e)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::i2c::{ErrorType, Operation};
+    use std::vec::Vec;
+
+    struct FakeBus {
+        present: &'static [u8],
+        broken: Option<u8>,
+        read_probe: bool,
+        probed: Vec<u8>,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            match operations {
+                [Operation::Write([])] => assert!(!self.read_probe),
+                [Operation::Read([_])] => assert!(self.read_probe),
+                _ => panic!("unexpected probe"),
+            }
+            self.probed.push(address);
+            if self.broken == Some(address) {
+                Err(ErrorKind::Bus)
+            } else if self.present.contains(&address) {
+                Ok(())
+            } else {
+                Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+            }
+        }
+    }
+
+    fn bus(present: &'static [u8]) -> FakeBus {
+        FakeBus {
+            present,
+            broken: None,
+            read_probe: false,
+            probed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn finds_present_devices() {
+        let mut i2c = bus(&[0x08, 0x3C, 0x77]);
+        let found: Result<Vec<u8>, _> = I2cScanner::new(&mut i2c).scan().collect();
+        assert_eq!(found, Ok([0x08, 0x3C, 0x77].into()));
+    }
+
+    #[test]
+    fn skips_reserved_addresses() {
+        let mut i2c = bus(&[0x00, 0x07, 0x78, 0x7F]);
+        assert_eq!(I2cScanner::new(&mut i2c).scan().count(), 0);
+        assert_eq!(i2c.probed, (0x08..0x78).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn is_lazy() {
+        let mut i2c = bus(&[0x10, 0x20]);
+        assert_eq!(I2cScanner::new(&mut i2c).scan().next(), Some(Ok(0x10)));
+        assert_eq!(i2c.probed.last(), Some(&0x10));
+    }
+
+    #[test]
+    fn yields_unexpected_errors() {
+        let mut i2c = FakeBus {
+            broken: Some(0x30),
+            ..bus(&[0x40])
+        };
+        let found: Vec<_> = I2cScanner::new(&mut i2c).scan().collect();
+        assert_eq!(found, [Err(ErrorKind::Bus), Ok(0x40)]);
+    }
+
+    #[test]
+    fn read_probe() {
+        let mut i2c = FakeBus {
+            read_probe: true,
+            ..bus(&[0x50, 0x51])
+        };
+        let found: Result<Vec<u8>, _> = I2cScanner::new(&mut i2c)
+            .with_probe(ScanProbe::ReadByte)
+            .scan()
+            .collect();
+        assert_eq!(found, Ok([0x50, 0x51].into()));
+    }
+
+    #[test]
+    fn includes_reserved_addresses() {
+        let mut i2c = bus(&[0x00, 0x7F]);
+        let found: Result<Vec<u8>, _> = I2cScanner::new(&mut i2c)
+            .include_reserved()
+            .scan()
+            .collect();
+        assert_eq!(found, Ok([0x00, 0x7F].into()));
+        assert_eq!(i2c.probed.len(), 128);
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncI2c for FakeBus {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            I2c::transaction(self, address, operations)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn scan_async() {
+        use crate::util::poll_to_completion;
+
+        for read_probe in [false, true] {
+            let mut i2c = FakeBus {
+                read_probe,
+                ..bus(&[0x20, 0x68])
+            };
+            let probe = if read_probe {
+                ScanProbe::ReadByte
+            } else {
+                ScanProbe::EmptyWrite
+            };
+            let mut found = Vec::new();
+            poll_to_completion(
+                I2cScanner::new(&mut i2c)
+                    .with_probe(probe)
+                    .scan_async(|address| found.push(address)),
+            )
+            .unwrap();
+            assert_eq!(found, [0x20, 0x68]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn scan_async_stops_at_first_error() {
+        use crate::util::poll_to_completion;
+
+        let mut i2c = FakeBus {
+            broken: Some(0x30),
+            ..bus(&[0x10, 0x40])
+        };
+        let mut found = Vec::new();
+        let result =
+            poll_to_completion(I2cScanner::new(&mut i2c).scan_async(|address| found.push(address)));
+        assert_eq!(result, Err(ErrorKind::Bus));
+        assert_eq!(found, [0x10]);
+        assert_eq!(i2c.probed.last(), Some(&0x30));
+    }
+}
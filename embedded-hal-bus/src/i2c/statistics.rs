@@ -0,0 +1,262 @@
+use embedded_hal::i2c::{AddressMode, ErrorType, I2c, Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::stats::{i2c_lengths, Counters};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Snapshot of the counters of an [`I2cStatistics`].
+///
+/// All the counters wrap around on overflow.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct I2cStats {
+    /// Number of transactions, including the failed ones.
+    pub transactions: u32,
+    /// Number of bytes written by successful transactions.
+    pub bytes_written: u32,
+    /// Number of bytes read by successful transactions.
+    pub bytes_read: u32,
+    /// Number of transactions failed with [`NoAcknowledgeSource::Address`](embedded_hal::i2c::NoAcknowledgeSource::Address).
+    pub address_nacks: u32,
+    /// Number of transactions failed with [`NoAcknowledgeSource::Data`](embedded_hal::i2c::NoAcknowledgeSource::Data).
+    pub data_nacks: u32,
+    /// Number of transactions failed with [`ErrorKind::ArbitrationLoss`](embedded_hal::i2c::ErrorKind::ArbitrationLoss).
+    pub arbitration_losses: u32,
+    /// Number of transactions failed with [`ErrorKind::Timeout`](embedded_hal::i2c::ErrorKind::Timeout).
+    pub timeouts: u32,
+}
+
+/// [`I2c`] wrapper accumulating bus diagnostics.
+///
+/// All the operations are forwarded to the inner bus, and accounted in the wrapper's own [`Counters`].
+/// Along with the traffic, the failed transactions are counted by
+/// [error kind](embedded_hal::i2c::ErrorKind), to detect a degrading bus in the field: a rising
+/// number of address NACKs points at a missing or unresponsive target, arbitration losses and
+/// timeouts at electrical problems. Errors of other kinds, including NACKs of
+/// [unknown](embedded_hal::i2c::NoAcknowledgeSource::Unknown) source, are only counted as
+/// transactions.
+///
+/// [`snapshot`](I2cStatistics::snapshot) and [`reset_stats`](I2cStatistics::reset_stats) only need
+/// a shared reference, so they can be called from an interrupt handler while the driver owns the
+/// wrapper, e.g. through a `static`. Use [`stats::Counted`](crate::stats::Counted) with shared
+/// [`Counters`] to account the traffic of several devices together.
+///
+/// With the `async` feature, `I2cStatistics` also implements the async [`I2c`](embedded_hal_async::i2c::I2c)
+/// trait if the inner bus does.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::I2cStatistics;
+/// # use embedded_hal::i2c::{self, ErrorKind, NoAcknowledgeSource, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         match address {
+/// #             0x48 => Ok(()),
+/// #             _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+/// #         }
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// let mut i2c = I2cStatistics::new(i2c);
+/// i2c.write_read(0x48, &[0x00], &mut [0; 2])?;
+/// i2c.write(0x50, &[0x00]).unwrap_err();
+///
+/// let stats = i2c.snapshot();
+/// assert_eq!((stats.transactions, stats.bytes_read, stats.address_nacks), (2, 2, 1));
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct I2cStatistics<T> {
+    inner: T,
+    counters: Counters,
+}
+
+impl<T> I2cStatistics<T> {
+    /// Create a new [`I2cStatistics`] wrapper around `inner`, with all the counters at zero.
+    #[inline]
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            counters: Counters::new(),
+        }
+    }
+
+    /// Take a snapshot of the counters.
+    ///
+    /// The counters are read one after the other: the snapshot may be inconsistent if a transaction
+    /// completes concurrently.
+    pub fn snapshot(&self) -> I2cStats {
+        let stats = self.counters.stats();
+        I2cStats {
+            transactions: stats.transactions,
+            bytes_written: stats.bytes_written,
+            bytes_read: stats.bytes_read,
+            address_nacks: stats.address_nacks,
+            data_nacks: stats.data_nacks,
+            arbitration_losses: stats.arbitration_losses,
+            timeouts: stats.timeouts,
+        }
+    }
+
+    /// Reset all the counters to zero.
+    #[inline]
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: ErrorType> ErrorType for I2cStatistics<T> {
+    type Error = T::Error;
+}
+
+impl<T: I2c<A>, A: AddressMode> I2c<A> for I2cStatistics<T> {
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read);
+        self.counters.record_i2c(&result, 0, read.len());
+        result
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write);
+        self.counters.record_i2c(&result, write.len(), 0);
+        result
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read);
+        self.counters.record_i2c(&result, write.len(), read.len());
+        result
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations);
+        let (written, read) = i2c_lengths(operations);
+        self.counters.record_i2c(&result, written, read);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: AsyncI2c<A>, A: AddressMode> AsyncI2c<A> for I2cStatistics<T> {
+    #[inline]
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read).await;
+        self.counters.record_i2c(&result, 0, read.len());
+        result
+    }
+
+    #[inline]
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write).await;
+        self.counters.record_i2c(&result, write.len(), 0);
+        result
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read).await;
+        self.counters.record_i2c(&result, write.len(), read.len());
+        result
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations).await;
+        let (written, read) = i2c_lengths(operations);
+        self.counters.record_i2c(&result, written, read);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+    /// Bus failing the transactions to 0x10..=0x14 with a different error each.
+    struct FakeBus;
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            match address {
+                0x10 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+                0x11 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+                0x12 => Err(ErrorKind::ArbitrationLoss),
+                0x13 => Err(ErrorKind::Timeout),
+                0x14 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn counts_traffic_and_errors_by_kind() {
+        let mut i2c = I2cStatistics::new(FakeBus);
+        i2c.transaction(
+            0x20,
+            &mut [
+                Operation::Write(&[1, 2]),
+                Operation::Read(&mut [0; 3]),
+                Operation::Write(&[3]),
+            ],
+        )
+        .unwrap();
+        for address in [0x10, 0x10, 0x11, 0x12, 0x13, 0x14] {
+            i2c.write_read(address, &[1], &mut [0; 4]).unwrap_err();
+        }
+        assert_eq!(
+            i2c.snapshot(),
+            I2cStats {
+                transactions: 7,
+                bytes_written: 3,
+                bytes_read: 3,
+                address_nacks: 2,
+                data_nacks: 1,
+                arbitration_losses: 1,
+                timeouts: 1,
+            }
+        );
+
+        i2c.reset_stats();
+        assert_eq!(i2c.snapshot(), I2cStats::default());
+    }
+}
@@ -0,0 +1,264 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::delay::Counter;
+use embedded_hal::i2c::{AddressMode, Error, ErrorKind, ErrorType, I2c, Operation};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// [`I2c`] wrapper detecting targets stretching the clock for too long.
+///
+/// Clock stretching is legal, but some broken targets hold SCL low indefinitely, for example after a
+/// failed internal operation. The wrapper cannot monitor SCL, so instead it times each call, from its
+/// start to its completion, with a [`Counter`]. Each byte of the transaction, including the address byte
+/// of each operation, is allowed `byte_timeout_ns`.
+///
+/// The inner call cannot be interrupted, and it completed by the time its deadline is known to be missed:
+/// its writes were applied, and its read buffers hold the data received, so its result is returned as is.
+/// Instead, the *next* operation returns [`ClockStretchError::Timeout`], without accessing the bus. This
+/// does not unstick a bus by itself, but lets the driver notice the target is misbehaving, and for example
+/// reset it or recover the bus before retrying. The operations after that one run normally again.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::ClockStretchTimeout;
+/// # use embedded_hal::delay::Counter;
+/// # use embedded_hal::i2c::{self, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         Ok(())
+/// #     }
+/// # }
+/// # struct Timer;
+/// # impl Counter for Timer {
+/// #     fn start(&mut self) {}
+/// #     fn elapsed_ns(&self) -> u64 { 0 }
+/// # }
+/// # let (i2c, timer) = (I2c0, Timer);
+///
+/// // At 100 kHz, a byte and its acknowledge take 90 µs: allow 1 ms per byte.
+/// let mut i2c = ClockStretchTimeout::new(i2c, timer, 1_000_000);
+/// i2c.write(0x48, &[0x01, 0x60])?;
+/// // Fails if the previous write was too slow.
+/// i2c.write(0x48, &[0x00])?;
+/// # Ok::<(), embedded_hal_bus::i2c::ClockStretchError<ErrorKind>>(())
+/// ```
+pub struct ClockStretchTimeout<T, C> {
+    i2c: T,
+    counter: C,
+    byte_timeout_ns: u64,
+    /// Whether the previous call missed its deadline.
+    missed: bool,
+}
+
+impl<T, C> ClockStretchTimeout<T, C>
+where
+    C: Counter,
+{
+    /// Create a new [`ClockStretchTimeout`], allowing `byte_timeout_ns` per transferred byte.
+    #[inline]
+    pub fn new(i2c: T, counter: C, byte_timeout_ns: u64) -> Self {
+        Self {
+            i2c,
+            counter,
+            byte_timeout_ns,
+            missed: false,
+        }
+    }
+
+    /// Release the inner bus and counter.
+    #[inline]
+    pub fn release(self) -> (T, C) {
+        (self.i2c, self.counter)
+    }
+
+    fn timed<E>(
+        &mut self,
+        bytes: usize,
+        operations: usize,
+        f: impl FnOnce(&mut T) -> Result<(), E>,
+    ) -> Result<(), ClockStretchError<E>> {
+        if core::mem::take(&mut self.missed) {
+            return Err(ClockStretchError::Timeout);
+        }
+        let deadline_ns = self
+            .byte_timeout_ns
+            .saturating_mul((bytes + operations) as u64);
+        self.counter.start();
+        let result = f(&mut self.i2c);
+        self.missed = self.counter.has_exceeded(deadline_ns);
+        result.map_err(ClockStretchError::I2c)
+    }
+}
+
+/// Error type for [`ClockStretchTimeout`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ClockStretchError<E> {
+    /// The previous transaction took longer than allowed, likely because the target stretched the clock.
+    ///
+    /// The operation returning this error was not started.
+    Timeout,
+    /// An inner I2C bus operation failed.
+    I2c(E),
+}
+
+impl<E: Display> Display for ClockStretchError<E> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(
+                f,
+                "previous I2C transaction timed out, the clock was stretched too long"
+            ),
+            Self::I2c(e) => write!(f, "I2C bus error: {e}"),
+        }
+    }
+}
+
+impl<E: Debug + Display> core::error::Error for ClockStretchError<E> {}
+
+impl<E: Error> Error for ClockStretchError<E> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Timeout => ErrorKind::Timeout,
+            Self::I2c(e) => e.kind(),
+        }
+    }
+}
+
+impl<T: ErrorType, C> ErrorType for ClockStretchTimeout<T, C> {
+    type Error = ClockStretchError<T::Error>;
+}
+
+impl<T, C, A> I2c<A> for ClockStretchTimeout<T, C>
+where
+    T: I2c<A>,
+    C: Counter,
+    A: AddressMode,
+{
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.timed(read.len(), 1, |i2c| i2c.read(address, read))
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.timed(write.len(), 1, |i2c| i2c.write(address, write))
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.timed(write.len() + read.len(), 2, |i2c| {
+            i2c.write_read(address, write, read)
+        })
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let bytes = operations
+            .iter()
+            .map(|op| match op {
+                Operation::Read(buf) => buf.len(),
+                Operation::Write(buf) => buf.len(),
+            })
+            .sum();
+        self.timed(bytes, operations.len(), |i2c| {
+            i2c.transaction(address, operations)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::sim::{FakeCounter, SimClock};
+
+    /// Bus taking `byte_ns` per byte to the target at 0x20, and failing for other targets. Counts the
+    /// transactions in `calls`.
+    struct FakeBus {
+        clock: SimClock,
+        byte_ns: u64,
+        calls: usize,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.calls += 1;
+            if address != 0x20 {
+                return Err(ErrorKind::Other);
+            }
+            for op in operations {
+                let len = match op {
+                    Operation::Read(buf) => buf.len(),
+                    Operation::Write(buf) => buf.len(),
+                };
+                self.clock.advance_ns(self.byte_ns * (len as u64 + 1));
+            }
+            Ok(())
+        }
+    }
+
+    fn wrapper(byte_ns: u64) -> ClockStretchTimeout<FakeBus, FakeCounter> {
+        let clock = SimClock::new();
+        let bus = FakeBus {
+            clock: clock.clone(),
+            byte_ns,
+            calls: 0,
+        };
+        ClockStretchTimeout::new(bus, FakeCounter::new(&clock), 100)
+    }
+
+    #[test]
+    fn fast_transactions_pass() {
+        let mut i2c = wrapper(99);
+        let mut buf = [0; 4];
+        i2c.write(0x20, &[1, 2]).unwrap();
+        i2c.write_read(0x20, &[1], &mut buf).unwrap();
+        i2c.transaction(0x20, &mut [Operation::Read(&mut buf)])
+            .unwrap();
+    }
+
+    #[test]
+    fn slow_transaction_fails_the_next_operation() {
+        let mut i2c = wrapper(101);
+        // The slow read completes, its data is valid.
+        i2c.read(0x20, &mut [0; 8]).unwrap();
+        assert_eq!(i2c.write(0x20, &[1]), Err(ClockStretchError::Timeout));
+        assert_eq!(i2c.i2c.calls, 1);
+        assert_eq!(
+            ClockStretchError::<ErrorKind>::Timeout.kind(),
+            ErrorKind::Timeout
+        );
+
+        // The timeout is reported once.
+        i2c.i2c.byte_ns = 0;
+        i2c.write(0x20, &[1]).unwrap();
+        i2c.write(0x20, &[1]).unwrap();
+        assert_eq!(i2c.i2c.calls, 3);
+    }
+
+    #[test]
+    fn bus_errors_are_forwarded() {
+        let mut i2c = wrapper(0);
+        assert_eq!(
+            i2c.write(0x21, &[1]),
+            Err(ClockStretchError::I2c(ErrorKind::Other))
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use embedded_hal::i2c::{
+    Direction, Error, ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Bus event reported by [`I2cTrace`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum I2cTraceEvent {
+    /// Address frame, sent after a START or a repeated START condition.
+    Start {
+        /// Target address.
+        address: u8,
+        /// Direction of the transfer.
+        direction: Direction,
+    },
+    /// Byte written to the target.
+    ByteWritten(u8),
+    /// Byte read from the target.
+    ByteRead(u8),
+    /// The target acknowledged its address.
+    Ack,
+    /// The target did not acknowledge.
+    Nack(NoAcknowledgeSource),
+    /// Repeated START condition, between two transfers of opposite directions.
+    Restart,
+    /// STOP condition, ending the transaction.
+    Stop,
+    /// The transaction failed with an error other than a NACK.
+    Error,
+}
+
+/// [`I2c`] wrapper reporting every bus event to a callback.
+///
+/// The events of a transaction are reported once it is performed, so that bytes read can be
+/// included. A successful transaction is reported as the sequence of conditions and bytes seen
+/// on the bus: the address frame of each group of adjacent operations of the same direction,
+/// its acknowledge, the bytes transferred, and the final STOP. The inner bus only reports the
+/// result of the whole transaction, so a failed transaction is reported as its first address
+/// frame, followed by [`Nack`](I2cTraceEvent::Nack) or [`Error`](I2cTraceEvent::Error), and
+/// [`Stop`](I2cTraceEvent::Stop).
+///
+/// The callback can forward the events to `log`, `defmt`, a ring buffer, etc.
+///
+/// With the `async` feature, `I2cTrace` also implements the async [`I2c`](embedded_hal_async::i2c::I2c)
+/// trait if the inner bus does.
+///
+/// # Example
+///
+/// ```
+/// use core::cell::RefCell;
+/// use embedded_hal::i2c::{Direction, I2c};
+/// use embedded_hal_bus::i2c::{I2cTrace, I2cTraceEvent};
+/// # use embedded_hal::i2c::{self, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, _: u8, ops: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         if let [_, Operation::Read(buf)] = ops {
+/// #             buf.fill(0x2A);
+/// #         }
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// let events = RefCell::new(Vec::new());
+/// let mut i2c = I2cTrace::new(i2c, |event| events.borrow_mut().push(event));
+/// i2c.write_read(0x48, &[0x00], &mut [0])?;
+/// assert_eq!(
+///     *events.borrow(),
+///     [
+///         I2cTraceEvent::Start { address: 0x48, direction: Direction::Write },
+///         I2cTraceEvent::Ack,
+///         I2cTraceEvent::ByteWritten(0x00),
+///         I2cTraceEvent::Restart,
+///         I2cTraceEvent::Start { address: 0x48, direction: Direction::Read },
+///         I2cTraceEvent::Ack,
+///         I2cTraceEvent::ByteRead(0x2A),
+///         I2cTraceEvent::Stop,
+///     ]
+/// );
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct I2cTrace<T, F> {
+    inner: T,
+    trace: F,
+}
+
+impl<T, F> I2cTrace<T, F>
+where
+    F: Fn(I2cTraceEvent),
+{
+    /// Create a new [`I2cTrace`] wrapper around `inner`, reporting events to `trace`.
+    #[inline]
+    pub fn new(inner: T, trace: F) -> Self {
+        Self { inner, trace }
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> T {
+        self.inner
+    }
+
+    fn report<E: Error>(&self, address: u8, operations: &[Operation<'_>], result: &Result<(), E>) {
+        let direction = |operation: &Operation<'_>| match operation {
+            Operation::Read(_) => Direction::Read,
+            Operation::Write(_) => Direction::Write,
+        };
+
+        if let Err(e) = result {
+            if let Some(first) = operations.first() {
+                (self.trace)(I2cTraceEvent::Start {
+                    address,
+                    direction: direction(first),
+                });
+            }
+            (self.trace)(match e.kind() {
+                ErrorKind::NoAcknowledge(source) => I2cTraceEvent::Nack(source),
+                _ => I2cTraceEvent::Error,
+            });
+            (self.trace)(I2cTraceEvent::Stop);
+            return;
+        }
+
+        let mut previous = None;
+        for operation in operations {
+            let direction = direction(operation);
+            // Adjacent operations of the same direction are merged in a single transfer.
+            if previous != Some(direction) {
+                if previous.is_some() {
+                    (self.trace)(I2cTraceEvent::Restart);
+                }
+                (self.trace)(I2cTraceEvent::Start { address, direction });
+                (self.trace)(I2cTraceEvent::Ack);
+                previous = Some(direction);
+            }
+            match operation {
+                Operation::Read(data) => data
+                    .iter()
+                    .for_each(|&byte| (self.trace)(I2cTraceEvent::ByteRead(byte))),
+                Operation::Write(data) => data
+                    .iter()
+                    .for_each(|&byte| (self.trace)(I2cTraceEvent::ByteWritten(byte))),
+            }
+        }
+        if previous.is_some() {
+            (self.trace)(I2cTraceEvent::Stop);
+        }
+    }
+}
+
+impl<T: ErrorType, F> ErrorType for I2cTrace<T, F> {
+    type Error = T::Error;
+}
+
+impl<T, F> I2c for I2cTrace<T, F>
+where
+    T: I2c,
+    F: Fn(I2cTraceEvent),
+{
+    #[inline]
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read);
+        self.report(address, &[Operation::Read(read)], &result);
+        result
+    }
+
+    #[inline]
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write);
+        self.report(address, &[Operation::Write(write)], &result);
+        result
+    }
+
+    #[inline]
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read);
+        let operations = [Operation::Write(write), Operation::Read(read)];
+        self.report(address, &operations, &result);
+        result
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations);
+        self.report(address, operations, &result);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, F> AsyncI2c for I2cTrace<T, F>
+where
+    T: AsyncI2c,
+    F: Fn(I2cTraceEvent),
+{
+    #[inline]
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read).await;
+        self.report(address, &[Operation::Read(read)], &result);
+        result
+    }
+
+    #[inline]
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write).await;
+        self.report(address, &[Operation::Write(write)], &result);
+        result
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read).await;
+        let operations = [Operation::Write(write), Operation::Read(read)];
+        self.report(address, &operations, &result);
+        result
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations).await;
+        self.report(address, operations, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use std::vec::Vec;
+
+    struct FakeBus;
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            match address {
+                0x10 => return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+                0x11 => return Err(ErrorKind::ArbitrationLoss),
+                _ => {}
+            }
+            for op in operations {
+                if let Operation::Read(buf) = op {
+                    buf.fill(0xAB);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn trace(f: impl FnOnce(&mut dyn I2c<Error = ErrorKind>)) -> Vec<I2cTraceEvent> {
+        let events = RefCell::new(Vec::new());
+        f(&mut I2cTrace::new(FakeBus, |event| {
+            events.borrow_mut().push(event)
+        }));
+        events.into_inner()
+    }
+
+    #[test]
+    fn merges_adjacent_operations_of_the_same_direction() {
+        let events = trace(|i2c| {
+            i2c.transaction(
+                0x48,
+                &mut [
+                    Operation::Write(&[1]),
+                    Operation::Write(&[2]),
+                    Operation::Read(&mut [0; 2]),
+                ],
+            )
+            .unwrap()
+        });
+        assert_eq!(
+            events,
+            [
+                I2cTraceEvent::Start {
+                    address: 0x48,
+                    direction: Direction::Write
+                },
+                I2cTraceEvent::Ack,
+                I2cTraceEvent::ByteWritten(1),
+                I2cTraceEvent::ByteWritten(2),
+                I2cTraceEvent::Restart,
+                I2cTraceEvent::Start {
+                    address: 0x48,
+                    direction: Direction::Read
+                },
+                I2cTraceEvent::Ack,
+                I2cTraceEvent::ByteRead(0xAB),
+                I2cTraceEvent::ByteRead(0xAB),
+                I2cTraceEvent::Stop,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_nacks_and_errors() {
+        let events = trace(|i2c| {
+            i2c.read(0x10, &mut [0]).unwrap_err();
+            i2c.write(0x11, &[1]).unwrap_err();
+        });
+        assert_eq!(
+            events,
+            [
+                I2cTraceEvent::Start {
+                    address: 0x10,
+                    direction: Direction::Read
+                },
+                I2cTraceEvent::Nack(NoAcknowledgeSource::Address),
+                I2cTraceEvent::Stop,
+                I2cTraceEvent::Start {
+                    address: 0x11,
+                    direction: Direction::Write
+                },
+                I2cTraceEvent::Error,
+                I2cTraceEvent::Stop,
+            ]
+        );
+    }
+}
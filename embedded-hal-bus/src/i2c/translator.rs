@@ -0,0 +1,137 @@
+use embedded_hal::i2c::{ErrorType, I2c, Operation};
+
+/// [`I2c`] proxy remapping target addresses.
+///
+/// Every operation is forwarded to the inner bus, after mapping its address with `map`. This lets
+/// a driver written for a fixed address talk to a device at another one, for example behind an
+/// address translator chip, or on a channel of a bus multiplexer (such as the PCA9548) which
+/// exposes the downstream buses at an offset.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2c;
+/// use embedded_hal_bus::i2c::I2cAddressTranslator;
+/// # use embedded_hal::i2c::{self, ErrorKind, Operation};
+/// # struct I2c0;
+/// # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+/// #         assert_eq!(address, 0x4C);
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// // The sensor at 0x48 answers at 0x4C on this board.
+/// let mut i2c = I2cAddressTranslator::new(i2c, |address| address + 4);
+/// i2c.write(0x48, &[0x01, 0x60])?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct I2cAddressTranslator<T, F> {
+    i2c: T,
+    map: F,
+}
+
+impl<T, F> I2cAddressTranslator<T, F>
+where
+    T: I2c,
+    F: FnMut(u8) -> u8,
+{
+    /// Create a new [`I2cAddressTranslator`], mapping addresses with `map`.
+    #[inline]
+    pub fn new(i2c: T, map: F) -> Self {
+        Self { i2c, map }
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> T {
+        self.i2c
+    }
+}
+
+impl<T: ErrorType, F> ErrorType for I2cAddressTranslator<T, F> {
+    type Error = T::Error;
+}
+
+impl<T, F> I2c for I2cAddressTranslator<T, F>
+where
+    T: I2c,
+    F: FnMut(u8) -> u8,
+{
+    #[inline]
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c.read((self.map)(address), read)
+    }
+
+    #[inline]
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.write((self.map)(address), write)
+    }
+
+    #[inline]
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.i2c.write_read((self.map)(address), write, read)
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.i2c.transaction((self.map)(address), operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct FakeBus {
+        addresses: Vec<u8>,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = Infallible;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.addresses.push(address);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn maps_every_operation() {
+        let table = [0x10, 0x11, 0x12];
+        let mut calls = 0;
+        let mut i2c = I2cAddressTranslator::new(FakeBus::default(), |address| {
+            calls += 1;
+            table[usize::from(address)]
+        });
+        let mut buf = [0];
+        i2c.read(0, &mut buf).unwrap();
+        i2c.write(1, &[1]).unwrap();
+        i2c.write_read(2, &[1], &mut buf).unwrap();
+        i2c.transaction(0, &mut [Operation::Write(&[1])]).unwrap();
+        assert_eq!(i2c.release().addresses, [0x10, 0x11, 0x12, 0x10]);
+        assert_eq!(calls, 4);
+    }
+}
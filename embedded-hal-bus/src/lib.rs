@@ -7,6 +7,29 @@
 #[cfg(feature = "defmt-03")]
 use defmt_03 as defmt;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod adc;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod blocking;
+pub mod delay;
+pub mod gpio;
 pub mod i2c;
+#[cfg(any(feature = "log", feature = "defmt-03"))]
+mod logging;
+pub mod onewire;
+pub mod pwm;
 pub mod spi;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "32"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "portable-atomic", target_has_atomic = "32")))
+)]
+pub mod stats;
+pub mod throttle;
+#[cfg(feature = "nb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "nb")))]
+pub mod uart;
 pub mod util;
@@ -0,0 +1,144 @@
+//! Log backend of the `Logged` wrappers.
+//!
+//! Records are emitted with the `log` crate when the `log` feature is enabled, and with `defmt`
+//! otherwise. All the arguments of a record are formatted with `{}`, so that the same format
+//! string works for both.
+
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(all(feature = "defmt-03", not(feature = "log")))]
+use crate::defmt;
+
+/// Default number of bytes dumped per operation.
+pub(crate) const DEFAULT_MAX_DUMP: usize = 16;
+
+/// Emit a record at the debug level.
+macro_rules! record {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::debug!($($arg)*);
+        #[cfg(all(feature = "defmt-03", not(feature = "log")))]
+        $crate::defmt::debug!($($arg)*);
+    }};
+}
+pub(crate) use record;
+
+/// Hex dump of at most `max` bytes, followed by `...` if the data is longer.
+pub(crate) struct HexDump<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) max: usize,
+}
+
+impl HexDump<'_> {
+    fn shown(&self) -> &[u8] {
+        &self.data[..self.data.len().min(self.max)]
+    }
+
+    fn truncated(&self) -> bool {
+        self.data.len() > self.max
+    }
+}
+
+impl Display for HexDump<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, byte) in self.shown().iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+        if self.truncated() {
+            let separator = if self.max > 0 { " " } else { "" };
+            write!(f, "{separator}...")?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(all(feature = "defmt-03", not(feature = "log")))]
+impl defmt::Format for HexDump<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=[u8]:02x}", self.shown());
+        if self.truncated() {
+            defmt::write!(f, " ...");
+        }
+    }
+}
+
+/// Hexadecimal bus address.
+pub(crate) struct Address(pub(crate) u16);
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#04x}", self.0)
+    }
+}
+
+#[cfg(all(feature = "defmt-03", not(feature = "log")))]
+impl defmt::Format for Address {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=u16:#04x}", self.0);
+    }
+}
+
+/// Result of an operation, showing the error kind on failure.
+pub(crate) struct Outcome<K>(pub(crate) Result<(), K>);
+
+impl<K: fmt::Debug> Display for Outcome<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Ok(()) => write!(f, "ok"),
+            Err(kind) => write!(f, "error {kind:?}"),
+        }
+    }
+}
+
+#[cfg(all(feature = "defmt-03", not(feature = "log")))]
+impl<K: defmt::Format> defmt::Format for Outcome<K> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match &self.0 {
+            Ok(()) => defmt::write!(f, "ok"),
+            Err(kind) => defmt::write!(f, "error {}", kind),
+        }
+    }
+}
+
+/// Logger capturing the records of the current thread, for tests.
+#[cfg(all(test, feature = "log"))]
+pub(crate) mod capture {
+    extern crate std;
+
+    use std::{cell::RefCell, string::String, vec::Vec};
+
+    std::thread_local! {
+        static RECORDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            let message = std::format!("{}", record.args());
+            RECORDS.with(|records| records.borrow_mut().push(message));
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Run `f`, returning the records it logged.
+    pub(crate) fn records(f: impl FnOnce()) -> Vec<String> {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&Capture).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+        RECORDS.with(|records| records.borrow_mut().clear());
+        f();
+        RECORDS.with(|records| records.take())
+    }
+}
@@ -0,0 +1,164 @@
+//! `OneWire` device implementations.
+
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::onewire::{Error, ErrorKind};
+
+mod owned;
+pub use owned::*;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Error type for [`OwnedDevice`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DeviceError<BUS> {
+    /// An inner 1-Wire bus operation failed.
+    Bus(BUS),
+    /// No device answered the reset pulse.
+    NoPresence,
+}
+
+impl<BUS> From<BUS> for DeviceError<BUS> {
+    #[inline]
+    fn from(error: BUS) -> Self {
+        Self::Bus(error)
+    }
+}
+
+impl<BUS: Display> Display for DeviceError<BUS> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Bus(bus) => write!(f, "1-Wire bus error: {bus}"),
+            Self::NoPresence => write!(f, "No device answered the reset pulse"),
+        }
+    }
+}
+
+impl<BUS: Debug + Display> core::error::Error for DeviceError<BUS> {}
+
+impl<BUS: Error> Error for DeviceError<BUS> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Bus(e) => e.kind(),
+            Self::NoPresence => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::onewire::{ErrorType, OneWire};
+    use std::{collections::VecDeque, vec::Vec};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Reset,
+        Write(u8),
+        Read,
+    }
+
+    /// Bus recording whole bytes, with the bits of a byte written or read in a row.
+    #[derive(Default)]
+    struct FakeBus {
+        present: bool,
+        log: Vec<Event>,
+        written: Vec<bool>,
+        to_read: VecDeque<bool>,
+    }
+
+    impl FakeBus {
+        fn with_data(data: &[u8]) -> Self {
+            Self {
+                present: true,
+                to_read: data
+                    .iter()
+                    .flat_map(|byte| (0..8).map(move |i| byte & (1 << i) != 0))
+                    .collect(),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = Infallible;
+    }
+
+    impl OneWire for FakeBus {
+        fn reset(&mut self) -> Result<bool, Infallible> {
+            self.log.push(Event::Reset);
+            Ok(self.present)
+        }
+
+        fn read_bit(&mut self) -> Result<bool, Infallible> {
+            if self.to_read.len() % 8 == 0 {
+                self.log.push(Event::Read);
+            }
+            Ok(self.to_read.pop_front().unwrap())
+        }
+
+        fn write_bit(&mut self, bit: bool) -> Result<(), Infallible> {
+            self.written.push(bit);
+            if self.written.len() == 8 {
+                let byte = self
+                    .written
+                    .drain(..)
+                    .enumerate()
+                    .fold(0, |byte, (i, bit)| byte | (u8::from(bit) << i));
+                self.log.push(Event::Write(byte));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn match_rom() {
+        let rom = 0x2800_0001_2345_6728;
+        let mut device = OwnedDevice::new(FakeBus::with_data(&[0xAA, 0x55]), rom);
+        let mut read = [0; 2];
+        device.write_read(&[0xBE], &mut read).unwrap();
+
+        assert_eq!(read, [0xAA, 0x55]);
+        assert_eq!(
+            device.release().log,
+            [
+                Event::Reset,
+                Event::Write(0x55),
+                Event::Write(0x28),
+                Event::Write(0x67),
+                Event::Write(0x45),
+                Event::Write(0x23),
+                Event::Write(0x01),
+                Event::Write(0x00),
+                Event::Write(0x00),
+                Event::Write(0x28),
+                Event::Write(0xBE),
+                Event::Read,
+                Event::Read,
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_rom() {
+        let mut device = OwnedDevice::new_single(FakeBus::with_data(&[]));
+        device.write(&[0x44]).unwrap();
+
+        assert_eq!(
+            device.release().log,
+            [Event::Reset, Event::Write(0xCC), Event::Write(0x44)]
+        );
+    }
+
+    #[test]
+    fn no_presence() {
+        let mut device = OwnedDevice::new_single(FakeBus::default());
+        assert_eq!(device.write(&[0x44]), Err(DeviceError::NoPresence));
+        assert_eq!(device.release().log, [Event::Reset]);
+    }
+}
@@ -0,0 +1,121 @@
+use embedded_hal::onewire::OneWire;
+
+use super::DeviceError;
+
+/// `MATCH ROM` command, selecting a single device by its ROM code.
+const MATCH_ROM: u8 = 0x55;
+/// `SKIP ROM` command, addressing all devices of the bus.
+const SKIP_ROM: u8 = 0xCC;
+
+/// A device on a 1-Wire bus, selected by its ROM code.
+///
+/// Each operation starts with a reset pulse, followed by a `MATCH ROM` command with the
+/// ROM code of the device, or a `SKIP ROM` command if the device was created with
+/// [`new_single`](OwnedDevice::new_single). Drivers can then only deal with the function
+/// commands of the device, similarly to an [`SpiDevice`](embedded_hal::spi::SpiDevice).
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::onewire::OwnedDevice;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::onewire::{ErrorType, OneWire};
+/// # struct Bus;
+/// # impl ErrorType for Bus { type Error = Infallible; }
+/// # impl OneWire for Bus {
+/// #     fn reset(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// #     fn read_bit(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// #     fn write_bit(&mut self, _: bool) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # let bus = Bus;
+///
+/// const READ_SCRATCHPAD: u8 = 0xBE;
+///
+/// let mut sensor = OwnedDevice::new(bus, 0x2800_0001_2345_6728);
+/// let mut scratchpad = [0; 9];
+/// sensor.write_read(&[READ_SCRATCHPAD], &mut scratchpad)?;
+/// # Ok::<(), embedded_hal_bus::onewire::DeviceError<Infallible>>(())
+/// ```
+pub struct OwnedDevice<BUS> {
+    bus: BUS,
+    rom: Option<u64>,
+}
+
+impl<BUS: OneWire> OwnedDevice<BUS> {
+    /// Create a new [`OwnedDevice`], selected with its 64-bit ROM code.
+    ///
+    /// The ROM code is sent least significant byte (the family code) first.
+    #[inline]
+    pub fn new(bus: BUS, rom: u64) -> Self {
+        Self {
+            bus,
+            rom: Some(rom),
+        }
+    }
+
+    /// Create a new [`OwnedDevice`] for the only device of the bus, addressed with `SKIP ROM`.
+    #[inline]
+    pub fn new_single(bus: BUS) -> Self {
+        Self { bus, rom: None }
+    }
+
+    /// Returns the ROM code of the device, if it is selected with `MATCH ROM`.
+    #[inline]
+    pub fn rom(&self) -> Option<u64> {
+        self.rom
+    }
+
+    /// Release the inner bus.
+    #[inline]
+    pub fn release(self) -> BUS {
+        self.bus
+    }
+
+    /// Reset the bus and select the device.
+    pub fn select(&mut self) -> Result<(), DeviceError<BUS::Error>> {
+        if !self.bus.reset()? {
+            return Err(DeviceError::NoPresence);
+        }
+        match self.rom {
+            Some(rom) => {
+                self.bus.write_byte(MATCH_ROM)?;
+                for byte in rom.to_le_bytes() {
+                    self.bus.write_byte(byte)?;
+                }
+            }
+            None => self.bus.write_byte(SKIP_ROM)?,
+        }
+        Ok(())
+    }
+
+    /// Select the device, then write `bytes`.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), DeviceError<BUS::Error>> {
+        self.select()?;
+        for byte in bytes {
+            self.bus.write_byte(*byte)?;
+        }
+        Ok(())
+    }
+
+    /// Select the device, then read into `buffer`.
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<(), DeviceError<BUS::Error>> {
+        self.select()?;
+        for byte in buffer {
+            *byte = self.bus.read_byte()?;
+        }
+        Ok(())
+    }
+
+    /// Select the device, write `bytes`, then read into `buffer`.
+    pub fn write_read(
+        &mut self,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), DeviceError<BUS::Error>> {
+        self.write(bytes)?;
+        for byte in buffer {
+            *byte = self.bus.read_byte()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use embedded_hal::pwm::{ComplementaryPwm, ErrorType, SetDutyCycle};
+
+/// Software [`ComplementaryPwm`] implementation over two independent [`SetDutyCycle`] channels.
+///
+/// This is a fallback for timers without complementary outputs. The high side channel is set to
+/// `duty`, and the low side channel to `max_duty_cycle - duty`, both shortened by the dead time.
+///
+/// The two channels must run on the same timer, and be configured so that their active phases do
+/// not overlap: typically center-aligned mode, with the polarity of the low side channel inverted.
+/// The shortened pulses then leave a gap of the dead time at each transition. Unlike hardware dead
+/// time, this relies on the configuration of the channels being right, and the two duty cycles are
+/// updated one after the other, so check that the glitch between both updates is acceptable.
+///
+/// The dead time is converted to duty cycle steps using the `period_ns` of the timer, given when
+/// creating the wrapper.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::ComplementaryPwm;
+/// use embedded_hal_bus::pwm::SoftComplementaryPwm;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+/// # struct Channel;
+/// # impl ErrorType for Channel { type Error = Infallible; }
+/// # impl SetDutyCycle for Channel {
+/// #     fn max_duty_cycle(&self) -> u16 { 1000 }
+/// #     fn set_duty_cycle(&mut self, _duty: u16) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # let (high, low) = (Channel, Channel);
+///
+/// // 20 kHz timer.
+/// let mut bridge = SoftComplementaryPwm::new(high, low, 50_000);
+/// bridge.set_dead_time_ns(500)?;
+/// bridge.set_duty_cycle_high_side(250)?;
+/// bridge.enable()?;
+/// # Ok::<(), Infallible>(())
+/// ```
+pub struct SoftComplementaryPwm<H, L> {
+    high: H,
+    low: L,
+    period_ns: u32,
+    duty: u16,
+    dead_time: u16,
+    enabled: bool,
+}
+
+impl<H, L> SoftComplementaryPwm<H, L>
+where
+    H: SetDutyCycle,
+    L: SetDutyCycle + ErrorType<Error = H::Error>,
+{
+    /// Create a new [`SoftComplementaryPwm`], for channels of a timer with a period of `period_ns`.
+    ///
+    /// The outputs start disabled, with a duty cycle of `0` and no dead time.
+    #[inline]
+    pub fn new(high: H, low: L, period_ns: u32) -> Self {
+        Self {
+            high,
+            low,
+            period_ns,
+            duty: 0,
+            dead_time: 0,
+            enabled: false,
+        }
+    }
+
+    /// Release the high side and low side channels.
+    #[inline]
+    pub fn release(self) -> (H, L) {
+        (self.high, self.low)
+    }
+
+    fn apply(&mut self) -> Result<(), H::Error> {
+        let max = self.high.max_duty_cycle();
+        let high = self.duty.saturating_sub(self.dead_time);
+        let low = (max - self.duty).saturating_sub(self.dead_time);
+        self.high.set_duty_cycle(high)?;
+        self.low.set_duty_cycle_fraction(low, max)
+    }
+}
+
+impl<H: ErrorType, L> ErrorType for SoftComplementaryPwm<H, L> {
+    type Error = H::Error;
+}
+
+impl<H, L> ComplementaryPwm for SoftComplementaryPwm<H, L>
+where
+    H: SetDutyCycle,
+    L: SetDutyCycle + ErrorType<Error = H::Error>,
+{
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        self.high.max_duty_cycle()
+    }
+
+    fn set_duty_cycle_high_side(&mut self, duty: u16) -> Result<(), Self::Error> {
+        debug_assert!(duty <= self.high.max_duty_cycle());
+        self.duty = duty.min(self.high.max_duty_cycle());
+        if self.enabled {
+            self.apply()?;
+        }
+        Ok(())
+    }
+
+    fn set_dead_time_ns(&mut self, ns: u32) -> Result<(), Self::Error> {
+        let max = u64::from(self.high.max_duty_cycle());
+        // Round up, so the dead time is never shorter than requested.
+        let steps = (u64::from(ns) * max).div_ceil(u64::from(self.period_ns).max(1));
+        self.dead_time = steps.min(max) as u16;
+        if self.enabled {
+            self.apply()?;
+        }
+        Ok(())
+    }
+
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        self.enabled = true;
+        self.apply()
+    }
+
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        self.enabled = false;
+        self.high.set_duty_cycle_fully_off()?;
+        self.low.set_duty_cycle_fully_off()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+    /// Channel logging its duty cycles, in steps of `max`.
+    struct FakeChannel {
+        max: u16,
+        log: Rc<RefCell<Vec<u16>>>,
+    }
+
+    impl ErrorType for FakeChannel {
+        type Error = Infallible;
+    }
+
+    impl SetDutyCycle for FakeChannel {
+        fn max_duty_cycle(&self) -> u16 {
+            self.max
+        }
+
+        fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Infallible> {
+            self.log.borrow_mut().push(duty);
+            Ok(())
+        }
+    }
+
+    type Log = Rc<RefCell<Vec<u16>>>;
+
+    fn bridge(low_max: u16) -> (Log, Log, SoftComplementaryPwm<FakeChannel, FakeChannel>) {
+        let (high, low) = (Log::default(), Log::default());
+        let pwm = SoftComplementaryPwm::new(
+            FakeChannel {
+                max: 1000,
+                log: high.clone(),
+            },
+            FakeChannel {
+                max: low_max,
+                log: low.clone(),
+            },
+            50_000,
+        );
+        (high, low, pwm)
+    }
+
+    #[test]
+    fn complementary_duty_cycles() {
+        let (high, low, mut pwm) = bridge(1000);
+        pwm.set_duty_cycle_high_side(300).unwrap();
+        assert!(high.borrow().is_empty());
+        pwm.enable().unwrap();
+        assert_eq!(*high.borrow(), [300]);
+        assert_eq!(*low.borrow(), [700]);
+    }
+
+    #[test]
+    fn dead_time_shortens_both_sides() {
+        let (high, low, mut pwm) = bridge(1000);
+        pwm.enable().unwrap();
+        pwm.set_duty_cycle_high_side(300).unwrap();
+        // 990 ns is 19.8 steps of 50 ns, rounded up to 20.
+        pwm.set_dead_time_ns(990).unwrap();
+        assert_eq!(high.borrow().last(), Some(&280));
+        assert_eq!(low.borrow().last(), Some(&680));
+
+        pwm.set_duty_cycle_high_side(10).unwrap();
+        assert_eq!(high.borrow().last(), Some(&0));
+        assert_eq!(low.borrow().last(), Some(&970));
+    }
+
+    #[test]
+    fn low_side_is_scaled() {
+        let (_, low, mut pwm) = bridge(100);
+        pwm.set_duty_cycle_high_side(250).unwrap();
+        pwm.enable().unwrap();
+        assert_eq!(*low.borrow(), [75]);
+    }
+
+    #[test]
+    fn disable_turns_both_sides_off() {
+        let (high, low, mut pwm) = bridge(1000);
+        pwm.set_duty_cycle_high_side(500).unwrap();
+        pwm.enable().unwrap();
+        pwm.disable().unwrap();
+        assert_eq!(high.borrow().last(), Some(&0));
+        assert_eq!(low.borrow().last(), Some(&0));
+        pwm.set_duty_cycle_high_side(600).unwrap();
+        assert_eq!(high.borrow().len(), 2);
+    }
+}
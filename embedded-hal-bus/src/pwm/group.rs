@@ -0,0 +1,192 @@
+use embedded_hal::pwm::{ErrorType, PwmGroup, SetDutyCycle};
+
+/// Software [`PwmGroup`] implementation over `N` independent [`SetDutyCycle`] channels.
+///
+/// This is a fallback for timers which can not update several channels at once, or for channels of
+/// different timers. The duty cycles are set one channel after the other, in array order: the update is
+/// **not** atomic, and the outputs may run for a period with only some of the channels updated. Check that
+/// this glitch is acceptable, or use a [`PwmGroup`] implementation of the HAL.
+///
+/// The group uses the [`max_duty_cycle`](SetDutyCycle::max_duty_cycle) of the first channel. The duty
+/// cycles are scaled to the maximum of each channel, so that channels with different resolutions get the
+/// same duty cycle ratio.
+///
+/// Since [`SetDutyCycle`] can not disable a channel, disabled channels are set to a duty cycle of 0%, and
+/// enabling them applies the last duty cycles again.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::PwmGroup;
+/// use embedded_hal_bus::pwm::SoftPwmGroup;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+/// # struct Channel;
+/// # impl ErrorType for Channel { type Error = Infallible; }
+/// # impl SetDutyCycle for Channel {
+/// #     fn max_duty_cycle(&self) -> u16 { 255 }
+/// #     fn set_duty_cycle(&mut self, _duty: u16) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # let (red, green, blue) = (Channel, Channel, Channel);
+///
+/// let mut led = SoftPwmGroup::new([red, green, blue]);
+/// // Orange.
+/// led.set_all_duty_cycles(&[255, 128, 0])?;
+/// led.enable_all()?;
+/// # Ok::<(), Infallible>(())
+/// ```
+pub struct SoftPwmGroup<P, const N: usize> {
+    channels: [P; N],
+    duties: [u16; N],
+    enabled: bool,
+}
+
+impl<P: SetDutyCycle, const N: usize> SoftPwmGroup<P, N> {
+    /// Create a new [`SoftPwmGroup`].
+    ///
+    /// The outputs start disabled, with duty cycles of `0`. The channels are not touched until the
+    /// group is enabled.
+    #[inline]
+    pub fn new(channels: [P; N]) -> Self {
+        Self {
+            channels,
+            duties: [0; N],
+            enabled: false,
+        }
+    }
+
+    /// Release the channels.
+    #[inline]
+    pub fn release(self) -> [P; N] {
+        self.channels
+    }
+
+    fn apply(&mut self) -> Result<(), P::Error> {
+        let max = PwmGroup::max_duty_cycle(self);
+        for (channel, &duty) in self.channels.iter_mut().zip(&self.duties) {
+            channel.set_duty_cycle_fraction(duty, max)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: ErrorType, const N: usize> ErrorType for SoftPwmGroup<P, N> {
+    type Error = P::Error;
+}
+
+impl<P: SetDutyCycle, const N: usize> PwmGroup<N> for SoftPwmGroup<P, N> {
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        self.channels.first().map_or(0, |c| c.max_duty_cycle())
+    }
+
+    fn set_all_duty_cycles(&mut self, duties: &[u16; N]) -> Result<(), Self::Error> {
+        let max = PwmGroup::max_duty_cycle(self);
+        debug_assert!(duties.iter().all(|&d| d <= max));
+        self.duties = duties.map(|d| d.min(max));
+        if self.enabled {
+            self.apply()?;
+        }
+        Ok(())
+    }
+
+    fn enable_all(&mut self) -> Result<(), Self::Error> {
+        self.enabled = true;
+        self.apply()
+    }
+
+    fn disable_all(&mut self) -> Result<(), Self::Error> {
+        self.enabled = false;
+        self.channels
+            .iter_mut()
+            .try_for_each(|c| c.set_duty_cycle_fully_off())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+    /// Index of the channel and duty cycle of every update.
+    type Log = Rc<RefCell<Vec<(usize, u16)>>>;
+
+    /// Channel logging its index and duty cycles, in steps of `max`.
+    struct FakeChannel {
+        index: usize,
+        max: u16,
+        log: Log,
+    }
+
+    impl ErrorType for FakeChannel {
+        type Error = Infallible;
+    }
+
+    impl SetDutyCycle for FakeChannel {
+        fn max_duty_cycle(&self) -> u16 {
+            self.max
+        }
+
+        fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Infallible> {
+            self.log.borrow_mut().push((self.index, duty));
+            Ok(())
+        }
+    }
+
+    fn group(maxes: [u16; 3]) -> (SoftPwmGroup<FakeChannel, 3>, Log) {
+        let log = Log::default();
+        let mut index = 0;
+        let channels = maxes.map(|max| {
+            index += 1;
+            FakeChannel {
+                index: index - 1,
+                max,
+                log: log.clone(),
+            }
+        });
+        (SoftPwmGroup::new(channels), log)
+    }
+
+    #[test]
+    fn duties_are_applied_in_order_once_enabled() {
+        let (mut pwm, log) = group([100; 3]);
+        pwm.set_all_duty_cycles(&[10, 20, 30]).unwrap();
+        assert!(log.borrow().is_empty());
+
+        pwm.enable_all().unwrap();
+        pwm.set_all_duty_cycles(&[40, 50, 60]).unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [(0, 10), (1, 20), (2, 30), (0, 40), (1, 50), (2, 60)]
+        );
+    }
+
+    #[test]
+    fn disable_turns_off_and_enable_restores() {
+        let (mut pwm, log) = group([100; 3]);
+        pwm.enable_all().unwrap();
+        pwm.set_all_duty_cycles(&[10, 20, 30]).unwrap();
+        log.borrow_mut().clear();
+
+        pwm.disable_all().unwrap();
+        // Stored while disabled, applied when enabled.
+        pwm.set_all_duty_cycles(&[70, 80, 90]).unwrap();
+        assert_eq!(*log.borrow(), [(0, 0), (1, 0), (2, 0)]);
+        log.borrow_mut().clear();
+
+        pwm.enable_all().unwrap();
+        assert_eq!(*log.borrow(), [(0, 70), (1, 80), (2, 90)]);
+    }
+
+    #[test]
+    fn duties_scale_to_each_channel() {
+        let (mut pwm, log) = group([1000, 100, 4000]);
+        assert_eq!(PwmGroup::max_duty_cycle(&pwm), 1000);
+        pwm.enable_all().unwrap();
+        pwm.set_all_duty_cycles(&[500, 250, 1000]).unwrap();
+        assert_eq!(log.borrow()[3..], [(0, 500), (1, 25), (2, 4000)]);
+    }
+}
@@ -0,0 +1,8 @@
+//! Wrappers around `SetDutyCycle` implementations.
+
+mod complementary;
+pub use complementary::*;
+mod group;
+pub use group::*;
+mod servo;
+pub use servo::*;
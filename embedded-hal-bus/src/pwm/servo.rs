@@ -0,0 +1,176 @@
+use embedded_hal::pwm::SetDutyCycle;
+
+/// RC servo driven by a [`SetDutyCycle`] channel.
+///
+/// Servos expect a pulse every period, usually 20 ms (50 Hz), whose width sets the position:
+/// `min_pulse_ns` for 0° and `max_pulse_ns` for 180°. The nominal range is 1 ms to 2 ms, but many
+/// servos accept wider pulses, so both ends are configurable. The pulse widths are converted to
+/// duty cycles using the [`max_duty_cycle`](SetDutyCycle::max_duty_cycle) of the channel, and the
+/// `period_ns` of its timer, which must be configured by the HAL.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::pwm::PwmServo;
+/// # use core::convert::Infallible;
+/// # use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+/// # struct Channel;
+/// # impl ErrorType for Channel { type Error = Infallible; }
+/// # impl SetDutyCycle for Channel {
+/// #     fn max_duty_cycle(&self) -> u16 { 20_000 }
+/// #     fn set_duty_cycle(&mut self, _duty: u16) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # let channel = Channel;
+///
+/// // 50 Hz timer, servo moving from 0° to 180° for pulses from 0.5 ms to 2.5 ms.
+/// let mut servo = PwmServo::new(channel, 500_000, 2_500_000, 20_000_000);
+/// servo.set_angle_degrees(90.0)?;
+/// # Ok::<(), Infallible>(())
+/// ```
+pub struct PwmServo<P> {
+    pwm: P,
+    min_pulse_ns: u32,
+    max_pulse_ns: u32,
+    period_ns: u32,
+}
+
+impl<P: SetDutyCycle> PwmServo<P> {
+    /// Create a new [`PwmServo`], for a channel of a timer with a period of `period_ns`.
+    ///
+    /// The duty cycle of the channel is not changed until a position is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_pulse_ns` is larger than `max_pulse_ns`, or `max_pulse_ns` is larger than
+    /// `period_ns`.
+    #[inline]
+    pub fn new(pwm: P, min_pulse_ns: u32, max_pulse_ns: u32, period_ns: u32) -> Self {
+        assert!(
+            min_pulse_ns <= max_pulse_ns && max_pulse_ns <= period_ns && period_ns > 0,
+            "the pulse widths must be ordered and fit in the period"
+        );
+        Self {
+            pwm,
+            min_pulse_ns,
+            max_pulse_ns,
+            period_ns,
+        }
+    }
+
+    /// Release the inner channel.
+    #[inline]
+    pub fn release(self) -> P {
+        self.pwm
+    }
+
+    /// Move the servo to `deg` degrees.
+    ///
+    /// The angle is clamped to `0.0..=180.0`, and NaN is treated as `0.0`.
+    pub fn set_angle_degrees(&mut self, deg: f32) -> Result<(), P::Error> {
+        let deg = if deg.is_nan() {
+            0.0
+        } else {
+            deg.clamp(0.0, 180.0)
+        };
+        let range = (self.max_pulse_ns - self.min_pulse_ns) as f32;
+        // Round to the nearest nanosecond, the value is positive.
+        let offset = (range * deg / 180.0 + 0.5) as u32;
+        self.set_pulse_ns(self.min_pulse_ns + offset)
+    }
+
+    /// Output pulses of `ns` nanoseconds, clamped to `min_pulse_ns..=max_pulse_ns`.
+    pub fn set_pulse_ns(&mut self, ns: u32) -> Result<(), P::Error> {
+        let ns = ns.clamp(self.min_pulse_ns, self.max_pulse_ns);
+        let max = u64::from(self.pwm.max_duty_cycle());
+        let duty =
+            (u64::from(ns) * max + u64::from(self.period_ns) / 2) / u64::from(self.period_ns);
+        self.pwm.set_duty_cycle(duty.min(max) as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::pwm::ErrorType;
+    use std::vec::Vec;
+
+    /// Channel logging its duty cycles, in steps of `max`.
+    struct FakeChannel {
+        max: u16,
+        log: Vec<u16>,
+    }
+
+    impl ErrorType for FakeChannel {
+        type Error = Infallible;
+    }
+
+    impl SetDutyCycle for FakeChannel {
+        fn max_duty_cycle(&self) -> u16 {
+            self.max
+        }
+
+        fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Infallible> {
+            self.log.push(duty);
+            Ok(())
+        }
+    }
+
+    fn servo(max: u16) -> PwmServo<FakeChannel> {
+        let channel = FakeChannel {
+            max,
+            log: Vec::new(),
+        };
+        PwmServo::new(channel, 1_000_000, 2_000_000, 20_000_000)
+    }
+
+    #[test]
+    fn angles_map_to_pulse_widths() {
+        let mut servo = servo(20_000);
+        for deg in [0.0, 45.0, 90.0, 180.0] {
+            servo.set_angle_degrees(deg).unwrap();
+        }
+        // One step is 1 µs.
+        assert_eq!(servo.release().log, [1_000, 1_250, 1_500, 2_000]);
+    }
+
+    #[test]
+    fn inputs_are_clamped() {
+        let mut servo = servo(20_000);
+        servo.set_angle_degrees(-10.0).unwrap();
+        servo.set_angle_degrees(400.0).unwrap();
+        servo.set_angle_degrees(f32::NAN).unwrap();
+        servo.set_pulse_ns(100).unwrap();
+        servo.set_pulse_ns(3_000_000).unwrap();
+        servo.set_pulse_ns(1_600_000).unwrap();
+        assert_eq!(
+            servo.release().log,
+            [1_000, 2_000, 1_000, 1_000, 2_000, 1_600]
+        );
+    }
+
+    #[test]
+    fn coarse_duty_cycle_is_rounded() {
+        // 8-bit timer: one step is 78.125 µs, so 1.5 ms is 19.2 steps.
+        let mut servo = servo(256);
+        servo.set_angle_degrees(90.0).unwrap();
+        servo.set_pulse_ns(1_980_000).unwrap();
+        assert_eq!(servo.release().log, [19, 25]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pulse_longer_than_period() {
+        PwmServo::new(
+            FakeChannel {
+                max: 100,
+                log: Vec::new(),
+            },
+            1_000_000,
+            30_000_000,
+            20_000_000,
+        );
+    }
+}
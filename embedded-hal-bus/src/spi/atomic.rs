@@ -1,10 +1,22 @@
 use embedded_hal::delay::DelayNs;
 use embedded_hal::digital::OutputPin;
 use embedded_hal::spi::{Error, ErrorKind, ErrorType, Operation, SpiBus, SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::{
+    delay::DelayNs as AsyncDelayNs,
+    spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice},
+};
 
-use super::DeviceError;
+use super::{CsTiming, DeviceError};
 use crate::spi::shared::transaction;
+#[cfg(feature = "async")]
+use crate::spi::shared::transaction_async;
 use crate::util::AtomicCell;
+#[cfg(feature = "async")]
+use crate::util::AtomicGuard;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
 
 /// Atomics-based shared bus [`SpiDevice`] implementation.
 ///
@@ -26,6 +38,15 @@ use crate::util::AtomicCell;
 ///
 /// This primitive is particularly well-suited for applications that have external arbitration
 /// rules that prevent `Busy` errors in the first place, such as the RTIC framework.
+///
+/// With the `async` feature, [`AtomicDevice`] also implements the async [`SpiDevice`](AsyncSpiDevice) trait.
+/// Async transactions never return `Busy` errors: when the bus is in use, they wait for it to be released
+/// without blocking the executor, which makes [`AtomicDevice`] usable to share a bus between several async tasks.
+/// Up to 4 tasks wait for the bus without being polled, and are woken up when it is released, without using
+/// critical sections. When more tasks wait at once, the others are polled again until one of them is served.
+/// If an async transaction is cancelled, the CS pin is deasserted and the bus is released. Since the
+/// bus cannot be flushed from a destructor, the device may have received an incomplete transfer.
+/// Use [`TryAtomicDevice`] to get `Busy` errors from async transactions too.
 #[cfg_attr(
     docsrs,
     doc(cfg(any(feature = "portable-atomic", target_has_atomic = "8")))
@@ -34,9 +55,11 @@ pub struct AtomicDevice<'a, BUS, CS, D> {
     bus: &'a AtomicCell<BUS>,
     cs: CS,
     delay: D,
+    timing: CsTiming,
 }
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 /// Wrapper type for errors returned by [`AtomicDevice`].
 #[cfg_attr(
     docsrs,
@@ -63,7 +86,19 @@ impl<'a, BUS, CS, D> AtomicDevice<'a, BUS, CS, D> {
         CS: OutputPin,
     {
         cs.set_high()?;
-        Ok(Self { bus, cs, delay })
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            timing: CsTiming::default(),
+        })
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
     }
 }
 
@@ -101,6 +136,7 @@ where
             bus,
             cs,
             delay: super::NoDelay,
+            timing: CsTiming::default(),
         })
     }
 }
@@ -130,24 +166,224 @@ where
 {
     #[inline]
     fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
-        self.bus
-            .busy
-            .compare_exchange(
-                false,
-                true,
-                core::sync::atomic::Ordering::SeqCst,
-                core::sync::atomic::Ordering::SeqCst,
-            )
-            .map_err(|_| AtomicError::Busy)?;
+        if !self.bus.try_lock() {
+            return Err(AtomicError::Busy);
+        }
 
         let bus = unsafe { &mut *self.bus.bus.get() };
 
-        let result = transaction(operations, bus, &mut self.delay, &mut self.cs);
+        let result = transaction(operations, bus, &mut self.delay, &mut self.cs, self.timing);
 
-        self.bus
-            .busy
-            .store(false, core::sync::atomic::Ordering::SeqCst);
+        self.bus.unlock();
 
         result.map_err(AtomicError::Other)
     }
 }
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word> for AtomicDevice<'_, BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        let guard = self.bus.lock_async().await;
+        locked_transaction_async(
+            guard,
+            operations,
+            &mut self.cs,
+            &mut self.delay,
+            self.timing,
+        )
+        .await
+    }
+}
+
+/// Atomics-based shared bus [`SpiDevice`] implementation, returning `Busy` errors from async transactions.
+///
+/// This is the same as [`AtomicDevice`], except that async transactions started while the bus is in use do
+/// not wait for it: they return [`AtomicError::Busy`] right away, without touching the `CS` pin. The bus is
+/// claimed with a single compare-exchange at the start of the transaction, held across its `.await` points,
+/// and released when it completes. This suits applications where contention is a bug to detect, or where a
+/// task would rather drop or defer its transfer than wait for the bus.
+///
+/// If an async transaction is cancelled, the CS pin is deasserted and the bus is released. Since the bus
+/// cannot be flushed from a destructor, the device may have received an incomplete transfer.
+///
+/// Blocking transactions behave as those of [`AtomicDevice`].
+#[cfg(feature = "async")]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(
+        feature = "async",
+        any(feature = "portable-atomic", target_has_atomic = "8")
+    )))
+)]
+pub struct TryAtomicDevice<'a, BUS, CS, D> {
+    bus: &'a AtomicCell<BUS>,
+    cs: CS,
+    delay: D,
+    timing: CsTiming,
+}
+
+#[cfg(feature = "async")]
+impl<'a, BUS, CS, D> TryAtomicDevice<'a, BUS, CS, D> {
+    /// Create a new [`TryAtomicDevice`].
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    #[inline]
+    pub fn new(bus: &'a AtomicCell<BUS>, mut cs: CS, delay: D) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            timing: CsTiming::default(),
+        })
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, BUS, CS> TryAtomicDevice<'a, BUS, CS, super::NoDelay> {
+    /// Create a new [`TryAtomicDevice`] without support for in-transaction delays.
+    ///
+    /// See [`AtomicDevice::new_no_delay`] for the caveats of not supporting delays.
+    ///
+    /// # Panics
+    ///
+    /// The returned device will panic if you try to execute a transaction
+    /// that contains any operations of type [`Operation::DelayNs`].
+    #[inline]
+    pub fn new_no_delay(bus: &'a AtomicCell<BUS>, mut cs: CS) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay: super::NoDelay,
+            timing: CsTiming::default(),
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl<BUS, CS, D> ErrorType for TryAtomicDevice<'_, BUS, CS, D>
+where
+    BUS: ErrorType,
+    CS: OutputPin,
+{
+    type Error = AtomicError<DeviceError<BUS::Error, CS::Error>>;
+}
+
+#[cfg(feature = "async")]
+impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for TryAtomicDevice<'_, BUS, CS, D>
+where
+    BUS: SpiBus<Word>,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    #[inline]
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        if !self.bus.try_lock() {
+            return Err(AtomicError::Busy);
+        }
+
+        let bus = unsafe { &mut *self.bus.bus.get() };
+
+        let result = transaction(operations, bus, &mut self.delay, &mut self.cs, self.timing);
+
+        self.bus.unlock();
+
+        result.map_err(AtomicError::Other)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word> for TryAtomicDevice<'_, BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        let guard = self.bus.try_lock_guard().ok_or(AtomicError::Busy)?;
+        locked_transaction_async(
+            guard,
+            operations,
+            &mut self.cs,
+            &mut self.delay,
+            self.timing,
+        )
+        .await
+    }
+}
+
+/// Run an async transaction on the bus locked by `guard`, deasserting CS and releasing the bus even if
+/// the transaction is cancelled.
+#[cfg(feature = "async")]
+async fn locked_transaction_async<Word, BUS, CS, D>(
+    mut guard: AtomicGuard<'_, BUS>,
+    operations: &mut [Operation<'_, Word>],
+    cs: &mut CS,
+    delay: &mut D,
+    timing: CsTiming,
+) -> Result<(), AtomicError<DeviceError<BUS::Error, CS::Error>>>
+where
+    Word: Copy + 'static,
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    // Declared after `guard`, so that CS is deasserted before the bus is released.
+    let mut cs = CsGuard { cs, asserted: true };
+
+    let result = transaction_async(operations, guard.bus(), delay, &mut *cs.cs, timing).await;
+
+    // `transaction_async` deasserted CS itself, or never asserted it.
+    cs.asserted = false;
+    drop(cs);
+    drop(guard);
+
+    result.map_err(AtomicError::Other)
+}
+
+/// Deasserts CS when dropped, unless the transaction completed.
+///
+/// This makes sure the device is deselected if an async transaction is cancelled.
+#[cfg(feature = "async")]
+struct CsGuard<'a, CS: OutputPin> {
+    cs: &'a mut CS,
+    asserted: bool,
+}
+
+#[cfg(feature = "async")]
+impl<CS: OutputPin> Drop for CsGuard<'_, CS> {
+    fn drop(&mut self) {
+        if self.asserted {
+            let _ = self.cs.set_high();
+        }
+    }
+}
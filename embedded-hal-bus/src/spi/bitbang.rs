@@ -0,0 +1,583 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal::spi::{Error, ErrorKind, ErrorType, Mode, Phase, Polarity, SpiBus, MODE_0};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Order in which the bits of a word are shifted out and in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum BitOrder {
+    /// Most significant bit first.
+    MsbFirst,
+    /// Least significant bit first.
+    LsbFirst,
+}
+
+/// Configuration of a [`BitBangSpi`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct BitBangConfig {
+    /// Clock polarity and phase.
+    pub mode: Mode,
+    /// Bit order of the words.
+    pub bit_order: BitOrder,
+    /// SCK clock frequency, in Hz.
+    ///
+    /// This is an upper bound: the time spent toggling the pins adds to the configured delays.
+    pub frequency_hz: u32,
+}
+
+impl Default for BitBangConfig {
+    /// Mode 0, MSB first, at 100 kHz.
+    fn default() -> Self {
+        Self {
+            mode: MODE_0,
+            bit_order: BitOrder::MsbFirst,
+            frequency_hz: 100_000,
+        }
+    }
+}
+
+/// Error type for [`BitBangSpi`] operations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum BitBangError<SCK, MOSI, MISO> {
+    /// Setting the SCK pin failed.
+    Sck(SCK),
+    /// Setting the MOSI pin failed.
+    Mosi(MOSI),
+    /// Reading the MISO pin failed.
+    Miso(MISO),
+}
+
+impl<SCK, MOSI, MISO> Error for BitBangError<SCK, MOSI, MISO>
+where
+    SCK: core::fmt::Debug,
+    MOSI: core::fmt::Debug,
+    MISO: core::fmt::Debug,
+{
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+impl<SCK, MOSI, MISO> core::fmt::Display for BitBangError<SCK, MOSI, MISO>
+where
+    SCK: core::fmt::Display,
+    MOSI: core::fmt::Display,
+    MISO: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Sck(e) => write!(f, "SCK pin error: {e}"),
+            Self::Mosi(e) => write!(f, "MOSI pin error: {e}"),
+            Self::Miso(e) => write!(f, "MISO pin error: {e}"),
+        }
+    }
+}
+
+impl<SCK, MOSI, MISO> core::error::Error for BitBangError<SCK, MOSI, MISO>
+where
+    SCK: core::fmt::Debug + core::fmt::Display,
+    MOSI: core::fmt::Debug + core::fmt::Display,
+    MISO: core::fmt::Debug + core::fmt::Display,
+{
+}
+
+/// Software ("bit-banged") SPI bus.
+///
+/// Implements [`SpiBus`] on top of three GPIO pins and a [`DelayNs`], for when no hardware
+/// SPI peripheral is available. Like any [`SpiBus`], it does not handle chip select:
+/// wrap it in one of the [`SpiDevice`](embedded_hal::spi::SpiDevice) implementations
+/// of this crate, such as [`ExclusiveDevice`](super::ExclusiveDevice).
+///
+/// All transfers are done synchronously, so [`flush`](SpiBus::flush) is a no-op.
+/// When reading, zeros are sent on MOSI.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::MODE_3;
+/// use embedded_hal_bus::spi::{BitBangConfig, BitBangSpi, BitOrder};
+/// # use core::convert::Infallible;
+/// # use embedded_hal::{delay::DelayNs, digital::{ErrorType, InputPin, OutputPin}};
+/// # struct Pin;
+/// # impl ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # impl InputPin for Pin {
+/// #     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// #     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(false) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _ns: u32) {} }
+/// # let (sck, mosi, miso, delay) = (Pin, Pin, Pin, Delay);
+///
+/// let config = BitBangConfig {
+///     mode: MODE_3,
+///     bit_order: BitOrder::LsbFirst,
+///     frequency_hz: 1_000_000,
+/// };
+/// let spi = BitBangSpi::new(sck, mosi, miso, delay, config)?;
+/// # Ok::<(), embedded_hal_bus::spi::BitBangError<Infallible, Infallible, Infallible>>(())
+/// ```
+pub struct BitBangSpi<SCK, MOSI, MISO, D> {
+    sck: SCK,
+    mosi: MOSI,
+    miso: MISO,
+    delay: D,
+    config: BitBangConfig,
+    half_period_ns: u32,
+}
+
+impl<SCK, MOSI, MISO, D> BitBangSpi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayNs,
+{
+    /// Create a new [`BitBangSpi`].
+    ///
+    /// This drives SCK to its idle level, according to the configured mode.
+    #[inline]
+    pub fn new(
+        sck: SCK,
+        mosi: MOSI,
+        miso: MISO,
+        delay: D,
+        config: BitBangConfig,
+    ) -> Result<Self, <Self as ErrorType>::Error> {
+        let mut this = Self {
+            sck,
+            mosi,
+            miso,
+            delay,
+            config,
+            half_period_ns: 500_000_000 / config.frequency_hz.max(1),
+        };
+        this.set_sck(false)?;
+        Ok(this)
+    }
+
+    /// Release the pins and the delay.
+    #[inline]
+    pub fn release(self) -> (SCK, MOSI, MISO, D) {
+        (self.sck, self.mosi, self.miso, self.delay)
+    }
+
+    fn wait(&mut self) {
+        self.delay.delay_ns(self.half_period_ns);
+    }
+
+    /// Drive SCK to its active level if `active` is true, or to its idle level.
+    fn set_sck(&mut self, active: bool) -> Result<(), <Self as ErrorType>::Error> {
+        let high = active != (self.config.mode.polarity == Polarity::IdleHigh);
+        if high {
+            self.sck.set_high().map_err(BitBangError::Sck)
+        } else {
+            self.sck.set_low().map_err(BitBangError::Sck)
+        }
+    }
+
+    fn set_mosi(&mut self, high: bool) -> Result<(), <Self as ErrorType>::Error> {
+        if high {
+            self.mosi.set_high().map_err(BitBangError::Mosi)
+        } else {
+            self.mosi.set_low().map_err(BitBangError::Mosi)
+        }
+    }
+
+    fn read_miso(&mut self) -> Result<bool, <Self as ErrorType>::Error> {
+        self.miso.is_high().map_err(BitBangError::Miso)
+    }
+
+    /// Exchange a bit during one clock cycle. SCK is idle before and after.
+    fn exchange_bit(&mut self, bit: bool) -> Result<bool, <Self as ErrorType>::Error> {
+        match self.config.mode.phase {
+            Phase::CaptureOnFirstTransition => {
+                self.set_mosi(bit)?;
+                self.wait();
+                self.set_sck(true)?;
+                let read = self.read_miso()?;
+                self.wait();
+                self.set_sck(false)?;
+                Ok(read)
+            }
+            Phase::CaptureOnSecondTransition => {
+                self.set_sck(true)?;
+                self.set_mosi(bit)?;
+                self.wait();
+                self.set_sck(false)?;
+                let read = self.read_miso()?;
+                self.wait();
+                Ok(read)
+            }
+        }
+    }
+
+    fn exchange_word(&mut self, word: u8) -> Result<u8, <Self as ErrorType>::Error> {
+        let mut read = 0;
+        for i in 0..8 {
+            let mask = match self.config.bit_order {
+                BitOrder::MsbFirst => 0x80 >> i,
+                BitOrder::LsbFirst => 1 << i,
+            };
+            if self.exchange_bit(word & mask != 0)? {
+                read |= mask;
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl<SCK, MOSI, MISO, D> ErrorType for BitBangSpi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+{
+    type Error = BitBangError<SCK::Error, MOSI::Error, MISO::Error>;
+}
+
+impl<SCK, MOSI, MISO, D> SpiBus for BitBangSpi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayNs,
+{
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = self.exchange_word(0)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        for word in words {
+            self.exchange_word(*word)?;
+        }
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        for i in 0..read.len().max(write.len()) {
+            let word = self.exchange_word(write.get(i).copied().unwrap_or(0))?;
+            if let Some(r) = read.get_mut(i) {
+                *r = word;
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = self.exchange_word(*word)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+    use embedded_hal::digital;
+    use embedded_hal::spi::{MODE_1, MODE_2, MODE_3};
+    use std::{collections::VecDeque, rc::Rc, vec::Vec};
+
+    /// Wires with a simulated target, which shifts `tx` out and records what it receives in `rx`.
+    struct Wire {
+        mode: Mode,
+        sck: bool,
+        mosi: bool,
+        miso: bool,
+        tx: VecDeque<bool>,
+        rx: Vec<bool>,
+        /// Levels of SCK and MOSI on every SCK edge.
+        edges: Vec<(bool, bool)>,
+    }
+
+    impl Wire {
+        fn on_sck(&mut self, sck: bool) {
+            if sck == self.sck {
+                return;
+            }
+            self.sck = sck;
+            self.edges.push((sck, self.mosi));
+            let leading = sck != (self.mode.polarity == Polarity::IdleHigh);
+            let capture = match self.mode.phase {
+                Phase::CaptureOnFirstTransition => leading,
+                Phase::CaptureOnSecondTransition => !leading,
+            };
+            if capture {
+                self.rx.push(self.mosi);
+            } else {
+                self.miso = self.tx.pop_front().unwrap_or(true);
+            }
+        }
+    }
+
+    fn bits(words: &[u8], bit_order: BitOrder) -> Vec<bool> {
+        let mut bits = Vec::new();
+        for word in words {
+            for i in 0..8 {
+                let bit = match bit_order {
+                    BitOrder::MsbFirst => word & (0x80 >> i),
+                    BitOrder::LsbFirst => word & (1 << i),
+                };
+                bits.push(bit != 0);
+            }
+        }
+        bits
+    }
+
+    #[derive(Clone, Copy)]
+    enum Line {
+        Sck,
+        Mosi,
+        Miso,
+    }
+
+    struct SimPin {
+        wire: Rc<RefCell<Wire>>,
+        line: Line,
+    }
+
+    impl SimPin {
+        fn set(&mut self, high: bool) {
+            let mut wire = self.wire.borrow_mut();
+            match self.line {
+                Line::Sck => wire.on_sck(high),
+                Line::Mosi => wire.mosi = high,
+                Line::Miso => unreachable!(),
+            }
+        }
+    }
+
+    impl digital::ErrorType for SimPin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for SimPin {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.set(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.set(true);
+            Ok(())
+        }
+    }
+
+    impl InputPin for SimPin {
+        fn is_high(&mut self) -> Result<bool, Infallible> {
+            Ok(self.wire.borrow().miso)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Infallible> {
+            self.is_high().map(|high| !high)
+        }
+    }
+
+    /// Delay which records the total waited time.
+    struct RecordingDelay(u32);
+
+    impl DelayNs for RecordingDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 += ns;
+        }
+    }
+
+    type Spi = BitBangSpi<SimPin, SimPin, SimPin, RecordingDelay>;
+
+    fn setup(config: BitBangConfig, target_tx: &[u8]) -> (Rc<RefCell<Wire>>, Spi) {
+        let idle = config.mode.polarity == Polarity::IdleHigh;
+        let mut tx: VecDeque<bool> = bits(target_tx, config.bit_order).into();
+        // In phase 0, the first bit is shifted out when the target is selected.
+        let miso = match config.mode.phase {
+            Phase::CaptureOnFirstTransition => tx.pop_front().unwrap_or(true),
+            Phase::CaptureOnSecondTransition => true,
+        };
+        let wire = Rc::new(RefCell::new(Wire {
+            mode: config.mode,
+            sck: idle,
+            mosi: false,
+            miso,
+            tx,
+            rx: Vec::new(),
+            edges: Vec::new(),
+        }));
+        let pin = |line| SimPin {
+            wire: wire.clone(),
+            line,
+        };
+        let spi = BitBangSpi::new(
+            pin(Line::Sck),
+            pin(Line::Mosi),
+            pin(Line::Miso),
+            RecordingDelay(0),
+            config,
+        )
+        .unwrap();
+        (wire, spi)
+    }
+
+    fn check_mode(mode: Mode) {
+        let config = BitBangConfig {
+            mode,
+            ..Default::default()
+        };
+        let (wire, mut spi) = setup(config, &[0x3C, 0x81]);
+        let mut words = [0xA5, 0x0F];
+        spi.transfer_in_place(&mut words).unwrap();
+
+        assert_eq!(words, [0x3C, 0x81]);
+        let wire = wire.borrow();
+        assert_eq!(wire.rx, bits(&[0xA5, 0x0F], BitOrder::MsbFirst));
+        // 16 clock cycles, starting from and returning to the idle level.
+        let idle = mode.polarity == Polarity::IdleHigh;
+        assert_eq!(wire.edges.len(), 32);
+        for (i, (sck, _)) in wire.edges.iter().enumerate() {
+            assert_eq!(*sck, (i % 2 == 0) != idle);
+        }
+    }
+
+    #[test]
+    fn mode_0() {
+        check_mode(MODE_0);
+    }
+
+    #[test]
+    fn mode_1() {
+        check_mode(MODE_1);
+    }
+
+    #[test]
+    fn mode_2() {
+        check_mode(MODE_2);
+    }
+
+    #[test]
+    fn mode_3() {
+        check_mode(MODE_3);
+    }
+
+    #[test]
+    fn mode_0_waveform() {
+        let (wire, mut spi) = setup(BitBangConfig::default(), &[]);
+        spi.write(&[0b1011_0001]).unwrap();
+
+        // MOSI is stable on the rising (capturing) edge, and is only changed while SCK is low.
+        assert_eq!(
+            wire.borrow().edges,
+            [
+                (true, true),
+                (false, true),
+                (true, false),
+                (false, false),
+                (true, true),
+                (false, true),
+                (true, true),
+                (false, true),
+                (true, false),
+                (false, false),
+                (true, false),
+                (false, false),
+                (true, false),
+                (false, false),
+                (true, true),
+                (false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn mode_1_waveform() {
+        let config = BitBangConfig {
+            mode: MODE_1,
+            ..Default::default()
+        };
+        let (wire, mut spi) = setup(config, &[]);
+        spi.write(&[0b1000_0001]).unwrap();
+
+        // MOSI changes after the rising edge, and is stable on the falling (capturing) edge.
+        let edges = &wire.borrow().edges;
+        assert_eq!(
+            edges[..4],
+            [(true, false), (false, true), (true, true), (false, false)]
+        );
+        assert_eq!(edges[14..], [(true, false), (false, true)]);
+    }
+
+    #[test]
+    fn lsb_first() {
+        let config = BitBangConfig {
+            bit_order: BitOrder::LsbFirst,
+            ..Default::default()
+        };
+        let (wire, mut spi) = setup(config, &[0x01]);
+        let mut read = [0];
+        spi.transfer(&mut read, &[0x80]).unwrap();
+
+        assert_eq!(read, [0x01]);
+        assert_eq!(
+            wire.borrow().rx,
+            [false, false, false, false, false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn read_sends_zeros_and_write_discards() {
+        let (wire, mut spi) = setup(BitBangConfig::default(), &[0x12, 0x34, 0x56]);
+        spi.write(&[0xFF]).unwrap();
+        let mut read = [0; 2];
+        spi.read(&mut read).unwrap();
+
+        assert_eq!(read, [0x34, 0x56]);
+        assert_eq!(wire.borrow().rx, bits(&[0xFF, 0, 0], BitOrder::MsbFirst));
+    }
+
+    #[test]
+    fn transfer_with_different_lengths() {
+        let (wire, mut spi) = setup(BitBangConfig::default(), &[0x12, 0x34, 0x56]);
+        let mut read = [0; 3];
+        spi.transfer(&mut read, &[0xAB]).unwrap();
+        assert_eq!(read, [0x12, 0x34, 0x56]);
+        assert_eq!(wire.borrow().rx, bits(&[0xAB, 0, 0], BitOrder::MsbFirst));
+
+        let (wire, mut spi) = setup(BitBangConfig::default(), &[0x12, 0x34]);
+        let mut read = [0; 1];
+        spi.transfer(&mut read, &[0xAB, 0xCD]).unwrap();
+        assert_eq!(read, [0x12]);
+        assert_eq!(wire.borrow().rx, bits(&[0xAB, 0xCD], BitOrder::MsbFirst));
+    }
+
+    #[test]
+    fn clock_frequency() {
+        let config = BitBangConfig {
+            frequency_hz: 1_000_000,
+            ..Default::default()
+        };
+        let (_wire, mut spi) = setup(config, &[]);
+        spi.write(&[0x00, 0x00]).unwrap();
+
+        let (_, _, _, delay) = spi.release();
+        assert_eq!(delay.0, 16 * 1_000);
+    }
+}
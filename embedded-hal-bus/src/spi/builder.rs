@@ -0,0 +1,227 @@
+use embedded_hal::spi::{Operation, SpiDevice};
+
+/// Extension trait for [`SpiDevice`], adding [`begin`](SpiDeviceExt::begin).
+///
+/// This trait is implemented for all SPI devices.
+pub trait SpiDeviceExt<Word: Copy + 'static = u8>: SpiDevice<Word> {
+    /// Start building a transaction of at most `N` operations.
+    ///
+    /// The operations are collected by the returned [`SpiTransactionBuilder`], and executed
+    /// with [`SpiTransactionBuilder::commit`].
+    #[inline]
+    fn begin<'a, const N: usize>(&mut self) -> SpiTransactionBuilder<'_, 'a, Self, Word, N>
+    where
+        Self: Sized,
+    {
+        SpiTransactionBuilder::new(self)
+    }
+}
+
+impl<Word: Copy + 'static, D: SpiDevice<Word>> SpiDeviceExt<Word> for D {}
+
+/// Builder for an [`SpiDevice`] transaction.
+///
+/// This collects up to `N` operations in an array, without requiring the caller to build the
+/// slice of [`Operation`]s themselves. It is created by [`SpiDeviceExt::begin`].
+///
+/// Nothing happens on the bus until [`commit`](Self::commit) is called: CS is asserted when the
+/// transaction starts, and deasserted when it ends, before `commit` returns. The builder is
+/// consumed by `commit`, so a transaction can only be executed once. Dropping the builder without
+/// committing it discards the operations, without touching the bus.
+///
+/// # Panics
+///
+/// The builder methods panic if more than `N` operations are added.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::spi::SpiDeviceExt;
+/// # use embedded_hal::spi::{ErrorKind, ErrorType, Operation, SpiDevice};
+/// # struct Flash;
+/// # impl ErrorType for Flash { type Error = ErrorKind; }
+/// # impl SpiDevice for Flash {
+/// #     fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # let mut flash = Flash;
+///
+/// // Read 16 bytes at address 0x1000 of a SPI flash.
+/// let mut data = [0; 16];
+/// flash
+///     .begin::<2>()
+///     .write(&[0x03, 0x00, 0x10, 0x00])
+///     .read(&mut data)
+///     .commit()?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct SpiTransactionBuilder<'d, 'a, D, Word: 'static, const N: usize> {
+    device: &'d mut D,
+    operations: [Operation<'a, Word>; N],
+    len: usize,
+}
+
+impl<'d, 'a, D, Word, const N: usize> SpiTransactionBuilder<'d, 'a, D, Word, N>
+where
+    D: SpiDevice<Word>,
+    Word: Copy + 'static,
+{
+    fn new(device: &'d mut D) -> Self {
+        Self {
+            device,
+            operations: core::array::from_fn(|_| Operation::DelayNs(0)),
+            len: 0,
+        }
+    }
+
+    fn push(mut self, operation: Operation<'a, Word>) -> Self {
+        assert!(
+            self.len < N,
+            "too many operations for the transaction builder"
+        );
+        self.operations[self.len] = operation;
+        self.len += 1;
+        self
+    }
+
+    /// Add an [`Operation::Read`].
+    #[inline]
+    pub fn read(self, buf: &'a mut [Word]) -> Self {
+        self.push(Operation::Read(buf))
+    }
+
+    /// Add an [`Operation::Write`].
+    #[inline]
+    pub fn write(self, buf: &'a [Word]) -> Self {
+        self.push(Operation::Write(buf))
+    }
+
+    /// Add an [`Operation::Transfer`].
+    #[inline]
+    pub fn transfer(self, read: &'a mut [Word], write: &'a [Word]) -> Self {
+        self.push(Operation::Transfer(read, write))
+    }
+
+    /// Add an [`Operation::TransferInPlace`].
+    #[inline]
+    pub fn transfer_in_place(self, buf: &'a mut [Word]) -> Self {
+        self.push(Operation::TransferInPlace(buf))
+    }
+
+    /// Add an [`Operation::DelayNs`].
+    #[inline]
+    pub fn delay_ns(self, ns: u32) -> Self {
+        self.push(Operation::DelayNs(ns))
+    }
+
+    /// Returns the number of operations added so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no operations were added.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Execute the operations as a single transaction on the device.
+    #[inline]
+    pub fn commit(mut self) -> Result<(), D::Error> {
+        self.device.transaction(&mut self.operations[..self.len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::spi::{ErrorKind, ErrorType};
+    use std::{format, string::String, vec::Vec};
+
+    /// Device recording its transactions, and filling read buffers with `0xAA`.
+    #[derive(Default)]
+    struct FakeDevice {
+        transactions: Vec<Vec<String>>,
+    }
+
+    impl ErrorType for FakeDevice {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for FakeDevice {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+            let log = operations
+                .iter_mut()
+                .map(|operation| match operation {
+                    Operation::Read(buf) | Operation::TransferInPlace(buf) => {
+                        buf.fill(0xAA);
+                        format!("{operation:?}")
+                    }
+                    Operation::Transfer(read, _) => {
+                        read.fill(0xAA);
+                        format!("{operation:?}")
+                    }
+                    _ => format!("{operation:?}"),
+                })
+                .collect();
+            self.transactions.push(log);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn commit_executes_one_transaction() {
+        let mut device = FakeDevice::default();
+        let mut read = [0; 2];
+        let mut transfer = [0; 1];
+        let mut in_place = [1];
+        device
+            .begin::<5>()
+            .write(&[1, 2])
+            .delay_ns(100)
+            .read(&mut read)
+            .transfer(&mut transfer, &[3])
+            .transfer_in_place(&mut in_place)
+            .commit()
+            .unwrap();
+        assert_eq!(device.transactions.len(), 1);
+        assert_eq!(
+            device.transactions[0],
+            [
+                "Write([1, 2])",
+                "DelayNs(100)",
+                "Read([170, 170])",
+                "Transfer([170], [3])",
+                "TransferInPlace([170])",
+            ]
+        );
+        assert_eq!(read, [0xAA; 2]);
+        assert_eq!(transfer, [0xAA]);
+        assert_eq!(in_place, [0xAA]);
+    }
+
+    #[test]
+    fn only_added_operations_are_executed() {
+        let mut device = FakeDevice::default();
+        let builder = device.begin::<4>().write(&[1]);
+        assert_eq!(builder.len(), 1);
+        builder.commit().unwrap();
+        assert_eq!(device.transactions, [["Write([1])"]]);
+    }
+
+    #[test]
+    fn drop_without_commit_does_nothing() {
+        let mut device = FakeDevice::default();
+        let _ = device.begin::<1>().write(&[1]);
+        assert!(device.transactions.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "too many operations")]
+    fn too_many_operations() {
+        let mut device = FakeDevice::default();
+        let _ = device.begin::<1>().write(&[1]).write(&[2]);
+    }
+}
@@ -1,97 +1,104 @@
 use core::cell::RefCell;
 use critical_section::Mutex;
-use embedded_hal::delay::DelayNs;
-use embedded_hal::digital::OutputPin;
-use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal::{digital::OutputPin, spi::Operation};
+#[cfg(feature = "async")]
+use embedded_hal_async::{
+    delay::DelayNs as AsyncDelayNs,
+    spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice},
+};
 
-use super::DeviceError;
-use crate::spi::shared::transaction;
+use super::{Device, DeviceWithConfig};
+#[cfg(feature = "async")]
+use super::{DeviceError, SetConfig};
+#[cfg(feature = "async")]
+use crate::spi::shared::transaction_async;
+#[cfg(feature = "async")]
+use crate::util::{poll_to_completion, BusMutex};
 
-/// `critical-section`-based shared bus [`SpiDevice`] implementation.
+/// `critical-section`-based shared bus [`SpiDevice`](embedded_hal::spi::SpiDevice) implementation.
 ///
-/// This allows for sharing an [`SpiBus`], obtaining multiple [`SpiDevice`] instances,
-/// each with its own `CS` pin.
+/// This allows for sharing an [`SpiBus`](embedded_hal::spi::SpiBus), obtaining multiple
+/// [`SpiDevice`](embedded_hal::spi::SpiDevice) instances, each with its own `CS` pin.
 ///
 /// Sharing is implemented with a `critical-section` [`Mutex`]. A critical section is taken for
 /// the entire duration of a transaction. This allows sharing a single bus across multiple threads (interrupt priority levels).
 /// The downside is critical sections typically require globally disabling interrupts, so `CriticalSectionDevice` will likely
 /// negatively impact real-time properties, such as interrupt latency. If you can, prefer using
 /// [`RefCellDevice`](super::RefCellDevice) instead, which does not require taking critical sections.
-pub struct CriticalSectionDevice<'a, BUS, CS, D> {
-    bus: &'a Mutex<RefCell<BUS>>,
-    cs: CS,
-    delay: D,
-}
-
-impl<'a, BUS, CS, D> CriticalSectionDevice<'a, BUS, CS, D> {
-    /// Create a new [`CriticalSectionDevice`].
-    ///
-    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
-    /// to set the pin high the moment it's configured as an output, to avoid glitches.
-    #[inline]
-    pub fn new(bus: &'a Mutex<RefCell<BUS>>, mut cs: CS, delay: D) -> Result<Self, CS::Error>
-    where
-        CS: OutputPin,
-    {
-        cs.set_high()?;
-        Ok(Self { bus, cs, delay })
-    }
-}
+///
+/// To share a bus obtained at runtime between `'static` devices, for example moved to interrupt handlers,
+/// store it in a [`StaticBus`](crate::util::StaticBus).
+///
+/// # Async
+///
+/// With the `async` feature, `CriticalSectionDevice` also implements the async
+/// [`SpiDevice`](embedded_hal_async::spi::SpiDevice) trait if the bus does. Since a critical section can't be held
+/// across an `.await` point, the critical section is taken for the whole transaction, including asserting and
+/// deasserting CS, and the transaction future is polled to completion in a busy loop, without yielding to the executor.
+///
+/// This requires the bus and delay futures to make progress when polled with interrupts disabled, e.g. by
+/// checking the peripheral status registers or a free-running timer. A future which only completes after being
+/// woken by an interrupt handler will deadlock.
+pub type CriticalSectionDevice<'a, BUS, CS, D> = Device<&'a Mutex<RefCell<BUS>>, CS, D>;
 
-impl<'a, BUS, CS> CriticalSectionDevice<'a, BUS, CS, super::NoDelay> {
-    /// Create a new [`CriticalSectionDevice`] without support for in-transaction delays.
-    ///
-    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
-    /// to set the pin high the moment it's configured as an output, to avoid glitches.
-    ///
-    /// **Warning**: The returned instance *technically* doesn't comply with the `SpiDevice`
-    /// contract, which mandates delay support. It is relatively rare for drivers to use
-    /// in-transaction delays, so you might still want to use this method because it's more practical.
-    ///
-    /// Note that a future version of the driver might start using delays, causing your
-    /// code to panic. This wouldn't be considered a breaking change from the driver side, because
-    /// drivers are allowed to assume `SpiDevice` implementations comply with the contract.
-    /// If you feel this risk outweighs the convenience of having `cargo` automatically upgrade
-    /// the driver crate, you might want to pin the driver's version.
-    ///
-    /// # Panics
-    ///
-    /// The returned device will panic if you try to execute a transaction
-    /// that contains any operations of type [`Operation::DelayNs`].
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word>
+    for CriticalSectionDevice<'_, BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
     #[inline]
-    pub fn new_no_delay(bus: &'a Mutex<RefCell<BUS>>, mut cs: CS) -> Result<Self, CS::Error>
-    where
-        CS: OutputPin,
-    {
-        cs.set_high()?;
-        Ok(Self {
-            bus,
-            cs,
-            delay: super::NoDelay,
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| {
+            poll_to_completion(transaction_async(
+                operations,
+                bus,
+                &mut self.delay,
+                &mut self.cs,
+                self.timing,
+            ))
         })
     }
 }
 
-impl<BUS, CS, D> ErrorType for CriticalSectionDevice<'_, BUS, CS, D>
-where
-    BUS: ErrorType,
-    CS: OutputPin,
-{
-    type Error = DeviceError<BUS::Error, CS::Error>;
-}
+/// `critical-section`-based shared bus [`SpiDevice`](embedded_hal::spi::SpiDevice) implementation applying a
+/// per-device [`SetConfig`](super::SetConfig) configuration.
+///
+/// This is the same as [`CriticalSectionDevice`], except that the device configuration is applied to the bus
+/// at the start of every transaction, see [`DeviceWithConfig`].
+pub type CriticalSectionDeviceWithConfig<'a, BUS, CS, D> =
+    DeviceWithConfig<&'a Mutex<RefCell<BUS>>, CS, D>;
 
-impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for CriticalSectionDevice<'_, BUS, CS, D>
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word>
+    for CriticalSectionDeviceWithConfig<'_, BUS, CS, D>
 where
-    BUS: SpiBus<Word>,
+    BUS: AsyncSpiBus<Word> + SetConfig,
     CS: OutputPin,
-    D: DelayNs,
+    D: AsyncDelayNs,
 {
     #[inline]
-    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
-        critical_section::with(|cs| {
-            let bus = &mut *self.bus.borrow_ref_mut(cs);
-
-            transaction(operations, bus, &mut self.delay, &mut self.cs)
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| {
+            bus.set_config(&self.config).map_err(DeviceError::Config)?;
+            poll_to_completion(transaction_async(
+                operations,
+                bus,
+                &mut self.delay,
+                &mut self.cs,
+                self.timing,
+            ))
         })
     }
 }
@@ -0,0 +1,166 @@
+use embedded_hal::spi::{Operation, SpiDevice};
+
+/// `N` identical SPI shift registers chained together, such as 74HC595 outputs or 74HC165 inputs.
+///
+/// In a daisy chain, the data output of each device feeds the data input of the next one, and all
+/// the devices share the clock and CS lines. The whole chain behaves like a single shift register
+/// of `N` words of `WORD` bytes: it is written or read in a single transaction, and deasserting CS
+/// latches the outputs of all the devices at once.
+///
+/// Words are transferred in array order. When writing, `data[0]` is shifted first, so it ends up in
+/// the device farthest from the controller, and `data[N - 1]` in the device connected to MOSI.
+/// When reading, the first word received, `[0]`, comes from the device connected to MISO.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::spi::SpiDaisyChain;
+/// # use embedded_hal::spi::{ErrorKind, ErrorType, Operation, SpiDevice};
+/// # struct Device;
+/// # impl ErrorType for Device { type Error = ErrorKind; }
+/// # impl SpiDevice for Device {
+/// #     fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # let spi = Device;
+///
+/// // Three 74HC595 driving 24 LEDs.
+/// let mut leds: SpiDaisyChain<_, 3> = SpiDaisyChain::new(spi);
+/// leds.write_all(&[[0b1000_0001], [0x00], [0xFF]])?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct SpiDaisyChain<T, const N: usize, const WORD: usize = 1> {
+    spi: T,
+}
+
+impl<T, const N: usize, const WORD: usize> SpiDaisyChain<T, N, WORD> {
+    /// Create a new [`SpiDaisyChain`].
+    #[inline]
+    pub fn new(spi: T) -> Self {
+        Self { spi }
+    }
+
+    /// Release the inner SPI device.
+    #[inline]
+    pub fn release(self) -> T {
+        self.spi
+    }
+}
+
+impl<T: SpiDevice, const N: usize, const WORD: usize> SpiDaisyChain<T, N, WORD> {
+    /// Shift `data` into the chain in a single transaction, latching it when CS is deasserted.
+    #[inline]
+    pub fn write_all(&mut self, data: &[[u8; WORD]; N]) -> Result<(), T::Error> {
+        self.spi
+            .transaction(&mut [Operation::Write(data.as_flattened())])
+    }
+
+    /// Shift the same `word` into all the devices of the chain in a single transaction.
+    ///
+    /// This is useful for configuration words which every device of the chain must receive, such as
+    /// the modes of LED drivers or DACs. `word` is written `N` times while CS is asserted.
+    #[inline]
+    pub fn broadcast(&mut self, word: &[u8; WORD]) -> Result<(), T::Error> {
+        let mut operations: [Operation<'_, u8>; N] =
+            core::array::from_fn(|_| Operation::Write(word));
+        self.spi.transaction(&mut operations)
+    }
+
+    /// Shift a payload of any length into each device of the chain in a single transaction.
+    ///
+    /// Unlike [`write_all`](SpiDaisyChain::write_all), the payloads do not need to be `WORD` bytes
+    /// long, for chains mixing devices with different register widths. They are shifted in array
+    /// order, like the words of `write_all`: `payloads[0]` is written first, so it ends up in the
+    /// device farthest from the controller.
+    #[inline]
+    pub fn write_chain(&mut self, payloads: &[&[u8]; N]) -> Result<(), T::Error> {
+        let mut operations: [Operation<'_, u8>; N] =
+            core::array::from_fn(|i| Operation::Write(payloads[i]));
+        self.spi.transaction(&mut operations)
+    }
+
+    /// Shift the words of the whole chain out in a single transaction.
+    ///
+    /// Input shift registers such as the 74HC165 need to load their inputs first: this is usually
+    /// done by connecting their load input to CS.
+    #[inline]
+    pub fn read_all(&mut self) -> Result<[[u8; WORD]; N], T::Error> {
+        let mut data = [[0; WORD]; N];
+        self.spi
+            .transaction(&mut [Operation::Read(data.as_flattened_mut())])?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::spi::{ErrorKind, ErrorType};
+    use std::vec::Vec;
+
+    /// Device recording the transactions, and reading an incrementing counter.
+    #[derive(Default)]
+    struct FakeDevice {
+        transactions: Vec<Vec<u8>>,
+        counter: u8,
+    }
+
+    impl ErrorType for FakeDevice {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for FakeDevice {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+            let mut written = Vec::new();
+            for op in operations {
+                match op {
+                    Operation::Write(data) => written.extend_from_slice(data),
+                    Operation::Read(data) => data.iter_mut().for_each(|b| {
+                        *b = self.counter;
+                        self.counter += 1;
+                    }),
+                    _ => return Err(ErrorKind::Other),
+                }
+            }
+            self.transactions.push(written);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_all_in_one_transaction() {
+        let mut chain: SpiDaisyChain<_, 3> = SpiDaisyChain::new(FakeDevice::default());
+        chain.write_all(&[[0x01], [0x02], [0x03]]).unwrap();
+        chain.write_all(&[[0xA0], [0xB0], [0xC0]]).unwrap();
+        assert_eq!(
+            chain.release().transactions,
+            [[0x01, 0x02, 0x03], [0xA0, 0xB0, 0xC0]]
+        );
+    }
+
+    #[test]
+    fn broadcast_repeats_the_word_in_one_transaction() {
+        let mut chain: SpiDaisyChain<_, 3, 2> = SpiDaisyChain::new(FakeDevice::default());
+        chain.broadcast(&[0x12, 0x34]).unwrap();
+        assert_eq!(
+            chain.release().transactions,
+            [[0x12, 0x34, 0x12, 0x34, 0x12, 0x34]]
+        );
+    }
+
+    #[test]
+    fn write_chain_mixed_payload_lengths() {
+        let mut chain: SpiDaisyChain<_, 3> = SpiDaisyChain::new(FakeDevice::default());
+        chain.write_chain(&[&[0x01, 0x02], &[], &[0x03]]).unwrap();
+        assert_eq!(chain.release().transactions, [[0x01, 0x02, 0x03]]);
+    }
+
+    #[test]
+    fn read_all_multi_byte_words() {
+        let mut chain: SpiDaisyChain<_, 2, 2> = SpiDaisyChain::new(FakeDevice::default());
+        assert_eq!(chain.read_all(), Ok([[0, 1], [2, 3]]));
+        assert_eq!(chain.read_all(), Ok([[4, 5], [6, 7]]));
+        assert_eq!(chain.release().transactions.len(), 2);
+    }
+}
@@ -0,0 +1,244 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice, SpiDeviceWithConfig};
+
+use super::{CsTiming, DeviceError, SetConfig};
+use crate::spi::shared::transaction;
+use crate::util::BusMutex;
+
+/// Shared bus [`SpiDevice`] implementation, generic over the [`BusMutex`] protecting the bus.
+///
+/// This allows for sharing an [`SpiBus`], obtaining multiple [`SpiDevice`] instances,
+/// each with its own `CS` pin.
+///
+/// The mutex is locked for the entire duration of a transaction. The provided device types, such as
+/// [`RefCellDevice`](super::RefCellDevice) or [`CriticalSectionDevice`](super::CriticalSectionDevice),
+/// are instantiations of this type. Use it directly to share a bus with your own lock type.
+pub struct Device<M, CS, D> {
+    pub(super) bus: M,
+    pub(super) cs: CS,
+    pub(super) delay: D,
+    pub(super) timing: CsTiming,
+}
+
+impl<M, CS, D> Device<M, CS, D> {
+    /// Create a new [`Device`].
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    #[inline]
+    pub fn new(bus: M, mut cs: CS, delay: D) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            timing: CsTiming::default(),
+        })
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl<M, CS> Device<M, CS, super::NoDelay> {
+    /// Create a new [`Device`] without support for in-transaction delays.
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    ///
+    /// **Warning**: The returned instance *technically* doesn't comply with the `SpiDevice`
+    /// contract, which mandates delay support. It is relatively rare for drivers to use
+    /// in-transaction delays, so you might still want to use this method because it's more practical.
+    ///
+    /// Note that a future version of the driver might start using delays, causing your
+    /// code to panic. This wouldn't be considered a breaking change from the driver side, because
+    /// drivers are allowed to assume `SpiDevice` implementations comply with the contract.
+    /// If you feel this risk outweighs the convenience of having `cargo` automatically upgrade
+    /// the driver crate, you might want to pin the driver's version.
+    ///
+    /// # Panics
+    ///
+    /// The returned device will panic if you try to execute a transaction
+    /// that contains any operations of type [`Operation::DelayNs`].
+    #[inline]
+    pub fn new_no_delay(bus: M, mut cs: CS) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay: super::NoDelay,
+            timing: CsTiming::default(),
+        })
+    }
+}
+
+impl<M, CS, D> ErrorType for Device<M, CS, D>
+where
+    M: BusMutex,
+    M::Bus: ErrorType,
+    CS: OutputPin,
+{
+    type Error = DeviceError<<M::Bus as ErrorType>::Error, CS::Error>;
+}
+
+impl<Word: Copy + 'static, M, CS, D> SpiDevice<Word> for Device<M, CS, D>
+where
+    M: BusMutex,
+    M::Bus: SpiBus<Word>,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    #[inline]
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        self.bus
+            .lock(|bus| transaction(operations, bus, &mut self.delay, &mut self.cs, self.timing))
+    }
+}
+
+/// Shared bus [`SpiDevice`] implementation applying a per-device configuration to the bus.
+///
+/// This is the same as [`Device`], except that the device stores its own bus configuration, and
+/// applies it with [`SetConfig::set_config`] at the start of every transaction, while the mutex is
+/// locked and before asserting CS. This allows devices with different requirements, for example
+/// different clock frequencies or SPI modes, to share a bus.
+///
+/// The provided device types, such as [`RefCellDeviceWithConfig`](super::RefCellDeviceWithConfig),
+/// are instantiations of this type.
+pub struct DeviceWithConfig<M, CS, D>
+where
+    M: BusMutex,
+    M::Bus: SetConfig,
+{
+    pub(super) bus: M,
+    pub(super) cs: CS,
+    pub(super) delay: D,
+    pub(super) timing: CsTiming,
+    pub(super) config: <M::Bus as SetConfig>::Config,
+}
+
+impl<M, CS, D> DeviceWithConfig<M, CS, D>
+where
+    M: BusMutex,
+    M::Bus: SetConfig,
+{
+    /// Create a new [`DeviceWithConfig`].
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    #[inline]
+    pub fn new(
+        bus: M,
+        mut cs: CS,
+        delay: D,
+        config: <M::Bus as SetConfig>::Config,
+    ) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            config,
+            timing: CsTiming::default(),
+        })
+    }
+
+    /// Returns a reference to the configuration of the device.
+    #[inline]
+    pub fn config(&self) -> &<M::Bus as SetConfig>::Config {
+        &self.config
+    }
+
+    /// Returns a mutable reference to the configuration of the device, applied from the next transaction.
+    #[inline]
+    pub fn config_mut(&mut self) -> &mut <M::Bus as SetConfig>::Config {
+        &mut self.config
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl<M, CS> DeviceWithConfig<M, CS, super::NoDelay>
+where
+    M: BusMutex,
+    M::Bus: SetConfig,
+{
+    /// Create a new [`DeviceWithConfig`] without support for in-transaction delays.
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. See
+    /// [`Device::new_no_delay`] for the caveats of not supporting delays.
+    ///
+    /// # Panics
+    ///
+    /// The returned device will panic if you try to execute a transaction
+    /// that contains any operations of type [`Operation::DelayNs`].
+    #[inline]
+    pub fn new_no_delay(
+        bus: M,
+        cs: CS,
+        config: <M::Bus as SetConfig>::Config,
+    ) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        Self::new(bus, cs, super::NoDelay, config)
+    }
+}
+
+impl<M, CS, D> ErrorType for DeviceWithConfig<M, CS, D>
+where
+    M: BusMutex,
+    M::Bus: ErrorType + SetConfig,
+    CS: OutputPin,
+{
+    type Error =
+        DeviceError<<M::Bus as ErrorType>::Error, CS::Error, <M::Bus as SetConfig>::ConfigError>;
+}
+
+impl<Word: Copy + 'static, M, CS, D> SpiDevice<Word> for DeviceWithConfig<M, CS, D>
+where
+    M: BusMutex,
+    M::Bus: SpiBus<Word> + SetConfig,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    #[inline]
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        self.bus.lock(|bus| {
+            bus.set_config(&self.config).map_err(DeviceError::Config)?;
+            transaction(operations, bus, &mut self.delay, &mut self.cs, self.timing)
+        })
+    }
+}
+
+impl<Word: Copy + 'static, M, CS, D> SpiDeviceWithConfig<<M::Bus as SetConfig>::Config, Word>
+    for DeviceWithConfig<M, CS, D>
+where
+    M: BusMutex,
+    M::Bus: SpiBus<Word> + SetConfig,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    #[inline]
+    fn configuration(&self) -> &<M::Bus as SetConfig>::Config {
+        &self.config
+    }
+}
@@ -0,0 +1,138 @@
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{ErrorType, Operation};
+use embedded_hal_async::{
+    delay::DelayNs,
+    spi::{SpiBus, SpiDevice},
+};
+
+use super::shared::transaction_async;
+use super::{CsTiming, DeviceError};
+
+/// `embassy-sync` async mutex based shared bus [`SpiDevice`] implementation.
+///
+/// This allows multiple async tasks to share a bus, each with its own CS pin: a task waits for
+/// the bus to be released instead of failing, and the mutex is locked for the entire duration of
+/// a transaction, `.await` points included. The raw mutex type `M` chooses which contexts can
+/// share the bus, e.g. `NoopRawMutex` for tasks of a single executor, or `CriticalSectionRawMutex`
+/// across interrupt priority levels.
+///
+/// The CS pin and delay belong to the device, so only the bus is behind the mutex.
+///
+/// # Example
+///
+/// ```
+/// use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+/// use embassy_sync::mutex::Mutex;
+/// use embedded_hal::digital::OutputPin;
+/// use embedded_hal_async::spi::{SpiBus, SpiDevice};
+/// use embedded_hal_bus::spi::{AsyncMutexDevice, DeviceError};
+///
+/// async fn two_devices<T: SpiBus, CS: OutputPin>(
+///     spi: T,
+///     cs_flash: CS,
+///     cs_display: CS,
+/// ) -> Result<(), DeviceError<T::Error, CS::Error>> {
+///     let bus = Mutex::<NoopRawMutex, _>::new(spi);
+///     let mut flash = AsyncMutexDevice::new_no_delay(&bus, cs_flash).map_err(DeviceError::Cs)?;
+///     let mut display = AsyncMutexDevice::new_no_delay(&bus, cs_display).map_err(DeviceError::Cs)?;
+///     flash.write(&[0x06]).await?;
+///     display.write(&[0x29]).await?;
+///     Ok(())
+/// }
+/// ```
+pub struct AsyncMutexDevice<'a, M: RawMutex, BUS, CS, D> {
+    bus: &'a Mutex<M, BUS>,
+    cs: CS,
+    delay: D,
+    timing: CsTiming,
+}
+
+impl<'a, M: RawMutex, BUS, CS, D> AsyncMutexDevice<'a, M, BUS, CS, D> {
+    /// Create a new [`AsyncMutexDevice`].
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    #[inline]
+    pub fn new(bus: &'a Mutex<M, BUS>, mut cs: CS, delay: D) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            timing: CsTiming::default(),
+        })
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl<'a, M: RawMutex, BUS, CS> AsyncMutexDevice<'a, M, BUS, CS, super::NoDelay> {
+    /// Create a new [`AsyncMutexDevice`] without support for in-transaction delays.
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    ///
+    /// **Warning**: The returned instance *technically* doesn't comply with the `SpiDevice`
+    /// contract, which mandates delay support. It is relatively rare for drivers to use
+    /// in-transaction delays, so you might still want to use this method because it's more practical.
+    ///
+    /// Note that a future version of the driver might start using delays, causing your
+    /// code to panic. This wouldn't be considered a breaking change from the driver side, because
+    /// drivers are allowed to assume `SpiDevice` implementations comply with the contract.
+    /// If you feel this risk outweighs the convenience of having `cargo` automatically upgrade
+    /// the driver crate, you might want to pin the driver's version.
+    ///
+    /// # Panics
+    ///
+    /// The returned device will panic if you try to execute a transaction
+    /// that contains any operations of type [`Operation::DelayNs`].
+    #[inline]
+    pub fn new_no_delay(bus: &'a Mutex<M, BUS>, cs: CS) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        Self::new(bus, cs, super::NoDelay)
+    }
+}
+
+impl<M: RawMutex, BUS, CS, D> ErrorType for AsyncMutexDevice<'_, M, BUS, CS, D>
+where
+    BUS: ErrorType,
+    CS: OutputPin,
+{
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+impl<Word: Copy + 'static, M: RawMutex, BUS, CS, D> SpiDevice<Word>
+    for AsyncMutexDevice<'_, M, BUS, CS, D>
+where
+    BUS: SpiBus<Word>,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        let mut bus = self.bus.lock().await;
+        transaction_async(
+            operations,
+            &mut *bus,
+            &mut self.delay,
+            &mut self.cs,
+            self.timing,
+        )
+        .await
+    }
+}
@@ -2,15 +2,19 @@
 
 use embedded_hal::delay::DelayNs;
 use embedded_hal::digital::OutputPin;
-use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice};
+use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice, SpiDeviceWithConfig};
 #[cfg(feature = "async")]
 use embedded_hal_async::{
     delay::DelayNs as AsyncDelayNs,
-    spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice},
+    spi::{
+        SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice, SpiTransaction, SpiTransactionalDevice,
+    },
 };
 
 use super::shared::transaction;
-use super::DeviceError;
+#[cfg(feature = "async")]
+use super::shared::{transaction_async, transaction_result};
+use super::{CsTiming, DeviceError, SetConfig};
 
 /// [`SpiDevice`] implementation with exclusive access to the bus (not shared).
 ///
@@ -20,6 +24,7 @@ pub struct ExclusiveDevice<BUS, CS, D> {
     bus: BUS,
     cs: CS,
     delay: D,
+    timing: CsTiming,
 }
 
 impl<BUS, CS, D> ExclusiveDevice<BUS, CS, D> {
@@ -33,7 +38,12 @@ impl<BUS, CS, D> ExclusiveDevice<BUS, CS, D> {
         CS: OutputPin,
     {
         cs.set_high()?;
-        Ok(Self { bus, cs, delay })
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            timing: CsTiming::default(),
+        })
     }
 
     /// Returns a reference to the underlying bus object.
@@ -47,6 +57,13 @@ impl<BUS, CS, D> ExclusiveDevice<BUS, CS, D> {
     pub fn bus_mut(&mut self) -> &mut BUS {
         &mut self.bus
     }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
+    }
 }
 
 impl<BUS, CS> ExclusiveDevice<BUS, CS, super::NoDelay> {
@@ -79,6 +96,7 @@ impl<BUS, CS> ExclusiveDevice<BUS, CS, super::NoDelay> {
             bus,
             cs,
             delay: super::NoDelay,
+            timing: CsTiming::default(),
         })
     }
 }
@@ -99,7 +117,13 @@ where
 {
     #[inline]
     fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
-        transaction(operations, &mut self.bus, &mut self.delay, &mut self.cs)
+        transaction(
+            operations,
+            &mut self.bus,
+            &mut self.delay,
+            &mut self.cs,
+            self.timing,
+        )
     }
 }
 
@@ -116,38 +140,282 @@ where
         &mut self,
         operations: &mut [Operation<'_, Word>],
     ) -> Result<(), Self::Error> {
+        transaction_async(
+            operations,
+            &mut self.bus,
+            &mut self.delay,
+            &mut self.cs,
+            self.timing,
+        )
+        .await
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> SpiTransactionalDevice<Word> for ExclusiveDevice<BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    type Transaction<'a>
+        = ExclusiveTransaction<'a, BUS, CS, D>
+    where
+        Self: 'a;
+
+    #[inline]
+    async fn begin_transaction(&mut self) -> Result<Self::Transaction<'_>, Self::Error> {
         self.cs.set_low().map_err(DeviceError::Cs)?;
+        if self.timing.setup_ns > 0 {
+            self.delay.delay_ns(self.timing.setup_ns).await;
+        }
+        Ok(ExclusiveTransaction {
+            device: self,
+            committed: false,
+        })
+    }
+}
+
+/// Open [`SpiTransaction`] on an [`ExclusiveDevice`].
+///
+/// Returned by [`SpiTransactionalDevice::begin_transaction`]. CS stays asserted until the
+/// transaction is committed or dropped. Dropping it without committing sets CS high without
+/// flushing the bus or applying the hold and idle delays, and ignores CS errors.
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct ExclusiveTransaction<'a, BUS, CS: OutputPin, D> {
+    device: &'a mut ExclusiveDevice<BUS, CS, D>,
+    committed: bool,
+}
+
+#[cfg(feature = "async")]
+impl<BUS, CS, D> ErrorType for ExclusiveTransaction<'_, BUS, CS, D>
+where
+    BUS: ErrorType,
+    CS: OutputPin,
+{
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+#[cfg(feature = "async")]
+impl<Word: Copy + 'static, BUS, CS, D> SpiTransaction<Word> for ExclusiveTransaction<'_, BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    #[inline]
+    async fn read(&mut self, buf: &mut [Word]) -> Result<(), Self::Error> {
+        self.device.bus.read(buf).await.map_err(DeviceError::Spi)
+    }
 
-        let op_res = 'ops: {
-            for op in operations {
-                let res = match op {
-                    Operation::Read(buf) => self.bus.read(buf).await,
-                    Operation::Write(buf) => self.bus.write(buf).await,
-                    Operation::Transfer(read, write) => self.bus.transfer(read, write).await,
-                    Operation::TransferInPlace(buf) => self.bus.transfer_in_place(buf).await,
-                    Operation::DelayNs(ns) => match self.bus.flush().await {
-                        Err(e) => Err(e),
-                        Ok(()) => {
-                            self.delay.delay_ns(*ns).await;
-                            Ok(())
-                        }
-                    },
-                };
-                if let Err(e) = res {
-                    break 'ops Err(e);
-                }
-            }
-            Ok(())
-        };
-
-        // On failure, it's important to still flush and deassert CS.
-        let flush_res = self.bus.flush().await;
-        let cs_res = self.cs.set_high();
-
-        op_res.map_err(DeviceError::Spi)?;
-        flush_res.map_err(DeviceError::Spi)?;
-        cs_res.map_err(DeviceError::Cs)?;
+    #[inline]
+    async fn write(&mut self, buf: &[Word]) -> Result<(), Self::Error> {
+        self.device.bus.write(buf).await.map_err(DeviceError::Spi)
+    }
 
+    #[inline]
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error> {
+        self.device
+            .bus
+            .transfer(read, write)
+            .await
+            .map_err(DeviceError::Spi)
+    }
+
+    #[inline]
+    async fn transfer_in_place(&mut self, buf: &mut [Word]) -> Result<(), Self::Error> {
+        self.device
+            .bus
+            .transfer_in_place(buf)
+            .await
+            .map_err(DeviceError::Spi)
+    }
+
+    #[inline]
+    async fn delay_ns(&mut self, ns: u32) -> Result<(), Self::Error> {
+        self.device.bus.flush().await.map_err(DeviceError::Spi)?;
+        self.device.delay.delay_ns(ns).await;
         Ok(())
     }
+
+    async fn commit(mut self) -> Result<(), Self::Error> {
+        let device = &mut *self.device;
+        let flush_res = device.bus.flush().await;
+        if device.timing.hold_ns > 0 {
+            device.delay.delay_ns(device.timing.hold_ns).await;
+        }
+        let cs_res = device.cs.set_high();
+        // Only now: if `commit` is cancelled before this point, dropping `self` still deasserts CS.
+        self.committed = true;
+        if device.timing.idle_ns > 0 {
+            device.delay.delay_ns(device.timing.idle_ns).await;
+        }
+        transaction_result(Ok(()), flush_res, cs_res)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<BUS, CS: OutputPin, D> Drop for ExclusiveTransaction<'_, BUS, CS, D> {
+    fn drop(&mut self) {
+        if !self.committed {
+            // Abort: the device must not stay selected.
+            let _ = self.device.cs.set_high();
+        }
+    }
+}
+
+/// [`SpiDevice`] implementation with exclusive access to the bus, applying a per-device [`SetConfig`] configuration.
+///
+/// This is the same as [`ExclusiveDevice`], except that the device configuration is applied to the bus at
+/// the start of every transaction, before asserting CS. This is mostly useful when the bus is also used
+/// directly between transactions, through [`bus_mut`](ExclusiveDeviceWithConfig::bus_mut).
+pub struct ExclusiveDeviceWithConfig<BUS: SetConfig, CS, D> {
+    bus: BUS,
+    cs: CS,
+    delay: D,
+    timing: CsTiming,
+    config: BUS::Config,
+}
+
+impl<BUS: SetConfig, CS, D> ExclusiveDeviceWithConfig<BUS, CS, D> {
+    /// Create a new [`ExclusiveDeviceWithConfig`].
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    #[inline]
+    pub fn new(bus: BUS, mut cs: CS, delay: D, config: BUS::Config) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            config,
+            timing: CsTiming::default(),
+        })
+    }
+
+    /// Returns a reference to the underlying bus object.
+    #[inline]
+    pub fn bus(&self) -> &BUS {
+        &self.bus
+    }
+
+    /// Returns a mutable reference to the underlying bus object.
+    #[inline]
+    pub fn bus_mut(&mut self) -> &mut BUS {
+        &mut self.bus
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Returns a reference to the configuration of the device.
+    #[inline]
+    pub fn config(&self) -> &BUS::Config {
+        &self.config
+    }
+
+    /// Returns a mutable reference to the configuration of the device, applied from the next transaction.
+    #[inline]
+    pub fn config_mut(&mut self) -> &mut BUS::Config {
+        &mut self.config
+    }
+}
+
+impl<BUS: SetConfig, CS> ExclusiveDeviceWithConfig<BUS, CS, super::NoDelay> {
+    /// Create a new [`ExclusiveDeviceWithConfig`] without support for in-transaction delays.
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. See
+    /// [`ExclusiveDevice::new_no_delay`] for the caveats of not supporting delays.
+    ///
+    /// # Panics
+    ///
+    /// The returned device will panic if you try to execute a transaction
+    /// that contains any operations of type [`Operation::DelayNs`].
+    #[inline]
+    pub fn new_no_delay(bus: BUS, cs: CS, config: BUS::Config) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        Self::new(bus, cs, super::NoDelay, config)
+    }
+}
+
+impl<BUS, CS, D> ErrorType for ExclusiveDeviceWithConfig<BUS, CS, D>
+where
+    BUS: ErrorType + SetConfig,
+    CS: OutputPin,
+{
+    type Error = DeviceError<BUS::Error, CS::Error, BUS::ConfigError>;
+}
+
+impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for ExclusiveDeviceWithConfig<BUS, CS, D>
+where
+    BUS: SpiBus<Word> + SetConfig,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    #[inline]
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        self.bus
+            .set_config(&self.config)
+            .map_err(DeviceError::Config)?;
+        transaction(
+            operations,
+            &mut self.bus,
+            &mut self.delay,
+            &mut self.cs,
+            self.timing,
+        )
+    }
+}
+
+impl<Word: Copy + 'static, BUS, CS, D> SpiDeviceWithConfig<BUS::Config, Word>
+    for ExclusiveDeviceWithConfig<BUS, CS, D>
+where
+    BUS: SpiBus<Word> + SetConfig,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    #[inline]
+    fn configuration(&self) -> &BUS::Config {
+        &self.config
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word>
+    for ExclusiveDeviceWithConfig<BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word> + SetConfig,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        self.bus
+            .set_config(&self.config)
+            .map_err(DeviceError::Config)?;
+        transaction_async(
+            operations,
+            &mut self.bus,
+            &mut self.delay,
+            &mut self.cs,
+            self.timing,
+        )
+        .await
+    }
 }
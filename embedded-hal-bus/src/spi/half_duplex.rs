@@ -0,0 +1,116 @@
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{ErrorType, Operation, SpiBusHalfDuplex, SpiDevice};
+
+use super::shared::transaction_result;
+use super::DeviceError;
+
+/// [`SpiDevice`] implementation with exclusive access to a half-duplex (3-wire) bus.
+///
+/// This is the [`ExclusiveDevice`](super::ExclusiveDevice) equivalent for an [`SpiBusHalfDuplex`]:
+/// CS is asserted for the duration of the transaction, and reads and writes are forwarded to the bus,
+/// which switches the direction of the data line as needed.
+///
+/// # Panics
+///
+/// A half-duplex bus cannot read and write simultaneously: the device panics if a transaction
+/// contains any operations of type [`Operation::Transfer`] or [`Operation::TransferInPlace`].
+pub struct HalfDuplexDevice<BUS, CS, D> {
+    bus: BUS,
+    cs: CS,
+    delay: D,
+}
+
+impl<BUS, CS, D> HalfDuplexDevice<BUS, CS, D> {
+    /// Create a new [`HalfDuplexDevice`].
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    #[inline]
+    pub fn new(bus: BUS, mut cs: CS, delay: D) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self { bus, cs, delay })
+    }
+
+    /// Returns a reference to the underlying bus object.
+    #[inline]
+    pub fn bus(&self) -> &BUS {
+        &self.bus
+    }
+
+    /// Returns a mutable reference to the underlying bus object.
+    #[inline]
+    pub fn bus_mut(&mut self) -> &mut BUS {
+        &mut self.bus
+    }
+}
+
+impl<BUS, CS> HalfDuplexDevice<BUS, CS, super::NoDelay> {
+    /// Create a new [`HalfDuplexDevice`] without support for in-transaction delays.
+    ///
+    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
+    /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    ///
+    /// **Warning**: The returned instance *technically* doesn't comply with the `SpiDevice`
+    /// contract, which mandates delay support. See [`ExclusiveDevice::new_no_delay`](super::ExclusiveDevice::new_no_delay)
+    /// for details.
+    ///
+    /// # Panics
+    ///
+    /// The returned device will panic if you try to execute a transaction
+    /// that contains any operations of type [`Operation::DelayNs`].
+    #[inline]
+    pub fn new_no_delay(bus: BUS, mut cs: CS) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay: super::NoDelay,
+        })
+    }
+}
+
+impl<BUS, CS, D> ErrorType for HalfDuplexDevice<BUS, CS, D>
+where
+    BUS: ErrorType,
+    CS: OutputPin,
+{
+    type Error = DeviceError<BUS::Error, CS::Error>;
+}
+
+impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for HalfDuplexDevice<BUS, CS, D>
+where
+    BUS: SpiBusHalfDuplex<Word>,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        let bus = &mut self.bus;
+        self.cs.set_low().map_err(DeviceError::Cs)?;
+
+        let op_res = operations.iter_mut().try_for_each(|op| match op {
+            Operation::Read(buf) => bus.read(buf),
+            Operation::Write(buf) => bus.write(buf),
+            Operation::Transfer(..) | Operation::TransferInPlace(_) => {
+                panic!("Full-duplex transfers are not supported on a half-duplex SPI bus")
+            }
+            Operation::DelayNs(ns) => {
+                bus.flush()?;
+                self.delay.delay_ns(*ns);
+                Ok(())
+            }
+        });
+
+        // On failure, it's important to still flush and deassert CS.
+        let flush_res = bus.flush();
+        let cs_res = self.cs.set_high();
+
+        transaction_result(op_res, flush_res, cs_res)
+    }
+}
@@ -0,0 +1,289 @@
+use embedded_hal::spi::{Error, ErrorType, Operation, SpiBus, SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice};
+
+#[cfg(all(feature = "defmt-03", not(feature = "log")))]
+use crate::defmt;
+use crate::logging::{record, HexDump, Outcome, DEFAULT_MAX_DUMP};
+
+/// [`SpiDevice`] and [`SpiBus`] wrapper logging every operation.
+///
+/// Each operation is logged once performed, at the debug level, with the kind of operation, the
+/// number of words, a hex dump of the data, and the result of the call (of the whole transaction
+/// for an [`SpiDevice`]). The hex dump is limited to 16 bytes by default, see [`Logged::with_max_dump`].
+///
+/// For [`Operation::TransferInPlace`], only the data read back is dumped, since the written data was
+/// overwritten by the time the operation is logged.
+///
+/// Records are emitted with the `log` crate when the `log` feature is enabled, and with `defmt`
+/// when only the `defmt-03` feature is.
+///
+/// With the `async` feature, `Logged` also implements the async [`SpiDevice`](embedded_hal_async::spi::SpiDevice)
+/// and [`SpiBus`](embedded_hal_async::spi::SpiBus) traits if the inner type does.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::SpiDevice;
+/// use embedded_hal_bus::spi::Logged;
+/// # use embedded_hal::spi::{self, ErrorKind, Operation};
+/// # struct Flash;
+/// # impl spi::ErrorType for Flash { type Error = ErrorKind; }
+/// # impl SpiDevice for Flash {
+/// #     fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let flash = Flash;
+///
+/// let mut flash = Logged::new(flash);
+/// // Logs "spi write 1 bytes [9f] -> ok" then "spi read 3 bytes [00 00 00] -> ok".
+/// let mut id = [0; 3];
+/// flash.transaction(&mut [Operation::Write(&[0x9F]), Operation::Read(&mut id)])?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct Logged<T> {
+    inner: T,
+    max_dump: usize,
+}
+
+impl<T> Logged<T> {
+    /// Create a new [`Logged`] wrapper around `inner`.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            max_dump: DEFAULT_MAX_DUMP,
+        }
+    }
+
+    /// Set the maximum number of bytes dumped per operation.
+    ///
+    /// Longer data is truncated, and marked with `...`.
+    #[inline]
+    pub fn with_max_dump(mut self, max_dump: usize) -> Self {
+        self.max_dump = max_dump;
+        self
+    }
+
+    /// Release the inner bus or device.
+    #[inline]
+    pub fn release(self) -> T {
+        self.inner
+    }
+
+    fn dump<'a>(&self, data: &'a [u8]) -> HexDump<'a> {
+        HexDump {
+            data,
+            max: self.max_dump,
+        }
+    }
+
+    fn log<E: Error>(&self, operations: &[Operation<'_, u8>], result: &Result<(), E>) {
+        for operation in operations {
+            let outcome = Outcome(result.as_ref().copied().map_err(|e| e.kind()));
+            match operation {
+                Operation::Read(words) => record!(
+                    "spi read {} bytes {} -> {}",
+                    words.len(),
+                    self.dump(words),
+                    outcome
+                ),
+                Operation::Write(words) => record!(
+                    "spi write {} bytes {} -> {}",
+                    words.len(),
+                    self.dump(words),
+                    outcome
+                ),
+                Operation::Transfer(read, write) => record!(
+                    "spi transfer write {} bytes {} read {} bytes {} -> {}",
+                    write.len(),
+                    self.dump(write),
+                    read.len(),
+                    self.dump(read),
+                    outcome
+                ),
+                Operation::TransferInPlace(words) => record!(
+                    "spi transfer in place {} bytes read {} -> {}",
+                    words.len(),
+                    self.dump(words),
+                    outcome
+                ),
+                Operation::DelayNs(ns) => record!("spi delay {} ns -> {}", ns, outcome),
+            }
+        }
+    }
+
+    fn log_flush<E: Error>(&self, result: &Result<(), E>) {
+        record!(
+            "spi flush -> {}",
+            Outcome(result.as_ref().copied().map_err(|e| e.kind()))
+        );
+    }
+}
+
+impl<T: ErrorType> ErrorType for Logged<T> {
+    type Error = T::Error;
+}
+
+impl<T: SpiDevice> SpiDevice for Logged<T> {
+    #[inline]
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(operations);
+        self.log(operations, &result);
+        result
+    }
+}
+
+impl<T: SpiBus> SpiBus for Logged<T> {
+    #[inline]
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(words);
+        self.log(&[Operation::Read(words)], &result);
+        result
+    }
+
+    #[inline]
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(words);
+        self.log(&[Operation::Write(words)], &result);
+        result
+    }
+
+    #[inline]
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer(read, write);
+        self.log(&[Operation::Transfer(read, write)], &result);
+        result
+    }
+
+    #[inline]
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer_in_place(words);
+        self.log(&[Operation::TransferInPlace(words)], &result);
+        result
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        let result = self.inner.flush();
+        self.log_flush(&result);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: AsyncSpiDevice> AsyncSpiDevice for Logged<T> {
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(operations).await;
+        self.log(operations, &result);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: AsyncSpiBus> AsyncSpiBus for Logged<T> {
+    #[inline]
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(words).await;
+        self.log(&[Operation::Read(words)], &result);
+        result
+    }
+
+    #[inline]
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(words).await;
+        self.log(&[Operation::Write(words)], &result);
+        result
+    }
+
+    #[inline]
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer(read, write).await;
+        self.log(&[Operation::Transfer(read, write)], &result);
+        result
+    }
+
+    #[inline]
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer_in_place(words).await;
+        self.log(&[Operation::TransferInPlace(words)], &result);
+        result
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        let result = self.inner.flush().await;
+        self.log_flush(&result);
+        result
+    }
+}
+
+#[cfg(all(test, feature = "log"))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::logging::capture::records;
+    use embedded_hal::spi::ErrorKind;
+
+    struct FakeDevice;
+
+    impl ErrorType for FakeDevice {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for FakeDevice {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+            for op in &mut *operations {
+                if let Operation::Read(words) | Operation::Transfer(words, _) = op {
+                    words.fill(0x5A);
+                }
+            }
+            match operations {
+                [Operation::Write([0xFF])] => Err(ErrorKind::Overrun),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn logs_each_operation() {
+        let mut spi = Logged::new(FakeDevice);
+        let log = records(|| {
+            let mut read = [0; 2];
+            let mut transfer = [0; 1];
+            spi.transaction(&mut [
+                Operation::Write(&[0x03, 0x00]),
+                Operation::DelayNs(100),
+                Operation::Read(&mut read),
+                Operation::Transfer(&mut transfer, &[0x42]),
+            ])
+            .unwrap();
+        });
+        assert_eq!(
+            log,
+            [
+                "spi write 2 bytes [03 00] -> ok",
+                "spi delay 100 ns -> ok",
+                "spi read 2 bytes [5a 5a] -> ok",
+                "spi transfer write 1 bytes [42] read 1 bytes [5a] -> ok",
+            ]
+        );
+    }
+
+    #[test]
+    fn logs_errors_and_truncates() {
+        let mut spi = Logged::new(FakeDevice).with_max_dump(0);
+        let log = records(|| {
+            SpiDevice::write(&mut spi, &[0xFF]).unwrap_err();
+        });
+        assert_eq!(log, ["spi write 1 bytes [...] -> error Overrun"]);
+    }
+}
@@ -1,10 +1,39 @@
 //! `SpiDevice` implementations.
+//!
+//! All the devices drive CS low to select the device, and high to release it. For devices with an
+//! active-high chip select, wrap the pin in an [`ActiveHighCs`].
 
+use core::convert::Infallible;
 use core::fmt::{self, Debug, Display, Formatter};
 use embedded_hal::spi::{Error, ErrorKind};
 
+mod bitbang;
+pub use bitbang::*;
+mod builder;
+pub use builder::*;
+mod daisy_chain;
+pub use daisy_chain::*;
+mod device;
+pub use device::*;
+#[cfg(feature = "embassy-sync")]
+mod embassy_mutex;
+#[cfg(feature = "embassy-sync")]
+pub use embassy_mutex::*;
 mod exclusive;
 pub use exclusive::*;
+mod half_duplex;
+pub use half_duplex::*;
+#[cfg(any(feature = "log", feature = "defmt-03"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "log", feature = "defmt-03"))))]
+mod logged;
+#[cfg(any(feature = "log", feature = "defmt-03"))]
+pub use logged::*;
+mod nor_flash;
+pub use nor_flash::*;
+mod trace;
+pub use trace::*;
+mod transfer16;
+pub use transfer16::*;
 mod refcell;
 pub use refcell::*;
 #[cfg(feature = "std")]
@@ -29,40 +58,217 @@ pub use self::critical_section::*;
 use crate::defmt;
 
 /// Error type for [`ExclusiveDevice`] operations.
+///
+/// The `CFG` parameter is the error type of [`SetConfig::set_config`], for the devices applying a
+/// per-device configuration to the bus, such as [`ExclusiveDeviceWithConfig`]. It is [`Infallible`]
+/// for the other devices.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
-pub enum DeviceError<BUS, CS> {
+pub enum DeviceError<BUS, CS, CFG = Infallible> {
     /// An inner SPI bus operation failed.
     Spi(BUS),
     /// Asserting or deasserting CS failed.
     Cs(CS),
+    /// Applying the device configuration to the bus failed.
+    Config(CFG),
+    /// An inner SPI bus operation failed, then deasserting CS failed too.
+    ///
+    /// The device may still be selected: it is likely to misinterpret the next transactions on the bus.
+    Composite {
+        /// Error of the failed bus operation.
+        spi: BUS,
+        /// Error of the CS pin, when deasserting it after the failure.
+        cs: CS,
+    },
 }
 
-impl<BUS: Display, CS: Display> Display for DeviceError<BUS, CS> {
+impl<BUS: Display, CS: Display, CFG: Display> Display for DeviceError<BUS, CS, CFG> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             Self::Spi(bus) => write!(f, "SPI bus error: {bus}"),
             Self::Cs(cs) => write!(f, "SPI CS error: {cs}"),
+            Self::Config(cfg) => write!(f, "SPI bus configuration error: {cfg}"),
+            Self::Composite { spi, cs } => {
+                write!(f, "SPI bus error: {spi}, then SPI CS error: {cs}")
+            }
         }
     }
 }
 
-impl<BUS: Debug + Display, CS: Debug + Display> core::error::Error for DeviceError<BUS, CS> {}
+impl<BUS, CS, CFG> core::error::Error for DeviceError<BUS, CS, CFG>
+where
+    BUS: Debug + Display,
+    CS: Debug + Display,
+    CFG: Debug + Display,
+{
+}
 
-impl<BUS, CS> Error for DeviceError<BUS, CS>
+impl<BUS, CS, CFG> Error for DeviceError<BUS, CS, CFG>
 where
     BUS: Error + Debug,
     CS: Debug,
+    CFG: Debug,
 {
     #[inline]
     fn kind(&self) -> ErrorKind {
         match self {
             Self::Spi(e) => e.kind(),
             Self::Cs(_) => ErrorKind::ChipSelectFault,
+            Self::Config(_) => ErrorKind::Other,
+            Self::Composite { spi, .. } => spi.kind(),
         }
     }
 }
 
+/// Chip-select timings of an SPI device.
+///
+/// Some devices need more time between CS assertion and the first clock edge, or between the last
+/// clock edge and CS deassertion, than the bus naturally provides. The devices of this module wait
+/// for these delays with their [`DelayNs`](embedded_hal::delay::DelayNs) implementation, including when
+/// releasing CS after a failed operation. They are all zero by default, and zero delays are skipped.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::spi::{CsTiming, ExclusiveDevice};
+/// # use core::convert::Infallible;
+/// # use embedded_hal::delay::DelayNs;
+/// # use embedded_hal::digital::{self, OutputPin};
+/// # use embedded_hal::spi::{self, ErrorKind, SpiBus};
+/// # struct Spi0;
+/// # impl spi::ErrorType for Spi0 { type Error = ErrorKind; }
+/// # impl SpiBus for Spi0 {
+/// #     fn read(&mut self, _: &mut [u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn write(&mut self, _: &[u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn flush(&mut self) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # struct Pin;
+/// # impl digital::ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # struct Delay;
+/// # impl DelayNs for Delay { fn delay_ns(&mut self, _: u32) {} }
+/// # let (spi, cs, delay) = (Spi0, Pin, Delay);
+///
+/// // The MAX31855 needs 100 ns from CS falling to the first clock edge.
+/// let device = ExclusiveDevice::new(spi, cs, delay)?.with_cs_timing(CsTiming {
+///     setup_ns: 100,
+///     ..CsTiming::default()
+/// });
+/// # Ok::<(), Infallible>(())
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct CsTiming {
+    /// Delay between asserting CS and the first operation, in nanoseconds.
+    pub setup_ns: u32,
+    /// Delay between the final flush and deasserting CS, in nanoseconds.
+    pub hold_ns: u32,
+    /// Delay after deasserting CS, before the end of the transaction, in nanoseconds.
+    ///
+    /// This guarantees a minimum CS high time between two transactions of the device.
+    pub idle_ns: u32,
+}
+
+/// Configuration of an SPI bus, such as its clock frequency and mode.
+///
+/// HALs implement this on their [`SpiBus`](embedded_hal::spi::SpiBus) types, so that devices with
+/// different requirements can share a bus: the `*WithConfig` devices, such as
+/// [`RefCellDeviceWithConfig`], apply their own configuration to the bus at the start of every
+/// transaction, before asserting CS.
+/// They implement [`SpiDeviceWithConfig`](embedded_hal::spi::SpiDeviceWithConfig), returning this
+/// configuration.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::{Mode, MODE_0};
+/// use embedded_hal_bus::spi::SetConfig;
+///
+/// #[derive(Clone)]
+/// pub struct Config {
+///     pub frequency_hz: u32,
+///     pub mode: Mode,
+/// }
+///
+/// pub struct Spi0 {
+///     // ...
+/// #   config: Config,
+/// }
+///
+/// impl SetConfig for Spi0 {
+///     type Config = Config;
+///     type ConfigError = core::convert::Infallible;
+///
+///     fn set_config(&mut self, config: &Config) -> Result<(), Self::ConfigError> {
+///         // Write the clock divider and mode to the peripheral registers ...
+/// #       self.config = config.clone();
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait SetConfig {
+    /// Configuration of the bus.
+    type Config;
+    /// Error returned when the configuration cannot be applied, e.g. if the frequency is not supported.
+    type ConfigError: Debug;
+
+    /// Apply `config` to the bus.
+    fn set_config(&mut self, config: &Self::Config) -> Result<(), Self::ConfigError>;
+}
+
+impl<T: SetConfig + ?Sized> SetConfig for &mut T {
+    type Config = T::Config;
+    type ConfigError = T::ConfigError;
+
+    #[inline]
+    fn set_config(&mut self, config: &Self::Config) -> Result<(), Self::ConfigError> {
+        T::set_config(self, config)
+    }
+}
+
+/// Chip-select pin adapter for devices selected by driving CS high.
+///
+/// The [`SpiDevice`](embedded_hal::spi::SpiDevice) implementations of this module, blocking and async,
+/// assume an active-low CS. Wrapping the pin in an `ActiveHighCs` inverts the levels, including when
+/// releasing CS after a failed transaction and when initializing the pin in the constructors.
+///
+/// This is an alias of [`InvertedPin`](crate::gpio::InvertedPin).
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::spi::{ActiveHighCs, ExclusiveDevice};
+/// # use core::convert::Infallible;
+/// # use embedded_hal::digital::{self, OutputPin};
+/// # use embedded_hal::spi::{self, ErrorKind, SpiBus};
+/// # struct Spi0;
+/// # impl spi::ErrorType for Spi0 { type Error = ErrorKind; }
+/// # impl SpiBus for Spi0 {
+/// #     fn read(&mut self, _: &mut [u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn write(&mut self, _: &[u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), ErrorKind> { Ok(()) }
+/// #     fn flush(&mut self) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # struct Pin;
+/// # impl digital::ErrorType for Pin { type Error = Infallible; }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # let (spi, latch) = (Spi0, Pin);
+///
+/// // Drives the latch pin low until the first transaction.
+/// let device = ExclusiveDevice::new_no_delay(spi, ActiveHighCs::new(latch))?;
+/// # Ok::<(), Infallible>(())
+/// ```
+pub type ActiveHighCs<P> = crate::gpio::InvertedPin<P>;
+
 /// Dummy [`DelayNs`](embedded_hal::delay::DelayNs) implementation that panics on use.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
@@ -88,3 +294,1644 @@ impl embedded_hal_async::delay::DelayNs for NoDelay {
         no_delay_panic();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use embedded_hal::digital::{self, OutputPin};
+    use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiBusHalfDuplex, SpiDevice};
+    use std::{rc::Rc, vec::Vec};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Event {
+        CsLow,
+        CsHigh,
+        Write(u8),
+        Read(usize),
+        Flush,
+        Config(u32),
+        Delay(u32),
+    }
+
+    type Log = Rc<RefCell<Vec<Event>>>;
+
+    struct FakeBus {
+        log: Log,
+        fail_write: bool,
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = ErrorKind;
+    }
+
+    impl SpiBus for FakeBus {
+        fn read(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            let mut log = self.log.borrow_mut();
+            log.extend(words.iter().map(|w| Event::Write(*w)));
+            if self.fail_write {
+                Err(ErrorKind::Overrun)
+            } else {
+                Ok(())
+            }
+        }
+
+        fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, write)
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, words)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::Flush);
+            Ok(())
+        }
+    }
+
+    /// Bus configured with a clock frequency, rejecting 0 Hz.
+    impl SetConfig for FakeBus {
+        type Config = u32;
+        type ConfigError = ErrorKind;
+
+        fn set_config(&mut self, frequency_hz: &u32) -> Result<(), ErrorKind> {
+            if *frequency_hz == 0 {
+                return Err(ErrorKind::Other);
+            }
+            self.log.borrow_mut().push(Event::Config(*frequency_hz));
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::spi::SpiBus for FakeBus {
+        async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::read(self, words)
+        }
+
+        async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            // Suspend the transaction once, like a DMA transfer would.
+            let mut yielded = false;
+            core::future::poll_fn(|cx| {
+                if yielded {
+                    core::task::Poll::Ready(())
+                } else {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    core::task::Poll::Pending
+                }
+            })
+            .await;
+            SpiBus::write(self, words)
+        }
+
+        async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::transfer(self, read, write)
+        }
+
+        async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::transfer_in_place(self, words)
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            SpiBus::flush(self)
+        }
+    }
+
+    impl SpiBusHalfDuplex for FakeBus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::Read(words.len()));
+            Ok(())
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, words)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            SpiBus::flush(self)
+        }
+    }
+
+    struct FakePin {
+        log: Log,
+        fail: bool,
+    }
+
+    impl digital::ErrorType for FakePin {
+        type Error = digital::ErrorKind;
+    }
+
+    impl OutputPin for FakePin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::CsLow);
+            if self.fail {
+                Err(digital::ErrorKind::Other)
+            } else {
+                Ok(())
+            }
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::CsHigh);
+            Ok(())
+        }
+    }
+
+    fn fixture(fail_write: bool) -> (Log, FakeBus, FakePin) {
+        let log = Log::default();
+        let bus = FakeBus {
+            log: log.clone(),
+            fail_write,
+        };
+        let cs = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        (log, bus, cs)
+    }
+
+    #[test]
+    fn devices_with_config_apply_it_before_each_transaction() {
+        let (log, bus, cs) = fixture(false);
+        let cs2 = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = RefCell::new(bus);
+        let mut flash = RefCellDeviceWithConfig::new_no_delay(&bus, cs, 20_000_000).unwrap();
+        let mut adc = RefCellDeviceWithConfig::new_no_delay(&bus, cs2, 2_000_000).unwrap();
+        log.borrow_mut().clear();
+
+        flash.write(&[0x9F]).unwrap();
+        adc.write(&[0x01]).unwrap();
+        *flash.config_mut() = 10_000_000;
+        flash.write(&[0x05]).unwrap();
+
+        assert_eq!(
+            log.borrow().as_slice(),
+            [
+                Event::Config(20_000_000),
+                Event::CsLow,
+                Event::Write(0x9F),
+                Event::Flush,
+                Event::CsHigh,
+                Event::Config(2_000_000),
+                Event::CsLow,
+                Event::Write(0x01),
+                Event::Flush,
+                Event::CsHigh,
+                Event::Config(10_000_000),
+                Event::CsLow,
+                Event::Write(0x05),
+                Event::Flush,
+                Event::CsHigh,
+            ]
+        );
+    }
+
+    #[test]
+    fn config_errors_abort_the_transaction() {
+        let (log, bus, cs) = fixture(false);
+        let bus = ::critical_section::Mutex::new(RefCell::new(bus));
+        let mut device = CriticalSectionDeviceWithConfig::new_no_delay(&bus, cs, 0).unwrap();
+        log.borrow_mut().clear();
+
+        let err = device.write(&[0x01]).unwrap_err();
+        assert_eq!(err, DeviceError::Config(ErrorKind::Other));
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn exclusive_device_with_config() {
+        let (log, bus, cs) = fixture(false);
+        let mut device = ExclusiveDeviceWithConfig::new_no_delay(bus, cs, 1_000_000).unwrap();
+        log.borrow_mut().clear();
+
+        device.write(&[0x42]).unwrap();
+        assert_eq!(
+            log.borrow().as_slice(),
+            [
+                Event::Config(1_000_000),
+                Event::CsLow,
+                Event::Write(0x42),
+                Event::Flush,
+                Event::CsHigh,
+            ]
+        );
+    }
+
+    #[test]
+    fn devices_with_config_report_their_configuration() {
+        use embedded_hal::spi::SpiDeviceWithConfig;
+
+        fn clock_hz(device: &impl SpiDeviceWithConfig<u32>) -> u32 {
+            *device.configuration()
+        }
+
+        let (_log, bus, cs) = fixture(false);
+        let mut device = ExclusiveDeviceWithConfig::new_no_delay(bus, cs, 1_000_000).unwrap();
+        assert_eq!(clock_hz(&device), 1_000_000);
+        *device.config_mut() = 4_000_000;
+        assert_eq!(clock_hz(&&mut device), 4_000_000);
+
+        let (_log, bus, cs) = fixture(false);
+        let bus = RefCell::new(bus);
+        let device = RefCellDeviceWithConfig::new_no_delay(&bus, cs, 8_000_000).unwrap();
+        assert_eq!(clock_hz(&device), 8_000_000);
+    }
+
+    /// CS level changes recorded in `log`.
+    fn cs_levels(log: &Log) -> Vec<Event> {
+        log.borrow()
+            .iter()
+            .copied()
+            .filter(|e| matches!(e, Event::CsLow | Event::CsHigh))
+            .collect()
+    }
+
+    #[test]
+    fn cs_polarity_around_successful_and_failed_transactions() {
+        use Event::{CsHigh, CsLow};
+
+        let (log, bus, cs) = fixture(false);
+        let bus = RefCell::new(bus);
+        let mut device = RefCellDevice::new_no_delay(&bus, cs).unwrap();
+        device.write(&[0x01]).unwrap();
+        bus.borrow_mut().fail_write = true;
+        device.write(&[0x02]).unwrap_err();
+        assert_eq!(cs_levels(&log), [CsHigh, CsLow, CsHigh, CsLow, CsHigh]);
+
+        let (log, bus, cs) = fixture(false);
+        let bus = RefCell::new(bus);
+        let mut device = RefCellDevice::new_no_delay(&bus, ActiveHighCs::new(cs)).unwrap();
+        device.write(&[0x01]).unwrap();
+        bus.borrow_mut().fail_write = true;
+        device.write(&[0x02]).unwrap_err();
+        assert_eq!(cs_levels(&log), [CsLow, CsHigh, CsLow, CsHigh, CsLow]);
+
+        let (log, bus, cs) = fixture(false);
+        let mut device = ExclusiveDevice::new_no_delay(bus, ActiveHighCs::new(cs)).unwrap();
+        device.write(&[0x01]).unwrap();
+        device.bus_mut().fail_write = true;
+        device.write(&[0x02]).unwrap_err();
+        assert_eq!(cs_levels(&log), [CsLow, CsHigh, CsLow, CsHigh, CsLow]);
+    }
+
+    #[test]
+    fn critical_section_device_flushes_before_releasing_cs() {
+        let (log, bus, cs) = fixture(false);
+        let bus = ::critical_section::Mutex::new(RefCell::new(bus));
+        let mut device = CriticalSectionDevice::new_no_delay(&bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        device.write(&[1, 2]).unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(1),
+                Event::Write(2),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    fn critical_section_device_releases_cs_on_error() {
+        let (log, bus, cs) = fixture(true);
+        let bus = ::critical_section::Mutex::new(RefCell::new(bus));
+        let mut device = CriticalSectionDevice::new_no_delay(&bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        let result = device.transaction(&mut [Operation::Write(&[1]), Operation::Write(&[2])]);
+        assert_eq!(result, Err(DeviceError::Spi(ErrorKind::Overrun)));
+        assert_eq!(
+            *log.borrow(),
+            [Event::CsLow, Event::Write(1), Event::Flush, Event::CsHigh]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn critical_section_device_async_completes_without_yielding() {
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::Poll;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let bus = ::critical_section::Mutex::new(RefCell::new(bus));
+        let mut device = CriticalSectionDevice::new_no_delay(&bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        // The bus yields once during writes, but the transaction is polled to completion
+        // while holding the critical section.
+        let waker = crate::util::noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+        let mut future = pin!(AsyncSpiDevice::write(&mut device, &[1, 2]));
+        assert!(matches!(future.as_mut().poll(&mut cx), Poll::Ready(Ok(()))));
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(1),
+                Event::Write(2),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    /// Calls `nested` from every write.
+    struct ReentrantBus<'a> {
+        nested: Option<&'a dyn Fn()>,
+    }
+
+    impl ErrorType for ReentrantBus<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl SpiBus for ReentrantBus<'_> {
+        fn read(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn write(&mut self, _words: &[u8]) -> Result<(), Self::Error> {
+            if let Some(nested) = self.nested {
+                nested();
+            }
+            Ok(())
+        }
+
+        fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, write)
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, words)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn try_refcell_device_nested_transaction_is_busy() {
+        let log = Log::default();
+        let pin = || FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = RefCell::new(ReentrantBus { nested: None });
+        let result = core::cell::Cell::new(None);
+        let nested = || {
+            let mut device = TryRefCellDevice::new_no_delay(&bus, pin()).unwrap();
+            result.set(Some(SpiDevice::write(&mut device, &[2])));
+        };
+        bus.borrow_mut().nested = Some(&nested);
+
+        let mut device = TryRefCellDevice::new_no_delay(&bus, pin()).unwrap();
+        SpiDevice::write(&mut device, &[1]).unwrap();
+        assert_eq!(result.take(), Some(Err(RefCellDeviceError::Busy)));
+        // The nested device never asserted its CS.
+        assert_eq!(
+            log.borrow()[..],
+            [Event::CsHigh, Event::CsLow, Event::CsHigh, Event::CsHigh]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn refcell_device_async_transactions() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = RefCell::new(bus);
+        let mut a = RefCellDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = RefCellDevice::new_no_delay(&bus, cs_b).unwrap();
+        log.borrow_mut().clear();
+
+        poll_to_completion(AsyncSpiDevice::write(&mut a, &[1])).unwrap();
+        poll_to_completion(AsyncSpiDevice::write(&mut b, &[2])).unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(1),
+                Event::Flush,
+                Event::CsHigh,
+                Event::CsLow,
+                Event::Write(2),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn try_refcell_device_async_busy_bus() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(true);
+        let bus = RefCell::new(bus);
+        let mut device = TryRefCellDevice::new_no_delay(&bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        // Another transaction holds the bus, e.g. from a task suspended at an `.await` point.
+        let borrow = bus.borrow_mut();
+        assert_eq!(
+            poll_to_completion(AsyncSpiDevice::write(&mut device, &[1])),
+            Err(RefCellDeviceError::Busy)
+        );
+        assert!(log.borrow().is_empty());
+        drop(borrow);
+
+        // CS is released after an error of the bus.
+        assert_eq!(
+            poll_to_completion(AsyncSpiDevice::write(&mut device, &[2])),
+            Err(RefCellDeviceError::Bus(DeviceError::Spi(
+                ErrorKind::Overrun
+            )))
+        );
+        assert_eq!(
+            *log.borrow(),
+            [Event::CsLow, Event::Write(2), Event::Flush, Event::CsHigh]
+        );
+    }
+
+    /// CS pin expecting to be set high by the device constructor, then driven around `transactions`
+    /// transactions.
+    fn mock_cs(transactions: usize) -> embedded_hal_mock::digital::MockPin {
+        use embedded_hal::digital::PinState;
+        use embedded_hal_mock::digital::Transaction;
+
+        let around = [
+            Transaction::Set(PinState::Low),
+            Transaction::Set(PinState::High),
+        ];
+        embedded_hal_mock::digital::MockPin::new(
+            core::iter::once(Transaction::Set(PinState::High))
+                .chain(around.into_iter().cycle().take(2 * transactions)),
+        )
+    }
+
+    #[test]
+    fn exclusive_device_on_mock_bus() {
+        use embedded_hal_mock::spi::{MockSpi, Transaction};
+
+        let bus = MockSpi::new([
+            Transaction::Write(std::vec![0x03, 0x00]),
+            Transaction::Read(std::vec![0xAB, 0xCD]),
+            Transaction::Flush,
+            Transaction::Write(std::vec![0x06]),
+            Transaction::Error(ErrorKind::Overrun),
+            Transaction::Flush,
+        ]);
+        let cs = mock_cs(2);
+        let mut device = ExclusiveDevice::new_no_delay(bus.clone(), cs.clone()).unwrap();
+
+        let mut buf = [0; 2];
+        device
+            .transaction(&mut [Operation::Write(&[0x03, 0x00]), Operation::Read(&mut buf)])
+            .unwrap();
+        assert_eq!(buf, [0xAB, 0xCD]);
+        assert_eq!(
+            device.write(&[0x06]),
+            Err(DeviceError::Spi(ErrorKind::Overrun))
+        );
+        bus.assert_complete();
+        cs.assert_complete();
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn refcell_device_async_on_mock_bus() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+        use embedded_hal_mock::spi::{MockSpi, Transaction};
+
+        let mock = MockSpi::new([
+            Transaction::Write(std::vec![1]),
+            Transaction::Flush,
+            Transaction::Transfer {
+                write: std::vec![2],
+                response: std::vec![3],
+            },
+            Transaction::Flush,
+        ]);
+        let (cs_a, cs_b) = (mock_cs(1), mock_cs(1));
+        let bus = RefCell::new(mock.clone());
+        let mut a = RefCellDevice::new_no_delay(&bus, cs_a.clone()).unwrap();
+        let mut b = RefCellDevice::new_no_delay(&bus, cs_b.clone()).unwrap();
+
+        poll_to_completion(AsyncSpiDevice::write(&mut a, &[1])).unwrap();
+        let mut buf = [0];
+        poll_to_completion(AsyncSpiDevice::transfer(&mut b, &mut buf, &[2])).unwrap();
+        assert_eq!(buf, [3]);
+        mock.assert_complete();
+        cs_a.assert_complete();
+        cs_b.assert_complete();
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    #[should_panic]
+    fn refcell_device_async_overlapping_transactions_panic() {
+        use core::future::Future;
+        use core::pin::pin;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin { log, fail: false };
+        let bus = RefCell::new(bus);
+        let mut a = RefCellDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = RefCellDevice::new_no_delay(&bus, cs_b).unwrap();
+
+        let waker = crate::util::noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+        let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+        let mut fb = pin!(AsyncSpiDevice::write(&mut b, &[2]));
+        assert!(fa.as_mut().poll(&mut cx).is_pending());
+        let _ = fb.as_mut().poll(&mut cx);
+    }
+
+    /// Waker recording that it was woken up.
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    struct Flag(core::sync::atomic::AtomicBool);
+
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    impl Flag {
+        fn new() -> (std::sync::Arc<Self>, core::task::Waker) {
+            let flag = std::sync::Arc::new(Self(core::sync::atomic::AtomicBool::new(false)));
+            (flag.clone(), flag.into())
+        }
+
+        /// Returns whether the waker was woken up since the previous call.
+        fn take(&self) -> bool {
+            self.0.swap(false, core::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    impl std::task::Wake for Flag {
+        fn wake(self: std::sync::Arc<Self>) {
+            self.0.store(true, core::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn atomic_device_async_waits_for_bus() {
+        use crate::util::AtomicCell;
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll};
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = AtomicCell::new(bus);
+        let mut a = AtomicDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = AtomicDevice::new_no_delay(&bus, cs_b).unwrap();
+        log.borrow_mut().clear();
+
+        let waker_a = crate::util::noop_waker();
+        let (flag, waker_b) = Flag::new();
+        let mut cx_a = Context::from_waker(&waker_a);
+        let mut cx_b = Context::from_waker(&waker_b);
+        let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+        let mut fb = pin!(AsyncSpiDevice::write(&mut b, &[2]));
+
+        // `a` holds the bus: `b` waits instead of failing.
+        assert!(fa.as_mut().poll(&mut cx_a).is_pending());
+        assert!(fb.as_mut().poll(&mut cx_b).is_pending());
+        assert!(!flag.take());
+
+        // Releasing the bus wakes `b` up.
+        assert!(matches!(fa.as_mut().poll(&mut cx_a), Poll::Ready(Ok(()))));
+        assert!(flag.take());
+        assert!(fb.as_mut().poll(&mut cx_b).is_pending());
+        assert!(matches!(fb.as_mut().poll(&mut cx_b), Poll::Ready(Ok(()))));
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(1),
+                Event::Flush,
+                Event::CsHigh,
+                Event::CsLow,
+                Event::Write(2),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn atomic_device_async_waiters_do_not_wake_each_other() {
+        use crate::util::AtomicCell;
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll};
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let pin = || FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = AtomicCell::new(bus);
+        let mut a = AtomicDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = AtomicDevice::new_no_delay(&bus, pin()).unwrap();
+        let mut c = AtomicDevice::new_no_delay(&bus, pin()).unwrap();
+        log.borrow_mut().clear();
+
+        let waker_a = crate::util::noop_waker();
+        let (flag_b, waker_b) = Flag::new();
+        let (flag_c, waker_c) = Flag::new();
+        let mut cx_a = Context::from_waker(&waker_a);
+        let mut cx_b = Context::from_waker(&waker_b);
+        let mut cx_c = Context::from_waker(&waker_c);
+        let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+        let mut fb = pin!(AsyncSpiDevice::write(&mut b, &[2]));
+        let mut fc = pin!(AsyncSpiDevice::write(&mut c, &[3]));
+
+        // `a` holds the bus: `b` and `c` wait, and polling one does not wake the other up.
+        assert!(fa.as_mut().poll(&mut cx_a).is_pending());
+        for _ in 0..3 {
+            assert!(fb.as_mut().poll(&mut cx_b).is_pending());
+            assert!(fc.as_mut().poll(&mut cx_c).is_pending());
+        }
+        assert!(!flag_b.take() && !flag_c.take());
+
+        // Releasing the bus wakes both up: `b` gets it, `c` waits again.
+        assert!(matches!(fa.as_mut().poll(&mut cx_a), Poll::Ready(Ok(()))));
+        assert!(flag_b.take() && flag_c.take());
+        assert!(fb.as_mut().poll(&mut cx_b).is_pending());
+        assert!(fc.as_mut().poll(&mut cx_c).is_pending());
+        assert!(!flag_c.take());
+
+        assert!(matches!(fb.as_mut().poll(&mut cx_b), Poll::Ready(Ok(()))));
+        assert!(flag_c.take());
+        assert!(fc.as_mut().poll(&mut cx_c).is_pending());
+        assert!(matches!(fc.as_mut().poll(&mut cx_c), Poll::Ready(Ok(()))));
+        assert_eq!(
+            log.borrow()
+                .iter()
+                .filter(|e| matches!(e, Event::Write(_)))
+                .collect::<Vec<_>>(),
+            [&Event::Write(1), &Event::Write(2), &Event::Write(3)]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn atomic_device_async_more_waiters_than_slots() {
+        use crate::util::{poll_to_completion, AtomicCell};
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::Context;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let bus = AtomicCell::new(bus);
+        let mut a = AtomicDevice::new_no_delay(&bus, cs).unwrap();
+        let mut devices: Vec<_> = (0..6)
+            .map(|_| {
+                let cs = FakePin {
+                    log: log.clone(),
+                    fail: false,
+                };
+                AtomicDevice::new_no_delay(&bus, cs).unwrap()
+            })
+            .collect();
+
+        let waker = crate::util::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+        assert!(fa.as_mut().poll(&mut cx).is_pending());
+        let mut waiting: Vec<_> = devices
+            .iter_mut()
+            .map(|device| std::boxed::Box::pin(AsyncSpiDevice::write(device, &[2])))
+            .collect();
+        // The waiters beyond the slots are woken up right away.
+        let (flag, overflow) = Flag::new();
+        for future in &mut waiting {
+            assert!(future.as_mut().poll(&mut cx).is_pending());
+        }
+        assert!(waiting
+            .last_mut()
+            .unwrap()
+            .as_mut()
+            .poll(&mut Context::from_waker(&overflow))
+            .is_pending());
+        assert!(flag.take());
+
+        // Once the bus is released, everyone is served.
+        poll_to_completion(fa).unwrap();
+        for future in waiting {
+            poll_to_completion(future).unwrap();
+        }
+        // The slots were freed.
+        poll_to_completion(AsyncSpiDevice::write(&mut devices[0], &[3])).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "embassy-sync")]
+    fn async_mutex_device_waits_for_bus() {
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll};
+        use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+        use embassy_sync::mutex::Mutex;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = Mutex::<NoopRawMutex, _>::new(bus);
+        let mut a = AsyncMutexDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = AsyncMutexDevice::new_no_delay(&bus, cs_b).unwrap();
+        log.borrow_mut().clear();
+
+        let waker = crate::util::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+        let mut fb = pin!(AsyncSpiDevice::write(&mut b, &[2]));
+
+        // `a` holds the bus across its `.await` point: `b` waits without asserting its CS.
+        assert!(fa.as_mut().poll(&mut cx).is_pending());
+        assert!(fb.as_mut().poll(&mut cx).is_pending());
+        assert!(matches!(fa.as_mut().poll(&mut cx), Poll::Ready(Ok(()))));
+        assert!(fb.as_mut().poll(&mut cx).is_pending());
+        assert!(matches!(fb.as_mut().poll(&mut cx), Poll::Ready(Ok(()))));
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(1),
+                Event::Flush,
+                Event::CsHigh,
+                Event::CsLow,
+                Event::Write(2),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn atomic_device_sync_busy_during_async_transaction() {
+        use crate::util::AtomicCell;
+        use core::future::Future;
+        use core::pin::pin;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin { log, fail: false };
+        let bus = AtomicCell::new(bus);
+        let mut a = AtomicDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = AtomicDevice::new_no_delay(&bus, cs_b).unwrap();
+
+        let waker = crate::util::noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+        {
+            let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+            assert!(fa.as_mut().poll(&mut cx).is_pending());
+            assert!(matches!(
+                SpiDevice::write(&mut b, &[2]),
+                Err(AtomicError::Busy)
+            ));
+        }
+
+        // Dropping the pending transaction released the bus.
+        assert!(SpiDevice::write(&mut b, &[2]).is_ok());
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn atomic_device_async_cancelled_transaction_deasserts_cs() {
+        use crate::util::{poll_to_completion, AtomicCell};
+        use core::future::Future;
+        use core::pin::pin;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = AtomicCell::new(bus);
+        let mut a = AtomicDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = AtomicDevice::new_no_delay(&bus, cs_b).unwrap();
+        log.borrow_mut().clear();
+
+        let waker = crate::util::noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+        {
+            // Suspended in the middle of the write, then dropped.
+            let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+            assert!(fa.as_mut().poll(&mut cx).is_pending());
+        }
+        assert_eq!(*log.borrow(), [Event::CsLow, Event::CsHigh]);
+
+        // The bus can be claimed again, by both sync and async transactions.
+        SpiDevice::write(&mut b, &[2]).unwrap();
+        poll_to_completion(AsyncSpiDevice::write(&mut a, &[3])).unwrap();
+        assert_eq!(
+            log.borrow()[2..],
+            [
+                Event::CsLow,
+                Event::Write(2),
+                Event::Flush,
+                Event::CsHigh,
+                Event::CsLow,
+                Event::Write(3),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", target_has_atomic = "8"))]
+    fn try_atomic_device_async_busy_and_cancelled() {
+        use crate::util::{poll_to_completion, AtomicCell};
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll};
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = AtomicCell::new(bus);
+        let mut a = TryAtomicDevice::new_no_delay(&bus, cs).unwrap();
+        let mut b = TryAtomicDevice::new_no_delay(&bus, cs_b).unwrap();
+        log.borrow_mut().clear();
+
+        let waker = crate::util::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        {
+            // Suspended in the middle of the write: `b` is busy, without asserting its CS.
+            let mut fa = pin!(AsyncSpiDevice::write(&mut a, &[1]));
+            assert!(fa.as_mut().poll(&mut cx).is_pending());
+            let busy = pin!(AsyncSpiDevice::write(&mut b, &[2])).poll(&mut cx);
+            assert!(matches!(busy, Poll::Ready(Err(AtomicError::Busy))));
+            assert!(matches!(
+                SpiDevice::write(&mut b, &[2]),
+                Err(AtomicError::Busy)
+            ));
+            // Then cancelled.
+        }
+        assert_eq!(*log.borrow(), [Event::CsLow, Event::CsHigh]);
+
+        // The bus can be claimed again.
+        poll_to_completion(AsyncSpiDevice::write(&mut b, &[3])).unwrap();
+        assert_eq!(
+            log.borrow()[2..],
+            [Event::CsLow, Event::Write(3), Event::Flush, Event::CsHigh]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn mutex_device_reports_cs_errors() {
+        let (log, bus, _) = fixture(false);
+        let cs = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = std::sync::Mutex::new(bus);
+        let mut device = MutexDevice::new_no_delay(&bus, cs).unwrap();
+        device.cs.fail = true;
+        log.borrow_mut().clear();
+
+        let result = device.write(&[1]);
+        assert_eq!(result, Err(DeviceError::Cs(digital::ErrorKind::Other)));
+        // CS could not be asserted, so the bus must not be touched.
+        assert_eq!(*log.borrow(), [Event::CsLow]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[should_panic(expected = "PoisonError")]
+    fn mutex_device_panics_on_poisoned_mutex() {
+        let (_, bus, cs) = fixture(false);
+        let bus = std::sync::Mutex::new(bus);
+        let mut device = MutexDevice::new_no_delay(&bus, cs).unwrap();
+
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = bus.lock().unwrap();
+            panic!("poisoning the mutex");
+        });
+        let _ = device.write(&[1]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn try_mutex_device_reports_poisoned_and_busy_mutex() {
+        let (log, bus, cs) = fixture(false);
+        let cs_b = FakePin {
+            log: log.clone(),
+            fail: false,
+        };
+        let bus = std::sync::Mutex::new(bus);
+        let mut waiting = TryMutexDevice::new_no_delay(&bus, cs).unwrap();
+        let mut trying = TryMutexDevice::new_try_lock_no_delay(&bus, cs_b).unwrap();
+        log.borrow_mut().clear();
+
+        let guard = bus.lock().unwrap();
+        assert_eq!(trying.write(&[1]), Err(MutexDeviceError::Busy));
+        drop(guard);
+        trying.write(&[2]).unwrap();
+
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = bus.lock().unwrap();
+            panic!("poisoning the mutex");
+        });
+        assert_eq!(waiting.write(&[3]), Err(MutexDeviceError::Poisoned));
+        assert_eq!(trying.write(&[4]), Err(MutexDeviceError::Poisoned));
+        // Neither the bus nor CS were touched by the failed transactions.
+        assert_eq!(
+            *log.borrow(),
+            [Event::CsLow, Event::Write(2), Event::Flush, Event::CsHigh]
+        );
+
+        bus.clear_poison();
+        waiting.write(&[5]).unwrap();
+        assert_eq!(
+            log.borrow()[4..],
+            [Event::CsLow, Event::Write(5), Event::Flush, Event::CsHigh]
+        );
+    }
+
+    #[test]
+    fn half_duplex_device_transaction() {
+        let (log, bus, cs) = fixture(false);
+        let mut device = HalfDuplexDevice::new_no_delay(bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        let mut read = [0; 2];
+        device
+            .transaction(&mut [Operation::Write(&[0x9F]), Operation::Read(&mut read)])
+            .unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(0x9F),
+                Event::Read(2),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    fn half_duplex_device_releases_cs_on_error() {
+        let (log, bus, cs) = fixture(true);
+        let mut device = HalfDuplexDevice::new_no_delay(bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        let result = device.write(&[1]);
+        assert_eq!(result, Err(DeviceError::Spi(ErrorKind::Overrun)));
+        assert_eq!(
+            *log.borrow(),
+            [Event::CsLow, Event::Write(1), Event::Flush, Event::CsHigh]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "half-duplex")]
+    fn half_duplex_device_rejects_transfers() {
+        let (_log, bus, cs) = fixture(false);
+        let mut device = HalfDuplexDevice::new_no_delay(bus, cs).unwrap();
+        let _ = device.transfer_in_place(&mut [0]);
+    }
+
+    /// Bus of any word size, recording the written words and answering reads with `fill`.
+    struct WordBus<W> {
+        written: Vec<W>,
+        fill: W,
+    }
+
+    impl<W> ErrorType for WordBus<W> {
+        type Error = ErrorKind;
+    }
+
+    impl<W: Copy + 'static> SpiBus<W> for WordBus<W> {
+        fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            words.fill(self.fill);
+            Ok(())
+        }
+
+        fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+            self.written.extend_from_slice(words);
+            Ok(())
+        }
+
+        fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+            self.written.extend_from_slice(write);
+            read.fill(self.fill);
+            Ok(())
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            self.written.extend_from_slice(words);
+            words.fill(self.fill);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl<W: Copy + 'static> embedded_hal_async::spi::SpiBus<W> for WordBus<W> {
+        async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            SpiBus::read(self, words)
+        }
+
+        async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+            SpiBus::write(self, words)
+        }
+
+        async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+            SpiBus::transfer(self, read, write)
+        }
+
+        async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+            SpiBus::transfer_in_place(self, words)
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            SpiBus::flush(self)
+        }
+    }
+
+    /// Delay recording the requested delays.
+    #[derive(Default)]
+    struct RecordingDelay(Vec<u32>);
+
+    impl embedded_hal::delay::DelayNs for RecordingDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.push(ns);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::delay::DelayNs for RecordingDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0.push(ns);
+        }
+    }
+
+    /// Run every kind of operation with words of type `W` through an [`ExclusiveDevice`].
+    fn exclusive_device_words<W>(words: [W; 4], fill: W)
+    where
+        W: Copy + Default + PartialEq + Debug + 'static,
+    {
+        let (_, _, cs) = fixture(false);
+        let bus = WordBus {
+            written: Vec::new(),
+            fill,
+        };
+        let mut device = ExclusiveDevice::new(bus, cs, RecordingDelay::default()).unwrap();
+
+        let mut read = [W::default(); 2];
+        let mut transfer = [W::default(); 1];
+        let mut in_place = [words[3]];
+        device
+            .transaction(&mut [
+                Operation::Write(&words[..2]),
+                Operation::DelayNs(1_000),
+                Operation::Read(&mut read),
+                Operation::Transfer(&mut transfer, &words[2..3]),
+                Operation::TransferInPlace(&mut in_place),
+            ])
+            .unwrap();
+        assert_eq!(device.bus().written, words);
+        assert_eq!((read, transfer, in_place), ([fill; 2], [fill], [fill]));
+
+        SpiDevice::write(&mut device, &words[..1]).unwrap();
+        let mut read = [W::default(); 3];
+        SpiDevice::read(&mut device, &mut read).unwrap();
+        assert_eq!(read, [fill; 3]);
+        assert_eq!(device.bus().written.len(), 5);
+    }
+
+    #[test]
+    fn exclusive_device_u16_words() {
+        exclusive_device_words::<u16>([0x1234, 0x5678, 0x9ABC, 0xDEF0], 0xA55A);
+    }
+
+    #[test]
+    fn exclusive_device_u32_words() {
+        exclusive_device_words::<u32>([1, 0x1_0000, 0xFFFF_FFFF, 0x8000_0000], 0xDEAD_BEEF);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn exclusive_device_async_u16_words() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (_, _, cs) = fixture(false);
+        let bus = WordBus {
+            written: Vec::new(),
+            fill: 0xBEEFu16,
+        };
+        let mut device = ExclusiveDevice::new(bus, cs, RecordingDelay::default()).unwrap();
+        let mut read = [0u16; 2];
+        poll_to_completion(AsyncSpiDevice::transaction(
+            &mut device,
+            &mut [
+                Operation::Write(&[0x0102, 0x0304]),
+                Operation::DelayNs(500),
+                Operation::Read(&mut read),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(device.bus().written, [0x0102, 0x0304]);
+        assert_eq!(read, [0xBEEF; 2]);
+    }
+
+    /// Bus failing its writes, and its next `failing_flushes` flushes.
+    struct ScriptedBus {
+        log: Log,
+        fail_write: bool,
+        failing_flushes: usize,
+    }
+
+    impl ErrorType for ScriptedBus {
+        type Error = ErrorKind;
+    }
+
+    impl SpiBus for ScriptedBus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::Read(words.len()));
+            Ok(())
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.log
+                .borrow_mut()
+                .extend(words.iter().map(|w| Event::Write(*w)));
+            if self.fail_write {
+                Err(ErrorKind::Overrun)
+            } else {
+                Ok(())
+            }
+        }
+
+        fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, write)
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, words)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::Flush);
+            if self.failing_flushes > 0 {
+                self.failing_flushes -= 1;
+                Err(ErrorKind::ModeFault)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::spi::SpiBus for ScriptedBus {
+        async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::read(self, words)
+        }
+
+        async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, words)
+        }
+
+        async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::transfer(self, read, write)
+        }
+
+        async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::transfer_in_place(self, words)
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            SpiBus::flush(self)
+        }
+    }
+
+    impl SpiBusHalfDuplex for ScriptedBus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            SpiBus::read(self, words)
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            SpiBus::write(self, words)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            SpiBus::flush(self)
+        }
+    }
+
+    /// CS pin failing to deassert after the first time if `fail_deassert` is set.
+    struct ScriptedCs {
+        log: Log,
+        fail_deassert: bool,
+        deasserted: bool,
+    }
+
+    impl digital::ErrorType for ScriptedCs {
+        type Error = digital::ErrorKind;
+    }
+
+    impl OutputPin for ScriptedCs {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::CsLow);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::CsHigh);
+            if core::mem::replace(&mut self.deasserted, true) && self.fail_deassert {
+                Err(digital::ErrorKind::Other)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn scripted(
+        fail_write: bool,
+        failing_flushes: usize,
+        fail_cs: bool,
+    ) -> (Log, ScriptedBus, ScriptedCs) {
+        let log = Log::default();
+        let bus = ScriptedBus {
+            log: log.clone(),
+            fail_write,
+            failing_flushes,
+        };
+        let cs = ScriptedCs {
+            log: log.clone(),
+            fail_deassert: fail_cs,
+            deasserted: false,
+        };
+        (log, bus, cs)
+    }
+
+    #[test]
+    fn failed_last_operation_and_cs_are_both_reported() {
+        let (log, bus, cs) = scripted(true, 0, true);
+        let mut device = ExclusiveDevice::new_no_delay(bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        let err = device.write(&[0x01]).unwrap_err();
+        assert_eq!(
+            err,
+            DeviceError::Composite {
+                spi: ErrorKind::Overrun,
+                cs: digital::ErrorKind::Other
+            }
+        );
+        assert_eq!(err.kind(), ErrorKind::Overrun);
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(0x01),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    fn operation_error_takes_priority_over_flush_error() {
+        let (log, bus, cs) = scripted(true, 1, false);
+        let bus = RefCell::new(bus);
+        let mut device = RefCellDevice::new_no_delay(&bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        assert_eq!(
+            device.write(&[0x01]),
+            Err(DeviceError::Spi(ErrorKind::Overrun))
+        );
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(0x01),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_delay_flush_still_flushes_and_deasserts_cs() {
+        let (log, bus, cs) = scripted(false, 1, false);
+        let mut device = ExclusiveDevice::new(bus, cs, RecordingDelay::default()).unwrap();
+        log.borrow_mut().clear();
+
+        let err = device
+            .transaction(&mut [
+                Operation::Write(&[0x01]),
+                Operation::DelayNs(1_000),
+                Operation::Read(&mut [0; 2]),
+            ])
+            .unwrap_err();
+        assert_eq!(err, DeviceError::Spi(ErrorKind::ModeFault));
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(0x01),
+                Event::Flush,
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_final_flush_and_cs_are_both_reported() {
+        let (log, bus, cs) = scripted(false, 1, true);
+        let mut device = HalfDuplexDevice::new_no_delay(bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        assert_eq!(
+            device.read(&mut [0; 1]),
+            Err(DeviceError::Composite {
+                spi: ErrorKind::ModeFault,
+                cs: digital::ErrorKind::Other
+            })
+        );
+        assert_eq!(
+            *log.borrow(),
+            [Event::CsLow, Event::Read(1), Event::Flush, Event::CsHigh]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_failed_operation_and_cs_are_both_reported() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = scripted(false, 1, true);
+        let mut device = ExclusiveDevice::new(bus, cs, RecordingDelay::default()).unwrap();
+        log.borrow_mut().clear();
+
+        let err = poll_to_completion(AsyncSpiDevice::transaction(
+            &mut device,
+            &mut [Operation::DelayNs(1_000), Operation::Write(&[0x01])],
+        ))
+        .unwrap_err();
+        assert_eq!(
+            err,
+            DeviceError::Composite {
+                spi: ErrorKind::ModeFault,
+                cs: digital::ErrorKind::Other
+            }
+        );
+        assert_eq!(
+            *log.borrow(),
+            [Event::CsLow, Event::Flush, Event::Flush, Event::CsHigh]
+        );
+    }
+
+    /// Delay logging its delays along with the bus and CS events.
+    struct LoggedDelay(Log);
+
+    impl embedded_hal::delay::DelayNs for LoggedDelay {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0.borrow_mut().push(Event::Delay(ns));
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl embedded_hal_async::delay::DelayNs for LoggedDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0.borrow_mut().push(Event::Delay(ns));
+        }
+    }
+
+    const TIMING: CsTiming = CsTiming {
+        setup_ns: 10,
+        hold_ns: 20,
+        idle_ns: 30,
+    };
+
+    #[test]
+    fn exclusive_device_cs_timing() {
+        let (log, bus, cs) = fixture(false);
+        let mut device = ExclusiveDevice::new(bus, cs, LoggedDelay(log.clone()))
+            .unwrap()
+            .with_cs_timing(TIMING);
+        log.borrow_mut().clear();
+
+        device
+            .transaction(&mut [Operation::Write(&[0x01]), Operation::DelayNs(5)])
+            .unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Delay(10),
+                Event::Write(0x01),
+                Event::Flush,
+                Event::Delay(5),
+                Event::Flush,
+                Event::Delay(20),
+                Event::CsHigh,
+                Event::Delay(30)
+            ]
+        );
+    }
+
+    #[test]
+    fn refcell_device_cs_timing_on_error() {
+        let (log, bus, cs) = fixture(true);
+        let bus = RefCell::new(bus);
+        let mut device = RefCellDevice::new(&bus, cs, LoggedDelay(log.clone()))
+            .unwrap()
+            .with_cs_timing(CsTiming {
+                hold_ns: 20,
+                ..CsTiming::default()
+            });
+        log.borrow_mut().clear();
+
+        assert_eq!(
+            device.write(&[0x01]),
+            Err(DeviceError::Spi(ErrorKind::Overrun))
+        );
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(0x01),
+                Event::Flush,
+                Event::Delay(20),
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_cs_timing_does_not_delay() {
+        let (log, bus, cs) = fixture(false);
+        let bus = RefCell::new(bus);
+        let mut device = RefCellDevice::new_no_delay(&bus, cs)
+            .unwrap()
+            .with_cs_timing(CsTiming::default());
+        log.borrow_mut().clear();
+
+        device.write(&[0x01]).unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Write(0x01),
+                Event::Flush,
+                Event::CsHigh
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_exclusive_device_cs_timing() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+        let (log, bus, cs) = fixture(false);
+        let mut device = ExclusiveDevice::new(bus, cs, LoggedDelay(log.clone()))
+            .unwrap()
+            .with_cs_timing(TIMING);
+        log.borrow_mut().clear();
+
+        poll_to_completion(AsyncSpiDevice::write(&mut device, &[0x01])).unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Delay(10),
+                Event::Write(0x01),
+                Event::Flush,
+                Event::Delay(20),
+                Event::CsHigh,
+                Event::Delay(30)
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn exclusive_transaction_keeps_cs_asserted_until_commit() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::{SpiTransaction, SpiTransactionalDevice};
+
+        let (log, bus, cs) = fixture(false);
+        let mut device = ExclusiveDevice::new(bus, cs, LoggedDelay(log.clone()))
+            .unwrap()
+            .with_cs_timing(TIMING);
+        log.borrow_mut().clear();
+
+        poll_to_completion(async {
+            let mut tx = device.begin_transaction().await?;
+            tx.write(&[0x02]).await?;
+            tx.run(&mut [Operation::DelayNs(5), Operation::Write(&[0x03])])
+                .await?;
+            tx.commit().await
+        })
+        .unwrap();
+        assert_eq!(
+            *log.borrow(),
+            [
+                Event::CsLow,
+                Event::Delay(10),
+                Event::Write(0x02),
+                Event::Flush,
+                Event::Delay(5),
+                Event::Write(0x03),
+                Event::Flush,
+                Event::Delay(20),
+                Event::CsHigh,
+                Event::Delay(30)
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn dropped_exclusive_transaction_deasserts_cs_without_flushing() {
+        use crate::util::poll_to_completion;
+        use embedded_hal_async::spi::{SpiTransaction, SpiTransactionalDevice};
+
+        let (log, bus, cs) = fixture(true);
+        let mut device = ExclusiveDevice::new_no_delay(bus, cs).unwrap();
+        log.borrow_mut().clear();
+
+        let err = poll_to_completion(async {
+            let mut tx = device.begin_transaction().await?;
+            tx.write(&[0x02]).await?;
+            tx.commit().await
+        })
+        .unwrap_err();
+        assert_eq!(err, DeviceError::Spi(ErrorKind::Overrun));
+        assert_eq!(
+            *log.borrow(),
+            [Event::CsLow, Event::Write(0x02), Event::CsHigh]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "defmt-03")]
+    fn error_types_implement_defmt_format() {
+        use embedded_hal::{digital, spi::ErrorKind};
+
+        fn assert_format<T: crate::defmt::Format>() {}
+        assert_format::<DeviceError<ErrorKind, digital::ErrorKind>>();
+        assert_format::<AtomicError<ErrorKind>>();
+        assert_format::<RefCellDeviceError<ErrorKind>>();
+        assert_format::<NorFlashError<ErrorKind>>();
+        assert_format::<NorFlashCommandSet>();
+        assert_format::<BitBangError<digital::ErrorKind, digital::ErrorKind, digital::ErrorKind>>();
+    }
+}
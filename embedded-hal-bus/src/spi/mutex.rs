@@ -1,84 +1,180 @@
+use core::fmt::{self, Debug, Display, Formatter};
 use embedded_hal::delay::DelayNs;
 use embedded_hal::digital::OutputPin;
-use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice};
+use embedded_hal::spi::{Error, ErrorKind, ErrorType, Operation, SpiBus, SpiDevice};
 use std::sync::Mutex;
 
-use super::DeviceError;
+use super::{CsTiming, Device, DeviceError, DeviceWithConfig};
 use crate::spi::shared::transaction;
+use crate::util::{lock_mutex, LockError};
 
-/// `std` `Mutex`-based shared bus [`SpiDevice`] implementation.
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// `std` `Mutex`-based shared bus [`SpiDevice`](embedded_hal::spi::SpiDevice) implementation.
 ///
-/// This allows for sharing an [`SpiBus`], obtaining multiple [`SpiDevice`] instances,
-/// each with its own `CS` pin.
+/// This allows for sharing an [`SpiBus`](embedded_hal::spi::SpiBus), obtaining multiple
+/// [`SpiDevice`](embedded_hal::spi::SpiDevice) instances, each with its own `CS` pin.
 ///
 /// Sharing is implemented with a `std` [`Mutex`]. It allows a single bus across multiple threads,
 /// with finer-grained locking than [`CriticalSectionDevice`](super::CriticalSectionDevice). The downside is
 /// it is only available in `std` targets.
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-pub struct MutexDevice<'a, BUS, CS, D> {
+pub type MutexDevice<'a, BUS, CS, D> = Device<&'a Mutex<BUS>, CS, D>;
+
+/// `std` `Mutex`-based shared bus [`SpiDevice`](embedded_hal::spi::SpiDevice) implementation applying a
+/// per-device [`SetConfig`](super::SetConfig) configuration.
+///
+/// This is the same as [`MutexDevice`], except that the device configuration is applied to the bus at the
+/// start of every transaction, see [`DeviceWithConfig`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub type MutexDeviceWithConfig<'a, BUS, CS, D> = DeviceWithConfig<&'a Mutex<BUS>, CS, D>;
+
+/// `std` `Mutex`-based shared bus [`SpiDevice`] implementation, returning an error instead of panicking when
+/// the mutex is poisoned.
+///
+/// This is the same as [`MutexDevice`], except that a transaction started while the mutex is poisoned, because
+/// a thread panicked while using the bus, returns [`MutexDeviceError::Poisoned`] without touching the bus or the
+/// `CS` pin. The application can then bring the bus back to a known state, and call [`Mutex::clear_poison`].
+///
+/// A device created with [`new_try_lock`](TryMutexDevice::new_try_lock) does not wait for the mutex either:
+/// starting a transaction while another thread holds it returns [`MutexDeviceError::Busy`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct TryMutexDevice<'a, BUS, CS, D> {
     bus: &'a Mutex<BUS>,
     cs: CS,
     delay: D,
+    timing: CsTiming,
+    wait: bool,
 }
 
-impl<'a, BUS, CS, D> MutexDevice<'a, BUS, CS, D> {
-    /// Create a new [`MutexDevice`].
+impl<'a, BUS, CS, D> TryMutexDevice<'a, BUS, CS, D> {
+    /// Create a new [`TryMutexDevice`], waiting for the mutex when another thread holds it.
     ///
     /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
     /// to set the pin high the moment it's configured as an output, to avoid glitches.
     #[inline]
-    pub fn new(bus: &'a Mutex<BUS>, mut cs: CS, delay: D) -> Result<Self, CS::Error>
+    pub fn new(bus: &'a Mutex<BUS>, cs: CS, delay: D) -> Result<Self, CS::Error>
     where
         CS: OutputPin,
     {
-        cs.set_high()?;
-        Ok(Self { bus, cs, delay })
+        Self::with_wait(bus, cs, delay, true)
     }
-}
 
-impl<'a, BUS, CS> MutexDevice<'a, BUS, CS, super::NoDelay> {
-    /// Create a new [`MutexDevice`] without support for in-transaction delays.
+    /// Create a new [`TryMutexDevice`], returning [`MutexDeviceError::Busy`] when another thread holds the mutex.
     ///
     /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
     /// to set the pin high the moment it's configured as an output, to avoid glitches.
+    #[inline]
+    pub fn new_try_lock(bus: &'a Mutex<BUS>, cs: CS, delay: D) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        Self::with_wait(bus, cs, delay, false)
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    fn with_wait(bus: &'a Mutex<BUS>, mut cs: CS, delay: D, wait: bool) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        cs.set_high()?;
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            timing: CsTiming::default(),
+            wait,
+        })
+    }
+}
+
+impl<'a, BUS, CS> TryMutexDevice<'a, BUS, CS, super::NoDelay> {
+    /// Create a new [`TryMutexDevice`] without support for in-transaction delays, waiting for the mutex when
+    /// another thread holds it.
     ///
-    /// **Warning**: The returned instance *technically* doesn't comply with the `SpiDevice`
-    /// contract, which mandates delay support. It is relatively rare for drivers to use
-    /// in-transaction delays, so you might still want to use this method because it's more practical.
+    /// See [`Device::new_no_delay`] for the caveats of not supporting delays.
     ///
-    /// Note that a future version of the driver might start using delays, causing your
-    /// code to panic. This wouldn't be considered a breaking change from the driver side, because
-    /// drivers are allowed to assume `SpiDevice` implementations comply with the contract.
-    /// If you feel this risk outweighs the convenience of having `cargo` automatically upgrade
-    /// the driver crate, you might want to pin the driver's version.
+    /// # Panics
+    ///
+    /// The returned device will panic if you try to execute a transaction
+    /// that contains any operations of type [`Operation::DelayNs`].
+    #[inline]
+    pub fn new_no_delay(bus: &'a Mutex<BUS>, cs: CS) -> Result<Self, CS::Error>
+    where
+        CS: OutputPin,
+    {
+        Self::with_wait(bus, cs, super::NoDelay, true)
+    }
+
+    /// Create a new [`TryMutexDevice`] without support for in-transaction delays, returning
+    /// [`MutexDeviceError::Busy`] when another thread holds the mutex.
+    ///
+    /// See [`Device::new_no_delay`] for the caveats of not supporting delays.
     ///
     /// # Panics
     ///
     /// The returned device will panic if you try to execute a transaction
     /// that contains any operations of type [`Operation::DelayNs`].
     #[inline]
-    pub fn new_no_delay(bus: &'a Mutex<BUS>, mut cs: CS) -> Result<Self, CS::Error>
+    pub fn new_try_lock_no_delay(bus: &'a Mutex<BUS>, cs: CS) -> Result<Self, CS::Error>
     where
         CS: OutputPin,
     {
-        cs.set_high()?;
-        Ok(Self {
-            bus,
-            cs,
-            delay: super::NoDelay,
-        })
+        Self::with_wait(bus, cs, super::NoDelay, false)
+    }
+}
+
+/// Error type for [`TryMutexDevice`] operations.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum MutexDeviceError<T> {
+    /// A thread panicked while holding the mutex: the bus may be in an inconsistent state.
+    Poisoned,
+    /// The mutex was held by another thread when the transaction was started.
+    Busy,
+    /// The transaction failed.
+    Bus(T),
+}
+
+impl<T: Display> Display for MutexDeviceError<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Poisoned => write!(f, "SPI bus mutex poisoned"),
+            Self::Busy => write!(f, "SPI bus already in use"),
+            Self::Bus(e) => write!(f, "{e}"),
+        }
     }
 }
 
-impl<BUS, CS, D> ErrorType for MutexDevice<'_, BUS, CS, D>
+impl<T: Debug + Display> core::error::Error for MutexDeviceError<T> {}
+
+impl<T: Error> Error for MutexDeviceError<T> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Poisoned | Self::Busy => ErrorKind::Other,
+            Self::Bus(e) => e.kind(),
+        }
+    }
+}
+
+impl<BUS, CS, D> ErrorType for TryMutexDevice<'_, BUS, CS, D>
 where
     BUS: ErrorType,
     CS: OutputPin,
 {
-    type Error = DeviceError<BUS::Error, CS::Error>;
+    type Error = MutexDeviceError<DeviceError<BUS::Error, CS::Error>>;
 }
 
-impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for MutexDevice<'_, BUS, CS, D>
+impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for TryMutexDevice<'_, BUS, CS, D>
 where
     BUS: SpiBus<Word>,
     CS: OutputPin,
@@ -86,8 +182,13 @@ where
 {
     #[inline]
     fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.lock().unwrap();
-
-        transaction(operations, bus, &mut self.delay, &mut self.cs)
+        let result = lock_mutex(self.bus, self.wait, |bus| {
+            transaction(operations, bus, &mut self.delay, &mut self.cs, self.timing)
+        });
+        match result {
+            Ok(result) => result.map_err(MutexDeviceError::Bus),
+            Err(LockError::Poisoned) => Err(MutexDeviceError::Poisoned),
+            Err(LockError::WouldBlock) => Err(MutexDeviceError::Busy),
+        }
     }
 }
@@ -0,0 +1,663 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::delay::Counter;
+use embedded_hal::spi::{Error, ErrorKind, Operation, SpiDevice};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+const CMD_READ_JEDEC_ID: u8 = 0x9F;
+
+/// Write-in-progress bit of the status register.
+const STATUS_BUSY: u8 = 0x01;
+
+/// JEDEC identification of a flash device, as returned by the `READ ID` (0x9F) command.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct JedecId {
+    /// JEDEC manufacturer ID, for instance 0xEF for Winbond.
+    pub manufacturer: u8,
+    /// Manufacturer-specific memory type.
+    pub memory_type: u8,
+    /// Capacity code, the base-2 logarithm of the size in bytes for most devices.
+    pub capacity: u8,
+}
+
+impl JedecId {
+    /// Size of the device in bytes, decoded from the capacity code.
+    ///
+    /// Returns `None` if the code does not fit a `u32`, which is not a valid power-of-two capacity.
+    #[inline]
+    pub fn capacity_bytes(&self) -> Option<u32> {
+        1u32.checked_shl(self.capacity.into())
+    }
+}
+
+/// Commands and address width of a SPI NOR flash device.
+///
+/// The [default](NorFlashCommandSet::JEDEC) is the JEDEC command set, shared by most serial NOR flash
+/// devices. Devices deviating from it, such as OEM variants or devices with 4-byte addresses, are
+/// supported by changing the fields:
+///
+/// ```
+/// use embedded_hal_bus::spi::NorFlashCommandSet;
+///
+/// // 4-byte address commands of a 32 MiB Winbond W25Q256.
+/// const W25Q256: NorFlashCommandSet = NorFlashCommandSet {
+///     read_cmd: 0x13,
+///     page_program_cmd: 0x12,
+///     sector_erase_cmd: 0x21,
+///     block_erase_cmd: 0xDC,
+///     addr_bytes: 4,
+///     ..NorFlashCommandSet::JEDEC
+/// };
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct NorFlashCommandSet {
+    /// Read data, followed by the address.
+    pub read_cmd: u8,
+    /// Program a page, followed by the address.
+    pub page_program_cmd: u8,
+    /// Erase the 4 KiB sector containing the address that follows.
+    pub sector_erase_cmd: u8,
+    /// Erase the 64 KiB block containing the address that follows.
+    pub block_erase_cmd: u8,
+    /// Erase the whole device.
+    pub chip_erase_cmd: u8,
+    /// Read the status register, whose bit 0 is set while an operation is in progress.
+    pub read_status_cmd: u8,
+    /// Enable the next program or erase operation.
+    pub write_enable_cmd: u8,
+    /// Number of address bytes sent after the commands, from 2 to 4.
+    pub addr_bytes: u8,
+}
+
+impl NorFlashCommandSet {
+    /// JEDEC command set, with 3-byte addresses: read (0x03), page program (0x02), 4 KiB sector erase
+    /// (0x20), 64 KiB block erase (0xD8), chip erase (0xC7), read status (0x05) and write enable (0x06).
+    pub const JEDEC: Self = Self {
+        read_cmd: 0x03,
+        page_program_cmd: 0x02,
+        sector_erase_cmd: 0x20,
+        block_erase_cmd: 0xD8,
+        chip_erase_cmd: 0xC7,
+        read_status_cmd: 0x05,
+        write_enable_cmd: 0x06,
+        addr_bytes: 3,
+    };
+}
+
+impl Default for NorFlashCommandSet {
+    #[inline]
+    fn default() -> Self {
+        Self::JEDEC
+    }
+}
+
+/// SPI NOR flash driver, for the JEDEC command set or a custom [`NorFlashCommandSet`].
+///
+/// The JEDEC command set covers most serial NOR flash devices, such as the Winbond W25Q, GigaDevice
+/// GD25 and Micron MT25Q families. Its addresses are sent as 3 bytes, so only the first 16 MiB of a
+/// device can be accessed, unless a command set with 4-byte addresses is used.
+///
+/// [`program_page`](SpiNorFlash::program_page) and the erase methods only start the operation: call
+/// [`wait_ready`](SpiNorFlash::wait_ready) before issuing the next one. The [`Counter`] is used to
+/// time out `wait_ready`.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::spi::SpiNorFlash;
+/// # use embedded_hal::delay::Counter;
+/// # use embedded_hal::spi::{self, ErrorKind, Operation, SpiDevice};
+/// # struct Flash;
+/// # impl spi::ErrorType for Flash { type Error = ErrorKind; }
+/// # impl SpiDevice for Flash {
+/// #     fn transaction(&mut self, ops: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+/// #         if let [Operation::Write([0x9F]), Operation::Read(id)] = ops {
+/// #             id.copy_from_slice(&[0xEF, 0x40, 0x16]);
+/// #         }
+/// #         Ok(())
+/// #     }
+/// # }
+/// # struct Timer;
+/// # impl Counter for Timer {
+/// #     fn start(&mut self) {}
+/// #     fn elapsed_ns(&self) -> u64 { 0 }
+/// # }
+/// # let (spi, timer) = (Flash, Timer);
+///
+/// let mut flash = SpiNorFlash::new(spi, timer)?;
+/// assert_eq!(flash.jedec_id().capacity_bytes(), Some(4 * 1024 * 1024));
+///
+/// flash.erase_sector(0x1000)?;
+/// // A sector erase takes up to 400 ms on a W25Q32.
+/// flash.wait_ready(400_000_000)?;
+/// flash.program_page(0x1000, b"hello, world")?;
+/// flash.wait_ready(3_000_000)?;
+///
+/// let mut buf = [0; 12];
+/// flash.read(0x1000, &mut buf)?;
+/// # Ok::<(), embedded_hal_bus::spi::NorFlashError<ErrorKind>>(())
+/// ```
+pub struct SpiNorFlash<T, C> {
+    spi: T,
+    counter: C,
+    id: JedecId,
+    commands: NorFlashCommandSet,
+}
+
+impl<T, C> SpiNorFlash<T, C>
+where
+    T: SpiDevice,
+    C: Counter,
+{
+    /// Size of a program page, in bytes.
+    pub const PAGE_SIZE: u32 = 256;
+    /// Size of an erase sector, in bytes.
+    pub const SECTOR_SIZE: u32 = 4096;
+    /// Size of an erase block, in bytes.
+    pub const BLOCK_SIZE: u32 = 64 * 1024;
+
+    /// Create a new [`SpiNorFlash`] for the JEDEC command set, reading the JEDEC ID of the device.
+    ///
+    /// Returns [`NorFlashError::NoDevice`] if the ID reads as all zeros or all ones, which means
+    /// that no device is answering.
+    #[inline]
+    pub fn new(spi: T, counter: C) -> Result<Self, NorFlashError<T::Error>> {
+        Self::with_command_set(spi, counter, NorFlashCommandSet::JEDEC)
+    }
+
+    /// Create a new [`SpiNorFlash`] using `commands`, reading the JEDEC ID of the device.
+    ///
+    /// The JEDEC ID is read with the standard `READ ID` (0x9F) command. Returns
+    /// [`NorFlashError::NoDevice`] if the ID reads as all zeros or all ones, which means that no device
+    /// is answering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`addr_bytes`](NorFlashCommandSet::addr_bytes) of `commands` is not 2, 3 or 4.
+    pub fn with_command_set(
+        mut spi: T,
+        counter: C,
+        commands: NorFlashCommandSet,
+    ) -> Result<Self, NorFlashError<T::Error>> {
+        assert!(
+            matches!(commands.addr_bytes, 2..=4),
+            "flash addresses must be 2 to 4 bytes"
+        );
+        let mut id = [0; 3];
+        spi.transaction(&mut [
+            Operation::Write(&[CMD_READ_JEDEC_ID]),
+            Operation::Read(&mut id),
+        ])
+        .map_err(NorFlashError::Spi)?;
+        if id == [0x00; 3] || id == [0xFF; 3] {
+            return Err(NorFlashError::NoDevice);
+        }
+        let [manufacturer, memory_type, capacity] = id;
+        Ok(Self {
+            spi,
+            counter,
+            id: JedecId {
+                manufacturer,
+                memory_type,
+                capacity,
+            },
+            commands,
+        })
+    }
+
+    /// Command set used to access the device.
+    #[inline]
+    pub fn command_set(&self) -> NorFlashCommandSet {
+        self.commands
+    }
+
+    /// JEDEC ID read from the device at construction.
+    #[inline]
+    pub fn jedec_id(&self) -> JedecId {
+        self.id
+    }
+
+    /// Release the SPI device and the counter.
+    #[inline]
+    pub fn release(self) -> (T, C) {
+        (self.spi, self.counter)
+    }
+
+    /// Read `buf.len()` bytes starting at `addr`.
+    ///
+    /// Reads are not limited to a page or sector, and wrap around at the end of the device.
+    #[inline]
+    pub fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), NorFlashError<T::Error>> {
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&self.command(self.commands.read_cmd, addr)),
+                Operation::Read(buf),
+            ])
+            .map_err(NorFlashError::Spi)
+    }
+
+    /// Program `data` at `addr`, which must have been erased beforehand.
+    ///
+    /// The data must not cross a page boundary, or [`NorFlashError::PageBoundary`] is returned
+    /// without writing anything. Call [`wait_ready`](SpiNorFlash::wait_ready) afterwards.
+    pub fn program_page(&mut self, addr: u32, data: &[u8]) -> Result<(), NorFlashError<T::Error>> {
+        let room = Self::PAGE_SIZE - addr % Self::PAGE_SIZE;
+        if data.len() > room as usize {
+            return Err(NorFlashError::PageBoundary);
+        }
+        self.write_enable()?;
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&self.command(self.commands.page_program_cmd, addr)),
+                Operation::Write(data),
+            ])
+            .map_err(NorFlashError::Spi)
+    }
+
+    /// Erase the 4 KiB sector containing `addr`, setting all its bytes to 0xFF.
+    ///
+    /// Call [`wait_ready`](SpiNorFlash::wait_ready) afterwards.
+    pub fn erase_sector(&mut self, addr: u32) -> Result<(), NorFlashError<T::Error>> {
+        self.erase(self.commands.sector_erase_cmd, addr)
+    }
+
+    /// Erase the 64 KiB block containing `addr`, setting all its bytes to 0xFF.
+    ///
+    /// Call [`wait_ready`](SpiNorFlash::wait_ready) afterwards.
+    pub fn erase_block(&mut self, addr: u32) -> Result<(), NorFlashError<T::Error>> {
+        self.erase(self.commands.block_erase_cmd, addr)
+    }
+
+    /// Erase the whole device, setting all its bytes to 0xFF.
+    ///
+    /// This takes up to several minutes on large devices. Call [`wait_ready`](SpiNorFlash::wait_ready)
+    /// afterwards.
+    pub fn erase_chip(&mut self) -> Result<(), NorFlashError<T::Error>> {
+        self.write_enable()?;
+        self.spi
+            .write(&[self.commands.chip_erase_cmd])
+            .map_err(NorFlashError::Spi)
+    }
+
+    /// Poll the status register until the current program or erase operation completes.
+    ///
+    /// Returns [`NorFlashError::Timeout`] if the device is still busy after `timeout_ns`.
+    pub fn wait_ready(&mut self, timeout_ns: u64) -> Result<(), NorFlashError<T::Error>> {
+        self.counter.start();
+        loop {
+            let mut status = [0];
+            self.spi
+                .transaction(&mut [
+                    Operation::Write(&[self.commands.read_status_cmd]),
+                    Operation::Read(&mut status),
+                ])
+                .map_err(NorFlashError::Spi)?;
+            if status[0] & STATUS_BUSY == 0 {
+                return Ok(());
+            }
+            if self.counter.has_exceeded(timeout_ns) {
+                return Err(NorFlashError::Timeout);
+            }
+        }
+    }
+
+    fn erase(&mut self, cmd: u8, addr: u32) -> Result<(), NorFlashError<T::Error>> {
+        self.write_enable()?;
+        self.spi
+            .write(&self.command(cmd, addr))
+            .map_err(NorFlashError::Spi)
+    }
+
+    fn write_enable(&mut self) -> Result<(), NorFlashError<T::Error>> {
+        self.spi
+            .write(&[self.commands.write_enable_cmd])
+            .map_err(NorFlashError::Spi)
+    }
+}
+
+impl<T, C> SpiNorFlash<T, C> {
+    /// Encode `cmd` followed by the address `addr`, most significant byte first.
+    fn command(&self, cmd: u8, addr: u32) -> CommandBytes {
+        CommandBytes::new(cmd, addr, self.commands.addr_bytes)
+    }
+}
+
+/// Command byte followed by an address of 2 to 4 bytes.
+struct CommandBytes {
+    bytes: [u8; 5],
+    len: usize,
+}
+
+impl CommandBytes {
+    fn new(cmd: u8, addr: u32, addr_bytes: u8) -> Self {
+        let mut bytes = [cmd, 0, 0, 0, 0];
+        let len = 1 + usize::from(addr_bytes);
+        bytes[1..len].copy_from_slice(&addr.to_be_bytes()[4 - usize::from(addr_bytes)..]);
+        Self { bytes, len }
+    }
+}
+
+impl core::ops::Deref for CommandBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Error type for [`SpiNorFlash`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum NorFlashError<E> {
+    /// An inner SPI device operation failed.
+    Spi(E),
+    /// The JEDEC ID read as all zeros or all ones: no device is answering.
+    NoDevice,
+    /// The device was still busy when the timeout expired.
+    Timeout,
+    /// The data to program crosses a page boundary.
+    PageBoundary,
+}
+
+impl<E: Display> Display for NorFlashError<E> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Spi(e) => write!(f, "SPI device error: {e}"),
+            Self::NoDevice => write!(f, "no flash device is answering"),
+            Self::Timeout => write!(f, "flash device still busy after the timeout"),
+            Self::PageBoundary => write!(f, "data to program crosses a page boundary"),
+        }
+    }
+}
+
+impl<E: Debug + Display> core::error::Error for NorFlashError<E> {}
+
+impl<E: Error> Error for NorFlashError<E> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Spi(e) => e.kind(),
+            Self::NoDevice | Self::Timeout | Self::PageBoundary => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::spi::ErrorType;
+    use std::{vec, vec::Vec};
+
+    const SIZE: usize = 64 * 1024;
+
+    /// Fake 64 KiB flash, busy for `busy_polls` status reads after each program or erase.
+    struct FakeFlash {
+        id: [u8; 3],
+        commands: NorFlashCommandSet,
+        memory: Vec<u8>,
+        write_enabled: bool,
+        busy_polls: usize,
+        busy: usize,
+    }
+
+    impl FakeFlash {
+        fn new(busy_polls: usize) -> Self {
+            Self {
+                id: [0xEF, 0x40, 0x10],
+                commands: NorFlashCommandSet::JEDEC,
+                memory: vec![0xFF; SIZE],
+                write_enabled: false,
+                busy_polls,
+                busy: 0,
+            }
+        }
+    }
+
+    impl ErrorType for FakeFlash {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for FakeFlash {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+            let c = self.commands;
+            // Split the command from its address, of which only the 2 lowest bytes are used.
+            let addressed = |cmd: &[u8]| match cmd {
+                [cmd, addr @ ..] if addr.len() == usize::from(c.addr_bytes) => {
+                    let [.., a1, a0] = addr else { unreachable!() };
+                    Some((*cmd, usize::from(*a1) << 8 | usize::from(*a0)))
+                }
+                _ => None,
+            };
+            match operations {
+                [Operation::Write([CMD_READ_JEDEC_ID]), Operation::Read(id)] => {
+                    id.copy_from_slice(&self.id)
+                }
+                [Operation::Write([cmd]), Operation::Read(status)] if *cmd == c.read_status_cmd => {
+                    status[0] = u8::from(self.busy > 0);
+                    self.busy = self.busy.saturating_sub(1);
+                }
+                [Operation::Write([cmd])] if *cmd == c.write_enable_cmd => {
+                    self.write_enabled = true
+                }
+                [Operation::Write([cmd])] if *cmd == c.chip_erase_cmd => {
+                    assert!(self.write_enabled && self.busy == 0);
+                    self.memory.fill(0xFF);
+                    (self.write_enabled, self.busy) = (false, self.busy_polls);
+                }
+                [Operation::Write(cmd), Operation::Read(buf)] => match addressed(cmd) {
+                    Some((cmd, start)) if cmd == c.read_cmd => {
+                        buf.copy_from_slice(&self.memory[start..start + buf.len()]);
+                    }
+                    _ => return Err(ErrorKind::FrameFormat),
+                },
+                [Operation::Write(cmd), Operation::Write(data)] => match addressed(cmd) {
+                    Some((cmd, start)) if cmd == c.page_program_cmd => {
+                        assert!(self.write_enabled && self.busy == 0);
+                        for (byte, new) in self.memory[start..].iter_mut().zip(data.iter()) {
+                            *byte &= new;
+                        }
+                        (self.write_enabled, self.busy) = (false, self.busy_polls);
+                    }
+                    _ => return Err(ErrorKind::FrameFormat),
+                },
+                [Operation::Write(cmd)] => {
+                    let size = match addressed(cmd) {
+                        Some((cmd, _)) if cmd == c.sector_erase_cmd => 0x1000,
+                        Some((cmd, _)) if cmd == c.block_erase_cmd => 0x10000,
+                        _ => return Err(ErrorKind::FrameFormat),
+                    };
+                    assert!(self.write_enabled && self.busy == 0);
+                    let start = addressed(cmd).unwrap().1 & !(size - 1);
+                    self.memory[start..start + size].fill(0xFF);
+                    (self.write_enabled, self.busy) = (false, self.busy_polls);
+                }
+                _ => return Err(ErrorKind::FrameFormat),
+            }
+            Ok(())
+        }
+    }
+
+    /// Counter advancing by 1 µs each time it is read.
+    struct FakeCounter(core::cell::Cell<u64>);
+
+    impl Counter for FakeCounter {
+        fn start(&mut self) {
+            self.0.set(0);
+        }
+
+        fn elapsed_ns(&self) -> u64 {
+            self.0.set(self.0.get() + 1_000);
+            self.0.get()
+        }
+    }
+
+    fn flash(busy_polls: usize) -> SpiNorFlash<FakeFlash, FakeCounter> {
+        SpiNorFlash::new(FakeFlash::new(busy_polls), FakeCounter(Default::default())).unwrap()
+    }
+
+    #[test]
+    fn reads_jedec_id() {
+        let flash = flash(0);
+        assert_eq!(
+            flash.jedec_id(),
+            JedecId {
+                manufacturer: 0xEF,
+                memory_type: 0x40,
+                capacity: 0x10,
+            }
+        );
+        assert_eq!(flash.jedec_id().capacity_bytes(), Some(SIZE as u32));
+    }
+
+    #[test]
+    fn missing_device_is_detected() {
+        for id in [[0x00; 3], [0xFF; 3]] {
+            let spi = FakeFlash {
+                id,
+                ..FakeFlash::new(0)
+            };
+            let counter = FakeCounter(Default::default());
+            assert_eq!(
+                SpiNorFlash::new(spi, counter).err(),
+                Some(NorFlashError::NoDevice)
+            );
+        }
+    }
+
+    #[test]
+    fn erase_program_read() {
+        let mut flash = flash(3);
+        flash.program_page(0x1010, &[0x12, 0x34]).unwrap();
+        flash.wait_ready(10_000).unwrap();
+        let mut buf = [0; 4];
+        flash.read(0x100F, &mut buf).unwrap();
+        assert_eq!(buf, [0xFF, 0x12, 0x34, 0xFF]);
+
+        flash.erase_sector(0x1800).unwrap();
+        flash.wait_ready(10_000).unwrap();
+        flash.read(0x100F, &mut buf).unwrap();
+        assert_eq!(buf, [0xFF; 4]);
+    }
+
+    #[test]
+    fn program_rejects_page_crossing() {
+        let mut flash = flash(0);
+        flash.program_page(0x00F0, &[0; 16]).unwrap();
+        assert_eq!(
+            flash.program_page(0x01F0, &[0; 17]),
+            Err(NorFlashError::PageBoundary)
+        );
+        let (spi, _) = flash.release();
+        assert!(!spi.write_enabled);
+    }
+
+    #[test]
+    fn wait_ready_times_out() {
+        let mut flash = flash(usize::MAX);
+        flash.erase_sector(0).unwrap();
+        assert_eq!(flash.wait_ready(5_000), Err(NorFlashError::Timeout));
+    }
+
+    #[test]
+    fn default_command_set_is_jedec() {
+        let flash = flash(0);
+        assert_eq!(flash.command_set(), NorFlashCommandSet::default());
+        assert_eq!(NorFlashCommandSet::default(), NorFlashCommandSet::JEDEC);
+    }
+
+    #[test]
+    fn block_and_chip_erase() {
+        let mut flash = flash(1);
+        flash.program_page(0x0000, &[0x00]).unwrap();
+        flash.wait_ready(10_000).unwrap();
+        flash.program_page(0x8000, &[0x00]).unwrap();
+        flash.wait_ready(10_000).unwrap();
+
+        flash.erase_block(0x8000).unwrap();
+        flash.wait_ready(10_000).unwrap();
+        let (mut a, mut b) = ([0; 1], [0; 1]);
+        flash.read(0x0000, &mut a).unwrap();
+        flash.read(0x8000, &mut b).unwrap();
+        assert_eq!((a, b), ([0xFF], [0xFF]));
+
+        flash.program_page(0x1234, &[0x00]).unwrap();
+        flash.wait_ready(10_000).unwrap();
+        flash.erase_chip().unwrap();
+        flash.wait_ready(10_000).unwrap();
+        flash.read(0x1234, &mut a).unwrap();
+        assert_eq!(a, [0xFF]);
+    }
+
+    #[test]
+    fn custom_command_set() {
+        // 4-byte address commands of the Winbond W25Q256.
+        let commands = NorFlashCommandSet {
+            read_cmd: 0x13,
+            page_program_cmd: 0x12,
+            sector_erase_cmd: 0x21,
+            block_erase_cmd: 0xDC,
+            addr_bytes: 4,
+            ..NorFlashCommandSet::JEDEC
+        };
+        let spi = FakeFlash {
+            commands,
+            ..FakeFlash::new(2)
+        };
+        let mut flash =
+            SpiNorFlash::with_command_set(spi, FakeCounter(Default::default()), commands).unwrap();
+        assert_eq!(flash.command_set(), commands);
+
+        flash.program_page(0x2000, &[0x5A]).unwrap();
+        flash.wait_ready(10_000).unwrap();
+        let mut buf = [0; 2];
+        flash.read(0x2000, &mut buf).unwrap();
+        assert_eq!(buf, [0x5A, 0xFF]);
+        flash.erase_sector(0x2FFF).unwrap();
+        flash.wait_ready(10_000).unwrap();
+        flash.read(0x2000, &mut buf).unwrap();
+        assert_eq!(buf, [0xFF; 2]);
+
+        // The JEDEC commands are not understood by this device.
+        let (spi, counter) = flash.release();
+        let mut flash = SpiNorFlash::new(spi, counter).unwrap();
+        assert_eq!(
+            flash.read(0x2000, &mut buf),
+            Err(NorFlashError::Spi(ErrorKind::FrameFormat))
+        );
+    }
+
+    #[test]
+    fn command_encodes_address_width() {
+        assert_eq!(
+            &*CommandBytes::new(0x03, 0x0012_3456, 3),
+            [0x03, 0x12, 0x34, 0x56]
+        );
+        assert_eq!(
+            &*CommandBytes::new(0x13, 0x0112_3456, 4),
+            [0x13, 0x01, 0x12, 0x34, 0x56]
+        );
+        assert_eq!(
+            &*CommandBytes::new(0x03, 0x0000_3456, 2),
+            [0x03, 0x34, 0x56]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_address_width_panics() {
+        let commands = NorFlashCommandSet {
+            addr_bytes: 5,
+            ..NorFlashCommandSet::JEDEC
+        };
+        let _ = SpiNorFlash::with_command_set(
+            FakeFlash::new(0),
+            FakeCounter(Default::default()),
+            commands,
+        );
+    }
+}
@@ -1,27 +1,117 @@
 use core::cell::RefCell;
+use core::fmt::{self, Debug, Display, Formatter};
 use embedded_hal::delay::DelayNs;
 use embedded_hal::digital::OutputPin;
-use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice};
+use embedded_hal::spi::{Error, ErrorKind, ErrorType, Operation, SpiBus, SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::{
+    delay::DelayNs as AsyncDelayNs,
+    spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice},
+};
 
-use super::DeviceError;
+#[cfg(feature = "async")]
+use super::SetConfig;
+use super::{CsTiming, Device, DeviceError, DeviceWithConfig};
 use crate::spi::shared::transaction;
+#[cfg(feature = "async")]
+use crate::spi::shared::transaction_async;
 
-/// `RefCell`-based shared bus [`SpiDevice`] implementation.
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// `RefCell`-based shared bus [`SpiDevice`](embedded_hal::spi::SpiDevice) implementation.
 ///
-/// This allows for sharing an [`SpiBus`], obtaining multiple [`SpiDevice`] instances,
-/// each with its own `CS` pin.
+/// This allows for sharing an [`SpiBus`](embedded_hal::spi::SpiBus), obtaining multiple
+/// [`SpiDevice`](embedded_hal::spi::SpiDevice) instances, each with its own `CS` pin.
 ///
 /// Sharing is implemented with a `RefCell`. This means it has low overhead, but `RefCellDevice` instances are not `Send`,
 /// so it only allows sharing within a single thread (interrupt priority level). If you need to share a bus across several
 /// threads, use [`CriticalSectionDevice`](super::CriticalSectionDevice) instead.
-pub struct RefCellDevice<'a, BUS, CS, D> {
+///
+/// Starting a transaction while the bus is already borrowed, for example from a driver calling another device
+/// sharing the same bus in the middle of a transaction, panics. Use [`TryRefCellDevice`] to get a
+/// [`RefCellDeviceError::Busy`] error instead.
+///
+/// # Async
+///
+/// With the `async` feature, `RefCellDevice` also implements the async
+/// [`SpiDevice`](embedded_hal_async::spi::SpiDevice) trait if the bus does. The `RefCell` stays borrowed
+/// across `.await` points for the whole transaction, so all devices sharing the bus must be used from
+/// tasks of the same executor, and a transaction on one device must not be started while another
+/// device's transaction is still in progress: this causes a panic. [`TryRefCellDevice`] returns
+/// [`RefCellDeviceError::Busy`] instead, which the task can handle, e.g. by retrying later.
+pub type RefCellDevice<'a, BUS, CS, D> = Device<&'a RefCell<BUS>, CS, D>;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word> for RefCellDevice<'_, BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    #[inline]
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        // Holding the borrow across `.await` points is intentional, see the type's documentation.
+        let bus = &mut *self.bus.borrow_mut();
+
+        transaction_async(operations, bus, &mut self.delay, &mut self.cs, self.timing).await
+    }
+}
+
+/// `RefCell`-based shared bus [`SpiDevice`] implementation applying a per-device [`SetConfig`](super::SetConfig)
+/// configuration.
+///
+/// This is the same as [`RefCellDevice`], except that the device configuration is applied to the bus at the
+/// start of every transaction, see [`DeviceWithConfig`].
+pub type RefCellDeviceWithConfig<'a, BUS, CS, D> = DeviceWithConfig<&'a RefCell<BUS>, CS, D>;
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word>
+    for RefCellDeviceWithConfig<'_, BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word> + SetConfig,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    #[inline]
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        // Holding the borrow across `.await` points is intentional, see the documentation of `RefCellDevice`.
+        let bus = &mut *self.bus.borrow_mut();
+
+        bus.set_config(&self.config).map_err(DeviceError::Config)?;
+        transaction_async(operations, bus, &mut self.delay, &mut self.cs, self.timing).await
+    }
+}
+
+/// `RefCell`-based shared bus [`SpiDevice`] implementation, returning an error instead of panicking when the bus is busy.
+///
+/// This is the same as [`RefCellDevice`], except that starting a transaction while the `RefCell` is already
+/// borrowed returns [`RefCellDeviceError::Busy`], without touching the `CS` pin. Such reentrant accesses are
+/// usually bugs, and this lets an application handle them without a panic.
+///
+/// With the `async` feature, `TryRefCellDevice` also implements the async
+/// [`SpiDevice`](embedded_hal_async::spi::SpiDevice) trait if the bus does. As for [`RefCellDevice`], the
+/// `RefCell` stays borrowed across `.await` points for the whole transaction: a transaction started on
+/// another device sharing the bus meanwhile returns [`RefCellDeviceError::Busy`].
+pub struct TryRefCellDevice<'a, BUS, CS, D> {
     bus: &'a RefCell<BUS>,
     cs: CS,
     delay: D,
+    timing: CsTiming,
 }
 
-impl<'a, BUS, CS, D> RefCellDevice<'a, BUS, CS, D> {
-    /// Create a new [`RefCellDevice`].
+impl<'a, BUS, CS, D> TryRefCellDevice<'a, BUS, CS, D> {
+    /// Create a new [`TryRefCellDevice`].
     ///
     /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
     /// to set the pin high the moment it's configured as an output, to avoid glitches.
@@ -31,25 +121,26 @@ impl<'a, BUS, CS, D> RefCellDevice<'a, BUS, CS, D> {
         CS: OutputPin,
     {
         cs.set_high()?;
-        Ok(Self { bus, cs, delay })
+        Ok(Self {
+            bus,
+            cs,
+            delay,
+            timing: CsTiming::default(),
+        })
+    }
+
+    /// Set the CS timings of the device, see [`CsTiming`].
+    #[inline]
+    pub fn with_cs_timing(mut self, timing: CsTiming) -> Self {
+        self.timing = timing;
+        self
     }
 }
 
-impl<'a, BUS, CS> RefCellDevice<'a, BUS, CS, super::NoDelay> {
-    /// Create a new [`RefCellDevice`] without support for in-transaction delays.
-    ///
-    /// This sets the `cs` pin high, and returns an error if that fails. It is recommended
-    /// to set the pin high the moment it's configured as an output, to avoid glitches.
-    ///
-    /// **Warning**: The returned instance *technically* doesn't comply with the `SpiDevice`
-    /// contract, which mandates delay support. It is relatively rare for drivers to use
-    /// in-transaction delays, so you might still want to use this method because it's more practical.
+impl<'a, BUS, CS> TryRefCellDevice<'a, BUS, CS, super::NoDelay> {
+    /// Create a new [`TryRefCellDevice`] without support for in-transaction delays.
     ///
-    /// Note that a future version of the driver might start using delays, causing your
-    /// code to panic. This wouldn't be considered a breaking change from the driver side, because
-    /// drivers are allowed to assume `SpiDevice` implementations comply with the contract.
-    /// If you feel this risk outweighs the convenience of having `cargo` automatically upgrade
-    /// the driver crate, you might want to pin the driver's version.
+    /// See [`Device::new_no_delay`] for the caveats of not supporting delays.
     ///
     /// # Panics
     ///
@@ -65,19 +156,51 @@ impl<'a, BUS, CS> RefCellDevice<'a, BUS, CS, super::NoDelay> {
             bus,
             cs,
             delay: super::NoDelay,
+            timing: CsTiming::default(),
         })
     }
 }
 
-impl<BUS, CS, D> ErrorType for RefCellDevice<'_, BUS, CS, D>
+/// Error type for [`TryRefCellDevice`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum RefCellDeviceError<T> {
+    /// The bus was already borrowed when the transaction was started.
+    Busy,
+    /// The transaction failed.
+    Bus(T),
+}
+
+impl<T: Display> Display for RefCellDeviceError<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Busy => write!(f, "SPI bus already in use"),
+            Self::Bus(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl<T: Debug + Display> core::error::Error for RefCellDeviceError<T> {}
+
+impl<T: Error> Error for RefCellDeviceError<T> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Busy => ErrorKind::Other,
+            Self::Bus(e) => e.kind(),
+        }
+    }
+}
+
+impl<BUS, CS, D> ErrorType for TryRefCellDevice<'_, BUS, CS, D>
 where
     BUS: ErrorType,
     CS: OutputPin,
 {
-    type Error = DeviceError<BUS::Error, CS::Error>;
+    type Error = RefCellDeviceError<DeviceError<BUS::Error, CS::Error>>;
 }
 
-impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for RefCellDevice<'_, BUS, CS, D>
+impl<Word: Copy + 'static, BUS, CS, D> SpiDevice<Word> for TryRefCellDevice<'_, BUS, CS, D>
 where
     BUS: SpiBus<Word>,
     CS: OutputPin,
@@ -85,8 +208,50 @@ where
 {
     #[inline]
     fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
-        let bus = &mut *self.bus.borrow_mut();
+        let mut bus = self
+            .bus
+            .try_borrow_mut()
+            .map_err(|_| RefCellDeviceError::Busy)?;
+
+        transaction(
+            operations,
+            &mut *bus,
+            &mut self.delay,
+            &mut self.cs,
+            self.timing,
+        )
+        .map_err(RefCellDeviceError::Bus)
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, BUS, CS, D> AsyncSpiDevice<Word> for TryRefCellDevice<'_, BUS, CS, D>
+where
+    BUS: AsyncSpiBus<Word>,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+{
+    #[inline]
+    #[allow(clippy::await_holding_refcell_ref)]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        // Holding the borrow across `.await` points is intentional, see the type's documentation.
+        let mut bus = self
+            .bus
+            .try_borrow_mut()
+            .map_err(|_| RefCellDeviceError::Busy)?;
 
-        transaction(operations, bus, &mut self.delay, &mut self.cs)
+        transaction_async(
+            operations,
+            &mut *bus,
+            &mut self.delay,
+            &mut self.cs,
+            self.timing,
+        )
+        .await
+        .map_err(RefCellDeviceError::Bus)
     }
 }
@@ -1,17 +1,20 @@
 use embedded_hal::delay::DelayNs;
 use embedded_hal::digital::OutputPin;
 use embedded_hal::spi::{ErrorType, Operation, SpiBus};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiBus as AsyncSpiBus};
 
-use crate::spi::DeviceError;
+use crate::spi::{CsTiming, DeviceError};
 
 /// Common implementation to perform a transaction against the device.
 #[inline]
-pub fn transaction<Word, BUS, CS, D>(
+pub fn transaction<Word, BUS, CS, D, CFG>(
     operations: &mut [Operation<Word>],
     bus: &mut BUS,
     delay: &mut D,
     cs: &mut CS,
-) -> Result<(), DeviceError<BUS::Error, CS::Error>>
+    timing: CsTiming,
+) -> Result<(), DeviceError<BUS::Error, CS::Error, CFG>>
 where
     BUS: SpiBus<Word> + ErrorType,
     CS: OutputPin,
@@ -19,6 +22,7 @@ where
     Word: Copy,
 {
     cs.set_low().map_err(DeviceError::Cs)?;
+    cs_delay(delay, timing.setup_ns);
 
     let op_res = operations.iter_mut().try_for_each(|op| match op {
         Operation::Read(buf) => bus.read(buf),
@@ -34,11 +38,92 @@ where
 
     // On failure, it's important to still flush and deassert CS.
     let flush_res = bus.flush();
+    cs_delay(delay, timing.hold_ns);
     let cs_res = cs.set_high();
+    cs_delay(delay, timing.idle_ns);
 
-    op_res.map_err(DeviceError::Spi)?;
-    flush_res.map_err(DeviceError::Spi)?;
-    cs_res.map_err(DeviceError::Cs)?;
+    transaction_result(op_res, flush_res, cs_res)
+}
 
-    Ok(())
+/// Common implementation to perform an async transaction against the device.
+#[cfg(feature = "async")]
+#[inline]
+pub async fn transaction_async<Word, BUS, CS, D, CFG>(
+    operations: &mut [Operation<'_, Word>],
+    bus: &mut BUS,
+    delay: &mut D,
+    cs: &mut CS,
+    timing: CsTiming,
+) -> Result<(), DeviceError<BUS::Error, CS::Error, CFG>>
+where
+    BUS: AsyncSpiBus<Word> + ErrorType,
+    CS: OutputPin,
+    D: AsyncDelayNs,
+    Word: Copy + 'static,
+{
+    cs.set_low().map_err(DeviceError::Cs)?;
+    if timing.setup_ns > 0 {
+        delay.delay_ns(timing.setup_ns).await;
+    }
+
+    let op_res = 'ops: {
+        for op in operations {
+            let res = match op {
+                Operation::Read(buf) => bus.read(buf).await,
+                Operation::Write(buf) => bus.write(buf).await,
+                Operation::Transfer(read, write) => bus.transfer(read, write).await,
+                Operation::TransferInPlace(buf) => bus.transfer_in_place(buf).await,
+                Operation::DelayNs(ns) => match bus.flush().await {
+                    Err(e) => Err(e),
+                    Ok(()) => {
+                        delay.delay_ns(*ns).await;
+                        Ok(())
+                    }
+                },
+            };
+            if let Err(e) = res {
+                break 'ops Err(e);
+            }
+        }
+        Ok(())
+    };
+
+    // On failure, it's important to still flush and deassert CS.
+    let flush_res = bus.flush().await;
+    if timing.hold_ns > 0 {
+        delay.delay_ns(timing.hold_ns).await;
+    }
+    let cs_res = cs.set_high();
+    if timing.idle_ns > 0 {
+        delay.delay_ns(timing.idle_ns).await;
+    }
+
+    transaction_result(op_res, flush_res, cs_res)
+}
+
+/// Wait for a CS timing delay, skipping zero delays so that [`NoDelay`](super::NoDelay) can be used
+/// without CS timings.
+#[inline]
+fn cs_delay<D: DelayNs>(delay: &mut D, ns: u32) {
+    if ns > 0 {
+        delay.delay_ns(ns);
+    }
+}
+
+/// Combine the results of a transaction's operations, final flush and CS deassertion.
+///
+/// The error of the operations takes priority over the one of the flush. If deasserting CS failed
+/// too, both errors are kept in [`DeviceError::Composite`].
+#[inline]
+pub fn transaction_result<BUS, CS, CFG>(
+    op_res: Result<(), BUS>,
+    flush_res: Result<(), BUS>,
+    cs_res: Result<(), CS>,
+) -> Result<(), DeviceError<BUS, CS, CFG>> {
+    match (op_res.and(flush_res), cs_res) {
+        (Ok(()), Ok(())) => Ok(()),
+        (Err(spi), Ok(())) => Err(DeviceError::Spi(spi)),
+        (Ok(()), Err(cs)) => Err(DeviceError::Cs(cs)),
+        (Err(spi), Err(cs)) => Err(DeviceError::Composite { spi, cs }),
+    }
 }
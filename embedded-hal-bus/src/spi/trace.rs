@@ -0,0 +1,242 @@
+use embedded_hal::spi::{ErrorType, Operation, SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Bus event reported by [`SpiTrace`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum SpiTraceEvent {
+    /// CS was asserted, starting the transaction.
+    CsAsserted,
+    /// A word was exchanged with the device.
+    WordTransferred {
+        /// Word sent to the device.
+        mosi: u8,
+        /// Word received from the device.
+        miso: u8,
+    },
+    /// In-transaction delay, in nanoseconds.
+    DelayNs(u32),
+    /// CS was deasserted, ending the transaction.
+    CsDeasserted,
+    /// The transaction failed.
+    Error,
+}
+
+/// [`SpiDevice`] wrapper reporting every bus event to a callback.
+///
+/// The events of a transaction are reported once it is performed, so that the words read can be
+/// included: [`CsAsserted`](SpiTraceEvent::CsAsserted), a
+/// [`WordTransferred`](SpiTraceEvent::WordTransferred) event for every word of every operation and a
+/// [`DelayNs`](SpiTraceEvent::DelayNs) event for every delay, then
+/// [`CsDeasserted`](SpiTraceEvent::CsDeasserted). The inner device only reports the result of the
+/// whole transaction, so a failed transaction is reported as `CsAsserted`,
+/// [`Error`](SpiTraceEvent::Error) and `CsDeasserted`.
+///
+/// Words the wrapper cannot observe are reported as `0`: the MISO words of writes, the MOSI words of
+/// reads, and the MOSI words of in-place transfers, which were overwritten by the time the
+/// transaction is reported. For transfers with buffers of different lengths, the missing words are
+/// also reported as `0`.
+///
+/// With the `async` feature, `SpiTrace` also implements the async [`SpiDevice`](embedded_hal_async::spi::SpiDevice)
+/// trait if the inner device does.
+///
+/// # Example
+///
+/// ```
+/// use core::cell::RefCell;
+/// use embedded_hal::spi::SpiDevice;
+/// use embedded_hal_bus::spi::{SpiTrace, SpiTraceEvent};
+/// # use embedded_hal::spi::{self, ErrorKind, Operation};
+/// # struct Flash;
+/// # impl spi::ErrorType for Flash { type Error = ErrorKind; }
+/// # impl SpiDevice for Flash {
+/// #     fn transaction(&mut self, ops: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+/// #         if let [Operation::Transfer(read, _)] = ops {
+/// #             read.fill(0xEF);
+/// #         }
+/// #         Ok(())
+/// #     }
+/// # }
+/// # let flash = Flash;
+///
+/// let events = RefCell::new(Vec::new());
+/// let mut flash = SpiTrace::new(flash, |event| events.borrow_mut().push(event));
+/// flash.transfer(&mut [0], &[0x9F])?;
+/// assert_eq!(
+///     *events.borrow(),
+///     [
+///         SpiTraceEvent::CsAsserted,
+///         SpiTraceEvent::WordTransferred { mosi: 0x9F, miso: 0xEF },
+///         SpiTraceEvent::CsDeasserted,
+///     ]
+/// );
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct SpiTrace<T, F> {
+    inner: T,
+    trace: F,
+}
+
+impl<T, F> SpiTrace<T, F>
+where
+    F: Fn(SpiTraceEvent),
+{
+    /// Create a new [`SpiTrace`] wrapper around `inner`, reporting events to `trace`.
+    #[inline]
+    pub fn new(inner: T, trace: F) -> Self {
+        Self { inner, trace }
+    }
+
+    /// Release the inner device.
+    #[inline]
+    pub fn release(self) -> T {
+        self.inner
+    }
+
+    fn report<E>(&self, operations: &[Operation<'_, u8>], result: &Result<(), E>) {
+        (self.trace)(SpiTraceEvent::CsAsserted);
+        if result.is_err() {
+            (self.trace)(SpiTraceEvent::Error);
+            (self.trace)(SpiTraceEvent::CsDeasserted);
+            return;
+        }
+
+        let word = |mosi, miso| (self.trace)(SpiTraceEvent::WordTransferred { mosi, miso });
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => read.iter().for_each(|&miso| word(0, miso)),
+                Operation::Write(write) => write.iter().for_each(|&mosi| word(mosi, 0)),
+                Operation::Transfer(read, write) => {
+                    for i in 0..read.len().max(write.len()) {
+                        word(
+                            write.get(i).copied().unwrap_or(0),
+                            read.get(i).copied().unwrap_or(0),
+                        );
+                    }
+                }
+                Operation::TransferInPlace(words) => words.iter().for_each(|&miso| word(0, miso)),
+                Operation::DelayNs(ns) => (self.trace)(SpiTraceEvent::DelayNs(*ns)),
+            }
+        }
+        (self.trace)(SpiTraceEvent::CsDeasserted);
+    }
+}
+
+impl<T: ErrorType, F> ErrorType for SpiTrace<T, F> {
+    type Error = T::Error;
+}
+
+impl<T, F> SpiDevice for SpiTrace<T, F>
+where
+    T: SpiDevice,
+    F: Fn(SpiTraceEvent),
+{
+    #[inline]
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(operations);
+        self.report(operations, &result);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, F> AsyncSpiDevice for SpiTrace<T, F>
+where
+    T: AsyncSpiDevice,
+    F: Fn(SpiTraceEvent),
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(operations).await;
+        self.report(operations, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::cell::RefCell;
+    use embedded_hal::spi::ErrorKind;
+    use std::vec::Vec;
+
+    /// Answers every word with its complement, and fails transactions starting with `0xFF`.
+    struct FakeDevice;
+
+    impl ErrorType for FakeDevice {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for FakeDevice {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+            if let Some(Operation::Write([0xFF, ..])) = operations.first() {
+                return Err(ErrorKind::Overrun);
+            }
+            for op in operations {
+                match op {
+                    Operation::Read(read) => read.fill(0xA5),
+                    Operation::Transfer(read, write) => {
+                        read.iter_mut().zip(write.iter()).for_each(|(r, w)| *r = !w)
+                    }
+                    Operation::TransferInPlace(words) => words.iter_mut().for_each(|w| *w = !*w),
+                    Operation::Write(_) | Operation::DelayNs(_) => {}
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reports_every_word() {
+        let events = RefCell::new(Vec::new());
+        let mut spi = SpiTrace::new(FakeDevice, |event| events.borrow_mut().push(event));
+        spi.transaction(&mut [
+            Operation::Write(&[0x01]),
+            Operation::DelayNs(100),
+            Operation::Transfer(&mut [0; 2], &[0x0F]),
+            Operation::TransferInPlace(&mut [0xF0]),
+            Operation::Read(&mut [0]),
+        ])
+        .unwrap();
+
+        let word = |mosi, miso| SpiTraceEvent::WordTransferred { mosi, miso };
+        assert_eq!(
+            events.into_inner(),
+            [
+                SpiTraceEvent::CsAsserted,
+                word(0x01, 0),
+                SpiTraceEvent::DelayNs(100),
+                word(0x0F, 0xF0),
+                word(0, 0),
+                word(0, 0x0F),
+                word(0, 0xA5),
+                SpiTraceEvent::CsDeasserted,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        let events = RefCell::new(Vec::new());
+        let mut spi = SpiTrace::new(FakeDevice, |event| events.borrow_mut().push(event));
+        assert_eq!(spi.write(&[0xFF, 0x00]), Err(ErrorKind::Overrun));
+        assert_eq!(
+            events.into_inner(),
+            [
+                SpiTraceEvent::CsAsserted,
+                SpiTraceEvent::Error,
+                SpiTraceEvent::CsDeasserted
+            ]
+        );
+    }
+}
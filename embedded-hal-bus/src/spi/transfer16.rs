@@ -0,0 +1,163 @@
+use embedded_hal::spi::SpiDevice;
+
+/// Transfers of 16-bit words over an 8-bit [`SpiDevice`], most significant byte first.
+///
+/// Displays such as the ILI9341 or ST7789 take 16-bit pixels in big-endian byte order on an 8-bit bus.
+/// These methods send and receive `u16` words in that order, in a single transaction, without copying
+/// the buffers: on little-endian targets, the words are byte-swapped in place before the transfer, and
+/// swapped back before returning, even if it fails. This is why the buffers to write are mutable. On
+/// big-endian targets, the buffers are transferred as they are.
+///
+/// Received words are read most significant byte first, too.
+///
+/// This trait is implemented for every [`SpiDevice<u8>`] implementation, it does not need to be implemented
+/// manually. Use a [`SpiDevice<u16>`] implementation instead, if the bus supports 16-bit frames.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::SpiDevice;
+/// use embedded_hal_bus::spi::SpiTransfer16;
+/// # use embedded_hal::spi::{ErrorKind, ErrorType, Operation};
+/// # struct Display;
+/// # impl ErrorType for Display { type Error = ErrorKind; }
+/// # impl SpiDevice for Display {
+/// #     fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> { Ok(()) }
+/// # }
+/// # let mut display = Display;
+///
+/// const RAMWR: u8 = 0x2C;
+/// const RED: u16 = 0xF800;
+///
+/// let mut line = [RED; 240];
+/// display.write(&[RAMWR])?;
+/// display.write16(&mut line)?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub trait SpiTransfer16: SpiDevice<u8> {
+    /// Write `words`, most significant byte first.
+    ///
+    /// See [`SpiDevice::write`].
+    #[inline]
+    fn write16(&mut self, words: &mut [u16]) -> Result<(), Self::Error> {
+        with_be_bytes(words, |bytes| self.write(bytes))
+    }
+
+    /// Write `write` and read into `read`, most significant byte first.
+    ///
+    /// See [`SpiDevice::transfer`].
+    #[inline]
+    fn transfer16(&mut self, read: &mut [u16], write: &mut [u16]) -> Result<(), Self::Error> {
+        with_be_bytes(write, |write| {
+            with_be_bytes(read, |read| self.transfer(read, write))
+        })
+    }
+
+    /// Write `words` and replace them with the words read, most significant byte first.
+    ///
+    /// See [`SpiDevice::transfer_in_place`].
+    #[inline]
+    fn transfer_in_place16(&mut self, words: &mut [u16]) -> Result<(), Self::Error> {
+        with_be_bytes(words, |bytes| self.transfer_in_place(bytes))
+    }
+}
+
+impl<T: SpiDevice<u8> + ?Sized> SpiTransfer16 for T {}
+
+/// Calls `f` with the bytes of `words` in big-endian order, then restores `words` to the native order.
+fn with_be_bytes<R>(words: &mut [u16], f: impl FnOnce(&mut [u8]) -> R) -> R {
+    for word in words.iter_mut() {
+        *word = word.to_be();
+    }
+    // SAFETY: `u8` has no alignment requirement, and every bit pattern is valid for both types, so
+    // the bytes of `words` can be reinterpreted, and modified, as `u8`s.
+    let bytes = unsafe {
+        core::slice::from_raw_parts_mut(words.as_mut_ptr().cast::<u8>(), 2 * words.len())
+    };
+    let result = f(bytes);
+    for word in words.iter_mut() {
+        *word = u16::from_be(*word);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::spi::{ErrorKind, ErrorType, Operation};
+    use std::vec::Vec;
+
+    /// Device recording the bytes written, and answering with `miso`. Fails if `fail` is set.
+    #[derive(Default)]
+    struct FakeDevice {
+        mosi: Vec<u8>,
+        miso: Vec<u8>,
+        fail: bool,
+    }
+
+    impl ErrorType for FakeDevice {
+        type Error = ErrorKind;
+    }
+
+    impl SpiDevice for FakeDevice {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+            for op in operations {
+                match op {
+                    Operation::Write(bytes) => self.mosi.extend_from_slice(bytes),
+                    Operation::Transfer(read, write) => {
+                        self.mosi.extend_from_slice(write);
+                        read.copy_from_slice(&self.miso[..read.len()]);
+                    }
+                    Operation::TransferInPlace(bytes) => {
+                        self.mosi.extend_from_slice(bytes);
+                        bytes.copy_from_slice(&self.miso[..bytes.len()]);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            if self.fail {
+                return Err(ErrorKind::Other);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn words_are_sent_big_endian() {
+        let mut spi = FakeDevice::default();
+        let mut words = [0xF800, 0x07E0];
+        spi.write16(&mut words).unwrap();
+        assert_eq!(spi.mosi, [0xF8, 0x00, 0x07, 0xE0]);
+        assert_eq!(words, [0xF800, 0x07E0]);
+    }
+
+    #[test]
+    fn words_are_received_big_endian() {
+        let mut spi = FakeDevice {
+            miso: std::vec![0x12, 0x34, 0xAB, 0xCD],
+            ..Default::default()
+        };
+        let mut read = [0; 2];
+        spi.transfer16(&mut read, &mut [0x0102]).unwrap();
+        assert_eq!(read, [0x1234, 0xABCD]);
+        assert_eq!(spi.mosi, [0x01, 0x02]);
+
+        let mut words = [0x5678];
+        spi.transfer_in_place16(&mut words).unwrap();
+        assert_eq!(words, [0x1234]);
+        assert_eq!(spi.mosi[2..], [0x56, 0x78]);
+    }
+
+    #[test]
+    fn words_are_restored_on_error() {
+        let mut spi = FakeDevice {
+            fail: true,
+            ..Default::default()
+        };
+        let mut words = [0x1234];
+        spi.write16(&mut words).unwrap_err();
+        assert_eq!(words, [0x1234]);
+    }
+}
@@ -0,0 +1,628 @@
+//! Traffic statistics.
+//!
+//! [`Counted`] wraps an [`I2c`], [`SpiDevice`] or [`SpiBus`] implementation, forwarding all the operations
+//! while accumulating statistics in a [`Counters`]. The counters are atomics, so they can be read and reset
+//! through a shared reference, for example from a `static` or an interrupt handler, while the driver owns
+//! the wrapped device.
+//!
+//! Along with the traffic, failed I2C transactions are counted by [error kind](i2c::ErrorKind), to
+//! detect a degrading bus in the field: a rising number of address NACKs points at a missing or
+//! unresponsive target, arbitration losses and timeouts at electrical problems.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::i2c::I2c;
+//! use embedded_hal_bus::stats::{Counted, Counters};
+//! # use embedded_hal::i2c::{self, ErrorKind, Operation};
+//! # struct I2c0;
+//! # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+//! # impl I2c for I2c0 {
+//! #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # let i2c = I2c0;
+//!
+//! static SENSOR_TRAFFIC: Counters = Counters::new();
+//!
+//! let mut i2c = Counted::new(i2c, &SENSOR_TRAFFIC);
+//! let mut temperature = [0; 2];
+//! i2c.write_read(0x48, &[0x00], &mut temperature)?;
+//!
+//! let stats = SENSOR_TRAFFIC.stats();
+//! assert_eq!((stats.transactions, stats.bytes_written, stats.bytes_read), (1, 1, 2));
+//! # Ok::<(), ErrorKind>(())
+//! ```
+
+use core::mem::{size_of, size_of_val};
+use core::sync::atomic::Ordering;
+use embedded_hal::i2c::{self, AddressMode, I2c, NoAcknowledgeSource};
+use embedded_hal::spi::{self, SpiBus, SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::{
+    i2c::I2c as AsyncI2c,
+    spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice},
+};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic::AtomicU32;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic::AtomicU32;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Snapshot of [`Counters`].
+///
+/// All the counters wrap around on overflow.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Stats {
+    /// Number of transactions, including the failed ones.
+    ///
+    /// Each method call is one transaction, except for [`SpiBus::flush`] which is not counted.
+    pub transactions: u32,
+    /// Number of bytes written by successful transactions.
+    pub bytes_written: u32,
+    /// Number of bytes read by successful transactions.
+    pub bytes_read: u32,
+    /// Number of failed transactions.
+    pub errors: u32,
+    /// Number of I2C transactions failed with [`NoAcknowledgeSource::Address`].
+    pub address_nacks: u32,
+    /// Number of I2C transactions failed with [`NoAcknowledgeSource::Data`].
+    pub data_nacks: u32,
+    /// Number of I2C transactions failed with [`ErrorKind::ArbitrationLoss`](i2c::ErrorKind::ArbitrationLoss).
+    pub arbitration_losses: u32,
+    /// Number of I2C transactions failed with [`ErrorKind::Timeout`](i2c::ErrorKind::Timeout).
+    pub timeouts: u32,
+}
+
+/// Traffic counters, updated by [`Counted`].
+///
+/// Several [`Counted`] wrappers can share the same counters, for example to account all the traffic of a bus.
+#[derive(Debug, Default)]
+pub struct Counters {
+    transactions: AtomicU32,
+    bytes_written: AtomicU32,
+    bytes_read: AtomicU32,
+    errors: AtomicU32,
+    address_nacks: AtomicU32,
+    data_nacks: AtomicU32,
+    arbitration_losses: AtomicU32,
+    timeouts: AtomicU32,
+}
+
+impl Counters {
+    /// Create new counters, all zero.
+    pub const fn new() -> Self {
+        Self {
+            transactions: AtomicU32::new(0),
+            bytes_written: AtomicU32::new(0),
+            bytes_read: AtomicU32::new(0),
+            errors: AtomicU32::new(0),
+            address_nacks: AtomicU32::new(0),
+            data_nacks: AtomicU32::new(0),
+            arbitration_losses: AtomicU32::new(0),
+            timeouts: AtomicU32::new(0),
+        }
+    }
+
+    /// Take a snapshot of the counters.
+    ///
+    /// The counters are read one after the other: the snapshot may be inconsistent if a transaction
+    /// completes concurrently.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            transactions: self.transactions.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            address_nacks: self.address_nacks.load(Ordering::Relaxed),
+            data_nacks: self.data_nacks.load(Ordering::Relaxed),
+            arbitration_losses: self.arbitration_losses.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset all the counters to zero.
+    pub fn reset(&self) {
+        self.transactions.store(0, Ordering::Relaxed);
+        self.bytes_written.store(0, Ordering::Relaxed);
+        self.bytes_read.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+        self.address_nacks.store(0, Ordering::Relaxed);
+        self.data_nacks.store(0, Ordering::Relaxed);
+        self.arbitration_losses.store(0, Ordering::Relaxed);
+        self.timeouts.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record<T, E>(&self, result: &Result<T, E>, written: usize, read: usize) {
+        self.transactions.fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.bytes_written
+                .fetch_add(written as u32, Ordering::Relaxed);
+            self.bytes_read.fetch_add(read as u32, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_i2c<T, E: i2c::Error>(
+        &self,
+        result: &Result<T, E>,
+        written: usize,
+        read: usize,
+    ) {
+        self.record(result, written, read);
+        let Err(e) = result else {
+            return;
+        };
+        let counter = match e.kind() {
+            i2c::ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address) => &self.address_nacks,
+            i2c::ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data) => &self.data_nacks,
+            i2c::ErrorKind::ArbitrationLoss => &self.arbitration_losses,
+            i2c::ErrorKind::Timeout => &self.timeouts,
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Wrapper counting the traffic of an [`I2c`], [`SpiDevice`] or [`SpiBus`] implementation.
+///
+/// All the operations are forwarded to the inner type, and accounted in the [`Counters`]. This works
+/// with any implementation, including the shared bus devices of this crate such as
+/// [`i2c::CriticalSectionDevice`](crate::i2c::CriticalSectionDevice).
+///
+/// With the `async` feature, `Counted` also implements the async traits if the inner type does.
+pub struct Counted<'a, T> {
+    inner: T,
+    counters: &'a Counters,
+}
+
+impl<'a, T> Counted<'a, T> {
+    /// Create a new [`Counted`] wrapper around `inner`, updating `counters`.
+    #[inline]
+    pub fn new(inner: T, counters: &'a Counters) -> Self {
+        Self { inner, counters }
+    }
+
+    /// Take a snapshot of the counters.
+    #[inline]
+    pub fn stats(&self) -> Stats {
+        self.counters.stats()
+    }
+
+    /// Release the inner type.
+    #[inline]
+    pub fn release(self) -> T {
+        self.inner
+    }
+}
+
+pub(crate) fn i2c_lengths(operations: &[i2c::Operation<'_>]) -> (usize, usize) {
+    operations
+        .iter()
+        .fold((0, 0), |(written, read), op| match op {
+            i2c::Operation::Write(buf) => (written + buf.len(), read),
+            i2c::Operation::Read(buf) => (written, read + buf.len()),
+        })
+}
+
+fn spi_lengths<Word>(operations: &[spi::Operation<'_, Word>]) -> (usize, usize) {
+    let (written, read) = operations
+        .iter()
+        .fold((0, 0), |(written, read), op| match op {
+            spi::Operation::Read(words) => (written, read + words.len()),
+            spi::Operation::Write(words) => (written + words.len(), read),
+            spi::Operation::Transfer(r, w) => (written + w.len(), read + r.len()),
+            spi::Operation::TransferInPlace(words) => (written + words.len(), read + words.len()),
+            spi::Operation::DelayNs(_) => (written, read),
+        });
+    (written * size_of::<Word>(), read * size_of::<Word>())
+}
+
+impl<T: i2c::ErrorType> i2c::ErrorType for Counted<'_, T> {
+    type Error = T::Error;
+}
+
+impl<T: I2c<A>, A: AddressMode> I2c<A> for Counted<'_, T> {
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read);
+        self.counters.record_i2c(&result, 0, read.len());
+        result
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write);
+        self.counters.record_i2c(&result, write.len(), 0);
+        result
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read);
+        self.counters.record_i2c(&result, write.len(), read.len());
+        result
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations);
+        let (written, read) = i2c_lengths(operations);
+        self.counters.record_i2c(&result, written, read);
+        result
+    }
+}
+
+impl<T: spi::ErrorType> spi::ErrorType for Counted<'_, T> {
+    type Error = T::Error;
+}
+
+impl<Word: Copy + 'static, T: SpiDevice<Word>> SpiDevice<Word> for Counted<'_, T> {
+    #[inline]
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(operations);
+        let (written, read) = spi_lengths(operations);
+        self.counters.record(&result, written, read);
+        result
+    }
+}
+
+impl<Word: Copy + 'static, T: SpiBus<Word>> SpiBus<Word> for Counted<'_, T> {
+    #[inline]
+    fn read(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        let result = self.inner.read(words);
+        self.counters.record(&result, 0, size_of_val(words));
+        result
+    }
+
+    #[inline]
+    fn write(&mut self, words: &[Word]) -> Result<(), Self::Error> {
+        let result = self.inner.write(words);
+        self.counters.record(&result, size_of_val(words), 0);
+        result
+    }
+
+    #[inline]
+    fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer(read, write);
+        self.counters
+            .record(&result, size_of_val(write), size_of_val(read));
+        result
+    }
+
+    #[inline]
+    fn transfer_in_place(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer_in_place(words);
+        let bytes = size_of_val(words);
+        self.counters.record(&result, bytes, bytes);
+        result
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T: AsyncI2c<A>, A: AddressMode> AsyncI2c<A> for Counted<'_, T> {
+    #[inline]
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.inner.read(address, read).await;
+        self.counters.record_i2c(&result, 0, read.len());
+        result
+    }
+
+    #[inline]
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.inner.write(address, write).await;
+        self.counters.record_i2c(&result, write.len(), 0);
+        result
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.write_read(address, write, read).await;
+        self.counters.record_i2c(&result, write.len(), read.len());
+        result
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(address, operations).await;
+        let (written, read) = i2c_lengths(operations);
+        self.counters.record_i2c(&result, written, read);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, T: AsyncSpiDevice<Word>> AsyncSpiDevice<Word> for Counted<'_, T> {
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        let result = self.inner.transaction(operations).await;
+        let (written, read) = spi_lengths(operations);
+        self.counters.record(&result, written, read);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word: Copy + 'static, T: AsyncSpiBus<Word>> AsyncSpiBus<Word> for Counted<'_, T> {
+    #[inline]
+    async fn read(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        let result = self.inner.read(words).await;
+        self.counters.record(&result, 0, size_of_val(words));
+        result
+    }
+
+    #[inline]
+    async fn write(&mut self, words: &[Word]) -> Result<(), Self::Error> {
+        let result = self.inner.write(words).await;
+        self.counters.record(&result, size_of_val(words), 0);
+        result
+    }
+
+    #[inline]
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer(read, write).await;
+        self.counters
+            .record(&result, size_of_val(write), size_of_val(read));
+        result
+    }
+
+    #[inline]
+    async fn transfer_in_place(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        let result = self.inner.transfer_in_place(words).await;
+        let bytes = size_of_val(words);
+        self.counters.record(&result, bytes, bytes);
+        result
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use embedded_hal::i2c::ErrorKind;
+
+    /// Bus failing all the transactions to 0x10.
+    struct FakeI2c;
+
+    impl i2c::ErrorType for FakeI2c {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeI2c {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            if address == 0x10 {
+                Err(ErrorKind::Other)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    struct FakeSpi;
+
+    impl spi::ErrorType for FakeSpi {
+        type Error = spi::ErrorKind;
+    }
+
+    impl SpiDevice<u16> for FakeSpi {
+        fn transaction(
+            &mut self,
+            _operations: &mut [spi::Operation<'_, u16>],
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn counts_multi_operation_transactions() {
+        let counters = Counters::new();
+        let bus = RefCell::new(FakeI2c);
+        let mut i2c = Counted::new(crate::i2c::RefCellDevice::new(&bus), &counters);
+        let (mut a, mut b) = ([0; 2], [0; 3]);
+        i2c.transaction(
+            0x20,
+            &mut [
+                i2c::Operation::Write(&[1]),
+                i2c::Operation::Read(&mut a),
+                i2c::Operation::Write(&[2, 3]),
+                i2c::Operation::Read(&mut b),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            i2c.stats(),
+            Stats {
+                transactions: 1,
+                bytes_written: 3,
+                bytes_read: 5,
+                errors: 0,
+                ..Stats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn errors_do_not_count_bytes() {
+        let counters = Counters::new();
+        let mut i2c = Counted::new(FakeI2c, &counters);
+        i2c.write(0x20, &[1, 2]).unwrap();
+        i2c.write_read(0x10, &[1], &mut [0; 4]).unwrap_err();
+        assert_eq!(
+            counters.stats(),
+            Stats {
+                transactions: 2,
+                bytes_written: 2,
+                bytes_read: 0,
+                errors: 1,
+                ..Stats::default()
+            }
+        );
+        counters.reset();
+        assert_eq!(counters.stats(), Stats::default());
+    }
+
+    #[test]
+    fn counts_i2c_errors_by_kind() {
+        use embedded_hal::i2c::Operation;
+
+        /// Bus failing the transactions to 0x10..=0x14 with a different error each.
+        struct FailingI2c;
+
+        impl i2c::ErrorType for FailingI2c {
+            type Error = ErrorKind;
+        }
+
+        impl I2c for FailingI2c {
+            fn transaction(
+                &mut self,
+                address: u8,
+                _operations: &mut [Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                match address {
+                    0x10 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+                    0x11 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+                    0x12 => Err(ErrorKind::ArbitrationLoss),
+                    0x13 => Err(ErrorKind::Timeout),
+                    0x14 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)),
+                    _ => Ok(()),
+                }
+            }
+        }
+
+        let counters = Counters::new();
+        let mut i2c = Counted::new(FailingI2c, &counters);
+        i2c.write(0x20, &[1]).unwrap();
+        for address in [0x10, 0x10, 0x11, 0x12, 0x13, 0x14] {
+            i2c.write_read(address, &[1], &mut [0; 4]).unwrap_err();
+        }
+        assert_eq!(
+            counters.stats(),
+            Stats {
+                transactions: 7,
+                bytes_written: 1,
+                bytes_read: 0,
+                errors: 6,
+                address_nacks: 2,
+                data_nacks: 1,
+                arbitration_losses: 1,
+                timeouts: 1,
+            }
+        );
+
+        counters.reset();
+        assert_eq!(counters.stats(), Stats::default());
+    }
+
+    #[test]
+    fn counts_timeouts_of_the_crate_wrappers() {
+        use crate::i2c::ClockStretchTimeout;
+        use embedded_hal::delay::Counter;
+
+        /// Counter always past any deadline.
+        struct Late;
+
+        impl Counter for Late {
+            fn start(&mut self) {}
+
+            fn elapsed_ns(&self) -> u64 {
+                u64::MAX
+            }
+        }
+
+        let counters = Counters::new();
+        let mut i2c = Counted::new(ClockStretchTimeout::new(FakeI2c, Late, 1_000), &counters);
+        i2c.write(0x20, &[1]).unwrap();
+        i2c.write(0x20, &[1]).unwrap_err();
+        assert_eq!(counters.stats().timeouts, 1);
+    }
+
+    #[test]
+    fn spi_counts_bytes_of_words() {
+        let counters = Counters::new();
+        let mut spi = Counted::new(FakeSpi, &counters);
+        let mut buf = [0u16; 2];
+        spi.transaction(&mut [
+            spi::Operation::Write(&[1]),
+            spi::Operation::DelayNs(10),
+            spi::Operation::Transfer(&mut buf, &[2, 3, 4]),
+        ])
+        .unwrap();
+        assert_eq!(
+            counters.stats(),
+            Stats {
+                transactions: 1,
+                bytes_written: 8,
+                bytes_read: 4,
+                errors: 0,
+                ..Stats::default()
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn counts_async_transactions() {
+        use embedded_hal_async::i2c::I2c as _;
+
+        struct AsyncFake;
+        impl i2c::ErrorType for AsyncFake {
+            type Error = ErrorKind;
+        }
+        impl AsyncI2c for AsyncFake {
+            async fn transaction(
+                &mut self,
+                _address: u8,
+                _operations: &mut [i2c::Operation<'_>],
+            ) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+
+        let counters = Counters::new();
+        let mut i2c = Counted::new(AsyncFake, &counters);
+        crate::util::poll_to_completion(i2c.write(0x20, &[1, 2, 3])).unwrap();
+        assert_eq!(counters.stats().bytes_written, 3);
+    }
+}
@@ -0,0 +1,541 @@
+//! Minimum interval between transactions.
+//!
+//! Some devices misbehave when they are accessed too often, or too soon after the previous transaction:
+//! humidity sensors measuring between two commands, EEPROMs completing a write cycle, converters needing
+//! a quiet bus. [`Throttled`] wraps an [`I2c`] or [`SpiDevice`] implementation, and delays every
+//! transaction until a minimum gap elapsed since the previous one completed.
+//!
+//! The gap applies between transactions, never between the operations of a transaction: each call to
+//! [`I2c::read`], [`I2c::write`], [`I2c::write_read`], [`I2c::transaction`] or [`SpiDevice::transaction`]
+//! (including the provided methods calling it) is one transaction. The gap is measured from the completion
+//! of the previous transaction, whether it succeeded or failed.
+//!
+//! Drivers which must not block, such as superloops writing a flash chip page after page, can check
+//! [`remaining_ns`](Throttled::remaining_ns) first, or, with the `nb` feature, poll
+//! [`ready`](Throttled::ready) until the gap elapsed: the next transaction then starts without delay.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::i2c::I2c;
+//! use embedded_hal_bus::throttle::Throttled;
+//! # use embedded_hal::delay::DelayNs;
+//! # use embedded_hal::i2c::{self, ErrorKind, Operation};
+//! # use embedded_hal::time::{Instant, MonotonicClock};
+//! # struct I2c0;
+//! # impl i2c::ErrorType for I2c0 { type Error = ErrorKind; }
+//! # impl I2c for I2c0 {
+//! #     fn transaction(&mut self, _: u8, _: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! # struct Delay;
+//! # impl DelayNs for Delay { fn delay_ns(&mut self, _: u32) {} }
+//! # struct SysTick;
+//! # impl MonotonicClock for SysTick {
+//! #     fn now(&self) -> Instant { Instant::from_nanos(0) }
+//! # }
+//! # let (i2c, delay, clock) = (I2c0, Delay, SysTick);
+//!
+//! // The sensor needs 1 ms between two commands.
+//! let mut sensor = Throttled::new(i2c, delay, 1_000_000).with_clock(&clock);
+//! sensor.write(0x44, &[0x24, 0x00])?;
+//! // Delayed until 1 ms after the completion of the write.
+//! sensor.read(0x44, &mut [0; 6])?;
+//! # Ok::<(), ErrorKind>(())
+//! ```
+
+use embedded_hal::delay::{Counter, DelayNs};
+use embedded_hal::i2c::{self, AddressMode, I2c};
+use embedded_hal::spi::{self, SpiDevice};
+use embedded_hal::time::{Instant, MonotonicClock};
+#[cfg(feature = "async")]
+use embedded_hal_async::{
+    delay::DelayNs as AsyncDelayNs, i2c::I2c as AsyncI2c, spi::SpiDevice as AsyncSpiDevice,
+};
+
+#[cfg(feature = "nb")]
+use embedded_hal_nb::nb;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Clock which never advances, used by [`Throttled`] when no clock is provided.
+///
+/// Since no time seems to elapse after a transaction, the whole gap is delayed before the next one. This
+/// is conservative: the gap may already have elapsed, but it is never cut short.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct NoClock;
+
+impl MonotonicClock for NoClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::from_nanos(0)
+    }
+}
+
+/// Clock measuring the time with a [`Counter`], used by [`Throttled`] when set with
+/// [`with_counter`](Throttled::with_counter).
+///
+/// The counter is started when the clock is created, and never restarted: the instants of the clock are the
+/// time elapsed since then.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct CounterClock<C> {
+    counter: C,
+}
+
+impl<C: Counter> CounterClock<C> {
+    /// Create a new [`CounterClock`], starting `counter`.
+    #[inline]
+    pub fn new(mut counter: C) -> Self {
+        counter.start();
+        Self { counter }
+    }
+
+    /// Release the counter.
+    #[inline]
+    pub fn release(self) -> C {
+        self.counter
+    }
+}
+
+impl<C: Counter> MonotonicClock for CounterClock<C> {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::from_nanos(self.counter.elapsed_ns())
+    }
+}
+
+/// [`I2c`] and [`SpiDevice`] wrapper enforcing a minimum gap between transactions.
+///
+/// See the [module-level documentation](self) for how the gap is applied. Without a clock, the whole gap
+/// is delayed before each transaction but the first. With a clock, set with
+/// [`with_clock`](Throttled::with_clock), only what remains of the gap since the completion of the previous
+/// transaction is delayed, and nothing if it already elapsed. A [`Counter`] can be used instead, with
+/// [`with_counter`](Throttled::with_counter).
+///
+/// With the `async` feature, `Throttled` also implements the async [`I2c`](AsyncI2c) and
+/// [`SpiDevice`](AsyncSpiDevice) traits if the inner type does, delaying with an async
+/// [`DelayNs`](AsyncDelayNs).
+pub struct Throttled<T, D, C = NoClock> {
+    inner: T,
+    delay: D,
+    clock: C,
+    min_gap_ns: u32,
+    /// Completion of the previous transaction, if any.
+    last: Option<Instant>,
+}
+
+impl<T, D> Throttled<T, D> {
+    /// Create a new [`Throttled`] wrapper around `inner`, keeping at least `min_gap_ns` nanoseconds between
+    /// transactions with `delay`.
+    #[inline]
+    pub fn new(inner: T, delay: D, min_gap_ns: u32) -> Self {
+        Self {
+            inner,
+            delay,
+            clock: NoClock,
+            min_gap_ns,
+            last: None,
+        }
+    }
+}
+
+impl<T, D, C> Throttled<T, D, C> {
+    /// Measure the time elapsed since the previous transaction with `clock`, to only delay what remains of
+    /// the gap.
+    #[inline]
+    pub fn with_clock<C2: MonotonicClock>(self, clock: C2) -> Throttled<T, D, C2> {
+        Throttled {
+            inner: self.inner,
+            delay: self.delay,
+            clock,
+            min_gap_ns: self.min_gap_ns,
+            last: self.last,
+        }
+    }
+
+    /// Measure the time elapsed since the previous transaction with `counter`, to only delay what remains of
+    /// the gap.
+    ///
+    /// The counter is started, and must not be restarted by another user: see [`CounterClock`].
+    #[inline]
+    pub fn with_counter<C2: Counter>(self, counter: C2) -> Throttled<T, D, CounterClock<C2>> {
+        self.with_clock(CounterClock::new(counter))
+    }
+
+    /// Release the inner type.
+    #[inline]
+    pub fn release(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, D, C: MonotonicClock> Throttled<T, D, C> {
+    /// Returns how long the next transaction would be delayed, in nanoseconds.
+    ///
+    /// Without a clock, this is the whole gap, except before the first transaction.
+    pub fn remaining_ns(&self) -> u32 {
+        let Some(last) = self.last else {
+            return 0;
+        };
+        let elapsed = self.clock.elapsed(last).as_nanos();
+        // Smaller than `min_gap_ns`, so it fits in 32 bits.
+        u64::from(self.min_gap_ns).saturating_sub(elapsed) as u32
+    }
+
+    /// Returns [`WouldBlock`](nb::Error::WouldBlock) until the next transaction can start without delay.
+    #[cfg(feature = "nb")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "nb")))]
+    #[inline]
+    pub fn ready(&self) -> nb::Result<(), core::convert::Infallible> {
+        if self.remaining_ns() > 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        Ok(())
+    }
+
+    fn completed(&mut self) {
+        self.last = Some(self.clock.now());
+    }
+}
+
+impl<T, D: DelayNs, C: MonotonicClock> Throttled<T, D, C> {
+    fn throttle<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        let remaining = self.remaining_ns();
+        if remaining > 0 {
+            self.delay.delay_ns(remaining);
+        }
+        let result = f(&mut self.inner);
+        self.completed();
+        result
+    }
+}
+
+impl<T: i2c::ErrorType, D, C> i2c::ErrorType for Throttled<T, D, C> {
+    type Error = T::Error;
+}
+
+impl<T: I2c<A>, A: AddressMode, D: DelayNs, C: MonotonicClock> I2c<A> for Throttled<T, D, C> {
+    #[inline]
+    fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.throttle(|inner| inner.read(address, read))
+    }
+
+    #[inline]
+    fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.throttle(|inner| inner.write(address, write))
+    }
+
+    #[inline]
+    fn write_read(&mut self, address: A, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        self.throttle(|inner| inner.write_read(address, write, read))
+    }
+
+    #[inline]
+    fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.throttle(|inner| inner.transaction(address, operations))
+    }
+}
+
+impl<T: spi::ErrorType, D, C> spi::ErrorType for Throttled<T, D, C> {
+    type Error = T::Error;
+}
+
+impl<Word, T, D, C> SpiDevice<Word> for Throttled<T, D, C>
+where
+    Word: Copy + 'static,
+    T: SpiDevice<Word>,
+    D: DelayNs,
+    C: MonotonicClock,
+{
+    #[inline]
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        self.throttle(|inner| inner.transaction(operations))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T, D: AsyncDelayNs, C: MonotonicClock> Throttled<T, D, C> {
+    async fn wait(&mut self) {
+        let remaining = self.remaining_ns();
+        if remaining > 0 {
+            self.delay.delay_ns(remaining).await;
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<T, A, D, C> AsyncI2c<A> for Throttled<T, D, C>
+where
+    T: AsyncI2c<A>,
+    A: AddressMode,
+    D: AsyncDelayNs,
+    C: MonotonicClock,
+{
+    #[inline]
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.wait().await;
+        let result = self.inner.read(address, read).await;
+        self.completed();
+        result
+    }
+
+    #[inline]
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.wait().await;
+        let result = self.inner.write(address, write).await;
+        self.completed();
+        result
+    }
+
+    #[inline]
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.wait().await;
+        let result = self.inner.write_read(address, write, read).await;
+        self.completed();
+        result
+    }
+
+    #[inline]
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.wait().await;
+        let result = self.inner.transaction(address, operations).await;
+        self.completed();
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+impl<Word, T, D, C> AsyncSpiDevice<Word> for Throttled<T, D, C>
+where
+    Word: Copy + 'static,
+    T: AsyncSpiDevice<Word>,
+    D: AsyncDelayNs,
+    C: MonotonicClock,
+{
+    #[inline]
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        self.wait().await;
+        let result = self.inner.transaction(operations).await;
+        self.completed();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, Operation};
+    use embedded_hal_mock::sim::{FakeCounter, FakeDelay, SimClock};
+    use std::vec::Vec;
+
+    /// Device recording the start time of every transaction, each lasting 100 ns, and failing the
+    /// transactions to 0x10.
+    struct FakeDevice {
+        clock: SimClock,
+        starts: Vec<u64>,
+    }
+
+    impl FakeDevice {
+        fn new(clock: &SimClock) -> Self {
+            Self {
+                clock: clock.clone(),
+                starts: Vec::new(),
+            }
+        }
+
+        fn run(&mut self) {
+            self.starts.push(self.clock.now_ns());
+            self.clock.advance_ns(100);
+        }
+    }
+
+    impl i2c::ErrorType for FakeDevice {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for FakeDevice {
+        fn transaction(
+            &mut self,
+            address: u8,
+            _operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.run();
+            if address == 0x10 {
+                Err(ErrorKind::Other)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl spi::ErrorType for FakeDevice {
+        type Error = spi::ErrorKind;
+    }
+
+    impl SpiDevice for FakeDevice {
+        fn transaction(
+            &mut self,
+            _operations: &mut [spi::Operation<'_, u8>],
+        ) -> Result<(), Self::Error> {
+            self.run();
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncI2c for FakeDevice {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            I2c::transaction(self, address, operations)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncSpiDevice for FakeDevice {
+        async fn transaction(
+            &mut self,
+            operations: &mut [spi::Operation<'_, u8>],
+        ) -> Result<(), Self::Error> {
+            SpiDevice::transaction(self, operations)
+        }
+    }
+
+    #[test]
+    fn clock_delays_the_remaining_gap() {
+        let clock = SimClock::new();
+        let mut i2c = Throttled::new(FakeDevice::new(&clock), FakeDelay::new(&clock), 1_000)
+            .with_clock(&clock);
+
+        I2c::write(&mut i2c, 0x20, &[0]).unwrap();
+        clock.advance_ns(300);
+        // Started 1000 ns after the completion of the first one, at 100.
+        I2c::write_read(&mut i2c, 0x20, &[0], &mut [0]).unwrap();
+        // Failed transactions are throttled, and delay the next ones, too.
+        I2c::write(&mut i2c, 0x10, &[0]).unwrap_err();
+        clock.advance_ns(5_000);
+        I2c::read(&mut i2c, 0x20, &mut [0]).unwrap();
+        assert_eq!(i2c.release().starts, [0, 1_100, 2_200, 7_300]);
+    }
+
+    #[test]
+    fn gap_applies_per_transaction() {
+        let clock = SimClock::new();
+        let mut i2c = Throttled::new(FakeDevice::new(&clock), FakeDelay::new(&clock), 1_000)
+            .with_clock(&clock);
+
+        I2c::transaction(
+            &mut i2c,
+            0x20,
+            &mut [
+                Operation::Write(&[1]),
+                Operation::Read(&mut [0; 2]),
+                Operation::Write(&[2]),
+            ],
+        )
+        .unwrap();
+        I2c::transaction(
+            &mut i2c,
+            0x20,
+            &mut [Operation::Write(&[3]), Operation::Write(&[4])],
+        )
+        .unwrap();
+        assert_eq!(i2c.release().starts, [0, 1_100]);
+    }
+
+    #[test]
+    fn no_clock_delays_the_whole_gap() {
+        let clock = SimClock::new();
+        let mut spi = Throttled::new(FakeDevice::new(&clock), FakeDelay::new(&clock), 1_000);
+
+        SpiDevice::write(&mut spi, &[1]).unwrap();
+        clock.advance_ns(5_000);
+        SpiDevice::transaction(
+            &mut spi,
+            &mut [spi::Operation::Write(&[2]), spi::Operation::Read(&mut [0])],
+        )
+        .unwrap();
+        SpiDevice::read(&mut spi, &mut [0]).unwrap();
+        assert_eq!(spi.release().starts, [0, 6_100, 7_200]);
+    }
+
+    #[test]
+    fn counter_delays_the_remaining_gap() {
+        let clock = SimClock::new();
+        let counter = FakeCounter::new(&clock);
+        clock.advance_ns(10_000);
+        let mut spi = Throttled::new(FakeDevice::new(&clock), FakeDelay::new(&clock), 1_000)
+            .with_counter(counter);
+
+        assert_eq!(spi.remaining_ns(), 0);
+        SpiDevice::write(&mut spi, &[1]).unwrap();
+        clock.advance_ns(400);
+        assert_eq!(spi.remaining_ns(), 600);
+        SpiDevice::write(&mut spi, &[2]).unwrap();
+        assert_eq!(spi.release().starts, [10_000, 11_100]);
+    }
+
+    #[test]
+    #[cfg(feature = "nb")]
+    fn ready_until_the_gap_elapsed() {
+        let clock = SimClock::new();
+        let mut spi = Throttled::new(FakeDevice::new(&clock), FakeDelay::new(&clock), 1_000)
+            .with_clock(&clock);
+
+        assert_eq!(spi.ready(), Ok(()));
+        SpiDevice::write(&mut spi, &[1]).unwrap();
+        clock.advance_ns(999);
+        assert_eq!(spi.ready(), Err(nb::Error::WouldBlock));
+        clock.advance_ns(1);
+        assert_eq!(spi.ready(), Ok(()));
+        // Not delayed.
+        SpiDevice::write(&mut spi, &[2]).unwrap();
+        assert_eq!(spi.release().starts, [0, 1_100]);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_clock_delays_the_remaining_gap() {
+        use crate::util::poll_to_completion;
+
+        let clock = SimClock::new();
+        let mut device = Throttled::new(FakeDevice::new(&clock), FakeDelay::new(&clock), 1_000)
+            .with_clock(&clock);
+
+        poll_to_completion(AsyncI2c::write(&mut device, 0x20, &[0])).unwrap();
+        clock.advance_ns(300);
+        poll_to_completion(AsyncSpiDevice::write(&mut device, &[0])).unwrap();
+        clock.advance_ns(2_000);
+        poll_to_completion(AsyncI2c::read(&mut device, 0x20, &mut [0])).unwrap();
+        assert_eq!(device.release().starts, [0, 1_100, 3_200]);
+    }
+}
@@ -0,0 +1,202 @@
+use core::sync::atomic::Ordering;
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{Error, ErrorKind, ErrorType, Read};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic::AtomicU32;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic::AtomicU32;
+
+/// UART receiver wrapper recovering from framing errors.
+///
+/// A framing error means that a stop bit was not found where expected: the receiver lost track of the
+/// frame boundaries, for example after a glitch on the line or a baud rate mismatch, and the following
+/// bytes are likely corrupt too. Every read is forwarded to the inner UART, and framing errors are
+/// counted. After `threshold` consecutive framing errors, the receive buffer is
+/// [flushed](UartFramingGuard::flush_rx), so that the reception resynchronizes on the next idle line.
+///
+/// The errors are still returned to the caller. The total count of framing errors is kept in an
+/// atomic, so that [`framing_error_count`](UartFramingGuard::framing_error_count) only needs a shared
+/// reference, and can be called from an interrupt handler while the driver owns the wrapper.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::uart::UartFramingGuard;
+/// use embedded_hal_nb::serial::Read;
+/// # use embedded_hal_nb::{nb, serial::{ErrorKind, ErrorType}};
+/// # struct Uart0(u32);
+/// # impl ErrorType for Uart0 { type Error = ErrorKind; }
+/// # impl Read for Uart0 {
+/// #     fn read(&mut self) -> nb::Result<u8, ErrorKind> {
+/// #         self.0 = self.0.checked_sub(1).ok_or(nb::Error::WouldBlock)?;
+/// #         Err(nb::Error::Other(ErrorKind::FrameFormat))
+/// #     }
+/// # }
+/// # let uart = Uart0(5);
+///
+/// // Flush the receive buffer after 3 framing errors in a row.
+/// let mut uart = UartFramingGuard::new(uart, 3);
+/// for _ in 0..3 {
+///     assert_eq!(uart.read(), Err(nb::Error::Other(ErrorKind::FrameFormat)));
+/// }
+/// assert_eq!(uart.framing_error_count(), 3);
+/// ```
+pub struct UartFramingGuard<T> {
+    uart: T,
+    threshold: u32,
+    consecutive: u32,
+    framing_errors: AtomicU32,
+}
+
+impl<T> UartFramingGuard<T> {
+    /// Create a new [`UartFramingGuard`], flushing the receive buffer after `threshold` consecutive
+    /// framing errors.
+    ///
+    /// A `threshold` of `0` or `1` flushes after every framing error.
+    #[inline]
+    pub const fn new(uart: T, threshold: u32) -> Self {
+        Self {
+            uart,
+            threshold,
+            consecutive: 0,
+            framing_errors: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns the number of framing errors since the wrapper was created or the count was reset.
+    ///
+    /// The count wraps around on overflow.
+    #[inline]
+    pub fn framing_error_count(&self) -> u32 {
+        self.framing_errors.load(Ordering::Relaxed)
+    }
+
+    /// Reset the count of framing errors to zero.
+    #[inline]
+    pub fn reset_framing_error_count(&self) {
+        self.framing_errors.store(0, Ordering::Relaxed);
+    }
+
+    /// Release the inner UART.
+    #[inline]
+    pub fn release(self) -> T {
+        self.uart
+    }
+}
+
+impl<T: Read<u8>> UartFramingGuard<T> {
+    /// Discard the received bytes, until the receive buffer is empty. Returns the number of bytes and
+    /// errors discarded.
+    ///
+    /// Errors are discarded like bytes: they are expected while the reception is desynchronized, and are
+    /// not counted as framing errors. The inner UART must clear its error flags when it returns an error,
+    /// otherwise this never returns.
+    pub fn flush_rx(&mut self) -> usize {
+        let mut discarded = 0;
+        while !matches!(self.uart.read(), Err(nb::Error::WouldBlock)) {
+            discarded += 1;
+        }
+        self.consecutive = 0;
+        discarded
+    }
+}
+
+impl<T: ErrorType> ErrorType for UartFramingGuard<T> {
+    type Error = T::Error;
+}
+
+impl<T: Read<u8>> Read<u8> for UartFramingGuard<T> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        match self.uart.read() {
+            Err(nb::Error::Other(e)) if e.kind() == ErrorKind::FrameFormat => {
+                self.framing_errors.fetch_add(1, Ordering::Relaxed);
+                self.consecutive += 1;
+                if self.consecutive >= self.threshold {
+                    self.flush_rx();
+                }
+                Err(nb::Error::Other(e))
+            }
+            Err(nb::Error::WouldBlock) => Err(nb::Error::WouldBlock),
+            result => {
+                self.consecutive = 0;
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// UART receiving `rx`, `None` meaning that the receive buffer is empty.
+    struct FakeUart {
+        rx: VecDeque<Option<Result<u8, ErrorKind>>>,
+    }
+
+    impl ErrorType for FakeUart {
+        type Error = ErrorKind;
+    }
+
+    impl Read for FakeUart {
+        fn read(&mut self) -> nb::Result<u8, ErrorKind> {
+            match self.rx.pop_front().flatten() {
+                Some(byte) => byte.map_err(nb::Error::Other),
+                None => Err(nb::Error::WouldBlock),
+            }
+        }
+    }
+
+    const FRAMING: Option<Result<u8, ErrorKind>> = Some(Err(ErrorKind::FrameFormat));
+
+    fn guard(
+        rx: impl IntoIterator<Item = Option<Result<u8, ErrorKind>>>,
+    ) -> UartFramingGuard<FakeUart> {
+        let uart = FakeUart {
+            rx: rx.into_iter().collect(),
+        };
+        UartFramingGuard::new(uart, 2)
+    }
+
+    #[test]
+    fn flushes_after_consecutive_framing_errors() {
+        let mut uart = guard([
+            FRAMING,
+            FRAMING,
+            Some(Ok(0xFF)),
+            Some(Err(ErrorKind::Parity)),
+            None,
+            Some(Ok(b'a')),
+        ]);
+        assert_eq!(uart.read(), Err(nb::Error::Other(ErrorKind::FrameFormat)));
+        assert_eq!(uart.read(), Err(nb::Error::Other(ErrorKind::FrameFormat)));
+        // The garbage up to the empty buffer was discarded.
+        assert_eq!(uart.read(), Ok(b'a'));
+        assert_eq!(uart.framing_error_count(), 2);
+    }
+
+    #[test]
+    fn valid_bytes_reset_the_consecutive_count() {
+        let mut uart = guard([FRAMING, Some(Ok(b'a')), FRAMING, Some(Ok(b'b'))]);
+        assert!(uart.read().is_err());
+        assert_eq!(uart.read(), Ok(b'a'));
+        assert!(uart.read().is_err());
+        assert_eq!(uart.read(), Ok(b'b'));
+        assert_eq!(uart.framing_error_count(), 2);
+
+        uart.reset_framing_error_count();
+        assert_eq!(uart.framing_error_count(), 0);
+    }
+
+    #[test]
+    fn flush_rx_discards_until_empty() {
+        let mut uart = guard([Some(Ok(1)), FRAMING, Some(Ok(2)), None, Some(Ok(3))]);
+        assert_eq!(uart.flush_rx(), 3);
+        assert_eq!(uart.framing_error_count(), 0);
+        assert_eq!(uart.read(), Ok(3));
+    }
+}
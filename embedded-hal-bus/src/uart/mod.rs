@@ -0,0 +1,12 @@
+//! Wrappers around UART receivers.
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "32"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "portable-atomic", target_has_atomic = "32")))
+)]
+mod framing;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "32"))]
+pub use framing::*;
+mod timeout;
+pub use timeout::*;
@@ -0,0 +1,213 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use embedded_hal::delay::Counter;
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{Error, ErrorKind, Read};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// UART receiver with a deadline on blocking reads.
+///
+/// Waiting indefinitely for bytes which may never come, because the other end was reset or the cable
+/// unplugged, stalls the firmware. [`read_within`](UartRxWithTimeout::read_within) instead polls the
+/// non-blocking [`Read`] implementation of the UART, checking a [`Counter`] between the attempts, and
+/// gives up once the timeout elapsed, like `select` with a timeout on POSIX systems.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::uart::{TimeoutError, UartRxWithTimeout};
+/// # use embedded_hal::delay::Counter;
+/// # use embedded_hal_nb::{nb, serial::{ErrorKind, ErrorType, Read}};
+/// # struct Uart0;
+/// # impl ErrorType for Uart0 { type Error = ErrorKind; }
+/// # impl Read for Uart0 {
+/// #     fn read(&mut self) -> nb::Result<u8, ErrorKind> { Ok(b'O') }
+/// # }
+/// # struct Timer;
+/// # impl Counter for Timer {
+/// #     fn start(&mut self) {}
+/// #     fn elapsed_ns(&self) -> u64 { 0 }
+/// # }
+/// # let (uart, timer) = (Uart0, Timer);
+///
+/// let mut uart = UartRxWithTimeout::new(uart, timer);
+/// let mut reply = [0; 4];
+/// // Wait at most 100 ms for the modem to reply.
+/// match uart.read_within(&mut reply, 100_000_000) {
+///     Ok(_) => { /* Handle the reply. */ }
+///     Err(TimeoutError::Timeout { read }) => { /* Only `reply[..read]` was received: retry. */ }
+///     Err(TimeoutError::Other(e)) => return Err(e),
+/// }
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct UartRxWithTimeout<U, C> {
+    uart: U,
+    counter: C,
+}
+
+impl<U, C> UartRxWithTimeout<U, C>
+where
+    U: Read<u8>,
+    C: Counter,
+{
+    /// Create a new [`UartRxWithTimeout`].
+    #[inline]
+    pub fn new(uart: U, counter: C) -> Self {
+        Self { uart, counter }
+    }
+
+    /// Release the inner UART and counter.
+    #[inline]
+    pub fn release(self) -> (U, C) {
+        (self.uart, self.counter)
+    }
+
+    /// Read bytes until `buf` is full, for at most `timeout_ns` nanoseconds.
+    ///
+    /// Returns `buf.len()` once `buf` is full. The timeout applies to the whole call, not to each byte.
+    /// A byte which is already received is always read, even if `timeout_ns` is 0.
+    ///
+    /// If the timeout elapses first, [`TimeoutError::Timeout`] tells how many bytes were stored at the
+    /// start of `buf`. If the UART fails, the bytes read before the error are stored at the start of
+    /// `buf`, but their number is not returned.
+    pub fn read_within(
+        &mut self,
+        buf: &mut [u8],
+        timeout_ns: u64,
+    ) -> Result<usize, TimeoutError<U::Error>> {
+        self.counter.start();
+        for (read, byte) in buf.iter_mut().enumerate() {
+            *byte = loop {
+                match self.uart.read() {
+                    Ok(word) => break word,
+                    Err(nb::Error::WouldBlock) => {
+                        if self.counter.has_exceeded(timeout_ns) {
+                            return Err(TimeoutError::Timeout { read });
+                        }
+                    }
+                    Err(nb::Error::Other(e)) => return Err(TimeoutError::Other(e)),
+                }
+            };
+        }
+        Ok(buf.len())
+    }
+}
+
+/// Error type for [`UartRxWithTimeout`] operations.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum TimeoutError<E> {
+    /// The timeout elapsed before the buffer was full.
+    Timeout {
+        /// Number of bytes stored at the start of the buffer.
+        read: usize,
+    },
+    /// The inner UART failed.
+    Other(E),
+}
+
+impl<E: Display> Display for TimeoutError<E> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Timeout { read } => write!(f, "UART read timed out after {read} bytes"),
+            Self::Other(e) => write!(f, "UART error: {e}"),
+        }
+    }
+}
+
+impl<E: Debug + Display> core::error::Error for TimeoutError<E> {}
+
+impl<E: Error> Error for TimeoutError<E> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Timeout { .. } => ErrorKind::Other,
+            Self::Other(e) => e.kind(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use embedded_hal_mock::sim::{FakeCounter, SimClock};
+    use embedded_hal_nb::serial::ErrorType;
+    use std::collections::VecDeque;
+
+    /// UART receiving the bytes of `rx`, `None` meaning no byte yet. Every attempt takes 10 ns.
+    struct FakeUart {
+        clock: SimClock,
+        rx: VecDeque<Option<Result<u8, ErrorKind>>>,
+    }
+
+    impl ErrorType for FakeUart {
+        type Error = ErrorKind;
+    }
+
+    impl Read for FakeUart {
+        fn read(&mut self) -> nb::Result<u8, ErrorKind> {
+            self.clock.advance_ns(10);
+            match self.rx.pop_front().flatten() {
+                Some(byte) => byte.map_err(nb::Error::Other),
+                None => Err(nb::Error::WouldBlock),
+            }
+        }
+    }
+
+    fn uart(
+        rx: impl IntoIterator<Item = Option<Result<u8, ErrorKind>>>,
+    ) -> UartRxWithTimeout<FakeUart, FakeCounter> {
+        let clock = SimClock::new();
+        let uart = FakeUart {
+            clock: clock.clone(),
+            rx: rx.into_iter().collect(),
+        };
+        UartRxWithTimeout::new(uart, FakeCounter::new(&clock))
+    }
+
+    #[test]
+    fn reads_until_full() {
+        let mut uart = uart([Some(Ok(1)), None, None, Some(Ok(2)), Some(Ok(3))]);
+        let mut buf = [0; 2];
+        assert_eq!(uart.read_within(&mut buf, 100), Ok(2));
+        assert_eq!(buf, [1, 2]);
+        // The next byte is left in the UART.
+        assert_eq!(uart.release().0.rx.len(), 1);
+    }
+
+    #[test]
+    fn times_out_with_partial_data() {
+        let mut uart = uart([Some(Ok(1)), None, None, None, Some(Ok(2))]);
+        let mut buf = [0; 2];
+        assert_eq!(
+            uart.read_within(&mut buf, 30),
+            Err(TimeoutError::Timeout { read: 1 })
+        );
+        assert_eq!(buf[0], 1);
+        // The deadline restarts on every call.
+        assert_eq!(uart.read_within(&mut buf[1..], 30), Ok(1));
+        assert_eq!(buf, [1, 2]);
+    }
+
+    #[test]
+    fn zero_timeout_reads_received_bytes() {
+        let mut uart = uart([Some(Ok(7)), None]);
+        let mut buf = [0; 2];
+        assert_eq!(
+            uart.read_within(&mut buf, 0),
+            Err(TimeoutError::Timeout { read: 1 })
+        );
+        assert_eq!(uart.read_within(&mut [], 0), Ok(0));
+    }
+
+    #[test]
+    fn uart_errors_are_returned() {
+        let mut uart = uart([None, Some(Err(ErrorKind::Parity))]);
+        let err = uart.read_within(&mut [0; 2], 100).unwrap_err();
+        assert_eq!(err, TimeoutError::Other(ErrorKind::Parity));
+        assert_eq!(err.kind(), ErrorKind::Parity);
+    }
+}
@@ -1,12 +1,17 @@
 //! Utilities shared by all bus types.
 
+use core::cell::RefCell;
 #[allow(unused_imports)]
 use core::cell::UnsafeCell;
 
 #[cfg(not(feature = "portable-atomic"))]
 use core::sync::atomic::AtomicBool;
+#[cfg(all(feature = "async", not(feature = "portable-atomic")))]
+use core::sync::atomic::AtomicU8;
 #[cfg(feature = "portable-atomic")]
 use portable_atomic::AtomicBool;
+#[cfg(all(feature = "async", feature = "portable-atomic"))]
+use portable_atomic::AtomicU8;
 
 #[cfg(any(feature = "portable-atomic", target_has_atomic = "8"))]
 /// Cell type used by [`spi::AtomicDevice`](crate::spi::AtomicDevice) and [`i2c::AtomicDevice`](crate::i2c::AtomicDevice).
@@ -16,6 +21,9 @@ use portable_atomic::AtomicBool;
 pub struct AtomicCell<BUS> {
     pub(crate) bus: UnsafeCell<BUS>,
     pub(crate) busy: AtomicBool,
+    /// Tasks waiting for the bus to be released, for async devices.
+    #[cfg(feature = "async")]
+    waiters: [WakerSlot; WAITERS],
 }
 #[cfg(any(feature = "portable-atomic", target_has_atomic = "8"))]
 unsafe impl<BUS: Send> Send for AtomicCell<BUS> {}
@@ -29,6 +37,466 @@ impl<BUS> AtomicCell<BUS> {
         Self {
             bus: UnsafeCell::new(bus),
             busy: AtomicBool::from(false),
+            #[cfg(feature = "async")]
+            waiters: [const { WakerSlot::new() }; WAITERS],
         }
     }
+
+    /// Try to lock the bus, returning `false` if it is already locked.
+    pub(crate) fn try_lock(&self) -> bool {
+        self.busy
+            .compare_exchange(
+                false,
+                true,
+                core::sync::atomic::Ordering::SeqCst,
+                core::sync::atomic::Ordering::SeqCst,
+            )
+            .is_ok()
+    }
+
+    /// Unlock the bus, waking the tasks waiting for it, if any.
+    pub(crate) fn unlock(&self) {
+        self.busy.store(false, core::sync::atomic::Ordering::SeqCst);
+        #[cfg(feature = "async")]
+        for slot in &self.waiters {
+            if let Some(waker) = slot.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Try to lock the bus, returning `None` if it is already locked.
+    #[cfg(feature = "async")]
+    pub(crate) fn try_lock_guard(&self) -> Option<AtomicGuard<'_, BUS>> {
+        self.try_lock().then(|| AtomicGuard { cell: self })
+    }
+
+    /// Wait until the bus can be locked, then lock it.
+    ///
+    /// Up to [`WAITERS`] tasks wait in a slot of their own, and are all woken up when the bus is
+    /// unlocked: one of them locks it, the others wait again. When all the slots are taken, the
+    /// other tasks are woken up right away, to be polled again until a slot is free.
+    #[cfg(feature = "async")]
+    pub(crate) async fn lock_async(&self) -> AtomicGuard<'_, BUS> {
+        // Frees the slot when dropped, including when the future is cancelled.
+        let mut waiter = Waiter {
+            slots: &self.waiters,
+            index: None,
+        };
+        core::future::poll_fn(|cx| {
+            if self.try_lock() {
+                return core::task::Poll::Ready(AtomicGuard { cell: self });
+            }
+            match waiter.slot() {
+                Some(slot) => slot.register(cx.waker()),
+                None => cx.waker().wake_by_ref(),
+            }
+            // The bus may have been unlocked before the waker was registered.
+            if self.try_lock() {
+                core::task::Poll::Ready(AtomicGuard { cell: self })
+            } else {
+                core::task::Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+/// Number of tasks which can wait for an [`AtomicCell`] without being polled again before it is unlocked.
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+const WAITERS: usize = 4;
+
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+const WAITING: u8 = 0;
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+const REGISTERING: u8 = 0b01;
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+const WAKING: u8 = 0b10;
+
+/// Waker of a task waiting for an [`AtomicCell`], registered and taken without critical sections.
+///
+/// This follows the `AtomicWaker` algorithm of the `futures` crate: `state` tells whether the waker is
+/// being registered or taken, so that the waker is never accessed concurrently, and a wake up racing with
+/// a registration is forwarded to the registering task.
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+struct WakerSlot {
+    /// Whether a waiting task owns the slot: only that task registers its waker.
+    claimed: AtomicBool,
+    state: AtomicU8,
+    waker: UnsafeCell<Option<core::task::Waker>>,
+}
+
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+impl WakerSlot {
+    const fn new() -> Self {
+        Self {
+            claimed: AtomicBool::new(false),
+            state: AtomicU8::new(WAITING),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Store `waker`, to be woken up by the next [`take`](Self::take). Only the owner of the slot calls this.
+    fn register(&self, waker: &core::task::Waker) {
+        use core::sync::atomic::Ordering::SeqCst;
+
+        match self
+            .state
+            .compare_exchange(WAITING, REGISTERING, SeqCst, SeqCst)
+            .unwrap_or_else(|state| state)
+        {
+            WAITING => {
+                // SAFETY: in the `REGISTERING` state, `take` does not access the waker.
+                let slot = unsafe { &mut *self.waker.get() };
+                match slot {
+                    Some(old) if old.will_wake(waker) => {}
+                    _ => *slot = Some(waker.clone()),
+                }
+                if self
+                    .state
+                    .compare_exchange(REGISTERING, WAITING, SeqCst, SeqCst)
+                    .is_err()
+                {
+                    // `take` was called during the registration, and left the waker to this task.
+                    // SAFETY: in the `REGISTERING | WAKING` state, `take` does not access the waker.
+                    let waker = unsafe { (*self.waker.get()).take() };
+                    self.state.store(WAITING, SeqCst);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            // The previous waker is being taken: this wake up may be for this task.
+            _ => waker.wake_by_ref(),
+        }
+    }
+
+    /// Take the registered waker, if any.
+    fn take(&self) -> Option<core::task::Waker> {
+        use core::sync::atomic::Ordering::SeqCst;
+
+        match self.state.fetch_or(WAKING, SeqCst) {
+            WAITING => {
+                // SAFETY: in the `WAKING` state, neither `register` nor another `take` access the waker.
+                let waker = unsafe { (*self.waker.get()).take() };
+                self.state.fetch_and(!WAKING, SeqCst);
+                waker
+            }
+            // A registration is in progress and will wake its task, or another call is taking the waker.
+            _ => None,
+        }
+    }
+}
+
+/// Slot of a task waiting for an [`AtomicCell`], freed when dropped.
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+struct Waiter<'a> {
+    slots: &'a [WakerSlot; WAITERS],
+    index: Option<usize>,
+}
+
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+impl Waiter<'_> {
+    /// Returns the slot of the task, claiming a free one the first time. Returns `None` if all are taken.
+    fn slot(&mut self) -> Option<&WakerSlot> {
+        use core::sync::atomic::Ordering::SeqCst;
+
+        if self.index.is_none() {
+            self.index = self.slots.iter().position(|slot| {
+                slot.claimed
+                    .compare_exchange(false, true, SeqCst, SeqCst)
+                    .is_ok()
+            });
+        }
+        self.index.map(|index| &self.slots[index])
+    }
+}
+
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        if let Some(index) = self.index {
+            let slot = &self.slots[index];
+            // Do not keep the task alive with a stale waker.
+            drop(slot.take());
+            slot.claimed
+                .store(false, core::sync::atomic::Ordering::SeqCst);
+        }
+    }
+}
+
+/// Lock on an [`AtomicCell`], unlocking it when dropped.
+///
+/// This makes sure the bus is unlocked even if an async transaction is cancelled.
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+pub(crate) struct AtomicGuard<'a, BUS> {
+    cell: &'a AtomicCell<BUS>,
+}
+
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+impl<BUS> AtomicGuard<'_, BUS> {
+    /// Access the locked bus.
+    pub(crate) fn bus(&mut self) -> &mut BUS {
+        // SAFETY: the bus is locked for as long as the guard exists.
+        unsafe { &mut *self.cell.bus.get() }
+    }
+}
+
+#[cfg(all(
+    feature = "async",
+    any(feature = "portable-atomic", target_has_atomic = "8")
+))]
+impl<BUS> Drop for AtomicGuard<'_, BUS> {
+    fn drop(&mut self) {
+        self.cell.unlock();
+    }
+}
+
+/// A mutex used to share a bus between several devices.
+///
+/// [`i2c::Device`](crate::i2c::Device) and [`spi::Device`](crate::spi::Device) are generic over this trait,
+/// so implementing it for a custom lock type (for example an RTOS mutex) is enough to obtain shared
+/// [`I2c`](embedded_hal::i2c::I2c) and [`SpiDevice`](embedded_hal::spi::SpiDevice) implementations.
+///
+/// Implementations are provided for `RefCell`, `critical_section::Mutex<RefCell<_>>` and, with the `std`
+/// feature, `std::sync::Mutex`. They are also provided for references to and, with the `alloc` feature,
+/// `Rc`s of any `BusMutex`.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::util::BusMutex;
+///
+/// /// A lock provided by an RTOS.
+/// # struct RtosMutex<T>(core::cell::RefCell<T>);
+/// # impl<T> RtosMutex<T> {
+/// #     fn take(&self) -> core::cell::RefMut<'_, T> { self.0.borrow_mut() }
+/// # }
+///
+/// impl<T> BusMutex for RtosMutex<T> {
+///     type Bus = T;
+///
+///     fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+///         // The guard releases the RTOS mutex when dropped.
+///         let mut guard = self.take();
+///         f(&mut guard)
+///     }
+/// }
+/// ```
+pub trait BusMutex {
+    /// The bus type protected by the mutex.
+    type Bus;
+
+    /// Lock the mutex, and call `f` with exclusive access to the bus.
+    ///
+    /// The mutex must stay locked until `f` returns.
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Bus) -> R) -> R;
+}
+
+impl<M: BusMutex + ?Sized> BusMutex for &M {
+    type Bus = M::Bus;
+
+    #[inline]
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Bus) -> R) -> R {
+        M::lock(self, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<M: BusMutex + ?Sized> BusMutex for alloc::rc::Rc<M> {
+    type Bus = M::Bus;
+
+    #[inline]
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Bus) -> R) -> R {
+        M::lock(self, f)
+    }
+}
+
+impl<T> BusMutex for RefCell<T> {
+    type Bus = T;
+
+    #[inline]
+    fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.borrow_mut())
+    }
+}
+
+impl<T> BusMutex for critical_section::Mutex<RefCell<T>> {
+    type Bus = T;
+
+    #[inline]
+    fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.borrow_ref_mut(cs)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> BusMutex for std::sync::Mutex<T> {
+    type Bus = T;
+
+    #[inline]
+    fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock().unwrap())
+    }
+}
+
+/// Storage for a bus shared with [`critical_section::Mutex`], initialized at runtime in a `static`.
+///
+/// Bus peripherals are usually obtained at runtime, so the `critical_section::Mutex<RefCell<_>>` shared by
+/// [`i2c::CriticalSectionDevice`](crate::i2c::CriticalSectionDevice) and
+/// [`spi::CriticalSectionDevice`](crate::spi::CriticalSectionDevice) instances can't be created in a `static`,
+/// and a local variable does not live long enough for devices moved to interrupt handlers or `'static` tasks.
+/// `StaticBus` is created empty in a `static`, and [`init`](StaticBus::init) moves the bus into it, returning
+/// a `'static` reference: this needs neither `unsafe` code nor a third-party crate.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal_bus::i2c;
+/// use embedded_hal_bus::util::StaticBus;
+/// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation};
+/// # pub struct I2c0;
+/// # impl hali2c::ErrorType for I2c0 {
+/// #     type Error = ErrorKind;
+/// # }
+/// # impl I2c for I2c0 {
+/// #     fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+/// #       Ok(())
+/// #     }
+/// # }
+/// # let i2c = I2c0;
+///
+/// static I2C_BUS: StaticBus<I2c0> = StaticBus::new();
+///
+/// let bus = I2C_BUS.init(i2c);
+/// let mut temperature_sensor = i2c::CriticalSectionDevice::new(bus);
+/// let mut pressure_sensor = i2c::CriticalSectionDevice::new(bus);
+/// temperature_sensor.write(0x48, &[0x00])?;
+/// pressure_sensor.write(0x77, &[0xD0])?;
+/// # Ok::<(), ErrorKind>(())
+/// ```
+pub struct StaticBus<T> {
+    initialized: critical_section::Mutex<core::cell::Cell<bool>>,
+    bus: UnsafeCell<core::mem::MaybeUninit<critical_section::Mutex<RefCell<T>>>>,
+}
+
+// SAFETY: the bus is only written once, by the `init` call which set `initialized` in a critical section,
+// and then only accessed through the `critical_section::Mutex`, which is `Sync` if `T` is `Send`.
+unsafe impl<T: Send> Sync for StaticBus<T> {}
+
+impl<T> StaticBus<T> {
+    /// Create a new, empty, [`StaticBus`].
+    pub const fn new() -> Self {
+        Self {
+            initialized: critical_section::Mutex::new(core::cell::Cell::new(false)),
+            bus: UnsafeCell::new(core::mem::MaybeUninit::uninit()),
+        }
+    }
+
+    /// Move `bus` into the [`StaticBus`], returning a reference to share it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`StaticBus`] is already initialized.
+    pub fn init(&'static self, bus: T) -> &'static critical_section::Mutex<RefCell<T>> {
+        let first = critical_section::with(|cs| !self.initialized.borrow(cs).replace(true));
+        assert!(first, "StaticBus is already initialized");
+        // SAFETY: `initialized` was false, so this is the only `init` call which reaches this point: no
+        // other reference to the bus exists. The bus is never moved or dropped, since `self` is `'static`.
+        unsafe { (*self.bus.get()).write(critical_section::Mutex::new(RefCell::new(bus))) }
+    }
+}
+
+impl<T> Default for StaticBus<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reason a [`std::sync::Mutex`] could not be locked without panicking.
+#[cfg(feature = "std")]
+pub(crate) enum LockError {
+    Poisoned,
+    WouldBlock,
+}
+
+/// Lock `mutex`, waiting for it if `wait`, and call `f` with exclusive access to the bus.
+#[cfg(feature = "std")]
+pub(crate) fn lock_mutex<T, R>(
+    mutex: &std::sync::Mutex<T>,
+    wait: bool,
+    f: impl FnOnce(&mut T) -> R,
+) -> Result<R, LockError> {
+    use std::sync::TryLockError;
+
+    let mut guard = if wait {
+        mutex.lock().map_err(|_| LockError::Poisoned)?
+    } else {
+        mutex.try_lock().map_err(|e| match e {
+            TryLockError::Poisoned(_) => LockError::Poisoned,
+            TryLockError::WouldBlock => LockError::WouldBlock,
+        })?
+    };
+    Ok(f(&mut guard))
+}
+
+/// Poll `future` to completion in a busy loop, without ever yielding to the executor.
+///
+/// This is used to run an async operation while holding a lock that cannot be held
+/// across an `.await` point, such as a critical section.
+#[cfg(feature = "async")]
+pub(crate) fn poll_to_completion<F: core::future::Future>(future: F) -> F::Output {
+    let mut future = core::pin::pin!(future);
+    let waker = noop_waker();
+    let mut cx = core::task::Context::from_waker(&waker);
+    loop {
+        if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) fn noop_waker() -> core::task::Waker {
+    use core::task::{RawWaker, RawWakerVTable, Waker};
+
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+    const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+    // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+    unsafe { Waker::from_raw(RAW) }
 }
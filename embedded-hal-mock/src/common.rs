@@ -0,0 +1,134 @@
+//! Expectations shared by all mocks.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// List of expected transactions, shared between the clones of a mock.
+#[derive(Debug)]
+pub(crate) struct Expectations<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+#[derive(Debug)]
+struct State<T> {
+    name: &'static str,
+    expected: VecDeque<T>,
+    performed: usize,
+}
+
+// Not derived, as that would require `T: Clone`.
+impl<T> Clone for Expectations<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Debug> Expectations<T> {
+    /// Create a new list of expectations for the mock named `name`.
+    pub(crate) fn new(name: &'static str, expected: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                name,
+                expected: expected.into_iter().collect(),
+                performed: 0,
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State<T>> {
+        // A failed assertion poisons the mutex: keep going, so that the clones still report errors.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Pop the next expected transaction, checking that `actual` matches it with `matches`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no expected transaction left, or if `actual` does not match.
+    #[track_caller]
+    pub(crate) fn next(&self, actual: impl Debug, matches: impl FnOnce(&T) -> bool) -> T {
+        let mut state = self.state();
+        let index = state.performed;
+        let Some(expected) = state.expected.pop_front() else {
+            let name = state.name;
+            drop(state);
+            panic!(
+                "{name}: unexpected transaction #{index}, all the expected transactions were performed\n\
+                 \x20 actual: {actual:?}"
+            );
+        };
+        if !matches(&expected) {
+            let name = state.name;
+            drop(state);
+            panic!(
+                "{name}: transaction #{index} does not match the expected one\n\
+                 expected: {expected:?}\n\
+                 \x20 actual: {actual:?}"
+            );
+        }
+        state.performed += 1;
+        expected
+    }
+
+    /// Pop the next expected transaction if `matches` returns `true` for it.
+    pub(crate) fn next_if(&self, matches: impl FnOnce(&T) -> bool) -> Option<T> {
+        let mut state = self.state();
+        if !matches(state.expected.front()?) {
+            return None;
+        }
+        state.performed += 1;
+        state.expected.pop_front()
+    }
+
+    /// Check that all the expected transactions were performed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some expected transactions are left.
+    #[track_caller]
+    pub(crate) fn assert_complete(&self) {
+        let state = self.state();
+        if !state.expected.is_empty() {
+            let message = format!(
+                "{name}: {left} expected transaction(s) not performed, after {performed} performed \
+                 transaction(s)\nremaining: {remaining:?}",
+                name = state.name,
+                left = state.expected.len(),
+                performed = state.performed,
+                remaining = state.expected,
+            );
+            drop(state);
+            panic!("{message}");
+        }
+    }
+}
+
+/// Description of an actual transaction which has no `Debug`-able value, printed as is on mismatch.
+pub(crate) struct Described(pub(crate) String);
+
+impl Debug for Described {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Poll `future` to completion, for the async tests of mocks which never return `Pending`.
+#[cfg(all(test, feature = "async"))]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::pin::pin;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+    const RAW: RawWaker = RawWaker::new(std::ptr::null(), &VTABLE);
+
+    // SAFETY: the vtable functions do nothing, so the contract of `RawWaker` is trivially upheld.
+    let waker = unsafe { Waker::from_raw(RAW) };
+    let mut future = pin!(future);
+    match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("mock futures complete on their first poll"),
+    }
+}
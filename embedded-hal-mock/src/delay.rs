@@ -0,0 +1,114 @@
+//! Mock [`DelayNs`] implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::delay::DelayNs;
+//! use embedded_hal_mock::delay::{MockDelay, Transaction};
+//!
+//! let mut delay = MockDelay::new([Transaction::DelayMs(10), Transaction::DelayUs(50)]);
+//!
+//! delay.delay_ms(10);
+//! delay.delay_us(50);
+//!
+//! delay.assert_complete();
+//! ```
+
+use embedded_hal::delay::DelayNs;
+
+use crate::common::Expectations;
+
+/// Expected delay.
+///
+/// Delays are expected in the unit they are requested in: a [`DelayNs::delay_ms`] call does not match
+/// [`Transaction::DelayUs`], even for the same duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transaction {
+    /// [`DelayNs::delay_ns`] with the given duration.
+    DelayNs(u32),
+    /// [`DelayNs::delay_us`] with the given duration.
+    DelayUs(u32),
+    /// [`DelayNs::delay_ms`] with the given duration.
+    DelayMs(u32),
+}
+
+/// Mock [`DelayNs`] implementation.
+///
+/// Each delay is checked against the next expected [`Transaction`], and returns immediately. Clones
+/// share the same expectations.
+///
+/// # Panics
+///
+/// Methods panic if the delay does not match the next expected transaction, or if all the expected
+/// transactions were already performed.
+#[derive(Debug, Clone)]
+pub struct MockDelay {
+    expectations: Expectations<Transaction>,
+}
+
+impl MockDelay {
+    /// Create a new [`MockDelay`], expecting the `expected` delays in order.
+    pub fn new(expected: impl IntoIterator<Item = Transaction>) -> Self {
+        Self {
+            expectations: Expectations::new("MockDelay", expected),
+        }
+    }
+
+    /// Check that all the expected delays were performed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some expected delays are left.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        self.expectations.assert_complete();
+    }
+
+    #[track_caller]
+    fn expect(&self, actual: Transaction) {
+        self.expectations
+            .next(actual, |expected| *expected == actual);
+    }
+}
+
+impl DelayNs for MockDelay {
+    #[track_caller]
+    fn delay_ns(&mut self, ns: u32) {
+        self.expect(Transaction::DelayNs(ns));
+    }
+
+    #[track_caller]
+    fn delay_us(&mut self, us: u32) {
+        self.expect(Transaction::DelayUs(us));
+    }
+
+    #[track_caller]
+    fn delay_ms(&mut self, ms: u32) {
+        self.expect(Transaction::DelayMs(ms));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_in_order() {
+        let mut delay = MockDelay::new([
+            Transaction::DelayNs(100),
+            Transaction::DelayUs(u32::MAX),
+            Transaction::DelayMs(1),
+        ]);
+        delay.delay_ns(100);
+        delay.delay_us(u32::MAX);
+        delay.delay_ms(1);
+        delay.assert_complete();
+    }
+
+    #[test]
+    #[should_panic(expected = "MockDelay: transaction #0 does not match the expected one")]
+    fn other_unit_panics() {
+        let mut delay = MockDelay::new([Transaction::DelayUs(1000)]);
+        delay.delay_ms(1);
+    }
+}
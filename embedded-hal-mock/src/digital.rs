@@ -0,0 +1,226 @@
+//! Mock [`InputPin`], [`OutputPin`] and [`FlexPin`] implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::digital::{InputPin, OutputPin, PinState};
+//! use embedded_hal_mock::digital::{MockPin, Transaction};
+//!
+//! let mut pin = MockPin::new([
+//!     Transaction::Set(PinState::Low),
+//!     Transaction::Get(PinState::High),
+//! ]);
+//!
+//! pin.set_low().unwrap();
+//! assert!(pin.is_high().unwrap());
+//!
+//! pin.assert_complete();
+//! ```
+
+use embedded_hal::digital::{
+    ErrorKind, ErrorType, FlexPin, InputPin, OutputPin, PinState, PullMode, StatefulOutputPin,
+};
+
+use crate::common::{Described, Expectations};
+
+/// Expected pin transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transaction {
+    /// [`OutputPin`] set to the given state.
+    Set(PinState),
+    /// [`InputPin`] read, answered with the given state.
+    ///
+    /// Both [`InputPin::is_high`] and [`InputPin::is_low`] match this transaction.
+    Get(PinState),
+    /// [`StatefulOutputPin`] read of the output state, answered with the given state.
+    ///
+    /// Both [`StatefulOutputPin::is_set_high`] and [`StatefulOutputPin::is_set_low`] match this transaction.
+    GetOutput(PinState),
+    /// [`StatefulOutputPin::toggle`].
+    Toggle,
+    /// [`FlexPin::set_as_input`] with the given pull resistor configuration.
+    SetAsInput(PullMode),
+    /// [`FlexPin::set_as_output`] with the given state.
+    SetAsOutput(PinState),
+}
+
+/// Mock [`InputPin`], [`OutputPin`], [`StatefulOutputPin`] and [`FlexPin`] implementation.
+///
+/// Each call is checked against the next expected [`Transaction`], and reads are answered with the
+/// expected states. Clones share the same expectations.
+///
+/// # Panics
+///
+/// Methods panic if the call does not match the next expected transaction, or if all the expected
+/// transactions were already performed.
+#[derive(Debug, Clone)]
+pub struct MockPin {
+    expectations: Expectations<Transaction>,
+}
+
+impl MockPin {
+    /// Create a new [`MockPin`], expecting the `expected` transactions in order.
+    pub fn new(expected: impl IntoIterator<Item = Transaction>) -> Self {
+        Self {
+            expectations: Expectations::new("MockPin", expected),
+        }
+    }
+
+    /// Check that all the expected transactions were performed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some expected transactions are left.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        self.expectations.assert_complete();
+    }
+
+    #[track_caller]
+    fn get(&self) -> PinState {
+        match self
+            .expectations
+            .next(Described("Get(_)".into()), |expected| {
+                matches!(expected, Transaction::Get(_))
+            }) {
+            Transaction::Get(state) => state,
+            _ => unreachable!(),
+        }
+    }
+
+    #[track_caller]
+    fn get_output(&self) -> PinState {
+        match self
+            .expectations
+            .next(Described("GetOutput(_)".into()), |expected| {
+                matches!(expected, Transaction::GetOutput(_))
+            }) {
+            Transaction::GetOutput(state) => state,
+            _ => unreachable!(),
+        }
+    }
+
+    #[track_caller]
+    fn expect(&self, actual: Transaction) {
+        self.expectations
+            .next(actual, |expected| *expected == actual);
+    }
+}
+
+impl ErrorType for MockPin {
+    type Error = ErrorKind;
+}
+
+impl InputPin for MockPin {
+    #[track_caller]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.get() == PinState::High)
+    }
+
+    #[track_caller]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.get() == PinState::Low)
+    }
+}
+
+impl OutputPin for MockPin {
+    #[track_caller]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.expect(Transaction::Set(PinState::Low));
+        Ok(())
+    }
+
+    #[track_caller]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.expect(Transaction::Set(PinState::High));
+        Ok(())
+    }
+}
+
+impl StatefulOutputPin for MockPin {
+    #[track_caller]
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.get_output() == PinState::High)
+    }
+
+    #[track_caller]
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.get_output() == PinState::Low)
+    }
+
+    #[track_caller]
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.expect(Transaction::Toggle);
+        Ok(())
+    }
+}
+
+impl FlexPin for MockPin {
+    #[track_caller]
+    fn set_as_input(&mut self, pull: PullMode) -> Result<(), Self::Error> {
+        self.expect(Transaction::SetAsInput(pull));
+        Ok(())
+    }
+
+    #[track_caller]
+    fn set_as_output(&mut self, state: PinState) -> Result<(), Self::Error> {
+        self.expect(Transaction::SetAsOutput(state));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_and_output() {
+        let mut pin = MockPin::new([
+            Transaction::Set(PinState::High),
+            Transaction::Get(PinState::Low),
+            Transaction::Get(PinState::Low),
+            Transaction::GetOutput(PinState::High),
+            Transaction::Toggle,
+        ]);
+        pin.set_state(PinState::High).unwrap();
+        assert!(!pin.is_high().unwrap());
+        assert!(pin.is_low().unwrap());
+        assert!(pin.is_set_high().unwrap());
+        pin.toggle().unwrap();
+        pin.assert_complete();
+    }
+
+    #[test]
+    fn flex_pin_direction_changes() {
+        let mut pin = MockPin::new([
+            Transaction::SetAsOutput(PinState::Low),
+            Transaction::SetAsInput(PullMode::Floating),
+            Transaction::Get(PinState::Low),
+        ]);
+        pin.set_as_output(PinState::Low).unwrap();
+        pin.set_as_input(PullMode::Floating).unwrap();
+        assert!(pin.is_low().unwrap());
+        pin.assert_complete();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match")]
+    fn wrong_pull_mode_panics() {
+        let mut pin = MockPin::new([Transaction::SetAsInput(PullMode::PullUp)]);
+        pin.set_as_input(PullMode::Floating).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "MockPin: transaction #0 does not match the expected one")]
+    fn wrong_state_panics() {
+        let mut pin = MockPin::new([Transaction::Set(PinState::High)]);
+        pin.set_low().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match")]
+    fn output_read_instead_of_input_read_panics() {
+        let mut pin = MockPin::new([Transaction::Get(PinState::High)]);
+        pin.is_set_high().unwrap();
+    }
+}
@@ -0,0 +1,323 @@
+//! Mock [`I2c`] implementation.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::i2c::I2c;
+//! use embedded_hal_mock::i2c::{MockI2c, Transaction};
+//!
+//! let mut i2c = MockI2c::new([
+//!     Transaction::write(0x48, [0x01, 0x60]),
+//!     Transaction::write_read(0x48, [0x00], [0x19, 0x80]),
+//! ]);
+//!
+//! i2c.write(0x48, &[0x01, 0x60]).unwrap();
+//! let mut temperature = [0; 2];
+//! i2c.write_read(0x48, &[0x00], &mut temperature).unwrap();
+//! assert_eq!(temperature, [0x19, 0x80]);
+//!
+//! i2c.assert_complete();
+//! ```
+//!
+//! With the `async` feature, [`MockI2c`] also implements the async
+//! [`I2c`](embedded_hal_async::i2c::I2c) trait, checked against the same expectations.
+
+use embedded_hal::i2c::{self, ErrorKind, ErrorType, I2c, SevenBitAddress};
+
+use crate::common::Expectations;
+
+/// Operation of an expected I2C [`Transaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// Write of the given bytes.
+    Write(Vec<u8>),
+    /// Read, answered with the given bytes.
+    ///
+    /// The read buffer must have the same length as the response.
+    Read(Vec<u8>),
+}
+
+/// Expected I2C transaction.
+///
+/// Each call to an [`I2c`] method is one transaction: for example, [`I2c::write_read`] is a transaction
+/// with a write and a read operation, which can be built with [`Transaction::write_read`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    address: SevenBitAddress,
+    operations: Vec<Operation>,
+    error: Option<ErrorKind>,
+}
+
+impl Transaction {
+    /// Transaction writing `bytes` to `address`.
+    pub fn write(address: SevenBitAddress, bytes: impl Into<Vec<u8>>) -> Self {
+        Self::new(address, vec![Operation::Write(bytes.into())])
+    }
+
+    /// Transaction reading from `address`, answered with `response`.
+    pub fn read(address: SevenBitAddress, response: impl Into<Vec<u8>>) -> Self {
+        Self::new(address, vec![Operation::Read(response.into())])
+    }
+
+    /// Transaction writing `bytes` to `address`, then reading from it, answered with `response`.
+    pub fn write_read(
+        address: SevenBitAddress,
+        bytes: impl Into<Vec<u8>>,
+        response: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self::new(
+            address,
+            vec![
+                Operation::Write(bytes.into()),
+                Operation::Read(response.into()),
+            ],
+        )
+    }
+
+    /// Transaction with arbitrary `operations`, as performed by [`I2c::transaction`].
+    pub fn new(address: SevenBitAddress, operations: Vec<Operation>) -> Self {
+        Self {
+            address,
+            operations,
+            error: None,
+        }
+    }
+
+    /// Make the transaction fail with `error`.
+    ///
+    /// The transaction is still checked, and its reads answered, before the error is returned.
+    pub fn with_error(mut self, error: ErrorKind) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    fn matches(&self, address: SevenBitAddress, operations: &[i2c::Operation<'_>]) -> bool {
+        self.address == address
+            && self.operations.len() == operations.len()
+            && self
+                .operations
+                .iter()
+                .zip(operations)
+                .all(|(expected, actual)| match (expected, actual) {
+                    (Operation::Write(expected), i2c::Operation::Write(actual)) => {
+                        expected == actual
+                    }
+                    (Operation::Read(response), i2c::Operation::Read(buf)) => {
+                        response.len() == buf.len()
+                    }
+                    _ => false,
+                })
+    }
+}
+
+/// Transaction performed on a [`MockI2c`], as printed on mismatch.
+#[derive(Debug)]
+#[allow(dead_code)] // Only used through `Debug`.
+struct Actual<'a> {
+    address: SevenBitAddress,
+    operations: Vec<ActualOperation<'a>>,
+}
+
+#[derive(Debug)]
+#[allow(dead_code)] // Only used through `Debug`.
+enum ActualOperation<'a> {
+    Write(&'a [u8]),
+    Read { len: usize },
+}
+
+/// Mock [`I2c`] implementation.
+///
+/// Each transaction is checked against the next expected [`Transaction`], and reads are answered with the
+/// expected responses. Clones share the same expectations.
+///
+/// # Panics
+///
+/// Methods panic if the transaction does not match the next expected one, or if all the expected
+/// transactions were already performed.
+#[derive(Debug, Clone)]
+pub struct MockI2c {
+    expectations: Expectations<Transaction>,
+}
+
+impl MockI2c {
+    /// Create a new [`MockI2c`], expecting the `expected` transactions in order.
+    pub fn new(expected: impl IntoIterator<Item = Transaction>) -> Self {
+        Self {
+            expectations: Expectations::new("MockI2c", expected),
+        }
+    }
+
+    /// Check that all the expected transactions were performed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some expected transactions are left.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        self.expectations.assert_complete();
+    }
+}
+
+impl ErrorType for MockI2c {
+    type Error = ErrorKind;
+}
+
+impl I2c for MockI2c {
+    #[track_caller]
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let actual = Actual {
+            address,
+            operations: operations
+                .iter()
+                .map(|op| match op {
+                    i2c::Operation::Write(bytes) => ActualOperation::Write(bytes),
+                    i2c::Operation::Read(buf) => ActualOperation::Read { len: buf.len() },
+                })
+                .collect(),
+        };
+        let expected = self
+            .expectations
+            .next(actual, |expected| expected.matches(address, operations));
+
+        for (expected, actual) in expected.operations.iter().zip(operations) {
+            if let (Operation::Read(response), i2c::Operation::Read(buf)) = (expected, actual) {
+                buf.copy_from_slice(response);
+            }
+        }
+        expected.error.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for MockI2c {
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        I2c::transaction(self, address, operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_reads() {
+        let mut i2c = MockI2c::new([
+            Transaction::read(0x10, [1, 2]),
+            Transaction::new(
+                0x11,
+                vec![
+                    Operation::Read(vec![3]),
+                    Operation::Write(vec![4]),
+                    Operation::Read(vec![5]),
+                ],
+            ),
+        ]);
+        let mut a = [0; 2];
+        i2c.read(0x10, &mut a).unwrap();
+        assert_eq!(a, [1, 2]);
+
+        let (mut b, mut c) = ([0], [0]);
+        i2c.transaction(
+            0x11,
+            &mut [
+                i2c::Operation::Read(&mut b),
+                i2c::Operation::Write(&[4]),
+                i2c::Operation::Read(&mut c),
+            ],
+        )
+        .unwrap();
+        assert_eq!((b, c), ([3], [5]));
+        i2c.assert_complete();
+    }
+
+    #[test]
+    fn injected_error() {
+        let nack = ErrorKind::NoAcknowledge(i2c::NoAcknowledgeSource::Data);
+        let mut i2c = MockI2c::new([
+            Transaction::write(0x10, [1, 2]).with_error(nack),
+            Transaction::read(0x10, [3]).with_error(ErrorKind::Bus),
+        ]);
+        assert_eq!(i2c.write(0x10, &[1, 2]), Err(nack));
+        let mut buf = [0];
+        assert_eq!(i2c.read(0x10, &mut buf), Err(ErrorKind::Bus));
+        assert_eq!(buf, [3]);
+        i2c.assert_complete();
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_transactions() {
+        use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+        let mut i2c = MockI2c::new([
+            Transaction::write_read(0x48, [0x00], [0x19, 0x80]),
+            Transaction::write(0x48, [0x01]).with_error(ErrorKind::Bus),
+        ]);
+        let mut temperature = [0; 2];
+        crate::common::block_on(AsyncI2c::write_read(
+            &mut i2c,
+            0x48,
+            &[0x00],
+            &mut temperature,
+        ))
+        .unwrap();
+        assert_eq!(temperature, [0x19, 0x80]);
+        assert_eq!(
+            crate::common::block_on(AsyncI2c::write(&mut i2c, 0x48, &[0x01])),
+            Err(ErrorKind::Bus)
+        );
+        i2c.assert_complete();
+    }
+
+    #[test]
+    fn clones_share_expectations() {
+        let i2c = MockI2c::new([Transaction::write(0x10, [1])]);
+        let mut clone = i2c.clone();
+        clone.write(0x10, &[1]).unwrap();
+        i2c.assert_complete();
+    }
+
+    #[test]
+    #[should_panic(expected = "MockI2c: transaction #1 does not match the expected one")]
+    fn wrong_data_panics() {
+        let mut i2c = MockI2c::new([Transaction::write(0x10, [1]), Transaction::write(0x10, [2])]);
+        i2c.write(0x10, &[1]).unwrap();
+        i2c.write(0x10, &[3]).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match")]
+    fn wrong_address_panics() {
+        let mut i2c = MockI2c::new([Transaction::write(0x10, [1])]);
+        i2c.write(0x11, &[1]).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match")]
+    fn wrong_read_length_panics() {
+        let mut i2c = MockI2c::new([Transaction::read(0x10, [1, 2])]);
+        i2c.read(0x10, &mut [0]).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "all the expected transactions were performed")]
+    fn unexpected_transaction_panics() {
+        let mut i2c = MockI2c::new([]);
+        i2c.write(0x10, &[1]).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "MockI2c: 1 expected transaction(s) not performed")]
+    fn incomplete_panics() {
+        let i2c = MockI2c::new([Transaction::write(0x10, [1])]);
+        i2c.assert_complete();
+    }
+}
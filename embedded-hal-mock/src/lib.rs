@@ -0,0 +1,11 @@
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+
+mod common;
+
+pub mod delay;
+pub mod digital;
+pub mod i2c;
+pub mod rng;
+pub mod sim;
+pub mod spi;
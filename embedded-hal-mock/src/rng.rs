@@ -0,0 +1,153 @@
+//! Deterministic [`Rng`] implementation.
+//!
+//! Unlike the mocks, [`FakeRng`] does not check a list of expected transactions: it generates a
+//! pseudo-random sequence from a seed, so that tests of code consuming random bytes are
+//! reproducible. Errors can be injected to test how the code handles a failing generator.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::rng::{ErrorKind, Rng};
+//! use embedded_hal_mock::rng::FakeRng;
+//!
+//! let mut rng = FakeRng::new(42).with_errors([ErrorKind::NotReady]);
+//! let mut nonce = [0; 12];
+//!
+//! assert_eq!(rng.fill_bytes(&mut nonce), Err(ErrorKind::NotReady));
+//! rng.fill_bytes(&mut nonce).unwrap();
+//!
+//! // The same seed generates the same bytes.
+//! let mut expected = [0; 12];
+//! FakeRng::new(42).fill_bytes(&mut expected).unwrap();
+//! assert_eq!(nonce, expected);
+//! ```
+
+use std::collections::VecDeque;
+
+use embedded_hal::rng::{ErrorKind, ErrorType, Rng};
+
+/// Deterministic [`Rng`] implementation.
+///
+/// The bytes are generated by a SplitMix64 generator: they are reproducible from the seed, but
+/// not suitable for cryptographic use. The bytes of a call do not depend on how the previous
+/// calls split their buffers: filling 4 then 8 bytes gives the same bytes as filling 12.
+///
+/// With the `async` feature, the async [`Rng`](embedded_hal_async::rng::Rng) trait is also
+/// implemented, and completes immediately.
+#[derive(Debug, Clone)]
+pub struct FakeRng {
+    state: u64,
+    word: [u8; 8],
+    used: usize,
+    errors: VecDeque<ErrorKind>,
+}
+
+impl FakeRng {
+    /// Create a new [`FakeRng`], generating bytes from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            word: [0; 8],
+            used: 8,
+            errors: VecDeque::new(),
+        }
+    }
+
+    /// Make the next calls fail, one for each error of `errors` in order.
+    ///
+    /// Failed calls do not consume bytes from the sequence.
+    pub fn with_errors(mut self, errors: impl IntoIterator<Item = ErrorKind>) -> Self {
+        self.errors.extend(errors);
+        self
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        if self.used == self.word.len() {
+            self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            self.word = (z ^ (z >> 31)).to_le_bytes();
+            self.used = 0;
+        }
+        self.used += 1;
+        self.word[self.used - 1]
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        if let Some(error) = self.errors.pop_front() {
+            return Err(error);
+        }
+        buf.iter_mut().for_each(|b| *b = self.next_byte());
+        Ok(())
+    }
+}
+
+impl ErrorType for FakeRng {
+    type Error = ErrorKind;
+}
+
+impl Rng for FakeRng {
+    fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.fill(buf)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::rng::Rng for FakeRng {
+    async fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.fill(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_does_not_depend_on_buffer_sizes() {
+        let mut whole = [0; 20];
+        FakeRng::new(7).fill_bytes(&mut whole).unwrap();
+
+        let mut rng = FakeRng::new(7);
+        let mut parts = [0; 20];
+        let (a, rest) = parts.split_at_mut(3);
+        let (b, c) = rest.split_at_mut(9);
+        for part in [a, b, c] {
+            rng.fill_bytes(part).unwrap();
+        }
+        assert_eq!(parts, whole);
+
+        let mut other = [0; 20];
+        FakeRng::new(8).fill_bytes(&mut other).unwrap();
+        assert_ne!(other, whole);
+    }
+
+    #[test]
+    fn injected_errors_do_not_consume_bytes() {
+        let mut rng = FakeRng::new(1).with_errors([ErrorKind::HardwareFault, ErrorKind::NotReady]);
+        let mut buf = [0; 4];
+        assert_eq!(rng.fill_bytes(&mut buf), Err(ErrorKind::HardwareFault));
+        assert_eq!(rng.fill_bytes(&mut buf), Err(ErrorKind::NotReady));
+        rng.fill_bytes(&mut buf).unwrap();
+
+        let mut expected = [0; 4];
+        FakeRng::new(1).fill_bytes(&mut expected).unwrap();
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_fill_bytes() {
+        let mut buf = [0; 4];
+        crate::common::block_on(embedded_hal_async::rng::Rng::fill_bytes(
+            &mut FakeRng::new(1),
+            &mut buf,
+        ))
+        .unwrap();
+
+        let mut expected = [0; 4];
+        FakeRng::new(1).fill_bytes(&mut expected).unwrap();
+        assert_eq!(buf, expected);
+    }
+}
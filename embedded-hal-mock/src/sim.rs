@@ -0,0 +1,633 @@
+//! Simulated time, for testing code combining pins and delays.
+//!
+//! Unlike the mocks, these fakes do not check a list of expected transactions. [`SimClock`] is a
+//! virtual time source: [`FakeDelay`] advances it instead of sleeping, [`FakeCounter`] measures time
+//! on it, [`FakeTimer`] expires on it, and [`FakePin`] follows a schedule of levels on it, while
+//! recording the levels it is driven to.
+//! Tests can then assert on *when* things happened.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::delay::DelayNs;
+//! use embedded_hal::digital::{InputPin, OutputPin, PinState};
+//! use embedded_hal_mock::sim::{FakeDelay, FakePin, SimClock, Transition};
+//!
+//! let clock = SimClock::new();
+//! let mut delay = FakeDelay::new(&clock);
+//! let mut cs = FakePin::new(&clock, PinState::High);
+//! // The device pulls its busy line low after 3 µs.
+//! let mut busy = FakePin::new(&clock, PinState::High).with_schedule([(3_000, PinState::Low)]);
+//!
+//! cs.set_low().unwrap();
+//! delay.delay_us(5);
+//! assert!(busy.is_low().unwrap());
+//! cs.set_high().unwrap();
+//!
+//! assert_eq!(
+//!     cs.transitions(),
+//!     [
+//!         Transition { at_ns: 0, state: PinState::Low },
+//!         Transition { at_ns: 5_000, state: PinState::High },
+//!     ]
+//! );
+//! ```
+
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use embedded_hal::delay::{Counter, DelayNs};
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin, PinState, StatefulOutputPin};
+use embedded_hal::time::{Instant, MonotonicClock};
+use embedded_hal::timer::{self, CountDown, ErrorKind, Periodic};
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A failed assertion poisons the mutex: keep going, so that the clones still work.
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Virtual time source, in nanoseconds, starting at 0.
+///
+/// Time only passes when it is advanced, by [`advance_ns`](SimClock::advance_ns), a [`FakeDelay`] or
+/// a [`FakePin`] waiting for an edge. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct SimClock {
+    now_ns: Arc<Mutex<u64>>,
+}
+
+impl SimClock {
+    /// Create a new [`SimClock`], at time 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current time, in nanoseconds.
+    pub fn now_ns(&self) -> u64 {
+        *lock(&self.now_ns)
+    }
+
+    /// Advance the time by `ns` nanoseconds.
+    pub fn advance_ns(&self, ns: u64) {
+        *lock(&self.now_ns) += ns;
+    }
+}
+
+impl MonotonicClock for SimClock {
+    fn now(&self) -> Instant {
+        Instant::from_nanos(self.now_ns())
+    }
+}
+
+/// [`DelayNs`] implementation advancing a [`SimClock`] instead of sleeping.
+#[derive(Debug, Clone)]
+pub struct FakeDelay {
+    clock: SimClock,
+}
+
+impl FakeDelay {
+    /// Create a new [`FakeDelay`], advancing `clock`.
+    pub fn new(clock: &SimClock) -> Self {
+        Self {
+            clock: clock.clone(),
+        }
+    }
+}
+
+impl DelayNs for FakeDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance_ns(u64::from(ns));
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for FakeDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance_ns(u64::from(ns));
+    }
+}
+
+/// [`Counter`] implementation measuring the time elapsed on a [`SimClock`].
+#[derive(Debug, Clone)]
+pub struct FakeCounter {
+    clock: SimClock,
+    started_ns: u64,
+}
+
+impl FakeCounter {
+    /// Create a new [`FakeCounter`] on `clock`, started now.
+    pub fn new(clock: &SimClock) -> Self {
+        Self {
+            clock: clock.clone(),
+            started_ns: clock.now_ns(),
+        }
+    }
+}
+
+impl Counter for FakeCounter {
+    fn start(&mut self) {
+        self.started_ns = self.clock.now_ns();
+    }
+
+    fn elapsed_ns(&self) -> u64 {
+        self.clock.now_ns() - self.started_ns
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Countdown {
+    /// End of the current period, or time of expiry of a one-shot timer.
+    deadline_ns: u64,
+    /// Period of a periodic timer.
+    period_ns: Option<u64>,
+}
+
+/// [`CountDown`] and [`Periodic`] implementation expiring on a [`SimClock`].
+///
+/// Waiting advances the clock to the expiry of the timer. The errors are the [`ErrorKind`]s
+/// documented by the traits: [`ErrorKind::NotRunning`] when the timer is not running, and
+/// [`ErrorKind::UnsupportedDuration`] for a period of 0 or a deadline overflowing the clock.
+///
+/// With the `async` feature, the async [`CountDown`](embedded_hal_async::timer::CountDown) and
+/// [`Periodic`](embedded_hal_async::timer::Periodic) traits are also implemented.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::timer::{CountDown, Periodic};
+/// use embedded_hal_mock::sim::{FakeTimer, SimClock};
+///
+/// let clock = SimClock::new();
+/// let mut timer = FakeTimer::new(&clock);
+/// timer.start_periodic(1_000).unwrap();
+/// clock.advance_ns(1_500);
+/// timer.wait().unwrap();
+/// timer.wait().unwrap();
+/// // The periods do not drift.
+/// assert_eq!(clock.now_ns(), 2_000);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FakeTimer {
+    clock: SimClock,
+    countdown: Option<Countdown>,
+}
+
+impl FakeTimer {
+    /// Create a new [`FakeTimer`] on `clock`, not running.
+    pub fn new(clock: &SimClock) -> Self {
+        Self {
+            clock: clock.clone(),
+            countdown: None,
+        }
+    }
+
+    /// Returns the time at which the timer expires, or the current period ends, in nanoseconds, or
+    /// `None` if the timer is not running.
+    pub fn deadline_ns(&self) -> Option<u64> {
+        self.countdown.map(|c| c.deadline_ns)
+    }
+
+    fn run(&mut self, duration_ns: u64, period_ns: Option<u64>) -> Result<(), ErrorKind> {
+        self.countdown = None;
+        let deadline_ns = self
+            .clock
+            .now_ns()
+            .checked_add(duration_ns)
+            .ok_or(ErrorKind::UnsupportedDuration)?;
+        self.countdown = Some(Countdown {
+            deadline_ns,
+            period_ns,
+        });
+        Ok(())
+    }
+
+    /// Returns `true` if the timer expired, moving a periodic timer to the first period ending after now.
+    fn observe(&mut self) -> Result<bool, ErrorKind> {
+        let now = self.clock.now_ns();
+        let countdown = self.countdown.as_mut().ok_or(ErrorKind::NotRunning)?;
+        if now < countdown.deadline_ns {
+            return Ok(false);
+        }
+        if let Some(period_ns) = countdown.period_ns {
+            let ended = (now - countdown.deadline_ns) / period_ns + 1;
+            countdown.deadline_ns += ended * period_ns;
+        }
+        Ok(true)
+    }
+
+    fn wait_expired(&mut self) -> Result<(), ErrorKind> {
+        let deadline_ns = self.deadline_ns().ok_or(ErrorKind::NotRunning)?;
+        let now = self.clock.now_ns();
+        if now < deadline_ns {
+            self.clock.advance_ns(deadline_ns - now);
+        }
+        self.observe().map(drop)
+    }
+
+    fn stop(&mut self) -> Result<(), ErrorKind> {
+        self.countdown.take().map(drop).ok_or(ErrorKind::NotRunning)
+    }
+
+    fn run_periodic(&mut self, period_ns: u64) -> Result<(), ErrorKind> {
+        if period_ns == 0 {
+            self.countdown = None;
+            return Err(ErrorKind::UnsupportedDuration);
+        }
+        self.run(period_ns, Some(period_ns))
+    }
+}
+
+impl timer::ErrorType for FakeTimer {
+    type Error = ErrorKind;
+}
+
+impl CountDown for FakeTimer {
+    fn start(&mut self, duration_ns: u64) -> Result<(), Self::Error> {
+        self.run(duration_ns, None)
+    }
+
+    fn is_expired(&mut self) -> Result<bool, Self::Error> {
+        self.observe()
+    }
+
+    fn wait(&mut self) -> Result<(), Self::Error> {
+        self.wait_expired()
+    }
+
+    fn cancel(&mut self) -> Result<(), Self::Error> {
+        self.stop()
+    }
+}
+
+impl Periodic for FakeTimer {
+    fn start_periodic(&mut self, period_ns: u64) -> Result<(), Self::Error> {
+        self.run_periodic(period_ns)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::timer::CountDown for FakeTimer {
+    fn start(&mut self, duration_ns: u64) -> Result<(), Self::Error> {
+        self.run(duration_ns, None)
+    }
+
+    fn is_expired(&mut self) -> Result<bool, Self::Error> {
+        self.observe()
+    }
+
+    async fn wait(&mut self) -> Result<(), Self::Error> {
+        self.wait_expired()
+    }
+
+    fn cancel(&mut self) -> Result<(), Self::Error> {
+        self.stop()
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::timer::Periodic for FakeTimer {
+    fn start_periodic(&mut self, period_ns: u64) -> Result<(), Self::Error> {
+        self.run_periodic(period_ns)
+    }
+}
+
+/// Level of a [`FakePin`] from a point in time on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// Time of the transition, in nanoseconds.
+    pub at_ns: u64,
+    /// Level of the pin from then on.
+    pub state: PinState,
+}
+
+#[derive(Debug)]
+struct Schedule {
+    /// Levels of the pin, sorted by time. Levels scheduled at the same time apply in order.
+    levels: Vec<Transition>,
+    /// Levels the pin was driven to.
+    driven: Vec<Transition>,
+}
+
+/// [`InputPin`], [`OutputPin`] and [`StatefulOutputPin`] implementation following a schedule of
+/// levels on a [`SimClock`].
+///
+/// The level of the pin at any time is the last one scheduled at or before that time. Driving the
+/// pin as an output schedules its new level at the current time, and records it in the
+/// [`transitions`](FakePin::transitions) log. Clones share the same schedule and log.
+///
+/// With the `async` feature, [`Wait`](embedded_hal_async::digital::Wait) is also implemented, by
+/// advancing the clock to the next scheduled level or edge waited for.
+///
+/// # Panics
+///
+/// Waiting panics if the waited for level or edge is never scheduled, as it would wait forever.
+#[derive(Debug, Clone)]
+pub struct FakePin {
+    clock: SimClock,
+    schedule: Arc<Mutex<Schedule>>,
+}
+
+impl FakePin {
+    /// Create a new [`FakePin`] on `clock`, at the `initial` level.
+    pub fn new(clock: &SimClock, initial: PinState) -> Self {
+        Self {
+            clock: clock.clone(),
+            schedule: Arc::new(Mutex::new(Schedule {
+                levels: vec![Transition {
+                    at_ns: 0,
+                    state: initial,
+                }],
+                driven: Vec::new(),
+            })),
+        }
+    }
+
+    /// Schedule the `(at_ns, state)` levels of the pin, see [`schedule`](FakePin::schedule).
+    pub fn with_schedule(self, schedule: impl IntoIterator<Item = (u64, PinState)>) -> Self {
+        for (at_ns, state) in schedule {
+            self.schedule(at_ns, state);
+        }
+        self
+    }
+
+    /// Schedule the pin to be at `state` from `at_ns` nanoseconds on.
+    ///
+    /// This is how the outside world, e.g. the device a driver talks to, changes the level of the pin.
+    pub fn schedule(&self, at_ns: u64, state: PinState) {
+        let levels = &mut lock(&self.schedule).levels;
+        let index = levels.partition_point(|t| t.at_ns <= at_ns);
+        levels.insert(index, Transition { at_ns, state });
+    }
+
+    /// Returns the current level of the pin.
+    pub fn level(&self) -> PinState {
+        let now = self.clock.now_ns();
+        let levels = &lock(&self.schedule).levels;
+        let index = levels.partition_point(|t| t.at_ns <= now);
+        levels[index - 1].state
+    }
+
+    /// Returns the levels the pin was driven to as an output, with their times, in order.
+    pub fn transitions(&self) -> Vec<Transition> {
+        lock(&self.schedule).driven.clone()
+    }
+
+    fn drive(&mut self, state: PinState) {
+        let at_ns = self.clock.now_ns();
+        self.schedule(at_ns, state);
+        lock(&self.schedule)
+            .driven
+            .push(Transition { at_ns, state });
+    }
+
+    /// Advance the clock to the first change of level after now to a level for which `wanted`
+    /// returns `true`.
+    #[cfg(feature = "async")]
+    #[track_caller]
+    fn wait_for(&mut self, what: &str, wanted: impl Fn(PinState) -> bool) {
+        let now = self.clock.now_ns();
+        let mut level = self.level();
+        let at_ns = {
+            let levels = &lock(&self.schedule).levels;
+            let start = levels.partition_point(|t| t.at_ns <= now);
+            levels[start..].iter().find_map(|t| {
+                let found = t.state != level && wanted(t.state);
+                level = t.state;
+                found.then_some(t.at_ns)
+            })
+        };
+        let Some(at_ns) = at_ns else {
+            panic!("FakePin: waiting forever for {what}, none is scheduled after {now} ns");
+        };
+        self.clock.advance_ns(at_ns - now);
+    }
+}
+
+impl ErrorType for FakePin {
+    type Error = Infallible;
+}
+
+impl InputPin for FakePin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.level() == PinState::High)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.level() == PinState::Low)
+    }
+}
+
+impl OutputPin for FakePin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.drive(PinState::Low);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.drive(PinState::High);
+        Ok(())
+    }
+}
+
+impl StatefulOutputPin for FakePin {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        self.is_high()
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_low()
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::digital::Wait for FakePin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        if self.level() != PinState::High {
+            self.wait_for("a high level", |to| to == PinState::High);
+        }
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        if self.level() != PinState::Low {
+            self.wait_for("a low level", |to| to == PinState::Low);
+        }
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for("a rising edge", |to| to == PinState::High);
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for("a falling edge", |to| to == PinState::Low);
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for("an edge", |_| true);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_follows_its_schedule() {
+        let clock = SimClock::new();
+        let mut delay = FakeDelay::new(&clock);
+        let mut pin = FakePin::new(&clock, PinState::Low)
+            .with_schedule([(2_000, PinState::Low), (1_000, PinState::High)]);
+
+        assert!(pin.is_low().unwrap());
+        delay.delay_ns(1_000);
+        assert!(pin.is_high().unwrap());
+        delay.delay_us(1);
+        assert!(pin.is_low().unwrap());
+        assert_eq!(clock.now(), Instant::from_nanos(2_000));
+    }
+
+    #[test]
+    fn counter_measures_from_its_start() {
+        let clock = SimClock::new();
+        clock.advance_ns(1_000);
+        let mut counter = FakeCounter::new(&clock);
+        clock.advance_ns(300);
+        assert_eq!(counter.elapsed_ns(), 300);
+        assert!(counter.has_exceeded(300));
+        counter.start();
+        assert_eq!(counter.elapsed_ns(), 0);
+    }
+
+    #[test]
+    fn driven_levels_are_logged() {
+        let clock = SimClock::new();
+        let mut pin = FakePin::new(&clock, PinState::Low).with_schedule([(500, PinState::Low)]);
+        let mut clone = pin.clone();
+
+        pin.set_high().unwrap();
+        assert!(clone.is_set_high().unwrap());
+        clock.advance_ns(500);
+        // Scheduled levels still apply after driving the pin.
+        assert!(clone.is_low().unwrap());
+        clone.toggle().unwrap();
+        assert_eq!(
+            pin.transitions(),
+            [
+                Transition {
+                    at_ns: 0,
+                    state: PinState::High
+                },
+                Transition {
+                    at_ns: 500,
+                    state: PinState::High
+                },
+            ]
+        );
+        assert_eq!(pin.level(), PinState::High);
+    }
+
+    #[test]
+    fn one_shot_timer_stays_expired() {
+        let clock = SimClock::new();
+        let mut timer = FakeTimer::new(&clock);
+        assert_eq!(timer.is_expired(), Err(ErrorKind::NotRunning));
+        assert_eq!(timer.wait(), Err(ErrorKind::NotRunning));
+        assert_eq!(timer.cancel(), Err(ErrorKind::NotRunning));
+
+        timer.start(1_000).unwrap();
+        clock.advance_ns(999);
+        assert_eq!(timer.is_expired(), Ok(false));
+        timer.wait().unwrap();
+        assert_eq!(clock.now_ns(), 1_000);
+        assert_eq!(timer.is_expired(), Ok(true));
+        assert_eq!(timer.is_expired(), Ok(true));
+
+        timer.cancel().unwrap();
+        assert_eq!(timer.is_expired(), Err(ErrorKind::NotRunning));
+        assert_eq!(timer.start(u64::MAX), Err(ErrorKind::UnsupportedDuration));
+        assert_eq!(timer.deadline_ns(), None);
+    }
+
+    #[test]
+    fn periodic_timer_does_not_drift() {
+        let clock = SimClock::new();
+        let mut timer = FakeTimer::new(&clock);
+        assert_eq!(timer.start_periodic(0), Err(ErrorKind::UnsupportedDuration));
+
+        timer.start_periodic(1_000).unwrap();
+        timer.wait().unwrap();
+        assert_eq!(clock.now_ns(), 1_000);
+        // The period ending now was observed by `wait`.
+        assert_eq!(timer.is_expired(), Ok(false));
+
+        // Work for longer than two periods: the missed periods are merged.
+        clock.advance_ns(2_300);
+        assert_eq!(timer.is_expired(), Ok(true));
+        assert_eq!(timer.is_expired(), Ok(false));
+        timer.wait().unwrap();
+        assert_eq!(clock.now_ns(), 4_000);
+
+        timer.start(100).unwrap();
+        timer.wait().unwrap();
+        assert_eq!(timer.is_expired(), Ok(true));
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_timer_wait_advances_the_clock() {
+        use crate::common::block_on;
+        use embedded_hal_async::timer::{CountDown as AsyncCountDown, Periodic as AsyncPeriodic};
+
+        let clock = SimClock::new();
+        let mut timer = FakeTimer::new(&clock);
+        assert_eq!(
+            block_on(AsyncCountDown::wait(&mut timer)),
+            Err(ErrorKind::NotRunning)
+        );
+        AsyncPeriodic::start_periodic(&mut timer, 250).unwrap();
+        for _ in 0..4 {
+            block_on(AsyncCountDown::wait(&mut timer)).unwrap();
+        }
+        assert_eq!(clock.now_ns(), 1_000);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn wait_advances_the_clock() {
+        use crate::common::block_on;
+        use embedded_hal_async::digital::Wait;
+
+        let clock = SimClock::new();
+        let mut pin = FakePin::new(&clock, PinState::High).with_schedule([
+            (1_000, PinState::High),
+            (2_000, PinState::Low),
+            (3_000, PinState::High),
+            (4_000, PinState::Low),
+        ]);
+
+        // Already high: no time passes.
+        block_on(pin.wait_for_high()).unwrap();
+        assert_eq!(clock.now_ns(), 0);
+        // Scheduling the current level again is not an edge.
+        block_on(pin.wait_for_any_edge()).unwrap();
+        assert_eq!(clock.now_ns(), 2_000);
+        block_on(pin.wait_for_rising_edge()).unwrap();
+        assert_eq!(clock.now_ns(), 3_000);
+        block_on(pin.wait_for_low()).unwrap();
+        assert_eq!(clock.now_ns(), 4_000);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    #[should_panic(expected = "FakePin: waiting forever for a falling edge")]
+    fn waiting_forever_panics() {
+        use embedded_hal_async::digital::Wait;
+
+        let clock = SimClock::new();
+        let mut pin = FakePin::new(&clock, PinState::High);
+        crate::common::block_on(pin.wait_for_falling_edge()).unwrap();
+    }
+}
@@ -0,0 +1,563 @@
+//! Mock [`SpiBus`] and [`SpiDevice`] implementation.
+//!
+//! [`MockSpi`] implements both traits. As an [`SpiDevice`], each transaction is expected to be enclosed
+//! in [`Transaction::TransactionStart`] and [`Transaction::TransactionEnd`], with the operations of the
+//! transaction expected in between.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::spi::SpiDevice;
+//! use embedded_hal_mock::spi::{MockSpi, Transaction};
+//!
+//! let mut spi = MockSpi::new([
+//!     Transaction::TransactionStart,
+//!     Transaction::Write(vec![0x9F]),
+//!     Transaction::Read(vec![0xEF, 0x40, 0x18]),
+//!     Transaction::TransactionEnd,
+//! ]);
+//!
+//! let mut id = [0; 3];
+//! spi.transaction(&mut [
+//!     embedded_hal::spi::Operation::Write(&[0x9F]),
+//!     embedded_hal::spi::Operation::Read(&mut id),
+//! ])
+//! .unwrap();
+//! assert_eq!(id, [0xEF, 0x40, 0x18]);
+//!
+//! spi.assert_complete();
+//! ```
+//!
+//! With the `async` feature, [`MockSpi`] also implements the async
+//! [`SpiBus`](embedded_hal_async::spi::SpiBus) and [`SpiDevice`](embedded_hal_async::spi::SpiDevice)
+//! traits, checked against the same expectations.
+//!
+//! [`SpiLoopback`] is a fake instead: it does not check expected transactions, but echoes the
+//! written words back, like a bus with MOSI wired to MISO through a shift register.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use embedded_hal::spi::{self, ErrorKind, ErrorType, SpiBus, SpiDevice};
+
+use crate::common::{Described, Expectations};
+
+/// Expected SPI transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transaction {
+    /// [`SpiBus::write`] of the given words.
+    Write(Vec<u8>),
+    /// [`SpiBus::read`], answered with the given words.
+    ///
+    /// The read buffer must have the same length as the response.
+    Read(Vec<u8>),
+    /// [`SpiBus::transfer`] of `write`, answered with `response`.
+    ///
+    /// The read buffer must have the same length as the response.
+    Transfer {
+        /// Expected written words.
+        write: Vec<u8>,
+        /// Words read back.
+        response: Vec<u8>,
+    },
+    /// [`SpiBus::transfer_in_place`] of `write`, answered with `response`.
+    ///
+    /// Both must have the same length.
+    TransferInPlace {
+        /// Expected written words.
+        write: Vec<u8>,
+        /// Words read back.
+        response: Vec<u8>,
+    },
+    /// [`SpiBus::flush`].
+    Flush,
+    /// [`Operation::DelayNs`](spi::Operation::DelayNs) of the given duration, in an [`SpiDevice`] transaction.
+    DelayNs(u32),
+    /// Start of an [`SpiDevice`] transaction, i.e. CS asserted.
+    TransactionStart,
+    /// End of an [`SpiDevice`] transaction, i.e. CS deasserted.
+    TransactionEnd,
+    /// Failure of the previous [`SpiBus`] call with the given error.
+    ///
+    /// The failing call is still checked, and its reads answered, before the error is returned. In an
+    /// [`SpiDevice`] transaction, the remaining operations are skipped, and the transaction still ends
+    /// with [`Transaction::TransactionEnd`]:
+    ///
+    /// ```
+    /// use embedded_hal::spi::{ErrorKind, SpiDevice};
+    /// use embedded_hal_mock::spi::{MockSpi, Transaction};
+    ///
+    /// let mut spi = MockSpi::new([
+    ///     Transaction::TransactionStart,
+    ///     Transaction::Write(vec![0x01]),
+    ///     Transaction::Error(ErrorKind::Overrun),
+    ///     Transaction::TransactionEnd,
+    /// ]);
+    /// assert_eq!(spi.write(&[0x01]), Err(ErrorKind::Overrun));
+    /// spi.assert_complete();
+    /// ```
+    Error(ErrorKind),
+}
+
+/// Mock [`SpiBus`] and [`SpiDevice`] implementation.
+///
+/// Each call is checked against the next expected [`Transaction`], and reads are answered with the
+/// expected responses. Clones share the same expectations.
+///
+/// # Panics
+///
+/// Methods panic if the call does not match the next expected transaction, or if all the expected
+/// transactions were already performed.
+#[derive(Debug, Clone)]
+pub struct MockSpi {
+    expectations: Expectations<Transaction>,
+}
+
+impl MockSpi {
+    /// Create a new [`MockSpi`], expecting the `expected` transactions in order.
+    pub fn new(expected: impl IntoIterator<Item = Transaction>) -> Self {
+        Self {
+            expectations: Expectations::new("MockSpi", expected),
+        }
+    }
+
+    /// Check that all the expected transactions were performed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some expected transactions are left.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        self.expectations.assert_complete();
+    }
+
+    #[track_caller]
+    fn expect(&self, actual: Transaction) {
+        self.expectations
+            .next(&actual, |expected| *expected == actual);
+    }
+
+    /// Returns the error injected for the call which was just checked, if any.
+    fn result(&self) -> Result<(), ErrorKind> {
+        match self
+            .expectations
+            .next_if(|expected| matches!(expected, Transaction::Error(_)))
+        {
+            Some(Transaction::Error(error)) => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ErrorType for MockSpi {
+    type Error = ErrorKind;
+}
+
+impl SpiBus for MockSpi {
+    #[track_caller]
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let actual = Described(format!("Read([_; {}])", words.len()));
+        match self.expectations.next(actual, |expected| {
+            matches!(expected, Transaction::Read(response) if response.len() == words.len())
+        }) {
+            Transaction::Read(response) => words.copy_from_slice(&response),
+            _ => unreachable!(),
+        }
+        self.result()
+    }
+
+    #[track_caller]
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.expect(Transaction::Write(words.to_vec()));
+        self.result()
+    }
+
+    #[track_caller]
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        let actual = Described(format!(
+            "Transfer {{ write: {write:?}, response: [_; {}] }}",
+            read.len()
+        ));
+        match self.expectations.next(actual, |expected| {
+            matches!(
+                expected,
+                Transaction::Transfer { write: w, response } if w == write && response.len() == read.len()
+            )
+        }) {
+            Transaction::Transfer { response, .. } => read.copy_from_slice(&response),
+            _ => unreachable!(),
+        }
+        self.result()
+    }
+
+    #[track_caller]
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let actual = Described(format!("TransferInPlace {{ write: {words:?} }}"));
+        match self.expectations.next(actual, |expected| {
+            matches!(
+                expected,
+                Transaction::TransferInPlace { write, response }
+                    if write == words && response.len() == words.len()
+            )
+        }) {
+            Transaction::TransferInPlace { response, .. } => words.copy_from_slice(&response),
+            _ => unreachable!(),
+        }
+        self.result()
+    }
+
+    #[track_caller]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.expect(Transaction::Flush);
+        self.result()
+    }
+}
+
+impl SpiDevice for MockSpi {
+    #[track_caller]
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        self.expect(Transaction::TransactionStart);
+        let result = operations.iter_mut().try_for_each(|op| match op {
+            spi::Operation::Read(words) => SpiBus::read(self, words),
+            spi::Operation::Write(words) => SpiBus::write(self, words),
+            spi::Operation::Transfer(read, write) => SpiBus::transfer(self, read, write),
+            spi::Operation::TransferInPlace(words) => SpiBus::transfer_in_place(self, words),
+            spi::Operation::DelayNs(ns) => {
+                self.expect(Transaction::DelayNs(*ns));
+                Ok(())
+            }
+        });
+        self.expect(Transaction::TransactionEnd);
+        result
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiBus for MockSpi {
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        SpiBus::read(self, words)
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        SpiBus::write(self, words)
+    }
+
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        SpiBus::transfer(self, read, write)
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        SpiBus::transfer_in_place(self, words)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        SpiBus::flush(self)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiDevice for MockSpi {
+    async fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        SpiDevice::transaction(self, operations)
+    }
+}
+
+#[derive(Debug)]
+struct Loopback {
+    /// Words in the shift register, the oldest first.
+    register: VecDeque<u8>,
+    written: Vec<u8>,
+}
+
+/// Fake [`SpiBus`] echoing the written words back.
+///
+/// Each word read is the word written `depth` words earlier, as if MOSI was connected to MISO
+/// through a shift register of `depth` words, initially filled with `0x00`: with a depth of 0, the
+/// words are read back as they are written. Reads write `0x00` words. All the written words are
+/// logged, see [`written`](SpiLoopback::written). Clones share the same state.
+///
+/// With the `async` feature, the async [`SpiBus`](embedded_hal_async::spi::SpiBus) trait is also
+/// implemented.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::SpiBus;
+/// use embedded_hal_mock::spi::SpiLoopback;
+///
+/// let mut spi = SpiLoopback::with_depth(1);
+/// let mut words = [0x01, 0x02, 0x03];
+/// spi.transfer_in_place(&mut words).unwrap();
+/// assert_eq!(words, [0x00, 0x01, 0x02]);
+/// assert_eq!(spi.written(), [0x01, 0x02, 0x03]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpiLoopback {
+    state: Arc<Mutex<Loopback>>,
+}
+
+impl SpiLoopback {
+    /// Create a new [`SpiLoopback`], reading back the words as they are written.
+    pub fn new() -> Self {
+        Self::with_depth(0)
+    }
+
+    /// Create a new [`SpiLoopback`], reading back the words written `depth` words earlier.
+    pub fn with_depth(depth: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(Loopback {
+                register: VecDeque::from(vec![0; depth]),
+                written: Vec::new(),
+            })),
+        }
+    }
+
+    /// Returns all the words written so far.
+    pub fn written(&self) -> Vec<u8> {
+        self.lock().written.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Loopback> {
+        // A failed assertion poisons the mutex: keep going, so that the clones still work.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Shift `mosi` in, and return the word shifted out.
+    fn shift(state: &mut Loopback, mosi: u8) -> u8 {
+        state.written.push(mosi);
+        state.register.push_back(mosi);
+        // Cannot fail, a word was just pushed.
+        state.register.pop_front().unwrap()
+    }
+}
+
+impl Default for SpiLoopback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorType for SpiLoopback {
+    type Error = ErrorKind;
+}
+
+impl SpiBus for SpiLoopback {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        words
+            .iter_mut()
+            .for_each(|word| *word = Self::shift(&mut state, 0x00));
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        words.iter().for_each(|&word| {
+            Self::shift(&mut state, word);
+        });
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        for i in 0..read.len().max(write.len()) {
+            let miso = Self::shift(&mut state, write.get(i).copied().unwrap_or(0x00));
+            if let Some(word) = read.get_mut(i) {
+                *word = miso;
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let mut state = self.lock();
+        words
+            .iter_mut()
+            .for_each(|word| *word = Self::shift(&mut state, *word));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::spi::SpiBus for SpiLoopback {
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        SpiBus::read(self, words)
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        SpiBus::write(self, words)
+    }
+
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        SpiBus::transfer(self, read, write)
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        SpiBus::transfer_in_place(self, words)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        SpiBus::flush(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loopback_echoes_through_the_shift_register() {
+        let mut spi = SpiLoopback::new();
+        let mut buf = [0; 2];
+        SpiBus::transfer(&mut spi, &mut buf, &[0xA5, 0x5A]).unwrap();
+        assert_eq!(buf, [0xA5, 0x5A]);
+
+        let mut spi = SpiLoopback::with_depth(2);
+        let probe = spi.clone();
+        SpiBus::write(&mut spi, &[1, 2, 3]).unwrap();
+        let mut buf = [0; 3];
+        SpiBus::read(&mut spi, &mut buf).unwrap();
+        assert_eq!(buf, [2, 3, 0]);
+        let mut buf = [0; 1];
+        SpiBus::transfer(&mut spi, &mut buf, &[4, 5]).unwrap();
+        assert_eq!(buf, [0]);
+        assert_eq!(probe.written(), [1, 2, 3, 0, 0, 0, 4, 5]);
+    }
+
+    #[test]
+    fn bus_operations() {
+        let mut spi = MockSpi::new([
+            Transaction::Write(vec![1, 2]),
+            Transaction::Read(vec![3]),
+            Transaction::Transfer {
+                write: vec![4],
+                response: vec![5, 6],
+            },
+            Transaction::TransferInPlace {
+                write: vec![7],
+                response: vec![8],
+            },
+            Transaction::Flush,
+        ]);
+        SpiBus::write(&mut spi, &[1, 2]).unwrap();
+        let mut buf = [0];
+        SpiBus::read(&mut spi, &mut buf).unwrap();
+        assert_eq!(buf, [3]);
+        let mut buf = [0; 2];
+        SpiBus::transfer(&mut spi, &mut buf, &[4]).unwrap();
+        assert_eq!(buf, [5, 6]);
+        let mut buf = [7];
+        SpiBus::transfer_in_place(&mut spi, &mut buf).unwrap();
+        assert_eq!(buf, [8]);
+        SpiBus::flush(&mut spi).unwrap();
+        spi.assert_complete();
+    }
+
+    #[test]
+    fn device_transaction_with_delay() {
+        let mut spi = MockSpi::new([
+            Transaction::TransactionStart,
+            Transaction::Write(vec![1]),
+            Transaction::DelayNs(500),
+            Transaction::Write(vec![2]),
+            Transaction::TransactionEnd,
+        ]);
+        spi.transaction(&mut [
+            spi::Operation::Write(&[1]),
+            spi::Operation::DelayNs(500),
+            spi::Operation::Write(&[2]),
+        ])
+        .unwrap();
+        spi.assert_complete();
+    }
+
+    #[test]
+    fn injected_errors() {
+        let mut spi = MockSpi::new([
+            Transaction::Read(vec![1]),
+            Transaction::Error(ErrorKind::Overrun),
+            Transaction::TransactionStart,
+            Transaction::Write(vec![2]),
+            Transaction::Error(ErrorKind::ModeFault),
+            Transaction::TransactionEnd,
+        ]);
+        let mut buf = [0];
+        assert_eq!(SpiBus::read(&mut spi, &mut buf), Err(ErrorKind::Overrun));
+        assert_eq!(buf, [1]);
+        // The write fails: the delay and the last write are not performed.
+        assert_eq!(
+            spi.transaction(&mut [
+                spi::Operation::Write(&[2]),
+                spi::Operation::DelayNs(100),
+                spi::Operation::Write(&[3]),
+            ]),
+            Err(ErrorKind::ModeFault)
+        );
+        spi.assert_complete();
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_transactions() {
+        use embedded_hal_async::spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice};
+
+        let mut spi = MockSpi::new([
+            Transaction::TransactionStart,
+            Transaction::Write(vec![0x9F]),
+            Transaction::Read(vec![0xEF]),
+            Transaction::TransactionEnd,
+            Transaction::Flush,
+            Transaction::Error(ErrorKind::Other),
+        ]);
+        let mut id = [0];
+        crate::common::block_on(AsyncSpiDevice::transaction(
+            &mut spi,
+            &mut [
+                spi::Operation::Write(&[0x9F]),
+                spi::Operation::Read(&mut id),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(id, [0xEF]);
+        assert_eq!(
+            crate::common::block_on(AsyncSpiBus::flush(&mut spi)),
+            Err(ErrorKind::Other)
+        );
+        spi.assert_complete();
+    }
+
+    #[test]
+    #[should_panic(expected = "MockSpi: transaction #0 does not match the expected one")]
+    fn bus_call_in_device_expectations_panics() {
+        let mut spi = MockSpi::new([
+            Transaction::TransactionStart,
+            Transaction::Write(vec![1]),
+            Transaction::TransactionEnd,
+        ]);
+        SpiBus::write(&mut spi, &[1]).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match")]
+    fn wrong_read_length_panics() {
+        let mut spi = MockSpi::new([Transaction::Read(vec![1, 2])]);
+        SpiBus::read(&mut spi, &mut [0]).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "MockSpi: 1 expected transaction(s) not performed")]
+    fn incomplete_panics() {
+        let spi = MockSpi::new([Transaction::Flush]);
+        spi.assert_complete();
+    }
+}
@@ -0,0 +1,227 @@
+//! Analog-to-digital converter (ADC) traits.
+//!
+//! The [`AdcChannel`] trait reads one sample from a single ADC channel, such as a
+//! battery voltage divider or a thermistor. The [`Adc`] trait reads from a multi-channel
+//! ADC, selecting channels by number. The [`AdcDifferential`] trait reads the signed difference
+//! between two channels of an ADC with differential inputs.
+//!
+//! Samples are raw counts, from 0 to [`max_count`](AdcChannel::max_count), which corresponds to
+//! the full-scale input (usually the reference voltage). This lets drivers convert samples
+//! to a voltage, or to a ratio, without target-specific knowledge of the resolution.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::adc::AdcChannel;
+//!
+//! /// Read the battery voltage through a divider by two, with a 3.3 V reference.
+//! fn battery_millivolts<A: AdcChannel>(adc: &mut A) -> Result<u32, A::Error> {
+//!     let sample = u64::from(adc.read()?);
+//!     let millivolts = sample * 3_300 * 2 / u64::from(adc.max_count());
+//!     Ok(millivolts as u32)
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// ADC error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic ADC error kind.
+    ///
+    /// By using this method, ADC errors freely defined by HAL implementations
+    /// can be converted to a set of generic ADC errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// ADC error kind.
+///
+/// This represents a common set of ADC operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common ADC errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A new conversion completed before the previous result was read, which was lost.
+    Overrun,
+    /// The conversion did not complete in time.
+    Timeout,
+    /// The requested channel does not exist on this ADC.
+    InvalidChannel,
+    /// The buffer provided for the results is shorter than the list of channels.
+    BufferTooSmall,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Overrun => write!(f, "The conversion result was overwritten before being read"),
+            Self::Timeout => write!(f, "The conversion did not complete in time"),
+            Self::InvalidChannel => write!(f, "The requested channel does not exist"),
+            Self::BufferTooSmall => write!(f, "The results buffer is too small"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// ADC error type trait.
+///
+/// This just defines the error type, to be used by the other ADC traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Single ADC channel, performing one-shot conversions.
+pub trait AdcChannel: ErrorType {
+    /// Get the maximum sample value.
+    ///
+    /// This value corresponds to the full-scale input, for instance `4095` for a 12-bit ADC.
+    fn max_count(&self) -> u32;
+
+    /// Perform a conversion, and return the sample, from 0 to [`max_count`](AdcChannel::max_count).
+    fn read(&mut self) -> Result<u32, Self::Error>;
+}
+
+impl<T: AdcChannel + ?Sized> AdcChannel for &mut T {
+    #[inline]
+    fn max_count(&self) -> u32 {
+        T::max_count(self)
+    }
+
+    #[inline]
+    fn read(&mut self) -> Result<u32, Self::Error> {
+        T::read(self)
+    }
+}
+
+/// Multi-channel ADC, performing one-shot conversions.
+///
+/// Channels are selected by number, whose meaning is defined by the HAL: usually the channel
+/// number of the datasheet.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::adc::Adc;
+///
+/// /// Read the three axes of an analog accelerometer, as ratios of the full scale in per mille.
+/// fn read_axes<A: Adc>(adc: &mut A) -> Result<[u32; 3], A::Error> {
+///     let mut samples = [0; 3];
+///     adc.read_channels(&[0, 1, 2], &mut samples)?;
+///     Ok(samples.map(|s| (u64::from(s) * 1_000 / u64::from(adc.max_count())) as u32))
+/// }
+/// ```
+pub trait Adc: ErrorType {
+    /// Get the maximum sample value.
+    ///
+    /// This value corresponds to the full-scale input, for instance `4095` for a 12-bit ADC.
+    fn max_count(&self) -> u32;
+
+    /// Perform a conversion on `channel`, and return the sample, from 0 to [`max_count`](Adc::max_count).
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidChannel`] if the channel does not exist.
+    fn read_channel(&mut self, channel: u8) -> Result<u32, Self::Error>;
+
+    /// Perform a conversion on each channel of `channels`, in order, storing the samples in `results`.
+    ///
+    /// Implementations may use a hardware scan sequence. `results` must be at least as long as `channels`,
+    /// otherwise an error of kind [`ErrorKind::BufferTooSmall`] is returned before any conversion.
+    /// Returns an error of kind [`ErrorKind::InvalidChannel`] if a channel does not exist.
+    fn read_channels(&mut self, channels: &[u8], results: &mut [u32]) -> Result<(), Self::Error>;
+}
+
+impl<T: Adc + ?Sized> Adc for &mut T {
+    #[inline]
+    fn max_count(&self) -> u32 {
+        T::max_count(self)
+    }
+
+    #[inline]
+    fn read_channel(&mut self, channel: u8) -> Result<u32, Self::Error> {
+        T::read_channel(self, channel)
+    }
+
+    #[inline]
+    fn read_channels(&mut self, channels: &[u8], results: &mut [u32]) -> Result<(), Self::Error> {
+        T::read_channels(self, channels, results)
+    }
+}
+
+/// ADC with differential inputs, converting the difference between two channels.
+///
+/// Differential conversions reject the noise common to both inputs, and measure signals going
+/// below the negative input, such as the voltage across a current shunt or a bridge sensor. Channels
+/// are selected by number, like for [`Adc`]: an ADC with a fixed negative input, or with only some
+/// valid pairs, returns an error of kind [`ErrorKind::InvalidChannel`] for the others.
+///
+/// Samples are signed, from `-max_count` to [`max_count`](AdcDifferential::max_count), which
+/// correspond to the negative and positive full-scale differences. They are 32-bit wide, to fit the
+/// 18 to 24-bit results of precision ADCs.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::adc::AdcDifferential;
+///
+/// /// Read the current through a 10 mΩ shunt between channels 0 and 1, with a ±256 mV full scale.
+/// fn shunt_milliamps<A: AdcDifferential>(adc: &mut A) -> Result<i32, A::Error> {
+///     let sample = i64::from(adc.read_differential(0, 1)?);
+///     let microvolts = sample * 256_000 / i64::from(adc.max_count());
+///     Ok((microvolts / 10) as i32)
+/// }
+/// ```
+pub trait AdcDifferential: ErrorType {
+    /// Get the maximum sample value.
+    ///
+    /// This value corresponds to the positive full-scale difference, for instance `32767` for a
+    /// 16-bit ADC. The samples range from `-max_count` to `max_count`.
+    fn max_count(&self) -> i32;
+
+    /// Perform a conversion of the difference between `positive` and `negative`, and return the
+    /// sample, from `-max_count` to [`max_count`](AdcDifferential::max_count).
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidChannel`] if the pair of channels can not be
+    /// converted.
+    fn read_differential(&mut self, positive: u8, negative: u8) -> Result<i32, Self::Error>;
+}
+
+impl<T: AdcDifferential + ?Sized> AdcDifferential for &mut T {
+    #[inline]
+    fn max_count(&self) -> i32 {
+        T::max_count(self)
+    }
+
+    #[inline]
+    fn read_differential(&mut self, positive: u8, negative: u8) -> Result<i32, Self::Error> {
+        T::read_differential(self, positive, negative)
+    }
+}
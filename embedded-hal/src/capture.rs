@@ -0,0 +1,211 @@
+//! Input capture traits.
+//!
+//! The [`InputCapture`] trait measures the period or the pulse width of an input signal,
+//! using a timer in input capture mode. This is used by fan tachometers, anemometers or
+//! infrared receivers.
+//!
+//! The [`PwmInputCapture`] trait measures both the period and the duty cycle of a PWM signal,
+//! for example for servo feedback loops.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::capture::InputCapture;
+//!
+//! /// Returns the speed of a fan emitting two tachometer pulses per revolution.
+//! fn fan_rpm<C: InputCapture>(tach: &mut C) -> Result<u32, C::Error> {
+//!     let period_ns = u64::from(tach.measure_period_ns()?);
+//!     Ok((60_000_000_000 / (2 * period_ns)) as u32)
+//! }
+//! ```
+
+use crate::digital::PinState;
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Input capture error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic input capture error kind.
+    ///
+    /// By using this method, input capture errors freely defined by HAL implementations
+    /// can be converted to a set of generic input capture errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Input capture error kind.
+///
+/// This represents a common set of input capture operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common input capture errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// No edge was detected in time: there is no signal, or it is too slow to be measured.
+    Timeout,
+    /// An edge was captured before the previous capture was read, which was lost.
+    Overcapture,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "No edge was detected in time"),
+            Self::Overcapture => write!(f, "A capture was overwritten before being read"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// Input capture error type trait.
+///
+/// This just defines the error type, to be used by the other input capture traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Input capture channel.
+pub trait InputCapture: ErrorType {
+    /// Measure the period of the input signal, between two consecutive rising edges, in nanoseconds.
+    ///
+    /// This blocks until a complete period was measured. Returns an error of kind [`ErrorKind::Timeout`]
+    /// if no period could be measured in time.
+    fn measure_period_ns(&mut self) -> Result<u32, Self::Error>;
+
+    /// Measure the width of a pulse of the input signal at `level`, in nanoseconds.
+    ///
+    /// For instance with [`PinState::High`], this is the time between a rising edge and the next
+    /// falling edge. This blocks until a complete pulse was measured. Returns an error of kind
+    /// [`ErrorKind::Timeout`] if no pulse could be measured in time.
+    fn measure_pulse_width_ns(&mut self, level: PinState) -> Result<u32, Self::Error>;
+}
+
+impl<T: InputCapture + ?Sized> InputCapture for &mut T {
+    #[inline]
+    fn measure_period_ns(&mut self) -> Result<u32, Self::Error> {
+        T::measure_period_ns(self)
+    }
+
+    #[inline]
+    fn measure_pulse_width_ns(&mut self, level: PinState) -> Result<u32, Self::Error> {
+        T::measure_pulse_width_ns(self, level)
+    }
+}
+
+/// PWM input capture channel.
+///
+/// This measures the period and the duty cycle of a PWM signal, using a timer in PWM input mode.
+/// Duty cycles use the same scale as [`SetDutyCycle`](crate::pwm::SetDutyCycle): `0` is 0%, and
+/// [`max_duty_cycle`](PwmInputCapture::max_duty_cycle) is 100%.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::capture::PwmInputCapture;
+///
+/// /// Returns the position of a servo with a PWM feedback signal, in per mille of its range.
+/// fn servo_position<C: PwmInputCapture>(feedback: &mut C) -> Result<u32, C::Error> {
+///     let duty = u32::from(feedback.measure_duty_cycle()?);
+///     Ok(duty * 1000 / u32::from(feedback.max_duty_cycle()))
+/// }
+/// ```
+pub trait PwmInputCapture: ErrorType {
+    /// Get the maximum duty cycle value.
+    ///
+    /// This value corresponds to a 100% duty cycle.
+    fn max_duty_cycle(&self) -> u16;
+
+    /// Measure the period of the input signal, between two consecutive rising edges, in nanoseconds.
+    ///
+    /// This blocks until a complete period was measured. Returns an error of kind [`ErrorKind::Timeout`]
+    /// if no period could be measured in time. The period is never 0.
+    fn measure_period_ns(&mut self) -> Result<u64, Self::Error>;
+
+    /// Measure the duty cycle of the input signal: the ratio between the high time and the period,
+    /// scaled to [`max_duty_cycle`](PwmInputCapture::max_duty_cycle).
+    ///
+    /// This blocks until a complete period was measured. Returns an error of kind [`ErrorKind::Timeout`]
+    /// if no period could be measured in time.
+    fn measure_duty_cycle(&mut self) -> Result<u16, Self::Error>;
+
+    /// Measure the frequency of the input signal, in Hz, rounded to the nearest integer.
+    #[inline]
+    fn measure_frequency_hz(&mut self) -> Result<u32, Self::Error> {
+        self.measure_period_ns().map(frequency_hz)
+    }
+}
+
+impl<T: PwmInputCapture + ?Sized> PwmInputCapture for &mut T {
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        T::max_duty_cycle(self)
+    }
+
+    #[inline]
+    fn measure_period_ns(&mut self) -> Result<u64, Self::Error> {
+        T::measure_period_ns(self)
+    }
+
+    #[inline]
+    fn measure_duty_cycle(&mut self) -> Result<u16, Self::Error> {
+        T::measure_duty_cycle(self)
+    }
+
+    #[inline]
+    fn measure_frequency_hz(&mut self) -> Result<u32, Self::Error> {
+        T::measure_frequency_hz(self)
+    }
+}
+
+/// Convert a period in nanoseconds to a frequency in Hz, rounded to the nearest integer.
+///
+/// A period of 0 is treated as 1 ns.
+#[inline]
+fn frequency_hz(period_ns: u64) -> u32 {
+    let period_ns = period_ns.max(1);
+    ((1_000_000_000 + period_ns / 2) / period_ns) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_rounding() {
+        assert_eq!(frequency_hz(1_000_000), 1_000);
+        assert_eq!(frequency_hz(3), 333_333_333);
+        assert_eq!(frequency_hz(20_000_000), 50);
+        assert_eq!(frequency_hz(1_500_000_000), 1);
+        assert_eq!(frequency_hz(3_000_000_000), 0);
+        assert_eq!(frequency_hz(0), 1_000_000_000);
+    }
+}
@@ -1,4 +1,7 @@
 //! Delays.
+//!
+//! [`DelayNs`] waits for a given duration, while [`Counter`] measures the time elapsed since it was
+//! started. A hardware timer can implement both traits.
 
 /// Nanoseconds per microsecond
 const NANOS_PER_MICRO: u32 = 1_000;
@@ -60,3 +63,62 @@ where
         T::delay_ms(self, ms);
     }
 }
+
+/// Counter measuring elapsed time with up to nanosecond precision.
+///
+/// This allows drivers to implement timeouts, e.g. when polling a status register, without depending
+/// on a clock. The counter is infallible: implementations must handle overflows of the underlying
+/// hardware timer, so that [`elapsed_ns`](Counter::elapsed_ns) keeps growing.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::delay::{Counter, DelayNs};
+///
+/// /// Poll `ready` every 10 µs, for at most 1 ms.
+/// fn wait_ready<T: Counter + DelayNs>(timer: &mut T, mut ready: impl FnMut() -> bool) -> bool {
+///     timer.start();
+///     while !ready() {
+///         if timer.has_exceeded(1_000_000) {
+///             return false;
+///         }
+///         timer.delay_us(10);
+///     }
+///     true
+/// }
+/// ```
+pub trait Counter {
+    /// Restarts the counter, so that [`elapsed_ns`](Counter::elapsed_ns) counts from now.
+    fn start(&mut self);
+
+    /// Returns the number of nanoseconds elapsed since the counter was last started.
+    ///
+    /// The value may be rounded down to the precision of the implementation.
+    fn elapsed_ns(&self) -> u64;
+
+    /// Returns `true` if at least `deadline_ns` nanoseconds elapsed since the counter was last started.
+    #[inline]
+    fn has_exceeded(&self, deadline_ns: u64) -> bool {
+        self.elapsed_ns() >= deadline_ns
+    }
+}
+
+impl<T> Counter for &mut T
+where
+    T: Counter + ?Sized,
+{
+    #[inline]
+    fn start(&mut self) {
+        T::start(self);
+    }
+
+    #[inline]
+    fn elapsed_ns(&self) -> u64 {
+        T::elapsed_ns(self)
+    }
+
+    #[inline]
+    fn has_exceeded(&self, deadline_ns: u64) -> bool {
+        T::has_exceeded(self, deadline_ns)
+    }
+}
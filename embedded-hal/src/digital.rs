@@ -222,3 +222,290 @@ impl<T: InputPin + ?Sized> InputPin for &mut T {
         T::is_low(self)
     }
 }
+
+/// Bank of `WIDTH` GPIO pins, read and written at once.
+///
+/// Setting several [`OutputPin`]s one after the other changes them at different times, which is
+/// wrong for parallel buses or LED matrices. This trait exposes a whole port, so all its pins
+/// change in a single operation.
+///
+/// Bit `n` of the port values corresponds to the `n`-th pin of the port, a `1` meaning high.
+/// `WIDTH` must be at most 32: bits at and above `WIDTH` are read as `0`, and ignored on writes.
+///
+/// Without native port access, the `PinGroup` adapter of the `embedded-hal-bus` crate implements
+/// this trait over individual pins, accessing them one after the other.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::GpioPort;
+///
+/// /// Put a byte on the lower 8 pins of a parallel bus, leaving the other pins untouched.
+/// fn put_byte<P: GpioPort<16>>(port: &mut P, byte: u8) -> Result<(), P::Error> {
+///     port.write_port(u32::from(byte), 0x00FF)
+/// }
+/// ```
+pub trait GpioPort<const WIDTH: usize>: ErrorType {
+    /// Reads the input state of all the pins of the port.
+    fn read_port(&mut self) -> Result<u32, Self::Error>;
+
+    /// Drives the pins selected by `mask` to the state given by `value`, in one operation.
+    ///
+    /// Pins whose bit is `0` in `mask` keep their current state.
+    fn write_port(&mut self, value: u32, mask: u32) -> Result<(), Self::Error>;
+}
+
+impl<const WIDTH: usize, T: GpioPort<WIDTH> + ?Sized> GpioPort<WIDTH> for &mut T {
+    #[inline]
+    fn read_port(&mut self) -> Result<u32, Self::Error> {
+        T::read_port(self)
+    }
+
+    #[inline]
+    fn write_port(&mut self, value: u32, mask: u32) -> Result<(), Self::Error> {
+        T::write_port(self, value, mask)
+    }
+}
+
+/// 8-bit GPIO port.
+///
+/// This is an alias of [`GpioPort<8>`], implemented for every 8-bit port.
+pub trait GpioPort8: GpioPort<8> {}
+
+impl<T: GpioPort<8> + ?Sized> GpioPort8 for T {}
+
+/// GPIO expander, such as the MCP23017, PCF8574 or PCA9555, connected over I2C or SPI.
+///
+/// The pins of an expander are grouped in 8-bit ports, numbered from `0` to
+/// [`port_count`](GpioExpander::port_count) `- 1`. In the masks and values, bit `n` is pin `n` of the port.
+/// Methods return an error if `port` is out of range.
+///
+/// Individual pins can be used as [`InputPin`] and [`OutputPin`] with the `ExpanderPin` adapter of the
+/// `embedded-hal-bus` crate.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::digital::GpioExpander;
+///
+/// /// Drive LEDs on the pins of port 1, and return the state of the buttons on port 0.
+/// fn update<E: GpioExpander>(expander: &mut E, leds: u8) -> Result<u8, E::Error> {
+///     expander.set_direction(1, 0xFF)?;
+///     expander.write_port(1, leds, 0xFF)?;
+///     expander.read_port(0)
+/// }
+/// ```
+pub trait GpioExpander: ErrorType {
+    /// Returns the number of 8-bit ports of the expander.
+    fn port_count(&self) -> u8;
+
+    /// Reads the input state of the pins of `port`.
+    fn read_port(&mut self, port: u8) -> Result<u8, Self::Error>;
+
+    /// Drives the pins of `port` selected by `output_mask` to the state given by `value`.
+    ///
+    /// Pins whose bit is `0` in `output_mask` keep their current state.
+    fn write_port(&mut self, port: u8, value: u8, output_mask: u8) -> Result<(), Self::Error>;
+
+    /// Sets the direction of all the pins of `port`: pins whose bit is `1` in `dir_mask` are
+    /// outputs, the others are inputs.
+    fn set_direction(&mut self, port: u8, dir_mask: u8) -> Result<(), Self::Error>;
+}
+
+impl<T: GpioExpander + ?Sized> GpioExpander for &mut T {
+    #[inline]
+    fn port_count(&self) -> u8 {
+        T::port_count(self)
+    }
+
+    #[inline]
+    fn read_port(&mut self, port: u8) -> Result<u8, Self::Error> {
+        T::read_port(self, port)
+    }
+
+    #[inline]
+    fn write_port(&mut self, port: u8, value: u8, output_mask: u8) -> Result<(), Self::Error> {
+        T::write_port(self, port, value, output_mask)
+    }
+
+    #[inline]
+    fn set_direction(&mut self, port: u8, dir_mask: u8) -> Result<(), Self::Error> {
+        T::set_direction(self, port, dir_mask)
+    }
+}
+
+/// Output drive strength of a pin.
+///
+/// The current corresponding to each level is device-specific: for example, an MCU with 2, 4, 8 and 12 mA
+/// settings would map them to [`Low`](DriveStrength::Low), [`Medium`](DriveStrength::Medium),
+/// [`High`](DriveStrength::High) and [`VeryHigh`](DriveStrength::VeryHigh). HALs should document their mapping.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DriveStrength {
+    /// Lowest drive strength, reducing noise and power consumption.
+    Low,
+    /// Medium drive strength.
+    Medium,
+    /// High drive strength.
+    High,
+    /// Highest drive strength, for high-speed signals such as SDIO or QSPI.
+    VeryHigh,
+}
+
+/// Configure the drive strength of an output pin.
+///
+/// The drive strength can be changed at any time, whatever the current state of the pin.
+pub trait GpioDriveConfig: ErrorType {
+    /// Sets the drive strength of the pin.
+    ///
+    /// If the pin does not support `strength`, an error is returned: the setting is never
+    /// silently ignored.
+    fn set_drive_strength(&mut self, strength: DriveStrength) -> Result<(), Self::Error>;
+}
+
+impl<T: GpioDriveConfig + ?Sized> GpioDriveConfig for &mut T {
+    #[inline]
+    fn set_drive_strength(&mut self, strength: DriveStrength) -> Result<(), Self::Error> {
+        T::set_drive_strength(self, strength)
+    }
+}
+
+/// Output slew rate of a pin, i.e. how fast its edges rise and fall.
+///
+/// Slower edges reduce electromagnetic emissions and ringing on long traces, at the cost of the maximum
+/// toggling frequency. The rise and fall times of each level are device-specific: MCUs with only two
+/// settings would map them to [`Slow`](SlewRate::Slow) and [`Fast`](SlewRate::Fast). HALs should document
+/// their mapping.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum SlewRate {
+    /// Slowest edges, reducing emissions.
+    Slow,
+    /// Medium edges.
+    Medium,
+    /// Fastest edges, for high-speed signals.
+    Fast,
+}
+
+/// Configure the slew rate of an output pin.
+///
+/// This complements [`GpioDriveConfig`]: both affect the edges of the signal, and are often tuned together
+/// to pass emissions tests.
+pub trait GpioSlewConfig: ErrorType {
+    /// Sets the slew rate of the pin.
+    ///
+    /// If the pin does not support `rate`, an error is returned: the setting is never
+    /// silently ignored.
+    fn set_slew_rate(&mut self, rate: SlewRate) -> Result<(), Self::Error>;
+}
+
+impl<T: GpioSlewConfig + ?Sized> GpioSlewConfig for &mut T {
+    #[inline]
+    fn set_slew_rate(&mut self, rate: SlewRate) -> Result<(), Self::Error> {
+        T::set_slew_rate(self, rate)
+    }
+}
+
+/// Pull resistor configuration of a pin.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum PullMode {
+    /// Pull-up resistor enabled: an unconnected pin reads high.
+    PullUp,
+    /// Pull-down resistor enabled: an unconnected pin reads low.
+    PullDown,
+    /// No pull resistor: the pin is high-impedance.
+    Floating,
+    /// Bus keeper ("bus hold"): the pin weakly keeps the last level it was driven to.
+    BusKeeper,
+}
+
+/// Condition triggering the interrupt of a pin.
+///
+/// Used by the `PinChangeWaker` trait of [`embedded-hal-async`](https://docs.rs/embedded-hal-async).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Trigger {
+    /// Transition from low to high.
+    RisingEdge,
+    /// Transition from high to low.
+    FallingEdge,
+    /// Any transition.
+    BothEdges,
+    /// High level.
+    LevelHigh,
+    /// Low level.
+    LevelLow,
+}
+
+/// Configure the pull resistor of a pin at runtime.
+pub trait GpioPullConfig: ErrorType {
+    /// Sets the pull resistor configuration of the pin.
+    ///
+    /// If the pin does not support `mode`, an error is returned: the closest mode is never
+    /// silently used instead.
+    fn set_pull(&mut self, mode: PullMode) -> Result<(), Self::Error>;
+}
+
+impl<T: GpioPullConfig + ?Sized> GpioPullConfig for &mut T {
+    #[inline]
+    fn set_pull(&mut self, mode: PullMode) -> Result<(), Self::Error> {
+        T::set_pull(self, mode)
+    }
+}
+
+/// Pin whose direction can be switched between input and output at runtime.
+///
+/// Single-wire protocols such as 1-Wire or the DHT22 protocol, and bit-banged buses, drive a line
+/// and then read it back from the same pin. Implementations keep a single pin type, switching its
+/// direction with [`set_as_input`](FlexPin::set_as_input) and
+/// [`set_as_output`](FlexPin::set_as_output):
+///
+/// - the [`InputPin`] methods read the level of the line, in both directions.
+/// - the [`OutputPin`] methods drive the line while the pin is an output. Implementations may return
+///   an error if they are called while the pin is an input.
+///
+/// # Example
+///
+/// Start a DHT22 measurement: pull the line low for at least 1 ms, release it, then check that the
+/// sensor responds by pulling it low.
+///
+/// ```
+/// use embedded_hal::delay::DelayNs;
+/// use embedded_hal::digital::{FlexPin, PinState, PullMode};
+///
+/// fn start_measurement<P: FlexPin, D: DelayNs>(pin: &mut P, delay: &mut D) -> Result<bool, P::Error> {
+///     pin.set_as_output(PinState::Low)?;
+///     delay.delay_ms(1);
+///     // The line has an external pull-up resistor, the internal one is not needed.
+///     pin.set_as_input(PullMode::Floating)?;
+///     delay.delay_us(40);
+///     pin.is_low()
+/// }
+/// ```
+pub trait FlexPin: InputPin + OutputPin {
+    /// Switches the pin to input, with the pull resistor configuration `pull`.
+    ///
+    /// The pin stops driving the line: with [`PullMode::Floating`], it is high-impedance. If the
+    /// pin does not support `pull`, an error is returned.
+    fn set_as_input(&mut self, pull: PullMode) -> Result<(), Self::Error>;
+
+    /// Switches the pin to output, driving `state`.
+    ///
+    /// Implementations set the output level before enabling the output driver, so that the pin
+    /// does not glitch to the other level.
+    fn set_as_output(&mut self, state: PinState) -> Result<(), Self::Error>;
+}
+
+impl<T: FlexPin + ?Sized> FlexPin for &mut T {
+    #[inline]
+    fn set_as_input(&mut self, pull: PullMode) -> Result<(), Self::Error> {
+        T::set_as_input(self, pull)
+    }
+
+    #[inline]
+    fn set_as_output(&mut self, state: PinState) -> Result<(), Self::Error> {
+        T::set_as_output(self, state)
+    }
+}
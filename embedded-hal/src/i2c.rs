@@ -47,6 +47,17 @@
 //! (Implementations must wait until the last ACK bit to report it as an error anyway. Therefore pipelining would only
 //! yield very small time savings, not worth the complexity)
 //!
+//! # Timeouts
+//!
+//! A misbehaving target can hold SCL or SDA low indefinitely. Only the implementation can abort a transfer
+//! that is in progress: wrappers built on top of this trait (such as the ones in
+//! [`embedded-hal-bus`](https://docs.rs/embedded-hal-bus)) only regain control once the method returns.
+//! A wrapper can still time each call with a [`Counter`](crate::delay::Counter) and report the ones that
+//! exceeded their deadline, as `embedded-hal-bus`'s `i2c::ClockStretchTimeout` does, but it cannot stop a
+//! transfer that never completes.
+//! Implementations should therefore bound the time they wait on the bus, e.g. using a hardware timeout,
+//! and report an expired timeout as an error of kind [`ErrorKind::Timeout`] instead of blocking forever.
+//!
 //! # For driver authors
 //!
 //! Drivers can select the adequate address length with `I2c<SevenBitAddress>` or `I2c<TenBitAddress>` depending
@@ -160,6 +171,13 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Target mode
+//!
+//! The [`I2c`] trait is for controllers (masters). Devices acting as a target (slave) on the bus
+//! use the [`I2cTarget`] trait instead: they [`listen`](I2cTarget::listen) for [`TargetEvent`]s,
+//! and [`respond`](I2cTarget::respond) to controller reads or [`accept`](I2cTarget::accept)
+//! controller writes.
 
 use crate::private;
 
@@ -196,6 +214,11 @@ pub enum ErrorKind {
     /// located after a multiple of 9 SCL clock pulses.
     Bus,
     /// The arbitration was lost, e.g. electrical problems with the clock signal.
+    ///
+    /// On a multi-master bus, this is also reported when another controller won the arbitration
+    /// in the middle of the transaction. The bus was not damaged: the whole transaction can be
+    /// retried once the bus is idle again. Implementations supporting multi-master mode should map
+    /// their arbitration errors to this kind, so that drivers can tell them from other failures.
     ArbitrationLoss,
     /// A bus operation was not acknowledged, e.g. due to the addressed device not
     /// being available on the bus or the device not being ready to process requests
@@ -203,6 +226,10 @@ pub enum ErrorKind {
     NoAcknowledge(NoAcknowledgeSource),
     /// The peripheral receive buffer was overrun.
     Overrun,
+    /// The operation did not complete in time, e.g. a target held SCL low for too long.
+    ///
+    /// See the [module-level documentation](self#timeouts).
+    Timeout,
     /// A different error occurred. The original error may contain more information.
     Other,
 }
@@ -242,6 +269,7 @@ impl core::fmt::Display for ErrorKind {
             Self::ArbitrationLoss => write!(f, "The arbitration was lost"),
             Self::NoAcknowledge(s) => s.fmt(f),
             Self::Overrun => write!(f, "The peripheral receive buffer was overrun"),
+            Self::Timeout => write!(f, "The operation did not complete in time"),
             Self::Other => write!(
                 f,
                 "A different error occurred. The original error may contain more information"
@@ -293,6 +321,13 @@ pub trait AddressMode: Copy + 'static + private::Sealed {}
 pub type SevenBitAddress = u8;
 
 /// 10-bit address mode type.
+///
+/// Note that 10-bit addresses defined by drivers should be specified in **right-aligned** form,
+/// e.g. in the range `0x000..=0x3FF`. The `0b11110` prefix and the split of the address over
+/// two bytes on the wire are handled by the HAL implementation.
+///
+/// 10-bit support is opt-in: HALs implement `I2c<TenBitAddress>` in addition to the default
+/// `I2c<SevenBitAddress>`, so existing 7-bit implementations and drivers are unaffected.
 pub type TenBitAddress = u16;
 
 impl AddressMode for SevenBitAddress {}
@@ -432,3 +467,609 @@ impl<A: AddressMode, T: I2c<A> + ?Sized> I2c<A> for &mut T {
         T::transaction(self, address, operations)
     }
 }
+
+/// General call address, addressing all the devices of the bus at once.
+pub const GENERAL_CALL_ADDRESS: SevenBitAddress = 0x00;
+
+/// Writes to the general call address.
+///
+/// A general call write is received by every device on the bus which supports it. The first byte
+/// defines its meaning: for example, `0x06` requests a software reset, and `0x04` requests
+/// the devices to latch their programmable address pins.
+///
+/// This makes the intent explicit, since some implementations reject address `0x00` in
+/// [`I2c::write`] or need special hardware configuration. Since several devices may acknowledge
+/// the write, an error of kind [`ErrorKind::NoAcknowledge`] means that no device acknowledged it.
+pub trait I2cGeneralCall: I2c {
+    /// Writes `data` to the general call address, in a single transaction.
+    fn write_general_call(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<T: I2cGeneralCall + ?Sized> I2cGeneralCall for &mut T {
+    #[inline]
+    fn write_general_call(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        T::write_general_call(self, data)
+    }
+}
+
+/// Scatter-gather transfers, writing or reading non-contiguous buffers in a single transaction.
+///
+/// This is the I2C equivalent of POSIX `writev` and `readv`: the buffers are transferred back to
+/// back, as a single write or read, without copying them to a contiguous buffer first. It is
+/// mostly useful when the number of buffers is only known at runtime. Otherwise, adjacent
+/// operations of the same type in [`I2c::transaction`] already have the same effect.
+///
+/// There are no default implementations, since building the operations of a transaction from a
+/// runtime number of buffers would need an allocator. Implementations typically chain DMA
+/// descriptors, or feed the buffers one after the other to the data register.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2cVectored;
+///
+/// /// Send a command header and its payload, stored in separate buffers.
+/// fn send_command<I: I2cVectored>(
+///     i2c: &mut I,
+///     header: &[u8; 2],
+///     payload: &[u8],
+/// ) -> Result<(), I::Error> {
+///     i2c.write_vectored(0x28, &[header, payload])
+/// }
+/// ```
+pub trait I2cVectored<A: AddressMode = SevenBitAddress>: I2c<A> {
+    /// Writes all the `bufs` to the target with `address`, in order, in a single transaction.
+    ///
+    /// The bytes are sent exactly like [`I2c::write`] of the concatenated buffers.
+    fn write_vectored(&mut self, address: A, bufs: &[&[u8]]) -> Result<(), Self::Error>;
+
+    /// Reads enough bytes from the target with `address` to fill all the `bufs`, in order, in a
+    /// single transaction.
+    ///
+    /// The bytes are received exactly like [`I2c::read`] of the concatenated buffers: only the last
+    /// byte of the last buffer is not acknowledged.
+    fn read_vectored(&mut self, address: A, bufs: &mut [&mut [u8]]) -> Result<(), Self::Error>;
+}
+
+impl<A: AddressMode, T: I2cVectored<A> + ?Sized> I2cVectored<A> for &mut T {
+    #[inline]
+    fn write_vectored(&mut self, address: A, bufs: &[&[u8]]) -> Result<(), Self::Error> {
+        T::write_vectored(self, address, bufs)
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, address: A, bufs: &mut [&mut [u8]]) -> Result<(), Self::Error> {
+        T::read_vectored(self, address, bufs)
+    }
+}
+
+/// Runtime configuration of the SCL clock frequency of a bus.
+///
+/// Devices sharing a bus may support different maximum frequencies, such as a 100 kHz RTC and a
+/// 400 kHz IMU. Shared bus implementations, such as the `*WithClockSpeed` devices of
+/// [`embedded-hal-bus`](https://docs.rs/embedded-hal-bus), use this trait to switch the frequency
+/// between transactions.
+pub trait I2cBusSpeed: ErrorType {
+    /// Sets the SCL clock frequency, in Hz, applied from the next transaction.
+    ///
+    /// Implementations use the highest supported frequency not above `frequency_hz`, so that
+    /// devices are never clocked faster than requested. If there is none, an error is returned.
+    fn set_clock_speed(&mut self, frequency_hz: u32) -> Result<(), Self::Error>;
+}
+
+impl<T: I2cBusSpeed + ?Sized> I2cBusSpeed for &mut T {
+    #[inline]
+    fn set_clock_speed(&mut self, frequency_hz: u32) -> Result<(), Self::Error> {
+        T::set_clock_speed(self, frequency_hz)
+    }
+}
+
+/// Runtime tuning of the internal pull-ups of a bus, for its actual capacitance.
+///
+/// The rise time of SCL and SDA grows with the capacitance of the bus, which depends on the length of the
+/// wires and the number of devices. The I2C specification limits it to 1000 ns in standard mode, 300 ns in
+/// fast mode and 120 ns in fast mode plus. MCUs with programmable pull-up currents implement this trait,
+/// so that a calibration routine can select the weakest pull-up meeting the limit, which also minimizes
+/// the power consumption.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::i2c::I2cBusCapacitance;
+///
+/// /// Select the weakest pull-up giving rise times below 300 ns, for fast mode.
+/// fn calibrate<I: I2cBusCapacitance>(i2c: &mut I) -> Result<Option<u32>, I::Error> {
+///     for microamps in [2, 5, 11, 22] {
+///         i2c.set_pullup_strength(microamps)?;
+///         if i2c.measure_rise_time_ns()? <= 300 {
+///             return Ok(Some(microamps));
+///         }
+///     }
+///     Ok(None)
+/// }
+/// ```
+pub trait I2cBusCapacitance: ErrorType {
+    /// Sets the current of the internal pull-ups of SCL and SDA, in microamps.
+    ///
+    /// Implementations use the strongest supported current not above `microamps`, so that the current
+    /// sunk by the devices is never higher than requested. A current of `0` disables the internal
+    /// pull-ups, if supported. If there is no such current, an error is returned.
+    fn set_pullup_strength(&mut self, microamps: u32) -> Result<(), Self::Error>;
+
+    /// Measures the rise time of the bus, in nanoseconds, from 30% to 70% of the supply voltage.
+    ///
+    /// The bus must be idle: implementations typically drive the lines low, release them, and time
+    /// the rising edge. If several edges or lines are measured, the slowest rise time is returned.
+    fn measure_rise_time_ns(&mut self) -> Result<u32, Self::Error>;
+}
+
+impl<T: I2cBusCapacitance + ?Sized> I2cBusCapacitance for &mut T {
+    #[inline]
+    fn set_pullup_strength(&mut self, microamps: u32) -> Result<(), Self::Error> {
+        T::set_pullup_strength(self, microamps)
+    }
+
+    #[inline]
+    fn measure_rise_time_ns(&mut self) -> Result<u32, Self::Error> {
+        T::measure_rise_time_ns(self)
+    }
+}
+
+/// Power management of the bus peripheral, for low-power designs.
+///
+/// Some MCUs can gate the clock or the power of their I2C peripheral while it is idle. Shared bus
+/// implementations, such as the `*WithPowerManagement` devices of
+/// [`embedded-hal-bus`](https://docs.rs/embedded-hal-bus), use this trait to suspend the peripheral
+/// between transactions.
+///
+/// Both methods must be idempotent: suspending a suspended peripheral, or resuming a running one,
+/// does nothing and succeeds. Starting a transaction while the peripheral is suspended is an error.
+pub trait I2cPowerManagement: ErrorType {
+    /// Suspends the peripheral, to reduce its power consumption until [`resume`](I2cPowerManagement::resume).
+    ///
+    /// Implementations disable the peripheral clock and, if supported, put the SCL and SDA pins in
+    /// high-impedance mode, leaving the lines to the pull-up resistors. The configuration, such as the
+    /// clock frequency set with [`I2cBusSpeed`], is kept.
+    fn suspend(&mut self) -> Result<(), Self::Error>;
+
+    /// Resumes the peripheral, and waits until the bus is idle so that a transaction can be started.
+    fn resume(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: I2cPowerManagement + ?Sized> I2cPowerManagement for &mut T {
+    #[inline]
+    fn suspend(&mut self) -> Result<(), Self::Error> {
+        T::suspend(self)
+    }
+
+    #[inline]
+    fn resume(&mut self) -> Result<(), Self::Error> {
+        T::resume(self)
+    }
+}
+
+/// Size of the chunks in which the default implementations of [`I2cIter`] send the bytes.
+pub const WRITE_ITER_CHUNK_SIZE: usize = 32;
+
+/// Writes streaming the bytes from an iterator, such as the encoder of a framebuffer, instead of a
+/// contiguous buffer.
+///
+/// This replaces the `WriteIter` and `WriteIterRead` traits of `embedded-hal` 0.2.
+///
+/// # Chunks
+///
+/// [`I2c::transaction`] needs all the operations of a transaction upfront, so the default
+/// implementations cannot stream an iterator in a single transaction. Instead, they copy it to an
+/// internal buffer of [`WRITE_ITER_CHUNK_SIZE`] bytes, and send each chunk in its own transaction:
+///
+/// - Iterators of at most [`WRITE_ITER_CHUNK_SIZE`] bytes are sent exactly like [`I2c::write`] and
+///   [`I2c::write_read`].
+/// - For longer iterators, there is neither continuation nor a repeated start between chunks: each
+///   chunk ends with a stop condition, and the next one starts with ST SAD+W. The target receives
+///   several writes, of [`WRITE_ITER_CHUNK_SIZE`] bytes except for the last one. This is fine
+///   for targets keeping their address pointer between writes, but not for targets expecting a
+///   register address at the start of each write.
+/// - The read of [`write_iter_read`](I2cIter::write_iter_read) follows the last chunk, after a
+///   repeated start.
+///
+/// Implementations able to stream bytes to the hardware, from an interrupt or by feeding the data
+/// register directly, should override both methods to send the whole iterator in a single
+/// transaction. Implementations without such support opt into the default ones with an empty impl:
+///
+/// ```
+/// use embedded_hal::i2c::{self, I2c, I2cIter, Operation};
+///
+/// struct MyI2c;
+/// # impl i2c::ErrorType for MyI2c {
+/// #     type Error = i2c::ErrorKind;
+/// # }
+/// impl I2c for MyI2c {
+///     // ...
+/// #     fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+/// #         Ok(())
+/// #     }
+/// }
+///
+/// impl I2cIter for MyI2c {}
+///
+/// # let mut i2c = MyI2c;
+/// // Clear a line of an SSD1306 display: data control byte, then 128 zero bytes.
+/// i2c.write_iter(0x3C, core::iter::once(0x40).chain(core::iter::repeat(0).take(128)))?;
+/// # Ok::<(), i2c::ErrorKind>(())
+/// ```
+pub trait I2cIter<A: AddressMode = SevenBitAddress>: I2c<A> {
+    /// Writes the `bytes` to the target with `address`.
+    ///
+    /// If `bytes` is empty, an empty write is sent, like [`I2c::write`] of an empty buffer. See the
+    /// [trait documentation](I2cIter#chunks) for iterators longer than [`WRITE_ITER_CHUNK_SIZE`].
+    fn write_iter<B>(&mut self, address: A, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        write_chunks(self, address, bytes, None)
+    }
+
+    /// Writes the `bytes` to the target with `address` and then reads enough bytes to fill `read`.
+    ///
+    /// See the [trait documentation](I2cIter#chunks) for iterators longer than
+    /// [`WRITE_ITER_CHUNK_SIZE`].
+    fn write_iter_read<B>(
+        &mut self,
+        address: A,
+        bytes: B,
+        read: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        write_chunks(self, address, bytes, Some(read))
+    }
+}
+
+impl<A: AddressMode, T: I2cIter<A> + ?Sized> I2cIter<A> for &mut T {
+    #[inline]
+    fn write_iter<B>(&mut self, address: A, bytes: B) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        T::write_iter(self, address, bytes)
+    }
+
+    #[inline]
+    fn write_iter_read<B>(
+        &mut self,
+        address: A,
+        bytes: B,
+        read: &mut [u8],
+    ) -> Result<(), Self::Error>
+    where
+        B: IntoIterator<Item = u8>,
+    {
+        T::write_iter_read(self, address, bytes, read)
+    }
+}
+
+/// Sends `bytes` in transactions of [`WRITE_ITER_CHUNK_SIZE`] bytes, the last one followed by `read`.
+fn write_chunks<A, T, B>(
+    i2c: &mut T,
+    address: A,
+    bytes: B,
+    mut read: Option<&mut [u8]>,
+) -> Result<(), T::Error>
+where
+    A: AddressMode,
+    T: I2c<A> + ?Sized,
+    B: IntoIterator<Item = u8>,
+{
+    let mut bytes = bytes.into_iter().peekable();
+    let mut chunk = [0; WRITE_ITER_CHUNK_SIZE];
+    loop {
+        let mut len = 0;
+        for (byte, new) in chunk.iter_mut().zip(&mut bytes) {
+            *byte = new;
+            len += 1;
+        }
+        let write = Operation::Write(&chunk[..len]);
+        match (bytes.peek(), read.take()) {
+            (Some(_), pending) => {
+                i2c.transaction(address, &mut [write])?;
+                read = pending;
+            }
+            (None, Some(read)) => {
+                return i2c.transaction(address, &mut [write, Operation::Read(read)])
+            }
+            (None, None) => return i2c.transaction(address, &mut [write]),
+        }
+    }
+}
+
+/// I2C multiplexer (switch), such as the PCA9548, connecting one of its downstream channels at a
+/// time to the upstream bus.
+///
+/// Implementations send the multiplexer's own commands: they usually hold the upstream bus (or a
+/// shared device on it) and the address of the multiplexer. The downstream devices are then
+/// accessed through the upstream bus while their channel is selected.
+pub trait Multiplexer: ErrorType {
+    /// Connects `channel` to the upstream bus, disconnecting the other channels.
+    ///
+    /// Channels are numbered from 0. Implementations should return an error if `channel` does not
+    /// exist, rather than selecting another one.
+    fn select_channel(&mut self, channel: u8) -> Result<(), Self::Error>;
+
+    /// Disconnects all the channels from the upstream bus.
+    fn deselect_all(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: Multiplexer + ?Sized> Multiplexer for &mut T {
+    #[inline]
+    fn select_channel(&mut self, channel: u8) -> Result<(), Self::Error> {
+        T::select_channel(self, channel)
+    }
+
+    #[inline]
+    fn deselect_all(&mut self) -> Result<(), Self::Error> {
+        T::deselect_all(self)
+    }
+}
+
+/// Direction of an I2C transfer, from the point of view of the controller.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Direction {
+    /// The controller reads from the target (`SAD+R`).
+    Read,
+    /// The controller writes to the target (`SAD+W`).
+    Write,
+}
+
+/// Event reported by an I2C target.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum TargetEvent<A: AddressMode = SevenBitAddress> {
+    /// A start condition (`ST`), followed by one of the addresses of the target.
+    Start {
+        /// Address the controller selected.
+        address: A,
+        /// Direction of the transfer.
+        direction: Direction,
+    },
+    /// A repeated start condition (`SR`), followed by one of the addresses of the target.
+    Restart {
+        /// Address the controller selected.
+        address: A,
+        /// Direction of the transfer.
+        direction: Direction,
+    },
+    /// A stop condition (`SP`), ending the transaction.
+    Stop,
+}
+
+/// Blocking I2C target (slave).
+///
+/// A target is driven by the controller: it [`listen`](I2cTarget::listen)s for events, and
+/// runs the transfer requested by the controller after each [`TargetEvent::Start`] or
+/// [`TargetEvent::Restart`]:
+///
+/// - For [`Direction::Read`], the target sends bytes with [`respond`](I2cTarget::respond).
+/// - For [`Direction::Write`], the target receives bytes with [`accept`](I2cTarget::accept).
+///
+/// # Clock stretching
+///
+/// Once an address event was reported, the target stretches the clock (holds SCL low) until
+/// `respond` or `accept` is called, so the controller waits for the application to be ready.
+/// Calling `listen` again instead skips the transfer: bytes written by the controller are
+/// acknowledged and discarded, and bytes read by the controller are sent as `0xFF`.
+///
+/// # Early stop
+///
+/// The controller decides when a transfer ends:
+///
+/// - `respond` returns once the controller does not acknowledge a byte (`NMAK`). If the
+///   controller reads fewer bytes than provided, the remaining bytes are not sent. If it reads
+///   more bytes, the extra bytes are sent as `0xFF`.
+/// - `accept` returns once the controller sends a stop or repeated start condition. If the
+///   controller writes more bytes than fit in the buffer, the first extra byte is not acknowledged.
+///
+/// In both cases, the stop or repeated start condition is then reported by the next call to `listen`.
+///
+/// # Example
+///
+/// Emulating a 256-byte EEPROM: writes start with the memory address, followed by the data to store,
+/// while reads return the data starting at the current memory address.
+///
+/// ```
+/// use embedded_hal::i2c::{Direction, I2cTarget, TargetEvent};
+///
+/// fn serve<T: I2cTarget>(target: &mut T, memory: &mut [u8; 256]) -> Result<(), T::Error> {
+///     let mut pointer = 0;
+///     loop {
+///         match target.listen()? {
+///             TargetEvent::Start { direction, .. } | TargetEvent::Restart { direction, .. } => {
+///                 match direction {
+///                     Direction::Write => {
+///                         let mut buffer = [0; 17];
+///                         let len = target.accept(&mut buffer)?;
+///                         if let [address, data @ ..] = &buffer[..len] {
+///                             pointer = usize::from(*address);
+///                             for byte in data {
+///                                 memory[pointer] = *byte;
+///                                 pointer = (pointer + 1) % memory.len();
+///                             }
+///                         }
+///                     }
+///                     Direction::Read => {
+///                         let read = target.respond(&memory[pointer..])?;
+///                         pointer = (pointer + read) % memory.len();
+///                     }
+///                 }
+///             }
+///             TargetEvent::Stop => {}
+///         }
+///     }
+/// }
+/// ```
+pub trait I2cTarget<A: AddressMode = SevenBitAddress>: ErrorType {
+    /// Wait for the next event on the bus.
+    ///
+    /// Only start and repeated start conditions followed by one of the addresses of
+    /// the target are reported. Transactions addressed to other targets are ignored.
+    fn listen(&mut self) -> Result<TargetEvent<A>, Self::Error>;
+
+    /// Send `write` to the controller, after a [`Direction::Read`] event.
+    ///
+    /// Returns the number of bytes read by the controller, which can be smaller or larger than
+    /// the length of `write`. See the [trait documentation](I2cTarget#early-stop) for details.
+    fn respond(&mut self, write: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Receive bytes from the controller into `read`, after a [`Direction::Write`] event.
+    ///
+    /// Returns the number of bytes received, which is at most the length of `read`.
+    /// See the [trait documentation](I2cTarget#early-stop) for details.
+    fn accept(&mut self, read: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+impl<A: AddressMode, T: I2cTarget<A> + ?Sized> I2cTarget<A> for &mut T {
+    #[inline]
+    fn listen(&mut self) -> Result<TargetEvent<A>, Self::Error> {
+        T::listen(self)
+    }
+
+    #[inline]
+    fn respond(&mut self, write: &[u8]) -> Result<usize, Self::Error> {
+        T::respond(self, write)
+    }
+
+    #[inline]
+    fn accept(&mut self, read: &mut [u8]) -> Result<usize, Self::Error> {
+        T::accept(self, read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bus event recorded by [`RecordingBus`].
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Event {
+        /// Start or repeated start condition.
+        Start,
+        Written(u8),
+        /// Read of this many bytes.
+        Read(usize),
+        Stop,
+    }
+
+    /// Controller recording the events of the transactions it runs.
+    struct RecordingBus {
+        events: [Event; 128],
+        len: usize,
+    }
+
+    impl RecordingBus {
+        fn new() -> Self {
+            Self {
+                events: [Event::Stop; 128],
+                len: 0,
+            }
+        }
+
+        fn record(&mut self, event: Event) {
+            self.events[self.len] = event;
+            self.len += 1;
+        }
+
+        fn events(&self) -> &[Event] {
+            &self.events[..self.len]
+        }
+    }
+
+    impl ErrorType for RecordingBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for RecordingBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), ErrorKind> {
+            assert_eq!(address, 0x3C);
+            let mut reading = None;
+            for operation in operations {
+                let is_read = matches!(operation, Operation::Read(_));
+                if reading != Some(is_read) {
+                    self.record(Event::Start);
+                    reading = Some(is_read);
+                }
+                match operation {
+                    Operation::Write(write) => {
+                        write.iter().for_each(|&b| self.record(Event::Written(b)))
+                    }
+                    Operation::Read(read) => {
+                        read.fill(0xA5);
+                        self.record(Event::Read(read.len()));
+                    }
+                }
+            }
+            self.record(Event::Stop);
+            Ok(())
+        }
+    }
+
+    impl I2cIter for RecordingBus {}
+
+    /// Returns the events of a write transaction of `bytes`, not yet stopped.
+    fn write(bytes: core::ops::Range<u8>) -> impl Iterator<Item = Event> {
+        core::iter::once(Event::Start).chain(bytes.map(Event::Written))
+    }
+
+    const STOP: [Event; 1] = [Event::Stop];
+
+    #[test]
+    fn write_iter_fits_in_one_chunk() {
+        for len in [0, 1, WRITE_ITER_CHUNK_SIZE as u8] {
+            let mut bus = RecordingBus::new();
+            bus.write_iter(0x3C, 0..len).unwrap();
+            assert!(bus.events().iter().copied().eq(write(0..len).chain(STOP)));
+        }
+    }
+
+    #[test]
+    fn write_iter_splits_long_iterators() {
+        let mut bus = RecordingBus::new();
+        bus.write_iter(0x3C, 0..70).unwrap();
+        let expected = write(0..32)
+            .chain(STOP)
+            .chain(write(32..64))
+            .chain(STOP)
+            .chain(write(64..70))
+            .chain(STOP);
+        assert!(bus.events().iter().copied().eq(expected));
+
+        // Exact multiples of the chunk size do not end with an empty write.
+        let mut bus = RecordingBus::new();
+        bus.write_iter(0x3C, 0..64).unwrap();
+        let expected = write(0..32).chain(STOP).chain(write(32..64)).chain(STOP);
+        assert!(bus.events().iter().copied().eq(expected));
+    }
+
+    #[test]
+    fn write_iter_read_reads_after_the_last_chunk() {
+        let mut bus = RecordingBus::new();
+        let mut read = [0; 3];
+        bus.write_iter_read(0x3C, 0x10..0x12, &mut read).unwrap();
+        assert_eq!(read, [0xA5; 3]);
+        let expected = write(0x10..0x12).chain([Event::Start, Event::Read(3), Event::Stop]);
+        assert!(bus.events().iter().copied().eq(expected));
+
+        let mut bus = RecordingBus::new();
+        bus.write_iter_read(0x3C, 0..40, &mut read).unwrap();
+        let expected = write(0..32).chain(STOP).chain(write(32..40)).chain([
+            Event::Start,
+            Event::Read(3),
+            Event::Stop,
+        ]);
+        assert!(bus.events().iter().copied().eq(expected));
+    }
+}
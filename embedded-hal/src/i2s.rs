@@ -0,0 +1,245 @@
+//! Blocking I2S traits.
+//!
+//! I2S (Inter-IC Sound) connects audio DACs, ADCs and codecs, such as the MAX98357, PCM5102 or WM8978,
+//! to a microcontroller. The [`I2sBus`] trait transmits and receives audio samples, with the format
+//! set by an [`I2sConfig`].
+//!
+//! # Samples
+//!
+//! Samples are signed integers, in the range of the configured [`BitDepth`]: for instance,
+//! `-32768..=32767` for [`BitDepth::Sixteen`]. In [`ChannelMode::Stereo`], the samples of the left
+//! and right channels are interleaved, starting with the left channel. In the mono modes, each
+//! sample is one frame, on the selected channel.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::i2s::{BitDepth, I2sBus, I2sConfig};
+//!
+//! /// Play one period of a 1 kHz square wave, at 48 kHz in stereo.
+//! fn beep<I: I2sBus>(i2s: &mut I) -> Result<(), I::Error> {
+//!     i2s.set_config(&I2sConfig::new(48_000).bit_depth(BitDepth::Sixteen))?;
+//!     let mut samples = [0; 96];
+//!     for (i, frame) in samples.chunks_mut(2).enumerate() {
+//!         let level = if i < 24 { 8_000 } else { -8_000 };
+//!         frame.fill(level);
+//!     }
+//!     i2s.transmit(&samples)
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// I2S error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic I2S error kind.
+    ///
+    /// By using this method, I2S errors freely defined by HAL implementations
+    /// can be converted to a set of generic I2S errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// I2S error kind.
+///
+/// This represents a common set of I2S operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common I2S errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The transmitter ran out of samples, and sent silence or repeated samples.
+    Underrun,
+    /// The receiver got samples faster than they were read, and some were lost.
+    Overrun,
+    /// The requested sample rate, bit depth or channel mode is not supported.
+    UnsupportedConfig,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Underrun => write!(f, "The transmitter ran out of samples"),
+            Self::Overrun => write!(f, "Received samples were lost"),
+            Self::UnsupportedConfig => write!(f, "The requested configuration is not supported"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// I2S error type trait.
+///
+/// This just defines the error type, to be used by the other I2S traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Number of bits of each sample.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum BitDepth {
+    /// 16-bit samples.
+    Sixteen,
+    /// 24-bit samples.
+    TwentyFour,
+    /// 32-bit samples.
+    ThirtyTwo,
+}
+
+impl BitDepth {
+    /// Returns the number of bits of each sample.
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::Sixteen => 16,
+            Self::TwentyFour => 24,
+            Self::ThirtyTwo => 32,
+        }
+    }
+}
+
+/// Channels carrying the samples.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ChannelMode {
+    /// Both channels, with interleaved samples, starting with the left channel.
+    Stereo,
+    /// Only the left channel.
+    MonoLeft,
+    /// Only the right channel.
+    MonoRight,
+}
+
+/// I2S configuration.
+///
+/// Configurations are built from [`I2sConfig::new`], or from the default configuration, and adjusted
+/// with the builder methods:
+///
+/// ```
+/// use embedded_hal::i2s::{BitDepth, ChannelMode, I2sConfig};
+///
+/// // 16 kHz, 24-bit samples from a single MEMS microphone.
+/// const CONFIG: I2sConfig = I2sConfig::new(16_000)
+///     .bit_depth(BitDepth::TwentyFour)
+///     .channel_mode(ChannelMode::MonoLeft);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct I2sConfig {
+    /// Sample rate, in frames per second.
+    pub sample_rate_hz: u32,
+    /// Number of bits of each sample.
+    pub bit_depth: BitDepth,
+    /// Channels carrying the samples.
+    pub channel_mode: ChannelMode,
+}
+
+impl I2sConfig {
+    /// Create a new configuration with the given sample rate, and 16-bit stereo samples.
+    #[inline]
+    pub const fn new(sample_rate_hz: u32) -> Self {
+        Self {
+            sample_rate_hz,
+            bit_depth: BitDepth::Sixteen,
+            channel_mode: ChannelMode::Stereo,
+        }
+    }
+
+    /// Set the sample rate, in frames per second.
+    #[inline]
+    pub const fn sample_rate_hz(mut self, sample_rate_hz: u32) -> Self {
+        self.sample_rate_hz = sample_rate_hz;
+        self
+    }
+
+    /// Set the number of bits of each sample.
+    #[inline]
+    pub const fn bit_depth(mut self, bit_depth: BitDepth) -> Self {
+        self.bit_depth = bit_depth;
+        self
+    }
+
+    /// Set the channels carrying the samples.
+    #[inline]
+    pub const fn channel_mode(mut self, channel_mode: ChannelMode) -> Self {
+        self.channel_mode = channel_mode;
+        self
+    }
+}
+
+impl Default for I2sConfig {
+    /// 48 kHz, 16-bit stereo.
+    #[inline]
+    fn default() -> Self {
+        Self::new(48_000)
+    }
+}
+
+/// Blocking I2S bus, in controller mode.
+///
+/// See the [module documentation](self) for the format of the samples.
+pub trait I2sBus: ErrorType {
+    /// Apply `config` to the bus.
+    ///
+    /// Implementations should wait for pending transmissions to complete before changing the configuration.
+    /// Returns an error of kind [`ErrorKind::UnsupportedConfig`] if the configuration is not supported,
+    /// in which case the previous configuration is kept.
+    fn set_config(&mut self, config: &I2sConfig) -> Result<(), Self::Error>;
+
+    /// Transmit `samples`, blocking until all of them were handed to the hardware.
+    ///
+    /// In [`ChannelMode::Stereo`], `samples` should contain whole frames: a trailing left sample may
+    /// not be sent until the next call.
+    fn transmit(&mut self, samples: &[i32]) -> Result<(), Self::Error>;
+
+    /// Receive samples into `samples`, blocking until at least one frame was received.
+    ///
+    /// Returns the number of samples received, which is a whole number of frames.
+    fn receive(&mut self, samples: &mut [i32]) -> Result<usize, Self::Error>;
+}
+
+impl<T: I2sBus + ?Sized> I2sBus for &mut T {
+    #[inline]
+    fn set_config(&mut self, config: &I2sConfig) -> Result<(), Self::Error> {
+        T::set_config(self, config)
+    }
+
+    #[inline]
+    fn transmit(&mut self, samples: &[i32]) -> Result<(), Self::Error> {
+        T::transmit(self, samples)
+    }
+
+    #[inline]
+    fn receive(&mut self, samples: &mut [i32]) -> Result<usize, Self::Error> {
+        T::receive(self, samples)
+    }
+}
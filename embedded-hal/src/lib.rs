@@ -2,11 +2,23 @@
 #![warn(missing_docs)]
 #![no_std]
 
+pub mod adc;
+pub mod capture;
 pub mod delay;
 pub mod digital;
 pub mod i2c;
+pub mod i2s;
+pub mod onewire;
 pub mod pwm;
+pub mod qei;
+pub mod rng;
+pub mod rtc;
+pub mod smbus;
 pub mod spi;
+pub mod time;
+pub mod timer;
+pub mod uart;
+pub mod watchdog;
 
 mod private {
     use crate::i2c::{SevenBitAddress, TenBitAddress};
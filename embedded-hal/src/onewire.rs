@@ -0,0 +1,157 @@
+//! 1-Wire bus traits.
+//!
+//! The [`OneWire`] trait provides the low-level primitives of the 1-Wire protocol, used by
+//! devices such as the DS18B20 temperature sensor or the DS2431 EEPROM: the reset and presence
+//! pulse, and the read and write time slots. Bytes are built on top of these primitives, least
+//! significant bit first.
+//!
+//! Implementations can use a dedicated peripheral, a UART, or bit-bang a GPIO pin.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::onewire::OneWire;
+//!
+//! const SKIP_ROM: u8 = 0xCC;
+//! const CONVERT_T: u8 = 0x44;
+//!
+//! /// Start a temperature conversion on the only sensor of the bus.
+//! fn start_conversion<B: OneWire>(bus: &mut B) -> Result<bool, B::Error> {
+//!     if !bus.reset()? {
+//!         // No device on the bus.
+//!         return Ok(false);
+//!     }
+//!     bus.write_byte(SKIP_ROM)?;
+//!     bus.write_byte(CONVERT_T)?;
+//!     Ok(true)
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// 1-Wire error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic 1-Wire error kind.
+    ///
+    /// By using this method, 1-Wire errors freely defined by HAL implementations
+    /// can be converted to a set of generic 1-Wire errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// 1-Wire error kind.
+///
+/// This represents a common set of 1-Wire operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common 1-Wire errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The bus is held low, and cannot be released.
+    BusShorted,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BusShorted => write!(f, "The bus is held low"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// 1-Wire error type trait.
+///
+/// This just defines the error type, to be used by the other 1-Wire traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// 1-Wire bus.
+pub trait OneWire: ErrorType {
+    /// Send a reset pulse, and return whether a device answered with a presence pulse.
+    fn reset(&mut self) -> Result<bool, Self::Error>;
+
+    /// Generate a read time slot, and return the bit sent by the device.
+    fn read_bit(&mut self) -> Result<bool, Self::Error>;
+
+    /// Generate a write time slot for `bit`.
+    fn write_bit(&mut self, bit: bool) -> Result<(), Self::Error>;
+
+    /// Read a byte, least significant bit first.
+    #[inline]
+    fn read_byte(&mut self) -> Result<u8, Self::Error> {
+        let mut byte = 0;
+        for i in 0..8 {
+            if self.read_bit()? {
+                byte |= 1 << i;
+            }
+        }
+        Ok(byte)
+    }
+
+    /// Write a byte, least significant bit first.
+    #[inline]
+    fn write_byte(&mut self, byte: u8) -> Result<(), Self::Error> {
+        for i in 0..8 {
+            self.write_bit(byte & (1 << i) != 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: OneWire + ?Sized> OneWire for &mut T {
+    #[inline]
+    fn reset(&mut self) -> Result<bool, Self::Error> {
+        T::reset(self)
+    }
+
+    #[inline]
+    fn read_bit(&mut self) -> Result<bool, Self::Error> {
+        T::read_bit(self)
+    }
+
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Result<(), Self::Error> {
+        T::write_bit(self, bit)
+    }
+
+    #[inline]
+    fn read_byte(&mut self) -> Result<u8, Self::Error> {
+        T::read_byte(self)
+    }
+
+    #[inline]
+    fn write_byte(&mut self, byte: u8) -> Result<(), Self::Error> {
+        T::write_byte(self, byte)
+    }
+}
@@ -1,4 +1,14 @@
 //! Pulse Width Modulation (PWM) traits.
+//!
+//! The [`SetDutyCycle`] trait sets the duty cycle of a PWM channel, while the [`SetPeriod`] trait
+//! changes its period (or frequency). Both traits share the same [`ErrorType`], so a single
+//! channel type can implement both.
+//!
+//! The [`ComplementaryPwm`] trait drives a pair of complementary outputs with dead time, as used
+//! by half-bridges in motor drivers and DC-DC converters.
+//!
+//! The [`PwmGroup`] trait updates the duty cycles of several channels of a timer at once, such as
+//! the three channels of an RGB LED, without the glitches of updating them one after the other.
 
 #[cfg(feature = "defmt-03")]
 use crate::defmt;
@@ -29,6 +39,10 @@ impl Error for core::convert::Infallible {
 #[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
 #[non_exhaustive]
 pub enum ErrorKind {
+    /// The requested period is out of the range supported by the timer.
+    InvalidPeriod,
+    /// The requested dead time is out of the range supported by the timer.
+    InvalidDeadTime,
     /// A different error occurred. The original error may contain more information.
     Other,
 }
@@ -46,6 +60,10 @@ impl core::fmt::Display for ErrorKind {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
+            Self::InvalidPeriod => write!(f, "The requested period is out of the supported range"),
+            Self::InvalidDeadTime => {
+                write!(f, "The requested dead time is out of the supported range")
+            }
             Self::Other => write!(
                 f,
                 "A different error occurred. The original error may contain more information"
@@ -150,3 +168,202 @@ impl<T: SetDutyCycle + ?Sized> SetDutyCycle for &mut T {
         T::set_duty_cycle_percent(self, percent)
     }
 }
+
+/// PWM channel / pin with a configurable period.
+///
+/// Changing the period preserves the duty cycle *ratio*, not the raw compare value: a channel
+/// at 50% duty cycle stays at 50% duty cycle. Since the resolution of the timer can depend
+/// on the period, [`SetDutyCycle::max_duty_cycle`] may return a different value afterwards.
+///
+/// Note that several channels can share the same timer, in which case changing the period of one
+/// channel also changes the period of the others.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::{SetDutyCycle, SetPeriod};
+///
+/// /// Tone generator driving a piezo buzzer.
+/// struct Buzzer<P> {
+///     pwm: P,
+/// }
+///
+/// impl<P: SetDutyCycle + SetPeriod> Buzzer<P> {
+///     /// Play a tone at `frequency_hz`.
+///     fn play(&mut self, frequency_hz: u32) -> Result<(), P::Error> {
+///         self.pwm.set_period_ns(1_000_000_000 / frequency_hz)?;
+///         // A square wave is the loudest.
+///         self.pwm.set_duty_cycle_percent(50)
+///     }
+///
+///     fn stop(&mut self) -> Result<(), P::Error> {
+///         self.pwm.set_duty_cycle_fully_off()
+///     }
+/// }
+/// ```
+pub trait SetPeriod: ErrorType {
+    /// Set the period to `period_ns` nanoseconds, or the closest period achievable by the timer.
+    ///
+    /// Use [`period_ns`](SetPeriod::period_ns) to get the period which was actually configured.
+    /// Returns an error of kind [`ErrorKind::InvalidPeriod`] if the period is out of the range
+    /// supported by the timer.
+    fn set_period_ns(&mut self, period_ns: u32) -> Result<(), Self::Error>;
+
+    /// Get the current period, in nanoseconds.
+    ///
+    /// Because timers quantize the period, this may differ from the value passed to
+    /// [`set_period_ns`](SetPeriod::set_period_ns).
+    fn period_ns(&self) -> u32;
+}
+
+impl<T: SetPeriod + ?Sized> SetPeriod for &mut T {
+    #[inline]
+    fn set_period_ns(&mut self, period_ns: u32) -> Result<(), Self::Error> {
+        T::set_period_ns(self, period_ns)
+    }
+
+    #[inline]
+    fn period_ns(&self) -> u32 {
+        T::period_ns(self)
+    }
+}
+
+/// Pair of complementary PWM outputs, with dead time.
+///
+/// The high side output is active for `duty / max_duty_cycle` of the period, and the low side output
+/// for the rest of it, minus the dead time. During the dead time, inserted at each transition, both
+/// outputs are inactive, so the switches of a half-bridge are never on at the same time
+/// ("shoot-through").
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::ComplementaryPwm;
+///
+/// /// Half-bridge of a buck converter.
+/// fn start<P: ComplementaryPwm>(bridge: &mut P) -> Result<(), P::Error> {
+///     // The MOSFETs need 200 ns to turn off.
+///     bridge.set_dead_time_ns(200)?;
+///     // Soft start, at a quarter of the input voltage.
+///     let max = bridge.max_duty_cycle();
+///     bridge.set_duty_cycle_high_side(max / 4)?;
+///     bridge.enable()
+/// }
+/// ```
+pub trait ComplementaryPwm: ErrorType {
+    /// Get the maximum duty cycle value.
+    ///
+    /// This value corresponds to the high side being active for the whole period.
+    fn max_duty_cycle(&self) -> u16;
+
+    /// Set the duty cycle of the high side to `duty / max_duty_cycle`.
+    ///
+    /// The low side is active for the rest of the period, `max_duty_cycle - duty`.
+    /// The duty cycle is only applied to the outputs while they are enabled.
+    fn set_duty_cycle_high_side(&mut self, duty: u16) -> Result<(), Self::Error>;
+
+    /// Set the dead time inserted at each transition, in nanoseconds.
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidDeadTime`] if the dead time is out of the range
+    /// supported by the timer.
+    fn set_dead_time_ns(&mut self, ns: u32) -> Result<(), Self::Error>;
+
+    /// Enable the outputs.
+    fn enable(&mut self) -> Result<(), Self::Error>;
+
+    /// Disable the outputs, making both of them inactive.
+    fn disable(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: ComplementaryPwm + ?Sized> ComplementaryPwm for &mut T {
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        T::max_duty_cycle(self)
+    }
+
+    #[inline]
+    fn set_duty_cycle_high_side(&mut self, duty: u16) -> Result<(), Self::Error> {
+        T::set_duty_cycle_high_side(self, duty)
+    }
+
+    #[inline]
+    fn set_dead_time_ns(&mut self, ns: u32) -> Result<(), Self::Error> {
+        T::set_dead_time_ns(self, ns)
+    }
+
+    #[inline]
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        T::enable(self)
+    }
+
+    #[inline]
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        T::disable(self)
+    }
+}
+
+/// Group of `N` PWM channels, updated together.
+///
+/// Setting the duty cycles of several channels one after the other lets the outputs run for a period
+/// with some of the channels updated and some not, which shows as a color glitch on an RGB LED, or a
+/// torque step on a motor. [`set_all_duty_cycles`](PwmGroup::set_all_duty_cycles) updates all the
+/// channels in the same period, for instance by writing all the preload (shadow) registers of the timer
+/// before triggering a simultaneous update.
+///
+/// All the channels share the same [`max_duty_cycle`](PwmGroup::max_duty_cycle). When the hardware can
+/// not update the channels atomically, implementations should document it.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::pwm::PwmGroup;
+///
+/// /// Fade an RGB LED to `color`, given in 8-bit components.
+/// fn set_color<P: PwmGroup<3>>(led: &mut P, color: [u8; 3]) -> Result<(), P::Error> {
+///     let max = u32::from(led.max_duty_cycle());
+///     let duties = color.map(|c| (u32::from(c) * max / 255) as u16);
+///     led.set_all_duty_cycles(&duties)?;
+///     led.enable_all()
+/// }
+/// ```
+pub trait PwmGroup<const N: usize>: ErrorType {
+    /// Get the maximum duty cycle value, shared by all the channels.
+    ///
+    /// This value corresponds to a 100% duty cycle.
+    fn max_duty_cycle(&self) -> u16;
+
+    /// Set the duty cycle of channel `i` to `duties[i] / max_duty_cycle`, for all the channels at once.
+    ///
+    /// The caller is responsible for ensuring that the duty cycle values are less than or equal to the
+    /// maximum duty cycle value, as reported by [`max_duty_cycle`](PwmGroup::max_duty_cycle). The duty
+    /// cycles are only applied to the outputs while they are enabled.
+    fn set_all_duty_cycles(&mut self, duties: &[u16; N]) -> Result<(), Self::Error>;
+
+    /// Enable the outputs of all the channels.
+    fn enable_all(&mut self) -> Result<(), Self::Error>;
+
+    /// Disable the outputs of all the channels, making them inactive.
+    fn disable_all(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: PwmGroup<N> + ?Sized, const N: usize> PwmGroup<N> for &mut T {
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        T::max_duty_cycle(self)
+    }
+
+    #[inline]
+    fn set_all_duty_cycles(&mut self, duties: &[u16; N]) -> Result<(), Self::Error> {
+        T::set_all_duty_cycles(self, duties)
+    }
+
+    #[inline]
+    fn enable_all(&mut self) -> Result<(), Self::Error> {
+        T::enable_all(self)
+    }
+
+    #[inline]
+    fn disable_all(&mut self) -> Result<(), Self::Error> {
+        T::disable_all(self)
+    }
+}
@@ -0,0 +1,209 @@
+//! Quadrature encoder interface (QEI) traits.
+//!
+//! The [`Qei`] trait reads a hardware quadrature decoder, counting the steps of a rotary or
+//! linear encoder. The counter wraps around at the width of the hardware counter:
+//! use [`delta`] to compute the signed number of steps between two readings.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::qei::{delta, Qei};
+//!
+//! /// Track the absolute position of a motor shaft, with a 16-bit hardware counter.
+//! struct Position<Q: Qei> {
+//!     qei: Q,
+//!     last: Q::Count,
+//!     steps: i64,
+//! }
+//!
+//! impl<Q: Qei> Position<Q> {
+//!     fn update(&mut self) -> Result<i64, Q::Error> {
+//!         let now = self.qei.count()?;
+//!         self.steps += delta(self.last.into(), now.into(), 16);
+//!         self.last = now;
+//!         Ok(self.steps)
+//!     }
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// QEI error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic QEI error kind.
+    ///
+    /// By using this method, QEI errors freely defined by HAL implementations
+    /// can be converted to a set of generic QEI errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// QEI error kind.
+///
+/// This represents a common set of QEI operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common QEI errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// QEI error type trait.
+///
+/// This just defines the error type, to be used by the other QEI traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &T {
+    type Error = T::Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Counting direction of a quadrature encoder.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Direction {
+    /// The counter is incremented.
+    Upcounting,
+    /// The counter is decremented.
+    Downcounting,
+}
+
+/// Quadrature encoder interface.
+pub trait Qei: ErrorType {
+    /// Counter type, an unsigned integer as wide as the hardware counter.
+    type Count: Copy + Into<u64>;
+
+    /// Returns the current value of the counter.
+    fn count(&self) -> Result<Self::Count, Self::Error>;
+
+    /// Returns the direction in which the counter was last updated.
+    fn direction(&self) -> Result<Direction, Self::Error>;
+}
+
+impl<T: Qei + ?Sized> Qei for &T {
+    type Count = T::Count;
+
+    #[inline]
+    fn count(&self) -> Result<Self::Count, Self::Error> {
+        T::count(self)
+    }
+
+    #[inline]
+    fn direction(&self) -> Result<Direction, Self::Error> {
+        T::direction(self)
+    }
+}
+
+impl<T: Qei + ?Sized> Qei for &mut T {
+    type Count = T::Count;
+
+    #[inline]
+    fn count(&self) -> Result<Self::Count, Self::Error> {
+        T::count(self)
+    }
+
+    #[inline]
+    fn direction(&self) -> Result<Direction, Self::Error> {
+        T::direction(self)
+    }
+}
+
+/// Returns the signed number of steps from `previous` to `current`, for a counter
+/// of `bits` bits wrapping around.
+///
+/// The shortest path is assumed: the counter must be read at least twice per half
+/// revolution of the counter, or steps are lost.
+///
+/// `bits` must be between 1 and 64, and both values must fit in `bits` bits.
+///
+/// ```
+/// use embedded_hal::qei::delta;
+///
+/// assert_eq!(delta(10, 15, 16), 5);
+/// // The counter wrapped around while counting up.
+/// assert_eq!(delta(0xFFFE, 0x0001, 16), 3);
+/// // The counter wrapped around while counting down.
+/// assert_eq!(delta(0x0001, 0xFFFE, 16), -3);
+/// ```
+#[inline]
+pub fn delta(previous: u64, current: u64, bits: u32) -> i64 {
+    debug_assert!((1..=64).contains(&bits));
+    let shift = 64 - bits;
+    // Move the counter to the most significant bits, so the subtraction wraps at the counter width.
+    ((current << shift).wrapping_sub(previous << shift) as i64) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_without_wraparound() {
+        assert_eq!(delta(100, 100, 16), 0);
+        assert_eq!(delta(100, 250, 16), 150);
+        assert_eq!(delta(250, 100, 16), -150);
+    }
+
+    #[test]
+    fn delta_across_wraparound() {
+        assert_eq!(delta(0xFFFF, 0x0000, 16), 1);
+        assert_eq!(delta(0x0000, 0xFFFF, 16), -1);
+        assert_eq!(delta(0xFF, 0x01, 8), 2);
+        assert_eq!(delta(0x01, 0xFF, 8), -2);
+        assert_eq!(delta(0xFFFF_FFF0, 0x10, 32), 0x20);
+    }
+
+    #[test]
+    fn delta_half_range() {
+        // Up to half of the range is counted up, beyond it is counted down.
+        assert_eq!(delta(0, 0x7FFF, 16), 0x7FFF);
+        assert_eq!(delta(0, 0x8000, 16), -0x8000);
+        assert_eq!(delta(0, 0x8001, 16), -0x7FFF);
+    }
+
+    #[test]
+    fn delta_extreme_widths() {
+        assert_eq!(delta(1, 0, 1), -1);
+        assert_eq!(delta(0, 1, 1), -1);
+        assert_eq!(delta(u64::MAX, 0, 64), 1);
+        assert_eq!(delta(0, u64::MAX, 64), -1);
+    }
+}
@@ -0,0 +1,264 @@
+//! Random number generator traits.
+//!
+//! The [`Rng`] trait fills buffers with random bytes from a hardware random number generator
+//! (or TRNG), such as the ones found in most recent microcontrollers. Unlike `rand_core`'s
+//! `RngCore`, it reports failures, so that crypto and networking code never silently uses
+//! bytes which are not random.
+//!
+//! Whether the bytes are suitable for cryptographic use depends on the hardware: refer to the
+//! documentation of the HAL.
+//!
+//! With the `rand_core-09` feature, [`RandCore`] exposes an [`Rng`] implementation through the
+//! `rand_core` 0.9 traits, for code built on them.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::rng::Rng;
+//!
+//! /// Generate a random 96-bit nonce.
+//! fn nonce<R: Rng>(rng: &mut R) -> Result<[u8; 12], R::Error> {
+//!     let mut nonce = [0; 12];
+//!     rng.fill_bytes(&mut nonce)?;
+//!     Ok(nonce)
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// RNG error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic RNG error kind.
+    ///
+    /// By using this method, RNG errors freely defined by HAL implementations
+    /// can be converted to a set of generic RNG errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// RNG error kind.
+///
+/// This represents a common set of RNG operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common RNG errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Not enough entropy is available yet, e.g. the generator is still starting up.
+    ///
+    /// The operation can be retried later.
+    NotReady,
+    /// The generator failed, e.g. a health test detected a stuck or biased entropy source.
+    ///
+    /// The bytes it produced must not be used. The generator may need to be reset.
+    HardwareFault,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotReady => write!(f, "Not enough entropy is available yet"),
+            Self::HardwareFault => write!(f, "The random number generator failed"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// RNG error type trait.
+///
+/// This just defines the error type, to be used by the other RNG traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Random number generator.
+pub trait Rng: ErrorType {
+    /// Fill `buf` with random bytes.
+    ///
+    /// Implementations may block until enough entropy is available, or return an error of kind
+    /// [`ErrorKind::NotReady`]. On error, the content of `buf` is unspecified and must not be used.
+    fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+impl<T: Rng + ?Sized> Rng for &mut T {
+    #[inline]
+    fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        T::fill_bytes(self, buf)
+    }
+}
+
+/// Adapter exposing an [`Rng`] implementation as a `rand_core` 0.9 `TryRngCore`.
+///
+/// Errors are propagated, as `rand_core` expects from fallible generators. For code requiring an
+/// infallible `RngCore`, `TryRngCore::unwrap_err` wraps the adapter in an `UnwrapErr`, which
+/// panics on error, following the `rand_core` conventions.
+///
+/// `TryCryptoRng` is not implemented: whether the bytes are suitable for cryptographic use depends
+/// on the hardware.
+///
+/// Only available with the `rand_core-09` feature.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::rng::{RandCore, Rng};
+/// use rand_core_09::{RngCore, TryRngCore};
+///
+/// fn seed<R: Rng>(rng: R) -> Result<u64, R::Error>
+/// where
+///     R::Error: core::fmt::Display,
+/// {
+///     RandCore::new(rng).try_next_u64()
+/// }
+///
+/// fn shuffle_key<R: Rng>(rng: R) -> u32
+/// where
+///     R::Error: core::fmt::Display,
+/// {
+///     // Panics if the generator fails.
+///     RandCore::new(rng).unwrap_err().next_u32()
+/// }
+/// ```
+#[cfg(feature = "rand_core-09")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandCore<T> {
+    inner: T,
+}
+
+#[cfg(feature = "rand_core-09")]
+impl<T> RandCore<T> {
+    /// Create a new [`RandCore`] adapter around `inner`.
+    #[inline]
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the wrapped generator.
+    #[inline]
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped generator.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Release the wrapped generator.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[cfg(feature = "rand_core-09")]
+impl<T> rand_core_09::TryRngCore for RandCore<T>
+where
+    T: Rng,
+    T::Error: core::fmt::Display,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        let mut bytes = [0; 4];
+        self.inner.fill_bytes(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    #[inline]
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        let mut bytes = [0; 8];
+        self.inner.fill_bytes(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        self.inner.fill_bytes(dst)
+    }
+}
+
+#[cfg(all(test, feature = "rand_core-09"))]
+mod tests {
+    use super::*;
+    use rand_core_09::{RngCore, TryRngCore};
+
+    /// Generator returning 1, 2, 3... until `remaining` bytes were produced, then failing.
+    struct Counting {
+        next: u8,
+        remaining: usize,
+    }
+
+    impl ErrorType for Counting {
+        type Error = ErrorKind;
+    }
+
+    impl Rng for Counting {
+        fn fill_bytes(&mut self, buf: &mut [u8]) -> Result<(), ErrorKind> {
+            self.remaining = self
+                .remaining
+                .checked_sub(buf.len())
+                .ok_or(ErrorKind::NotReady)?;
+            for byte in buf {
+                self.next += 1;
+                *byte = self.next;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rand_core_adapter() {
+        let mut rng = RandCore::new(Counting {
+            next: 0,
+            remaining: 14,
+        });
+        assert_eq!(rng.try_next_u32(), Ok(0x0403_0201));
+        assert_eq!(rng.try_next_u64(), Ok(0x0C0B_0A09_0807_0605));
+        let mut buf = [0; 2];
+        rng.try_fill_bytes(&mut buf).unwrap();
+        assert_eq!(buf, [13, 14]);
+        assert_eq!(rng.try_next_u32(), Err(ErrorKind::NotReady));
+    }
+
+    #[test]
+    #[should_panic]
+    fn unwrap_err_panics_on_error() {
+        let rng = RandCore::new(Counting {
+            next: 0,
+            remaining: 0,
+        });
+        rng.unwrap_err().next_u32();
+    }
+}
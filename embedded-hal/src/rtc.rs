@@ -0,0 +1,239 @@
+//! Real-time clock (RTC) traits.
+//!
+//! The [`RealTimeClock`] trait reads and sets the calendar date and time of an RTC,
+//! using the common [`DateTime`] type so drivers for different RTCs are interchangeable.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::rtc::RealTimeClock;
+//!
+//! /// Returns true if the current time is between 8:00 and 20:00.
+//! fn is_daytime<R: RealTimeClock>(rtc: &mut R) -> Result<bool, R::Error> {
+//!     let now = rtc.get_datetime()?;
+//!     Ok((8..20).contains(&now.hour))
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// RTC error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic RTC error kind.
+    ///
+    /// By using this method, RTC errors freely defined by HAL implementations
+    /// can be converted to a set of generic RTC errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// RTC error kind.
+///
+/// This represents a common set of RTC operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common RTC errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The date and time is invalid, or out of the range supported by the RTC.
+    InvalidDateTime,
+    /// The oscillator of the RTC was stopped, for instance after a power loss. The date and time
+    /// is not reliable until it is set again.
+    ClockStopped,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidDateTime => write!(f, "The date and time is invalid or out of range"),
+            Self::ClockStopped => write!(
+                f,
+                "The clock was stopped, the date and time is not reliable"
+            ),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// RTC error type trait.
+///
+/// This just defines the error type, to be used by the other RTC traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Calendar date and time, in the proleptic Gregorian calendar.
+///
+/// The time zone is left to the application: RTCs are usually set to UTC.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct DateTime {
+    /// Year, for instance `2024`.
+    pub year: u16,
+    /// Month, from 1 (January) to 12 (December).
+    pub month: u8,
+    /// Day of the month, from 1 to 31.
+    pub day: u8,
+    /// Hour, from 0 to 23.
+    pub hour: u8,
+    /// Minute, from 0 to 59.
+    pub minute: u8,
+    /// Second, from 0 to 59.
+    pub second: u8,
+    /// Microsecond, from 0 to 999 999.
+    ///
+    /// RTCs without sub-second resolution always return 0.
+    pub microsecond: u32,
+}
+
+impl DateTime {
+    /// Returns true if all the fields are in range, taking the number of days of the month into account.
+    pub fn is_valid(&self) -> bool {
+        let days_in_month = match self.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if is_leap_year(self.year) => 29,
+            2 => 28,
+            _ => return false,
+        };
+        (1..=days_in_month).contains(&self.day)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+            && self.microsecond < 1_000_000
+    }
+
+    /// Returns the number of seconds elapsed since the Unix epoch (1970-01-01 00:00:00),
+    /// ignoring leap seconds and the microseconds.
+    ///
+    /// The date and time must be valid, see [`is_valid`](DateTime::is_valid).
+    pub fn unix_timestamp(&self) -> i64 {
+        // Days from civil, see http://howardhinnant.github.io/date_algorithms.html
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+
+        days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Real-time clock.
+pub trait RealTimeClock: ErrorType {
+    /// Get the current date and time.
+    fn get_datetime(&mut self) -> Result<DateTime, Self::Error>;
+
+    /// Set the current date and time.
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidDateTime`] if `datetime` is invalid, or out of the
+    /// range supported by the RTC. The microseconds are ignored by RTCs without sub-second resolution.
+    fn set_datetime(&mut self, datetime: &DateTime) -> Result<(), Self::Error>;
+
+    /// Get the number of seconds elapsed since the Unix epoch, see [`DateTime::unix_timestamp`].
+    #[inline]
+    fn unix_timestamp(&mut self) -> Result<i64, Self::Error> {
+        Ok(self.get_datetime()?.unix_timestamp())
+    }
+}
+
+impl<T: RealTimeClock + ?Sized> RealTimeClock for &mut T {
+    #[inline]
+    fn get_datetime(&mut self) -> Result<DateTime, Self::Error> {
+        T::get_datetime(self)
+    }
+
+    #[inline]
+    fn set_datetime(&mut self, datetime: &DateTime) -> Result<(), Self::Error> {
+        T::set_datetime(self, datetime)
+    }
+
+    #[inline]
+    fn unix_timestamp(&mut self) -> Result<i64, Self::Error> {
+        T::unix_timestamp(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTime {
+        DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            microsecond: 0,
+        }
+    }
+
+    #[test]
+    fn unix_timestamp() {
+        assert_eq!(datetime(1970, 1, 1, 0, 0, 0).unix_timestamp(), 0);
+        assert_eq!(datetime(1969, 12, 31, 23, 59, 59).unix_timestamp(), -1);
+        assert_eq!(datetime(2000, 3, 1, 0, 0, 0).unix_timestamp(), 951_868_800);
+        assert_eq!(
+            datetime(2024, 2, 29, 12, 30, 15).unix_timestamp(),
+            1_709_209_815
+        );
+        assert_eq!(datetime(2038, 1, 19, 3, 14, 8).unix_timestamp(), 1 << 31);
+    }
+
+    #[test]
+    fn is_valid() {
+        assert!(datetime(2024, 2, 29, 23, 59, 59).is_valid());
+        assert!(datetime(2000, 2, 29, 0, 0, 0).is_valid());
+        assert!(!datetime(2023, 2, 29, 0, 0, 0).is_valid());
+        assert!(!datetime(2100, 2, 29, 0, 0, 0).is_valid());
+        assert!(!datetime(2024, 4, 31, 0, 0, 0).is_valid());
+        assert!(!datetime(2024, 13, 1, 0, 0, 0).is_valid());
+        assert!(!datetime(2024, 1, 0, 0, 0, 0).is_valid());
+        assert!(!datetime(2024, 1, 1, 24, 0, 0).is_valid());
+        assert!(!DateTime {
+            microsecond: 1_000_000,
+            ..datetime(2024, 1, 1, 0, 0, 0)
+        }
+        .is_valid());
+    }
+}
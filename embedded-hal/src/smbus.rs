@@ -0,0 +1,647 @@
+//! Blocking SMBus API.
+//!
+//! The System Management Bus (SMBus) and PMBus define a standard set of commands layered on top of I2C.
+//! The [`Smbus`] extension trait provides these commands for every [`I2c`] implementation, so SMBus and PMBus
+//! drivers can be written against any HAL.
+//!
+//! SMBus only uses 7-bit addresses. Words are transferred least significant byte first, and blocks
+//! are prefixed by their byte count, with a maximum of [`MAX_BLOCK_LEN`] bytes.
+//!
+//! # Packet Error Checking
+//!
+//! Each command has a `_pec` variant, which appends a Packet Error Code to writes, and checks the one
+//! sent by the device after reads. The PEC is a CRC-8 (polynomial `0x07`, see [`pec`]) computed over
+//! every byte of the transfer, including the address bytes. A mismatch is reported as [`SmbusError::Pec`].
+//!
+//! # Alerts
+//!
+//! Devices signal the host by pulling the shared, active-low SMBALERT# line. The host then reads the
+//! [`ALERT_RESPONSE_ADDRESS`] with [`read_alert_response`](Smbus::read_alert_response): the alerting
+//! device responds with its own address, and releases the line. If several devices alert at the same
+//! time, the one with the lowest address wins the arbitration: read the alert response again until
+//! the line is released.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::smbus::Smbus;
+//! # use embedded_hal::i2c::{self, ErrorType, I2c, Operation};
+//! # struct I2c0;
+//! # impl ErrorType for I2c0 { type Error = i2c::ErrorKind; }
+//! # impl I2c for I2c0 {
+//! #     fn transaction(&mut self, _: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+//! #         if let Some(Operation::Read(buf)) = operations.last_mut() {
+//! #             buf.copy_from_slice(&[0x2C, 0x01][..buf.len()]);
+//! #         }
+//! #         Ok(())
+//! #     }
+//! # }
+//! # let mut i2c = I2c0;
+//!
+//! const ADDRESS: u8 = 0x40;
+//! const READ_VOUT: u8 = 0x8B;
+//!
+//! let vout = i2c.read_word_data(ADDRESS, READ_VOUT)?;
+//! assert_eq!(vout, 0x012C);
+//! # Ok::<(), i2c::ErrorKind>(())
+//! ```
+
+use crate::i2c::{Error, ErrorKind, I2c, Operation, SevenBitAddress};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Maximum number of data bytes in an SMBus block transfer.
+pub const MAX_BLOCK_LEN: usize = 32;
+
+/// Alert Response Address, read by the host to find which device pulled SMBALERT#.
+pub const ALERT_RESPONSE_ADDRESS: SevenBitAddress = 0x0C;
+
+/// SMBus error.
+///
+/// Returned by SMBus commands which can fail for reasons other than an I2C error.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum SmbusError<E> {
+    /// The underlying I2C transfer failed.
+    I2c(E),
+    /// A block is longer than [`MAX_BLOCK_LEN`], or than the provided buffer.
+    ///
+    /// For block reads, this contains the byte count sent by the device.
+    InvalidBlockLength(usize),
+    /// The Packet Error Code sent by the device does not match the received data.
+    Pec {
+        /// PEC computed over the transfer.
+        expected: u8,
+        /// PEC sent by the device.
+        received: u8,
+    },
+}
+
+impl<E> From<E> for SmbusError<E> {
+    #[inline]
+    fn from(error: E) -> Self {
+        Self::I2c(error)
+    }
+}
+
+impl<E: Error> Error for SmbusError<E> {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::I2c(e) => e.kind(),
+            Self::InvalidBlockLength(_) | Self::Pec { .. } => ErrorKind::Other,
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for SmbusError<E> {}
+
+impl<E: core::fmt::Display> core::fmt::Display for SmbusError<E> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::I2c(e) => write!(f, "I2C error: {e}"),
+            Self::InvalidBlockLength(len) => write!(f, "Invalid block length: {len}"),
+            Self::Pec { expected, received } => write!(
+                f,
+                "PEC mismatch: expected {expected:#04x}, received {received:#04x}"
+            ),
+        }
+    }
+}
+
+/// Compute the SMBus Packet Error Code (CRC-8, polynomial `0x07`) of `bytes`, continuing from `crc`.
+///
+/// Start with a `crc` of `0`. The PEC of a transfer covers the address bytes: `address << 1` for
+/// writes, and `(address << 1) | 1` for reads.
+pub fn pec(crc: u8, bytes: &[u8]) -> u8 {
+    bytes.iter().fold(crc, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// SMBus commands.
+///
+/// This trait is implemented for every [`I2c`] implementation, it does not need to be implemented manually.
+pub trait Smbus: I2c<SevenBitAddress> {
+    /// Send Byte: write a single byte, without a command code.
+    #[inline]
+    fn send_byte(&mut self, address: SevenBitAddress, byte: u8) -> Result<(), Self::Error> {
+        self.write(address, &[byte])
+    }
+
+    /// Receive Byte: read a single byte, without a command code.
+    #[inline]
+    fn receive_byte(&mut self, address: SevenBitAddress) -> Result<u8, Self::Error> {
+        let mut byte = [0];
+        self.read(address, &mut byte)?;
+        Ok(byte[0])
+    }
+
+    /// Write Byte: write a byte to the `command` register.
+    #[inline]
+    fn write_byte_data(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        value: u8,
+    ) -> Result<(), Self::Error> {
+        self.write(address, &[command, value])
+    }
+
+    /// Read Byte: read a byte from the `command` register.
+    #[inline]
+    fn read_byte_data(&mut self, address: SevenBitAddress, command: u8) -> Result<u8, Self::Error> {
+        let mut byte = [0];
+        self.write_read(address, &[command], &mut byte)?;
+        Ok(byte[0])
+    }
+
+    /// Write Word: write a 16-bit word to the `command` register.
+    #[inline]
+    fn write_word_data(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        value: u16,
+    ) -> Result<(), Self::Error> {
+        let [low, high] = value.to_le_bytes();
+        self.write(address, &[command, low, high])
+    }
+
+    /// Read Word: read a 16-bit word from the `command` register.
+    #[inline]
+    fn read_word_data(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+    ) -> Result<u16, Self::Error> {
+        let mut word = [0; 2];
+        self.write_read(address, &[command], &mut word)?;
+        Ok(u16::from_le_bytes(word))
+    }
+
+    /// Process Call: write a 16-bit word to the `command` register, and read a 16-bit word back
+    /// in the same transaction.
+    #[inline]
+    fn process_call(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        value: u16,
+    ) -> Result<u16, Self::Error> {
+        let [low, high] = value.to_le_bytes();
+        let mut word = [0; 2];
+        self.write_read(address, &[command, low, high], &mut word)?;
+        Ok(u16::from_le_bytes(word))
+    }
+
+    /// Block Write: write the byte count followed by `data` to the `command` register.
+    ///
+    /// Returns [`SmbusError::InvalidBlockLength`] if `data` is longer than [`MAX_BLOCK_LEN`].
+    #[inline]
+    fn block_write(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), SmbusError<Self::Error>> {
+        if data.len() > MAX_BLOCK_LEN {
+            return Err(SmbusError::InvalidBlockLength(data.len()));
+        }
+        self.transaction(
+            address,
+            &mut [
+                Operation::Write(&[command, data.len() as u8]),
+                Operation::Write(data),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Block Read: read a block from the `command` register into `buffer`, returning its length.
+    ///
+    /// The [`I2c`] trait cannot size a read from data received in the same transaction, so exactly
+    /// `len + 1` bytes are read from the device, where `len` is `buffer.len()` capped at
+    /// [`MAX_BLOCK_LEN`]: the byte count, then `len` data bytes, whatever count the device sends. If
+    /// the block is shorter, the bytes clocked past its end are discarded, but the device sees them
+    /// acknowledged instead of the last byte being NACKed, so size `buffer` to the expected block
+    /// length.
+    ///
+    /// Returns [`SmbusError::InvalidBlockLength`] if the byte count sent by the device is larger
+    /// than `len`.
+    #[inline]
+    fn block_read(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        buffer: &mut [u8],
+    ) -> Result<usize, SmbusError<Self::Error>> {
+        let mut block = [0; MAX_BLOCK_LEN + 1];
+        let len = buffer.len().min(MAX_BLOCK_LEN);
+        self.write_read(address, &[command], &mut block[..=len])?;
+
+        let count = usize::from(block[0]);
+        if count > len {
+            return Err(SmbusError::InvalidBlockLength(count));
+        }
+        buffer[..count].copy_from_slice(&block[1..=count]);
+        Ok(count)
+    }
+
+    /// Read the Alert Response Address, returning the address of the device which raised an alert.
+    ///
+    /// The device sends its 7-bit address in the upper bits of the byte: the lowest bit is ignored.
+    /// An error of kind [`ErrorKind::NoAcknowledge`] is returned if no device is alerting.
+    #[inline]
+    fn read_alert_response(&mut self) -> Result<SevenBitAddress, Self::Error> {
+        Ok(self.receive_byte(ALERT_RESPONSE_ADDRESS)? >> 1)
+    }
+
+    /// Send Byte, with PEC.
+    #[inline]
+    fn send_byte_pec(
+        &mut self,
+        address: SevenBitAddress,
+        byte: u8,
+    ) -> Result<(), SmbusError<Self::Error>> {
+        write_pec(self, address, &[byte], &[])
+    }
+
+    /// Receive Byte, with PEC.
+    #[inline]
+    fn receive_byte_pec(
+        &mut self,
+        address: SevenBitAddress,
+    ) -> Result<u8, SmbusError<Self::Error>> {
+        let mut buf = [0; 2];
+        self.read(address, &mut buf)?;
+        check_pec(address, &[], &buf[..1], buf[1])?;
+        Ok(buf[0])
+    }
+
+    /// Read the Alert Response Address, with PEC.
+    #[inline]
+    fn read_alert_response_pec(&mut self) -> Result<SevenBitAddress, SmbusError<Self::Error>> {
+        Ok(self.receive_byte_pec(ALERT_RESPONSE_ADDRESS)? >> 1)
+    }
+
+    /// Write Byte, with PEC.
+    #[inline]
+    fn write_byte_data_pec(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        value: u8,
+    ) -> Result<(), SmbusError<Self::Error>> {
+        write_pec(self, address, &[command, value], &[])
+    }
+
+    /// Read Byte, with PEC.
+    #[inline]
+    fn read_byte_data_pec(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+    ) -> Result<u8, SmbusError<Self::Error>> {
+        let mut byte = [0];
+        write_read_pec(self, address, &[command], &mut byte)?;
+        Ok(byte[0])
+    }
+
+    /// Write Word, with PEC.
+    #[inline]
+    fn write_word_data_pec(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        value: u16,
+    ) -> Result<(), SmbusError<Self::Error>> {
+        let [low, high] = value.to_le_bytes();
+        write_pec(self, address, &[command, low, high], &[])
+    }
+
+    /// Read Word, with PEC.
+    #[inline]
+    fn read_word_data_pec(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+    ) -> Result<u16, SmbusError<Self::Error>> {
+        let mut word = [0; 2];
+        write_read_pec(self, address, &[command], &mut word)?;
+        Ok(u16::from_le_bytes(word))
+    }
+
+    /// Process Call, with PEC.
+    #[inline]
+    fn process_call_pec(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        value: u16,
+    ) -> Result<u16, SmbusError<Self::Error>> {
+        let [low, high] = value.to_le_bytes();
+        let mut word = [0; 2];
+        write_read_pec(self, address, &[command, low, high], &mut word)?;
+        Ok(u16::from_le_bytes(word))
+    }
+
+    /// Block Write, with PEC.
+    ///
+    /// Returns [`SmbusError::InvalidBlockLength`] if `data` is longer than [`MAX_BLOCK_LEN`].
+    #[inline]
+    fn block_write_pec(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(), SmbusError<Self::Error>> {
+        if data.len() > MAX_BLOCK_LEN {
+            return Err(SmbusError::InvalidBlockLength(data.len()));
+        }
+        write_pec(self, address, &[command, data.len() as u8], data)
+    }
+
+    /// Block Read, with PEC.
+    ///
+    /// As for [`block_read`](Smbus::block_read), exactly `len + 2` bytes are read from the device,
+    /// where `len` is `buffer.len()` capped at [`MAX_BLOCK_LEN`]: the byte count, `len` data bytes and
+    /// the PEC. The PEC is taken right after the `count` bytes of the block, so a shorter block is
+    /// still checked, but `buffer` should be sized to the expected block length.
+    #[inline]
+    fn block_read_pec(
+        &mut self,
+        address: SevenBitAddress,
+        command: u8,
+        buffer: &mut [u8],
+    ) -> Result<usize, SmbusError<Self::Error>> {
+        // Byte count, data and PEC.
+        let mut block = [0; MAX_BLOCK_LEN + 2];
+        let len = buffer.len().min(MAX_BLOCK_LEN);
+        self.write_read(address, &[command], &mut block[..len + 2])?;
+
+        let count = usize::from(block[0]);
+        if count > len {
+            return Err(SmbusError::InvalidBlockLength(count));
+        }
+        check_pec(address, &[command], &block[..=count], block[count + 1])?;
+        buffer[..count].copy_from_slice(&block[1..=count]);
+        Ok(count)
+    }
+}
+
+impl<T: I2c<SevenBitAddress> + ?Sized> Smbus for T {}
+
+/// Write `header` followed by `data`, then their PEC.
+fn write_pec<T: I2c<SevenBitAddress> + ?Sized>(
+    i2c: &mut T,
+    address: SevenBitAddress,
+    header: &[u8],
+    data: &[u8],
+) -> Result<(), SmbusError<T::Error>> {
+    let crc = pec(pec(pec(0, &[address << 1]), header), data);
+    i2c.transaction(
+        address,
+        &mut [
+            Operation::Write(header),
+            Operation::Write(data),
+            Operation::Write(&[crc]),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Write `bytes`, then read `buffer` and the PEC in the same transaction.
+fn write_read_pec<T: I2c<SevenBitAddress> + ?Sized>(
+    i2c: &mut T,
+    address: SevenBitAddress,
+    bytes: &[u8],
+    buffer: &mut [u8],
+) -> Result<(), SmbusError<T::Error>> {
+    let mut read = [0; 3];
+    let len = buffer.len();
+    i2c.write_read(address, bytes, &mut read[..=len])?;
+    check_pec(address, bytes, &read[..len], read[len])?;
+    buffer.copy_from_slice(&read[..len]);
+    Ok(())
+}
+
+/// Check the PEC `received` after writing `written` (if not empty) then reading `read`.
+fn check_pec<E>(
+    address: SevenBitAddress,
+    written: &[u8],
+    read: &[u8],
+    received: u8,
+) -> Result<(), SmbusError<E>> {
+    let mut crc = 0;
+    if !written.is_empty() {
+        crc = pec(pec(crc, &[address << 1]), written);
+    }
+    let expected = pec(pec(crc, &[(address << 1) | 1]), read);
+    if expected == received {
+        Ok(())
+    } else {
+        Err(SmbusError::Pec { expected, received })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bus recording the bytes written, and answering reads with a scripted response.
+    struct ScriptedBus {
+        written: [u8; 40],
+        written_len: usize,
+        response: [u8; 40],
+        response_len: usize,
+        read_len: usize,
+    }
+
+    impl ScriptedBus {
+        fn new(response: &[u8]) -> Self {
+            let mut bus = Self {
+                written: [0; 40],
+                written_len: 0,
+                response: [0; 40],
+                response_len: response.len(),
+                read_len: 0,
+            };
+            bus.response[..response.len()].copy_from_slice(response);
+            bus
+        }
+
+        fn written(&self) -> &[u8] {
+            &self.written[..self.written_len]
+        }
+    }
+
+    impl crate::i2c::ErrorType for ScriptedBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for ScriptedBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            assert_eq!(address, 0x50);
+            for op in operations {
+                match op {
+                    Operation::Write(bytes) => {
+                        self.written[self.written_len..][..bytes.len()].copy_from_slice(bytes);
+                        self.written_len += bytes.len();
+                    }
+                    Operation::Read(buf) => {
+                        self.read_len = buf.len();
+                        let n = buf.len().min(self.response_len);
+                        buf[..n].copy_from_slice(&self.response[..n]);
+                        buf[n..].fill(0xFF);
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Bus where the device with address `alerting` responds to the Alert Response Address.
+    struct AlertBus {
+        alerting: Option<u8>,
+    }
+
+    impl crate::i2c::ErrorType for AlertBus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for AlertBus {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            assert_eq!(address, ALERT_RESPONSE_ADDRESS);
+            let alerting = self.alerting.take().ok_or(ErrorKind::NoAcknowledge(
+                crate::i2c::NoAcknowledgeSource::Address,
+            ))?;
+            if let [Operation::Read(buf)] = operations {
+                // The lowest bit is left set, it must be ignored.
+                let response = [alerting << 1 | 1];
+                let crc = pec(0, &[ALERT_RESPONSE_ADDRESS << 1 | 1, response[0]]);
+                buf.copy_from_slice(&[response[0], crc][..buf.len()]);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn alert_response() {
+        let mut bus = AlertBus {
+            alerting: Some(0x5C),
+        };
+        assert_eq!(bus.read_alert_response(), Ok(0x5C));
+        assert_eq!(
+            bus.read_alert_response().unwrap_err().kind(),
+            ErrorKind::NoAcknowledge(crate::i2c::NoAcknowledgeSource::Address)
+        );
+
+        bus.alerting = Some(0x40);
+        assert_eq!(bus.read_alert_response_pec(), Ok(0x40));
+    }
+
+    #[test]
+    fn pec_is_crc8() {
+        assert_eq!(pec(0, b"123456789"), 0xF4);
+        assert_eq!(pec(pec(0, b"1234"), b"56789"), 0xF4);
+    }
+
+    #[test]
+    fn write_pec_covers_address() {
+        let mut bus = ScriptedBus::new(&[]);
+        bus.write_word_data_pec(0x50, 0x10, 0x1234).unwrap();
+        assert_eq!(bus.written(), [0x10, 0x34, 0x12, 0x8E]);
+    }
+
+    #[test]
+    fn read_pec() {
+        let mut bus = ScriptedBus::new(&[0x34, 0x12, 0x00]);
+        let expected = pec(0, &[0xA0, 0x10, 0xA1, 0x34, 0x12]);
+        assert_eq!(
+            bus.read_word_data_pec(0x50, 0x10),
+            Err(SmbusError::Pec {
+                expected,
+                received: 0x00
+            })
+        );
+
+        let mut bus = ScriptedBus::new(&[0x34, 0x12, expected]);
+        assert_eq!(bus.read_word_data_pec(0x50, 0x10), Ok(0x1234));
+    }
+
+    #[test]
+    fn receive_byte_pec_covers_read_address_only() {
+        let crc = pec(0, &[0xA1, 0x42]);
+        let mut bus = ScriptedBus::new(&[0x42, crc]);
+        assert_eq!(bus.receive_byte_pec(0x50), Ok(0x42));
+    }
+
+    #[test]
+    fn block_read_pec() {
+        let mut bus = ScriptedBus::new(&[3, 1, 2, 3, 0xF3]);
+        let mut buf = [0; 8];
+        assert_eq!(bus.block_read_pec(0x50, 0x20, &mut buf), Ok(3));
+        assert_eq!(buf[..3], [1, 2, 3]);
+        assert_eq!(bus.read_len, 10);
+    }
+
+    #[test]
+    fn block_read_is_limited_to_32_bytes() {
+        let mut response = [0; 33];
+        response[0] = 32;
+        let mut bus = ScriptedBus::new(&response);
+        let mut buf = [0; 40];
+        assert_eq!(bus.block_read(0x50, 0x20, &mut buf), Ok(32));
+        assert_eq!(bus.read_len, 33);
+    }
+
+    #[test]
+    fn block_read_bogus_count() {
+        let mut bus = ScriptedBus::new(&[0xFF]);
+        let mut buf = [0; 32];
+        assert_eq!(
+            bus.block_read_pec(0x50, 0x20, &mut buf),
+            Err(SmbusError::InvalidBlockLength(0xFF))
+        );
+
+        let mut bus = ScriptedBus::new(&[5, 1, 2, 3, 4, 5]);
+        let mut buf = [0; 4];
+        assert_eq!(
+            bus.block_read(0x50, 0x20, &mut buf),
+            Err(SmbusError::InvalidBlockLength(5))
+        );
+        assert_eq!(bus.read_len, 5);
+    }
+
+    #[test]
+    fn block_write_pec() {
+        let mut bus = ScriptedBus::new(&[]);
+        bus.block_write_pec(0x50, 0x30, &[9, 8]).unwrap();
+        let crc = pec(0, &[0xA0, 0x30, 2, 9, 8]);
+        assert_eq!(bus.written(), [0x30, 2, 9, 8, crc]);
+        assert_eq!(
+            bus.block_write_pec(0x50, 0x30, &[0; 33]),
+            Err(SmbusError::InvalidBlockLength(33))
+        );
+    }
+}
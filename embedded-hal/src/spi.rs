@@ -131,6 +131,48 @@
 //! HALs **must not** add infrastructure for sharing at the [`SpiBus`] level. User code owning a [`SpiBus`] must have the guarantee
 //! of exclusive access.
 //!
+//! # Word sizes
+//!
+//! [`SpiBus`] and [`SpiDevice`] are generic over the word type, which defaults to `u8`. HALs whose
+//! peripheral supports wider frames should additionally implement `SpiBus<u16>` (and `SpiBus<u32>`
+//! if applicable), so drivers such as 16-bit color displays can send one word per pixel instead of
+//! packing it into two bytes. The [`SpiDevice`] implementations in `embedded-hal-bus` implement
+//! `SpiDevice<Word>` for every `Word` the underlying bus supports.
+//!
+//! ```
+//! use embedded_hal::spi::SpiDevice;
+//!
+//! /// Fill a window of a 16-bit color display with a single color.
+//! fn fill<SPI: SpiDevice<u16>>(spi: &mut SPI, color: u16, pixels: usize) -> Result<(), SPI::Error> {
+//!     let chunk = [color; 32];
+//!     let mut remaining = pixels;
+//!     while remaining > 0 {
+//!         let n = remaining.min(chunk.len());
+//!         spi.write(&chunk[..n])?;
+//!         remaining -= n;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Quad-SPI
+//!
+//! Memory devices such as NOR flash chips are often connected through a Quad-SPI (QSPI) peripheral, which
+//! transfers the address and data phases of a command on 2 or 4 data lines. These are driven with the
+//! [`QspiDevice`] trait instead of [`SpiDevice`]. Peripherals able to map the memory in the CPU address space,
+//! for execute-in-place (XIP), also implement [`MemoryMappedRead`].
+//!
+//! # Peripheral mode
+//!
+//! The traits above are for SPI controllers (masters). Devices acting as a peripheral (slave) on the bus,
+//! for example for inter-processor links, use the [`SpiPeripheral`] trait instead.
+//!
+//! # DMA
+//!
+//! Buses transferring the words with DMA may require the buffers to be aligned, for instance on the
+//! cache lines on Cortex-M7. Such buses implement [`SpiBusDma`], which exposes the alignment they
+//! require, so that drivers and bus sharing wrappers can check their buffers, or align them at compile time.
+//!
 //! # Flushing
 //!
 //! To improve performance, [`SpiBus`] implementations are allowed to return before the operation is finished, i.e. when the bus is still not
@@ -432,6 +474,26 @@ impl<Word: Copy + 'static, T: SpiDevice<Word> + ?Sized> SpiDevice<Word> for &mut
     }
 }
 
+/// [`SpiDevice`] applying its own configuration of the bus, such as its clock frequency and
+/// [`Mode`], at the start of each transaction.
+///
+/// Devices with different requirements can then share a bus: each transaction reconfigures the
+/// bus for the device before asserting CS. This trait lets drivers and applications inspect the
+/// configuration used by a device.
+pub trait SpiDeviceWithConfig<Config, Word: Copy + 'static = u8>: SpiDevice<Word> {
+    /// Returns the bus configuration applied before each transaction.
+    fn configuration(&self) -> &Config;
+}
+
+impl<Config, Word: Copy + 'static, T: SpiDeviceWithConfig<Config, Word> + ?Sized>
+    SpiDeviceWithConfig<Config, Word> for &mut T
+{
+    #[inline]
+    fn configuration(&self) -> &Config {
+        T::configuration(self)
+    }
+}
+
 /// SPI bus.
 ///
 /// `SpiBus` represents **exclusive ownership** over the whole SPI bus, with SCK, MOSI and MISO pins.
@@ -506,3 +568,732 @@ impl<T: SpiBus<Word> + ?Sized, Word: Copy + 'static> SpiBus<Word> for &mut T {
         T::flush(self)
     }
 }
+
+/// SPI bus transferring the words with DMA, which requires aligned buffers.
+///
+/// Implementations must still accept any buffer, as required by [`SpiBus`], e.g. by copying unaligned
+/// buffers to an internal bounce buffer or by transferring them without DMA, but aligned buffers
+/// avoid this cost.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::SpiBusDma;
+///
+/// /// Buffer aligned on 32 bytes, the cache line size of the Cortex-M7.
+/// #[repr(C, align(32))]
+/// struct Aligned([u8; 64]);
+///
+/// fn check_buffer<B: SpiBusDma>(buffer: &Aligned) {
+///     const { assert!(B::MIN_ALIGN <= 32) };
+///     debug_assert!(B::is_buffer_compatible(buffer.0.as_ptr(), buffer.0.len()));
+/// }
+/// ```
+pub trait SpiBusDma<Word: Copy + 'static = u8>: SpiBus<Word> {
+    /// Minimum alignment of the buffers, in bytes. This must be a power of two.
+    const MIN_ALIGN: usize;
+
+    /// Returns `true` if the `len` bytes buffer at `buf` can be transferred with DMA.
+    ///
+    /// By default, this checks that `buf` is aligned on [`MIN_ALIGN`](SpiBusDma::MIN_ALIGN) bytes.
+    /// Implementations with other requirements, such as buffers spanning whole cache lines or placed
+    /// in a memory region reachable by the DMA controller, should override it.
+    #[inline]
+    fn is_buffer_compatible(buf: *const u8, len: usize) -> bool {
+        let _ = len;
+        (buf as usize) % Self::MIN_ALIGN == 0
+    }
+}
+
+impl<T: SpiBusDma<Word> + ?Sized, Word: Copy + 'static> SpiBusDma<Word> for &mut T {
+    const MIN_ALIGN: usize = T::MIN_ALIGN;
+
+    #[inline]
+    fn is_buffer_compatible(buf: *const u8, len: usize) -> bool {
+        T::is_buffer_compatible(buf, len)
+    }
+}
+
+/// Size of the chunks in which [`SpiBusExt::write_iter`] and [`SpiBusExt::write_from_fn`] send
+/// the words.
+pub const WRITE_ITER_CHUNK_SIZE: usize = 32;
+
+/// Extension methods for [`SpiBus`], streaming words that are not in a contiguous buffer.
+///
+/// This trait is implemented for every [`SpiBus<u8>`](SpiBus). The words are sent in chunks, with
+/// one bus operation per chunk: since [`SpiBus`] does not handle CS, the chunks are sent back to
+/// back, exactly like a single operation on the concatenated words. Each method calls
+/// [`flush`](SpiBus::flush) once, after the last chunk.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::{SpiBus, SpiBusExt};
+///
+/// /// Fill a 240x240 RGB565 display with a color, without a 115 KiB framebuffer.
+/// fn fill<B: SpiBus>(bus: &mut B, color: u16) -> Result<(), B::Error> {
+///     let [high, low] = color.to_be_bytes();
+///     bus.write_from_fn(240 * 240 * 2, |i| if i % 2 == 0 { high } else { low })
+/// }
+/// ```
+pub trait SpiBusExt: SpiBus<u8> {
+    /// Writes the `words` from an iterator, ignoring all the incoming words.
+    ///
+    /// The words are copied to an internal buffer of [`WRITE_ITER_CHUNK_SIZE`] words, and each
+    /// chunk is sent with [`SpiBus::write`].
+    fn write_iter<W>(&mut self, words: W) -> Result<(), Self::Error>
+    where
+        W: IntoIterator<Item = u8>,
+    {
+        let mut words = words.into_iter();
+        let mut chunk = [0; WRITE_ITER_CHUNK_SIZE];
+        loop {
+            let len = fill_chunk(&mut chunk, &mut words);
+            if len == 0 {
+                break;
+            }
+            self.write(&chunk[..len])?;
+        }
+        self.flush()
+    }
+
+    /// Writes `len` words, the word at index `i` being `word(i)`, ignoring all the incoming words.
+    ///
+    /// This is [`write_iter`](SpiBusExt::write_iter) of `(0..len).map(word)`.
+    #[inline]
+    fn write_from_fn<F>(&mut self, len: usize, word: F) -> Result<(), Self::Error>
+    where
+        F: FnMut(usize) -> u8,
+    {
+        self.write_iter((0..len).map(word))
+    }
+
+    /// Like [`SpiBus::transfer`], split into transfers of at most `chunk_len` words.
+    ///
+    /// This suits implementations limiting the length of a single transfer, for instance to the
+    /// maximum length of a DMA transfer. The transfer runs for `max(read.len(), write.len())`
+    /// words, and the words of each chunk are written and read simultaneously, so each word of
+    /// `read` is received while the word at the same index of `write` is sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is zero.
+    fn transfer_chunked(
+        &mut self,
+        read: &mut [u8],
+        write: &[u8],
+        chunk_len: usize,
+    ) -> Result<(), Self::Error> {
+        assert!(chunk_len > 0, "chunk_len must not be zero");
+        let len = read.len().max(write.len());
+        for start in (0..len).step_by(chunk_len) {
+            let (read, write) = chunk_at(read, write, start, chunk_len);
+            self.transfer(read, write)?;
+        }
+        self.flush()
+    }
+}
+
+impl<T: SpiBus<u8> + ?Sized> SpiBusExt for T {}
+
+/// Fills `chunk` from `words`, returning the number of words copied.
+fn fill_chunk(chunk: &mut [u8], words: &mut impl Iterator<Item = u8>) -> usize {
+    let mut len = 0;
+    for (word, new) in chunk.iter_mut().zip(words) {
+        *word = new;
+        len += 1;
+    }
+    len
+}
+
+/// Returns the parts of `read` and `write` in the chunk of `chunk_len` words at `start`, which may
+/// be shorter or empty.
+fn chunk_at<'a, 'b>(
+    read: &'a mut [u8],
+    write: &'b [u8],
+    start: usize,
+    chunk_len: usize,
+) -> (&'a mut [u8], &'b [u8]) {
+    let range = |len: usize| start.min(len)..start.saturating_add(chunk_len).min(len);
+    let (read_range, write_range) = (range(read.len()), range(write.len()));
+    (&mut read[read_range], &write[write_range])
+}
+
+/// Half-duplex SPI bus.
+///
+/// This is the bus trait for 3-wire SPI, where a single bidirectional data line is used instead of
+/// separate MOSI and MISO lines. Only one direction is active at a time, so there are no
+/// full-duplex transfers. Implementations are responsible for switching the direction of the data line
+/// between reads and writes.
+///
+/// Like [`SpiBus`], this represents exclusive ownership over the whole bus, and does not handle CS.
+pub trait SpiBusHalfDuplex<Word: Copy + 'static = u8>: ErrorType {
+    /// Read `words` from the slave, with the data line as an input.
+    ///
+    /// Implementations are allowed to return before the operation is
+    /// complete. See the [module-level documentation](self) for details.
+    fn read(&mut self, words: &mut [Word]) -> Result<(), Self::Error>;
+
+    /// Write `words` to the slave, with the data line as an output.
+    ///
+    /// Implementations are allowed to return before the operation is
+    /// complete. See the [module-level documentation](self) for details.
+    fn write(&mut self, words: &[Word]) -> Result<(), Self::Error>;
+
+    /// Wait until all operations have completed and the bus is idle.
+    ///
+    /// See the [module-level documentation](self) for important usage information.
+    fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: SpiBusHalfDuplex<Word> + ?Sized, Word: Copy + 'static> SpiBusHalfDuplex<Word> for &mut T {
+    #[inline]
+    fn read(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        T::read(self, words)
+    }
+
+    #[inline]
+    fn write(&mut self, words: &[Word]) -> Result<(), Self::Error> {
+        T::write(self, words)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        T::flush(self)
+    }
+}
+
+/// Number of data lines used by the address and data phases of a [`QspiDevice`] command.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum QspiMode {
+    /// One line (standard SPI).
+    Single,
+    /// Two lines.
+    Dual,
+    /// Four lines.
+    Quad,
+}
+
+/// Quad-SPI device, for memory devices such as NOR flash chips.
+///
+/// A command has up to three phases, all within a single assertion of CS:
+///
+/// 1. The instruction phase: `command` is sent on a single line.
+/// 2. The address phase: the `address_bytes` least significant bytes of `address` are sent, most
+///    significant first, on the lines selected by `mode`. There is no address phase if `address_bytes` is `0`.
+/// 3. The data phase, on the lines selected by `mode`.
+///
+/// Dummy cycles between the address and data phases, required by the fast read commands of most flash
+/// chips, are inserted by the implementation as configured when it was created.
+///
+/// Implementations must return [`ErrorKind::Other`] for an `address_bytes` larger than `4`, or a `mode`
+/// they do not support.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::{QspiDevice, QspiMode};
+///
+/// const WRITE_ENABLE: u8 = 0x06;
+/// const SECTOR_ERASE: u8 = 0x20;
+/// const QUAD_PAGE_PROGRAM: u8 = 0x32;
+/// const FAST_READ_QUAD_OUTPUT: u8 = 0x6B;
+///
+/// fn read<Q: QspiDevice>(flash: &mut Q, address: u32, buf: &mut [u8]) -> Result<(), Q::Error> {
+///     flash.read_qspi(FAST_READ_QUAD_OUTPUT, address, 3, QspiMode::Quad, buf)
+/// }
+///
+/// fn erase_and_program<Q: QspiDevice>(flash: &mut Q, address: u32, data: &[u8]) -> Result<(), Q::Error> {
+///     flash.execute_qspi(WRITE_ENABLE, 0, 0)?;
+///     flash.execute_qspi(SECTOR_ERASE, address, 3)?;
+///     // ... poll the status register until the erase completes ...
+///     flash.execute_qspi(WRITE_ENABLE, 0, 0)?;
+///     flash.write_qspi(QUAD_PAGE_PROGRAM, address, 3, QspiMode::Quad, data)
+/// }
+/// ```
+pub trait QspiDevice: ErrorType {
+    /// Send `command` and its address, then read `buf` from the device.
+    fn read_qspi(
+        &mut self,
+        command: u8,
+        address: u32,
+        address_bytes: u8,
+        mode: QspiMode,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Send `command` and its address, then write `data` to the device.
+    fn write_qspi(
+        &mut self,
+        command: u8,
+        address: u32,
+        address_bytes: u8,
+        mode: QspiMode,
+        data: &[u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Send `command` and its address, without a data phase.
+    ///
+    /// This is used for instructions such as write enable or erase. The address, if any, is sent on a
+    /// single line.
+    fn execute_qspi(
+        &mut self,
+        command: u8,
+        address: u32,
+        address_bytes: u8,
+    ) -> Result<(), Self::Error>;
+}
+
+impl<T: QspiDevice + ?Sized> QspiDevice for &mut T {
+    #[inline]
+    fn read_qspi(
+        &mut self,
+        command: u8,
+        address: u32,
+        address_bytes: u8,
+        mode: QspiMode,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        T::read_qspi(self, command, address, address_bytes, mode, buf)
+    }
+
+    #[inline]
+    fn write_qspi(
+        &mut self,
+        command: u8,
+        address: u32,
+        address_bytes: u8,
+        mode: QspiMode,
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        T::write_qspi(self, command, address, address_bytes, mode, data)
+    }
+
+    #[inline]
+    fn execute_qspi(
+        &mut self,
+        command: u8,
+        address: u32,
+        address_bytes: u8,
+    ) -> Result<(), Self::Error> {
+        T::execute_qspi(self, command, address, address_bytes)
+    }
+}
+
+/// Memory-mapped (execute-in-place, XIP) reads of a memory device, such as a NOR flash chip.
+///
+/// In XIP mode, the SPI or QSPI peripheral translates CPU reads of a region of the address space into
+/// read commands, so that the contents of the memory can be read, or executed, like internal memory.
+/// The peripheral can not run other commands in this mode.
+///
+/// Implementations provide [`enable_xip`](MemoryMappedRead::enable_xip) and
+/// [`disable_xip`](MemoryMappedRead::disable_xip), and users call
+/// [`enter_xip`](MemoryMappedRead::enter_xip): the returned [`MemoryMappedGuard`] borrows the device
+/// mutably, so that no other command can be sent while the memory is mapped, and leaves XIP mode when
+/// dropped.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::spi::MemoryMappedRead;
+///
+/// /// Find the length of the firmware image stored at `offset`, without copying its header.
+/// fn image_len<F: MemoryMappedRead>(flash: &mut F, offset: u32) -> Result<u32, F::Error> {
+///     let mapped = flash.enter_xip()?;
+///     let header = mapped.mapped_range(offset, 8);
+///     let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+///     mapped.exit()?;
+///     Ok(len)
+/// }
+/// ```
+pub trait MemoryMappedRead: ErrorType {
+    /// Enters XIP mode, mapping the memory in the address space.
+    ///
+    /// This is called by [`enter_xip`](MemoryMappedRead::enter_xip), which should be used instead.
+    fn enable_xip(&mut self) -> Result<(), Self::Error>;
+
+    /// Leaves XIP mode, so that the device can run other commands again.
+    ///
+    /// This is called when a [`MemoryMappedGuard`] is dropped or [exited](MemoryMappedGuard::exit).
+    fn disable_xip(&mut self) -> Result<(), Self::Error>;
+
+    /// Returns the whole mapped memory.
+    ///
+    /// Implementations must return an empty slice when the device is not in XIP mode, so that reading the
+    /// slice never accesses an unmapped region.
+    fn as_mapped_slice(&self) -> &[u8];
+
+    /// Returns the `len` bytes of the mapped memory starting at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is outside of the [mapped memory](MemoryMappedRead::as_mapped_slice).
+    #[inline]
+    fn mapped_range(&self, offset: u32, len: u32) -> &[u8] {
+        let start = offset as usize;
+        &self.as_mapped_slice()[start..start + len as usize]
+    }
+
+    /// Enters XIP mode, returning a guard giving access to the mapped memory until it is dropped.
+    #[inline]
+    fn enter_xip(&mut self) -> Result<MemoryMappedGuard<'_, Self>, Self::Error> {
+        self.enable_xip()?;
+        Ok(MemoryMappedGuard { device: self })
+    }
+}
+
+impl<T: MemoryMappedRead + ?Sized> MemoryMappedRead for &mut T {
+    #[inline]
+    fn enable_xip(&mut self) -> Result<(), Self::Error> {
+        T::enable_xip(self)
+    }
+
+    #[inline]
+    fn disable_xip(&mut self) -> Result<(), Self::Error> {
+        T::disable_xip(self)
+    }
+
+    #[inline]
+    fn as_mapped_slice(&self) -> &[u8] {
+        T::as_mapped_slice(self)
+    }
+
+    #[inline]
+    fn mapped_range(&self, offset: u32, len: u32) -> &[u8] {
+        T::mapped_range(self, offset, len)
+    }
+}
+
+/// Exclusive access to the memory of a [`MemoryMappedRead`] device, while it is in XIP mode.
+///
+/// The device leaves XIP mode when the guard is dropped. Errors are ignored in that case: call
+/// [`exit`](MemoryMappedGuard::exit) to handle them.
+pub struct MemoryMappedGuard<'a, T: MemoryMappedRead + ?Sized> {
+    device: &'a mut T,
+}
+
+impl<T: MemoryMappedRead + ?Sized> MemoryMappedGuard<'_, T> {
+    /// Returns the whole mapped memory.
+    #[inline]
+    pub fn as_mapped_slice(&self) -> &[u8] {
+        self.device.as_mapped_slice()
+    }
+
+    /// Returns the `len` bytes of the mapped memory starting at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is outside of the mapped memory.
+    #[inline]
+    pub fn mapped_range(&self, offset: u32, len: u32) -> &[u8] {
+        self.device.mapped_range(offset, len)
+    }
+
+    /// Leaves XIP mode, returning the error of [`disable_xip`](MemoryMappedRead::disable_xip), if any.
+    #[inline]
+    pub fn exit(self) -> Result<(), T::Error> {
+        let mut guard = core::mem::ManuallyDrop::new(self);
+        guard.device.disable_xip()
+    }
+}
+
+impl<T: MemoryMappedRead + ?Sized> Drop for MemoryMappedGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = self.device.disable_xip();
+    }
+}
+
+/// SPI peripheral (slave).
+///
+/// A peripheral does not drive the clock: the controller decides when a transfer starts, by asserting CS,
+/// how many words are clocked, and when the transfer ends, by deasserting CS.
+///
+/// # Example
+///
+/// An echo peripheral, sending back the frame received in the previous transfer:
+///
+/// ```
+/// use embedded_hal::spi::SpiPeripheral;
+///
+/// fn echo<P: SpiPeripheral>(spi: &mut P) -> Result<(), P::Error> {
+///     let mut frame = [0; 32];
+///     let mut len = 0;
+///     loop {
+///         let mut received = [0; 32];
+///         let clocked = spi.transfer(&mut received, &frame[..len])?;
+///         len = clocked.min(received.len());
+///         frame[..len].copy_from_slice(&received[..len]);
+///     }
+/// }
+/// ```
+pub trait SpiPeripheral<Word: Copy + 'static = u8>: ErrorType {
+    /// Wait for the controller to run a transfer, sending `write` and receiving into `read`.
+    ///
+    /// The transfer starts when the controller asserts CS, and completes when it deasserts CS.
+    /// Returns the number of words actually clocked by the controller, which can be smaller or
+    /// larger than the lengths of the buffers:
+    ///
+    /// - If fewer words are clocked, the end of `write` is not sent, and the end of `read` is left unchanged.
+    /// - If more words are clocked than the length of `write`, the extra words sent are implementation-defined
+    ///   (they are typically all zeros or all ones).
+    /// - If more words are clocked than the length of `read`, the extra words received are discarded.
+    ///
+    /// Ending a transfer early is not an error. [`ErrorKind::Overrun`] is returned if the peripheral
+    /// could not keep up with the clock of the controller, losing received words.
+    fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<usize, Self::Error>;
+}
+
+impl<T: SpiPeripheral<Word> + ?Sized, Word: Copy + 'static> SpiPeripheral<Word> for &mut T {
+    #[inline]
+    fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<usize, Self::Error> {
+        T::transfer(self, read, write)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flash chip mapping `memory` in XIP mode, counting the mode changes.
+    struct Flash {
+        memory: [u8; 16],
+        xip: bool,
+        changes: usize,
+    }
+
+    impl ErrorType for Flash {
+        type Error = ErrorKind;
+    }
+
+    impl MemoryMappedRead for Flash {
+        fn enable_xip(&mut self) -> Result<(), ErrorKind> {
+            self.xip = true;
+            self.changes += 1;
+            Ok(())
+        }
+
+        fn disable_xip(&mut self) -> Result<(), ErrorKind> {
+            self.xip = false;
+            self.changes += 1;
+            Ok(())
+        }
+
+        fn as_mapped_slice(&self) -> &[u8] {
+            if self.xip {
+                &self.memory
+            } else {
+                &[]
+            }
+        }
+    }
+
+    #[test]
+    fn memory_mapped_guard_leaves_xip() {
+        let mut flash = Flash {
+            memory: core::array::from_fn(|i| i as u8),
+            xip: false,
+            changes: 0,
+        };
+        {
+            let mapped = flash.enter_xip().unwrap();
+            assert_eq!(mapped.as_mapped_slice().len(), 16);
+            assert_eq!(mapped.mapped_range(4, 3), [4, 5, 6]);
+        }
+        assert!(!flash.xip);
+        assert!(flash.as_mapped_slice().is_empty());
+
+        flash.enter_xip().unwrap().exit().unwrap();
+        assert!(!flash.xip);
+        // `exit` does not leave XIP mode a second time on drop.
+        assert_eq!(flash.changes, 4);
+    }
+
+    const DUMMY: u8 = 0xFF;
+
+    /// Bus recording the calls and the words sent on MOSI, and receiving on MISO the index of each
+    /// word clocked, modulo 256.
+    struct RecordingBus {
+        mosi: [u8; 128],
+        clocked: usize,
+        /// Number of words of each read, write or transfer call.
+        calls: [usize; 8],
+        call_count: usize,
+        flushed_after: Option<usize>,
+    }
+
+    impl RecordingBus {
+        fn new() -> Self {
+            Self {
+                mosi: [0; 128],
+                clocked: 0,
+                calls: [0; 8],
+                call_count: 0,
+                flushed_after: None,
+            }
+        }
+
+        fn mosi(&self) -> &[u8] {
+            &self.mosi[..self.clocked]
+        }
+
+        fn calls(&self) -> &[usize] {
+            &self.calls[..self.call_count]
+        }
+    }
+
+    impl ErrorType for RecordingBus {
+        type Error = ErrorKind;
+    }
+
+    impl SpiBus for RecordingBus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), ErrorKind> {
+            self.transfer(words, &[])
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), ErrorKind> {
+            self.transfer(&mut [], words)
+        }
+
+        fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), ErrorKind> {
+            assert_eq!(self.flushed_after, None, "operation after flush");
+            let len = read.len().max(write.len());
+            for i in 0..len {
+                self.mosi[self.clocked] = write.get(i).copied().unwrap_or(DUMMY);
+                if let Some(word) = read.get_mut(i) {
+                    *word = self.clocked as u8;
+                }
+                self.clocked += 1;
+            }
+            self.calls[self.call_count] = len;
+            self.call_count += 1;
+            Ok(())
+        }
+
+        fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), ErrorKind> {
+            unimplemented!()
+        }
+
+        fn flush(&mut self) -> Result<(), ErrorKind> {
+            assert_eq!(self.flushed_after, None, "flushed twice");
+            self.flushed_after = Some(self.call_count);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_iter_sends_all_chunks() {
+        for len in [0, 1, 31, 32, 33, 64, 70] {
+            let mut bus = RecordingBus::new();
+            bus.write_iter(0..len).unwrap();
+            assert!(bus.mosi().iter().copied().eq(0..len));
+            let chunks = usize::from(len).div_ceil(WRITE_ITER_CHUNK_SIZE);
+            assert_eq!(bus.calls().len(), chunks);
+            assert!(bus.calls()[..chunks.saturating_sub(1)]
+                .iter()
+                .all(|&call| call == WRITE_ITER_CHUNK_SIZE));
+            assert_eq!(bus.flushed_after, Some(chunks));
+        }
+    }
+
+    #[test]
+    fn write_from_fn_sends_every_index() {
+        let mut bus = RecordingBus::new();
+        bus.write_from_fn(70, |i| i as u8 * 3).unwrap();
+        assert!(bus.mosi().iter().copied().eq((0..70).map(|i| i * 3)));
+        assert_eq!(bus.calls(), [32, 32, 6]);
+        assert_eq!(bus.flushed_after, Some(3));
+    }
+
+    #[test]
+    fn transfer_chunked_stays_full_duplex() {
+        let write: [u8; 10] = core::array::from_fn(|i| 100 + i as u8);
+
+        // Same lengths, not a multiple of the chunk length.
+        let mut bus = RecordingBus::new();
+        let mut read = [0; 10];
+        bus.transfer_chunked(&mut read, &write, 4).unwrap();
+        assert_eq!(bus.mosi(), write);
+        assert!(read.iter().copied().eq(0..10));
+        assert_eq!(bus.calls(), [4, 4, 2]);
+        assert_eq!(bus.flushed_after, Some(3));
+
+        // Shorter read: the incoming words after it are discarded.
+        let mut bus = RecordingBus::new();
+        let mut read = [0; 6];
+        bus.transfer_chunked(&mut read, &write, 4).unwrap();
+        assert_eq!(bus.mosi(), write);
+        assert!(read.iter().copied().eq(0..6));
+        assert_eq!(bus.calls(), [4, 4, 2]);
+
+        // Shorter write: dummy words are sent after it.
+        let mut bus = RecordingBus::new();
+        let mut read = [0; 10];
+        bus.transfer_chunked(&mut read, &write[..5], 4).unwrap();
+        assert_eq!(bus.mosi()[..5], write[..5]);
+        assert_eq!(bus.mosi()[5..], [DUMMY; 5]);
+        assert!(read.iter().copied().eq(0..10));
+        assert_eq!(bus.calls(), [4, 4, 2]);
+
+        // Empty transfer: only a flush.
+        let mut bus = RecordingBus::new();
+        bus.transfer_chunked(&mut [], &[], 4).unwrap();
+        assert_eq!(bus.calls(), []);
+        assert_eq!(bus.flushed_after, Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk_len must not be zero")]
+    fn transfer_chunked_rejects_empty_chunks() {
+        RecordingBus::new()
+            .transfer_chunked(&mut [0], &[0], 0)
+            .unwrap();
+    }
+
+    /// Bus requiring 4-byte aligned buffers.
+    struct Dma;
+
+    impl ErrorType for Dma {
+        type Error = ErrorKind;
+    }
+
+    impl SpiBus for Dma {
+        fn read(&mut self, _: &mut [u8]) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+
+        fn write(&mut self, _: &[u8]) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+
+        fn transfer(&mut self, _: &mut [u8], _: &[u8]) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+
+        fn transfer_in_place(&mut self, _: &mut [u8]) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    impl SpiBusDma for Dma {
+        const MIN_ALIGN: usize = 4;
+    }
+
+    #[test]
+    fn dma_buffer_alignment() {
+        #[repr(align(4))]
+        struct Aligned([u8; 8]);
+
+        let buffer = Aligned([0; 8]);
+        let ptr = buffer.0.as_ptr();
+        assert!(Dma::is_buffer_compatible(ptr, 8));
+        assert!(!Dma::is_buffer_compatible(ptr.wrapping_add(1), 7));
+        assert!(<&mut Dma>::is_buffer_compatible(ptr.wrapping_add(4), 4));
+        assert_eq!(<&mut Dma as SpiBusDma>::MIN_ALIGN, 4);
+    }
+}
@@ -0,0 +1,389 @@
+//! Monotonic time.
+//!
+//! [`MonotonicClock`] reads a clock which never goes backwards, returning an [`Instant`]. Subtracting
+//! two instants gives the [`Duration`] elapsed between them, which lets drivers implement deadlines
+//! and timeouts. All the types count nanoseconds in a `u64`, which lasts for more than 584 years.
+//!
+//! Unlike [`Counter`](crate::delay::Counter), which measures the time since it was last started, a
+//! clock can be shared by any number of deadlines at once.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_hal::time::{Duration, MonotonicClock};
+//!
+//! /// Poll `ready` until it returns `true`, for at most 10 ms.
+//! fn wait_ready<C: MonotonicClock>(clock: &C, mut ready: impl FnMut() -> bool) -> bool {
+//!     let deadline = clock.now() + Duration::from_millis(10);
+//!     while !ready() {
+//!         if clock.now() >= deadline {
+//!             return false;
+//!         }
+//!     }
+//!     true
+//! }
+//! ```
+
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Nanoseconds per microsecond
+const NANOS_PER_MICRO: u64 = 1_000;
+/// Nanoseconds per millisecond
+const NANOS_PER_MILLI: u64 = 1_000_000;
+/// Nanoseconds per second
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Span of time, with nanosecond precision.
+///
+/// The conversions from coarser units saturate at [`Duration::MAX`]. The arithmetic operators panic
+/// on overflow in debug builds, like integer arithmetic: use the `checked_` and `saturating_`
+/// methods when the operands are not known to be in range.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Duration(u64);
+
+impl Duration {
+    /// Zero duration.
+    pub const ZERO: Self = Self(0);
+    /// Longest representable duration.
+    pub const MAX: Self = Self(u64::MAX);
+
+    /// Create a duration of `nanos` nanoseconds.
+    #[inline]
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+    /// Create a duration of `micros` microseconds.
+    #[inline]
+    pub const fn from_micros(micros: u64) -> Self {
+        Self(micros.saturating_mul(NANOS_PER_MICRO))
+    }
+
+    /// Create a duration of `millis` milliseconds.
+    #[inline]
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis.saturating_mul(NANOS_PER_MILLI))
+    }
+
+    /// Create a duration of `secs` seconds.
+    #[inline]
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(secs.saturating_mul(NANOS_PER_SEC))
+    }
+
+    /// Returns the number of whole nanoseconds.
+    #[inline]
+    pub const fn as_nanos(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of whole microseconds.
+    #[inline]
+    pub const fn as_micros(&self) -> u64 {
+        self.0 / NANOS_PER_MICRO
+    }
+
+    /// Returns the number of whole milliseconds.
+    #[inline]
+    pub const fn as_millis(&self) -> u64 {
+        self.0 / NANOS_PER_MILLI
+    }
+
+    /// Returns the number of whole seconds.
+    #[inline]
+    pub const fn as_secs(&self) -> u64 {
+        self.0 / NANOS_PER_SEC
+    }
+
+    /// Add two durations, returning `None` on overflow.
+    #[inline]
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(nanos) => Some(Self(nanos)),
+            None => None,
+        }
+    }
+
+    /// Subtract two durations, returning `None` if `rhs` is longer than `self`.
+    #[inline]
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(nanos) => Some(Self(nanos)),
+            None => None,
+        }
+    }
+
+    /// Add two durations, saturating at [`Duration::MAX`].
+    #[inline]
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// Subtract two durations, saturating at [`Duration::ZERO`].
+    #[inline]
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Add for Duration {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Duration {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Duration {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Duration {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl From<Duration> for core::time::Duration {
+    #[inline]
+    fn from(duration: Duration) -> Self {
+        core::time::Duration::from_nanos(duration.0)
+    }
+}
+
+/// Point in time, in nanoseconds since an arbitrary epoch, such as the boot of the device.
+///
+/// Instants are only comparable if they were obtained from the same [`MonotonicClock`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Instant(u64);
+
+impl Instant {
+    /// Create an instant `nanos` nanoseconds after the epoch of the clock.
+    #[inline]
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+    /// Returns the number of nanoseconds since the epoch of the clock.
+    #[inline]
+    pub const fn as_nanos(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the duration elapsed from `earlier` to `self`, or `None` if `earlier` is later than `self`.
+    #[inline]
+    pub const fn checked_duration_since(&self, earlier: Self) -> Option<Duration> {
+        match self.0.checked_sub(earlier.0) {
+            Some(nanos) => Some(Duration(nanos)),
+            None => None,
+        }
+    }
+
+    /// Returns the duration elapsed from `earlier` to `self`, or zero if `earlier` is later than `self`.
+    #[inline]
+    pub const fn saturating_duration_since(&self, earlier: Self) -> Duration {
+        Duration(self.0.saturating_sub(earlier.0))
+    }
+
+    /// Returns `self + duration`, or `None` on overflow.
+    #[inline]
+    pub const fn checked_add(&self, duration: Duration) -> Option<Self> {
+        match self.0.checked_add(duration.0) {
+            Some(nanos) => Some(Self(nanos)),
+            None => None,
+        }
+    }
+
+    /// Returns `self - duration`, or `None` if it would be before the epoch.
+    #[inline]
+    pub const fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        match self.0.checked_sub(duration.0) {
+            Some(nanos) => Some(Self(nanos)),
+            None => None,
+        }
+    }
+}
+
+impl Sub for Instant {
+    type Output = Duration;
+
+    /// Returns the duration elapsed from `rhs` to `self`.
+    ///
+    /// Panics in debug builds if `rhs` is later than `self`, see
+    /// [`saturating_duration_since`](Instant::saturating_duration_since).
+    #[inline]
+    fn sub(self, rhs: Self) -> Duration {
+        Duration(self.0 - rhs.0)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Duration) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    #[inline]
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Duration) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+/// Clock returning monotonically non-decreasing instants.
+///
+/// Implementations must handle overflows of the underlying hardware timer, so that
+/// [`now`](MonotonicClock::now) never goes backwards: a narrow hardware counter has to be extended
+/// to 64 bits, for instance by counting its overflows in an interrupt handler. The clock is
+/// infallible, and reading it only takes a shared reference, so that it can be shared between drivers.
+pub trait MonotonicClock {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+
+    /// Returns the duration elapsed since `since`, or zero if `since` is in the future.
+    #[inline]
+    fn elapsed(&self, since: Instant) -> Duration {
+        self.now().saturating_duration_since(since)
+    }
+}
+
+impl<T: MonotonicClock + ?Sized> MonotonicClock for &T {
+    #[inline]
+    fn now(&self) -> Instant {
+        T::now(self)
+    }
+
+    #[inline]
+    fn elapsed(&self, since: Instant) -> Duration {
+        T::elapsed(self, since)
+    }
+}
+
+impl<T: MonotonicClock + ?Sized> MonotonicClock for &mut T {
+    #[inline]
+    fn now(&self) -> Instant {
+        T::now(self)
+    }
+
+    #[inline]
+    fn elapsed(&self, since: Instant) -> Duration {
+        T::elapsed(self, since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_conversions() {
+        assert_eq!(Duration::from_micros(3).as_nanos(), 3_000);
+        assert_eq!(Duration::from_millis(2_500).as_secs(), 2);
+        assert_eq!(Duration::from_secs(1).as_millis(), 1_000);
+        assert_eq!(Duration::from_nanos(1_999).as_micros(), 1);
+        assert_eq!(Duration::from_secs(u64::MAX), Duration::MAX);
+        assert_eq!(
+            core::time::Duration::from(Duration::from_millis(1_500)),
+            core::time::Duration::from_millis(1_500)
+        );
+    }
+
+    #[test]
+    fn duration_arithmetic() {
+        let mut d = Duration::from_millis(1) + Duration::from_micros(500);
+        assert_eq!(d, Duration::from_micros(1_500));
+        d -= Duration::from_millis(1);
+        assert_eq!(d.as_micros(), 500);
+        assert_eq!(Duration::ZERO.checked_sub(d), None);
+        assert_eq!(Duration::ZERO.saturating_sub(d), Duration::ZERO);
+        assert_eq!(Duration::MAX.checked_add(d), None);
+        assert_eq!(Duration::MAX.saturating_add(d), Duration::MAX);
+    }
+
+    #[test]
+    fn instant_arithmetic() {
+        let start = Instant::from_nanos(1_000);
+        let later = start + Duration::from_micros(2);
+        assert_eq!(later.as_nanos(), 3_000);
+        assert_eq!(later - start, Duration::from_nanos(2_000));
+        assert_eq!(later - Duration::from_micros(2), start);
+        assert_eq!(start.checked_duration_since(later), None);
+        assert_eq!(start.saturating_duration_since(later), Duration::ZERO);
+        assert_eq!(start.checked_sub(Duration::from_micros(2)), None);
+        assert_eq!(
+            Instant::from_nanos(u64::MAX).checked_add(Duration::from_nanos(1)),
+            None
+        );
+        assert!(later > start);
+    }
+
+    struct FakeClock(core::cell::Cell<u64>);
+
+    impl MonotonicClock for FakeClock {
+        fn now(&self) -> Instant {
+            self.0.set(self.0.get() + 10);
+            Instant::from_nanos(self.0.get())
+        }
+    }
+
+    #[test]
+    fn clock_through_references() {
+        fn elapsed(clock: impl MonotonicClock) -> Duration {
+            let start = clock.now();
+            clock.now() - start
+        }
+
+        let mut clock = FakeClock(Default::default());
+        assert_eq!(elapsed(&clock), Duration::from_nanos(10));
+        assert_eq!(elapsed(&mut clock), Duration::from_nanos(10));
+    }
+
+    #[test]
+    fn elapsed_since() {
+        let clock = FakeClock(Default::default());
+        let start = clock.now();
+        assert_eq!(clock.elapsed(start), Duration::from_nanos(10));
+        assert_eq!(
+            clock.elapsed(start + Duration::from_secs(1)),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Timer traits.
+//!
+//! The [`CountDown`] trait starts a timer expiring after a duration, which can be checked without
+//! blocking, unlike [`DelayNs`](crate::delay::DelayNs). The [`Periodic`] trait extends it with an
+//! auto-reloading mode.
+//!
+//! The [`HardwareCounter`] trait uses a timer in counter mode, counting the pulses of an external
+//! signal on a GPIO without CPU intervention. This is used by flow meters, rain gauges or
+//! frequency measurements.
+//!
+//! # Examples
+//!
+//! ```
+//! use embedded_hal::timer::CountDown;
+//!
+//! /// Poll a sensor while it converts, giving up after 10 ms.
+//! fn wait_conversion<T: CountDown>(
+//!     timer: &mut T,
+//!     mut ready: impl FnMut() -> bool,
+//! ) -> Result<bool, T::Error> {
+//!     timer.start(10_000_000)?;
+//!     while !ready() {
+//!         if timer.is_expired()? {
+//!             return Ok(false);
+//!         }
+//!     }
+//!     timer.cancel()?;
+//!     Ok(true)
+//! }
+//! ```
+//!
+//! ```
+//! use embedded_hal::delay::DelayNs;
+//! use embedded_hal::timer::{CountEdge, HardwareCounter};
+//!
+//! /// Returns the flow of a sensor emitting 450 pulses per liter, in milliliters per minute.
+//! fn flow_ml_per_min<C: HardwareCounter>(
+//!     counter: &mut C,
+//!     delay: &mut impl DelayNs,
+//! ) -> Result<u32, C::Error> {
+//!     counter.set_count_edge(CountEdge::Rising)?;
+//!     counter.reset_count()?;
+//!     counter.start_counting()?;
+//!     delay.delay_ms(1_000);
+//!     counter.stop_counting()?;
+//!     let pulses = counter.read_count()?;
+//!     Ok(pulses * 60 * 1_000 / 450)
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Timer error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic timer error kind.
+    ///
+    /// By using this method, timer errors freely defined by HAL implementations
+    /// can be converted to a set of generic timer errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Timer error kind.
+///
+/// This represents a common set of timer operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common timer errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The requested [`CountEdge`] is not supported by the hardware.
+    UnsupportedEdge,
+    /// The timer is not running: it was never started, or it was cancelled.
+    NotRunning,
+    /// The requested duration is out of the range of the timer.
+    UnsupportedDuration,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedEdge => write!(f, "The requested count edge is not supported"),
+            Self::NotRunning => write!(f, "The timer is not running"),
+            Self::UnsupportedDuration => write!(f, "The requested duration is out of range"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// Timer error type trait.
+///
+/// This just defines the error type, to be used by the other timer traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Timer expiring once, after a duration.
+///
+/// A timer is *running* from the time it is started until it is cancelled. A one-shot timer keeps
+/// running after it expired: it stays expired until it is started again.
+///
+/// Starting a timer which is already running restarts it, from the time of the call.
+pub trait CountDown: ErrorType {
+    /// Start the timer, expiring after `duration_ns` nanoseconds.
+    ///
+    /// The timer may expire later, up to the resolution of the hardware, but never earlier. Returns an
+    /// error of kind [`ErrorKind::UnsupportedDuration`] if the duration is too long for the timer, in
+    /// which case the timer is not running.
+    fn start(&mut self, duration_ns: u64) -> Result<(), Self::Error>;
+
+    /// Returns `true` if the timer expired.
+    ///
+    /// Returns an error of kind [`ErrorKind::NotRunning`] if the timer is not running.
+    fn is_expired(&mut self) -> Result<bool, Self::Error>;
+
+    /// Block until the timer expired.
+    ///
+    /// This returns immediately if the timer already expired. Returns an error of kind
+    /// [`ErrorKind::NotRunning`] if the timer is not running, instead of blocking forever.
+    fn wait(&mut self) -> Result<(), Self::Error>;
+
+    /// Stop the timer.
+    ///
+    /// Returns an error of kind [`ErrorKind::NotRunning`] if the timer is not running.
+    fn cancel(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: CountDown + ?Sized> CountDown for &mut T {
+    #[inline]
+    fn start(&mut self, duration_ns: u64) -> Result<(), Self::Error> {
+        T::start(self, duration_ns)
+    }
+
+    #[inline]
+    fn is_expired(&mut self) -> Result<bool, Self::Error> {
+        T::is_expired(self)
+    }
+
+    #[inline]
+    fn wait(&mut self) -> Result<(), Self::Error> {
+        T::wait(self)
+    }
+
+    #[inline]
+    fn cancel(&mut self) -> Result<(), Self::Error> {
+        T::cancel(self)
+    }
+}
+
+/// Timer expiring periodically.
+///
+/// In periodic mode, the timer reloads itself at the end of each period, without drifting: the periods
+/// follow each other from the time the timer was started, however late they are observed.
+/// [`is_expired`](CountDown::is_expired) and [`wait`](CountDown::wait) then observe the end of a
+/// period once: `is_expired` returns `true` if a period ended since the last time one was observed,
+/// and `wait` blocks until the end of the next period which was not observed yet. Periods ending while
+/// none is observed are merged.
+///
+/// [`start`](CountDown::start) puts the timer back in one-shot mode.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::timer::{CountDown, Periodic};
+///
+/// /// Sample `read` at 100 Hz.
+/// fn sample<T: Periodic>(
+///     timer: &mut T,
+///     mut read: impl FnMut() -> i16,
+///     samples: &mut [i16],
+/// ) -> Result<(), T::Error> {
+///     timer.start_periodic(10_000_000)?;
+///     for sample in samples {
+///         timer.wait()?;
+///         *sample = read();
+///     }
+///     timer.cancel()
+/// }
+/// ```
+pub trait Periodic: CountDown {
+    /// Start the timer in periodic mode, with a period of `period_ns` nanoseconds.
+    ///
+    /// Returns an error of kind [`ErrorKind::UnsupportedDuration`] if the period is 0 or too long for
+    /// the timer, in which case the timer is not running.
+    fn start_periodic(&mut self, period_ns: u64) -> Result<(), Self::Error>;
+}
+
+impl<T: Periodic + ?Sized> Periodic for &mut T {
+    #[inline]
+    fn start_periodic(&mut self, period_ns: u64) -> Result<(), Self::Error> {
+        T::start_periodic(self, period_ns)
+    }
+}
+
+/// Edges of the input signal counted by a [`HardwareCounter`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum CountEdge {
+    /// Count the rising edges.
+    Rising,
+    /// Count the falling edges.
+    Falling,
+    /// Count both the rising and the falling edges.
+    Both,
+}
+
+/// Timer counting the pulses of an external signal.
+///
+/// The count is kept while the counter is stopped, until it is [reset](HardwareCounter::reset_count).
+/// It wraps around at the width of the hardware counter, which can be narrower than 32 bits.
+pub trait HardwareCounter: ErrorType {
+    /// Start counting the edges of the input signal.
+    ///
+    /// Counting continues from the current count.
+    fn start_counting(&mut self) -> Result<(), Self::Error>;
+
+    /// Stop counting, keeping the current count.
+    fn stop_counting(&mut self) -> Result<(), Self::Error>;
+
+    /// Returns the current count.
+    ///
+    /// This can be called while counting.
+    fn read_count(&mut self) -> Result<u32, Self::Error>;
+
+    /// Reset the count to 0.
+    fn reset_count(&mut self) -> Result<(), Self::Error>;
+
+    /// Select the edges of the input signal to count.
+    ///
+    /// Returns an error of kind [`ErrorKind::UnsupportedEdge`] if the hardware cannot count `edge`.
+    fn set_count_edge(&mut self, edge: CountEdge) -> Result<(), Self::Error>;
+}
+
+impl<T: HardwareCounter + ?Sized> HardwareCounter for &mut T {
+    #[inline]
+    fn start_counting(&mut self) -> Result<(), Self::Error> {
+        T::start_counting(self)
+    }
+
+    #[inline]
+    fn stop_counting(&mut self) -> Result<(), Self::Error> {
+        T::stop_counting(self)
+    }
+
+    #[inline]
+    fn read_count(&mut self) -> Result<u32, Self::Error> {
+        T::read_count(self)
+    }
+
+    #[inline]
+    fn reset_count(&mut self) -> Result<(), Self::Error> {
+        T::reset_count(self)
+    }
+
+    #[inline]
+    fn set_count_edge(&mut self, edge: CountEdge) -> Result<(), Self::Error> {
+        T::set_count_edge(self, edge)
+    }
+}
@@ -0,0 +1,299 @@
+//! UART configuration traits.
+//!
+//! Reading and writing serial data is covered by the [`embedded-io`](https://docs.rs/embedded-io) traits.
+//! The traits of this module let drivers reconfigure the link: [`SerialConfig`] changes the baud rate
+//! and frame format, while [`SendBreak`] sends a break condition and [`DetectBreak`] reports received ones.
+//! HAL authors implement them on their UART type, alongside `embedded_io::Read` and `embedded_io::Write`.
+//! The async data transfer traits, `UartRx` and `UartTx` in `embedded-hal-async`, report errors with
+//! this module's [`ErrorKind`].
+//!
+//! # Example
+//!
+//! A DMX512 transmitter, which runs at 250 kbaud with 8 data bits, no parity and 2 stop bits, and
+//! starts each packet with a break:
+//!
+//! ```
+//! use embedded_hal::uart::{Config, SendBreak, SerialConfig, StopBits};
+//!
+//! fn start_packet<U: SerialConfig + SendBreak>(uart: &mut U) -> Result<(), U::Error> {
+//!     let config = Config::new(250_000).stop_bits(StopBits::Two);
+//!     uart.set_config(&config)?;
+//!     // The break must last at least 92 µs.
+//!     uart.send_break(100_000)
+//! }
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// UART error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic UART error kind.
+    ///
+    /// By using this method, UART errors freely defined by HAL implementations
+    /// can be converted to a set of generic UART errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// UART error kind.
+///
+/// This represents a common set of UART operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common UART errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The requested baud rate cannot be generated by the UART.
+    UnsupportedBaudRate,
+    /// The requested combination of data bits, parity and stop bits is not supported by the UART.
+    UnsupportedFrameFormat,
+    /// The peripheral receive buffer was overrun.
+    Overrun,
+    /// Received data does not conform to the peripheral configuration.
+    /// Can be caused by a misconfigured device on either end of the serial line.
+    FrameFormat,
+    /// Parity check failed.
+    Parity,
+    /// Serial line is too noisy to read valid data.
+    Noise,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedBaudRate => write!(f, "The requested baud rate is not supported"),
+            Self::UnsupportedFrameFormat => {
+                write!(f, "The requested frame format is not supported")
+            }
+            Self::Overrun => write!(f, "The peripheral receive buffer was overrun"),
+            Self::FrameFormat => write!(
+                f,
+                "Received data does not conform to the peripheral configuration"
+            ),
+            Self::Parity => write!(f, "Parity check failed"),
+            Self::Noise => write!(f, "Serial line is too noisy to read valid data"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// UART error type trait.
+///
+/// This just defines the error type, to be used by the other UART traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Number of data bits in a UART frame.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DataBits {
+    /// 5 data bits.
+    Five,
+    /// 6 data bits.
+    Six,
+    /// 7 data bits.
+    Seven,
+    /// 8 data bits.
+    Eight,
+    /// 9 data bits.
+    Nine,
+}
+
+/// Parity bit of a UART frame.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Parity {
+    /// No parity bit.
+    None,
+    /// Even parity: the number of `1` bits, including the parity bit, is even.
+    Even,
+    /// Odd parity: the number of `1` bits, including the parity bit, is odd.
+    Odd,
+}
+
+/// Number of stop bits in a UART frame.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum StopBits {
+    /// 1 stop bit.
+    One,
+    /// 1.5 stop bits.
+    OnePointFive,
+    /// 2 stop bits.
+    Two,
+}
+
+/// UART configuration.
+///
+/// Configurations are built from [`Config::new`], or from the default configuration, and adjusted with
+/// the builder methods:
+///
+/// ```
+/// use embedded_hal::uart::{Config, DataBits, Parity};
+///
+/// // 9600 baud, 7E1.
+/// const CONFIG: Config = Config::new(9_600)
+///     .data_bits(DataBits::Seven)
+///     .parity(Parity::Even);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct Config {
+    /// Baud rate, in bits per second.
+    pub baud_rate: u32,
+    /// Number of data bits.
+    pub data_bits: DataBits,
+    /// Parity bit.
+    pub parity: Parity,
+    /// Number of stop bits.
+    pub stop_bits: StopBits,
+}
+
+impl Config {
+    /// Create a new configuration with the given baud rate, and the common "8N1" frame format:
+    /// 8 data bits, no parity and 1 stop bit.
+    #[inline]
+    pub const fn new(baud_rate: u32) -> Self {
+        Self {
+            baud_rate,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+        }
+    }
+
+    /// Set the baud rate, in bits per second.
+    #[inline]
+    pub const fn baud_rate(mut self, baud_rate: u32) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    /// Set the number of data bits.
+    #[inline]
+    pub const fn data_bits(mut self, data_bits: DataBits) -> Self {
+        self.data_bits = data_bits;
+        self
+    }
+
+    /// Set the parity bit.
+    #[inline]
+    pub const fn parity(mut self, parity: Parity) -> Self {
+        self.parity = parity;
+        self
+    }
+
+    /// Set the number of stop bits.
+    #[inline]
+    pub const fn stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+}
+
+impl Default for Config {
+    /// 115200 baud, 8N1.
+    #[inline]
+    fn default() -> Self {
+        Self::new(115_200)
+    }
+}
+
+/// Reconfigure a UART.
+pub trait SerialConfig: ErrorType {
+    /// Apply `config` to the UART.
+    ///
+    /// Implementations should wait for pending transmissions to complete before changing the configuration.
+    /// Returns an error of kind [`ErrorKind::UnsupportedBaudRate`] or [`ErrorKind::UnsupportedFrameFormat`]
+    /// if the configuration is not supported, in which case the previous configuration is kept.
+    fn set_config(&mut self, config: &Config) -> Result<(), Self::Error>;
+}
+
+impl<T: SerialConfig + ?Sized> SerialConfig for &mut T {
+    #[inline]
+    fn set_config(&mut self, config: &Config) -> Result<(), Self::Error> {
+        T::set_config(self, config)
+    }
+}
+
+/// Send a break condition on a UART.
+///
+/// Break conditions are used by protocols such as DMX512 and LIN to mark the start of a packet, and
+/// by some bootloaders to enter their command mode.
+///
+/// UARTs without hardware break support can still implement this trait if the TX pin can be
+/// switched to a GPIO output: drive it low for the requested duration, then hand it back to the UART.
+pub trait SendBreak: ErrorType {
+    /// Hold the TX line low for at least `duration_ns` nanoseconds.
+    ///
+    /// Pending transmissions are completed before the break starts, and this method
+    /// returns once the break is over.
+    fn send_break(&mut self, duration_ns: u32) -> Result<(), Self::Error>;
+}
+
+impl<T: SendBreak + ?Sized> SendBreak for &mut T {
+    #[inline]
+    fn send_break(&mut self, duration_ns: u32) -> Result<(), Self::Error> {
+        T::send_break(self, duration_ns)
+    }
+}
+
+/// Detect break conditions received by a UART.
+///
+/// A break is detected when the RX line stays low for longer than a full frame, including the stop
+/// bits. LIN targets use this to detect the start of a header.
+///
+/// # Example
+///
+/// ```
+/// use embedded_hal::uart::DetectBreak;
+///
+/// /// Wait for the break starting a LIN header, discarding the data received in the meantime.
+/// fn wait_header<U: DetectBreak>(uart: &mut U) -> Result<(), U::Error> {
+///     while !uart.received_break()? {}
+///     Ok(())
+/// }
+/// ```
+pub trait DetectBreak: ErrorType {
+    /// Returns whether a break was received since the last call, and clears the break flag.
+    ///
+    /// Implementations should report a break once, however long the RX line stays low.
+    fn received_break(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<T: DetectBreak + ?Sized> DetectBreak for &mut T {
+    #[inline]
+    fn received_break(&mut self) -> Result<bool, Self::Error> {
+        T::received_break(self)
+    }
+}
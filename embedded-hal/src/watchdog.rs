@@ -0,0 +1,164 @@
+//! Watchdog timer traits.
+//!
+//! A watchdog timer resets the system if it is not fed periodically. It is used to recover
+//! from a hung system.
+//!
+//! Feeding a watchdog is done through the [`Watchdog`] trait, while starting and stopping it
+//! is done through the [`Enable`] and [`Disable`] traits. These traits use typestates: [`Enable::start`]
+//! consumes the disabled watchdog and returns the enabled one, which implements [`Watchdog`].
+//! This allows system-level code to only accept a watchdog that it can feed, while the application
+//! keeps control over its configuration.
+//!
+//! # Examples
+//!
+//! A driver generic over a running watchdog:
+//!
+//! ```
+//! use embedded_hal::watchdog::Watchdog;
+//!
+//! /// Feed the watchdog once the main loop of the application made progress.
+//! fn on_progress<W: Watchdog>(watchdog: &mut W) -> Result<(), W::Error> {
+//!     watchdog.feed()
+//! }
+//! ```
+//!
+//! Starting a watchdog, then handing it over to generic code:
+//!
+//! ```
+//! use embedded_hal::watchdog::{Enable, Watchdog};
+//! # use core::convert::Infallible;
+//! # use embedded_hal::watchdog::ErrorType;
+//! # struct Disabled;
+//! # struct Enabled;
+//! # impl ErrorType for Disabled { type Error = Infallible; }
+//! # impl ErrorType for Enabled { type Error = Infallible; }
+//! # impl Enable for Disabled {
+//! #     type Target = Enabled;
+//! #     fn start(self, _period_ns: u64) -> Result<Enabled, Infallible> { Ok(Enabled) }
+//! # }
+//! # impl Watchdog for Enabled { fn feed(&mut self) -> Result<(), Infallible> { Ok(()) } }
+//! # let watchdog = Disabled;
+//!
+//! fn run<W: Watchdog>(mut watchdog: W) -> Result<(), W::Error> {
+//!     for _ in 0..3 {
+//!         // ... do some work ...
+//!         watchdog.feed()?;
+//!     }
+//!     Ok(())
+//! }
+//!
+//! // Reset the system if it hangs for more than 500 ms.
+//! let watchdog = watchdog.start(500_000_000)?;
+//! run(watchdog)?;
+//! # Ok::<(), Infallible>(())
+//! ```
+
+#[cfg(feature = "defmt-03")]
+use crate::defmt;
+
+/// Watchdog error.
+pub trait Error: core::fmt::Debug {
+    /// Convert error to a generic watchdog error kind.
+    ///
+    /// By using this method, watchdog errors freely defined by HAL implementations
+    /// can be converted to a set of generic watchdog errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for core::convert::Infallible {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+/// Watchdog error kind.
+///
+/// This represents a common set of watchdog operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common watchdog errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The requested period cannot be configured on this watchdog.
+    InvalidPeriod,
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl Error for ErrorKind {
+    #[inline]
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+impl core::fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidPeriod => write!(f, "The requested period cannot be configured"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// Watchdog error type trait.
+///
+/// This just defines the error type, to be used by the other watchdog traits.
+pub trait ErrorType {
+    /// Error type
+    type Error: Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+/// Feed a watchdog.
+pub trait Watchdog: ErrorType {
+    /// Feed ("pet") the watchdog, restarting its countdown.
+    ///
+    /// This must be called periodically, more often than the configured period,
+    /// to prevent the watchdog from resetting the system.
+    fn feed(&mut self) -> Result<(), Self::Error>;
+}
+
+impl<T: Watchdog + ?Sized> Watchdog for &mut T {
+    #[inline]
+    fn feed(&mut self) -> Result<(), Self::Error> {
+        T::feed(self)
+    }
+}
+
+/// Start a disabled watchdog.
+///
+/// This consumes the disabled watchdog, and returns the enabled one, which can then be fed.
+pub trait Enable: ErrorType {
+    /// Enabled watchdog type.
+    type Target: Watchdog;
+
+    /// Start the watchdog, with a period of at least `period_ns` nanoseconds.
+    ///
+    /// Returns an error of kind [`ErrorKind::InvalidPeriod`] if the period cannot be configured.
+    fn start(self, period_ns: u64) -> Result<Self::Target, Self::Error>;
+}
+
+/// Disable an enabled watchdog.
+///
+/// This consumes the enabled watchdog, and returns the disabled one, which can be started again.
+/// Watchdogs which cannot be disabled once started do not implement this trait.
+pub trait Disable: ErrorType {
+    /// Disabled watchdog type.
+    type Target: Enable;
+
+    /// Disable the watchdog.
+    fn disable(self) -> Result<Self::Target, Self::Error>;
+}
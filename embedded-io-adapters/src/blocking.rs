@@ -0,0 +1,322 @@
+//! Adapters from `embedded-io-async` traits to the blocking `embedded-io` traits.
+
+use core::future::Future;
+
+/// Executor hook driving a future to completion.
+///
+/// This is implemented by the user to plug in any executor, such as `embassy_futures::block_on`
+/// or `cassette`. With the `std` feature, [`ThreadBlocker`] can be used.
+pub trait Blocker {
+    /// Run `future` to completion, blocking the current thread, and return its output.
+    ///
+    /// The future must not be dropped before it completes.
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output;
+}
+
+impl<T: Blocker + ?Sized> Blocker for &mut T {
+    #[inline]
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        T::block_on(self, future)
+    }
+}
+
+/// [`Blocker`] parking the current thread until the future is woken.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadBlocker;
+
+#[cfg(feature = "std")]
+impl Blocker for ThreadBlocker {
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake, Waker};
+        use std::thread::{self, Thread};
+
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+
+            fn wake_by_ref(self: &Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
+
+/// Adapter from `embedded-io-async` traits.
+///
+/// This implements the blocking `embedded-io` traits by running each operation of the inner
+/// async object to completion with a [`Blocker`]. This allows using a driver written against
+/// `embedded-io-async` in a blocking application.
+///
+/// # Example
+///
+/// ```
+/// use embedded_io::Read;
+/// use embedded_io_adapters::blocking::{Blocker, BlockingAdapter};
+/// # struct MyBlocker;
+/// # impl Blocker for MyBlocker {
+/// #     fn block_on<F: core::future::Future>(&mut self, future: F) -> F::Output {
+/// #         use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+/// #         const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+/// #         const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+/// #         let waker = unsafe { Waker::from_raw(RAW) };
+/// #         let mut cx = Context::from_waker(&waker);
+/// #         let mut future = core::pin::pin!(future);
+/// #         loop {
+/// #             if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+/// #                 return output;
+/// #             }
+/// #         }
+/// #     }
+/// # }
+///
+/// // `&[u8]` implements `embedded_io_async::Read`.
+/// let mut reader = BlockingAdapter::new(&b"hello"[..], MyBlocker);
+/// let mut buf = [0; 5];
+/// reader.read_exact(&mut buf).unwrap();
+/// assert_eq!(&buf, b"hello");
+/// ```
+#[derive(Clone)]
+pub struct BlockingAdapter<T, B> {
+    inner: T,
+    blocker: B,
+}
+
+impl<T, B> BlockingAdapter<T, B> {
+    /// Create a new adapter, running the operations of `inner` with `blocker`.
+    pub fn new(inner: T, blocker: B) -> Self {
+        Self { inner, blocker }
+    }
+
+    /// Consume the adapter, returning the inner object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Borrow the inner object.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner object.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: embedded_io::ErrorType, B> embedded_io::ErrorType for BlockingAdapter<T, B> {
+    type Error = T::Error;
+}
+
+impl<T: embedded_io_async::Read, B: Blocker> embedded_io::Read for BlockingAdapter<T, B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.blocker.block_on(self.inner.read(buf))
+    }
+
+    fn read_exact(
+        &mut self,
+        buf: &mut [u8],
+    ) -> Result<(), embedded_io::ReadExactError<Self::Error>> {
+        self.blocker.block_on(self.inner.read_exact(buf))
+    }
+}
+
+impl<T: embedded_io_async::BufRead, B: Blocker> embedded_io::BufRead for BlockingAdapter<T, B> {
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        self.blocker.block_on(self.inner.fill_buf())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+    }
+}
+
+impl<T: embedded_io_async::Write, B: Blocker> embedded_io::Write for BlockingAdapter<T, B> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.blocker.block_on(self.inner.write(buf))
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.blocker.block_on(self.inner.flush())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.blocker.block_on(self.inner.write_all(buf))
+    }
+}
+
+impl<T: embedded_io_async::Seek, B: Blocker> embedded_io::Seek for BlockingAdapter<T, B> {
+    fn seek(&mut self, pos: embedded_io::SeekFrom) -> Result<u64, Self::Error> {
+        self.blocker.block_on(self.inner.seek(pos))
+    }
+}
+
+impl<T: embedded_io::ReadReady, B> embedded_io::ReadReady for BlockingAdapter<T, B> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        self.inner.read_ready()
+    }
+}
+
+impl<T: embedded_io::WriteReady, B> embedded_io::WriteReady for BlockingAdapter<T, B> {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        self.inner.write_ready()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::future::poll_fn;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use embedded_io::{BufRead, ErrorKind, Read, Write};
+
+    /// Busy-polling blocker, counting the polls.
+    struct Spin {
+        polls: usize,
+    }
+
+    impl Blocker for Spin {
+        fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+            const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+            const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+            // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+            let waker = unsafe { Waker::from_raw(RAW) };
+            let mut cx = Context::from_waker(&waker);
+            let mut future = core::pin::pin!(future);
+            loop {
+                self.polls += 1;
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+        }
+    }
+
+    /// Async I/O object yielding `delay` times before completing each operation.
+    struct Slow {
+        data: &'static [u8],
+        delay: usize,
+        written: usize,
+        fail: bool,
+    }
+
+    impl Slow {
+        async fn yield_now(&self) {
+            let mut remaining = self.delay;
+            poll_fn(|cx| {
+                if remaining == 0 {
+                    Poll::Ready(())
+                } else {
+                    remaining -= 1;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            })
+            .await
+        }
+    }
+
+    impl embedded_io::ErrorType for Slow {
+        type Error = ErrorKind;
+    }
+
+    impl embedded_io_async::Read for Slow {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+            self.yield_now().await;
+            if self.fail {
+                return Err(ErrorKind::ConnectionReset);
+            }
+            embedded_io_async::Read::read(&mut self.data, buf)
+                .await
+                .map_err(|e| match e {})
+        }
+    }
+
+    impl embedded_io_async::BufRead for Slow {
+        async fn fill_buf(&mut self) -> Result<&[u8], ErrorKind> {
+            self.yield_now().await;
+            Ok(self.data)
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.data = &self.data[amt..];
+        }
+    }
+
+    impl embedded_io_async::Write for Slow {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+            self.yield_now().await;
+            if self.fail {
+                return Err(ErrorKind::BrokenPipe);
+            }
+            self.written += buf.len();
+            Ok(buf.len())
+        }
+
+        async fn flush(&mut self) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    fn slow(fail: bool) -> BlockingAdapter<Slow, Spin> {
+        let slow = Slow {
+            data: b"hello",
+            delay: 3,
+            written: 0,
+            fail,
+        };
+        BlockingAdapter::new(slow, Spin { polls: 0 })
+    }
+
+    #[test]
+    fn operations_run_to_completion() {
+        let mut adapter = slow(false);
+        let mut buf = [0; 2];
+        assert_eq!(adapter.read(&mut buf), Ok(2));
+        assert_eq!(&buf, b"he");
+        assert_eq!(adapter.blocker.polls, 4);
+        assert_eq!(adapter.fill_buf(), Ok(&b"llo"[..]));
+        adapter.consume(3);
+        assert_eq!(adapter.write(b"abc"), Ok(3));
+        adapter.write_all(b"defg").unwrap();
+        assert_eq!(adapter.into_inner().written, 7);
+    }
+
+    #[test]
+    fn errors_are_passed_through() {
+        let mut adapter = slow(true);
+        assert_eq!(adapter.read(&mut [0; 2]), Err(ErrorKind::ConnectionReset));
+        assert_eq!(adapter.write(b"abc"), Err(ErrorKind::BrokenPipe));
+        assert_eq!(
+            adapter.read_exact(&mut [0; 2]),
+            Err(embedded_io::ReadExactError::Other(
+                ErrorKind::ConnectionReset
+            ))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn thread_blocker() {
+        let mut adapter = BlockingAdapter::new(slow(false).into_inner(), ThreadBlocker);
+        let mut buf = [0; 5];
+        adapter.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+    }
+}
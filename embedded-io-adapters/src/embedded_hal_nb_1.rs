@@ -0,0 +1,749 @@
+//! Adapters to/from `embedded_hal_nb::serial` traits.
+//!
+//! With the `embedded-hal-async-1` feature, [`FromNbAsync`] also adapts `embedded_hal_nb` serial
+//! and SPI implementations to async traits.
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial;
+
+/// Error of the [`FromNbSerial`] and [`ToNbSerial`] adapters, wrapping the error of the inner object.
+///
+/// The error kind is mapped between the `embedded-hal-nb` serial and `embedded-io` error kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialError<E>(pub E);
+
+impl<E: serial::Error> embedded_io::Error for SerialError<E> {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self.0.kind() {
+            serial::ErrorKind::Overrun
+            | serial::ErrorKind::FrameFormat
+            | serial::ErrorKind::Parity
+            | serial::ErrorKind::Noise => embedded_io::ErrorKind::InvalidData,
+            _ => embedded_io::ErrorKind::Other,
+        }
+    }
+}
+
+impl<E: embedded_io::Error> serial::Error for SerialError<E> {
+    fn kind(&self) -> serial::ErrorKind {
+        serial::ErrorKind::Other
+    }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for SerialError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl<E: core::fmt::Debug> core::error::Error for SerialError<E> {}
+
+/// Adapter from `embedded_hal_nb::serial` traits.
+///
+/// `read` and `write` block until at least one word is transferred, then transfer the words
+/// which are immediately available, without blocking again.
+///
+/// If an error happens after some words were transferred, the number of words transferred is
+/// returned, and the error is returned by the next call.
+pub struct FromNbSerial<T: serial::ErrorType> {
+    inner: T,
+    error: Option<T::Error>,
+}
+
+impl<T: serial::ErrorType> FromNbSerial<T> {
+    /// Create a new adapter.
+    pub fn new(inner: T) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Consume the adapter, returning the inner object.
+    ///
+    /// An error which was not returned yet is lost.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Borrow the inner object.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner object.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Return the error of a previous call, if any.
+    fn take_error(&mut self) -> Result<(), SerialError<T::Error>> {
+        self.error.take().map_or(Ok(()), |e| Err(SerialError(e)))
+    }
+}
+
+impl<T: serial::ErrorType> embedded_io::ErrorType for FromNbSerial<T> {
+    type Error = SerialError<T::Error>;
+}
+
+impl<T: serial::Read> embedded_io::Read for FromNbSerial<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.take_error()?;
+        let Some((first, rest)) = buf.split_first_mut() else {
+            return Ok(0);
+        };
+        *first = nb::block!(self.inner.read()).map_err(SerialError)?;
+        let mut n = 1;
+        for word in rest {
+            match self.inner.read() {
+                Ok(w) => *word = w,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => {
+                    self.error = Some(e);
+                    break;
+                }
+            }
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+impl<T: serial::Write> embedded_io::Write for FromNbSerial<T> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.take_error()?;
+        let Some((first, rest)) = buf.split_first() else {
+            return Ok(0);
+        };
+        nb::block!(self.inner.write(*first)).map_err(SerialError)?;
+        let mut n = 1;
+        for word in rest {
+            match self.inner.write(*word) {
+                Ok(()) => n += 1,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => {
+                    self.error = Some(e);
+                    break;
+                }
+            }
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.take_error()?;
+        nb::block!(self.inner.flush()).map_err(SerialError)
+    }
+}
+
+/// Adapter to `embedded_hal_nb::serial` traits.
+///
+/// A word is only read (resp. written) if [`ReadReady`](embedded_io::ReadReady) (resp.
+/// [`WriteReady`](embedded_io::WriteReady)) reports that this does not block: otherwise,
+/// [`nb::Error::WouldBlock`] is returned.
+///
+/// `embedded-io` has no non-blocking flush: `flush` calls the blocking `flush` of the
+/// inner object. A `read` reaching the end of the stream returns [`nb::Error::WouldBlock`].
+pub struct ToNbSerial<T> {
+    inner: T,
+}
+
+impl<T> ToNbSerial<T> {
+    /// Create a new adapter.
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Consume the adapter, returning the inner object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Borrow the inner object.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner object.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: embedded_io::ErrorType> serial::ErrorType for ToNbSerial<T> {
+    type Error = SerialError<T::Error>;
+}
+
+impl<T: embedded_io::Read + embedded_io::ReadReady> serial::Read for ToNbSerial<T> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        if !self.inner.read_ready().map_err(SerialError)? {
+            return Err(nb::Error::WouldBlock);
+        }
+        let mut word = [0];
+        match self.inner.read(&mut word).map_err(SerialError)? {
+            0 => Err(nb::Error::WouldBlock),
+            _ => Ok(word[0]),
+        }
+    }
+}
+
+impl<T: embedded_io::Write + embedded_io::WriteReady> serial::Write for ToNbSerial<T> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        if !self.inner.write_ready().map_err(SerialError)? {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.inner.write_all(&[word]).map_err(SerialError)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(self.inner.flush().map_err(SerialError)?)
+    }
+}
+
+/// Strategy used by [`FromNbAsync`] to wait before polling an `nb` operation again.
+///
+/// HALs which can signal readiness from an interrupt can implement this trait, e.g. by
+/// registering the waker of the task and waiting for the interrupt.
+#[cfg(feature = "embedded-hal-async-1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async-1")))]
+#[allow(async_fn_in_trait)]
+pub trait Poller {
+    /// Wait until the operation should be polled again.
+    async fn wait(&mut self);
+}
+
+/// [`Poller`] yielding to the executor, which polls the operation again as soon as possible.
+///
+/// This keeps the executor busy while waiting.
+#[cfg(feature = "embedded-hal-async-1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async-1")))]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Yield;
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl Poller for Yield {
+    async fn wait(&mut self) {
+        let mut yielded = false;
+        core::future::poll_fn(|cx| {
+            if yielded {
+                core::task::Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            }
+        })
+        .await;
+    }
+}
+
+/// [`Poller`] sleeping for a fixed interval with an async [`DelayNs`](embedded_hal_async::delay::DelayNs).
+#[cfg(feature = "embedded-hal-async-1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async-1")))]
+pub struct PollInterval<D> {
+    delay: D,
+    interval_ns: u32,
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<D> PollInterval<D> {
+    /// Create a new poller, waiting `interval_ns` nanoseconds between polls.
+    pub fn new(delay: D, interval_ns: u32) -> Self {
+        Self { delay, interval_ns }
+    }
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<D: embedded_hal_async::delay::DelayNs> Poller for PollInterval<D> {
+    async fn wait(&mut self) {
+        self.delay.delay_ns(self.interval_ns).await;
+    }
+}
+
+/// Adapter from `embedded_hal_nb` traits to async traits.
+///
+/// This implements [`embedded_io_async::Read`] and [`embedded_io_async::Write`] for
+/// [`serial::Read`] and [`serial::Write`] implementations, and
+/// [`embedded_hal_async::spi::SpiBus`] for [`FullDuplex`](embedded_hal_nb::spi::FullDuplex)
+/// implementations. Operations returning [`nb::Error::WouldBlock`] are polled again after
+/// waiting with the [`Poller`] `P`.
+///
+/// Like [`FromNbSerial`], `read` and `write` wait until at least one word is transferred, then
+/// transfer the words which are immediately available. Errors are returned immediately, even if
+/// some words were already transferred.
+#[cfg(feature = "embedded-hal-async-1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async-1")))]
+pub struct FromNbAsync<T, P = Yield> {
+    inner: T,
+    poller: P,
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<T> FromNbAsync<T> {
+    /// Create a new adapter, yielding to the executor between polls.
+    pub fn new(inner: T) -> Self {
+        Self::with_poller(inner, Yield)
+    }
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<T, P> FromNbAsync<T, P> {
+    /// Create a new adapter, waiting with `poller` between polls.
+    pub fn with_poller(inner: T, poller: P) -> Self {
+        Self { inner, poller }
+    }
+
+    /// Consume the adapter, returning the inner object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Borrow the inner object.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner object.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+/// Poll `f` until it does not return `WouldBlock`, waiting with `poller` in between.
+#[cfg(feature = "embedded-hal-async-1")]
+async fn poll_nb<R, E>(
+    poller: &mut impl Poller,
+    mut f: impl FnMut() -> nb::Result<R, E>,
+) -> Result<R, E> {
+    loop {
+        match f() {
+            Ok(r) => return Ok(r),
+            Err(nb::Error::Other(e)) => return Err(e),
+            Err(nb::Error::WouldBlock) => poller.wait().await,
+        }
+    }
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<T: serial::ErrorType, P> embedded_io::ErrorType for FromNbAsync<T, P> {
+    type Error = SerialError<T::Error>;
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<T: serial::Read, P: Poller> embedded_io_async::Read for FromNbAsync<T, P> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let Some((first, rest)) = buf.split_first_mut() else {
+            return Ok(0);
+        };
+        let inner = &mut self.inner;
+        *first = poll_nb(&mut self.poller, || inner.read())
+            .await
+            .map_err(SerialError)?;
+        let mut n = 1;
+        for word in rest {
+            match self.inner.read() {
+                Ok(w) => *word = w,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(SerialError(e)),
+            }
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<T: serial::Write, P: Poller> embedded_io_async::Write for FromNbAsync<T, P> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let Some((first, rest)) = buf.split_first() else {
+            return Ok(0);
+        };
+        let inner = &mut self.inner;
+        poll_nb(&mut self.poller, || inner.write(*first))
+            .await
+            .map_err(SerialError)?;
+        let mut n = 1;
+        for word in rest {
+            match self.inner.write(*word) {
+                Ok(()) => n += 1,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(SerialError(e)),
+            }
+        }
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        let inner = &mut self.inner;
+        poll_nb(&mut self.poller, || inner.flush())
+            .await
+            .map_err(SerialError)
+    }
+}
+
+#[cfg(feature = "embedded-hal-async-1")]
+impl<T: embedded_hal_nb::spi::ErrorType, P> embedded_hal_nb::spi::ErrorType for FromNbAsync<T, P> {
+    type Error = T::Error;
+}
+
+/// Exchange one word on a `FullDuplex` bus.
+#[cfg(feature = "embedded-hal-async-1")]
+async fn exchange<T, Word>(
+    inner: &mut T,
+    poller: &mut impl Poller,
+    word: Word,
+) -> Result<Word, T::Error>
+where
+    T: embedded_hal_nb::spi::FullDuplex<Word>,
+    Word: Copy,
+{
+    poll_nb(poller, || inner.write(word)).await?;
+    poll_nb(poller, || inner.read()).await
+}
+
+/// The words written while reading are `Word::default()`, i.e. `0x00` for `u8`.
+///
+/// Each word is read back before the next one is written, so `flush` does nothing.
+#[cfg(feature = "embedded-hal-async-1")]
+impl<T, P, Word> embedded_hal_async::spi::SpiBus<Word> for FromNbAsync<T, P>
+where
+    T: embedded_hal_nb::spi::FullDuplex<Word>,
+    P: Poller,
+    Word: Copy + Default + 'static,
+{
+    async fn read(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = exchange(&mut self.inner, &mut self.poller, Word::default()).await?;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[Word]) -> Result<(), Self::Error> {
+        for word in words {
+            exchange(&mut self.inner, &mut self.poller, *word).await?;
+        }
+        Ok(())
+    }
+
+    async fn transfer(&mut self, read: &mut [Word], write: &[Word]) -> Result<(), Self::Error> {
+        for i in 0..read.len().max(write.len()) {
+            let word = write.get(i).copied().unwrap_or_default();
+            let word = exchange(&mut self.inner, &mut self.poller, word).await?;
+            if let Some(r) = read.get_mut(i) {
+                *r = word;
+            }
+        }
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [Word]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = exchange(&mut self.inner, &mut self.poller, *word).await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_io::{Error as _, ErrorKind, Read, ReadReady, Write, WriteReady};
+    use serial::{Read as _, Write as _};
+
+    /// Serial port following a script: `None` is a `WouldBlock`, and `0xFF` an overrun error.
+    struct NbPort {
+        rx: &'static [Option<u8>],
+        tx: [u8; 8],
+        tx_len: usize,
+        tx_space: usize,
+        pending_flush: usize,
+    }
+
+    fn nb_port(rx: &'static [Option<u8>]) -> NbPort {
+        NbPort {
+            rx,
+            tx: [0; 8],
+            tx_len: 0,
+            tx_space: 2,
+            pending_flush: 0,
+        }
+    }
+
+    impl serial::ErrorType for NbPort {
+        type Error = serial::ErrorKind;
+    }
+
+    impl serial::Read for NbPort {
+        fn read(&mut self) -> nb::Result<u8, serial::ErrorKind> {
+            let (step, rest) = self.rx.split_first().ok_or(nb::Error::WouldBlock)?;
+            self.rx = rest;
+            match step {
+                None => Err(nb::Error::WouldBlock),
+                Some(0xFF) => Err(nb::Error::Other(serial::ErrorKind::Overrun)),
+                Some(word) => Ok(*word),
+            }
+        }
+    }
+
+    impl serial::Write for NbPort {
+        fn write(&mut self, word: u8) -> nb::Result<(), serial::ErrorKind> {
+            if self.tx_space == 0 {
+                // Make room for the next call.
+                self.tx_space = 2;
+                return Err(nb::Error::WouldBlock);
+            }
+            self.tx_space -= 1;
+            self.tx[self.tx_len] = word;
+            self.tx_len += 1;
+            self.pending_flush += 1;
+            Ok(())
+        }
+
+        fn flush(&mut self) -> nb::Result<(), serial::ErrorKind> {
+            if self.pending_flush == 0 {
+                Ok(())
+            } else {
+                self.pending_flush -= 1;
+                Err(nb::Error::WouldBlock)
+            }
+        }
+    }
+
+    #[test]
+    fn read_returns_early_with_partial_data() {
+        let mut port = FromNbSerial::new(nb_port(&[None, None, Some(1), Some(2), None, Some(3)]));
+        let mut buf = [0; 8];
+        assert_eq!(port.read(&mut buf), Ok(2));
+        assert_eq!(&buf[..2], [1, 2]);
+        assert_eq!(port.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 3);
+    }
+
+    #[test]
+    fn read_error_after_data_is_returned_next() {
+        let mut port = FromNbSerial::new(nb_port(&[Some(1), Some(0xFF), Some(2)]));
+        let mut buf = [0; 8];
+        assert_eq!(port.read(&mut buf), Ok(1));
+        let err = port.read(&mut buf).unwrap_err();
+        assert_eq!(err, SerialError(serial::ErrorKind::Overrun));
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(port.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 2);
+    }
+
+    #[test]
+    fn write_and_flush() {
+        let mut port = FromNbSerial::new(nb_port(&[]));
+        assert_eq!(port.write(b"abc"), Ok(2));
+        port.write_all(b"cde").unwrap();
+        port.flush().unwrap();
+        let port = port.into_inner();
+        assert_eq!(&port.tx[..port.tx_len], b"abcde");
+        assert_eq!(port.pending_flush, 0);
+    }
+
+    /// `embedded-io` object over fixed buffers, with `ready` controlling readiness.
+    struct IoPort {
+        rx: &'static [u8],
+        tx: [u8; 8],
+        tx_len: usize,
+        ready: bool,
+        flushed: bool,
+    }
+
+    impl embedded_io::ErrorType for IoPort {
+        type Error = ErrorKind;
+    }
+
+    impl Read for IoPort {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+            self.rx.read(buf).map_err(|e| match e {})
+        }
+    }
+
+    impl ReadReady for IoPort {
+        fn read_ready(&mut self) -> Result<bool, ErrorKind> {
+            Ok(self.ready)
+        }
+    }
+
+    impl Write for IoPort {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+            self.tx[self.tx_len] = buf[0];
+            self.tx_len += 1;
+            self.flushed = false;
+            Ok(1)
+        }
+
+        fn flush(&mut self) -> Result<(), ErrorKind> {
+            self.flushed = true;
+            Ok(())
+        }
+    }
+
+    impl WriteReady for IoPort {
+        fn write_ready(&mut self) -> Result<bool, ErrorKind> {
+            Ok(self.ready)
+        }
+    }
+
+    #[test]
+    fn not_ready_is_would_block() {
+        let mut port = ToNbSerial::new(IoPort {
+            rx: b"a",
+            tx: [0; 8],
+            tx_len: 0,
+            ready: false,
+            flushed: false,
+        });
+        assert_eq!(port.read(), Err(nb::Error::WouldBlock));
+        assert_eq!(port.write(b'x'), Err(nb::Error::WouldBlock));
+        port.inner_mut().ready = true;
+        assert_eq!(port.read(), Ok(b'a'));
+        assert_eq!(port.read(), Err(nb::Error::WouldBlock));
+        assert_eq!(port.write(b'x'), Ok(()));
+        assert_eq!(port.flush(), Ok(()));
+        let port = port.into_inner();
+        assert_eq!(&port.tx[..port.tx_len], b"x");
+        assert!(port.flushed);
+    }
+
+    #[cfg(feature = "embedded-hal-async-1")]
+    mod nb_async {
+        use super::*;
+        use core::future::Future;
+        use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+        use embedded_hal_async::spi::SpiBus;
+
+        /// Run `future` to completion, returning its output and the number of polls.
+        fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+            const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+            const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+            // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+            let waker = unsafe { Waker::from_raw(RAW) };
+            let mut cx = Context::from_waker(&waker);
+            let mut future = core::pin::pin!(future);
+            let mut polls = 0;
+            loop {
+                polls += 1;
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return (output, polls);
+                }
+            }
+        }
+
+        /// Loopback SPI bus, returning `WouldBlock` `busy` times before each operation.
+        struct Loopback {
+            busy: usize,
+            remaining: usize,
+            shift: Option<u8>,
+        }
+
+        impl Loopback {
+            fn new(busy: usize) -> Self {
+                Self {
+                    busy,
+                    remaining: busy,
+                    shift: None,
+                }
+            }
+
+            fn ready(&mut self) -> nb::Result<(), ErrorKind> {
+                if self.remaining == 0 {
+                    self.remaining = self.busy;
+                    Ok(())
+                } else {
+                    self.remaining -= 1;
+                    Err(nb::Error::WouldBlock)
+                }
+            }
+        }
+
+        impl embedded_hal_nb::spi::ErrorType for Loopback {
+            type Error = embedded_hal_nb::spi::ErrorKind;
+        }
+
+        impl embedded_hal_nb::spi::FullDuplex for Loopback {
+            fn read(&mut self) -> nb::Result<u8, Self::Error> {
+                self.ready().map_err(|_| nb::Error::WouldBlock)?;
+                Ok(self.shift.take().expect("read before write"))
+            }
+
+            fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+                self.ready().map_err(|_| nb::Error::WouldBlock)?;
+                self.shift = Some(word.wrapping_add(1));
+                Ok(())
+            }
+        }
+
+        /// Delay counting the requested intervals.
+        struct CountingDelay(usize);
+
+        impl embedded_hal_async::delay::DelayNs for CountingDelay {
+            async fn delay_ns(&mut self, ns: u32) {
+                assert_eq!(ns, 1_000);
+                self.0 += 1;
+            }
+        }
+
+        #[test]
+        fn serial_write_yields_on_would_block() {
+            let mut port = FromNbAsync::new(nb_port(&[]));
+            let (n, polls) = block_on(embedded_io_async::Write::write(&mut port, b"ab"));
+            assert_eq!(n, Ok(2));
+            assert_eq!(polls, 1);
+            // The port is full: the next write yields once before making progress.
+            let (result, polls) = block_on(embedded_io_async::Write::write_all(&mut port, b"c"));
+            assert_eq!(result, Ok(()));
+            assert_eq!(polls, 2);
+            let (result, polls) = block_on(embedded_io_async::Write::flush(&mut port));
+            assert_eq!(result, Ok(()));
+            assert_eq!(polls, 4);
+            let port = port.into_inner();
+            assert_eq!(&port.tx[..port.tx_len], b"abc");
+        }
+
+        #[test]
+        fn serial_read_returns_early_with_partial_data() {
+            let mut port = FromNbAsync::new(nb_port(&[None, Some(1), Some(2), None, Some(0xFF)]));
+            let mut buf = [0; 8];
+            let (n, polls) = block_on(embedded_io_async::Read::read(&mut port, &mut buf));
+            assert_eq!(n, Ok(2));
+            assert_eq!(polls, 2);
+            let (result, _) = block_on(embedded_io_async::Read::read(&mut port, &mut buf));
+            assert_eq!(result, Err(SerialError(serial::ErrorKind::Overrun)));
+        }
+
+        #[test]
+        fn spi_bus_polls_with_interval() {
+            let mut bus = FromNbAsync::with_poller(
+                Loopback::new(2),
+                PollInterval::new(CountingDelay(0), 1_000),
+            );
+            let mut read = [0; 3];
+            let (result, polls) = block_on(bus.transfer(&mut read, &[10, 20]));
+            assert_eq!(result, Ok(()));
+            // The delay completes immediately, so the future never returns `Pending`.
+            assert_eq!(polls, 1);
+            assert_eq!(read, [11, 21, 1]);
+            let mut words = [5, 6];
+            block_on(bus.transfer_in_place(&mut words)).0.unwrap();
+            assert_eq!(words, [6, 7]);
+            // Two busy polls before each of the 10 write and read operations.
+            assert_eq!(bus.poller.delay.0, 20);
+        }
+
+        #[test]
+        fn spi_bus_yields() {
+            let mut bus = FromNbAsync::new(Loopback::new(1));
+            let mut read = [0; 2];
+            let (result, polls) = block_on(bus.read(&mut read));
+            assert_eq!(result, Ok(()));
+            assert_eq!(read, [1, 1]);
+            assert_eq!(polls, 5);
+        }
+    }
+}
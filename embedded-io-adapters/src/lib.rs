@@ -5,6 +5,10 @@
 
 pub mod fmt;
 
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub mod blocking;
+
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 pub mod std;
@@ -16,3 +20,11 @@ pub mod futures_03;
 #[cfg(feature = "tokio-1")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio-1")))]
 pub mod tokio_1;
+
+#[cfg(feature = "embedded-hal-nb-1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-nb-1")))]
+pub mod embedded_hal_nb_1;
+
+#[cfg(feature = "embedded-hal-async-1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "embedded-hal-async-1")))]
+pub mod timeout;
@@ -2,6 +2,12 @@
 
 use embedded_io::Error as _;
 
+/// Maximum number of buffers forwarded by a single vectored write.
+///
+/// The slices are converted on the stack; extra buffers are left for the next call,
+/// which is allowed since vectored writes can be partial.
+const MAX_IO_SLICES: usize = 16;
+
 /// Adapter from `std::io` traits.
 #[derive(Clone)]
 pub struct FromStd<T: ?Sized> {
@@ -78,6 +84,21 @@ impl<T: std::io::Write + ?Sized> embedded_io::Write for FromStd<T> {
         self.inner.write_all(buf)
     }
 
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        let mut slices = [std::io::IoSlice::new(&[]); MAX_IO_SLICES];
+        for (slice, buf) in slices.iter_mut().zip(bufs) {
+            *slice = std::io::IoSlice::new(buf);
+        }
+        let slices = &slices[..bufs.len().min(MAX_IO_SLICES)];
+        match self.inner.write_vectored(slices) {
+            Ok(0) if slices.iter().any(|slice| !slice.is_empty()) => {
+                Err(std::io::ErrorKind::WriteZero.into())
+            }
+            Ok(n) => Ok(n),
+            Err(e) => Err(e),
+        }
+    }
+
     fn write_fmt(
         &mut self,
         fmt: core::fmt::Arguments<'_>,
@@ -168,6 +189,21 @@ impl<T: embedded_io::Write + ?Sized> std::io::Write for ToStd<T> {
         self.inner.write_all(buf).map_err(to_std_error)
     }
 
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> Result<usize, std::io::Error> {
+        let mut slices: [&[u8]; MAX_IO_SLICES] = [&[]; MAX_IO_SLICES];
+        for (slice, buf) in slices.iter_mut().zip(bufs) {
+            *slice = buf;
+        }
+        match self
+            .inner
+            .write_vectored(&slices[..bufs.len().min(MAX_IO_SLICES)])
+        {
+            Ok(n) => Ok(n),
+            Err(e) if e.kind() == embedded_io::ErrorKind::WriteZero => Ok(0),
+            Err(e) => Err(to_std_error(e)),
+        }
+    }
+
     fn write_fmt(&mut self, fmt: core::fmt::Arguments<'_>) -> Result<(), std::io::Error> {
         match self.inner.write_fmt(fmt) {
             Ok(()) => Ok(()),
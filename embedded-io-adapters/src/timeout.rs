@@ -0,0 +1,285 @@
+//! Timeout adapter for `embedded-io-async` traits, using an `embedded-hal-async` delay.
+
+use core::fmt;
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+
+use embedded_hal_async::delay::DelayNs;
+
+/// Error returned by [`WithTimeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutError<E> {
+    /// The operation did not complete before the timeout expired.
+    TimedOut,
+    /// The inner operation failed.
+    Other(E),
+}
+
+impl<E: embedded_io::Error> embedded_io::Error for TimeoutError<E> {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self {
+            Self::TimedOut => embedded_io::ErrorKind::TimedOut,
+            Self::Other(e) => e.kind(),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for TimeoutError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TimedOut => write!(f, "Operation timed out"),
+            Self::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for TimeoutError<E> {}
+
+/// Adapter bounding the duration of each operation of an async I/O object.
+///
+/// Each call to `read`, `write` or `flush` is raced against [`DelayNs::delay_us`]: if the delay
+/// completes first, the operation is cancelled and [`TimeoutError::TimedOut`] is returned.
+/// The timeout applies to each call as a whole, not to each byte. Cancelling an operation drops
+/// its future, so check the cancel-safety of the inner type: for example, a cancelled `write`
+/// may have sent part of the data.
+///
+/// A `read` returning `Ok(0)` (EOF) before the timeout is returned as is.
+#[derive(Clone)]
+pub struct WithTimeout<T, D> {
+    inner: T,
+    delay: D,
+    timeout_us: u32,
+}
+
+impl<T, D> WithTimeout<T, D> {
+    /// Create a new adapter, with a timeout of `timeout_us` microseconds per operation.
+    pub fn new(inner: T, delay: D, timeout_us: u32) -> Self {
+        Self {
+            inner,
+            delay,
+            timeout_us,
+        }
+    }
+
+    /// Consume the adapter, returning the inner object and the delay.
+    pub fn into_inner(self) -> (T, D) {
+        (self.inner, self.delay)
+    }
+
+    /// Borrow the inner object.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Mutably borrow the inner object.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns the timeout of each operation, in microseconds.
+    pub fn timeout_us(&self) -> u32 {
+        self.timeout_us
+    }
+
+    /// Set the timeout of each operation, in microseconds.
+    pub fn set_timeout_us(&mut self, timeout_us: u32) {
+        self.timeout_us = timeout_us;
+    }
+}
+
+/// Run `operation`, unless `delay` completes first.
+async fn with_timeout<R, E, D: DelayNs>(
+    delay: &mut D,
+    timeout_us: u32,
+    operation: impl Future<Output = Result<R, E>>,
+) -> Result<R, TimeoutError<E>> {
+    let mut operation = pin!(operation);
+    let mut timeout = pin!(delay.delay_us(timeout_us));
+    poll_fn(|cx| {
+        // Poll the operation first, so that it wins if both are ready.
+        if let Poll::Ready(result) = operation.as_mut().poll(cx) {
+            return Poll::Ready(result.map_err(TimeoutError::Other));
+        }
+        match timeout.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(TimeoutError::TimedOut)),
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .await
+}
+
+impl<T: embedded_io::ErrorType, D> embedded_io::ErrorType for WithTimeout<T, D> {
+    type Error = TimeoutError<T::Error>;
+}
+
+impl<T: embedded_io_async::Read, D: DelayNs> embedded_io_async::Read for WithTimeout<T, D> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        with_timeout(&mut self.delay, self.timeout_us, self.inner.read(buf)).await
+    }
+}
+
+impl<T: embedded_io_async::Write, D: DelayNs> embedded_io_async::Write for WithTimeout<T, D> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        with_timeout(&mut self.delay, self.timeout_us, self.inner.write(buf)).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        with_timeout(&mut self.delay, self.timeout_us, self.inner.flush()).await
+    }
+}
+
+impl<T: embedded_io::ReadReady, D> embedded_io::ReadReady for WithTimeout<T, D> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        self.inner.read_ready().map_err(TimeoutError::Other)
+    }
+}
+
+impl<T: embedded_io::WriteReady, D> embedded_io::WriteReady for WithTimeout<T, D> {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        self.inner.write_ready().map_err(TimeoutError::Other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use core::task::{Context, RawWaker, RawWakerVTable, Waker};
+    use embedded_io::ErrorKind;
+    use embedded_io_async::{Read, Write};
+    use std::vec::Vec;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+        const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+        // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+        let waker = unsafe { Waker::from_raw(RAW) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Yield to the executor `polls` times.
+    async fn pending(polls: usize) {
+        let mut remaining = polls;
+        poll_fn(|_| {
+            if remaining == 0 {
+                Poll::Ready(())
+            } else {
+                remaining -= 1;
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Delay completing after one poll per microsecond.
+    struct PolledDelay {
+        requested: Vec<u32>,
+    }
+
+    impl DelayNs for PolledDelay {
+        async fn delay_ns(&mut self, _ns: u32) {
+            unreachable!()
+        }
+
+        async fn delay_us(&mut self, us: u32) {
+            self.requested.push(us);
+            pending(us as usize).await;
+        }
+    }
+
+    /// Reader following a script of steps: `Some((polls, data))` returns `data` after
+    /// yielding `polls` times, and `None` stalls forever.
+    struct ScriptedReader {
+        script: &'static [Option<(usize, &'static [u8])>],
+    }
+
+    impl embedded_io::ErrorType for ScriptedReader {
+        type Error = ErrorKind;
+    }
+
+    impl Read for ScriptedReader {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+            let (step, rest) = self.script.split_first().ok_or(ErrorKind::Other)?;
+            self.script = rest;
+            match step {
+                Some((polls, data)) => {
+                    pending(*polls).await;
+                    buf[..data.len()].copy_from_slice(data);
+                    Ok(data.len())
+                }
+                None => core::future::pending().await,
+            }
+        }
+    }
+
+    impl Write for ScriptedReader {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+            Ok(buf.len())
+        }
+
+        async fn flush(&mut self) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    fn reader(
+        script: &'static [Option<(usize, &'static [u8])>],
+    ) -> WithTimeout<ScriptedReader, PolledDelay> {
+        let delay = PolledDelay {
+            requested: Vec::new(),
+        };
+        WithTimeout::new(ScriptedReader { script }, delay, 10)
+    }
+
+    #[test]
+    fn read_before_timeout() {
+        let mut reader = reader(&[Some((9, b"ok"))]);
+        let mut buf = [0; 4];
+        assert_eq!(block_on(reader.read(&mut buf)), Ok(2));
+        assert_eq!(&buf[..2], b"ok");
+    }
+
+    #[test]
+    fn stalled_read_times_out() {
+        let mut reader = reader(&[Some((0, b"a")), None, Some((11, b"b")), Some((0, b"c"))]);
+        let mut buf = [0; 1];
+        assert_eq!(block_on(reader.read(&mut buf)), Ok(1));
+        assert_eq!(block_on(reader.read(&mut buf)), Err(TimeoutError::TimedOut));
+        assert_eq!(block_on(reader.read(&mut buf)), Err(TimeoutError::TimedOut));
+        // The timeout applies per call: a later read can succeed.
+        assert_eq!(block_on(reader.read(&mut buf)), Ok(1));
+        assert_eq!(buf, *b"c");
+        let (_, delay) = reader.into_inner();
+        // The delay only starts when the operation is not immediately ready.
+        assert_eq!(delay.requested, [10, 10]);
+        assert_eq!(
+            embedded_io::Error::kind(&TimeoutError::<ErrorKind>::TimedOut),
+            ErrorKind::TimedOut
+        );
+    }
+
+    #[test]
+    fn eof_is_not_a_timeout() {
+        let mut reader = reader(&[Some((3, b""))]);
+        assert_eq!(block_on(reader.read(&mut [0; 4])), Ok(0));
+    }
+
+    #[test]
+    fn inner_errors_are_wrapped() {
+        let mut reader = reader(&[]);
+        assert_eq!(
+            block_on(reader.read(&mut [0; 4])),
+            Err(TimeoutError::Other(ErrorKind::Other))
+        );
+        assert_eq!(block_on(reader.write(b"abc")), Ok(3));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{BufRead, ErrorType, Read, ReadReady};
+
+/// Adds buffering to any async [`Read`]er, using a buffer provided by the user.
+///
+/// This is the async version of [`embedded_io::BufReader`]. Reads larger than the buffer bypass
+/// it when it is empty, going directly to the inner reader.
+pub struct BufReader<'a, R> {
+    inner: R,
+    buf: &'a mut [u8],
+    pos: usize,
+    filled: usize,
+}
+
+impl<'a, R> BufReader<'a, R> {
+    /// Creates a new `BufReader`, buffering `inner` with `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is empty.
+    #[inline]
+    pub fn new(inner: R, buf: &'a mut [u8]) -> Self {
+        assert!(
+            !buf.is_empty(),
+            "the buffer of a BufReader must not be empty"
+        );
+        Self {
+            inner,
+            buf,
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Gets a reference to the inner reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the inner reader.
+    ///
+    /// Reading directly from the inner reader skips the data which is already buffered.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the buffered data which was not read yet.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Consumes this `BufReader`, returning the inner reader and the buffer.
+    ///
+    /// Any buffered data which was not read yet is lost.
+    #[inline]
+    pub fn into_inner(self) -> (R, &'a mut [u8]) {
+        (self.inner, self.buf)
+    }
+}
+
+impl<R: Read> BufReader<'_, R> {
+    /// Reads bytes into `out` until `delimiter` or EOF is reached, or `out` is full.
+    ///
+    /// The delimiter is included in `out`. Returns the number of bytes read: if the last byte read is not the
+    /// delimiter, either EOF was reached or `out` is full. Returns 0 at EOF.
+    pub async fn read_until(&mut self, delimiter: u8, out: &mut [u8]) -> Result<usize, R::Error> {
+        let mut len = 0;
+        while len < out.len() {
+            let available = self.fill_buf().await?;
+            if available.is_empty() {
+                break;
+            }
+            let max = available.len().min(out.len() - len);
+            let (used, found) = match available[..max].iter().position(|b| *b == delimiter) {
+                Some(i) => (i + 1, true),
+                None => (max, false),
+            };
+            out[len..len + used].copy_from_slice(&available[..used]);
+            self.consume(used);
+            len += used;
+            if found {
+                break;
+            }
+        }
+        Ok(len)
+    }
+
+    /// Reads a line into `out`, including its terminating `\n`.
+    ///
+    /// This is [`read_until`](BufReader::read_until) with `\n` as delimiter.
+    #[inline]
+    pub async fn read_line(&mut self, out: &mut [u8]) -> Result<usize, R::Error> {
+        self.read_until(b'\n', out).await
+    }
+}
+
+impl<R: ErrorType> ErrorType for BufReader<'_, R> {
+    type Error = R::Error;
+}
+
+impl<R: Read> Read for BufReader<'_, R> {
+    async fn read(&mut self, out: &mut [u8]) -> Result<usize, Self::Error> {
+        // Bypass the buffer for large reads.
+        if self.pos == self.filled && out.len() >= self.buf.len() {
+            return self.inner.read(out).await;
+        }
+        let available = self.fill_buf().await?;
+        let amt = available.len().min(out.len());
+        out[..amt].copy_from_slice(&available[..amt]);
+        self.consume(amt);
+        Ok(amt)
+    }
+}
+
+impl<R: Read> BufRead for BufReader<'_, R> {
+    async fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if self.pos == self.filled {
+            self.filled = self.inner.read(self.buf).await?;
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..self.filled])
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.filled);
+    }
+}
+
+impl<R: ReadReady> ReadReady for BufReader<'_, R> {
+    #[inline]
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.pos < self.filled || self.inner.read_ready()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use core::future::Future;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+        const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+        // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+        let waker = unsafe { Waker::from_raw(RAW) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Reader returning at most `chunk` bytes per read.
+    struct Chunked {
+        data: &'static [u8],
+        chunk: usize,
+    }
+
+    impl ErrorType for Chunked {
+        type Error = Infallible;
+    }
+
+    impl Read for Chunked {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let len = buf.len().min(self.chunk);
+            self.data.read(&mut buf[..len]).await
+        }
+    }
+
+    #[test]
+    fn read_lines() {
+        let mut scratch = [0; 4];
+        let inner = Chunked {
+            data: b"hello\nworld",
+            chunk: 3,
+        };
+        let mut reader = BufReader::new(inner, &mut scratch);
+        let mut line = [0; 16];
+        assert_eq!(block_on(reader.read_line(&mut line)), Ok(6));
+        assert_eq!(&line[..6], b"hello\n");
+        assert_eq!(block_on(reader.read_line(&mut line)), Ok(5));
+        assert_eq!(&line[..5], b"world");
+        assert_eq!(block_on(reader.read_line(&mut line)), Ok(0));
+    }
+}
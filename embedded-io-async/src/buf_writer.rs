@@ -0,0 +1,177 @@
+use crate::{ErrorType, Write};
+
+/// Adds buffering to any async [`Write`]r, using a buffer provided by the user.
+///
+/// This is the async version of [`embedded_io::BufWriter`]. Small writes are gathered in the
+/// buffer, which is written to the inner writer when it is full, and on [`flush`](Write::flush).
+/// Writes which don't fit in the whole buffer bypass it, going directly to the inner writer.
+///
+/// Dropping a `BufWriter` does **not** flush it: the buffered data is lost.
+/// Call [`flush`](Write::flush) or [`into_inner`](BufWriter::into_inner) first.
+pub struct BufWriter<'a, W: Write> {
+    inner: W,
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a, W: Write> BufWriter<'a, W> {
+    /// Creates a new `BufWriter`, buffering `inner` with `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is empty.
+    #[inline]
+    pub fn new(inner: W, buf: &'a mut [u8]) -> Self {
+        assert!(
+            !buf.is_empty(),
+            "the buffer of a BufWriter must not be empty"
+        );
+        Self { inner, buf, len: 0 }
+    }
+
+    /// Gets a reference to the inner writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the inner writer.
+    ///
+    /// Writing directly to the inner writer bypasses the data which is still buffered.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the buffered data which was not written to the inner writer yet.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Writes the buffered data, then returns the inner writer.
+    ///
+    /// If writing the buffered data fails, the error is returned along with the `BufWriter`
+    /// itself, so no data is lost and the operation can be retried.
+    pub async fn into_inner(mut self) -> Result<W, (Self, W::Error)> {
+        match self.flush_buf().await {
+            Ok(()) => Ok(self.inner),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Writes all the buffered data to the inner writer.
+    ///
+    /// On error, the data which was not written yet stays in the buffer. If the future is dropped
+    /// before completion, the data which was already written is removed from the buffer.
+    async fn flush_buf(&mut self) -> Result<(), W::Error> {
+        while self.len > 0 {
+            match self.inner.write(&self.buf[..self.len]).await {
+                Ok(0) => panic!("write() returned Ok(0)"),
+                Ok(n) => {
+                    self.buf.copy_within(n..self.len, 0);
+                    self.len -= n;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> ErrorType for BufWriter<'_, W> {
+    type Error = W::Error;
+}
+
+impl<W: Write> Write for BufWriter<'_, W> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if self.len + buf.len() > self.buf.len() {
+            self.flush_buf().await?;
+        }
+        // Bypass the buffer for large writes.
+        if buf.len() >= self.buf.len() {
+            return self.inner.write(buf).await;
+        }
+        self.buf[self.len..self.len + buf.len()].copy_from_slice(buf);
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flush_buf().await?;
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+    use core::future::Future;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+        const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+        // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+        let waker = unsafe { Waker::from_raw(RAW) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Writer accepting at most `chunk` bytes per write, and failing once after `fail_at` successful writes.
+    struct Sink {
+        data: [u8; 32],
+        len: usize,
+        chunk: usize,
+        writes: usize,
+        fail_at: Option<usize>,
+    }
+
+    impl ErrorType for Sink {
+        type Error = ErrorKind;
+    }
+
+    impl Write for Sink {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+            if self.fail_at == Some(self.writes) {
+                self.fail_at = None;
+                return Err(ErrorKind::Other);
+            }
+            self.writes += 1;
+            let n = buf.len().min(self.chunk);
+            self.data[self.len..self.len + n].copy_from_slice(&buf[..n]);
+            self.len += n;
+            Ok(n)
+        }
+
+        async fn flush(&mut self) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn short_writes_and_errors_during_flush() {
+        let sink = Sink {
+            data: [0; 32],
+            len: 0,
+            chunk: 2,
+            writes: 0,
+            fail_at: Some(1),
+        };
+        let mut scratch = [0; 8];
+        let mut writer = BufWriter::new(sink, &mut scratch);
+        block_on(writer.write_all(b"abcdef")).unwrap();
+        assert_eq!(block_on(writer.flush()), Err(ErrorKind::Other));
+        assert_eq!(writer.buffer(), b"cdef");
+        let sink = block_on(writer.into_inner()).ok().unwrap();
+        assert_eq!(&sink.data[..sink.len], b"abcdef");
+    }
+}
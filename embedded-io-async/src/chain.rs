@@ -0,0 +1,78 @@
+use crate::{BufRead, ErrorType, Read};
+
+/// Async reader adapter which chains two readers.
+///
+/// This struct is created by [`Read::chain`]. It is the async version of [`embedded_io::Chain`].
+/// Once the first reader reaches EOF, it is not used anymore, and all reads go to the second reader.
+#[derive(Debug)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+    done_first: bool,
+}
+
+impl<A, B> Chain<A, B> {
+    pub(crate) fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            done_first: false,
+        }
+    }
+
+    /// Gets references to the underlying readers.
+    #[inline]
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    /// Gets mutable references to the underlying readers.
+    #[inline]
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.first, &mut self.second)
+    }
+
+    /// Consumes this adapter, returning the underlying readers.
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: ErrorType, B> ErrorType for Chain<A, B> {
+    type Error = A::Error;
+}
+
+impl<A: Read, B: Read<Error = A::Error>> Read for Chain<A, B> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if !self.done_first {
+            match self.first.read(buf).await? {
+                0 if !buf.is_empty() => self.done_first = true,
+                n => return Ok(n),
+            }
+        }
+        self.second.read(buf).await
+    }
+}
+
+impl<A: BufRead, B: BufRead<Error = A::Error>> BufRead for Chain<A, B> {
+    async fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if !self.done_first {
+            if self.first.fill_buf().await?.is_empty() {
+                self.done_first = true;
+            } else {
+                // The data is already buffered: this does not read from the first reader again.
+                return self.first.fill_buf().await;
+            }
+        }
+        self.second.fill_buf().await
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.done_first {
+            self.second.consume(amt);
+        } else {
+            self.first.consume(amt);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{BufRead, CopyError, Read, Write};
+
+/// Copy all the bytes of `reader` into `writer`, until EOF, returning the number of bytes copied.
+///
+/// This is the `async` equivalent of [`embedded_io::copy`]. The data goes through `buf`: each
+/// [`Read::read`] into it is followed by as many [`Write::write`] calls as needed to write everything
+/// read. The writer is not flushed.
+///
+/// # Panics
+///
+/// Panics if `buf` is empty.
+pub async fn copy<R: Read + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    buf: &mut [u8],
+) -> Result<u64, CopyError<R::Error, W::Error>> {
+    assert!(!buf.is_empty(), "copy buffer must not be empty");
+    let mut copied = 0;
+    loop {
+        let len = reader.read(buf).await.map_err(CopyError::Read)?;
+        if len == 0 {
+            return Ok(copied);
+        }
+        let mut data = &buf[..len];
+        while !data.is_empty() {
+            match writer.write(data).await.map_err(CopyError::Write)? {
+                0 => return Err(CopyError::WriteZero),
+                n => data = &data[n..],
+            }
+        }
+        copied += len as u64;
+    }
+}
+
+/// Copy all the bytes of `reader` into `writer`, until EOF, returning the number of bytes copied.
+///
+/// This is the `async` equivalent of [`embedded_io::copy_buf`]. The data is written straight from
+/// the buffer of `reader`, with [`BufRead::fill_buf`] and [`BufRead::consume`]. The writer is not
+/// flushed.
+pub async fn copy_buf<R: BufRead + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<u64, CopyError<R::Error, W::Error>> {
+    let mut copied = 0;
+    loop {
+        let data = reader.fill_buf().await.map_err(CopyError::Read)?;
+        if data.is_empty() {
+            return Ok(copied);
+        }
+        let len = writer.write(data).await.map_err(CopyError::Write)?;
+        if len == 0 {
+            return Err(CopyError::WriteZero);
+        }
+        reader.consume(len);
+        copied += len as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, ErrorType};
+    use core::convert::Infallible;
+    use core::future::Future;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+        const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+        // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+        let waker = unsafe { Waker::from_raw(RAW) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Reader returning at most `max` bytes per read, and failing at the end of `data` if `fail`.
+    struct ShortReader<'a> {
+        data: &'a [u8],
+        max: usize,
+        fail: bool,
+    }
+
+    impl ErrorType for ShortReader<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read for ShortReader<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+            let data = self.fill_buf().await?;
+            let len = buf.len().min(data.len());
+            buf[..len].copy_from_slice(&data[..len]);
+            self.consume(len);
+            Ok(len)
+        }
+    }
+
+    impl BufRead for ShortReader<'_> {
+        async fn fill_buf(&mut self) -> Result<&[u8], ErrorKind> {
+            if self.data.is_empty() && self.fail {
+                return Err(ErrorKind::BrokenPipe);
+            }
+            Ok(&self.data[..self.max.min(self.data.len())])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.data = &self.data[amt..];
+        }
+    }
+
+    /// Writer accepting at most `max` bytes per write, then returning `Ok(0)` once `out` is full.
+    struct ShortWriter<'a> {
+        out: &'a mut [u8],
+        len: usize,
+        max: usize,
+    }
+
+    impl ErrorType for ShortWriter<'_> {
+        type Error = Infallible;
+    }
+
+    impl Write for ShortWriter<'_> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            let len = buf.len().min(self.max).min(self.out.len() - self.len);
+            self.out[self.len..][..len].copy_from_slice(&buf[..len]);
+            self.len += len;
+            Ok(len)
+        }
+
+        async fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    const DATA: &[u8] = b"the quick brown fox";
+
+    #[test]
+    fn copy_with_short_reads_and_writes() {
+        let mut reader = ShortReader {
+            data: DATA,
+            max: 3,
+            fail: false,
+        };
+        let mut out = [0; 32];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 2,
+        };
+        let mut buf = [0; 5];
+        assert_eq!(block_on(copy(&mut reader, &mut writer, &mut buf)), Ok(19));
+        assert_eq!(&writer.out[..writer.len], DATA);
+    }
+
+    #[test]
+    fn copy_buf_with_short_reads_and_writes() {
+        let mut reader = ShortReader {
+            data: DATA,
+            max: 4,
+            fail: false,
+        };
+        let mut out = [0; 32];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 3,
+        };
+        assert_eq!(block_on(copy_buf(&mut reader, &mut writer)), Ok(19));
+        assert_eq!(&writer.out[..writer.len], DATA);
+    }
+
+    #[test]
+    fn errors_tell_the_side() {
+        let mut out = [0; 8];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 8,
+        };
+        let mut buf = [0; 4];
+        let result = block_on(copy(&mut &DATA[..], &mut writer, &mut buf));
+        assert_eq!(result, Err(CopyError::WriteZero));
+        assert_eq!(writer.out, b"the quic");
+
+        let mut reader = ShortReader {
+            data: b"ab",
+            max: 8,
+            fail: true,
+        };
+        let mut out = [0; 8];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 8,
+        };
+        let result = block_on(copy_buf(&mut reader, &mut writer));
+        assert_eq!(result, Err(CopyError::Read(ErrorKind::BrokenPipe)));
+        assert_eq!(&writer.out[..writer.len], b"ab");
+    }
+}
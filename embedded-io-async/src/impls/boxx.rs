@@ -42,6 +42,21 @@ impl<T: ?Sized + Write> Write for Box<T> {
         T::write_all(self, buf).await
     }
 
+    #[inline]
+    async fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        T::write_vectored(self, bufs).await
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        T::is_write_vectored(self)
+    }
+
+    #[inline]
+    async fn write_all_vectored(&mut self, bufs: &mut [&[u8]]) -> Result<(), Self::Error> {
+        T::write_all_vectored(self, bufs).await
+    }
+
     #[inline]
     async fn flush(&mut self) -> Result<(), Self::Error> {
         T::flush(self).await
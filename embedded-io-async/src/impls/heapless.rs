@@ -0,0 +1,43 @@
+use heapless::{String, Vec};
+
+use crate::Write;
+
+/// Write is implemented for `heapless::Vec<u8, N>` by appending to the vector, like the
+/// [blocking implementation](embedded_io::Write), and is always ready.
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> Write for Vec<u8, N> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        embedded_io::Write::write(self, buf)
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    #[inline]
+    async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        embedded_io::Write::write_all(self, buf)
+    }
+}
+
+/// Write is implemented for `heapless::String<N>` by appending to the string, like the
+/// [blocking implementation](embedded_io::Write), and is always ready.
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> Write for String<N> {
+    #[inline]
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        embedded_io::Write::write(self, buf)
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    #[inline]
+    async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        embedded_io::Write::write_all(self, buf)
+    }
+}
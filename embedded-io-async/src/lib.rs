@@ -7,10 +7,24 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+use core::fmt;
+
+mod buf_reader;
+mod buf_writer;
+mod chain;
+mod copy;
 mod impls;
+mod take;
+
+pub use buf_reader::BufReader;
+pub use buf_writer::BufWriter;
+pub use chain::Chain;
+pub use copy::{copy, copy_buf};
+pub use take::Take;
 
 pub use embedded_io::{
-    Error, ErrorKind, ErrorType, ReadExactError, ReadReady, SeekFrom, WriteReady,
+    CopyError, Error, ErrorKind, ErrorType, ExtendBuf, ReadExactError, ReadReady, ReadToEndError,
+    SeekFrom, WriteFmtError, WriteReady,
 };
 
 /// Async reader.
@@ -77,6 +91,67 @@ pub trait Read: ErrorType {
             Err(ReadExactError::UnexpectedEof)
         }
     }
+
+    /// Read all the bytes until EOF, appending them to `buf`, and return how many were read.
+    ///
+    /// This is the `async` equivalent of [`embedded_io::Read::read_to_end`]. If `buf` becomes full
+    /// before EOF, [`ReadToEndError::BufferFull`] is returned with the number of bytes stored. A byte
+    /// is read to tell EOF from more data once `buf` is full: that byte is lost.
+    ///
+    /// This function is not side-effect-free on cancel (AKA "cancel-safe"), i.e. if you cancel (drop) a returned
+    /// future that hasn't completed yet, some bytes might have already been read, which will get lost.
+    async fn read_to_end(
+        &mut self,
+        buf: &mut impl ExtendBuf,
+    ) -> Result<usize, ReadToEndError<Self::Error>> {
+        let start = buf.len();
+        loop {
+            let len = buf.len();
+            // Grow by small chunks, a fixed-capacity buffer may zero the added bytes.
+            let spare = buf.grow(64);
+            if spare.is_empty() {
+                return match self.read(&mut [0]).await {
+                    Ok(0) => Ok(len - start),
+                    Ok(_) => Err(ReadToEndError::BufferFull {
+                        stored: len - start,
+                    }),
+                    Err(e) => Err(ReadToEndError::Other(e)),
+                };
+            }
+            match self.read(spare).await {
+                Ok(0) => {
+                    buf.truncate(len);
+                    return Ok(len - start);
+                }
+                Ok(n) => buf.truncate(len + n),
+                Err(e) => {
+                    buf.truncate(len);
+                    return Err(ReadToEndError::Other(e));
+                }
+            }
+        }
+    }
+
+    /// Creates an adapter which reads at most `limit` bytes from this reader.
+    ///
+    /// This is the `embedded-io-async` equivalent of [`std::io::Read::take`].
+    fn take(self, limit: u64) -> Take<Self>
+    where
+        Self: Sized,
+    {
+        Take::new(self, limit)
+    }
+
+    /// Creates an adapter which reads from this reader until EOF, then from `next`.
+    ///
+    /// This is the `embedded-io-async` equivalent of [`std::io::Read::chain`]. Both readers must
+    /// have the same error type.
+    fn chain<R: Read<Error = Self::Error>>(self, next: R) -> Chain<Self, R>
+    where
+        Self: Sized,
+    {
+        Chain::new(self, next)
+    }
 }
 
 /// Async buffered reader.
@@ -94,6 +169,38 @@ pub trait BufRead: Read {
 
     /// Tell this buffer that `amt` bytes have been consumed from the buffer, so they should no longer be returned in calls to `fill_buf`.
     fn consume(&mut self, amt: usize);
+
+    /// Read all the bytes until the `delim` byte or EOF, appending them to `buf`, and return how many were read.
+    ///
+    /// This is the `async` equivalent of [`embedded_io::BufRead::read_until`]. The delimiter is appended
+    /// to `buf`, if found. If `buf` becomes full before the delimiter, [`ReadToEndError::BufferFull`] is
+    /// returned with the number of bytes stored. No byte is lost: the next bytes remain to be read.
+    async fn read_until(
+        &mut self,
+        delim: u8,
+        buf: &mut impl ExtendBuf,
+    ) -> Result<usize, ReadToEndError<Self::Error>> {
+        let mut read = 0;
+        loop {
+            let available = self.fill_buf().await?;
+            if available.is_empty() {
+                return Ok(read);
+            }
+            let (found, used) = match available.iter().position(|&b| b == delim) {
+                Some(i) => (true, i + 1),
+                None => (false, available.len()),
+            };
+            let stored = buf.extend_from_slice(&available[..used]);
+            self.consume(stored);
+            read += stored;
+            if stored < used {
+                return Err(ReadToEndError::BufferFull { stored: read });
+            }
+            if found {
+                return Ok(read);
+            }
+        }
+    }
 }
 
 /// Async writer.
@@ -147,6 +254,99 @@ pub trait Write: ErrorType {
         }
         Ok(())
     }
+
+    /// Write the concatenation of several buffers into this writer, returning how many bytes were written.
+    ///
+    /// This behaves like [`Write::write`] on the concatenation of `bufs`: the same waiting rules apply,
+    /// and `Ok(0)` must not be returned unless all buffers are empty. Writers which can send several
+    /// buffers at once, e.g. with a DMA descriptor chain, should override this method, and
+    /// [`Write::is_write_vectored`].
+    ///
+    /// The default implementation writes the first non-empty buffer.
+    async fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        let buf = bufs
+            .iter()
+            .find(|buf| !buf.is_empty())
+            .map_or(&[][..], |buf| buf);
+        self.write(buf).await
+    }
+
+    /// Returns `true` if this writer has an efficient [`Write::write_vectored`] implementation.
+    ///
+    /// Callers can use this hint to decide between a vectored write and copying the buffers into
+    /// a single one. The default implementation returns `false`.
+    fn is_write_vectored(&self) -> bool {
+        false
+    }
+
+    /// Write the entire concatenation of several buffers into this writer.
+    ///
+    /// This function calls `write_vectored()` in a loop until all the buffers have been written,
+    /// waiting if needed. The slices in `bufs` are updated to track the progress, so their contents
+    /// are unspecified when this function returns.
+    ///
+    /// This function is not side-effect-free on cancel (AKA "cancel-safe"), i.e. if you cancel (drop) a returned
+    /// future that hasn't completed yet, some bytes might have already been written.
+    ///
+    /// This function will panic if `write_vectored()` returns `Ok(0)`.
+    async fn write_all_vectored(&mut self, mut bufs: &mut [&[u8]]) -> Result<(), Self::Error> {
+        advance_slices(&mut bufs, 0);
+        while !bufs.is_empty() {
+            match self.write_vectored(bufs).await {
+                Ok(0) => panic!("write_vectored() returned Ok(0)"),
+                Ok(n) => advance_slices(&mut bufs, n),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a formatted string into this writer, using `buf` as intermediate storage.
+    ///
+    /// [`core::fmt::Write`] cannot wait, so the string is formatted into `buf`, which is then written
+    /// with `write_all()`. If the string does not fit, it is formatted again for each chunk of
+    /// `buf.len()` bytes, skipping the chunks already written: this needs the [`Display`](fmt::Display)
+    /// implementations to always produce the same output, which is the case of the `core` types.
+    ///
+    /// ```rust
+    /// # async fn example(mut uart: impl embedded_io_async::Write, t: i32) {
+    /// let mut buf = [0; 32];
+    /// let _ = uart.write_fmt_buffered(&mut buf, format_args!("temp={t}\r\n")).await;
+    /// # }
+    /// ```
+    ///
+    /// This function is not side-effect-free on cancel (AKA "cancel-safe"), i.e. if you cancel (drop) a returned
+    /// future that hasn't completed yet, some bytes might have already been written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is empty.
+    async fn write_fmt_buffered(
+        &mut self,
+        buf: &mut [u8],
+        fmt: fmt::Arguments<'_>,
+    ) -> Result<(), WriteFmtError<Self::Error>> {
+        assert!(!buf.is_empty(), "the formatting buffer must not be empty");
+        let mut skip = 0;
+        loop {
+            let mut chunk = FmtChunk {
+                buf: &mut *buf,
+                skip,
+                len: 0,
+                full: false,
+            };
+            let result = fmt::write(&mut chunk, fmt);
+            let (len, full) = (chunk.len, chunk.full);
+            if result.is_err() && !full {
+                return Err(WriteFmtError::FmtError);
+            }
+            self.write_all(&buf[..len]).await?;
+            if !full {
+                return Ok(());
+            }
+            skip += len;
+        }
+    }
 }
 
 /// Async seek within streams.
@@ -207,6 +407,30 @@ impl<T: ?Sized + Write> Write for &mut T {
     async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
         T::write_all(self, buf).await
     }
+
+    #[inline]
+    async fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        T::write_vectored(self, bufs).await
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        T::is_write_vectored(self)
+    }
+
+    #[inline]
+    async fn write_all_vectored(&mut self, bufs: &mut [&[u8]]) -> Result<(), Self::Error> {
+        T::write_all_vectored(self, bufs).await
+    }
+
+    #[inline]
+    async fn write_fmt_buffered(
+        &mut self,
+        buf: &mut [u8],
+        fmt: fmt::Arguments<'_>,
+    ) -> Result<(), WriteFmtError<Self::Error>> {
+        T::write_fmt_buffered(self, buf, fmt).await
+    }
 }
 
 impl<T: ?Sized + Seek> Seek for &mut T {
@@ -225,3 +449,132 @@ impl<T: ?Sized + Seek> Seek for &mut T {
         T::stream_position(self).await
     }
 }
+
+/// Remove the first `n` bytes from `bufs`, dropping the buffers which are entirely written,
+/// as well as the empty buffers at the front.
+fn advance_slices(bufs: &mut &mut [&[u8]], mut n: usize) {
+    let mut written = 0;
+    for buf in bufs.iter() {
+        if buf.len() > n {
+            break;
+        }
+        n -= buf.len();
+        written += 1;
+    }
+    *bufs = &mut core::mem::take(bufs)[written..];
+    match bufs.first_mut() {
+        Some(first) => *first = &first[n..],
+        None => assert!(n == 0, "advancing past the end of the buffers"),
+    }
+}
+
+/// [`fmt::Write`] implementation capturing a chunk of a formatted string, after skipping `skip` bytes.
+struct FmtChunk<'a> {
+    buf: &'a mut [u8],
+    skip: usize,
+    len: usize,
+    /// The chunk is full, and formatting was stopped.
+    full: bool,
+}
+
+impl fmt::Write for FmtChunk<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let skipped = self.skip.min(s.len());
+        self.skip -= skipped;
+        let bytes = &s.as_bytes()[skipped..];
+        let n = bytes.len().min(self.buf.len() - self.len);
+        self.buf[self.len..][..n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+        if n < bytes.len() {
+            self.full = true;
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::future::Future;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+        const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+        // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+        let waker = unsafe { Waker::from_raw(RAW) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Display implementation always failing.
+    struct Broken;
+
+    impl fmt::Display for Broken {
+        fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[test]
+    fn write_fmt_buffered_in_chunks() {
+        let mut out = [0; 32];
+        let mut writer = &mut out[..];
+        let mut buf = [0; 4];
+        let t = 21;
+        block_on(writer.write_fmt_buffered(&mut buf, format_args!("temp={t}, hum={}%\r\n", 40)))
+            .unwrap();
+        let len = 32 - writer.len();
+        assert_eq!(&out[..len], b"temp=21, hum=40%\r\n");
+    }
+
+    #[test]
+    fn write_fmt_buffered_reports_errors() {
+        let mut out = [0; 8];
+        let mut buf = [0; 4];
+        assert_eq!(
+            block_on((&mut out[..]).write_fmt_buffered(&mut buf, format_args!("too long to fit"))),
+            Err(WriteFmtError::Other(embedded_io::SliceWriteError::Full))
+        );
+        assert_eq!(
+            block_on((&mut out[..]).write_fmt_buffered(&mut buf, format_args!("{Broken}"))),
+            Err(WriteFmtError::FmtError)
+        );
+    }
+
+    #[test]
+    fn read_to_end_at_capacity_and_overflow() {
+        let mut buf = [0; 5];
+        let mut cursor = embedded_io::Cursor::new(&mut buf[..]);
+        assert_eq!(block_on((&b"abcde"[..]).read_to_end(&mut cursor)), Ok(5));
+
+        let mut buf = [0; 5];
+        let mut cursor = embedded_io::Cursor::new(&mut buf[..]);
+        assert_eq!(
+            block_on((&b"abcdefg"[..]).read_to_end(&mut cursor)),
+            Err(ReadToEndError::BufferFull { stored: 5 })
+        );
+        assert_eq!(&buf, b"abcde");
+    }
+
+    #[test]
+    fn read_until_delimiter_and_buffer_full() {
+        let mut reader = &b"AT\r\nOK\r\n"[..];
+        let mut buf = [0; 6];
+        let mut cursor = embedded_io::Cursor::new(&mut buf[..]);
+        assert_eq!(block_on(reader.read_until(b'\n', &mut cursor)), Ok(4));
+        assert_eq!(
+            block_on(reader.read_until(b'\n', &mut cursor)),
+            Err(ReadToEndError::BufferFull { stored: 2 })
+        );
+        assert_eq!(&buf, b"AT\r\nOK");
+        assert_eq!(reader, b"\r\n");
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{BufRead, ErrorType, Read};
+
+/// Async reader adapter which limits the bytes read from an underlying reader.
+///
+/// This struct is created by [`Read::take`]. It is the async version of [`embedded_io::Take`].
+#[derive(Debug)]
+pub struct Take<R> {
+    inner: R,
+    limit: u64,
+}
+
+impl<R> Take<R> {
+    pub(crate) fn new(inner: R, limit: u64) -> Self {
+        Self { inner, limit }
+    }
+
+    /// Returns the number of bytes that can be read before this adapter returns EOF.
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Sets the number of bytes that can be read before this adapter returns EOF.
+    #[inline]
+    pub fn set_limit(&mut self, limit: u64) {
+        self.limit = limit;
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Reading directly from the underlying reader does not change the limit.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes this adapter, returning the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Clamp `len` to the limit.
+    fn clamp(&self, len: usize) -> usize {
+        usize::try_from(self.limit).map_or(len, |limit| len.min(limit))
+    }
+}
+
+impl<R: ErrorType> ErrorType for Take<R> {
+    type Error = R::Error;
+}
+
+impl<R: Read> Read for Take<R> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if self.limit == 0 {
+            return Ok(0);
+        }
+        let max = self.clamp(buf.len());
+        let n = self.inner.read(&mut buf[..max]).await?;
+        self.limit -= n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Take<R> {
+    async fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if self.limit == 0 {
+            return Ok(&[]);
+        }
+        let limit = self.limit;
+        let buf = self.inner.fill_buf().await?;
+        let len = usize::try_from(limit).map_or(buf.len(), |limit| buf.len().min(limit));
+        Ok(&buf[..len])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = self.clamp(amt);
+        self.limit -= amt as u64;
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BufRead, BufReader, Read};
+    use core::future::Future;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+        const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+
+        // SAFETY: the vtable functions do nothing and never dereference the data pointer.
+        let waker = unsafe { Waker::from_raw(RAW) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn limit_in_middle_of_fill_buf() {
+        let mut scratch = [0; 4];
+        let mut reader = BufReader::new(&b"abcdefgh"[..], &mut scratch);
+        let mut take = (&mut reader).take(6);
+        assert_eq!(block_on(take.fill_buf()), Ok(&b"abcd"[..]));
+        take.consume(4);
+        assert_eq!(block_on(take.fill_buf()), Ok(&b"ef"[..]));
+        take.consume(8);
+        assert_eq!(take.limit(), 0);
+        assert_eq!(block_on(take.fill_buf()), Ok(&[][..]));
+        assert_eq!(reader.buffer(), b"gh");
+    }
+
+    #[test]
+    fn chain_slices() {
+        let mut chain = (&b"abc"[..]).chain(&b"defgh"[..]).take(6);
+        let mut buf = [0; 8];
+        assert_eq!(block_on(chain.read(&mut buf)), Ok(3));
+        assert_eq!(block_on(chain.read(&mut buf[3..])), Ok(3));
+        assert_eq!(block_on(chain.read(&mut buf[6..])), Ok(0));
+        assert_eq!(&buf[..6], b"abcdef");
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{BufRead, ErrorType, Read, ReadReady};
+
+/// Adds buffering to any [`Read`]er, using a buffer provided by the user.
+///
+/// This is the `embedded-io` equivalent of [`std::io::BufReader`]. It implements [`BufRead`] on top
+/// of readers that don't, reading from the inner reader in chunks of the size of the buffer.
+///
+/// Reads larger than the buffer bypass it when it is empty, going directly to the inner reader, so the data
+/// is not copied twice.
+///
+/// # Example
+///
+/// ```
+/// use embedded_io::BufReader;
+///
+/// let mut scratch = [0; 16];
+/// let mut reader = BufReader::new(&b"AT\r\nOK\r\n"[..], &mut scratch);
+/// let mut line = [0; 8];
+/// let len = reader.read_line(&mut line)?;
+/// assert_eq!(&line[..len], b"AT\r\n");
+/// # Ok::<(), core::convert::Infallible>(())
+/// ```
+pub struct BufReader<'a, R> {
+    inner: R,
+    buf: &'a mut [u8],
+    pos: usize,
+    filled: usize,
+}
+
+impl<'a, R> BufReader<'a, R> {
+    /// Creates a new `BufReader`, buffering `inner` with `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is empty.
+    #[inline]
+    pub fn new(inner: R, buf: &'a mut [u8]) -> Self {
+        assert!(
+            !buf.is_empty(),
+            "the buffer of a BufReader must not be empty"
+        );
+        Self {
+            inner,
+            buf,
+            pos: 0,
+            filled: 0,
+        }
+    }
+
+    /// Gets a reference to the inner reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the inner reader.
+    ///
+    /// Reading directly from the inner reader skips the data which is already buffered.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the buffered data which was not read yet.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.filled]
+    }
+
+    /// Consumes this `BufReader`, returning the inner reader and the buffer.
+    ///
+    /// Any buffered data which was not read yet is lost.
+    #[inline]
+    pub fn into_inner(self) -> (R, &'a mut [u8]) {
+        (self.inner, self.buf)
+    }
+}
+
+impl<R: Read> BufReader<'_, R> {
+    /// Reads bytes into `out` until `delimiter` or EOF is reached, or `out` is full.
+    ///
+    /// The delimiter is included in `out`. Returns the number of bytes read: if the last byte read is not the
+    /// delimiter, either EOF was reached or `out` is full. Returns 0 at EOF.
+    pub fn read_until(&mut self, delimiter: u8, out: &mut [u8]) -> Result<usize, R::Error> {
+        let mut len = 0;
+        while len < out.len() {
+            let available = self.fill_buf()?;
+            if available.is_empty() {
+                break;
+            }
+            let max = available.len().min(out.len() - len);
+            let (used, found) = match available[..max].iter().position(|b| *b == delimiter) {
+                Some(i) => (i + 1, true),
+                None => (max, false),
+            };
+            out[len..len + used].copy_from_slice(&available[..used]);
+            self.consume(used);
+            len += used;
+            if found {
+                break;
+            }
+        }
+        Ok(len)
+    }
+
+    /// Reads a line into `out`, including its terminating `\n`.
+    ///
+    /// This is [`read_until`](BufReader::read_until) with `\n` as delimiter.
+    #[inline]
+    pub fn read_line(&mut self, out: &mut [u8]) -> Result<usize, R::Error> {
+        self.read_until(b'\n', out)
+    }
+}
+
+impl<R: ErrorType> ErrorType for BufReader<'_, R> {
+    type Error = R::Error;
+}
+
+impl<R: Read> Read for BufReader<'_, R> {
+    fn read(&mut self, out: &mut [u8]) -> Result<usize, Self::Error> {
+        // Bypass the buffer for large reads.
+        if self.pos == self.filled && out.len() >= self.buf.len() {
+            return self.inner.read(out);
+        }
+        let available = self.fill_buf()?;
+        let amt = available.len().min(out.len());
+        out[..amt].copy_from_slice(&available[..amt]);
+        self.consume(amt);
+        Ok(amt)
+    }
+}
+
+impl<R: Read> BufRead for BufReader<'_, R> {
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if self.pos == self.filled {
+            self.filled = self.inner.read(self.buf)?;
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..self.filled])
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.filled);
+    }
+}
+
+impl<R: ReadReady> ReadReady for BufReader<'_, R> {
+    #[inline]
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.pos < self.filled || self.inner.read_ready()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+
+    /// Reader returning at most `chunk` bytes per read, and counting the reads.
+    struct Chunked {
+        data: &'static [u8],
+        chunk: usize,
+        reads: usize,
+    }
+
+    impl Chunked {
+        fn new(data: &'static [u8], chunk: usize) -> Self {
+            Self {
+                data,
+                chunk,
+                reads: 0,
+            }
+        }
+    }
+
+    impl ErrorType for Chunked {
+        type Error = Infallible;
+    }
+
+    impl Read for Chunked {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            self.reads += 1;
+            let len = buf.len().min(self.chunk);
+            self.data.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn delimiter_straddling_refill() {
+        let mut scratch = [0; 4];
+        let mut reader = BufReader::new(Chunked::new(b"hello\nworld\n", 8), &mut scratch);
+        let mut line = [0; 16];
+        assert_eq!(reader.read_line(&mut line), Ok(6));
+        assert_eq!(&line[..6], b"hello\n");
+        assert_eq!(reader.read_line(&mut line), Ok(6));
+        assert_eq!(&line[..6], b"world\n");
+        assert_eq!(reader.read_line(&mut line), Ok(0));
+    }
+
+    #[test]
+    fn eof_mid_line() {
+        let mut scratch = [0; 4];
+        let mut reader = BufReader::new(Chunked::new(b"ab\ncde", 2), &mut scratch);
+        let mut line = [0; 16];
+        assert_eq!(reader.read_line(&mut line), Ok(3));
+        assert_eq!(reader.read_line(&mut line), Ok(3));
+        assert_eq!(&line[..3], b"cde");
+        assert_eq!(reader.read_line(&mut line), Ok(0));
+    }
+
+    #[test]
+    fn read_until_full_output() {
+        let mut scratch = [0; 4];
+        let mut reader = BufReader::new(Chunked::new(b"abcdef;", 16), &mut scratch);
+        let mut out = [0; 3];
+        assert_eq!(reader.read_until(b';', &mut out), Ok(3));
+        assert_eq!(out, *b"abc");
+        assert_eq!(reader.buffer(), b"d");
+        assert_eq!(reader.read_until(b';', &mut out), Ok(3));
+        assert_eq!(out, *b"def");
+        assert_eq!(reader.read_until(b';', &mut out), Ok(1));
+        assert_eq!(out[0], b';');
+    }
+
+    #[test]
+    fn large_reads_bypass_buffer() {
+        let mut scratch = [0; 4];
+        let mut reader = BufReader::new(Chunked::new(b"0123456789", 16), &mut scratch);
+        let mut out = [0; 2];
+        assert_eq!(reader.read(&mut out), Ok(2));
+        assert_eq!(reader.buffer(), b"23");
+        // The buffered data is returned first.
+        let mut out = [0; 8];
+        assert_eq!(reader.read(&mut out), Ok(2));
+        assert_eq!(&out[..2], b"23");
+        assert_eq!(reader.read(&mut out), Ok(6));
+        assert_eq!(&out[..6], b"456789");
+        assert!(reader.buffer().is_empty());
+        assert_eq!(reader.get_ref().reads, 2);
+    }
+}
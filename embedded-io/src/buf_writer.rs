@@ -0,0 +1,257 @@
+use crate::{ErrorType, Write};
+
+/// Adds buffering to any [`Write`]r, using a buffer provided by the user.
+///
+/// This is the `embedded-io` equivalent of [`std::io::BufWriter`]. Small writes are gathered in the
+/// buffer, which is written to the inner writer when it is full, and on [`flush`](Write::flush).
+/// Writes which don't fit in the whole buffer bypass it, going directly to the inner writer.
+///
+/// Unlike `std::io::BufWriter`, dropping a `BufWriter` does **not** flush it: the buffered data is lost.
+/// Call [`flush`](Write::flush) or [`into_inner`](BufWriter::into_inner) first.
+///
+/// # Example
+///
+/// ```
+/// use embedded_io::{BufWriter, Write};
+///
+/// let mut uart = [0; 32];
+/// let mut scratch = [0; 16];
+/// let mut writer = BufWriter::new(&mut uart[..], &mut scratch);
+/// // These writes are buffered, and sent to the inner writer at once.
+/// write!(writer, "temperature: {} C\r\n", 21).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(&uart[..17], b"temperature: 21 C");
+/// ```
+pub struct BufWriter<'a, W: Write> {
+    inner: W,
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a, W: Write> BufWriter<'a, W> {
+    /// Creates a new `BufWriter`, buffering `inner` with `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is empty.
+    #[inline]
+    pub fn new(inner: W, buf: &'a mut [u8]) -> Self {
+        assert!(
+            !buf.is_empty(),
+            "the buffer of a BufWriter must not be empty"
+        );
+        Self { inner, buf, len: 0 }
+    }
+
+    /// Gets a reference to the inner writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the inner writer.
+    ///
+    /// Writing directly to the inner writer bypasses the data which is still buffered.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the buffered data which was not written to the inner writer yet.
+    #[inline]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Writes the buffered data, then returns the inner writer.
+    ///
+    /// If writing the buffered data fails, the error is returned along with the `BufWriter`
+    /// itself, so no data is lost and the operation can be retried.
+    pub fn into_inner(mut self) -> Result<W, (Self, W::Error)> {
+        match self.flush_buf() {
+            Ok(()) => Ok(self.inner),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Writes all the buffered data to the inner writer.
+    ///
+    /// On error, the data which was not written yet stays in the buffer.
+    fn flush_buf(&mut self) -> Result<(), W::Error> {
+        let mut written = 0;
+        let result = loop {
+            if written == self.len {
+                break Ok(());
+            }
+            match self.inner.write(&self.buf[written..self.len]) {
+                Ok(0) => panic!("write() returned Ok(0)"),
+                Ok(n) => written += n,
+                Err(e) => break Err(e),
+            }
+        };
+        self.buf.copy_within(written..self.len, 0);
+        self.len -= written;
+        result
+    }
+}
+
+impl<W: Write> ErrorType for BufWriter<'_, W> {
+    type Error = W::Error;
+}
+
+impl<W: Write> Write for BufWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if self.len + buf.len() > self.buf.len() {
+            self.flush_buf()?;
+        }
+        // Bypass the buffer for large writes.
+        if buf.len() >= self.buf.len() {
+            return self.inner.write(buf);
+        }
+        self.buf[self.len..self.len + buf.len()].copy_from_slice(buf);
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    /// Writer accepting at most `chunk` bytes per write, and failing once after `fail_at` successful writes.
+    struct Sink {
+        data: [u8; 32],
+        len: usize,
+        chunk: usize,
+        writes: usize,
+        fail_at: Option<usize>,
+    }
+
+    impl Sink {
+        fn new(chunk: usize) -> Self {
+            Self {
+                data: [0; 32],
+                len: 0,
+                chunk,
+                writes: 0,
+                fail_at: None,
+            }
+        }
+
+        fn data(&self) -> &[u8] {
+            &self.data[..self.len]
+        }
+    }
+
+    impl ErrorType for Sink {
+        type Error = ErrorKind;
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+            if self.fail_at == Some(self.writes) {
+                self.fail_at = None;
+                return Err(ErrorKind::Other);
+            }
+            self.writes += 1;
+            let n = buf.len().min(self.chunk);
+            self.data[self.len..self.len + n].copy_from_slice(&buf[..n]);
+            self.len += n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn buffers_small_writes() {
+        let mut scratch = [0; 8];
+        let mut writer = BufWriter::new(Sink::new(32), &mut scratch);
+        assert_eq!(writer.write(b"ab"), Ok(2));
+        assert_eq!(writer.write(b"cd"), Ok(2));
+        assert_eq!(writer.get_ref().writes, 0);
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().writes, 1);
+        assert_eq!(writer.get_ref().data(), b"abcd");
+    }
+
+    #[test]
+    fn flushes_when_full() {
+        let mut scratch = [0; 4];
+        let mut writer = BufWriter::new(Sink::new(32), &mut scratch);
+        writer.write_all(b"abc").unwrap();
+        writer.write_all(b"de").unwrap();
+        assert_eq!(writer.get_ref().data(), b"abc");
+        assert_eq!(writer.buffer(), b"de");
+    }
+
+    #[test]
+    fn large_writes_bypass_buffer() {
+        let mut scratch = [0; 4];
+        let mut writer = BufWriter::new(Sink::new(32), &mut scratch);
+        writer.write_all(b"ab").unwrap();
+        assert_eq!(writer.write(b"0123456789"), Ok(10));
+        assert_eq!(writer.get_ref().writes, 2);
+        assert_eq!(writer.get_ref().data(), b"ab0123456789");
+        assert!(writer.buffer().is_empty());
+    }
+
+    #[test]
+    fn short_writes_during_flush() {
+        let mut scratch = [0; 8];
+        let mut writer = BufWriter::new(Sink::new(1), &mut scratch);
+        writer.write_all(b"abcd").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().writes, 4);
+        assert_eq!(writer.get_ref().data(), b"abcd");
+    }
+
+    #[test]
+    fn error_during_flush_keeps_pending_data() {
+        let mut scratch = [0; 8];
+        let mut writer = BufWriter::new(Sink::new(2), &mut scratch);
+        writer.write_all(b"abcdef").unwrap();
+        // The first write of the flush succeeds, the second one fails.
+        writer.get_mut().fail_at = Some(1);
+        assert_eq!(writer.flush(), Err(ErrorKind::Other));
+        assert_eq!(writer.get_ref().data(), b"ab");
+        assert_eq!(writer.buffer(), b"cdef");
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref().data(), b"abcdef");
+    }
+
+    #[test]
+    fn error_before_write_rejects_data() {
+        let mut scratch = [0; 4];
+        let mut writer = BufWriter::new(Sink::new(32), &mut scratch);
+        writer.write_all(b"abc").unwrap();
+        writer.get_mut().fail_at = Some(0);
+        assert_eq!(writer.write(b"de"), Err(ErrorKind::Other));
+        assert_eq!(writer.buffer(), b"abc");
+        writer.write_all(b"de").unwrap();
+        assert_eq!(writer.get_ref().data(), b"abc");
+        assert_eq!(writer.buffer(), b"de");
+    }
+
+    #[test]
+    fn into_inner_reports_errors() {
+        let mut scratch = [0; 8];
+        let mut writer = BufWriter::new(Sink::new(32), &mut scratch);
+        writer.write_all(b"abc").unwrap();
+        writer.get_mut().fail_at = Some(0);
+        let (writer, e) = writer.into_inner().err().unwrap();
+        assert_eq!(e, ErrorKind::Other);
+        assert_eq!(writer.buffer(), b"abc");
+        let sink = writer.into_inner().ok().unwrap();
+        assert_eq!(sink.data(), b"abc");
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{BufRead, ErrorType, Read};
+
+/// Reader adapter which chains two readers.
+///
+/// This struct is created by [`Read::chain`]. Once the first reader reaches EOF, it is not used
+/// anymore, and all reads go to the second reader.
+///
+/// # Example
+///
+/// ```
+/// use embedded_io::Read;
+///
+/// let mut reader = (&b"head"[..]).chain(&b"er"[..]);
+/// let mut buf = [0; 8];
+/// let mut len = 0;
+/// loop {
+///     match reader.read(&mut buf[len..])? {
+///         0 => break,
+///         n => len += n,
+///     }
+/// }
+/// assert_eq!(&buf[..len], b"header");
+/// # Ok::<(), core::convert::Infallible>(())
+/// ```
+#[derive(Debug)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+    done_first: bool,
+}
+
+impl<A, B> Chain<A, B> {
+    pub(crate) fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            done_first: false,
+        }
+    }
+
+    /// Gets references to the underlying readers.
+    #[inline]
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    /// Gets mutable references to the underlying readers.
+    #[inline]
+    pub fn get_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.first, &mut self.second)
+    }
+
+    /// Consumes this adapter, returning the underlying readers.
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: ErrorType, B> ErrorType for Chain<A, B> {
+    type Error = A::Error;
+}
+
+impl<A: Read, B: Read<Error = A::Error>> Read for Chain<A, B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if !self.done_first {
+            match self.first.read(buf)? {
+                0 if !buf.is_empty() => self.done_first = true,
+                n => return Ok(n),
+            }
+        }
+        self.second.read(buf)
+    }
+}
+
+impl<A: BufRead, B: BufRead<Error = A::Error>> BufRead for Chain<A, B> {
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if !self.done_first {
+            if self.first.fill_buf()?.is_empty() {
+                self.done_first = true;
+            } else {
+                // The data is already buffered: this does not read from the first reader again.
+                return self.first.fill_buf();
+            }
+        }
+        self.second.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.done_first {
+            self.second.consume(amt);
+        } else {
+            self.first.consume(amt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BufRead, ErrorType, Read};
+    use core::convert::Infallible;
+
+    #[test]
+    fn chain_slices() {
+        let mut chain = (&b"abc"[..]).chain(&b"defgh"[..]);
+        let mut buf = [0; 8];
+        assert_eq!(chain.read(&mut buf), Ok(3));
+        assert_eq!(chain.read(&mut buf[3..]), Ok(5));
+        assert_eq!(buf, *b"abcdefgh");
+        assert_eq!(chain.read(&mut buf), Ok(0));
+    }
+
+    #[test]
+    fn chain_buffered() {
+        let mut chain = (&b"ab"[..]).chain(&b"cd"[..]);
+        assert_eq!(chain.fill_buf(), Ok(&b"ab"[..]));
+        chain.consume(2);
+        assert_eq!(chain.fill_buf(), Ok(&b"cd"[..]));
+        chain.consume(1);
+        assert_eq!(chain.into_inner(), (&b""[..], &b"d"[..]));
+    }
+
+    #[test]
+    fn first_reader_not_read_after_eof() {
+        /// Reader returning EOF, then data.
+        struct Revived(usize);
+
+        impl ErrorType for Revived {
+            type Error = Infallible;
+        }
+
+        impl Read for Revived {
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+                self.0 += 1;
+                if self.0 == 1 {
+                    Ok(0)
+                } else {
+                    buf[0] = b'!';
+                    Ok(1)
+                }
+            }
+        }
+
+        let mut chain = Revived(0).chain(&b"x"[..]);
+        let mut buf = [0; 4];
+        assert_eq!(chain.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], b'x');
+        assert_eq!(chain.read(&mut buf), Ok(0));
+        assert_eq!(chain.get_ref().0 .0, 1);
+    }
+}
@@ -0,0 +1,254 @@
+use core::fmt;
+
+use crate::{BufRead, Error, ErrorKind, Read, Write};
+
+/// Error returned by [`copy`] and [`copy_buf`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CopyError<R, W> {
+    /// Error returned by the reader.
+    Read(R),
+    /// Error returned by the writer.
+    Write(W),
+    /// The writer returned `Ok(0)`: it does not accept any more data.
+    WriteZero,
+}
+
+impl<R: fmt::Debug, W: fmt::Debug> fmt::Display for CopyError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(e) => write!(f, "read error: {e:?}"),
+            Self::Write(e) => write!(f, "write error: {e:?}"),
+            Self::WriteZero => write!(f, "the writer did not accept any more data"),
+        }
+    }
+}
+
+impl<R: fmt::Debug, W: fmt::Debug> core::error::Error for CopyError<R, W> {}
+
+impl<R: Error, W: Error> Error for CopyError<R, W> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Read(e) => e.kind(),
+            Self::Write(e) => e.kind(),
+            Self::WriteZero => ErrorKind::WriteZero,
+        }
+    }
+}
+
+/// Copy all the bytes of `reader` into `writer`, until EOF, returning the number of bytes copied.
+///
+/// The data goes through `buf`: each [`Read::read`] into it is followed by as many [`Write::write`]
+/// calls as needed to write everything read. The writer is not flushed.
+///
+/// # Panics
+///
+/// Panics if `buf` is empty.
+///
+/// # Example
+///
+/// ```
+/// let mut out = [0; 16];
+/// let mut buf = [0; 4];
+/// let copied = embedded_io::copy(&mut &b"hello, world"[..], &mut &mut out[..], &mut buf)?;
+/// assert_eq!(&out[..copied as usize], b"hello, world");
+/// # Ok::<(), embedded_io::CopyError<core::convert::Infallible, embedded_io::SliceWriteError>>(())
+/// ```
+pub fn copy<R: Read + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    buf: &mut [u8],
+) -> Result<u64, CopyError<R::Error, W::Error>> {
+    assert!(!buf.is_empty(), "copy buffer must not be empty");
+    let mut copied = 0;
+    loop {
+        let len = reader.read(buf).map_err(CopyError::Read)?;
+        if len == 0 {
+            return Ok(copied);
+        }
+        let mut data = &buf[..len];
+        while !data.is_empty() {
+            match writer.write(data).map_err(CopyError::Write)? {
+                0 => return Err(CopyError::WriteZero),
+                n => data = &data[n..],
+            }
+        }
+        copied += len as u64;
+    }
+}
+
+/// Copy all the bytes of `reader` into `writer`, until EOF, returning the number of bytes copied.
+///
+/// Unlike [`copy`], no intermediate buffer is needed: the data is written straight from the buffer of
+/// `reader`, with [`BufRead::fill_buf`] and [`BufRead::consume`]. The writer is not flushed.
+pub fn copy_buf<R: BufRead + ?Sized, W: Write + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<u64, CopyError<R::Error, W::Error>> {
+    let mut copied = 0;
+    loop {
+        let data = reader.fill_buf().map_err(CopyError::Read)?;
+        if data.is_empty() {
+            return Ok(copied);
+        }
+        let len = writer.write(data).map_err(CopyError::Write)?;
+        if len == 0 {
+            return Err(CopyError::WriteZero);
+        }
+        reader.consume(len);
+        copied += len as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorType, SliceWriteError};
+    use core::convert::Infallible;
+
+    /// Reader returning at most `max` bytes per read, and failing at the end of `data` if `fail`.
+    struct ShortReader<'a> {
+        data: &'a [u8],
+        max: usize,
+        fail: bool,
+    }
+
+    impl ErrorType for ShortReader<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read for ShortReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+            if self.data.is_empty() && self.fail {
+                return Err(ErrorKind::BrokenPipe);
+            }
+            let len = buf.len().min(self.max).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    impl BufRead for ShortReader<'_> {
+        fn fill_buf(&mut self) -> Result<&[u8], ErrorKind> {
+            if self.data.is_empty() && self.fail {
+                return Err(ErrorKind::BrokenPipe);
+            }
+            Ok(&self.data[..self.max.min(self.data.len())])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.data = &self.data[amt..];
+        }
+    }
+
+    /// Writer accepting at most `max` bytes per write, then returning `Ok(0)` once `out` is full.
+    struct ShortWriter<'a> {
+        out: &'a mut [u8],
+        len: usize,
+        max: usize,
+    }
+
+    impl ErrorType for ShortWriter<'_> {
+        type Error = Infallible;
+    }
+
+    impl Write for ShortWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            let len = buf.len().min(self.max).min(self.out.len() - self.len);
+            self.out[self.len..][..len].copy_from_slice(&buf[..len]);
+            self.len += len;
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    const DATA: &[u8] = b"the quick brown fox";
+
+    #[test]
+    fn copy_with_short_reads_and_writes() {
+        let mut reader = ShortReader {
+            data: DATA,
+            max: 3,
+            fail: false,
+        };
+        let mut out = [0; 32];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 2,
+        };
+        let mut buf = [0; 5];
+        assert_eq!(copy(&mut reader, &mut writer, &mut buf), Ok(19));
+        assert_eq!(&writer.out[..writer.len], DATA);
+    }
+
+    #[test]
+    fn copy_buf_with_short_reads_and_writes() {
+        let mut reader = ShortReader {
+            data: DATA,
+            max: 4,
+            fail: false,
+        };
+        let mut out = [0; 32];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 3,
+        };
+        assert_eq!(copy_buf(&mut reader, &mut writer), Ok(19));
+        assert_eq!(&writer.out[..writer.len], DATA);
+    }
+
+    #[test]
+    fn write_zero() {
+        let mut out = [0; 8];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 8,
+        };
+        let mut buf = [0; 4];
+        let err = copy(&mut &DATA[..], &mut writer, &mut buf).unwrap_err();
+        assert_eq!(err, CopyError::WriteZero);
+        assert_eq!(err.kind(), ErrorKind::WriteZero);
+        assert_eq!(writer.out, b"the quic");
+
+        let mut out = [0; 8];
+        let mut writer = ShortWriter {
+            out: &mut out,
+            len: 0,
+            max: 8,
+        };
+        let result = copy_buf(&mut &DATA[..], &mut writer);
+        assert_eq!(result, Err(CopyError::WriteZero));
+    }
+
+    #[test]
+    fn errors_tell_the_side() {
+        let mut reader = ShortReader {
+            data: b"ab",
+            max: 8,
+            fail: true,
+        };
+        let mut out = [0; 8];
+        let mut buf = [0; 4];
+        let err = copy(&mut reader, &mut &mut out[..], &mut buf).unwrap_err();
+        assert_eq!(err, CopyError::Read(ErrorKind::BrokenPipe));
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        assert_eq!(&out[..2], b"ab");
+
+        let mut out = [0; 0];
+        let err = copy_buf(&mut &DATA[..], &mut &mut out[..]).unwrap_err();
+        assert_eq!(err, CopyError::Write(SliceWriteError::Full));
+    }
+
+    #[test]
+    #[should_panic(expected = "copy buffer must not be empty")]
+    fn copy_panics_on_empty_buffer() {
+        let _ = copy(&mut &DATA[..], &mut &mut [0; 32][..], &mut []);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{BufRead, ErrorKind, ErrorType, Read, ReadReady, Seek, SeekFrom, Write, WriteReady};
+
+/// A `Cursor` wraps an in-memory buffer and provides it with a [`Seek`] implementation.
+///
+/// This is the `embedded-io` equivalent of [`std::io::Cursor`]. It implements [`Read`] and
+/// [`BufRead`] for any `T: AsRef<[u8]>`, and [`Write`] for any `T: AsMut<[u8]>`, such as
+/// arrays, slices, and mutable references to them.
+///
+/// Unlike `std::io::Cursor<Vec<u8>>`, writes never grow the buffer: writing at or past the end of
+/// the buffer fails with [`ErrorKind::WriteZero`], and writes crossing the end of the buffer are short.
+/// Seeking past the end of the buffer is allowed, reads then return 0 bytes.
+///
+/// # Example
+///
+/// ```
+/// use embedded_io::{Cursor, Seek, SeekFrom, Write};
+///
+/// // Write a length-prefixed message, filling the length afterwards.
+/// let mut cursor = Cursor::new([0; 8]);
+/// cursor.seek(SeekFrom::Start(1))?;
+/// cursor.write_all(b"hello")?;
+/// let len = cursor.position() as u8 - 1;
+/// cursor.set_position(0);
+/// cursor.write_all(&[len])?;
+/// assert_eq!(&cursor.get_ref()[..6], b"\x05hello");
+/// # Ok::<(), embedded_io::ErrorKind>(())
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Cursor<T> {
+    inner: T,
+    pos: u64,
+}
+
+impl<T> Cursor<T> {
+    /// Creates a new cursor wrapping the provided buffer, positioned at its start.
+    #[inline]
+    pub const fn new(inner: T) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    /// Consumes this cursor, returning the underlying buffer.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Gets a reference to the underlying buffer.
+    #[inline]
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying buffer.
+    ///
+    /// Changing the length of the buffer does not change the position of the cursor.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns the current position of this cursor.
+    #[inline]
+    pub const fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Sets the position of this cursor.
+    ///
+    /// The position can be past the end of the buffer.
+    #[inline]
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+}
+
+impl<T: AsRef<[u8]>> Cursor<T> {
+    /// Returns the part of the buffer after the current position.
+    fn remaining_slice(&self) -> &[u8] {
+        let inner = self.inner.as_ref();
+        let start = usize::try_from(self.pos).map_or(inner.len(), |pos| pos.min(inner.len()));
+        &inner[start..]
+    }
+}
+
+impl<T> ErrorType for Cursor<T> {
+    type Error = ErrorKind;
+}
+
+impl<T: AsRef<[u8]>> Read for Cursor<T> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut remaining = self.remaining_slice();
+        let amt = Read::read(&mut remaining, buf).unwrap_or_else(|e| match e {});
+        self.pos += amt as u64;
+        Ok(amt)
+    }
+}
+
+impl<T: AsRef<[u8]>> BufRead for Cursor<T> {
+    #[inline]
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        Ok(self.remaining_slice())
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl<T: AsRef<[u8]>> ReadReady for Cursor<T> {
+    #[inline]
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+impl<T: AsMut<[u8]>> Write for Cursor<T> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let inner = self.inner.as_mut();
+        let len = inner.len();
+        let start = usize::try_from(self.pos).map_or(len, |pos| pos.min(len));
+        let mut remaining = &mut inner[start..];
+        let amt = Write::write(&mut remaining, buf).map_err(|_| ErrorKind::WriteZero)?;
+        self.pos += amt as u64;
+        Ok(amt)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<T: AsMut<[u8]>> WriteReady for Cursor<T> {
+    #[inline]
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Self::Error> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(pos) => {
+                self.pos = pos;
+                return Ok(pos);
+            }
+            SeekFrom::End(offset) => (self.inner.as_ref().len() as u64, offset),
+            SeekFrom::Current(offset) => (self.pos, offset),
+        };
+        // Seeking before the start of the buffer is an error.
+        let pos = base
+            .checked_add_signed(offset)
+            .ok_or(ErrorKind::InvalidInput)?;
+        self.pos = pos;
+        Ok(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seek() {
+        let mut cursor = Cursor::new([0; 8]);
+        assert_eq!(cursor.seek(SeekFrom::Start(3)), Ok(3));
+        assert_eq!(cursor.seek(SeekFrom::Current(2)), Ok(5));
+        assert_eq!(cursor.seek(SeekFrom::Current(-4)), Ok(1));
+        assert_eq!(cursor.seek(SeekFrom::End(-2)), Ok(6));
+        assert_eq!(
+            cursor.seek(SeekFrom::Current(-7)),
+            Err(ErrorKind::InvalidInput)
+        );
+        assert_eq!(cursor.position(), 6);
+    }
+
+    #[test]
+    fn seek_past_end() {
+        let mut cursor = Cursor::new([1, 2, 3, 4]);
+        assert_eq!(cursor.seek(SeekFrom::End(4)), Ok(8));
+        assert_eq!(cursor.read(&mut [0; 2]), Ok(0));
+        assert_eq!(cursor.fill_buf(), Ok(&[][..]));
+        assert_eq!(cursor.write(&[5]), Err(ErrorKind::WriteZero));
+        assert_eq!(cursor.into_inner(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn read_at_end() {
+        let mut cursor = Cursor::new(&[1, 2, 3][..]);
+        let mut buf = [0; 2];
+        assert_eq!(cursor.read(&mut buf), Ok(2));
+        assert_eq!(buf, [1, 2]);
+        assert_eq!(cursor.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 3);
+        assert_eq!(cursor.read(&mut buf), Ok(0));
+        assert_eq!(cursor.position(), 3);
+    }
+
+    #[test]
+    fn short_write_at_end() {
+        let mut buf = [0; 4];
+        let mut cursor = Cursor::new(&mut buf[..]);
+        cursor.set_position(2);
+        assert_eq!(cursor.write(&[1, 2, 3]), Ok(2));
+        assert_eq!(cursor.write(&[3]), Err(ErrorKind::WriteZero));
+        assert_eq!(cursor.write(&[]), Ok(0));
+        assert_eq!(buf, [0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn interleaved_read_write() {
+        let mut cursor = Cursor::new([0, 1, 2, 3, 4, 5]);
+        let mut buf = [0; 2];
+        cursor.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1]);
+        cursor.write_all(&[9, 9]).unwrap();
+        assert_eq!(cursor.fill_buf(), Ok(&[4, 5][..]));
+        cursor.consume(1);
+        cursor.read_exact(&mut buf[..1]).unwrap();
+        assert_eq!(buf[0], 5);
+        cursor.seek(SeekFrom::Start(1)).unwrap();
+        cursor.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [1, 9]);
+        assert_eq!(cursor.into_inner(), [0, 1, 9, 9, 4, 5]);
+    }
+}
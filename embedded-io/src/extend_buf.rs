@@ -0,0 +1,310 @@
+use core::fmt;
+
+use crate::Cursor;
+
+/// Buffer which bytes can be appended to, up to an optional capacity.
+///
+/// This is the destination of [`Read::read_to_end`](crate::Read::read_to_end) and
+/// [`BufRead::read_until`](crate::BufRead::read_until). Unlike `std`, which reads into a `Vec<u8>`,
+/// it lets `no_std` code read into a fixed buffer, and tells how much fit. It is implemented for:
+///
+/// - [`Cursor`], wrapping e.g. a `&mut [u8]`: the bytes are stored at the position of the cursor,
+///   which is advanced, up to the end of the buffer.
+/// - `heapless::Vec<u8, N>`, with the `heapless` feature, up to its capacity.
+/// - `Vec<u8>`, with the `alloc` feature, which grows as needed.
+///
+/// # Example
+///
+/// ```
+/// use embedded_io::{Cursor, Read};
+///
+/// let mut reader = &b"key=value"[..];
+/// let mut buf = [0; 16];
+/// let mut cursor = Cursor::new(&mut buf[..]);
+/// let len = reader.read_to_end(&mut cursor)?;
+/// assert_eq!(&buf[..len], b"key=value");
+/// # Ok::<(), embedded_io::ReadToEndError<core::convert::Infallible>>(())
+/// ```
+pub trait ExtendBuf {
+    /// Returns the number of bytes stored in the buffer.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if no bytes are stored in the buffer.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Grow the buffer, and return the bytes added after the stored ones.
+    ///
+    /// The buffer grows by up to `additional` bytes, or more if that is cheaper: the whole remaining
+    /// space of a fixed buffer can be returned. The added bytes are stored until the buffer is
+    /// [truncated](ExtendBuf::truncate) back. An empty slice is returned if the buffer is full, or if
+    /// `additional` is 0.
+    fn grow(&mut self, additional: usize) -> &mut [u8];
+
+    /// Shorten the buffer, keeping the first `len` bytes stored.
+    ///
+    /// This has no effect if `len` is greater than the number of bytes stored.
+    fn truncate(&mut self, len: usize);
+
+    /// Append as many bytes of `data` as possible to the buffer, returning how many were stored.
+    fn extend_from_slice(&mut self, mut data: &[u8]) -> usize {
+        let start = self.len();
+        while !data.is_empty() {
+            let len = self.len();
+            let spare = self.grow(data.len());
+            if spare.is_empty() {
+                break;
+            }
+            let n = spare.len().min(data.len());
+            spare[..n].copy_from_slice(&data[..n]);
+            self.truncate(len + n);
+            data = &data[n..];
+        }
+        self.len() - start
+    }
+}
+
+impl<T: ?Sized + ExtendBuf> ExtendBuf for &mut T {
+    #[inline]
+    fn len(&self) -> usize {
+        T::len(self)
+    }
+
+    #[inline]
+    fn grow(&mut self, additional: usize) -> &mut [u8] {
+        T::grow(self, additional)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        T::truncate(self, len)
+    }
+
+    #[inline]
+    fn extend_from_slice(&mut self, data: &[u8]) -> usize {
+        T::extend_from_slice(self, data)
+    }
+}
+
+/// The bytes before the position of the cursor are the stored ones.
+impl<T: AsMut<[u8]>> ExtendBuf for Cursor<T> {
+    #[inline]
+    fn len(&self) -> usize {
+        usize::try_from(self.position()).unwrap_or(usize::MAX)
+    }
+
+    fn grow(&mut self, additional: usize) -> &mut [u8] {
+        let len = self.len();
+        let end = self.get_mut().as_mut().len();
+        if additional == 0 || len >= end {
+            return &mut [];
+        }
+        self.set_position(end as u64);
+        &mut self.get_mut().as_mut()[len..]
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.set_position(len as u64);
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> ExtendBuf for heapless::Vec<u8, N> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn grow(&mut self, additional: usize) -> &mut [u8] {
+        let len = self.as_slice().len();
+        // Cannot fail, the new length is at most the capacity.
+        let _ = self.resize(len.saturating_add(additional).min(N), 0);
+        &mut self[len..]
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        heapless::Vec::truncate(self, len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl ExtendBuf for alloc::vec::Vec<u8> {
+    #[inline]
+    fn len(&self) -> usize {
+        alloc::vec::Vec::len(self)
+    }
+
+    fn grow(&mut self, additional: usize) -> &mut [u8] {
+        let len = alloc::vec::Vec::len(self);
+        self.resize(len + additional, 0);
+        &mut self[len..]
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        alloc::vec::Vec::truncate(self, len)
+    }
+}
+
+/// Error returned by [`Read::read_to_end`](crate::Read::read_to_end) and
+/// [`BufRead::read_until`](crate::BufRead::read_until).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadToEndError<E> {
+    /// The buffer was full before the end of the data.
+    BufferFull {
+        /// Number of bytes stored in the buffer by the call.
+        stored: usize,
+    },
+    /// Error returned by the inner Read.
+    Other(E),
+}
+
+impl<E> From<E> for ReadToEndError<E> {
+    fn from(err: E) -> Self {
+        Self::Other(err)
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for ReadToEndError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for ReadToEndError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BufRead, ErrorKind, ErrorType, Read};
+
+    /// Reader returning `data` by chunks of 3 bytes, then failing if `fail`.
+    struct Chunks<'a> {
+        data: &'a [u8],
+        fail: bool,
+    }
+
+    impl ErrorType for Chunks<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read for Chunks<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+            if self.data.is_empty() && self.fail {
+                return Err(ErrorKind::BrokenPipe);
+            }
+            let len = buf.len().min(3).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn read_to_end_eof_exactly_at_capacity() {
+        let mut buf = [0; 7];
+        let mut cursor = Cursor::new(&mut buf[..]);
+        let mut reader = Chunks {
+            data: b"abcdefg",
+            fail: false,
+        };
+        assert_eq!(reader.read_to_end(&mut cursor), Ok(7));
+        assert_eq!(&buf, b"abcdefg");
+    }
+
+    #[test]
+    fn read_to_end_overflow_mid_read() {
+        let mut buf = [0; 5];
+        let mut cursor = Cursor::new(&mut buf[..]);
+        let mut reader = Chunks {
+            data: b"abcdefg",
+            fail: false,
+        };
+        assert_eq!(
+            reader.read_to_end(&mut cursor),
+            Err(ReadToEndError::BufferFull { stored: 5 })
+        );
+        assert_eq!(&buf, b"abcde");
+        // One byte was read to detect the overflow.
+        assert_eq!(reader.data, b"g");
+    }
+
+    #[test]
+    fn read_to_end_keeps_bytes_on_error() {
+        let mut buf = [0; 8];
+        let mut cursor = Cursor::new(&mut buf[..]);
+        let mut reader = Chunks {
+            data: b"abcd",
+            fail: true,
+        };
+        assert_eq!(
+            reader.read_to_end(&mut cursor),
+            Err(ReadToEndError::Other(ErrorKind::BrokenPipe))
+        );
+        assert_eq!(ExtendBuf::len(&cursor), 4);
+    }
+
+    #[test]
+    fn read_until_delimiter() {
+        let mut reader = &b"ssid=home\npsk=secret"[..];
+        let mut buf = [0; 20];
+        let mut cursor = Cursor::new(&mut buf[..]);
+        assert_eq!(reader.read_until(b'\n', &mut cursor), Ok(10));
+        assert_eq!(reader, b"psk=secret");
+        assert_eq!(reader.read_until(b'\n', &mut cursor), Ok(10));
+        assert_eq!(&buf, b"ssid=home\npsk=secret");
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn read_until_buffer_full() {
+        let mut reader = &b"0123456789\n"[..];
+        let mut buf = [0; 4];
+        let mut cursor = Cursor::new(&mut buf[..]);
+        assert_eq!(
+            reader.read_until(b'\n', &mut cursor),
+            Err(ReadToEndError::BufferFull { stored: 4 })
+        );
+        // The bytes which did not fit can still be read.
+        assert_eq!(reader.fill_buf(), Ok(&b"456789\n"[..]));
+    }
+
+    #[test]
+    fn cursor_grows_up_to_the_end() {
+        let mut buf = [0; 4];
+        let mut cursor = Cursor::new(&mut buf[..]);
+        assert_eq!(cursor.extend_from_slice(b"ab"), 2);
+        assert_eq!(cursor.grow(1).len(), 2);
+        cursor.truncate(3);
+        assert_eq!(cursor.extend_from_slice(b"cd"), 1);
+        assert!(cursor.grow(1).is_empty());
+        assert_eq!(ExtendBuf::len(&cursor), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "heapless")]
+    fn read_to_end_eof_at_capacity() {
+        let mut vec = heapless::Vec::<u8, 5>::new();
+        vec.push(b'>').unwrap();
+        assert_eq!((&b"abcd"[..]).read_to_end(&mut vec), Ok(4));
+        assert_eq!(vec, b">abcd");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn vec_grows() {
+        let mut vec = alloc::vec::Vec::new();
+        let data = [7; 300];
+        assert_eq!((&data[..]).read_to_end(&mut vec), Ok(300));
+        assert_eq!(vec, data);
+    }
+}
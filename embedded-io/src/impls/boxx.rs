@@ -43,6 +43,21 @@ impl<T: ?Sized + Write> Write for Box<T> {
         T::write_all(self, buf)
     }
 
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        T::write_vectored(self, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        T::is_write_vectored(self)
+    }
+
+    #[inline]
+    fn write_all_vectored(&mut self, bufs: &mut [&[u8]]) -> Result<(), Self::Error> {
+        T::write_all_vectored(self, bufs)
+    }
+
     #[inline]
     fn write_fmt(
         &mut self,
@@ -0,0 +1,215 @@
+use crate::{Error, ErrorKind, ErrorType, SliceWriteError, StringWriteError, Write, WriteReady};
+use heapless::{String, Vec};
+
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> ErrorType for Vec<u8, N> {
+    type Error = SliceWriteError;
+}
+
+/// Write is implemented for `heapless::Vec<u8, N>` by appending to the vector.
+///
+/// If the number of bytes to be written exceeds the remaining capacity, write operations will
+/// return short writes: ultimately, a `SliceWriteError::Full`. `write_all` returns this error
+/// without writing anything if the bytes do not fit.
+///
+/// To read the bytes back, wrap the vector in a [`Cursor`](crate::Cursor).
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> Write for Vec<u8, N> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let amt = core::cmp::min(buf.len(), N - self.len());
+        if !buf.is_empty() && amt == 0 {
+            return Err(SliceWriteError::Full);
+        }
+        // Cannot fail, `amt` bytes fit.
+        let _ = self.extend_from_slice(&buf[..amt]);
+        Ok(amt)
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(buf)
+            .map_err(|()| SliceWriteError::Full)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> WriteReady for Vec<u8, N> {
+    #[inline]
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+impl Error for StringWriteError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            StringWriteError::Full => ErrorKind::WriteZero,
+            StringWriteError::InvalidUtf8 => ErrorKind::InvalidData,
+        }
+    }
+}
+
+impl core::fmt::Display for StringWriteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl core::error::Error for StringWriteError {}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> ErrorType for String<N> {
+    type Error = StringWriteError;
+}
+
+/// Write is implemented for `heapless::String<N>` by appending to the string.
+///
+/// The bytes must be valid UTF-8. `write` writes the longest prefix of complete characters which
+/// fits in the remaining capacity, returning `StringWriteError::InvalidUtf8` if `buf` starts with
+/// invalid UTF-8 or only contains part of a character, and `StringWriteError::Full` if not even
+/// one character fits. `write_all` checks the whole buffer first, and writes nothing on error.
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> Write for String<N> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let amt = core::cmp::min(buf.len(), N - self.len());
+        let valid = match core::str::from_utf8(&buf[..amt]) {
+            Ok(s) => s,
+            // Write the complete characters, the next call reports the error if there is one.
+            Err(e) if e.valid_up_to() > 0 => {
+                core::str::from_utf8(&buf[..e.valid_up_to()]).unwrap_or_default()
+            }
+            // A character cut by the capacity does not fit, anything else is invalid.
+            Err(e) if e.error_len().is_none() && amt < buf.len() => {
+                return Err(StringWriteError::Full)
+            }
+            Err(_) => return Err(StringWriteError::InvalidUtf8),
+        };
+        if valid.is_empty() {
+            return Err(StringWriteError::Full);
+        }
+        // Cannot fail, `valid` fits.
+        let _ = self.push_str(valid);
+        Ok(valid.len())
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let s = core::str::from_utf8(buf).map_err(|_| StringWriteError::InvalidUtf8)?;
+        self.push_str(s).map_err(|()| StringWriteError::Full)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+impl<const N: usize> WriteReady for String<N> {
+    #[inline]
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cursor, Read};
+
+    #[test]
+    fn vec_exactly_full() {
+        let mut vec = Vec::<u8, 4>::new();
+        vec.write_all(b"abcd").unwrap();
+        assert_eq!(vec, b"abcd");
+        assert_eq!(vec.write(b"e"), Err(SliceWriteError::Full));
+        assert_eq!(vec.write(b""), Ok(0));
+        assert_eq!(SliceWriteError::Full.kind(), ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn vec_overflow_by_one() {
+        let mut vec = Vec::<u8, 4>::new();
+        assert_eq!(vec.write_all(b"abcde"), Err(SliceWriteError::Full));
+        assert!(vec.is_empty());
+        assert_eq!(vec.write(b"abcde"), Ok(4));
+        assert_eq!(vec, b"abcd");
+    }
+
+    #[test]
+    fn vec_partial_write_then_error() {
+        let mut vec = Vec::<u8, 4>::new();
+        assert_eq!(vec.write(b"ab"), Ok(2));
+        assert_eq!(vec.write(b"cde"), Ok(2));
+        assert_eq!(vec.write(b"e"), Err(SliceWriteError::Full));
+        assert_eq!(vec, b"abcd");
+
+        let mut cursor = Cursor::new(vec);
+        let mut buf = [0; 8];
+        assert_eq!(cursor.read(&mut buf), Ok(4));
+        assert_eq!(&buf[..4], b"abcd");
+        assert_eq!(cursor.read(&mut buf), Ok(0));
+    }
+
+    #[test]
+    fn string_exactly_full() {
+        let mut string = String::<4>::new();
+        string.write_all("aé".as_bytes()).unwrap();
+        string.write_all(b"b").unwrap();
+        assert_eq!(string, "aéb");
+        assert_eq!(string.write(b"c"), Err(StringWriteError::Full));
+        assert_eq!(StringWriteError::Full.kind(), ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn string_overflow_by_one() {
+        let mut string = String::<4>::new();
+        assert_eq!(string.write_all(b"abcde"), Err(StringWriteError::Full));
+        assert!(string.is_empty());
+        assert_eq!(string.write(b"abcde"), Ok(4));
+        assert_eq!(string, "abcd");
+    }
+
+    #[test]
+    fn string_partial_write_then_error() {
+        // The last character would be cut by the capacity.
+        let mut string = String::<4>::new();
+        assert_eq!(string.write("abcé".as_bytes()), Ok(3));
+        assert_eq!(string.write("é".as_bytes()), Err(StringWriteError::Full));
+        assert_eq!(string, "abc");
+
+        // The valid prefix is written, then the invalid byte is reported.
+        let mut string = String::<4>::new();
+        assert_eq!(string.write(b"a\xFFb"), Ok(1));
+        assert_eq!(string.write(b"\xFFb"), Err(StringWriteError::InvalidUtf8));
+        assert_eq!(string, "a");
+        assert_eq!(StringWriteError::InvalidUtf8.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn string_rejects_partial_characters() {
+        let mut string = String::<4>::new();
+        assert_eq!(string.write(b"\xC3"), Err(StringWriteError::InvalidUtf8));
+        assert_eq!(
+            string.write_all(b"a\xC3"),
+            Err(StringWriteError::InvalidUtf8)
+        );
+        assert!(string.is_empty());
+    }
+
+    #[test]
+    fn string_write_fmt() {
+        let mut string = String::<8>::new();
+        write!(string, "t={}", 21).unwrap();
+        assert_eq!(string, "t=21");
+    }
+}
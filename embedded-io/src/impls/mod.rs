@@ -1,6 +1,9 @@
 mod slice_mut;
 mod slice_ref;
 
+#[cfg(feature = "heapless")]
+mod heapless;
+
 #[cfg(feature = "alloc")]
 mod boxx;
 #[cfg(feature = "alloc")]
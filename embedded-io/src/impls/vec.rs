@@ -20,6 +20,21 @@ impl Write for Vec<u8> {
         Ok(())
     }
 
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        self.reserve(len);
+        for buf in bufs {
+            self.extend_from_slice(buf);
+        }
+        Ok(len)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn flush(&mut self) -> Result<(), Self::Error> {
         Ok(())
@@ -8,7 +8,26 @@ use core::fmt;
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod buf_reader;
+mod buf_writer;
+mod chain;
+mod copy;
+mod cursor;
+mod extend_buf;
 mod impls;
+mod ready;
+mod take;
+mod write_fmt;
+
+pub use buf_reader::BufReader;
+pub use buf_writer::BufWriter;
+pub use chain::Chain;
+pub use copy::{copy, copy_buf, CopyError};
+pub use cursor::Cursor;
+pub use extend_buf::{ExtendBuf, ReadToEndError};
+pub use ready::{select2, NonBlockingError, Select2, SelectError, Which};
+pub use take::Take;
+pub use write_fmt::WriteFmt;
 
 /// Enumeration of possible methods to seek within an I/O object.
 ///
@@ -272,6 +291,19 @@ pub enum SliceWriteError {
     Full,
 }
 
+/// Errors that could be returned by `Write` on `heapless::String`.
+#[cfg(feature = "heapless")]
+#[cfg_attr(docsrs, doc(cfg(feature = "heapless")))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum StringWriteError {
+    /// The string was full and so could not receive any new data.
+    Full,
+    /// The data was not valid UTF-8.
+    InvalidUtf8,
+}
+
 /// Error returned by [`Write::write_fmt`]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -352,6 +384,67 @@ pub trait Read: ErrorType {
             Err(ReadExactError::UnexpectedEof)
         }
     }
+
+    /// Read all the bytes until EOF, appending them to `buf`, and return how many were read.
+    ///
+    /// This is the `embedded-io` equivalent of [`std::io::Read::read_to_end`]. It reads into any
+    /// [`ExtendBuf`], such as a [`Cursor`] over a fixed buffer, so that it does not need an allocator.
+    ///
+    /// If `buf` becomes full before EOF, [`ReadToEndError::BufferFull`] is returned with the number of
+    /// bytes stored. A byte is read to tell EOF from more data once `buf` is full: that byte is lost.
+    /// If `read()` returns an error, the bytes stored so far are kept in `buf`.
+    fn read_to_end(
+        &mut self,
+        buf: &mut impl ExtendBuf,
+    ) -> Result<usize, ReadToEndError<Self::Error>> {
+        let start = buf.len();
+        loop {
+            let len = buf.len();
+            // Grow by small chunks, a fixed-capacity buffer may zero the added bytes.
+            let spare = buf.grow(64);
+            if spare.is_empty() {
+                return match self.read(&mut [0]) {
+                    Ok(0) => Ok(len - start),
+                    Ok(_) => Err(ReadToEndError::BufferFull {
+                        stored: len - start,
+                    }),
+                    Err(e) => Err(ReadToEndError::Other(e)),
+                };
+            }
+            match self.read(spare) {
+                Ok(0) => {
+                    buf.truncate(len);
+                    return Ok(len - start);
+                }
+                Ok(n) => buf.truncate(len + n),
+                Err(e) => {
+                    buf.truncate(len);
+                    return Err(ReadToEndError::Other(e));
+                }
+            }
+        }
+    }
+
+    /// Creates an adapter which reads at most `limit` bytes from this reader.
+    ///
+    /// This is the `embedded-io` equivalent of [`std::io::Read::take`].
+    fn take(self, limit: u64) -> Take<Self>
+    where
+        Self: Sized,
+    {
+        Take::new(self, limit)
+    }
+
+    /// Creates an adapter which reads from this reader until EOF, then from `next`.
+    ///
+    /// This is the `embedded-io` equivalent of [`std::io::Read::chain`]. Both readers must
+    /// have the same error type.
+    fn chain<R: Read<Error = Self::Error>>(self, next: R) -> Chain<Self, R>
+    where
+        Self: Sized,
+    {
+        Chain::new(self, next)
+    }
 }
 
 /// Blocking buffered reader.
@@ -369,6 +462,40 @@ pub trait BufRead: Read {
 
     /// Tell this buffer that `amt` bytes have been consumed from the buffer, so they should no longer be returned in calls to `fill_buf`.
     fn consume(&mut self, amt: usize);
+
+    /// Read all the bytes until the `delim` byte or EOF, appending them to `buf`, and return how many were read.
+    ///
+    /// This is the `embedded-io` equivalent of [`std::io::BufRead::read_until`], for any [`ExtendBuf`].
+    /// The delimiter is appended to `buf`, if found.
+    ///
+    /// If `buf` becomes full before the delimiter, [`ReadToEndError::BufferFull`] is returned with the
+    /// number of bytes stored. No byte is lost: the next bytes remain to be read.
+    fn read_until(
+        &mut self,
+        delim: u8,
+        buf: &mut impl ExtendBuf,
+    ) -> Result<usize, ReadToEndError<Self::Error>> {
+        let mut read = 0;
+        loop {
+            let available = self.fill_buf()?;
+            if available.is_empty() {
+                return Ok(read);
+            }
+            let (found, used) = match available.iter().position(|&b| b == delim) {
+                Some(i) => (true, i + 1),
+                None => (false, available.len()),
+            };
+            let stored = buf.extend_from_slice(&available[..used]);
+            self.consume(stored);
+            read += stored;
+            if stored < used {
+                return Err(ReadToEndError::BufferFull { stored: read });
+            }
+            if found {
+                return Ok(read);
+            }
+        }
+    }
 }
 
 /// Blocking writer.
@@ -417,6 +544,49 @@ pub trait Write: ErrorType {
         Ok(())
     }
 
+    /// Write the concatenation of several buffers into this writer, returning how many bytes were written.
+    ///
+    /// This behaves like [`Write::write`] on the concatenation of `bufs`: the same blocking rules apply,
+    /// and `Ok(0)` must not be returned unless all buffers are empty. Writers which can send several
+    /// buffers at once, e.g. with a DMA descriptor chain, should override this method, and
+    /// [`Write::is_write_vectored`].
+    ///
+    /// The default implementation writes the first non-empty buffer.
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        let buf = bufs
+            .iter()
+            .find(|buf| !buf.is_empty())
+            .map_or(&[][..], |buf| buf);
+        self.write(buf)
+    }
+
+    /// Returns `true` if this writer has an efficient [`Write::write_vectored`] implementation.
+    ///
+    /// Callers can use this hint to decide between a vectored write and copying the buffers into
+    /// a single one. The default implementation returns `false`.
+    fn is_write_vectored(&self) -> bool {
+        false
+    }
+
+    /// Write the entire concatenation of several buffers into this writer.
+    ///
+    /// This function calls `write_vectored()` in a loop until all the buffers have been written,
+    /// blocking if needed. The slices in `bufs` are updated to track the progress, so their contents
+    /// are unspecified when this function returns.
+    ///
+    /// This function will panic if `write_vectored()` returns `Ok(0)`.
+    fn write_all_vectored(&mut self, mut bufs: &mut [&[u8]]) -> Result<(), Self::Error> {
+        advance_slices(&mut bufs, 0);
+        while !bufs.is_empty() {
+            match self.write_vectored(bufs) {
+                Ok(0) => panic!("write_vectored() returned Ok(0)"),
+                Ok(n) => advance_slices(&mut bufs, n),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
     /// Write a formatted string into this writer, returning any error encountered.
     ///
     /// This function calls `write()` in a loop until the entire formatted string has
@@ -437,35 +607,13 @@ pub trait Write: ErrorType {
     /// let len = write!(buf, "{}", "Test").and_then(|_| Ok(start - buf.len()));
     /// ```
     fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> Result<(), WriteFmtError<Self::Error>> {
-        // Create a shim which translates a Write to a fmt::Write and saves
-        // off I/O errors. instead of discarding them
-        struct Adapter<'a, T: Write + ?Sized + 'a> {
-            inner: &'a mut T,
-            error: Result<(), T::Error>,
-        }
-
-        impl<T: Write + ?Sized> fmt::Write for Adapter<'_, T> {
-            fn write_str(&mut self, s: &str) -> fmt::Result {
-                match self.inner.write_all(s.as_bytes()) {
-                    Ok(()) => Ok(()),
-                    Err(e) => {
-                        self.error = Err(e);
-                        Err(fmt::Error)
-                    }
-                }
-            }
-        }
-
-        let mut output = Adapter {
-            inner: self,
-            error: Ok(()),
-        };
+        let mut output = WriteFmt::new(self);
         match fmt::write(&mut output, fmt) {
             Ok(()) => Ok(()),
-            Err(..) => match output.error {
-                // check if the error came from the underlying `Write` or not
-                Err(e) => Err(WriteFmtError::Other(e)),
-                Ok(()) => Err(WriteFmtError::FmtError),
+            // check if the error came from the underlying `Write` or not
+            Err(..) => match output.take_error() {
+                Some(e) => Err(WriteFmtError::Other(e)),
+                None => Err(WriteFmtError::FmtError),
             },
         }
     }
@@ -538,6 +686,24 @@ pub trait ReadReady: ErrorType {
     ///
     /// If this returns `true`, it's guaranteed that the next call to [`Read::read`] or [`BufRead::fill_buf`] will not block.
     fn read_ready(&mut self) -> Result<bool, Self::Error>;
+
+    /// Read some bytes into `buf` if the reader is ready, without blocking.
+    ///
+    /// Returns `Ok(None)` if [`read_ready`](ReadReady::read_ready) returns `false`, without calling
+    /// [`Read::read`]. Otherwise, returns the result of [`Read::read`]: like it, `Ok(Some(0))` means that the
+    /// reader is at EOF, or that `buf` is empty.
+    ///
+    /// Errors of the readiness check are returned as [`NonBlockingError::Ready`], and errors of the read as
+    /// [`NonBlockingError::Io`].
+    fn read_nb(&mut self, buf: &mut [u8]) -> Result<Option<usize>, NonBlockingError<Self::Error>>
+    where
+        Self: Read,
+    {
+        if !self.read_ready().map_err(NonBlockingError::Ready)? {
+            return Ok(None);
+        }
+        self.read(buf).map(Some).map_err(NonBlockingError::Io)
+    }
 }
 
 /// Get whether a writer is ready.
@@ -551,6 +717,23 @@ pub trait WriteReady: ErrorType {
     ///
     /// If this returns `true`, it's guaranteed that the next call to [`Write::write`] will not block.
     fn write_ready(&mut self) -> Result<bool, Self::Error>;
+
+    /// Write some bytes of `buf` if the writer is ready, without blocking.
+    ///
+    /// Returns `Ok(None)` if [`write_ready`](WriteReady::write_ready) returns `false`, without calling
+    /// [`Write::write`]. Otherwise, returns the result of [`Write::write`], which may not write all of `buf`.
+    ///
+    /// Errors of the readiness check are returned as [`NonBlockingError::Ready`], and errors of the write as
+    /// [`NonBlockingError::Io`].
+    fn write_nb(&mut self, buf: &[u8]) -> Result<Option<usize>, NonBlockingError<Self::Error>>
+    where
+        Self: Write,
+    {
+        if !self.write_ready().map_err(NonBlockingError::Ready)? {
+            return Ok(None);
+        }
+        self.write(buf).map(Some).map_err(NonBlockingError::Io)
+    }
 }
 
 impl<T: ?Sized + Read> Read for &mut T {
@@ -583,6 +766,21 @@ impl<T: ?Sized + Write> Write for &mut T {
         T::write(self, buf)
     }
 
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Self::Error> {
+        T::write_vectored(self, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        T::is_write_vectored(self)
+    }
+
+    #[inline]
+    fn write_all_vectored(&mut self, bufs: &mut [&[u8]]) -> Result<(), Self::Error> {
+        T::write_all_vectored(self, bufs)
+    }
+
     #[inline]
     fn flush(&mut self) -> Result<(), Self::Error> {
         T::flush(self)
@@ -629,3 +827,121 @@ impl<T: ?Sized + WriteReady> WriteReady for &mut T {
         T::write_ready(self)
     }
 }
+
+/// Remove the first `n` bytes from `bufs`, dropping the buffers which are entirely written,
+/// as well as the empty buffers at the front.
+fn advance_slices(bufs: &mut &mut [&[u8]], mut n: usize) {
+    let mut written = 0;
+    for buf in bufs.iter() {
+        if buf.len() > n {
+            break;
+        }
+        n -= buf.len();
+        written += 1;
+    }
+    *bufs = &mut core::mem::take(bufs)[written..];
+    match bufs.first_mut() {
+        Some(first) => *first = &first[n..],
+        None => assert!(n == 0, "advancing past the end of the buffers"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+
+    /// Writer accepting at most `chunk` bytes per call, across buffers.
+    struct Chunked {
+        data: [u8; 16],
+        len: usize,
+        chunk: usize,
+        calls: usize,
+    }
+
+    impl Chunked {
+        fn new(chunk: usize) -> Self {
+            Self {
+                data: [0; 16],
+                len: 0,
+                chunk,
+                calls: 0,
+            }
+        }
+
+        fn written(&self) -> &[u8] {
+            &self.data[..self.len]
+        }
+    }
+
+    impl ErrorType for Chunked {
+        type Error = Infallible;
+    }
+
+    impl Write for Chunked {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.write_vectored(&[buf])
+        }
+
+        fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, Infallible> {
+            self.calls += 1;
+            let mut total = 0;
+            for buf in bufs {
+                let n = buf.len().min(self.chunk - total);
+                self.data[self.len..][..n].copy_from_slice(&buf[..n]);
+                self.len += n;
+                total += n;
+            }
+            Ok(total)
+        }
+
+        fn is_write_vectored(&self) -> bool {
+            true
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn default_write_vectored_writes_first_non_empty_buffer() {
+        let mut buf = [0; 8];
+        let mut writer = &mut buf[..];
+        assert_eq!(writer.write_vectored(&[b"", b"ab", b"cd"]), Ok(2));
+        assert_eq!(writer.write_vectored(&[b"", b""]), Ok(0));
+        assert!(!writer.is_write_vectored());
+        assert_eq!(&buf[..2], b"ab");
+    }
+
+    #[test]
+    fn partial_write_on_buffer_boundary() {
+        let mut writer = Chunked::new(3);
+        writer
+            .write_all_vectored(&mut [b"abc", b"", b"def", b"g"])
+            .unwrap();
+        assert_eq!(writer.written(), b"abcdefg");
+        assert_eq!(writer.calls, 3);
+    }
+
+    #[test]
+    fn partial_write_in_middle_of_buffer() {
+        let mut writer = Chunked::new(4);
+        writer
+            .write_all_vectored(&mut [b"", b"abc", b"defgh", b"ij"])
+            .unwrap();
+        assert_eq!(writer.written(), b"abcdefghij");
+        assert_eq!(writer.calls, 3);
+    }
+
+    #[test]
+    fn write_all_vectored_with_default_write_vectored() {
+        let mut buf = [0; 8];
+        let mut writer = &mut buf[..];
+        writer
+            .write_all_vectored(&mut [b"ab", b"", b"cde"])
+            .unwrap();
+        assert_eq!(writer.len(), 3);
+        assert_eq!(&buf[..5], b"abcde");
+    }
+}
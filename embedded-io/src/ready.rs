@@ -0,0 +1,322 @@
+use core::fmt;
+
+use crate::{Error, ErrorKind, ReadReady};
+
+/// Error returned by [`ReadReady::read_nb`] and [`WriteReady::write_nb`](crate::WriteReady::write_nb).
+///
+/// The readiness check and the transfer return the same error type, this tells which one failed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NonBlockingError<E> {
+    /// Checking whether the reader or writer is ready failed.
+    Ready(E),
+    /// The read or write failed, after the reader or writer was ready.
+    Io(E),
+}
+
+impl<E: fmt::Debug> fmt::Display for NonBlockingError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ready(e) => write!(f, "readiness check error: {e:?}"),
+            Self::Io(e) => write!(f, "io error: {e:?}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for NonBlockingError<E> {}
+
+impl<E: Error> Error for NonBlockingError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Ready(e) | Self::Io(e) => e.kind(),
+        }
+    }
+}
+
+/// Source selected by [`select2`] or [`Select2`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Which {
+    /// The first source is ready.
+    First,
+    /// The second source is ready.
+    Second,
+}
+
+/// Error returned by [`select2`] and [`Select2`], telling which readiness check failed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SelectError<A, B> {
+    /// Checking whether the first source is ready failed.
+    First(A),
+    /// Checking whether the second source is ready failed.
+    Second(B),
+}
+
+impl<A: fmt::Debug, B: fmt::Debug> fmt::Display for SelectError<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First(e) => write!(f, "first source error: {e:?}"),
+            Self::Second(e) => write!(f, "second source error: {e:?}"),
+        }
+    }
+}
+
+impl<A: fmt::Debug, B: fmt::Debug> core::error::Error for SelectError<A, B> {}
+
+impl<A: Error, B: Error> Error for SelectError<A, B> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::First(e) => e.kind(),
+            Self::Second(e) => e.kind(),
+        }
+    }
+}
+
+/// Returns which of `a` and `b` is ready to be read, if any, checking `a` first.
+///
+/// `b` is only checked if `a` is not ready: if `a` is always ready, `b` is never selected. Use
+/// [`Select2`] to alternate between the sources.
+pub fn select2<A: ReadReady + ?Sized, B: ReadReady + ?Sized>(
+    a: &mut A,
+    b: &mut B,
+) -> Result<Option<Which>, SelectError<A::Error, B::Error>> {
+    if a.read_ready().map_err(SelectError::First)? {
+        return Ok(Some(Which::First));
+    }
+    if b.read_ready().map_err(SelectError::Second)? {
+        return Ok(Some(Which::Second));
+    }
+    Ok(None)
+}
+
+/// Round-robin selection between two sources, for polling them without an async runtime.
+///
+/// Each call to [`poll`](Select2::poll) first checks the source which was not selected last, so that a
+/// source which is always ready does not starve the other one.
+///
+/// # Example
+///
+/// A superloop forwarding the bytes received from a UART and a radio link to a log.
+///
+/// ```
+/// use embedded_io::{ReadReady, Select2, Which, Write};
+/// # use embedded_io::{ErrorKind, ErrorType, Read};
+/// # struct Source(&'static [u8]);
+/// # impl ErrorType for Source { type Error = ErrorKind; }
+/// # impl Read for Source {
+/// #     fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+/// #         self.0.read(buf).map_err(|e| match e {})
+/// #     }
+/// # }
+/// # impl ReadReady for Source {
+/// #     fn read_ready(&mut self) -> Result<bool, ErrorKind> { Ok(!self.0.is_empty()) }
+/// # }
+/// # let (mut uart, mut radio) = (Source(b"gps\n"), Source(b"ping\n"));
+/// # let mut out = [0; 16];
+/// # let mut log = &mut out[..];
+///
+/// let mut select = Select2::new();
+/// let mut buf = [0; 4];
+/// # for _ in 0..4 {
+/// // loop {
+///     match select.poll(&mut uart, &mut radio)? {
+///         Some(Which::First) => {
+///             let len = uart.read_nb(&mut buf)?.unwrap_or(0);
+///             log.write_all(&buf[..len])?;
+///         }
+///         Some(Which::Second) => {
+///             let len = radio.read_nb(&mut buf)?.unwrap_or(0);
+///             log.write_all(&buf[..len])?;
+///         }
+///         None => { /* Nothing to do: sleep until the next interrupt. */ }
+///     }
+/// }
+/// # assert_eq!(&out[..9], b"gps\nping\n");
+/// # Ok::<(), Box<dyn core::error::Error>>(())
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Select2 {
+    next: Which,
+}
+
+impl Select2 {
+    /// Create a new [`Select2`], checking the first source first.
+    pub const fn new() -> Self {
+        Self { next: Which::First }
+    }
+
+    /// Returns which of `a` and `b` is ready to be read, if any.
+    ///
+    /// The source which was not selected by the previous call is checked first. The other one is only
+    /// checked if it is not ready.
+    pub fn poll<A: ReadReady + ?Sized, B: ReadReady + ?Sized>(
+        &mut self,
+        a: &mut A,
+        b: &mut B,
+    ) -> Result<Option<Which>, SelectError<A::Error, B::Error>> {
+        let selected = match self.next {
+            Which::First => select2(a, b)?,
+            Which::Second => {
+                if b.read_ready().map_err(SelectError::Second)? {
+                    Some(Which::Second)
+                } else if a.read_ready().map_err(SelectError::First)? {
+                    Some(Which::First)
+                } else {
+                    None
+                }
+            }
+        };
+        match selected {
+            Some(Which::First) => self.next = Which::Second,
+            Some(Which::Second) => self.next = Which::First,
+            None => {}
+        }
+        Ok(selected)
+    }
+}
+
+impl Default for Select2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorType, Read, Write, WriteReady};
+
+    /// Reader and writer which is ready according to `ready`, one check after the other, counting the
+    /// transfers in `calls`. Reads are at EOF once `data` is empty, and fail if it is `b"!"`.
+    struct Scripted<'a> {
+        ready: &'a [Result<bool, ErrorKind>],
+        data: &'a [u8],
+        calls: usize,
+    }
+
+    impl<'a> Scripted<'a> {
+        fn new(ready: &'a [Result<bool, ErrorKind>], data: &'a [u8]) -> Self {
+            Self {
+                ready,
+                data,
+                calls: 0,
+            }
+        }
+    }
+
+    impl ErrorType for Scripted<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Read for Scripted<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+            self.calls += 1;
+            if self.data == b"!" {
+                return Err(ErrorKind::BrokenPipe);
+            }
+            self.data.read(buf).map_err(|e| match e {})
+        }
+    }
+
+    impl ReadReady for Scripted<'_> {
+        fn read_ready(&mut self) -> Result<bool, ErrorKind> {
+            let (first, rest) = self
+                .ready
+                .split_first()
+                .expect("unexpected readiness check");
+            self.ready = rest;
+            *first
+        }
+    }
+
+    impl Write for Scripted<'_> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+            self.calls += 1;
+            Ok(buf.len().min(2))
+        }
+
+        fn flush(&mut self) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    impl WriteReady for Scripted<'_> {
+        fn write_ready(&mut self) -> Result<bool, ErrorKind> {
+            self.read_ready()
+        }
+    }
+
+    #[test]
+    fn read_nb_follows_readiness() {
+        let mut reader = Scripted::new(&[Ok(false), Ok(true), Ok(false), Ok(true)], b"abc");
+        let mut buf = [0; 2];
+        assert_eq!(reader.read_nb(&mut buf), Ok(None));
+        assert_eq!(reader.read_nb(&mut buf), Ok(Some(2)));
+        assert_eq!(reader.read_nb(&mut buf), Ok(None));
+        assert_eq!(reader.read_nb(&mut buf), Ok(Some(1)));
+        // `read` is only called when the reader is ready.
+        assert_eq!(reader.calls, 2);
+    }
+
+    #[test]
+    fn read_nb_reports_eof_as_zero() {
+        let mut reader = Scripted::new(&[Ok(true)], b"");
+        assert_eq!(reader.read_nb(&mut [0; 4]), Ok(Some(0)));
+    }
+
+    #[test]
+    fn errors_tell_the_check_from_the_transfer() {
+        let mut reader = Scripted::new(&[Err(ErrorKind::TimedOut), Ok(true)], b"!");
+        assert_eq!(
+            reader.read_nb(&mut [0; 4]),
+            Err(NonBlockingError::Ready(ErrorKind::TimedOut))
+        );
+        assert_eq!(reader.calls, 0);
+        let err = reader.read_nb(&mut [0; 4]).unwrap_err();
+        assert_eq!(err, NonBlockingError::Io(ErrorKind::BrokenPipe));
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn write_nb_follows_readiness() {
+        let mut writer = Scripted::new(&[Ok(false), Ok(true), Err(ErrorKind::Other)], b"");
+        assert_eq!(writer.write_nb(b"abc"), Ok(None));
+        assert_eq!(writer.write_nb(b"abc"), Ok(Some(2)));
+        assert_eq!(
+            writer.write_nb(b"abc"),
+            Err(NonBlockingError::Ready(ErrorKind::Other))
+        );
+        assert_eq!(writer.calls, 1);
+    }
+
+    #[test]
+    fn select2_checks_in_order() {
+        let mut a = Scripted::new(&[Ok(true), Ok(false), Ok(false)], b"");
+        let mut b = Scripted::new(&[Ok(true), Ok(false)], b"");
+        assert_eq!(select2(&mut a, &mut b), Ok(Some(Which::First)));
+        assert_eq!(select2(&mut a, &mut b), Ok(Some(Which::Second)));
+        assert_eq!(select2(&mut a, &mut b), Ok(None));
+
+        let mut a = Scripted::new(&[Ok(false)], b"");
+        let mut b = Scripted::new(&[Err(ErrorKind::Other)], b"");
+        assert_eq!(
+            select2(&mut a, &mut b),
+            Err(SelectError::Second(ErrorKind::Other))
+        );
+    }
+
+    #[test]
+    fn select2_round_robin_does_not_starve() {
+        let mut a = Scripted::new(&[Ok(true); 3], b"");
+        let mut b = Scripted::new(&[Ok(true), Ok(false)], b"");
+        let mut select = Select2::new();
+        assert_eq!(select.poll(&mut a, &mut b), Ok(Some(Which::First)));
+        assert_eq!(select.poll(&mut a, &mut b), Ok(Some(Which::Second)));
+        assert_eq!(select.poll(&mut a, &mut b), Ok(Some(Which::First)));
+        // `b` is checked first, and not ready.
+        assert_eq!(select.poll(&mut a, &mut b), Ok(Some(Which::First)));
+        assert!(a.ready.is_empty() && b.ready.is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{BufRead, ErrorType, Read};
+
+/// Reader adapter which limits the bytes read from an underlying reader.
+///
+/// This struct is created by [`Read::take`].
+///
+/// # Example
+///
+/// ```
+/// use embedded_io::Read;
+///
+/// // A length-prefixed message, followed by the next one.
+/// let mut stream = &b"\x03abcdef"[..];
+/// let mut len = [0];
+/// stream.read_exact(&mut len).unwrap();
+/// let mut message = (&mut stream).take(u64::from(len[0]));
+/// let mut payload = [0; 8];
+/// assert_eq!(message.read(&mut payload), Ok(3));
+/// assert_eq!(message.read(&mut payload), Ok(0));
+/// assert_eq!(stream, b"def");
+/// ```
+#[derive(Debug)]
+pub struct Take<R> {
+    inner: R,
+    limit: u64,
+}
+
+impl<R> Take<R> {
+    pub(crate) fn new(inner: R, limit: u64) -> Self {
+        Self { inner, limit }
+    }
+
+    /// Returns the number of bytes that can be read before this adapter returns EOF.
+    #[inline]
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Sets the number of bytes that can be read before this adapter returns EOF.
+    #[inline]
+    pub fn set_limit(&mut self, limit: u64) {
+        self.limit = limit;
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Reading directly from the underlying reader does not change the limit.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes this adapter, returning the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Clamp `len` to the limit.
+    fn clamp(&self, len: usize) -> usize {
+        usize::try_from(self.limit).map_or(len, |limit| len.min(limit))
+    }
+}
+
+impl<R: ErrorType> ErrorType for Take<R> {
+    type Error = R::Error;
+}
+
+impl<R: Read> Read for Take<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if self.limit == 0 {
+            return Ok(0);
+        }
+        let max = self.clamp(buf.len());
+        let n = self.inner.read(&mut buf[..max])?;
+        self.limit -= n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Take<R> {
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if self.limit == 0 {
+            return Ok(&[]);
+        }
+        let limit = self.limit;
+        let buf = self.inner.fill_buf()?;
+        let len = usize::try_from(limit).map_or(buf.len(), |limit| buf.len().min(limit));
+        Ok(&buf[..len])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = self.clamp(amt);
+        self.limit -= amt as u64;
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BufRead, BufReader, Read};
+
+    #[test]
+    fn read_up_to_limit() {
+        let mut take = (&b"hello world"[..]).take(5);
+        let mut buf = [0; 3];
+        assert_eq!(take.read(&mut buf), Ok(3));
+        assert_eq!(take.read(&mut buf), Ok(2));
+        assert_eq!(&buf[..2], b"lo");
+        assert_eq!(take.read(&mut buf), Ok(0));
+        assert_eq!(take.limit(), 0);
+        take.set_limit(2);
+        assert_eq!(take.read(&mut buf), Ok(2));
+        assert_eq!(take.into_inner(), b"orld");
+    }
+
+    #[test]
+    fn limit_in_middle_of_fill_buf() {
+        let mut scratch = [0; 4];
+        let mut reader = BufReader::new(&b"abcdefgh"[..], &mut scratch);
+        let mut take = (&mut reader).take(6);
+        assert_eq!(take.fill_buf(), Ok(&b"abcd"[..]));
+        take.consume(4);
+        // The limit ends in the middle of the second buffer.
+        assert_eq!(take.fill_buf(), Ok(&b"ef"[..]));
+        take.consume(8);
+        assert_eq!(take.limit(), 0);
+        assert_eq!(take.fill_buf(), Ok(&[][..]));
+        // The bytes after the limit are still buffered.
+        assert_eq!(reader.buffer(), b"gh");
+    }
+}
@@ -0,0 +1,151 @@
+use core::fmt;
+
+use crate::Write;
+
+/// Adapter implementing [`core::fmt::Write`] for any [`Write`]r.
+///
+/// This allows passing an `embedded-io` writer to code expecting a [`core::fmt::Write`]. Strings are
+/// written with [`Write::write_all`]. Since [`fmt::Error`] carries no information, the first error of
+/// the inner writer is stored, and can be retrieved with [`take_error`](WriteFmt::take_error).
+///
+/// To directly format into a writer, use [`Write::write_fmt`], e.g. with the [`write!`] macro, which
+/// already returns the errors of the writer.
+///
+/// # Example
+///
+/// ```
+/// use core::fmt::Write as _;
+/// use embedded_io::{SliceWriteError, WriteFmt};
+///
+/// let mut buf = [0; 4];
+/// let mut writer = &mut buf[..];
+/// let mut adapter = WriteFmt::new(&mut writer);
+/// assert!(write!(adapter, "temp={}", 21).is_err());
+/// assert_eq!(adapter.take_error(), Some(SliceWriteError::Full));
+/// ```
+pub struct WriteFmt<'a, W: Write + ?Sized> {
+    inner: &'a mut W,
+    error: Option<W::Error>,
+}
+
+impl<'a, W: Write + ?Sized> WriteFmt<'a, W> {
+    /// Creates a new adapter, writing to `inner`.
+    #[inline]
+    pub fn new(inner: &'a mut W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Returns the error of the inner writer, if a write failed.
+    #[inline]
+    pub fn error(&self) -> Option<&W::Error> {
+        self.error.as_ref()
+    }
+
+    /// Takes the error of the inner writer, if a write failed.
+    ///
+    /// If no error is returned after a [`fmt::Error`], the error came from the formatting itself.
+    #[inline]
+    pub fn take_error(&mut self) -> Option<W::Error> {
+        self.error.take()
+    }
+
+    /// Consumes the adapter, returning the inner writer.
+    #[inline]
+    pub fn into_inner(self) -> &'a mut W {
+        self.inner
+    }
+}
+
+impl<W: Write + ?Sized> fmt::Write for WriteFmt<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Do not write anything after an error, so that the stored error is the first one.
+        if self.error.is_some() {
+            return Err(fmt::Error);
+        }
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, ErrorType, SliceWriteError, WriteFmtError};
+    use core::fmt::Write as _;
+
+    /// Writer failing once `capacity` bytes were written, counting the calls after the failure.
+    struct Failing {
+        capacity: usize,
+        after_error: usize,
+    }
+
+    impl ErrorType for Failing {
+        type Error = ErrorKind;
+    }
+
+    impl Write for Failing {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+            if self.capacity == 0 {
+                self.after_error += 1;
+                return Err(ErrorKind::BrokenPipe);
+            }
+            let n = buf.len().min(self.capacity);
+            self.capacity -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<(), ErrorKind> {
+            Ok(())
+        }
+    }
+
+    /// Display implementation always failing.
+    struct Broken;
+
+    impl fmt::Display for Broken {
+        fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[test]
+    fn formats_into_writer() {
+        let mut buf = [0; 16];
+        let mut writer = &mut buf[..];
+        let mut adapter = WriteFmt::new(&mut writer);
+        write!(adapter, "temp={}\r\n", 21).unwrap();
+        assert_eq!(adapter.take_error(), None);
+        assert_eq!(writer.len(), 7);
+        assert_eq!(&buf[..9], b"temp=21\r\n");
+    }
+
+    #[test]
+    fn keeps_first_writer_error() {
+        let mut writer = Failing {
+            capacity: 3,
+            after_error: 0,
+        };
+        let mut adapter = WriteFmt::new(&mut writer);
+        let (a, b, c) = ("ab", "cd", "ef");
+        assert!(write!(adapter, "{a}{b}{c}").is_err());
+        assert_eq!(adapter.error(), Some(&ErrorKind::BrokenPipe));
+        assert!(adapter.write_str("gh").is_err());
+        assert_eq!(adapter.take_error(), Some(ErrorKind::BrokenPipe));
+        assert_eq!(writer.after_error, 1);
+    }
+
+    #[test]
+    fn write_fmt_reports_errors() {
+        let mut buf = [0; 4];
+        assert_eq!(
+            write!(&mut buf[..], "temp={}", 21),
+            Err(WriteFmtError::Other(SliceWriteError::Full))
+        );
+        assert_eq!(
+            write!(&mut buf[..], "{}", Broken),
+            Err(WriteFmtError::FmtError)
+        );
+    }
+}